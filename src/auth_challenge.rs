@@ -0,0 +1,426 @@
+use crate::ntlm::{self, ClientMessage};
+use crate::random::Random;
+use crate::{Request, ResponseTemplate};
+use base64::prelude::{Engine as _, BASE64_STANDARD};
+use http::header::WWW_AUTHENTICATE;
+use http::header::{HeaderName, AUTHORIZATION, PROXY_AUTHENTICATE, PROXY_AUTHORIZATION};
+use md5::{Digest, Md5};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+/// The authentication handshake required by a [`Mock`](crate::Mock) before it responds - see
+/// [`Mock::with_auth_challenge`](crate::Mock::with_auth_challenge).
+///
+/// Requests without valid credentials get a `401 Unauthorized` response, carrying a
+/// `WWW-Authenticate` header that tells the client how to authenticate. Requests with valid
+/// credentials get the response of the mock.
+/// Use [`AuthChallenge::for_proxy`] to emulate a proxy requiring authentication instead.
+#[derive(Debug, Clone)]
+pub struct AuthChallenge {
+    realm: String,
+    scheme: Scheme,
+    proxy: bool,
+}
+
+#[derive(Debug, Clone)]
+enum Scheme {
+    Basic {
+        username: String,
+        password: String,
+    },
+    Bearer {
+        token: String,
+    },
+    Digest {
+        username: String,
+        password: String,
+        nonce: String,
+        opaque: String,
+    },
+    /// NTLM, either on its own or negotiated using the `Negotiate` scheme.
+    Ntlm {
+        protocol: &'static str,
+        username: String,
+        /// The progress of the handshake on each connection.
+        handshakes: Arc<Mutex<HashMap<Option<u64>, Handshake>>>,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Handshake {
+    /// The server sent its challenge.
+    Challenged,
+    /// The client answered the challenge: the connection is authenticated.
+    Authenticated,
+}
+
+impl AuthChallenge {
+    /// Require `Basic` authentication ([RFC 7617](https://www.rfc-editor.org/rfc/rfc7617))
+    /// with `username` and `password`.
+    pub fn basic<R, U, P>(realm: R, username: U, password: P) -> Self
+    where
+        R: Into<String>,
+        U: Into<String>,
+        P: Into<String>,
+    {
+        Self::new(
+            realm,
+            Scheme::Basic {
+                username: username.into(),
+                password: password.into(),
+            },
+        )
+    }
+
+    /// Require a `Bearer` token ([RFC 6750](https://www.rfc-editor.org/rfc/rfc6750)).
+    ///
+    /// Requests carrying a different token are told that it is invalid, with
+    /// `error="invalid_token"`.
+    pub fn bearer<R, T>(realm: R, token: T) -> Self
+    where
+        R: Into<String>,
+        T: Into<String>,
+    {
+        Self::new(
+            realm,
+            Scheme::Bearer {
+                token: token.into(),
+            },
+        )
+    }
+
+    /// Require `Digest` authentication ([RFC 7616](https://www.rfc-editor.org/rfc/rfc7616))
+    /// with `username` and `password`, using the `MD5` algorithm and the `auth` quality of
+    /// protection - the combination supported by most clients.
+    ///
+    /// The server nonce is random: use [`AuthChallenge::nonce`] to set it.
+    pub fn digest<R, U, P>(realm: R, username: U, password: P) -> Self
+    where
+        R: Into<String>,
+        U: Into<String>,
+        P: Into<String>,
+    {
+        let random = Random::unseeded();
+        Self::new(
+            realm,
+            Scheme::Digest {
+                username: username.into(),
+                password: password.into(),
+                nonce: random.hex(32),
+                opaque: random.hex(32),
+            },
+        )
+    }
+
+    /// Require NTLM authentication as `username`, for a server in `domain`.
+    ///
+    /// NTLM authenticates connections rather than requests: the client sends a negotiate
+    /// message, gets a challenge in a `401` response and answers it - all on the same
+    /// keep-alive connection, see [`Request::connection_id`]. Further requests on that
+    /// connection get the response of the mock without sending credentials again. If the
+    /// client answers the challenge on another connection, the handshake starts over.
+    ///
+    /// The messages of the handshake are checked, as well as the user name (and the domain,
+    /// if the client sends one), ignoring case. The password is not: the response of the
+    /// client to the server challenge is not verified.
+    pub fn ntlm<D, U>(domain: D, username: U) -> Self
+    where
+        D: Into<String>,
+        U: Into<String>,
+    {
+        Self::connection_based("NTLM", domain, username)
+    }
+
+    /// Require `Negotiate` authentication ([RFC 4559](https://www.rfc-editor.org/rfc/rfc4559))
+    /// as `username`, for a server in `domain`.
+    ///
+    /// Only NTLM can be negotiated - Kerberos tickets are rejected - and the handshake works
+    /// as described in [`AuthChallenge::ntlm`].
+    pub fn negotiate<D, U>(domain: D, username: U) -> Self
+    where
+        D: Into<String>,
+        U: Into<String>,
+    {
+        Self::connection_based("Negotiate", domain, username)
+    }
+
+    fn connection_based<D, U>(protocol: &'static str, domain: D, username: U) -> Self
+    where
+        D: Into<String>,
+        U: Into<String>,
+    {
+        Self::new(
+            domain,
+            Scheme::Ntlm {
+                protocol,
+                username: username.into(),
+                handshakes: Default::default(),
+            },
+        )
+    }
+
+    /// Set the nonce sent in a `Digest` challenge - e.g. to check the response of your
+    /// client against a known value. It has no effect on other schemes.
+    pub fn nonce<T: Into<String>>(mut self, nonce: T) -> Self {
+        if let Scheme::Digest { nonce: current, .. } = &mut self.scheme {
+            *current = nonce.into();
+        }
+        self
+    }
+
+    /// Act as a proxy requiring authentication: requests without valid credentials get a
+    /// `407 Proxy Authentication Required` response, with a `Proxy-Authenticate` header, and
+    /// the credentials are read from the `Proxy-Authorization` header.
+    pub fn for_proxy(mut self) -> Self {
+        self.proxy = true;
+        self
+    }
+
+    fn new<R: Into<String>>(realm: R, scheme: Scheme) -> Self {
+        Self {
+            realm: realm.into(),
+            scheme,
+            proxy: false,
+        }
+    }
+
+    /// The response challenging `request`, or `None` if it carries valid credentials.
+    pub(crate) fn challenge(&self, request: &Request) -> Option<ResponseTemplate> {
+        let (status, challenge_header, credentials_header): (u16, HeaderName, HeaderName) =
+            if self.proxy {
+                (407, PROXY_AUTHENTICATE, PROXY_AUTHORIZATION)
+            } else {
+                (401, WWW_AUTHENTICATE, AUTHORIZATION)
+            };
+        let credentials = request
+            .headers
+            .get(credentials_header)
+            .and_then(|value| value.to_str().ok());
+        if let Scheme::Ntlm {
+            protocol,
+            username,
+            handshakes,
+        } = &self.scheme
+        {
+            let challenge =
+                self.ntlm_challenge(protocol, username, handshakes, credentials, request)?;
+            return Some(ResponseTemplate::new(status).insert_header(challenge_header, challenge));
+        }
+        if credentials.is_some_and(|credentials| self.is_valid(credentials, request)) {
+            return None;
+        }
+        let challenge = self.challenge_header(credentials.is_some());
+        Some(ResponseTemplate::new(status).insert_header(challenge_header, challenge.as_str()))
+    }
+
+    fn challenge_header(&self, rejected_credentials: bool) -> String {
+        let realm = quote(&self.realm);
+        match &self.scheme {
+            Scheme::Basic { .. } => format!("Basic realm={}, charset=\"UTF-8\"", realm),
+            Scheme::Bearer { .. } if rejected_credentials => {
+                format!("Bearer realm={}, error=\"invalid_token\"", realm)
+            }
+            Scheme::Bearer { .. } => format!("Bearer realm={}", realm),
+            Scheme::Digest { nonce, opaque, .. } => format!(
+                "Digest realm={}, qop=\"auth\", algorithm=MD5, nonce={}, opaque={}",
+                realm,
+                quote(nonce),
+                quote(opaque)
+            ),
+            Scheme::Ntlm { protocol, .. } => protocol.to_string(),
+        }
+    }
+
+    /// Advance the NTLM handshake on the connection of `request`, returning the next
+    /// challenge, if any.
+    fn ntlm_challenge(
+        &self,
+        protocol: &str,
+        username: &str,
+        handshakes: &Mutex<HashMap<Option<u64>, Handshake>>,
+        credentials: Option<&str>,
+        request: &Request,
+    ) -> Option<String> {
+        let connection = request.connection_id();
+        let message = credentials.and_then(|credentials| {
+            let (scheme, token) = credentials.trim().split_once(' ')?;
+            if !scheme.eq_ignore_ascii_case(protocol) {
+                return None;
+            }
+            ClientMessage::parse(&BASE64_STANDARD.decode(token.trim()).ok()?)
+        });
+        let mut handshakes = handshakes.lock().unwrap();
+        match (message, handshakes.get(&connection)) {
+            (None, Some(Handshake::Authenticated)) if credentials.is_none() => return None,
+            (Some(ClientMessage::Negotiate), _) => {
+                handshakes.insert(connection, Handshake::Challenged);
+                let server_challenge = Random::unseeded().u64().to_le_bytes();
+                let challenge = ntlm::challenge_message(&self.realm, server_challenge);
+                return Some(format!(
+                    "{} {}",
+                    protocol,
+                    BASE64_STANDARD.encode(challenge)
+                ));
+            }
+            (
+                Some(ClientMessage::Authenticate {
+                    domain,
+                    username: client_username,
+                }),
+                Some(Handshake::Challenged),
+            ) if client_username.eq_ignore_ascii_case(username)
+                && (domain.is_empty() || domain.eq_ignore_ascii_case(&self.realm)) =>
+            {
+                handshakes.insert(connection, Handshake::Authenticated);
+                return None;
+            }
+            _ => {}
+        }
+        handshakes.remove(&connection);
+        Some(protocol.to_owned())
+    }
+
+    fn is_valid(&self, credentials: &str, request: &Request) -> bool {
+        let (scheme, parameters) = credentials
+            .trim()
+            .split_once(' ')
+            .unwrap_or((credentials, ""));
+        match &self.scheme {
+            Scheme::Basic { username, password } if scheme.eq_ignore_ascii_case("basic") => {
+                let expected = BASE64_STANDARD.encode(format!("{}:{}", username, password));
+                parameters.trim() == expected
+            }
+            Scheme::Bearer { token } if scheme.eq_ignore_ascii_case("bearer") => {
+                parameters.trim() == token
+            }
+            Scheme::Digest {
+                username,
+                password,
+                nonce,
+                ..
+            } if scheme.eq_ignore_ascii_case("digest") => {
+                let parameters = parse_parameters(parameters);
+                let parameter = |name: &str| parameters.get(name).map(String::as_str);
+                let uri = parameter("uri").unwrap_or_default();
+                let request_target = match request.url.query() {
+                    Some(query) => format!("{}?{}", request.url.path(), query),
+                    None => request.url.path().to_owned(),
+                };
+                if parameter("username") != Some(username)
+                    || parameter("realm") != Some(&self.realm)
+                    || parameter("nonce") != Some(nonce)
+                    || !parameter("algorithm").is_none_or(|a| a.eq_ignore_ascii_case("md5"))
+                    || (uri != request_target && uri != request.url.as_str())
+                {
+                    return false;
+                }
+                let ha1 = md5_hex(format!("{}:{}:{}", username, self.realm, password).as_bytes());
+                let ha2 = md5_hex(format!("{}:{}", request.method, uri).as_bytes());
+                let expected = match parameter("qop") {
+                    Some("auth") => md5_hex(
+                        format!(
+                            "{}:{}:{}:{}:auth:{}",
+                            ha1,
+                            nonce,
+                            parameter("nc").unwrap_or_default(),
+                            parameter("cnonce").unwrap_or_default(),
+                            ha2
+                        )
+                        .as_bytes(),
+                    ),
+                    // RFC 2069 clients do not send a quality of protection.
+                    None => md5_hex(format!("{}:{}:{}", ha1, nonce, ha2).as_bytes()),
+                    Some(_) => return false,
+                };
+                parameter("response") == Some(&expected)
+            }
+            _ => false,
+        }
+    }
+}
+
+/// The lowercase hexadecimal MD5 digest of `input`, as used by `Digest` authentication.
+fn md5_hex(input: &[u8]) -> String {
+    Md5::digest(input)
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect()
+}
+
+fn quote(value: &str) -> String {
+    format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+/// Parse the comma-separated `name=value` parameters of a `Digest` authorization header.
+/// Values can be quoted, in which case they can contain commas and escaped characters.
+fn parse_parameters(input: &str) -> HashMap<String, String> {
+    let mut parameters = HashMap::new();
+    let mut chars = input.chars().peekable();
+    loop {
+        while chars.next_if(|c| c.is_whitespace() || *c == ',').is_some() {}
+        let name: String = std::iter::from_fn(|| chars.next_if(|c| *c != '=')).collect();
+        if chars.next().is_none() {
+            break;
+        }
+        let mut value = String::new();
+        if chars.next_if_eq(&'"').is_some() {
+            while let Some(c) = chars.next() {
+                match c {
+                    '\\' => value.extend(chars.next()),
+                    '"' => break,
+                    c => value.push(c),
+                }
+            }
+        } else {
+            value.extend(std::iter::from_fn(|| chars.next_if(|c| *c != ',')));
+        }
+        parameters.insert(name.trim().to_ascii_lowercase(), value.trim().to_owned());
+    }
+    parameters
+}
+
+#[cfg(test)]
+mod tests {
+    use super::AuthChallenge;
+    use crate::Request;
+
+    #[test]
+    fn digest_credentials_are_verified() {
+        // The example of RFC 2617, section 3.5.
+        let challenge = AuthChallenge::digest("testrealm@host.com", "Mufasa", "Circle Of Life")
+            .nonce("dcd98b7102dd2f0e8b11d0f600bfb0c093");
+        let authorization = |response: &str| {
+            format!(
+                r#"Digest username="Mufasa", realm="testrealm@host.com",
+                nonce="dcd98b7102dd2f0e8b11d0f600bfb0c093", uri="/dir/index.html", qop=auth,
+                nc=00000001, cnonce="0a4f113b", response="{}",
+                opaque="5ccc069c403ebaf9f0171e9517f40e41""#,
+                response
+            )
+        };
+        let request = |authorization: String| {
+            Request::builder()
+                .path("/dir/index.html")
+                .header("Authorization", authorization.replace('\n', " "))
+                .build()
+        };
+
+        let valid = request(authorization("6629fae49393a05397450978507c4ef1"));
+        let invalid = request(authorization("00000000000000000000000000000000"));
+
+        assert!(challenge.challenge(&valid).is_none());
+        assert!(challenge.challenge(&invalid).is_some());
+    }
+
+    #[test]
+    fn requests_without_credentials_are_challenged() {
+        let challenge = AuthChallenge::bearer("api", "secret").for_proxy();
+        let request = Request::builder()
+            .header("Proxy-Authorization", "Bearer secret")
+            .build();
+
+        assert!(challenge.challenge(&request).is_none());
+        let response = challenge.challenge(&Request::builder().build()).unwrap();
+        assert_eq!(response.status(), 407);
+    }
+}
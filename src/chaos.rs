@@ -0,0 +1,182 @@
+//! Probabilistic failure injection across all the mocks of a [`MockServer`] - see
+//! [`MockServerBuilder::chaos`].
+//!
+//! [`MockServer`]: crate::MockServer
+//! [`MockServerBuilder::chaos`]: crate::MockServerBuilder::chaos
+use crate::random::Rng;
+use crate::ResponseTemplate;
+use http::StatusCode;
+use std::time::Duration;
+
+/// How the [`MockServer`](crate::MockServer) should misbehave when chaos mode is enabled with
+/// [`MockServerBuilder::chaos`](crate::MockServerBuilder::chaos).
+///
+/// Chaos is only injected on top of requests that matched a mock: unmatched requests get
+/// their usual `404 Not Found`. The matched mock counts the request towards its expectations
+/// either way.
+///
+/// ### Example:
+/// ```rust
+/// use std::time::Duration;
+/// use wiremock::chaos::{ChaosConfig, Fault, Jitter};
+///
+/// let config = ChaosConfig::new()
+///     .error_rate(0.1)
+///     .latency(Jitter::between(Duration::from_millis(5), Duration::from_millis(50)))
+///     .faults([Fault::Status(503), Fault::DropConnection])
+///     .seed(42);
+/// ```
+#[derive(Debug, Clone)]
+pub struct ChaosConfig {
+    error_rate: f64,
+    latency: Option<Jitter>,
+    faults: Vec<Fault>,
+    seed: Option<u64>,
+}
+
+/// A random latency, uniformly distributed between a lower and an upper bound.
+#[derive(Debug, Clone, Copy)]
+pub struct Jitter {
+    min: Duration,
+    max: Duration,
+}
+
+/// A failure injected by chaos mode in place of the response of the matched mock.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Fault {
+    /// Respond with this status code, an empty body and no headers.
+    Status(u16),
+    /// Delay the response of the mock by this much, on top of its own delay.
+    Delay(Duration),
+    /// Drop the connection without sending a response.
+    DropConnection,
+}
+
+impl Jitter {
+    /// A latency uniformly distributed between `min` and `max`, both included.
+    ///
+    /// It panics if `min` is greater than `max`.
+    pub fn between(min: Duration, max: Duration) -> Self {
+        assert!(
+            min <= max,
+            "The lower bound of the jitter ({:?}) is greater than its upper bound ({:?}).",
+            min,
+            max
+        );
+        Self { min, max }
+    }
+}
+
+impl ChaosConfig {
+    /// A configuration that does not inject anything yet: no errors, no latency.
+    /// The faults default to [`Fault::Status(503)`](Fault::Status).
+    pub fn new() -> Self {
+        Self {
+            error_rate: 0.0,
+            latency: None,
+            faults: vec![Fault::Status(503)],
+            seed: None,
+        }
+    }
+
+    /// The probability, between `0.0` and `1.0`, that a matched request gets one of the
+    /// [`faults`](ChaosConfig::faults) instead of the response of its mock.
+    ///
+    /// It panics if `error_rate` is not within `[0.0, 1.0]`.
+    pub fn error_rate(mut self, error_rate: f64) -> Self {
+        assert!(
+            (0.0..=1.0).contains(&error_rate),
+            "The error rate must be between 0.0 and 1.0, got {}.",
+            error_rate
+        );
+        self.error_rate = error_rate;
+        self
+    }
+
+    /// Delay every matched response by a random latency, on top of the delay of its mock.
+    pub fn latency(mut self, latency: Jitter) -> Self {
+        self.latency = Some(latency);
+        self
+    }
+
+    /// The faults to pick from, uniformly, when an error is injected.
+    ///
+    /// It panics if `faults` is empty or if it contains an invalid status code.
+    pub fn faults<I: IntoIterator<Item = Fault>>(mut self, faults: I) -> Self {
+        let faults: Vec<Fault> = faults.into_iter().collect();
+        assert!(!faults.is_empty(), "At least one fault must be specified.");
+        for fault in &faults {
+            if let Fault::Status(status) = fault {
+                StatusCode::from_u16(*status)
+                    .unwrap_or_else(|_| panic!("{} is not a valid status code.", status));
+            }
+        }
+        self.faults = faults;
+        self
+    }
+
+    /// Seed the random number generator, to make the injected failures reproducible.
+    ///
+    /// If no seed is set, a random one is picked when the server starts. It is logged at the
+    /// `INFO` level: set it explicitly to replay a failing run.
+    ///
+    /// The outcome only depends on the order in which requests reach the server: concurrent
+    /// requests may get a different share of the failures from one run to the next.
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.seed = Some(seed);
+        self
+    }
+}
+
+impl Default for ChaosConfig {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// The state of chaos mode on a running server.
+#[derive(Debug)]
+pub(crate) struct Chaos {
+    config: ChaosConfig,
+    rng: Rng,
+}
+
+impl Chaos {
+    pub(crate) fn new(config: ChaosConfig) -> Self {
+        let seed = config.seed.unwrap_or_else(Rng::random_seed);
+        tracing::info!(seed, "Chaos mode enabled");
+        Self {
+            config,
+            rng: Rng::seeded(seed),
+        }
+    }
+
+    /// Inject latency and, randomly, a fault in the response to a matched request.
+    pub(crate) fn inject(&mut self, mut template: ResponseTemplate) -> ResponseTemplate {
+        if let Some(Jitter { min, max }) = self.config.latency {
+            let latency = self.rng.duration_between(min, max);
+            template = delay_by(template, latency);
+        }
+        if self.rng.next_f64() >= self.config.error_rate {
+            return template;
+        }
+        let faults = &self.config.faults;
+        let fault = faults[self.rng.below(faults.len() as u64) as usize];
+        tracing::debug!(?fault, "Injecting fault");
+        match fault {
+            Fault::Status(status) => {
+                let delay = template.delay().unwrap_or_default();
+                ResponseTemplate::new(status).set_delay(delay)
+            }
+            Fault::Delay(delay) => delay_by(template, delay),
+            Fault::DropConnection => template.drop_connection(),
+        }
+    }
+}
+
+/// Add `extra` to the delay of `template`.
+fn delay_by(template: ResponseTemplate, extra: Duration) -> ResponseTemplate {
+    let delay = template.delay().unwrap_or_default();
+    template.set_delay(delay + extra)
+}
@@ -0,0 +1,64 @@
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::watch;
+
+/// A clock driving the response delays of a [`MockServer`](crate::MockServer), advanced
+/// manually by your test - see [`MockServerBuilder::virtual_clock`](crate::MockServerBuilder::virtual_clock).
+///
+/// The [`MockServer`](crate::MockServer) runs on its own thread, with its own runtime:
+/// pausing time in your test runtime (e.g. with `tokio::time::pause`) does not affect it -
+/// unless it runs on your runtime, see
+/// [`MockServerBuilder::run_on_current_runtime`](crate::MockServerBuilder::run_on_current_runtime).
+/// With a `VirtualClock`, a response delayed by [`set_delay`](crate::ResponseTemplate::set_delay)
+/// is sent as soon as the clock has been advanced past its delay, no matter how much
+/// wall-clock time went by: tests of timeout and retry logic do not have to actually wait.
+///
+/// Clones share the same time.
+///
+/// ### Example:
+/// ```rust
+/// use std::time::Duration;
+/// use wiremock::VirtualClock;
+///
+/// let clock = VirtualClock::new();
+/// clock.advance(Duration::from_secs(30));
+///
+/// assert_eq!(clock.elapsed(), Duration::from_secs(30));
+/// ```
+#[derive(Debug, Clone)]
+pub struct VirtualClock {
+    elapsed: Arc<watch::Sender<Duration>>,
+}
+
+impl VirtualClock {
+    /// A clock that starts at `0` and only moves forward when [`advance`](VirtualClock::advance)
+    /// is called.
+    pub fn new() -> Self {
+        Self {
+            elapsed: Arc::new(watch::channel(Duration::ZERO).0),
+        }
+    }
+
+    /// Move the clock forward by `duration`, sending the responses whose delay has elapsed.
+    pub fn advance(&self, duration: Duration) {
+        self.elapsed.send_modify(|elapsed| *elapsed += duration);
+    }
+
+    /// How far the clock has been advanced since it was created.
+    pub fn elapsed(&self) -> Duration {
+        *self.elapsed.borrow()
+    }
+
+    /// Wait until the clock has been advanced up to `deadline`, measured from its creation.
+    pub(crate) async fn sleep_until(&self, deadline: Duration) {
+        let mut elapsed = self.elapsed.subscribe();
+        // The sender is owned by `self`: it cannot be dropped while we wait.
+        let _ = elapsed.wait_for(|elapsed| *elapsed >= deadline).await;
+    }
+}
+
+impl Default for VirtualClock {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,162 @@
+//! Formatting of timestamps, without pulling in a date-time library.
+use std::convert::TryFrom;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+const WEEKDAYS: [&str; 7] = ["Sun", "Mon", "Tue", "Wed", "Thu", "Fri", "Sat"];
+const MONTHS: [&str; 12] = [
+    "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+];
+
+/// A point in time broken down into its UTC calendar components.
+struct DateTime {
+    year: i64,
+    month: i64,
+    day: i64,
+    /// `0` is Sunday.
+    weekday: i64,
+    hour: u64,
+    minute: u64,
+    second: u64,
+    millisecond: u32,
+}
+
+impl DateTime {
+    fn from_system_time(time: SystemTime) -> Self {
+        let since_epoch = time.duration_since(UNIX_EPOCH).unwrap_or_default();
+        let seconds = since_epoch.as_secs();
+        let (days, seconds_of_day) = (seconds / 86_400, seconds % 86_400);
+
+        // Convert days since the epoch into a civil date - see
+        // http://howardhinnant.github.io/date_algorithms.html#civil_from_days
+        let z = days as i64 + 719_468;
+        let era = z.div_euclid(146_097);
+        let day_of_era = z.rem_euclid(146_097);
+        let year_of_era =
+            (day_of_era - day_of_era / 1_460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+        let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+        let mp = (5 * day_of_year + 2) / 153;
+        let day = day_of_year - (153 * mp + 2) / 5 + 1;
+        let month = if mp < 10 { mp + 3 } else { mp - 9 };
+        let year = year_of_era + era * 400 + i64::from(month <= 2);
+
+        Self {
+            year,
+            month,
+            day,
+            // The epoch was a Thursday.
+            weekday: (days as i64 + 4) % 7,
+            hour: seconds_of_day / 3_600,
+            minute: seconds_of_day % 3_600 / 60,
+            second: seconds_of_day % 60,
+            millisecond: since_epoch.subsec_millis(),
+        }
+    }
+}
+
+/// Format `time` as an ISO 8601 date-time in UTC, with millisecond precision -
+/// e.g. `2021-03-04T10:15:30.123Z`.
+pub(crate) fn iso8601(time: SystemTime) -> String {
+    let t = DateTime::from_system_time(time);
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}.{:03}Z",
+        t.year, t.month, t.day, t.hour, t.minute, t.second, t.millisecond
+    )
+}
+
+/// Format `time` as an RFC 3339 date-time in UTC, with second precision -
+/// e.g. `2021-03-04T10:15:30Z`.
+pub(crate) fn rfc3339(time: SystemTime) -> String {
+    let t = DateTime::from_system_time(time);
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z",
+        t.year, t.month, t.day, t.hour, t.minute, t.second
+    )
+}
+
+/// Format `time` as an [HTTP date](https://httpwg.org/specs/rfc9110.html#http.date) -
+/// e.g. `Thu, 04 Mar 2021 10:15:30 GMT`.
+pub(crate) fn http_date(time: SystemTime) -> String {
+    let t = DateTime::from_system_time(time);
+    format!(
+        "{}, {:02} {} {:04} {:02}:{:02}:{:02} GMT",
+        WEEKDAYS[t.weekday as usize],
+        t.day,
+        MONTHS[t.month as usize - 1],
+        t.year,
+        t.hour,
+        t.minute,
+        t.second
+    )
+}
+
+/// Parse an [HTTP date](https://httpwg.org/specs/rfc9110.html#http.date) in its preferred
+/// format - e.g. `Thu, 04 Mar 2021 10:15:30 GMT`.
+///
+/// The obsolete RFC 850 and asctime formats are not supported.
+pub(crate) fn parse_http_date(date: &str) -> Option<SystemTime> {
+    let (_weekday, date) = date.trim().split_once(", ")?;
+    let parts: Vec<&str> = date.split(' ').collect();
+    let [day, month, year, time, "GMT"] = parts[..] else {
+        return None;
+    };
+    let day: i64 = day.parse().ok()?;
+    let month = MONTHS.iter().position(|m| *m == month)? as i64 + 1;
+    let year: i64 = year.parse().ok()?;
+    let time: Vec<u64> = time
+        .split(':')
+        .map(|part| part.parse().ok())
+        .collect::<Option<_>>()?;
+    let [hour, minute, second] = time[..] else {
+        return None;
+    };
+    if !(1..=31).contains(&day) || hour > 23 || minute > 59 || second > 60 {
+        return None;
+    }
+
+    // Convert the civil date into days since the epoch - see
+    // http://howardhinnant.github.io/date_algorithms.html#days_from_civil
+    let y = if month <= 2 { year - 1 } else { year };
+    let era = y.div_euclid(400);
+    let year_of_era = y.rem_euclid(400);
+    let mp = (month + 9) % 12;
+    let day_of_year = (153 * mp + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    let days = u64::try_from(era * 146_097 + day_of_era - 719_468).ok()?;
+
+    let seconds = days * 86_400 + hour * 3_600 + minute * 60 + second;
+    Some(UNIX_EPOCH + Duration::from_secs(seconds))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{http_date, iso8601, parse_http_date, rfc3339};
+    use std::time::{Duration, UNIX_EPOCH};
+
+    #[test]
+    fn timestamps_are_formatted_as_iso_8601() {
+        assert_eq!(iso8601(UNIX_EPOCH), "1970-01-01T00:00:00.000Z");
+        // Leap day
+        let time = UNIX_EPOCH + Duration::from_millis(1_709_210_096_789);
+        assert_eq!(iso8601(time), "2024-02-29T12:34:56.789Z");
+        assert_eq!(rfc3339(time), "2024-02-29T12:34:56Z");
+    }
+
+    #[test]
+    fn timestamps_are_formatted_as_http_dates() {
+        assert_eq!(http_date(UNIX_EPOCH), "Thu, 01 Jan 1970 00:00:00 GMT");
+        let time = UNIX_EPOCH + Duration::from_millis(1_709_210_096_789);
+        assert_eq!(http_date(time), "Thu, 29 Feb 2024 12:34:56 GMT");
+    }
+
+    #[test]
+    fn http_dates_are_parsed() {
+        assert_eq!(
+            parse_http_date("Thu, 01 Jan 1970 00:00:00 GMT"),
+            Some(UNIX_EPOCH)
+        );
+        let time = UNIX_EPOCH + Duration::from_secs(1_709_210_096);
+        assert_eq!(parse_http_date(&http_date(time)), Some(time));
+        assert_eq!(parse_http_date("Thursday, 29-Feb-24 12:34:56 GMT"), None);
+        assert_eq!(parse_http_date("Thu, 29 Feb 2024 12:34 GMT"), None);
+    }
+}
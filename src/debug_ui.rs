@@ -0,0 +1,183 @@
+//! A web page to inspect and drive a running [`MockServer`](crate::MockServer) - see
+//! [`MockServerBuilder::enable_debug_ui`](crate::MockServerBuilder::enable_debug_ui).
+use crate::{JournalEntry, MatchedMock, Request, ResponseTemplate};
+use http::Method;
+use serde_json::json;
+
+/// The path of the debug UI, when it is enabled.
+pub(crate) const DEBUG_UI_PATH: &str = "/__admin/ui";
+
+/// How many of the most recent requests are listed by the debug UI.
+const MAX_LISTED_REQUESTS: usize = 100;
+
+/// A request to the debug UI.
+pub(crate) enum DebugUiRequest {
+    /// `GET /__admin/ui`: the page itself.
+    Page,
+    /// `GET /__admin/ui/state`: the mounted mocks and the received requests, as JSON.
+    State,
+    /// `POST /__admin/ui/mocks/{position}/toggle`: pause the mock mounted at `position`, or
+    /// resume it if it is paused.
+    Toggle(usize),
+}
+
+impl DebugUiRequest {
+    /// Recognise the requests addressed to the debug UI.
+    pub(crate) fn parse(request: &Request) -> Option<Self> {
+        let path = request.url.path().strip_prefix(DEBUG_UI_PATH)?;
+        match (&request.method, path) {
+            (&Method::GET, "" | "/") => Some(DebugUiRequest::Page),
+            (&Method::GET, "/state") => Some(DebugUiRequest::State),
+            (&Method::POST, path) => path
+                .strip_prefix("/mocks/")?
+                .strip_suffix("/toggle")?
+                .parse()
+                .ok()
+                .map(DebugUiRequest::Toggle),
+            _ => None,
+        }
+    }
+}
+
+/// What the debug UI shows about a mounted mock.
+pub(crate) struct MockOverview {
+    pub(crate) mock: MatchedMock,
+    pub(crate) priority: u8,
+    pub(crate) paused: bool,
+    pub(crate) n_matches: u64,
+    /// A description of each matcher of the mock.
+    pub(crate) matchers: Vec<String>,
+}
+
+/// The page of the debug UI.
+pub(crate) fn page() -> ResponseTemplate {
+    ResponseTemplate::new(200).set_body_raw(PAGE, "text/html; charset=utf-8")
+}
+
+/// The state of the server, as rendered by the page of the debug UI.
+///
+/// `journal` is `None` if request recording is disabled.
+pub(crate) fn state(mocks: &[MockOverview], journal: Option<&[JournalEntry]>) -> ResponseTemplate {
+    let mocks: Vec<_> = mocks
+        .iter()
+        .map(|overview| {
+            json!({
+                "position": overview.mock.position,
+                "name": overview.mock.to_string(),
+                "priority": overview.priority,
+                "paused": overview.paused,
+                "n_matches": overview.n_matches,
+                "matchers": overview.matchers,
+            })
+        })
+        .collect();
+    let requests = journal.map(|journal| {
+        journal
+            .iter()
+            .rev()
+            .take(MAX_LISTED_REQUESTS)
+            .map(|entry| {
+                json!({
+                    "method": entry.request.method.as_str(),
+                    "url": entry.request.url.as_str(),
+                    "matched_mock": entry.matched_mock.as_ref().map(ToString::to_string),
+                    "status": entry.response_status.as_u16(),
+                })
+            })
+            .collect::<Vec<_>>()
+    });
+    ResponseTemplate::new(200).set_body_json(json!({ "mocks": mocks, "requests": requests }))
+}
+
+/// The outcome of toggling a mock.
+pub(crate) fn toggled(found: bool) -> ResponseTemplate {
+    if found {
+        ResponseTemplate::new(204)
+    } else {
+        ResponseTemplate::new(404).set_body_string("No active mock is mounted at this position")
+    }
+}
+
+const PAGE: &str = r#"<!DOCTYPE html>
+<html>
+<head>
+<meta charset="utf-8">
+<title>wiremock</title>
+<style>
+  body { font-family: sans-serif; margin: 2em; }
+  table { border-collapse: collapse; margin-bottom: 2em; }
+  th, td { border: 1px solid #ccc; padding: 0.3em 0.6em; text-align: left; vertical-align: top; }
+  .paused { color: #999; }
+  .unmatched { background: #fee; }
+  code { font-size: 0.9em; }
+</style>
+</head>
+<body>
+<h1>wiremock</h1>
+<h2>Mounted mocks</h2>
+<table>
+  <thead><tr><th>#</th><th>Mock</th><th>Priority</th><th>Matchers</th><th>Matches</th><th></th></tr></thead>
+  <tbody id="mocks"></tbody>
+</table>
+<h2>Received requests</h2>
+<p id="recording"></p>
+<table>
+  <thead><tr><th>Method</th><th>URL</th><th>Matched mock</th><th>Status</th></tr></thead>
+  <tbody id="requests"></tbody>
+</table>
+<script>
+function cell(row, text) {
+  const td = row.insertCell();
+  td.textContent = text;
+  return td;
+}
+
+async function toggle(position) {
+  await fetch(`/__admin/ui/mocks/${position}/toggle`, { method: "POST" });
+  await refresh();
+}
+
+async function refresh() {
+  const state = await (await fetch("/__admin/ui/state")).json();
+
+  const mocks = document.getElementById("mocks");
+  mocks.replaceChildren();
+  for (const mock of state.mocks) {
+    const row = mocks.insertRow();
+    if (mock.paused) row.className = "paused";
+    cell(row, mock.position);
+    cell(row, mock.name);
+    cell(row, mock.priority);
+    const matchers = cell(row, "");
+    for (const matcher of mock.matchers) {
+      const code = document.createElement("code");
+      code.textContent = matcher;
+      matchers.append(code, document.createElement("br"));
+    }
+    cell(row, mock.n_matches);
+    const button = document.createElement("button");
+    button.textContent = mock.paused ? "Resume" : "Pause";
+    button.onclick = () => toggle(mock.position);
+    row.insertCell().append(button);
+  }
+
+  const requests = document.getElementById("requests");
+  requests.replaceChildren();
+  document.getElementById("recording").textContent =
+    state.requests === null ? "Request recording is disabled." : "";
+  for (const request of state.requests || []) {
+    const row = requests.insertRow();
+    if (request.matched_mock === null) row.className = "unmatched";
+    cell(row, request.method);
+    cell(row, request.url);
+    cell(row, request.matched_mock === null ? "-" : request.matched_mock);
+    cell(row, request.status);
+  }
+}
+
+refresh();
+setInterval(refresh, 1000);
+</script>
+</body>
+</html>
+"#;
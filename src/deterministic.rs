@@ -0,0 +1,172 @@
+use crate::response_template::fnv1a;
+use crate::templating;
+use crate::{Random, Request, Respond, ResponseTemplate};
+use http::StatusCode;
+use std::convert::TryInto;
+use std::time::SystemTime;
+
+/// A [`Respond`] implementation deriving the response from a hash of the request: the same
+/// request always gets the same response, different requests get different - but stable -
+/// responses.
+///
+/// It comes in handy for fuzz-style or property-based tests: your client sees a realistic mix
+/// of responses, without the mock keeping any state, and a failing case can be replayed by
+/// sending the same request again.
+///
+/// The hash covers the method, the path, the query and the body of the request - headers are
+/// left out, since clients tend to add varying ones (e.g. dates or trace ids). It is stable
+/// across runs and platforms; use [`seed`](DeterministicByRequest::seed) to get a different
+/// mapping from requests to responses.
+///
+/// Out of the hash it picks:
+/// - a status code among those added with [`with_status`](DeterministicByRequest::with_status),
+///   according to their weights;
+/// - a body among those added with
+///   [`with_body_template`](DeterministicByRequest::with_body_template). The template
+///   variables it contains (see [`ResponseTemplate::render_templates`]) are rendered using a
+///   [`Random`] seeded with the hash, so `{{random uuid}}` yields the same identifier for the
+///   same request.
+///
+/// Everything else - e.g. headers - comes from the base template passed to
+/// [`DeterministicByRequest::new`], which also provides the status code and the body if none
+/// were added.
+///
+/// ### Example:
+/// ```rust
+/// use wiremock::{DeterministicByRequest, MockServer, Mock, ResponseTemplate};
+/// use wiremock::matchers::path_regex;
+///
+/// #[async_std::main]
+/// async fn main() {
+///     // Arrange
+///     let mock_server = MockServer::start().await;
+///     let responder = DeterministicByRequest::new(ResponseTemplate::new(200))
+///         .with_status(200, 8)
+///         .with_status(500, 1)
+///         .with_status(503, 1)
+///         .with_body_template(r#"{"id": "{{random uuid}}"}"#);
+///     Mock::given(path_regex("^/users/"))
+///         .respond_with(responder)
+///         .mount(&mock_server)
+///         .await;
+///
+///     // Act
+///     let url = format!("{}/users/42", mock_server.uri());
+///     let mut first = surf::get(&url).await.unwrap();
+///     let mut second = surf::get(&url).await.unwrap();
+///
+///     // Assert
+///     assert_eq!(first.status(), second.status());
+///     assert_eq!(
+///         first.body_string().await.unwrap(),
+///         second.body_string().await.unwrap()
+///     );
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct DeterministicByRequest {
+    base: ResponseTemplate,
+    statuses: Vec<(StatusCode, u64)>,
+    body_templates: Vec<String>,
+    seed: u64,
+}
+
+impl DeterministicByRequest {
+    /// Derive the responses from `base`.
+    pub fn new(base: ResponseTemplate) -> Self {
+        Self {
+            base,
+            statuses: Vec::new(),
+            body_templates: Vec::new(),
+            seed: 0,
+        }
+    }
+
+    /// Add `status` to the status codes to pick from, with the given `weight`: a status with
+    /// weight `2` is returned twice as often as one with weight `1`, across requests.
+    ///
+    /// It panics if `weight` is `0`.
+    pub fn with_status<S>(mut self, status: S, weight: u64) -> Self
+    where
+        S: TryInto<StatusCode>,
+        <S as TryInto<StatusCode>>::Error: std::fmt::Debug,
+    {
+        assert!(
+            weight > 0,
+            "The weight of a status code must be greater than 0!"
+        );
+        let status = status
+            .try_into()
+            .expect("Failed to convert into status code.");
+        self.statuses.push((status, weight));
+        self
+    }
+
+    /// Add a body to pick from, with equal odds.
+    ///
+    /// Its template variables are rendered using a [`Random`] seeded with the hash of the
+    /// request. It panics if `template` contains invalid template variables.
+    pub fn with_body_template<T: Into<String>>(mut self, template: T) -> Self {
+        let template = template.into();
+        if let Err(e) = templating::render(&template, SystemTime::now(), &Random::seeded(0), None) {
+            panic!("`{}` is not a valid body template: {}", template, e);
+        }
+        self.body_templates.push(template);
+        self
+    }
+
+    /// Mix `seed` into the hash of the requests, to map them to different responses.
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.seed = seed;
+        self
+    }
+
+    /// The hash of the parts of `request` that determine its response.
+    fn hash(&self, request: &Request) -> u64 {
+        let mut bytes = self.seed.to_le_bytes().to_vec();
+        for part in [
+            request.method.as_str().as_bytes(),
+            request.url.path().as_bytes(),
+            request.url.query().unwrap_or_default().as_bytes(),
+            &request.body,
+        ] {
+            // Prefix each part with its length, so that moving bytes from one part to the
+            // next changes the hash.
+            bytes.extend_from_slice(&(part.len() as u64).to_le_bytes());
+            bytes.extend_from_slice(part);
+        }
+        fnv1a(&bytes)
+    }
+}
+
+impl Respond for DeterministicByRequest {
+    fn respond(&self, request: &Request) -> ResponseTemplate {
+        let random = Random::seeded(self.hash(request));
+        let mut template = self.base.clone();
+
+        let total_weight: u64 = self.statuses.iter().map(|(_, weight)| weight).sum();
+        if total_weight > 0 {
+            let mut pick = random.u64() % total_weight;
+            for (status, weight) in &self.statuses {
+                if pick < *weight {
+                    template = template.set_status(*status);
+                    break;
+                }
+                pick -= weight;
+            }
+        }
+
+        if !self.body_templates.is_empty() {
+            let index = random.u64() % self.body_templates.len() as u64;
+            let body = templating::render(
+                &self.body_templates[index as usize],
+                SystemTime::now(),
+                &random,
+                None,
+            )
+            .expect("Body templates are validated when they are added");
+            template = template.set_body_bytes(body);
+        }
+        template
+    }
+}
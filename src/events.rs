@@ -0,0 +1,113 @@
+//! Real-time notifications of what happens on a [`MockServer`](crate::MockServer) - see
+//! [`MockServer::events`](crate::MockServer::events).
+use crate::request::Redaction;
+use crate::{MatchedMock, Request};
+use futures::Stream;
+use http::StatusCode;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+use tokio::sync::broadcast;
+
+/// How many events a subscriber can fall behind before missing some.
+const EVENTS_CAPACITY: usize = 1024;
+
+/// Something that happened on a [`MockServer`](crate::MockServer), as emitted by
+/// [`MockServer::events`](crate::MockServer::events).
+///
+/// The events about the same request share the same `request_id`.
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+// Events are few and short-lived: boxing the request would only make matching on them clunkier.
+#[allow(clippy::large_enum_variant)]
+pub enum ServerEvent {
+    /// A request was received.
+    RequestReceived {
+        /// Identifies the request in the following events. Request ids are assigned in the
+        /// order requests are received, starting from `0` - and again from `0` when the
+        /// server is [reset](crate::MockServer::reset).
+        request_id: u64,
+        /// The request, with the redactions configured on the server applied - see
+        /// [`MockServerBuilder::redact_headers`](crate::MockServerBuilder::redact_headers).
+        request: Request,
+    },
+    /// A mock matched a request.
+    MockMatched {
+        /// The request that was matched.
+        request_id: u64,
+        /// The mock that matched it.
+        mock: MatchedMock,
+    },
+    /// A response was sent back to the client.
+    ResponseSent {
+        /// The request the response answers.
+        request_id: u64,
+        /// The mock that produced the response, `None` if no mock matched the request.
+        mock: Option<MatchedMock>,
+        /// The status code of the response.
+        status: StatusCode,
+        /// How long it took to respond, since the request was received.
+        latency: Duration,
+    },
+    /// Verifying the expectations of the mounted mocks failed - either explicitly, with
+    /// [`MockServer::verify`](crate::MockServer::verify), or when a server or a scoped mock
+    /// was dropped.
+    VerificationFailed {
+        /// The error message detailing the failures.
+        message: String,
+    },
+}
+
+/// Broadcasts the events of a server to its subscribers.
+pub(crate) struct EventBus {
+    sender: broadcast::Sender<ServerEvent>,
+    next_request_id: AtomicU64,
+    /// Applied to requests before they are broadcast.
+    redaction: Redaction,
+}
+
+impl EventBus {
+    pub(crate) fn new(redaction: Redaction) -> Self {
+        Self {
+            sender: broadcast::channel(EVENTS_CAPACITY).0,
+            next_request_id: AtomicU64::new(0),
+            redaction,
+        }
+    }
+
+    /// Subscribe to the events emitted from now on.
+    pub(crate) fn subscribe(&self) -> impl Stream<Item = ServerEvent> + Send + 'static {
+        futures::stream::unfold(self.sender.subscribe(), |mut receiver| async move {
+            loop {
+                match receiver.recv().await {
+                    Ok(event) => return Some((event, receiver)),
+                    // Slow subscribers miss the oldest events rather than slowing the server.
+                    Err(broadcast::error::RecvError::Lagged(_)) => continue,
+                    Err(broadcast::error::RecvError::Closed) => return None,
+                }
+            }
+        })
+    }
+
+    /// Start assigning request ids from `0` again, e.g. when a pooled server is reused.
+    pub(crate) fn reset(&self) {
+        self.next_request_id.store(0, Ordering::Relaxed);
+    }
+
+    /// Emit a [`ServerEvent::RequestReceived`], returning the id assigned to `request`.
+    pub(crate) fn request_received(&self, request: &Request) -> u64 {
+        let request_id = self.next_request_id.fetch_add(1, Ordering::Relaxed);
+        // Skip copying the request if nobody is listening.
+        if self.sender.receiver_count() > 0 {
+            self.publish(ServerEvent::RequestReceived {
+                request_id,
+                request: self.redaction.apply(request),
+            });
+        }
+        request_id
+    }
+
+    pub(crate) fn publish(&self, event: ServerEvent) {
+        // It only fails if there are no subscribers.
+        let _ = self.sender.send(event);
+    }
+}
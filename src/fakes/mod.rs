@@ -0,0 +1,12 @@
+//! Ready-made fakes of third-party services and common API patterns that are needed in tests.
+//!
+//! Each fake mounts a set of [`Mock`](crate::Mock)s on a [`MockServer`](crate::MockServer).
+//! The fakes that need extra dependencies live behind their own feature flag, to avoid pulling
+//! in dependencies you do not need.
+#[cfg(feature = "oauth2")]
+pub mod oauth2;
+mod pagination;
+#[cfg(feature = "aws")]
+pub mod s3;
+
+pub use pagination::{paginated, PageStyle, Paginated};
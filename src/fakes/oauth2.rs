@@ -0,0 +1,359 @@
+//! A fake OAuth2 / OpenID Connect identity provider.
+//!
+//! [`OAuth2Provider`] mounts on a [`MockServer`] a token endpoint issuing signed JWTs, the JWKS
+//! endpoint publishing the key used to sign them and an OpenID Connect discovery document
+//! pointing at both.
+//!
+//! This module is only available if the `oauth2` feature is enabled.
+use crate::matchers::{method, path};
+use crate::{Mock, MockServer, Request, ResponseTemplate};
+use base64::prelude::{Engine as _, BASE64_STANDARD, BASE64_URL_SAFE_NO_PAD};
+use p256::ecdsa::signature::Signer;
+use p256::ecdsa::{Signature, SigningKey};
+use rand_core::OsRng;
+use serde::Serialize;
+use serde_json::{json, Map, Value};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// The path of the token endpoint mounted by [`OAuth2Provider::mount`].
+pub const TOKEN_PATH: &str = "/oauth2/token";
+/// The path of the JWKS endpoint mounted by [`OAuth2Provider::mount`].
+pub const JWKS_PATH: &str = "/.well-known/jwks.json";
+/// The path of the OpenID Connect discovery document mounted by [`OAuth2Provider::mount`].
+pub const DISCOVERY_PATH: &str = "/.well-known/openid-configuration";
+
+/// A fake identity provider, issuing JWTs signed with a freshly generated `ES256` key.
+///
+/// [`OAuth2Provider::mount`] registers the following [`Mock`]s on a [`MockServer`]:
+///
+/// | Request                                  | Behaviour                                                  |
+/// |------------------------------------------|------------------------------------------------------------|
+/// | `POST /oauth2/token`                     | Issue an access token - see below.                         |
+/// | `GET /.well-known/jwks.json`             | Returns the JSON Web Key Set with the public signing key.  |
+/// | `GET /.well-known/openid-configuration`  | Returns the OpenID Connect discovery document.             |
+///
+/// The token endpoint accepts any `application/x-www-form-urlencoded` request with a
+/// `grant_type` parameter - no credentials are checked - and returns `400` with an
+/// `invalid_request` error otherwise.
+/// The access token carries the following claims:
+/// - `iss`: the issuer - the URI of the [`MockServer`] unless set with
+///   [`OAuth2Provider::issuer`];
+/// - `sub`: the subject set with [`OAuth2Provider::subject`], falling back to the client id
+///   of the request (from the `client_id` parameter or the `Authorization: Basic` header);
+/// - `aud`: the audience set with [`OAuth2Provider::audience`], if any;
+/// - `iat` and `exp`: the token is valid for [`OAuth2Provider::token_lifetime`];
+/// - `scope`: the `scope` parameter of the request, if any;
+/// - the custom claims added with [`OAuth2Provider::claim`], which take precedence over all
+///   of the above.
+///
+/// If the requested scope includes `openid`, the response also contains an `id_token` whose
+/// audience is the client id.
+///
+/// `OAuth2Provider` is cheap to clone: all clones share the same signing key.
+///
+/// ### Example:
+/// ```rust
+/// use wiremock::MockServer;
+/// use wiremock::fakes::oauth2::OAuth2Provider;
+/// use serde_json::{json, Value};
+///
+/// #[async_std::main]
+/// async fn main() {
+///     // Arrange
+///     let mock_server = MockServer::start().await;
+///     OAuth2Provider::new()
+///         .audience("billing-api")
+///         .claim("roles", json!(["admin"]))
+///         .mount(&mock_server)
+///         .await;
+///
+///     // Act
+///     let mut response = surf::post(format!("{}/oauth2/token", mock_server.uri()))
+///         .body_string("grant_type=client_credentials&client_id=my-service".into())
+///         .content_type("application/x-www-form-urlencoded")
+///         .await
+///         .unwrap();
+///
+///     // Assert
+///     assert_eq!(response.status(), 200);
+///     let body: Value = response.body_json().await.unwrap();
+///     assert_eq!(body["token_type"], "Bearer");
+///     assert_eq!(body["expires_in"], 3600);
+///     let access_token = body["access_token"].as_str().unwrap();
+///     assert_eq!(access_token.split('.').count(), 3);
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct OAuth2Provider {
+    key: Arc<SigningKey>,
+    key_id: String,
+    issuer: Option<String>,
+    audience: Option<String>,
+    subject: Option<String>,
+    token_lifetime: Duration,
+    claims: Map<String, Value>,
+}
+
+impl Default for OAuth2Provider {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl OAuth2Provider {
+    /// Create a new identity provider, with a randomly generated signing key.
+    pub fn new() -> Self {
+        let key = SigningKey::random(&mut OsRng);
+        let point = key.verifying_key().to_encoded_point(false);
+        let key_id = point
+            .x()
+            .expect("An uncompressed point has an x coordinate")
+            .iter()
+            .take(8)
+            .map(|byte| format!("{:02x}", byte))
+            .collect();
+        Self {
+            key: Arc::new(key),
+            key_id,
+            issuer: None,
+            audience: None,
+            subject: None,
+            token_lifetime: Duration::from_secs(3600),
+            claims: Map::new(),
+        }
+    }
+
+    /// Set the `iss` claim of the issued tokens. It defaults to the URI of the [`MockServer`]
+    /// the provider is mounted on.
+    pub fn issuer<T: Into<String>>(mut self, issuer: T) -> Self {
+        self.issuer = Some(issuer.into());
+        self
+    }
+
+    /// Set the `aud` claim of the issued access tokens. It is omitted by default.
+    pub fn audience<T: Into<String>>(mut self, audience: T) -> Self {
+        self.audience = Some(audience.into());
+        self
+    }
+
+    /// Set the `sub` claim of the issued tokens. It defaults to the client id of the request.
+    pub fn subject<T: Into<String>>(mut self, subject: T) -> Self {
+        self.subject = Some(subject.into());
+        self
+    }
+
+    /// Set how long the issued tokens are valid for. It defaults to one hour.
+    pub fn token_lifetime(mut self, lifetime: Duration) -> Self {
+        self.token_lifetime = lifetime;
+        self
+    }
+
+    /// Add a custom claim to the issued tokens.
+    ///
+    /// It panics if `value` cannot be serialized to JSON.
+    pub fn claim<K: Into<String>, V: Serialize>(mut self, name: K, value: V) -> Self {
+        let value = serde_json::to_value(value).expect("Failed to serialize claim");
+        self.claims.insert(name.into(), value);
+        self
+    }
+
+    /// The id of the signing key, set as `kid` in the header of the issued tokens.
+    pub fn key_id(&self) -> &str {
+        &self.key_id
+    }
+
+    /// Sign a JWT carrying exactly the specified `claims`.
+    ///
+    /// Use it to craft tokens the token endpoint would not issue - e.g. expired tokens or
+    /// tokens for a different audience.
+    ///
+    /// It panics if `claims` cannot be serialized to JSON.
+    pub fn sign<T: Serialize>(&self, claims: T) -> String {
+        let header = json!({"alg": "ES256", "typ": "JWT", "kid": self.key_id});
+        let claims = serde_json::to_vec(&claims).expect("Failed to serialize claims");
+        let signing_input = format!(
+            "{}.{}",
+            BASE64_URL_SAFE_NO_PAD.encode(header.to_string()),
+            BASE64_URL_SAFE_NO_PAD.encode(claims)
+        );
+        let signature: Signature = self.key.sign(signing_input.as_bytes());
+        format!(
+            "{}.{}",
+            signing_input,
+            BASE64_URL_SAFE_NO_PAD.encode(signature.to_bytes())
+        )
+    }
+
+    /// The JSON Web Key Set containing the public key used to verify the issued tokens.
+    pub fn jwks(&self) -> Value {
+        let point = self.key.verifying_key().to_encoded_point(false);
+        let coordinate = |c: Option<&_>| {
+            BASE64_URL_SAFE_NO_PAD
+                .encode(c.expect("An uncompressed point has both coordinates") as &[u8])
+        };
+        json!({
+            "keys": [{
+                "kty": "EC",
+                "crv": "P-256",
+                "x": coordinate(point.x()),
+                "y": coordinate(point.y()),
+                "use": "sig",
+                "alg": "ES256",
+                "kid": self.key_id,
+            }]
+        })
+    }
+
+    /// Mount the token, JWKS and discovery endpoints on `server`.
+    pub async fn mount(&self, server: &MockServer) {
+        let issuer = self.issuer.clone().unwrap_or_else(|| server.uri());
+
+        let provider = self.clone();
+        let token_issuer = issuer.clone();
+        Mock::given(method("POST"))
+            .and(path(TOKEN_PATH))
+            .respond_with(move |request: &Request| provider.token_response(&token_issuer, request))
+            .named(format!("OAuth2Provider - POST {}", TOKEN_PATH))
+            .mount(server)
+            .await;
+
+        Mock::given(method("GET"))
+            .and(path(JWKS_PATH))
+            .respond_with(ResponseTemplate::new(200).set_body_json(self.jwks()))
+            .named(format!("OAuth2Provider - GET {}", JWKS_PATH))
+            .mount(server)
+            .await;
+
+        let discovery = json!({
+            "issuer": issuer,
+            "token_endpoint": format!("{}{}", server.uri(), TOKEN_PATH),
+            "jwks_uri": format!("{}{}", server.uri(), JWKS_PATH),
+            "grant_types_supported": ["client_credentials", "authorization_code", "refresh_token", "password"],
+            "token_endpoint_auth_methods_supported": ["client_secret_basic", "client_secret_post"],
+            "id_token_signing_alg_values_supported": ["ES256"],
+            "subject_types_supported": ["public"],
+            "response_types_supported": ["code"],
+        });
+        Mock::given(method("GET"))
+            .and(path(DISCOVERY_PATH))
+            .respond_with(ResponseTemplate::new(200).set_body_json(discovery))
+            .named(format!("OAuth2Provider - GET {}", DISCOVERY_PATH))
+            .mount(server)
+            .await;
+    }
+
+    fn token_response(&self, issuer: &str, request: &Request) -> ResponseTemplate {
+        let params: Map<String, Value> = url::form_urlencoded::parse(&request.body)
+            .map(|(key, value)| (key.into_owned(), Value::from(value.into_owned())))
+            .collect();
+        if !params.contains_key("grant_type") {
+            return ResponseTemplate::new(400).set_body_json(json!({
+                "error": "invalid_request",
+                "error_description": "Missing `grant_type` parameter",
+            }));
+        }
+        let client_id = params
+            .get("client_id")
+            .and_then(Value::as_str)
+            .map(ToString::to_string)
+            .or_else(|| basic_auth_user(request));
+        let scope = params.get("scope").and_then(Value::as_str);
+
+        let issued_at = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+        let mut claims = Map::new();
+        claims.insert("iss".into(), json!(issuer));
+        if let Some(subject) = self.subject.as_ref().or(client_id.as_ref()) {
+            claims.insert("sub".into(), json!(subject));
+        }
+        claims.insert("iat".into(), json!(issued_at));
+        claims.insert(
+            "exp".into(),
+            json!(issued_at + self.token_lifetime.as_secs()),
+        );
+
+        let mut id_token_claims = claims.clone();
+        if let Some(audience) = &self.audience {
+            claims.insert("aud".into(), json!(audience));
+        }
+        if let Some(scope) = scope {
+            claims.insert("scope".into(), json!(scope));
+        }
+        claims.extend(self.claims.clone());
+
+        let mut body = json!({
+            "access_token": self.sign(&claims),
+            "token_type": "Bearer",
+            "expires_in": self.token_lifetime.as_secs(),
+        });
+        if let Some(scope) = scope {
+            body["scope"] = json!(scope);
+            if scope.split(' ').any(|s| s == "openid") {
+                if let Some(client_id) = &client_id {
+                    id_token_claims.insert("aud".into(), json!(client_id));
+                }
+                id_token_claims.extend(self.claims.clone());
+                body["id_token"] = json!(self.sign(&id_token_claims));
+            }
+        }
+        ResponseTemplate::new(200)
+            .insert_header(http::header::CACHE_CONTROL, "no-store")
+            .set_body_json(body)
+    }
+}
+
+/// The user name in the `Authorization: Basic` header of `request`, if any.
+fn basic_auth_user(request: &Request) -> Option<String> {
+    let value = request
+        .headers
+        .get(http::header::AUTHORIZATION)?
+        .to_str()
+        .ok()?;
+    let encoded = value.strip_prefix("Basic ")?;
+    let decoded = String::from_utf8(BASE64_STANDARD.decode(encoded.trim()).ok()?).ok()?;
+    let (user, _password) = decoded.split_once(':')?;
+    Some(user.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::OAuth2Provider;
+    use base64::prelude::{Engine as _, BASE64_URL_SAFE_NO_PAD};
+    use p256::ecdsa::signature::Verifier;
+    use p256::ecdsa::{Signature, VerifyingKey};
+    use serde_json::{json, Value};
+
+    fn decode(segment: &str) -> Value {
+        serde_json::from_slice(&BASE64_URL_SAFE_NO_PAD.decode(segment).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn tokens_can_be_verified_using_the_jwks() {
+        let provider = OAuth2Provider::new();
+        let token = provider.sign(json!({"sub": "ferris"}));
+
+        let jwk = &provider.jwks()["keys"][0];
+        let mut point = vec![0x04];
+        for coordinate in ["x", "y"] {
+            let encoded = jwk[coordinate].as_str().unwrap();
+            point.extend(BASE64_URL_SAFE_NO_PAD.decode(encoded).unwrap());
+        }
+        let key = VerifyingKey::from_sec1_bytes(&point).unwrap();
+
+        let (signing_input, signature) = token.rsplit_once('.').unwrap();
+        let signature =
+            Signature::from_slice(&BASE64_URL_SAFE_NO_PAD.decode(signature).unwrap()).unwrap();
+        assert!(key.verify(signing_input.as_bytes(), &signature).is_ok());
+
+        let segments: Vec<&str> = token.split('.').collect();
+        assert_eq!(
+            decode(segments[0]),
+            json!({"alg": "ES256", "typ": "JWT", "kid": provider.key_id()})
+        );
+        assert_eq!(decode(segments[1]), json!({"sub": "ferris"}));
+        assert_eq!(jwk["kid"], provider.key_id());
+    }
+}
@@ -0,0 +1,237 @@
+//! A fake paginated collection - see [`paginated`].
+use crate::matchers::{method, path};
+use crate::{Mock, MockServer, Request, Respond, ResponseTemplate};
+use base64::prelude::{Engine as _, BASE64_URL_SAFE_NO_PAD};
+use http::uri::Authority;
+use serde_json::{json, Value};
+
+/// The number of items per page, unless set with [`Paginated::page_size`].
+const DEFAULT_PAGE_SIZE: usize = 10;
+
+/// How clients move from one page of a [`Paginated`] collection to the next.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum PageStyle {
+    /// Pages are numbered, starting from `1`: `?page=2`.
+    PageNumber,
+    /// Each page hands over an opaque cursor pointing at the next one: `?cursor=<next_cursor>`.
+    Cursor,
+}
+
+/// Serve `items` in pages on `GET path`, to test the pagination loop of your client.
+///
+/// Each response carries a JSON body with the items of the page and a link to the next
+/// page, if any. The link is also sent in a `Link` header ([RFC 8288](https://www.rfc-editor.org/rfc/rfc8288)),
+/// while the `X-Total-Count` header holds the total number of items:
+///
+/// ```json
+/// {
+///   "items": [{"id": 1}, {"id": 2}],
+///   "next": "http://127.0.0.1:4242/items?page=2"
+/// }
+/// ```
+///
+/// With [`PageStyle::Cursor`], the body also holds the cursor of the next page, as
+/// `next_cursor`.
+/// Clients can change the number of items per page using the `per_page` (page numbers) or
+/// `limit` (cursors) query parameter. Other query parameters are carried over to the `next`
+/// link. Invalid page numbers and cursors get a `400 Bad Request` response.
+///
+/// ### Example:
+/// ```rust
+/// use wiremock::MockServer;
+/// use wiremock::fakes::{paginated, PageStyle};
+/// use serde_json::{json, Value};
+///
+/// #[async_std::main]
+/// async fn main() {
+///     // Arrange
+///     let mock_server = MockServer::start().await;
+///     let items = (1..=25).map(|id| json!({ "id": id }));
+///     paginated("/items", items, PageStyle::Cursor)
+///         .page_size(10)
+///         .mount(&mock_server)
+///         .await;
+///
+///     // Act - follow the `next` links
+///     let mut next = Some(format!("{}/items", mock_server.uri()));
+///     let mut n_pages = 0;
+///     let mut ids = vec![];
+///     while let Some(url) = next {
+///         let page: Value = surf::get(url).recv_json().await.unwrap();
+///         n_pages += 1;
+///         for item in page["items"].as_array().unwrap() {
+///             ids.push(item["id"].as_u64().unwrap());
+///         }
+///         next = page["next"].as_str().map(str::to_owned);
+///     }
+///
+///     // Assert
+///     assert_eq!(n_pages, 3);
+///     assert_eq!(ids, (1..=25).collect::<Vec<_>>());
+/// }
+/// ```
+pub fn paginated<P, I>(path: P, items: I, style: PageStyle) -> Paginated
+where
+    P: Into<String>,
+    I: IntoIterator<Item = Value>,
+{
+    Paginated {
+        path: path.into(),
+        items: items.into_iter().collect(),
+        style,
+        page_size: DEFAULT_PAGE_SIZE,
+    }
+}
+
+/// A paginated collection, built with [`paginated`].
+///
+/// It implements [`Respond`]: use [`Paginated::mount`] or pass it to
+/// [`MockBuilder::respond_with`](crate::MockBuilder::respond_with) to combine it with other
+/// matchers.
+#[derive(Debug, Clone)]
+pub struct Paginated {
+    path: String,
+    items: Vec<Value>,
+    style: PageStyle,
+    page_size: usize,
+}
+
+impl Paginated {
+    /// Set the number of items per page, unless the client asks for another one.
+    /// It defaults to `10`.
+    ///
+    /// It panics if `page_size` is `0`.
+    pub fn page_size(mut self, page_size: usize) -> Self {
+        assert!(page_size > 0, "page_size must be strictly greater than 0!");
+        self.page_size = page_size;
+        self
+    }
+
+    /// Mount the collection on `GET path` on `server`.
+    pub async fn mount(&self, server: &MockServer) {
+        Mock::given(method("GET"))
+            .and(path(self.path.as_str()))
+            .respond_with(self.clone())
+            .named(format!("Paginated {}", self.path))
+            .mount(server)
+            .await;
+    }
+
+    /// The offset of the first item of the requested page and the size of the page, or an
+    /// error message if the pagination parameters are invalid.
+    fn requested_page(&self, request: &Request) -> Result<(usize, usize), String> {
+        let (position_parameter, size_parameter) = self.parameters();
+        let parameter = |name: &str| {
+            request
+                .url
+                .query_pairs()
+                .find(|(key, _)| key == name)
+                .map(|(_, value)| value.into_owned())
+        };
+        let page_size = match parameter(size_parameter) {
+            Some(size) => match size.parse() {
+                Ok(size) if size > 0 => size,
+                _ => return Err(format!("`{}` must be a positive integer", size_parameter)),
+            },
+            None => self.page_size,
+        };
+        let offset = match (self.style, parameter(position_parameter)) {
+            (_, None) => 0,
+            (PageStyle::PageNumber, Some(page)) => match page.parse::<usize>() {
+                Ok(page) if page > 0 => (page - 1).saturating_mul(page_size),
+                _ => {
+                    return Err(format!(
+                        "`{}` must be a positive integer",
+                        position_parameter
+                    ))
+                }
+            },
+            (PageStyle::Cursor, Some(cursor)) => {
+                decode_cursor(&cursor).ok_or_else(|| format!("Invalid cursor: {}", cursor))?
+            }
+        };
+        Ok((offset, page_size))
+    }
+
+    /// The query parameters holding the position of the page and its size.
+    fn parameters(&self) -> (&'static str, &'static str) {
+        match self.style {
+            PageStyle::PageNumber => ("page", "per_page"),
+            PageStyle::Cursor => ("cursor", "limit"),
+        }
+    }
+}
+
+impl Respond for Paginated {
+    fn respond(&self, request: &Request) -> ResponseTemplate {
+        let (offset, page_size) = match self.requested_page(request) {
+            Ok(page) => page,
+            Err(message) => {
+                return ResponseTemplate::new(400).set_body_json(json!({ "error": message }))
+            }
+        };
+        let start = offset.min(self.items.len());
+        let end = offset.saturating_add(page_size).min(self.items.len());
+        let next_position = (end < self.items.len()).then(|| match self.style {
+            PageStyle::PageNumber => (offset / page_size + 2).to_string(),
+            PageStyle::Cursor => encode_cursor(end),
+        });
+        let next = next_position.as_ref().map(|position| {
+            let (position_parameter, _) = self.parameters();
+            next_url(request, position_parameter, position)
+        });
+
+        let mut body = json!({
+            "items": &self.items[start..end],
+            "next": next,
+        });
+        if self.style == PageStyle::Cursor {
+            body["next_cursor"] = json!(next_position);
+        }
+        let mut response = ResponseTemplate::new(200)
+            .insert_header("X-Total-Count", self.items.len().to_string().as_str())
+            .set_body_json(body);
+        if let Some(next) = next {
+            response = response.insert_header("Link", format!("<{}>; rel=\"next\"", next).as_str());
+        }
+        response
+    }
+}
+
+fn encode_cursor(offset: usize) -> String {
+    BASE64_URL_SAFE_NO_PAD.encode(format!("offset:{}", offset))
+}
+
+fn decode_cursor(cursor: &str) -> Option<usize> {
+    let decoded = BASE64_URL_SAFE_NO_PAD.decode(cursor).ok()?;
+    String::from_utf8(decoded)
+        .ok()?
+        .strip_prefix("offset:")?
+        .parse()
+        .ok()
+}
+
+/// The absolute URL of the next page: the URL of `request`, as seen by the client, with the
+/// `position_parameter` query parameter set to `position`.
+fn next_url(request: &Request, position_parameter: &str, position: &str) -> String {
+    let mut url = request.url.clone();
+    let authority = request
+        .headers
+        .get(http::header::HOST)
+        .and_then(|host| host.to_str().ok()?.parse::<Authority>().ok());
+    if let Some(authority) = authority {
+        let _ = url.set_host(Some(authority.host()));
+        let _ = url.set_port(authority.port_u16());
+    }
+    let pairs: Vec<(String, String)> = url
+        .query_pairs()
+        .filter(|(key, _)| key != position_parameter)
+        .map(|(key, value)| (key.into_owned(), value.into_owned()))
+        .collect();
+    url.query_pairs_mut()
+        .clear()
+        .extend_pairs(pairs)
+        .append_pair(position_parameter, position);
+    url.to_string()
+}
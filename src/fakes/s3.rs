@@ -0,0 +1,364 @@
+//! Helpers to build the XML responses returned by [Amazon S3](https://docs.aws.amazon.com/AmazonS3/latest/API/Welcome.html).
+//!
+//! [`S3Error`] and [`ListObjectsV2`] implement [`Respond`]: pass them to
+//! [`MockBuilder::respond_with`](crate::MockBuilder::respond_with) to have a [`MockServer`]
+//! behave like an S3 bucket towards the AWS SDKs. Combine them with
+//! [`aws_sigv4_valid`](crate::matchers::aws_sigv4_valid) to check that your client signs
+//! its requests properly.
+//!
+//! This module is only available if the `aws` feature is enabled.
+//!
+//! ### Example:
+//! ```rust
+//! use wiremock::{MockServer, Mock};
+//! use wiremock::fakes::s3::{ListObjectsV2, S3Error};
+//! use wiremock::matchers::{method, path, query_param};
+//!
+//! #[async_std::main]
+//! async fn main() {
+//!     // Arrange
+//!     let mock_server = MockServer::start().await;
+//!     Mock::given(method("GET"))
+//!         .and(path("/my-bucket"))
+//!         .and(query_param("list-type", "2"))
+//!         .respond_with(
+//!             ListObjectsV2::new("my-bucket")
+//!                 .object("reports/2024.csv", "year,total\n2024,42\n")
+//!                 .object("reports/2025.csv", "year,total\n2025,43\n")
+//!                 .object("README.md", "# Reports"),
+//!         )
+//!         .mount(&mock_server)
+//!         .await;
+//!     Mock::given(method("GET"))
+//!         .and(path("/my-bucket/missing.csv"))
+//!         .respond_with(S3Error::no_such_key("missing.csv"))
+//!         .mount(&mock_server)
+//!         .await;
+//!
+//!     // Act
+//!     let mut listing = surf::get(format!(
+//!         "{}/my-bucket?list-type=2&prefix=reports/",
+//!         mock_server.uri()
+//!     ))
+//!     .await
+//!     .unwrap();
+//!     let mut missing = surf::get(format!("{}/my-bucket/missing.csv", mock_server.uri()))
+//!         .await
+//!         .unwrap();
+//!
+//!     // Assert
+//!     let listing = listing.body_string().await.unwrap();
+//!     assert!(listing.contains("<KeyCount>2</KeyCount>"));
+//!     assert!(listing.contains("<Key>reports/2024.csv</Key>"));
+//!     assert!(!listing.contains("README.md"));
+//!
+//!     assert_eq!(missing.status(), 404);
+//!     assert!(missing.body_string().await.unwrap().contains("<Code>NoSuchKey</Code>"));
+//! }
+//! ```
+//!
+//! [`MockServer`]: crate::MockServer
+use crate::date::iso8601;
+use crate::{Request, Respond, ResponseTemplate};
+use base64::prelude::{Engine as _, BASE64_URL_SAFE_NO_PAD};
+use http::StatusCode;
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::convert::TryInto;
+use std::fmt::Write;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::SystemTime;
+
+/// The maximum number of keys returned by a `ListObjectsV2` call, if the request does not
+/// specify `max-keys`.
+const DEFAULT_MAX_KEYS: usize = 1000;
+
+/// An S3 [error response](https://docs.aws.amazon.com/AmazonS3/latest/API/ErrorResponses.html).
+///
+/// ```xml
+/// <?xml version="1.0" encoding="UTF-8"?>
+/// <Error>
+///   <Code>NoSuchKey</Code>
+///   <Message>The specified key does not exist.</Message>
+///   <Key>missing.csv</Key>
+///   <RequestId>0000000000000001</RequestId>
+/// </Error>
+/// ```
+#[derive(Debug, Clone)]
+pub struct S3Error {
+    status: StatusCode,
+    code: String,
+    message: String,
+    details: Vec<(String, String)>,
+}
+
+impl S3Error {
+    /// An error with the specified status, error code (e.g. `SlowDown`) and message.
+    pub fn new<S, C, M>(status: S, code: C, message: M) -> Self
+    where
+        S: TryInto<StatusCode>,
+        <S as TryInto<StatusCode>>::Error: std::fmt::Debug,
+        C: Into<String>,
+        M: Into<String>,
+    {
+        Self {
+            status: status
+                .try_into()
+                .expect("Failed to convert into status code."),
+            code: code.into(),
+            message: message.into(),
+            details: vec![],
+        }
+    }
+
+    /// `404 NoSuchKey`, returned when the requested object does not exist.
+    pub fn no_such_key<K: Into<String>>(key: K) -> Self {
+        Self::new(404, "NoSuchKey", "The specified key does not exist.").detail("Key", key)
+    }
+
+    /// `404 NoSuchBucket`, returned when the requested bucket does not exist.
+    pub fn no_such_bucket<B: Into<String>>(bucket: B) -> Self {
+        Self::new(404, "NoSuchBucket", "The specified bucket does not exist.")
+            .detail("BucketName", bucket)
+    }
+
+    /// `403 AccessDenied`.
+    pub fn access_denied() -> Self {
+        Self::new(403, "AccessDenied", "Access Denied")
+    }
+
+    /// `403 SignatureDoesNotMatch`, returned when the signature of the request is not valid.
+    pub fn signature_does_not_match() -> Self {
+        Self::new(
+            403,
+            "SignatureDoesNotMatch",
+            "The request signature we calculated does not match the signature you provided.",
+        )
+    }
+
+    /// Add an element to the error document - e.g. `Key` or `BucketName`.
+    pub fn detail<N: Into<String>, V: Into<String>>(mut self, name: N, value: V) -> Self {
+        self.details.push((name.into(), value.into()));
+        self
+    }
+}
+
+impl Respond for S3Error {
+    fn respond(&self, _request: &Request) -> ResponseTemplate {
+        let mut body = String::from(XML_DECLARATION);
+        body.push_str("<Error>");
+        write_element(&mut body, "Code", &self.code);
+        write_element(&mut body, "Message", &self.message);
+        for (name, value) in &self.details {
+            write_element(&mut body, name, value);
+        }
+        let request_id = next_request_id();
+        write_element(&mut body, "RequestId", &request_id);
+        body.push_str("</Error>");
+        xml_response(self.status, body, &request_id)
+    }
+}
+
+/// A [`ListObjectsV2`](https://docs.aws.amazon.com/AmazonS3/latest/API/API_ListObjectsV2.html)
+/// response, listing the objects of a bucket.
+///
+/// The objects are filtered and paginated according to the `prefix`, `delimiter`,
+/// `start-after`, `max-keys` and `continuation-token` query parameters of the request, hence
+/// the paginators of the AWS SDKs can walk through the listing.
+#[derive(Debug, Clone)]
+pub struct ListObjectsV2 {
+    bucket: String,
+    objects: BTreeMap<String, S3Object>,
+}
+
+/// An object listed by [`ListObjectsV2`].
+#[derive(Debug, Clone)]
+struct S3Object {
+    size: usize,
+    etag: String,
+    last_modified: SystemTime,
+}
+
+/// An entry of the listing: either an object or a common prefix, when a delimiter is used.
+enum Entry<'a> {
+    Object(&'a str, &'a S3Object),
+    CommonPrefix(String),
+}
+
+impl Entry<'_> {
+    fn key(&self) -> &str {
+        match self {
+            Entry::Object(key, _) => key,
+            Entry::CommonPrefix(prefix) => prefix,
+        }
+    }
+}
+
+impl ListObjectsV2 {
+    /// An empty listing of `bucket`.
+    pub fn new<B: Into<String>>(bucket: B) -> Self {
+        Self {
+            bucket: bucket.into(),
+            objects: BTreeMap::new(),
+        }
+    }
+
+    /// Add an object to the bucket. Its size and ETag are derived from `content`, its last
+    /// modification date is the current time.
+    pub fn object<K: Into<String>, C: AsRef<[u8]>>(mut self, key: K, content: C) -> Self {
+        let content = content.as_ref();
+        let digest = Sha256::digest(content);
+        // The ETag is opaque to clients: we do not need it to be an MD5 digest, as S3 does.
+        let etag = digest[..16].iter().fold(String::new(), |mut etag, byte| {
+            write!(etag, "{:02x}", byte).unwrap();
+            etag
+        });
+        let object = S3Object {
+            size: content.len(),
+            etag: format!("\"{}\"", etag),
+            last_modified: SystemTime::now(),
+        };
+        self.objects.insert(key.into(), object);
+        self
+    }
+
+    /// The entries matching `prefix`, in lexicographical order, with the keys containing
+    /// `delimiter` after the prefix rolled up into common prefixes.
+    fn entries(&self, prefix: &str, delimiter: Option<&str>) -> Vec<Entry<'_>> {
+        let mut entries: Vec<Entry<'_>> = vec![];
+        for (key, object) in self.objects.range(prefix.to_string()..) {
+            let rest = match key.strip_prefix(prefix) {
+                Some(rest) => rest,
+                None => break,
+            };
+            let common_prefix = delimiter.and_then(|d| {
+                rest.find(d)
+                    .map(|i| format!("{}{}", prefix, &rest[..i + d.len()]))
+            });
+            match common_prefix {
+                Some(common_prefix) => {
+                    if entries.last().map(Entry::key) != Some(common_prefix.as_str()) {
+                        entries.push(Entry::CommonPrefix(common_prefix));
+                    }
+                }
+                None => entries.push(Entry::Object(key, object)),
+            }
+        }
+        entries
+    }
+}
+
+impl Respond for ListObjectsV2 {
+    fn respond(&self, request: &Request) -> ResponseTemplate {
+        let params: BTreeMap<String, String> = request.url.query_pairs().into_owned().collect();
+        let prefix = params.get("prefix").map(String::as_str).unwrap_or_default();
+        let delimiter = params
+            .get("delimiter")
+            .map(String::as_str)
+            .filter(|d| !d.is_empty());
+        let max_keys = match params.get("max-keys").map(|m| m.parse::<usize>()) {
+            None => DEFAULT_MAX_KEYS,
+            Some(Ok(max_keys)) => max_keys.min(DEFAULT_MAX_KEYS),
+            Some(Err(_)) => {
+                return S3Error::new(400, "InvalidArgument", "Provided max-keys not an integer")
+                    .respond(request)
+            }
+        };
+        let start_after = match params.get("continuation-token") {
+            Some(token) => match BASE64_URL_SAFE_NO_PAD
+                .decode(token)
+                .ok()
+                .and_then(|key| String::from_utf8(key).ok())
+            {
+                Some(key) => Some(key),
+                None => {
+                    return S3Error::new(
+                        400,
+                        "InvalidArgument",
+                        "The continuation token provided is incorrect",
+                    )
+                    .respond(request)
+                }
+            },
+            None => params.get("start-after").cloned(),
+        };
+
+        let entries = self.entries(prefix, delimiter);
+        let mut remaining = entries
+            .iter()
+            .filter(|entry| start_after.as_deref().is_none_or(|s| entry.key() > s))
+            .peekable();
+        let page: Vec<&Entry<'_>> = remaining.by_ref().take(max_keys).collect();
+        let is_truncated = remaining.peek().is_some();
+
+        let mut body = String::from(XML_DECLARATION);
+        body.push_str("<ListBucketResult xmlns=\"http://s3.amazonaws.com/doc/2006-03-01/\">");
+        write_element(&mut body, "Name", &self.bucket);
+        write_element(&mut body, "Prefix", prefix);
+        if let Some(delimiter) = delimiter {
+            write_element(&mut body, "Delimiter", delimiter);
+        }
+        write_element(&mut body, "MaxKeys", &max_keys.to_string());
+        write_element(&mut body, "KeyCount", &page.len().to_string());
+        write_element(&mut body, "IsTruncated", &is_truncated.to_string());
+        if let Some(token) = params.get("continuation-token") {
+            write_element(&mut body, "ContinuationToken", token);
+        }
+        if let (true, Some(last)) = (is_truncated, page.last()) {
+            let token = BASE64_URL_SAFE_NO_PAD.encode(last.key());
+            write_element(&mut body, "NextContinuationToken", &token);
+        }
+        if let Some(start_after) = params.get("start-after") {
+            write_element(&mut body, "StartAfter", start_after);
+        }
+        for entry in page {
+            match entry {
+                Entry::Object(key, object) => {
+                    body.push_str("<Contents>");
+                    write_element(&mut body, "Key", key);
+                    write_element(&mut body, "LastModified", &iso8601(object.last_modified));
+                    write_element(&mut body, "ETag", &object.etag);
+                    write_element(&mut body, "Size", &object.size.to_string());
+                    write_element(&mut body, "StorageClass", "STANDARD");
+                    body.push_str("</Contents>");
+                }
+                Entry::CommonPrefix(prefix) => {
+                    body.push_str("<CommonPrefixes>");
+                    write_element(&mut body, "Prefix", prefix);
+                    body.push_str("</CommonPrefixes>");
+                }
+            }
+        }
+        body.push_str("</ListBucketResult>");
+        xml_response(StatusCode::OK, body, &next_request_id())
+    }
+}
+
+const XML_DECLARATION: &str = "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n";
+
+fn xml_response(status: StatusCode, body: String, request_id: &str) -> ResponseTemplate {
+    ResponseTemplate::new(status)
+        .insert_header("x-amz-request-id", request_id)
+        .set_body_raw(body, "application/xml")
+}
+
+/// S3 request ids are 16 uppercase hexadecimal characters.
+fn next_request_id() -> String {
+    static NEXT_REQUEST_ID: AtomicU64 = AtomicU64::new(1);
+    format!("{:016X}", NEXT_REQUEST_ID.fetch_add(1, Ordering::Relaxed))
+}
+
+fn write_element(xml: &mut String, name: &str, value: &str) {
+    write!(xml, "<{}>", name).unwrap();
+    for c in value.chars() {
+        match c {
+            '&' => xml.push_str("&amp;"),
+            '<' => xml.push_str("&lt;"),
+            '>' => xml.push_str("&gt;"),
+            '"' => xml.push_str("&quot;"),
+            '\'' => xml.push_str("&apos;"),
+            c => xml.push(c),
+        }
+    }
+    write!(xml, "</{}>", name).unwrap();
+}
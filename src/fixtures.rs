@@ -0,0 +1,163 @@
+use crate::stub::Stub;
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, RecvTimeoutError, Sender};
+use std::thread::JoinHandle;
+use std::time::{Duration, SystemTime};
+use tracing::warn;
+
+/// Keeps the [`Mock`](crate::Mock)s loaded from a fixture directory in sync with its content.
+///
+/// It is returned by [`MockServer::watch_fixture_dir`](crate::MockServer::watch_fixture_dir).
+/// The directory is watched until the `FixtureWatcher` is dropped: the mocks that were loaded
+/// last stay mounted afterwards.
+#[must_use = "The fixture directory stops being watched as soon as the `FixtureWatcher` is dropped."]
+pub struct FixtureWatcher {
+    stop: Sender<()>,
+    handle: Option<JoinHandle<()>>,
+}
+
+impl FixtureWatcher {
+    /// Poll `dir` every `poll_interval`, calling `reload` with the new set of stubs every time
+    /// a fixture file is added, removed or modified.
+    ///
+    /// The thread exits once `reload` returns `false`, or `is_attached` does - e.g. because the
+    /// stubs belong to a server that has been reset in the meantime.
+    pub(crate) fn spawn<A, F>(
+        dir: PathBuf,
+        poll_interval: Duration,
+        is_attached: A,
+        mut reload: F,
+    ) -> Self
+    where
+        A: Fn() -> bool + Send + 'static,
+        F: FnMut(Vec<Stub>) -> bool + Send + 'static,
+    {
+        let (stop, stopped) = mpsc::channel();
+        let handle = std::thread::spawn(move || {
+            let mut last_seen = fixture_files(&dir);
+            // Waiting on the channel rather than sleeping lets `drop` wake the thread up
+            // straight away.
+            while let Err(RecvTimeoutError::Timeout) = stopped.recv_timeout(poll_interval) {
+                if !is_attached() {
+                    break;
+                }
+                let current = fixture_files(&dir);
+                if current == last_seen {
+                    continue;
+                }
+                last_seen = current;
+                match try_load_stubs(&dir) {
+                    Ok(stubs) => {
+                        if !reload(stubs) {
+                            break;
+                        }
+                    }
+                    // A file might be caught half-written: keep the current mocks and
+                    // try again on the next change.
+                    Err(e) => warn!("Failed to reload fixtures: {}", e),
+                }
+            }
+        });
+        Self {
+            stop,
+            handle: Some(handle),
+        }
+    }
+}
+
+impl Drop for FixtureWatcher {
+    fn drop(&mut self) {
+        let _ = self.stop.send(());
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.join();
+        }
+    }
+}
+
+/// Load all the stubs stored in `dir`, in alphabetical order of the file names.
+///
+/// It panics if the directory cannot be read or if a fixture file is invalid.
+pub(crate) fn load_stubs(dir: &Path) -> Vec<Stub> {
+    try_load_stubs(dir).unwrap_or_else(|e| panic!("Failed to load fixtures: {}", e))
+}
+
+fn try_load_stubs(dir: &Path) -> Result<Vec<Stub>, String> {
+    let entries = fs::read_dir(dir)
+        .map_err(|e| format!("cannot read directory `{}`: {}", dir.display(), e))?;
+    let mut files: Vec<PathBuf> = entries
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| path.is_file() && format(path).is_some())
+        .collect();
+    files.sort();
+
+    let mut stubs = vec![];
+    for file in files {
+        let file_stubs = parse_file(&file)
+            .and_then(|file_stubs| {
+                file_stubs
+                    .iter()
+                    .try_for_each(Stub::check_custom_matchers)
+                    .map(|()| file_stubs)
+            })
+            .map_err(|e| format!("`{}`: {}", file.display(), e))?;
+        stubs.extend(file_stubs);
+    }
+    Ok(stubs)
+}
+
+#[derive(Clone, Copy)]
+enum Format {
+    Json,
+    Yaml,
+}
+
+/// Fixture files are recognised by their extension.
+fn format(path: &Path) -> Option<Format> {
+    match path.extension()?.to_str()? {
+        "json" => Some(Format::Json),
+        "yaml" | "yml" => Some(Format::Yaml),
+        _ => None,
+    }
+}
+
+/// A fixture file contains either a single stub or a list of stubs.
+fn parse_file(path: &Path) -> Result<Vec<Stub>, String> {
+    let content = fs::read_to_string(path).map_err(|e| e.to_string())?;
+    let value: Value = match format(path) {
+        Some(Format::Json) => serde_json::from_str(&content).map_err(|e| e.to_string())?,
+        #[cfg(feature = "yaml")]
+        Some(Format::Yaml) => serde_yaml::from_str(&content).map_err(|e| e.to_string())?,
+        #[cfg(not(feature = "yaml"))]
+        Some(Format::Yaml) => {
+            return Err("YAML fixtures require the `yaml` feature of `wiremock`".into())
+        }
+        None => unreachable!("Only fixture files are parsed"),
+    };
+    let stubs = if value.is_array() {
+        serde_json::from_value(value)
+    } else {
+        serde_json::from_value(value).map(|stub| vec![stub])
+    };
+    stubs.map_err(|e| e.to_string())
+}
+
+/// The fixture files in `dir`, with their last modification time and size.
+fn fixture_files(dir: &Path) -> BTreeMap<PathBuf, Option<(SystemTime, u64)>> {
+    fs::read_dir(dir)
+        .map(|entries| {
+            entries
+                .filter_map(|entry| entry.ok().map(|e| e.path()))
+                .filter(|path| format(path).is_some())
+                .map(|path| {
+                    let metadata = fs::metadata(&path)
+                        .and_then(|m| Ok((m.modified()?, m.len())))
+                        .ok();
+                    (path, metadata)
+                })
+                .collect()
+        })
+        .unwrap_or_default()
+}
@@ -0,0 +1,279 @@
+//! Record and replay traffic using [HTTP Archive (HAR)](http://www.softwareishard.com/blog/har-12-spec/)
+//! files.
+//!
+//! Use [`MockServer::export_har`] to save the traffic received by a [`MockServer`] and
+//! [`MockServer::mount_har`] to turn a HAR file - e.g. captured using the devtools of your
+//! browser or a proxy - into a set of mocks.
+//!
+//! [`MockServer`]: crate::MockServer
+//! [`MockServer::export_har`]: crate::MockServer::export_har
+//! [`MockServer::mount_har`]: crate::MockServer::mount_har
+use crate::date::iso8601;
+use crate::matchers::{body_bytes, method, path, query_param};
+use crate::{JournalEntry, Mock, ResponseTemplate};
+use base64::prelude::{Engine as _, BASE64_STANDARD};
+use http::HeaderMap;
+use http_body_util::BodyExt;
+use serde_json::{json, Value};
+
+/// How the mocks mounted by [`MockServer::mount_har`](crate::MockServer::mount_har) match
+/// incoming requests against the requests recorded in the HAR file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MatchStrategy {
+    /// Match on the method and the path of the recorded request.
+    MethodAndPath,
+    /// Match on the method, the path and the query parameters of the recorded request.
+    /// Incoming requests can have additional query parameters.
+    MethodPathAndQuery,
+    /// Match on the method, the path, the query parameters and the body of the recorded request.
+    MethodPathQueryAndBody,
+}
+
+/// Build a mock for each entry of a HAR document.
+///
+/// If several entries match the same requests according to `strategy`, they are replayed
+/// in order: each of them responds once, apart from the last one which keeps responding.
+///
+/// It panics if `har` is not a valid HAR document.
+pub(crate) fn mocks_from_har(har: &Value, strategy: MatchStrategy) -> Vec<Mock> {
+    let entries = har["log"]["entries"]
+        .as_array()
+        .expect("Invalid HAR: `log.entries` must be an array");
+    let requests: Vec<HarRequest> = entries
+        .iter()
+        .enumerate()
+        .map(|(i, entry)| HarRequest::parse(&entry["request"], i, strategy))
+        .collect();
+
+    entries
+        .iter()
+        .zip(&requests)
+        .enumerate()
+        .map(|(i, (entry, request))| {
+            let mut builder =
+                Mock::given(method(request.method.as_str())).and(path(request.url.path()));
+            if strategy != MatchStrategy::MethodAndPath {
+                for (key, value) in request.url.query_pairs() {
+                    builder = builder.and(query_param(key, value));
+                }
+            }
+            if strategy == MatchStrategy::MethodPathQueryAndBody {
+                builder = builder.and(body_bytes(request.body.clone()));
+            }
+            let mut mock = builder
+                .respond_with(response_from_har(&entry["response"], i))
+                .named(format!(
+                    "HAR entry #{} - {} {}",
+                    i, request.method, request.url
+                ));
+            let replayed_later = requests[i + 1..].iter().any(|r| r.key == request.key);
+            if replayed_later {
+                mock = mock.up_to_n_times(1);
+            }
+            mock
+        })
+        .collect()
+}
+
+/// The parts of a recorded request that are relevant for matching.
+struct HarRequest {
+    method: String,
+    url: url::Url,
+    body: Vec<u8>,
+    /// Requests with the same key match the same incoming requests.
+    key: String,
+}
+
+impl HarRequest {
+    fn parse(request: &Value, index: usize, strategy: MatchStrategy) -> Self {
+        let method = request["method"]
+            .as_str()
+            .unwrap_or_else(|| panic!("Invalid HAR: entry #{} has no request method", index))
+            .to_string();
+        let url: url::Url = request["url"]
+            .as_str()
+            .and_then(|url| url.parse().ok())
+            .unwrap_or_else(|| panic!("Invalid HAR: entry #{} has no valid request URL", index));
+        let body = request["postData"]["text"]
+            .as_str()
+            .unwrap_or_default()
+            .as_bytes()
+            .to_vec();
+
+        let mut query: Vec<_> = url.query_pairs().collect();
+        query.sort();
+        let key = match strategy {
+            MatchStrategy::MethodAndPath => format!("{} {}", method, url.path()),
+            MatchStrategy::MethodPathAndQuery => {
+                format!("{} {} {:?}", method, url.path(), query)
+            }
+            MatchStrategy::MethodPathQueryAndBody => {
+                format!("{} {} {:?} {:?}", method, url.path(), query, body)
+            }
+        };
+        Self {
+            method,
+            url,
+            body,
+            key,
+        }
+    }
+}
+
+/// Headers that describe how the recorded response was transferred, rather than its content.
+/// The recorded body is already decoded, hence they do not apply to the replayed response.
+const TRANSFER_HEADERS: [&str; 4] = [
+    "content-length",
+    "content-encoding",
+    "transfer-encoding",
+    "connection",
+];
+
+fn response_from_har(response: &Value, index: usize) -> ResponseTemplate {
+    let status = response["status"]
+        .as_u64()
+        .unwrap_or_else(|| panic!("Invalid HAR: entry #{} has no response status", index));
+    let mut template = ResponseTemplate::new(status as u16);
+
+    for header in response["headers"].as_array().into_iter().flatten() {
+        let (name, value) = match (header["name"].as_str(), header["value"].as_str()) {
+            (Some(name), Some(value)) => (name, value),
+            _ => continue,
+        };
+        if TRANSFER_HEADERS.contains(&name.to_ascii_lowercase().as_str()) {
+            continue;
+        }
+        template = template.append_header(name, value);
+    }
+
+    let content = &response["content"];
+    if let Some(text) = content["text"].as_str() {
+        let body = if content["encoding"].as_str() == Some("base64") {
+            BASE64_STANDARD.decode(text).unwrap_or_else(|e| {
+                panic!(
+                    "Invalid HAR: entry #{} has an invalid base64 body: {}",
+                    index, e
+                )
+            })
+        } else {
+            text.as_bytes().to_vec()
+        };
+        template = template.set_body_bytes(body);
+    }
+    template
+}
+
+/// Build a HAR document out of the entries of the request journal.
+pub(crate) async fn to_har(entries: &[JournalEntry]) -> Value {
+    let mut har_entries = Vec::with_capacity(entries.len());
+    for entry in entries {
+        har_entries.push(har_entry(entry).await);
+    }
+    json!({
+        "log": {
+            "version": "1.2",
+            "creator": {
+                "name": "wiremock",
+                "version": env!("CARGO_PKG_VERSION"),
+            },
+            "entries": har_entries,
+        }
+    })
+}
+
+async fn har_entry(entry: &JournalEntry) -> Value {
+    let request = &entry.request;
+    let response = entry.response.generate_response();
+    let (parts, body) = response.into_parts();
+    let body = body
+        .collect()
+        .await
+        .expect("Reading an in-memory body cannot fail")
+        .to_bytes();
+    let delay_ms = entry
+        .response
+        .delay()
+        .map(|d| d.as_secs_f64() * 1000.)
+        .unwrap_or(0.);
+
+    let mut har_request = json!({
+        "method": request.method.as_str(),
+        "url": request.url.as_str(),
+        "httpVersion": "HTTP/1.1",
+        "cookies": [],
+        "headers": headers(&request.headers),
+        "queryString": request
+            .url
+            .query_pairs()
+            .map(|(name, value)| json!({ "name": name, "value": value }))
+            .collect::<Vec<_>>(),
+        "headersSize": -1,
+        "bodySize": request.body.len(),
+    });
+    if !request.body.is_empty() {
+        har_request["postData"] = json!({
+            "mimeType": header_value(&request.headers, http::header::CONTENT_TYPE),
+            "text": String::from_utf8_lossy(&request.body),
+        });
+    }
+
+    let mut content = json!({
+        "size": body.len(),
+        "mimeType": header_value(&parts.headers, http::header::CONTENT_TYPE),
+    });
+    match std::str::from_utf8(&body) {
+        Ok(text) => content["text"] = json!(text),
+        Err(_) => {
+            content["text"] = json!(BASE64_STANDARD.encode(&body));
+            content["encoding"] = json!("base64");
+        }
+    }
+
+    json!({
+        "startedDateTime": iso8601(entry.received_at_wall),
+        "time": delay_ms,
+        "request": har_request,
+        "response": {
+            "status": parts.status.as_u16(),
+            "statusText": parts.status.canonical_reason().unwrap_or(""),
+            "httpVersion": "HTTP/1.1",
+            "cookies": [],
+            "headers": headers(&parts.headers),
+            "content": content,
+            "redirectURL": header_value(&parts.headers, http::header::LOCATION),
+            "headersSize": -1,
+            "bodySize": body.len(),
+        },
+        "cache": {},
+        "timings": {
+            "send": 0,
+            "wait": delay_ms,
+            "receive": 0,
+        },
+        "comment": entry
+            .matched_mock
+            .as_ref()
+            .map(|mock| format!("Matched by {}", mock))
+            .unwrap_or_else(|| "No mock matched the request".to_string()),
+    })
+}
+
+fn headers(headers: &HeaderMap) -> Vec<Value> {
+    headers
+        .iter()
+        .map(|(name, value)| {
+            json!({
+                "name": name.as_str(),
+                "value": String::from_utf8_lossy(value.as_bytes()),
+            })
+        })
+        .collect()
+}
+
+/// The value of `name` in `headers` as a string, empty if the header is missing.
+fn header_value(headers: &HeaderMap, name: http::header::HeaderName) -> String {
+    headers
+        .get(name)
+        .map(|value| String::from_utf8_lossy(value.as_bytes()).into_owned())
+        .unwrap_or_default()
+}
@@ -1,3 +1,3 @@
 //! Convenient re-exports of http types that are part of `wiremock`'s public API.
-pub use http::{HeaderMap, HeaderName, HeaderValue, Method};
+pub use http::{HeaderMap, HeaderName, HeaderValue, Method, StatusCode};
 pub use url::Url;
@@ -0,0 +1,67 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use tokio::sync::Notify;
+
+/// Keeps track of how many requests are being processed concurrently, as well as the highest
+/// number of concurrent requests observed so far.
+///
+/// A request is considered in-flight from the moment it enters [`InFlightCounter::enter`] until
+/// the returned [`InFlightGuard`] is dropped.
+#[derive(Debug, Default)]
+pub(crate) struct InFlightCounter {
+    current: AtomicUsize,
+    max: AtomicUsize,
+    /// Notified when the last in-flight request completes.
+    idle: Notify,
+}
+
+impl InFlightCounter {
+    /// Mark a new request as in-flight.
+    /// It stays in-flight until the returned guard is dropped.
+    pub(crate) fn enter(self: &Arc<Self>) -> InFlightGuard {
+        let current = self.current.fetch_add(1, Ordering::SeqCst) + 1;
+        self.max.fetch_max(current, Ordering::SeqCst);
+        InFlightGuard(self.clone())
+    }
+
+    /// The number of requests that are currently in-flight.
+    pub(crate) fn current(&self) -> usize {
+        self.current.load(Ordering::SeqCst)
+    }
+
+    /// Wait until no request is in-flight.
+    pub(crate) async fn wait_until_idle(&self) {
+        loop {
+            // `notify_waiters` wakes up the futures created before it is called, even if they
+            // have not been polled yet: no completion can slip in between the check and the wait.
+            let idle = self.idle.notified();
+            if self.current() == 0 {
+                return;
+            }
+            idle.await;
+        }
+    }
+
+    /// The highest number of concurrent in-flight requests observed so far.
+    pub(crate) fn max(&self) -> usize {
+        self.max.load(Ordering::SeqCst)
+    }
+
+    /// Forget about the peaks observed so far: the maximum restarts from the number of
+    /// requests that are currently in-flight.
+    pub(crate) fn reset_max(&self) {
+        self.max
+            .store(self.current.load(Ordering::SeqCst), Ordering::SeqCst);
+    }
+}
+
+/// A request is in-flight as long as its `InFlightGuard` is alive.
+pub(crate) struct InFlightGuard(Arc<InFlightCounter>);
+
+impl Drop for InFlightGuard {
+    fn drop(&mut self) {
+        if self.0.current.fetch_sub(1, Ordering::SeqCst) == 1 {
+            self.0.idle.notify_waiters();
+        }
+    }
+}
@@ -0,0 +1,54 @@
+use crate::{Request, ResponseTemplate};
+use http::StatusCode;
+use std::fmt;
+use std::time::{Instant, SystemTime};
+
+/// An entry in the journal of requests received by a [`MockServer`].
+///
+/// On top of the [`Request`] itself, it records when the request arrived, which [`Mock`] (if any)
+/// matched it and the status code of the response that was returned to the client.
+///
+/// Retrieve the journal using [`MockServer::request_journal`].
+///
+/// [`Mock`]: crate::Mock
+/// [`MockServer`]: crate::MockServer
+/// [`MockServer::request_journal`]: crate::MockServer::request_journal
+#[derive(Debug, Clone)]
+pub struct JournalEntry {
+    /// The incoming request.
+    pub request: Request,
+    /// When the request was received by the [`MockServer`](crate::MockServer).
+    ///
+    /// Subtract the `received_at` of two entries to get the time elapsed between the two
+    /// requests.
+    pub received_at: Instant,
+    /// The [`Mock`](crate::Mock) that matched the request.
+    /// `None` if no mock matched.
+    pub matched_mock: Option<MatchedMock>,
+    /// The status code of the response returned to the client.
+    pub response_status: StatusCode,
+    /// When the request was received, as wall-clock time.
+    pub(crate) received_at_wall: SystemTime,
+    /// The response returned to the client.
+    pub(crate) response: ResponseTemplate,
+}
+
+/// Identifies the [`Mock`](crate::Mock) that matched an incoming request.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MatchedMock {
+    /// The name assigned to the mock using [`Mock::named`](crate::Mock::named), if any.
+    pub name: Option<String>,
+    /// The order in which the mock was mounted on the [`MockServer`](crate::MockServer).
+    ///
+    /// E.g. `0` if it is the first mock that was mounted, `1` if it is the second, etc.
+    pub position: usize,
+}
+
+impl fmt::Display for MatchedMock {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.name {
+            Some(name) => write!(f, "{}", name),
+            None => write!(f, "Mock #{}", self.position),
+        }
+    }
+}
@@ -0,0 +1,244 @@
+//! A minimal subset of [JSONPath](https://goessner.net/articles/JsonPath/), to point at
+//! fields of a JSON document in matchers.
+//!
+//! Supported: the root (`$`), children by name (`.name`, `['name']`), array elements by
+//! index (`[0]`), wildcards (`.*`, `[*]`) and recursive descent (`..name`, `..*`).
+//! Filters, slices and unions are not.
+use serde_json::Value;
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct JsonPath {
+    steps: Vec<Step>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Step {
+    selector: Selector,
+    /// `true` for `..`: the selector applies at any depth.
+    recursive: bool,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Selector {
+    Key(String),
+    Index(usize),
+    Wildcard,
+}
+
+impl JsonPath {
+    /// Parse a JSONPath expression.
+    ///
+    /// It panics if `path` is not a valid expression, or relies on unsupported features.
+    pub(crate) fn parse(path: &str) -> Self {
+        let invalid =
+            |reason: &str| -> ! { panic!("`{}` is not a supported JSONPath: {}.", path, reason) };
+        let mut rest = path
+            .strip_prefix('$')
+            .unwrap_or_else(|| invalid("it must start with `$`"));
+        let mut steps = Vec::new();
+        while !rest.is_empty() {
+            let recursive = rest.starts_with("..");
+            if recursive {
+                rest = &rest[2..];
+            } else if let Some(tail) = rest.strip_prefix('.') {
+                rest = tail;
+            } else if !rest.starts_with('[') {
+                invalid("expected `.` or `[`");
+            }
+
+            let selector = if let Some(tail) = rest.strip_prefix('[') {
+                let end = tail.find(']').unwrap_or_else(|| invalid("unclosed `[`"));
+                rest = &tail[end + 1..];
+                let inner = tail[..end].trim();
+                if inner == "*" {
+                    Selector::Wildcard
+                } else if let Some(key) = unquote(inner) {
+                    Selector::Key(key.to_owned())
+                } else {
+                    let index = inner.parse().unwrap_or_else(|_| {
+                        invalid("brackets must contain a quoted name, an index or `*`")
+                    });
+                    Selector::Index(index)
+                }
+            } else {
+                let end = rest.find(['.', '[']).unwrap_or(rest.len());
+                let name = &rest[..end];
+                rest = &rest[end..];
+                match name {
+                    "" => invalid("empty field name"),
+                    "*" => Selector::Wildcard,
+                    name => Selector::Key(name.to_owned()),
+                }
+            };
+            steps.push(Step {
+                selector,
+                recursive,
+            });
+        }
+        Self { steps }
+    }
+
+    /// Remove the fields selected by this path from `value`.
+    ///
+    /// Array elements are replaced by `null` rather than removed, to leave the position of
+    /// their siblings untouched. The root (`$`) is replaced by `null` as well.
+    pub(crate) fn mask(&self, value: &mut Value) {
+        if self.steps.is_empty() {
+            *value = Value::Null;
+        } else {
+            mask(value, &self.steps);
+        }
+    }
+
+    /// The values selected by this path in `value`, in document order.
+    pub(crate) fn select<'a>(&self, value: &'a Value) -> Vec<&'a Value> {
+        let mut selected = Vec::new();
+        select(value, &self.steps, &mut selected);
+        selected
+    }
+}
+
+fn unquote(s: &str) -> Option<&str> {
+    ['\'', '"']
+        .iter()
+        .find_map(|quote| s.strip_prefix(*quote).and_then(|s| s.strip_suffix(*quote)))
+}
+
+/// The children of `value` selected by `selector`.
+fn children<'a>(value: &'a Value, selector: &Selector) -> Vec<&'a Value> {
+    match (value, selector) {
+        (Value::Object(map), Selector::Key(key)) => map.get(key).into_iter().collect(),
+        (Value::Array(array), Selector::Index(index)) => array.get(*index).into_iter().collect(),
+        (Value::Object(map), Selector::Wildcard) => map.values().collect(),
+        (Value::Array(array), Selector::Wildcard) => array.iter().collect(),
+        _ => vec![],
+    }
+}
+
+/// The children of `value` selected by `selector`.
+fn children_mut<'a>(value: &'a mut Value, selector: &Selector) -> Vec<&'a mut Value> {
+    match (value, selector) {
+        (Value::Object(map), Selector::Key(key)) => map.get_mut(key).into_iter().collect(),
+        (Value::Array(array), Selector::Index(index)) => {
+            array.get_mut(*index).into_iter().collect()
+        }
+        (Value::Object(map), Selector::Wildcard) => map.values_mut().collect(),
+        (Value::Array(array), Selector::Wildcard) => array.iter_mut().collect(),
+        _ => vec![],
+    }
+}
+
+fn all_children_mut(value: &mut Value) -> Vec<&mut Value> {
+    children_mut(value, &Selector::Wildcard)
+}
+
+fn mask(value: &mut Value, steps: &[Step]) {
+    let (step, rest) = match steps.split_first() {
+        Some(split) => split,
+        None => return,
+    };
+    if step.recursive {
+        for child in all_children_mut(value) {
+            mask(child, steps);
+        }
+    }
+    if rest.is_empty() {
+        match (value, &step.selector) {
+            (Value::Object(map), Selector::Key(key)) => {
+                map.remove(key);
+            }
+            (Value::Array(array), Selector::Index(index)) => {
+                if let Some(element) = array.get_mut(*index) {
+                    *element = Value::Null;
+                }
+            }
+            (Value::Object(map), Selector::Wildcard) => map.clear(),
+            (Value::Array(array), Selector::Wildcard) => {
+                array.iter_mut().for_each(|element| *element = Value::Null)
+            }
+            _ => {}
+        }
+    } else {
+        for child in children_mut(value, &step.selector) {
+            mask(child, rest);
+        }
+    }
+}
+
+fn select<'a>(value: &'a Value, steps: &[Step], selected: &mut Vec<&'a Value>) {
+    let (step, rest) = match steps.split_first() {
+        Some(split) => split,
+        None => return selected.push(value),
+    };
+    for child in children(value, &step.selector) {
+        select(child, rest, selected);
+    }
+    if step.recursive {
+        for child in children(value, &Selector::Wildcard) {
+            select(child, steps, selected);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::JsonPath;
+    use serde_json::json;
+
+    #[test]
+    fn fields_are_masked() {
+        let mut value = json!({
+            "id": 1,
+            "meta": { "created_at": "now", "author": "me" },
+            "items": [{ "id": 2, "name": "a" }, { "id": 3, "name": "b" }]
+        });
+
+        JsonPath::parse("$.meta.created_at").mask(&mut value);
+        JsonPath::parse("$['items'][*].id").mask(&mut value);
+        JsonPath::parse("$.items[1]").mask(&mut value);
+
+        assert_eq!(
+            value,
+            json!({
+                "id": 1,
+                "meta": { "author": "me" },
+                "items": [{ "name": "a" }, null]
+            })
+        );
+    }
+
+    #[test]
+    fn recursive_descent_masks_at_any_depth() {
+        let mut value = json!({ "id": 1, "child": { "id": 2, "items": [{ "id": 3 }] } });
+
+        JsonPath::parse("$..id").mask(&mut value);
+
+        assert_eq!(value, json!({ "child": { "items": [{}] } }));
+    }
+
+    #[test]
+    fn values_are_selected() {
+        let value = json!({ "items": [{ "id": 2 }, { "id": 3, "child": { "id": 4 } }] });
+
+        let ids = JsonPath::parse("$.items[*].id").select(&value);
+        assert_eq!(ids, [&json!(2), &json!(3)]);
+
+        let ids = JsonPath::parse("$..id").select(&value);
+        assert_eq!(ids, [&json!(2), &json!(3), &json!(4)]);
+
+        assert!(JsonPath::parse("$.missing[0]").select(&value).is_empty());
+        assert_eq!(JsonPath::parse("$").select(&value), [&value]);
+    }
+
+    #[test]
+    #[should_panic(expected = "`items` is not a supported JSONPath: it must start with `$`.")]
+    fn paths_must_start_at_the_root() {
+        JsonPath::parse("items");
+    }
+
+    #[test]
+    #[should_panic(expected = "unclosed `[`")]
+    fn brackets_must_be_closed() {
+        JsonPath::parse("$.items[0");
+    }
+}
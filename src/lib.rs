@@ -98,6 +98,15 @@
 //! futures runtimes.  
 //! If you encounter any compatibility bug, please open an issue on our [GitHub repository].
 //!
+//! ## Diagnostics
+//!
+//! `wiremock` is instrumented using [`tracing`](https://docs.rs/tracing): every incoming request
+//! is handled within a `wiremock.request` span, recording its method and path, the mock that
+//! matched it (if any) and the status of the response. Failed verifications are reported as
+//! `ERROR` events.  
+//! Install a subscriber (e.g. `tracing-subscriber`) in your tests to see what the mock server is
+//! doing. If you are using `log` instead, events are forwarded to it when no subscriber is set.
+//!
 //! ## Efficiency
 //!
 //! `wiremock` maintains a pool of mock servers in the background to minimise the number of
@@ -106,6 +115,36 @@
 //!
 //! The pool is designed to be invisible: it makes your life easier and your tests faster. If you
 //! end up having to worry about it, it's a bug: open an issue!
+//! Massive test suites or constrained CI agents can tune it using the [`pool`] module.
+//!
+//! ## Feature flags
+//!
+//! Ready-made fakes of commonly used services live in the [`fakes`] module, each behind its own
+//! (disabled by default) feature flag - except for the paginated collection fake
+//! ([`fakes::paginated`]), which is always available:
+//! - `oauth2`: a fake OAuth2 / OpenID Connect identity provider, issuing signed JWTs.
+//! - `aws`: helpers to build S3 responses, as well as a matcher for AWS SigV4-signed requests
+//!   ([`matchers::aws_sigv4_valid`]).
+//!
+//! The `debug-ui` feature flag adds a web page to inspect a running [`MockServer`] and pause its
+//! mocks - see `MockServerBuilder::enable_debug_ui`, and the `pact` feature flag turns the
+//! traffic received by a [`MockServer`] into a Pact contract and back - see
+//! `MockServer::write_pact` and `MockServer::mount_pact`.
+//!
+//! The `tls` feature flag lets a [`MockServer`] serve its mocks over HTTPS - see the [`tls`]
+//! module.
+//!
+//! The `protobuf` feature flag adds matchers for protobuf-encoded request bodies, decoded using
+//! [`prost`](https://docs.rs/prost) - see `matchers::body_protobuf`. Similarly, the `msgpack` and
+//! `cbor` feature flags add matchers and response bodies for APIs speaking MessagePack or CBOR -
+//! see `matchers::body_msgpack`, `matchers::body_cbor`, `ResponseTemplate::set_body_msgpack`
+//! and `ResponseTemplate::set_body_cbor`.
+//!
+//! The `async-std` feature flag lets a [`MockServer`] run on the `async-std` executor it is
+//! started from - see `MockServerBuilder::run_on_current_runtime`.
+//!
+//! The `yaml` feature flag lets `MockServer::load_fixture_dir` load fixture files written in
+//! YAML, next to JSON ones.
 //!
 //! ## Prior art
 //!
@@ -144,19 +183,70 @@
 //! [`httpmock`]: https://docs.rs/httpmock/
 //! [`async_std`]: https://docs.rs/async-std/
 //! [`tokio`]: https://docs.rs/tokio/
+mod auth_challenge;
+pub mod chaos;
+mod clock;
+mod date;
+#[cfg(feature = "debug-ui")]
+mod debug_ui;
+mod deterministic;
+mod events;
+pub mod fakes;
+mod fixtures;
+pub mod har;
 pub mod http;
+mod in_flight;
+mod journal;
+mod json_path;
+mod lint;
+mod long_poll;
 pub mod matchers;
+mod metrics;
 mod mock;
 mod mock_server;
 mod mock_set;
 mod mounted_mock;
+mod ntlm;
+#[cfg(feature = "pact")]
+mod pact;
+mod random;
+mod range;
+mod rate_limit;
+mod redirect;
 mod request;
 mod respond;
+mod response_body;
+mod response_sequence;
 mod response_template;
+mod session;
+mod stateful_resource;
+pub mod stub;
+mod templating;
+pub mod tls;
 mod verification;
+mod webhook;
 
-pub use mock::{Match, Mock, MockBuilder, Times};
-pub use mock_server::{MockGuard, MockServer, MockServerBuilder};
-pub use request::Request;
-pub use respond::Respond;
-pub use response_template::ResponseTemplate;
+pub use auth_challenge::AuthChallenge;
+pub use clock::VirtualClock;
+pub use deterministic::DeterministicByRequest;
+pub use events::ServerEvent;
+pub use fixtures::FixtureWatcher;
+pub use journal::{JournalEntry, MatchedMock};
+pub use lint::MockLint;
+pub use long_poll::{respond_when, LongPoll};
+pub use metrics::MockMetrics;
+pub use mock::{Match, MatchExt, Mock, MockBuilder, Times};
+pub use mock_server::pool;
+pub use mock_server::{
+    GroupGuard, MockGuard, MockServer, MockServerBuilder, RequestAssertions, ShutdownError,
+    VirtualHost,
+};
+pub use random::Random;
+pub use redirect::RedirectChain;
+pub use request::{Request, RequestBuilder};
+pub use respond::{AsyncRespond, Respond, ResponseContext};
+pub use response_sequence::ResponseSequence;
+pub use response_template::{ResponseTemplate, StreamReset};
+pub use session::SessionKey;
+pub use stateful_resource::StatefulResource;
+pub use webhook::WebhookTrigger;
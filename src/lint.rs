@@ -0,0 +1,115 @@
+//! Static analysis of the mocks mounted on a [`MockServer`](crate::MockServer) - see
+//! [`MockServer::lint_mocks`](crate::MockServer::lint_mocks).
+use crate::matchers::{
+    AnyMatcher, BasicAuthMatcher, BearerTokenMatcher, BodyContainsMatcher, BodyExactMatcher,
+    BodyPartialJsonMatcher, HeaderExactMatcher, HeaderExistsMatcher, HeaderRegexMatcher,
+    HostMatcher, MethodExactMatcher, PathExactMatcher, PathRegexMatcher, PathTemplateMatcher,
+    QueryParamExactMatcher, QueryParamIsMissingMatcher,
+};
+use crate::{Match, MatchedMock};
+use std::any::{Any, TypeId};
+use std::fmt;
+
+/// A problem found by [`MockServer::lint_mocks`](crate::MockServer::lint_mocks) in the mocks
+/// mounted on a [`MockServer`](crate::MockServer).
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum MockLint {
+    /// `mock` can never match a request: `shadowed_by` is tried first, and matches all the
+    /// requests `mock` would match.
+    Unreachable {
+        /// The mock that can never match.
+        mock: MatchedMock,
+        /// The mock that swallows its requests.
+        shadowed_by: MatchedMock,
+    },
+}
+
+impl fmt::Display for MockLint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MockLint::Unreachable { mock, shadowed_by } => write!(
+                f,
+                "{} can never match: {} is tried first and matches all the requests it would match",
+                mock, shadowed_by
+            ),
+        }
+    }
+}
+
+/// What can be known of a matcher without running it.
+///
+/// It doubles as a description of the matcher in the debug UI.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum Fingerprint {
+    /// The matcher matches all requests.
+    Any,
+    /// A built-in matcher of the given type and configuration: two matchers with the same
+    /// fingerprint match the same requests.
+    Exact(TypeId, String),
+}
+
+/// The fingerprint of `matcher`, if it is one of the built-in matchers we know how to compare.
+///
+/// Custom matchers - e.g. closures - are opaque: they get no fingerprint.
+pub(crate) fn fingerprint<M: Match + 'static>(matcher: &M) -> Option<Fingerprint> {
+    let matcher = matcher as &dyn Any;
+    if matcher.is::<AnyMatcher>() {
+        return Some(Fingerprint::Any);
+    }
+    macro_rules! exact {
+        ($($matcher:ty),* $(,)?) => {
+            $(
+                if let Some(m) = matcher.downcast_ref::<$matcher>() {
+                    return Some(Fingerprint::Exact(TypeId::of::<$matcher>(), format!("{:?}", m)));
+                }
+            )*
+        };
+    }
+    exact!(
+        MethodExactMatcher,
+        PathExactMatcher,
+        PathRegexMatcher,
+        PathTemplateMatcher,
+        HostMatcher,
+        HeaderExactMatcher,
+        HeaderExistsMatcher,
+        HeaderRegexMatcher,
+        QueryParamExactMatcher,
+        QueryParamIsMissingMatcher,
+        BodyExactMatcher,
+        BodyContainsMatcher,
+        BodyPartialJsonMatcher,
+        BasicAuthMatcher,
+        BearerTokenMatcher,
+    );
+    None
+}
+
+impl fmt::Display for Fingerprint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Fingerprint::Any => write!(f, "AnyMatcher"),
+            Fingerprint::Exact(_, description) => write!(f, "{}", description),
+        }
+    }
+}
+
+/// Whether two mocks with matchers `a` and `b` match exactly the same requests, as far as we
+/// can tell.
+pub(crate) fn equivalent(a: &[Option<Fingerprint>], b: &[Option<Fingerprint>]) -> bool {
+    subsumes(a, b) && subsumes(b, a)
+}
+
+/// Whether a mock with matchers `earlier` matches all the requests matched by a mock with
+/// matchers `later`: it is the case if each of the conditions of `earlier` is also a condition
+/// of `later`.
+///
+/// `None` stands for an opaque matcher: it is never assumed to be implied by anything.
+pub(crate) fn subsumes(earlier: &[Option<Fingerprint>], later: &[Option<Fingerprint>]) -> bool {
+    earlier.iter().all(|fingerprint| match fingerprint {
+        Some(Fingerprint::Any) => true,
+        Some(fingerprint) => later.contains(&Some(fingerprint.clone())),
+        None => false,
+    })
+}
@@ -0,0 +1,97 @@
+use crate::{AsyncRespond, Request, ResponseTemplate};
+use async_trait::async_trait;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::Notify;
+
+/// Hold each matching request until `trigger` fires, then respond with `response` - to test
+/// long-polling clients deterministically.
+///
+/// Pass the returned [`LongPoll`] to
+/// [`MockBuilder::respond_with_async`](crate::MockBuilder::respond_with_async).
+/// Each request waits for its own notification:
+/// - [`Notify::notify_one`] releases the request that has been waiting the longest - or, if
+///   none is waiting, the next one to arrive. It never misses a request: prefer it to keep
+///   your tests deterministic;
+/// - [`Notify::notify_waiters`] releases all the requests that are waiting. A request that
+///   has just been received might not be waiting yet, and would be missed.
+///
+/// Requests wait forever unless a timeout is set using [`LongPoll::or_timeout`].
+///
+/// ### Example:
+/// ```rust
+/// use wiremock::{respond_when, MockServer, Mock, ResponseTemplate};
+/// use wiremock::matchers::path;
+/// use std::sync::Arc;
+/// use std::time::Duration;
+/// use tokio::sync::Notify;
+///
+/// #[tokio::main]
+/// async fn main() {
+///     // Arrange
+///     let mock_server = MockServer::start().await;
+///     let new_event = Arc::new(Notify::new());
+///     Mock::given(path("/events"))
+///         .respond_with_async(
+///             respond_when(new_event.clone(), ResponseTemplate::new(200).set_body_string("ping"))
+///                 .or_timeout(Duration::from_secs(30), ResponseTemplate::new(204)),
+///         )
+///         .mount(&mock_server)
+///         .await;
+///
+///     // Act - the poll is pending until the event fires
+///     let poll = tokio::spawn(reqwest::get(format!("{}/events", mock_server.uri())));
+///     new_event.notify_one();
+///     let response = poll.await.unwrap().unwrap();
+///
+///     // Assert
+///     assert_eq!(response.status(), 200);
+///     assert_eq!(response.text().await.unwrap(), "ping");
+/// }
+/// ```
+pub fn respond_when(trigger: Arc<Notify>, response: ResponseTemplate) -> LongPoll {
+    LongPoll {
+        trigger,
+        response,
+        timeout: None,
+    }
+}
+
+/// A responder holding requests until a trigger fires - see [`respond_when`].
+#[derive(Debug, Clone)]
+pub struct LongPoll {
+    trigger: Arc<Notify>,
+    response: ResponseTemplate,
+    timeout: Option<(Duration, ResponseTemplate)>,
+}
+
+impl LongPoll {
+    /// Respond with `response` to the requests that have been waiting for longer than
+    /// `timeout` - e.g. an empty `204 No Content` response, telling the client to poll again.
+    ///
+    /// The timeout is measured using the wall clock, from the moment the request matched the
+    /// mock.
+    pub fn or_timeout(mut self, timeout: Duration, response: ResponseTemplate) -> Self {
+        self.timeout = Some((timeout, response));
+        self
+    }
+}
+
+#[async_trait]
+impl AsyncRespond for LongPoll {
+    async fn respond(&self, _request: &Request) -> ResponseTemplate {
+        let fired = self.trigger.notified();
+        match &self.timeout {
+            None => {
+                fired.await;
+                self.response.clone()
+            }
+            Some((timeout, timeout_response)) => {
+                match tokio::time::timeout(*timeout, fired).await {
+                    Ok(()) => self.response.clone(),
+                    Err(_) => timeout_response.clone(),
+                }
+            }
+        }
+    }
+}
@@ -7,18 +7,44 @@
 //! as input automatically implement [`Match`] and can be used where a matcher is expected.
 //!
 //! Check [`Match`]'s documentation for examples.
+use crate::date::parse_http_date;
+use crate::json_path::JsonPath;
+use crate::range::ByteRange;
+use crate::tls::TlsVersion;
 use crate::{Match, Request};
 use assert_json_diff::{assert_json_matches_no_panic, CompareMode};
 use base64::prelude::{Engine as _, BASE64_STANDARD};
 use http::{HeaderName, HeaderValue, Method};
-use log::debug;
 use regex::Regex;
 use serde::Serialize;
 use serde_json::Value;
+use std::collections::HashMap;
 use std::convert::TryInto;
+use std::ops::{Bound, RangeBounds};
 use std::str;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tracing::debug;
 use url::Url;
 
+#[cfg(feature = "aws")]
+mod aws;
+#[cfg(feature = "aws")]
+pub use aws::{aws_sigv4_valid, AwsCredentials, AwsSigV4Matcher};
+#[cfg(feature = "cbor")]
+mod cbor;
+#[cfg(feature = "cbor")]
+pub use cbor::{body_cbor, BodyCborMatcher};
+#[cfg(feature = "msgpack")]
+mod msgpack;
+#[cfg(feature = "msgpack")]
+pub use msgpack::{body_msgpack, BodyMsgpackMatcher};
+#[cfg(feature = "protobuf")]
+mod protobuf;
+#[cfg(feature = "protobuf")]
+pub use protobuf::{
+    body_protobuf, body_protobuf_partial, BodyProtobufMatcher, BodyProtobufPartialMatcher,
+};
+
 /// Implement the `Match` trait for all closures, out of the box,
 /// if their signature is compatible.
 impl<F> Match for F
@@ -32,6 +58,51 @@ where
     }
 }
 
+/// Match if both matchers match - see [`MatchExt::and`](crate::MatchExt::and).
+#[derive(Debug)]
+pub struct AndMatcher<A, B>(pub(crate) A, pub(crate) B);
+
+impl<A: Match, B: Match> Match for AndMatcher<A, B> {
+    fn matches(&self, request: &Request) -> bool {
+        self.0.matches(request) && self.1.matches(request)
+    }
+
+    fn capture_path_params(&self, request: &Request, params: &mut HashMap<String, String>) {
+        self.0.capture_path_params(request, params);
+        self.1.capture_path_params(request, params);
+    }
+}
+
+/// Match if either matcher matches - see [`MatchExt::or`](crate::MatchExt::or).
+#[derive(Debug)]
+pub struct OrMatcher<A, B>(pub(crate) A, pub(crate) B);
+
+impl<A: Match, B: Match> Match for OrMatcher<A, B> {
+    fn matches(&self, request: &Request) -> bool {
+        self.0.matches(request) || self.1.matches(request)
+    }
+
+    /// Only the matcher that matched captures path parameters - the first one, if both did.
+    fn capture_path_params(&self, request: &Request, params: &mut HashMap<String, String>) {
+        if self.0.matches(request) {
+            self.0.capture_path_params(request, params);
+        } else {
+            self.1.capture_path_params(request, params);
+        }
+    }
+}
+
+/// Match if the inner matcher does not match - see [`MatchExt::not`](crate::MatchExt::not).
+#[derive(Debug)]
+pub struct NotMatcher<M>(pub(crate) M);
+
+// The inner matcher did not match: it has no path parameters to capture.
+impl<M: Match> Match for NotMatcher<M> {
+    fn matches(&self, request: &Request) -> bool {
+        !self.0.matches(request)
+    }
+}
+
 #[derive(Debug)]
 /// Match **exactly** the method of a request.
 ///
@@ -193,7 +264,51 @@ impl Match for AnyMatcher {
 ///     assert_eq!(status, 200);
 /// }
 /// ```
-pub struct PathExactMatcher(String);
+///
+/// ### Example:
+///
+/// HTTP clients and frameworks do not always agree on how to normalize paths.
+/// Use [`PathExactMatcher::case_insensitive`] (or its shorthand, [`path_ci`]) and
+/// [`PathExactMatcher::ignore_trailing_slash`] to make the matcher more lenient:
+///
+/// ```rust
+/// use wiremock::{MockServer, Mock, ResponseTemplate};
+/// use wiremock::matchers::{path, path_ci};
+///
+/// #[async_std::main]
+/// async fn main() {
+///     // Arrange
+///     let mock_server = MockServer::start().await;
+///
+///     Mock::given(path_ci("/Hello"))
+///         .respond_with(ResponseTemplate::new(200))
+///         .mount(&mock_server)
+///         .await;
+///     Mock::given(path("/world").ignore_trailing_slash())
+///         .respond_with(ResponseTemplate::new(200))
+///         .mount(&mock_server)
+///         .await;
+///
+///     // Act
+///     let hello = surf::get(format!("{}/hello", &mock_server.uri()))
+///         .await
+///         .unwrap()
+///         .status();
+///     let world = surf::get(format!("{}/world/", &mock_server.uri()))
+///         .await
+///         .unwrap()
+///         .status();
+///
+///     // Assert
+///     assert_eq!(hello, 200);
+///     assert_eq!(world, 200);
+/// }
+/// ```
+pub struct PathExactMatcher {
+    path: String,
+    case_insensitive: bool,
+    ignore_trailing_slash: bool,
+}
 
 /// Shorthand for [`PathExactMatcher::new`].
 pub fn path<T>(path: T) -> PathExactMatcher
@@ -203,6 +318,14 @@ where
     PathExactMatcher::new(path)
 }
 
+/// Shorthand for [`PathExactMatcher::new`] followed by [`PathExactMatcher::case_insensitive`].
+pub fn path_ci<T>(path: T) -> PathExactMatcher
+where
+    T: Into<String>,
+{
+    PathExactMatcher::new(path).case_insensitive()
+}
+
 impl PathExactMatcher {
     pub fn new<T: Into<String>>(path: T) -> Self {
         let path = path.into();
@@ -218,17 +341,49 @@ impl PathExactMatcher {
         }
 
         // Prepend "/" to the path if missing.
-        if path.starts_with('/') {
-            Self(path)
+        let path = if path.starts_with('/') {
+            path
+        } else {
+            format!("/{}", path)
+        };
+        Self {
+            path,
+            case_insensitive: false,
+            ignore_trailing_slash: false,
+        }
+    }
+
+    /// Compare paths ignoring ASCII case - e.g. `/Hello` matches `/hello`.
+    pub fn case_insensitive(mut self) -> Self {
+        self.case_insensitive = true;
+        self
+    }
+
+    /// Ignore a trailing `/` when comparing paths - e.g. `/hello` matches `/hello/`
+    /// and vice versa.
+    pub fn ignore_trailing_slash(mut self) -> Self {
+        self.ignore_trailing_slash = true;
+        self
+    }
+
+    fn normalize<'a>(&self, path: &'a str) -> &'a str {
+        if self.ignore_trailing_slash && path.len() > 1 {
+            path.strip_suffix('/').unwrap_or(path)
         } else {
-            Self(format!("/{}", path))
+            path
         }
     }
 }
 
 impl Match for PathExactMatcher {
     fn matches(&self, request: &Request) -> bool {
-        request.url.path() == self.0
+        let expected = self.normalize(&self.path);
+        let actual = self.normalize(request.url.path());
+        if self.case_insensitive {
+            actual.eq_ignore_ascii_case(expected)
+        } else {
+            actual == expected
+        }
     }
 }
 
@@ -310,6 +465,201 @@ impl Match for PathRegexMatcher {
     }
 }
 
+#[derive(Debug, Clone)]
+/// Match the path of a request against a template with named parameters -
+/// e.g. `/users/{user_id}/posts/{post_id}`.
+///
+/// Each `{name}` placeholder matches exactly one non-empty path segment, while the rest
+/// of the template must match exactly.
+///
+/// The values captured by the placeholders are passed to the responder of the [`Mock`](crate::Mock)
+/// as [`ResponseContext::path_params`](crate::ResponseContext::path_params), so you can build
+/// dynamic responses without writing regular expressions. They are not percent-decoded.
+/// They are captured even if the template is combined with other matchers - e.g. with
+/// [`MatchExt::or`](crate::MatchExt::or) or in a tuple - as long as it matched the request;
+/// if several templates match, the placeholders of the latter ones win on name clashes.
+///
+/// ### Example:
+/// ```rust
+/// use wiremock::{MockServer, Mock, Request, Respond, ResponseContext, ResponseTemplate};
+/// use wiremock::matchers::path_template;
+///
+/// struct EchoPost;
+///
+/// impl Respond for EchoPost {
+///     fn respond(&self, _request: &Request) -> ResponseTemplate {
+///         ResponseTemplate::new(200)
+///     }
+///
+///     fn respond_with_ctx(&self, _request: &Request, ctx: &ResponseContext) -> ResponseTemplate {
+///         ResponseTemplate::new(200).set_body_json(serde_json::json!({
+///             "user": ctx.path_params["user_id"],
+///             "post": ctx.path_params["post_id"],
+///         }))
+///     }
+/// }
+///
+/// #[async_std::main]
+/// async fn main() {
+///     // Arrange
+///     let mock_server = MockServer::start().await;
+///
+///     Mock::given(path_template("/users/{user_id}/posts/{post_id}"))
+///         .respond_with(EchoPost)
+///         .mount(&mock_server)
+///         .await;
+///
+///     // Act
+///     let mut response = surf::get(format!("{}/users/42/posts/7", &mock_server.uri()))
+///         .await
+///         .unwrap();
+///
+///     // Assert
+///     assert_eq!(response.status(), 200);
+///     let body: serde_json::Value = response.body_json().await.unwrap();
+///     assert_eq!(body, serde_json::json!({ "user": "42", "post": "7" }));
+/// }
+/// ```
+pub struct PathTemplateMatcher {
+    regex: Regex,
+    names: Vec<String>,
+}
+
+/// Shorthand for [`PathTemplateMatcher::new`].
+pub fn path_template<T>(template: T) -> PathTemplateMatcher
+where
+    T: AsRef<str>,
+{
+    PathTemplateMatcher::new(template)
+}
+
+impl PathTemplateMatcher {
+    /// Build a matcher for `template`.
+    ///
+    /// It panics if a placeholder is not closed, is empty or its name is used more than once.
+    pub fn new<T: AsRef<str>>(template: T) -> Self {
+        let template = template.as_ref();
+        let mut pattern = "^".to_string();
+        if !template.starts_with('/') {
+            pattern.push('/');
+        }
+        let mut names: Vec<String> = vec![];
+        let mut rest = template;
+        while let Some(start) = rest.find('{') {
+            pattern.push_str(&regex::escape(&rest[..start]));
+            let end = rest[start..]
+                .find('}')
+                .unwrap_or_else(|| panic!("Invalid path template `{}`: unclosed `{{`", template))
+                + start;
+            let name = &rest[start + 1..end];
+            if name.is_empty() {
+                panic!("Invalid path template `{}`: empty placeholder", template);
+            }
+            if names.iter().any(|n| n == name) {
+                panic!(
+                    "Invalid path template `{}`: the placeholder `{}` is used more than once",
+                    template, name
+                );
+            }
+            names.push(name.to_string());
+            pattern.push_str("([^/]+)");
+            rest = &rest[end + 1..];
+        }
+        pattern.push_str(&regex::escape(rest));
+        pattern.push('$');
+
+        Self {
+            regex: Regex::new(&pattern).expect("Failed to create regex for path template"),
+            names,
+        }
+    }
+}
+
+impl Match for PathTemplateMatcher {
+    fn matches(&self, request: &Request) -> bool {
+        self.regex.is_match(request.url.path())
+    }
+
+    fn capture_path_params(&self, request: &Request, params: &mut HashMap<String, String>) {
+        if let Some(captures) = self.regex.captures(request.url.path()) {
+            for (name, value) in self.names.iter().zip(captures.iter().skip(1)) {
+                if let Some(value) = value {
+                    params.insert(name.clone(), value.as_str().to_string());
+                }
+            }
+        }
+    }
+}
+
+#[derive(Debug)]
+/// Match the host a request is addressed to, ignoring the port.
+///
+/// The host is taken from the request target if it is in absolute form (e.g.
+/// `GET http://api.example.com/users`), as sent by clients that use the [`MockServer`] as a
+/// forward proxy. It is taken from the `Host` header otherwise.
+/// Hosts are compared ignoring ASCII case.
+///
+/// ### Example:
+/// ```rust
+/// use wiremock::{MockServer, Mock, ResponseTemplate};
+/// use wiremock::matchers::{host, path};
+///
+/// #[tokio::main]
+/// async fn main() {
+///     // Arrange
+///     let mock_server = MockServer::start().await;
+///
+///     Mock::given(host("api.example.com"))
+///         .and(path("/users"))
+///         .respond_with(ResponseTemplate::new(200))
+///         .mount(&mock_server)
+///         .await;
+///
+///     // The client sends all its plain-HTTP requests to the mock server.
+///     let client = reqwest::Client::builder()
+///         .proxy(reqwest::Proxy::http(mock_server.uri()).unwrap())
+///         .build()
+///         .unwrap();
+///
+///     // Act
+///     let status = client
+///         .get("http://api.example.com/users")
+///         .send()
+///         .await
+///         .unwrap()
+///         .status();
+///
+///     // Assert
+///     assert_eq!(status, 200);
+/// }
+/// ```
+///
+/// [`MockServer`]: crate::MockServer
+pub struct HostMatcher(String);
+
+impl HostMatcher {
+    /// Specify the expected host, without scheme nor port - e.g. `api.example.com`.
+    pub fn new<T: Into<String>>(host: T) -> Self {
+        Self(host.into())
+    }
+}
+
+/// Shorthand for [`HostMatcher::new`].
+pub fn host<T>(host: T) -> HostMatcher
+where
+    T: Into<String>,
+{
+    HostMatcher::new(host)
+}
+
+impl Match for HostMatcher {
+    fn matches(&self, request: &Request) -> bool {
+        request
+            .host()
+            .is_some_and(|host| host.eq_ignore_ascii_case(&self.0))
+    }
+}
+
 #[derive(Debug)]
 /// Match **exactly** the header of a request.
 ///
@@ -404,186 +754,300 @@ impl Match for HeaderExactMatcher {
 }
 
 #[derive(Debug)]
-/// Match **exactly** the header name of a request. It checks that the
-/// header is present but does not validate the value.
+/// Match **exactly** a trailer of a request - see [`Request::trailers`].
+///
+/// Clients can only send trailers with a chunked body over HTTP/1.1, or over HTTP/2.
+/// It matches if any of the values of the trailer is equal to the expected one.
+///
+/// ### Example:
+/// ```rust
+/// use wiremock::{Match, Request};
+/// use wiremock::matchers::trailer;
+///
+/// let request = Request::builder()
+///     .method("POST")
+///     .body("hello")
+///     .trailer("x-checksum", "5d41402a")
+///     .build();
+///
+/// assert!(trailer("x-checksum", "5d41402a").matches(&request));
+/// assert!(!trailer("x-checksum", "00000000").matches(&request));
+/// ```
+pub struct TrailerExactMatcher(HeaderName, HeaderValue);
+
+impl TrailerExactMatcher {
+    /// Specify the name and the expected value of the trailer.
+    pub fn new<K, V>(key: K, value: V) -> Self
+    where
+        K: TryInto<HeaderName>,
+        <K as TryInto<HeaderName>>::Error: std::fmt::Debug,
+        V: TryInto<HeaderValue>,
+        <V as TryInto<HeaderValue>>::Error: std::fmt::Debug,
+    {
+        let key = key.try_into().expect("Failed to convert to trailer name.");
+        let value = value
+            .try_into()
+            .expect("Failed to convert to trailer value.");
+        Self(key, value)
+    }
+}
+
+/// Shorthand for [`TrailerExactMatcher::new`].
+pub fn trailer<K, V>(key: K, value: V) -> TrailerExactMatcher
+where
+    K: TryInto<HeaderName>,
+    <K as TryInto<HeaderName>>::Error: std::fmt::Debug,
+    V: TryInto<HeaderValue>,
+    <V as TryInto<HeaderValue>>::Error: std::fmt::Debug,
+{
+    TrailerExactMatcher::new(key, value)
+}
+
+impl Match for TrailerExactMatcher {
+    fn matches(&self, request: &Request) -> bool {
+        request
+            .trailers()
+            .get_all(&self.0)
+            .iter()
+            .any(|value| value == self.1)
+    }
+}
+
+#[derive(Debug)]
+/// Match requests asking for a specific range of bytes with their `Range` header - e.g.
+/// `Range: bytes=500-999`.
+///
+/// Use it to check from which offset your client resumes a download, together with
+/// [`ResponseTemplate::respond_with_range_of`](crate::ResponseTemplate::respond_with_range_of).
+/// Requests for more than one range, or for the last bytes of a file (e.g. `bytes=-500`),
+/// never match.
 ///
 /// ### Example:
 /// ```rust
 /// use wiremock::{MockServer, Mock, ResponseTemplate};
-/// use wiremock::matchers::header;
+/// use wiremock::matchers::range_header;
 ///
-/// #[async_std::main]
+/// #[tokio::main]
 /// async fn main() {
 ///     // Arrange
-///     use wiremock::matchers::header_exists;
 ///     let mock_server = MockServer::start().await;
 ///
-///     Mock::given(header_exists("custom"))
-///         .respond_with(ResponseTemplate::new(200))
+///     Mock::given(range_header(500..))
+///         .respond_with(ResponseTemplate::new(206))
 ///         .mount(&mock_server)
 ///         .await;
 ///
 ///     // Act
-///     let status = surf::get(&mock_server.uri())
-///         .header("custom", "header")
+///     let status = reqwest::Client::new()
+///         .get(&mock_server.uri())
+///         .header("Range", "bytes=500-")
+///         .send()
 ///         .await
 ///         .unwrap()
 ///         .status();
 ///
 ///     // Assert
-///     assert_eq!(status, 200);
+///     assert_eq!(status, 206);
 /// }
 /// ```
-pub struct HeaderExistsMatcher(HeaderName);
+pub struct RangeHeaderMatcher {
+    first: u64,
+    last: Option<u64>,
+}
 
-/// Shorthand for [`HeaderExistsMatcher::new`].
-pub fn header_exists<K>(key: K) -> HeaderExistsMatcher
+/// Match requests asking for the `range` of bytes - e.g. `0..500` (or `0..=499`) matches
+/// `bytes=0-499`, `500..` matches `bytes=500-` - see [`RangeHeaderMatcher`].
+///
+/// It panics if `range` is empty.
+pub fn range_header<R>(range: R) -> RangeHeaderMatcher
 where
-    K: TryInto<HeaderName>,
-    <K as TryInto<HeaderName>>::Error: std::fmt::Debug,
+    R: RangeBounds<u64>,
 {
-    HeaderExistsMatcher::new(key)
-}
-
-impl HeaderExistsMatcher {
-    pub fn new<K>(key: K) -> Self
-    where
-        K: TryInto<HeaderName>,
-        <K as TryInto<HeaderName>>::Error: std::fmt::Debug,
-    {
-        let key = key.try_into().expect("Failed to convert to header name.");
-        Self(key)
-    }
+    let first = match range.start_bound() {
+        Bound::Included(first) => *first,
+        Bound::Excluded(first) => first + 1,
+        Bound::Unbounded => 0,
+    };
+    let last = match range.end_bound() {
+        Bound::Included(last) => Some(*last),
+        Bound::Excluded(end) => Some(end.checked_sub(1).expect("The range of bytes is empty.")),
+        Bound::Unbounded => None,
+    };
+    assert!(
+        last.is_none_or(|last| first <= last),
+        "The range of bytes is empty."
+    );
+    RangeHeaderMatcher { first, last }
 }
 
-impl Match for HeaderExistsMatcher {
+impl Match for RangeHeaderMatcher {
     fn matches(&self, request: &Request) -> bool {
-        request.headers.get(&self.0).is_some()
+        let range = request
+            .headers
+            .get(http::header::RANGE)
+            .and_then(|value| value.to_str().ok())
+            .and_then(ByteRange::parse);
+        match range {
+            Some(ByteRange::Bounded { first, last }) => {
+                first == self.first && Some(last) == self.last
+            }
+            Some(ByteRange::From { first }) => first == self.first && self.last.is_none(),
+            _ => false,
+        }
     }
 }
 
 #[derive(Debug)]
-/// Match the value of a header using a regular expression.
-/// If the header is multi-valued, all values must satisfy the regular expression.
-/// If the header is missing, the mock will not match.
+/// Match requests whose `If-None-Match` header lists an `ETag` - i.e. conditional requests
+/// revalidating a cached response.
+///
+/// `ETag`s are compared with the weak comparison function: `W/"a"` matches `"a"`. The quotes
+/// around `etag` are optional. A `*` does not match a specific `ETag`.
 ///
 /// ### Example:
 /// ```rust
 /// use wiremock::{MockServer, Mock, ResponseTemplate};
-/// use wiremock::matchers::header_regex;
+/// use wiremock::matchers::if_none_match;
 ///
-/// #[async_std::main]
+/// #[tokio::main]
 /// async fn main() {
 ///     // Arrange
 ///     let mock_server = MockServer::start().await;
 ///
-///     Mock::given(header_regex("custom", "header"))
-///         .respond_with(ResponseTemplate::new(200))
+///     Mock::given(if_none_match("v1"))
+///         .respond_with(ResponseTemplate::new(304))
 ///         .mount(&mock_server)
 ///         .await;
 ///
 ///     // Act
-///     let status = surf::get(&mock_server.uri())
-///         .header("custom", "headers are fun to match on with a regex")
+///     let status = reqwest::Client::new()
+///         .get(&mock_server.uri())
+///         .header("If-None-Match", r#""v0", W/"v1""#)
+///         .send()
 ///         .await
 ///         .unwrap()
 ///         .status();
 ///
 ///     // Assert
-///     assert_eq!(status, 200);
+///     assert_eq!(status, 304);
 /// }
 /// ```
-pub struct HeaderRegexMatcher(HeaderName, Regex);
+pub struct IfNoneMatchMatcher(String);
 
-/// Shorthand for [`HeaderRegexMatcher::new`].
-pub fn header_regex<K>(key: K, value: &str) -> HeaderRegexMatcher
+/// Match requests whose `If-None-Match` header lists `etag` - see [`IfNoneMatchMatcher`].
+pub fn if_none_match<T>(etag: T) -> IfNoneMatchMatcher
 where
-    K: TryInto<HeaderName>,
-    <K as TryInto<HeaderName>>::Error: std::fmt::Debug,
+    T: Into<String>,
 {
-    HeaderRegexMatcher::new(key, value)
+    IfNoneMatchMatcher(opaque_tag(&etag.into()).to_owned())
 }
 
-impl HeaderRegexMatcher {
-    pub fn new<K>(key: K, value: &str) -> Self
-    where
-        K: TryInto<HeaderName>,
-        <K as TryInto<HeaderName>>::Error: std::fmt::Debug,
-    {
-        let key = key.try_into().expect("Failed to convert to header name.");
-        let value_matcher = Regex::new(value).expect("Failed to create regex for value matcher");
-        Self(key, value_matcher)
-    }
+/// The value of an `ETag`, without the weakness indicator nor the quotes.
+fn opaque_tag(etag: &str) -> &str {
+    let etag = etag.trim();
+    let etag = etag.strip_prefix("W/").unwrap_or(etag);
+    etag.trim_matches('"')
 }
 
-impl Match for HeaderRegexMatcher {
+impl Match for IfNoneMatchMatcher {
     fn matches(&self, request: &Request) -> bool {
-        let mut it = request
+        request
             .headers
-            .get_all(&self.0)
+            .get_all(http::header::IF_NONE_MATCH)
             .iter()
-            .filter_map(|v| v.to_str().ok())
-            .peekable();
-        if it.peek().is_some() {
-            it.all(|v| self.1.is_match(v))
-        } else {
-            false
-        }
+            .filter_map(|value| value.to_str().ok())
+            .flat_map(|value| value.split(','))
+            .any(|etag| etag.trim() != "*" && opaque_tag(etag) == self.0)
     }
 }
 
 #[derive(Debug)]
-/// Match **exactly** the body of a request.
+/// Match requests whose `If-Modified-Since` header is a specific date - e.g. the
+/// `Last-Modified` date of a response your client cached.
 ///
-/// ### Example (string):
+/// Dates are compared with a precision of one second, the precision of HTTP dates.
+///
+/// ### Example:
 /// ```rust
 /// use wiremock::{MockServer, Mock, ResponseTemplate};
-/// use wiremock::matchers::body_string;
+/// use wiremock::matchers::if_modified_since;
+/// use std::time::{Duration, UNIX_EPOCH};
 ///
-/// #[async_std::main]
+/// #[tokio::main]
 /// async fn main() {
 ///     // Arrange
 ///     let mock_server = MockServer::start().await;
+///     let last_modified = UNIX_EPOCH + Duration::from_secs(1_709_210_096);
 ///
-///     Mock::given(body_string("hello world!"))
-///         .respond_with(ResponseTemplate::new(200))
+///     Mock::given(if_modified_since(last_modified))
+///         .respond_with(ResponseTemplate::new(304))
 ///         .mount(&mock_server)
 ///         .await;
 ///
 ///     // Act
-///     let status = surf::post(&mock_server.uri())
-///         .body("hello world!")
+///     let status = reqwest::Client::new()
+///         .get(&mock_server.uri())
+///         .header("If-Modified-Since", "Thu, 29 Feb 2024 12:34:56 GMT")
+///         .send()
 ///         .await
 ///         .unwrap()
 ///         .status();
 ///
 ///     // Assert
-///     assert_eq!(status, 200);
+///     assert_eq!(status, 304);
 /// }
 /// ```
+pub struct IfModifiedSinceMatcher(u64);
+
+/// Match requests whose `If-Modified-Since` header is `date` - see
+/// [`IfModifiedSinceMatcher`].
+pub fn if_modified_since(date: SystemTime) -> IfModifiedSinceMatcher {
+    IfModifiedSinceMatcher(unix_seconds(date))
+}
+
+fn unix_seconds(time: SystemTime) -> u64 {
+    time.duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
+}
+
+impl Match for IfModifiedSinceMatcher {
+    fn matches(&self, request: &Request) -> bool {
+        request
+            .headers
+            .get(http::header::IF_MODIFIED_SINCE)
+            .and_then(|value| value.to_str().ok())
+            .and_then(parse_http_date)
+            .is_some_and(|date| unix_seconds(date) == self.0)
+    }
+}
+
+#[derive(Debug)]
+/// Match requests whose body was framed with a `Content-Length` header equal to the
+/// expected length.
 ///
-/// ### Example (json):
+/// Use it, together with [`is_chunked`], to check how your client frames its uploads - e.g.
+/// because the real server rejects chunked requests.
+/// Over HTTP/2 clients are not required to send a `Content-Length` header.
+///
+/// ### Example:
 /// ```rust
 /// use wiremock::{MockServer, Mock, ResponseTemplate};
-/// use wiremock::matchers::body_json;
-/// use serde_json::json;
+/// use wiremock::matchers::has_content_length;
 ///
 /// #[async_std::main]
 /// async fn main() {
 ///     // Arrange
 ///     let mock_server = MockServer::start().await;
 ///
-///     let expected_body = json!({
-///         "hello": "world!"
-///     });
-///     Mock::given(body_json(&expected_body))
+///     Mock::given(has_content_length(5))
 ///         .respond_with(ResponseTemplate::new(200))
 ///         .mount(&mock_server)
 ///         .await;
 ///
 ///     // Act
 ///     let status = surf::post(&mock_server.uri())
-///         .body(expected_body)
+///         .body("hello")
 ///         .await
 ///         .unwrap()
 ///         .status();
@@ -592,184 +1056,1289 @@ impl Match for HeaderRegexMatcher {
 ///     assert_eq!(status, 200);
 /// }
 /// ```
-pub struct BodyExactMatcher(Body);
+pub struct ContentLengthMatcher(u64);
 
-#[derive(Debug)]
-enum Body {
-    Bytes(Vec<u8>),
-    Json(Value),
+/// Match requests framed with a `Content-Length` header equal to `length` - see
+/// [`ContentLengthMatcher`].
+pub fn has_content_length(length: u64) -> ContentLengthMatcher {
+    ContentLengthMatcher(length)
 }
 
-impl BodyExactMatcher {
-    /// Specify the expected body as a string.
-    pub fn string<T: Into<String>>(body: T) -> Self {
-        let body = body.into();
-        Self(Body::Bytes(body.into_bytes()))
+impl Match for ContentLengthMatcher {
+    fn matches(&self, request: &Request) -> bool {
+        request
+            .headers
+            .get(http::header::CONTENT_LENGTH)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.trim().parse::<u64>().ok())
+            == Some(self.0)
     }
+}
 
-    /// Specify the expected body as a vector of bytes.
-    pub fn bytes<T: Into<Vec<u8>>>(body: T) -> Self {
-        let body = body.into();
-        Self(Body::Bytes(body))
-    }
+#[derive(Debug)]
+/// Match requests whose body was sent with the `chunked` transfer encoding, rather than
+/// with a `Content-Length` header.
+///
+/// Chunked encoding only exists in HTTP/1.1: it never matches HTTP/2 requests.
+/// See [`has_content_length`] for the opposite framing.
+///
+/// ### Example:
+/// ```rust
+/// use wiremock::{MockServer, Mock, ResponseTemplate};
+/// use wiremock::matchers::is_chunked;
+///
+/// #[async_std::main]
+/// async fn main() {
+///     // Arrange
+///     let mock_server = MockServer::start().await;
+///
+///     // Chunked uploads are rejected, like the real server does.
+///     Mock::given(is_chunked())
+///         .respond_with(ResponseTemplate::new(411))
+///         .mount(&mock_server)
+///         .await;
+///
+///     // Act
+///     let status = surf::post(&mock_server.uri())
+///         .body("hello")
+///         .await
+///         .unwrap()
+///         .status();
+///
+///     // Assert - the body had a known length: it was not chunked
+///     assert_eq!(status, 404);
+/// }
+/// ```
+pub struct ChunkedMatcher;
 
-    /// Specify something JSON-serializable as the expected body.
-    pub fn json<T: Serialize>(body: T) -> Self {
-        let bytes = serde_json::to_vec(&body).expect("Failed to serialize JSON body");
-        Self::json_string(bytes)
-    }
+/// Match requests whose body was sent with the `chunked` transfer encoding - see
+/// [`ChunkedMatcher`].
+pub fn is_chunked() -> ChunkedMatcher {
+    ChunkedMatcher
+}
 
-    /// Specify a JSON string as the expected body.
-    pub fn json_string(body: impl AsRef<[u8]>) -> Self {
-        let body = serde_json::from_slice(body.as_ref()).expect("Failed to parse JSON string");
-        Self(Body::Json(body))
+impl Match for ChunkedMatcher {
+    fn matches(&self, request: &Request) -> bool {
+        // `chunked` must be the last transfer coding applied to the body.
+        request
+            .headers
+            .get_all(http::header::TRANSFER_ENCODING)
+            .iter()
+            .filter_map(|value| value.to_str().ok())
+            .flat_map(|value| value.split(','))
+            .last()
+            .is_some_and(|coding| coding.trim().eq_ignore_ascii_case("chunked"))
     }
 }
 
-/// Shorthand for [`BodyExactMatcher::string`].
-pub fn body_string<T>(body: T) -> BodyExactMatcher
+#[derive(Debug)]
+/// Match **exactly** the header name of a request. It checks that the
+/// header is present but does not validate the value.
+///
+/// ### Example:
+/// ```rust
+/// use wiremock::{MockServer, Mock, ResponseTemplate};
+/// use wiremock::matchers::header;
+///
+/// #[async_std::main]
+/// async fn main() {
+///     // Arrange
+///     use wiremock::matchers::header_exists;
+///     let mock_server = MockServer::start().await;
+///
+///     Mock::given(header_exists("custom"))
+///         .respond_with(ResponseTemplate::new(200))
+///         .mount(&mock_server)
+///         .await;
+///
+///     // Act
+///     let status = surf::get(&mock_server.uri())
+///         .header("custom", "header")
+///         .await
+///         .unwrap()
+///         .status();
+///
+///     // Assert
+///     assert_eq!(status, 200);
+/// }
+/// ```
+pub struct HeaderExistsMatcher(HeaderName);
+
+/// Shorthand for [`HeaderExistsMatcher::new`].
+pub fn header_exists<K>(key: K) -> HeaderExistsMatcher
 where
-    T: Into<String>,
+    K: TryInto<HeaderName>,
+    <K as TryInto<HeaderName>>::Error: std::fmt::Debug,
 {
-    BodyExactMatcher::string(body)
+    HeaderExistsMatcher::new(key)
 }
 
-/// Shorthand for [`BodyExactMatcher::bytes`].
-pub fn body_bytes<T>(body: T) -> BodyExactMatcher
-where
-    T: Into<Vec<u8>>,
-{
-    BodyExactMatcher::bytes(body)
+impl HeaderExistsMatcher {
+    pub fn new<K>(key: K) -> Self
+    where
+        K: TryInto<HeaderName>,
+        <K as TryInto<HeaderName>>::Error: std::fmt::Debug,
+    {
+        let key = key.try_into().expect("Failed to convert to header name.");
+        Self(key)
+    }
 }
 
-/// Shorthand for [`BodyExactMatcher::json`].
-pub fn body_json<T>(body: T) -> BodyExactMatcher
+impl Match for HeaderExistsMatcher {
+    fn matches(&self, request: &Request) -> bool {
+        request.headers.get(&self.0).is_some()
+    }
+}
+
+#[derive(Debug)]
+/// Match the value of a header using a regular expression.
+/// If the header is multi-valued, all values must satisfy the regular expression.
+/// If the header is missing, the mock will not match.
+///
+/// ### Example:
+/// ```rust
+/// use wiremock::{MockServer, Mock, ResponseTemplate};
+/// use wiremock::matchers::header_regex;
+///
+/// #[async_std::main]
+/// async fn main() {
+///     // Arrange
+///     let mock_server = MockServer::start().await;
+///
+///     Mock::given(header_regex("custom", "header"))
+///         .respond_with(ResponseTemplate::new(200))
+///         .mount(&mock_server)
+///         .await;
+///
+///     // Act
+///     let status = surf::get(&mock_server.uri())
+///         .header("custom", "headers are fun to match on with a regex")
+///         .await
+///         .unwrap()
+///         .status();
+///
+///     // Assert
+///     assert_eq!(status, 200);
+/// }
+/// ```
+pub struct HeaderRegexMatcher(HeaderName, Regex);
+
+/// Shorthand for [`HeaderRegexMatcher::new`].
+pub fn header_regex<K>(key: K, value: &str) -> HeaderRegexMatcher
 where
-    T: Serialize,
+    K: TryInto<HeaderName>,
+    <K as TryInto<HeaderName>>::Error: std::fmt::Debug,
 {
-    BodyExactMatcher::json(body)
+    HeaderRegexMatcher::new(key, value)
 }
 
-/// Shorthand for [`BodyExactMatcher::json_string`].
-pub fn body_json_string(body: impl AsRef<[u8]>) -> BodyExactMatcher {
-    BodyExactMatcher::json_string(body)
+impl HeaderRegexMatcher {
+    pub fn new<K>(key: K, value: &str) -> Self
+    where
+        K: TryInto<HeaderName>,
+        <K as TryInto<HeaderName>>::Error: std::fmt::Debug,
+    {
+        let key = key.try_into().expect("Failed to convert to header name.");
+        let value_matcher = Regex::new(value).expect("Failed to create regex for value matcher");
+        Self(key, value_matcher)
+    }
 }
 
-impl Match for BodyExactMatcher {
+impl Match for HeaderRegexMatcher {
     fn matches(&self, request: &Request) -> bool {
-        match &self.0 {
-            Body::Bytes(bytes) => request.body == *bytes,
-            Body::Json(json) => {
-                if let Ok(body) = serde_json::from_slice::<Value>(&request.body) {
-                    body == *json
-                } else {
-                    false
-                }
-            }
+        let mut it = request
+            .headers
+            .get_all(&self.0)
+            .iter()
+            .filter_map(|v| v.to_str().ok())
+            .peekable();
+        if it.peek().is_some() {
+            it.all(|v| self.1.is_match(v))
+        } else {
+            false
         }
     }
 }
 
+/// Iterate over the `name=value` pairs contained in all the `Cookie` headers of a request.
+///
+/// Values wrapped in double quotes are returned without the quotes.
+fn request_cookies(request: &Request) -> impl Iterator<Item = (&str, &str)> {
+    request
+        .headers
+        .get_all(http::header::COOKIE)
+        .iter()
+        .filter_map(|v| v.to_str().ok())
+        .flat_map(|v| v.split(';'))
+        .filter_map(|pair| {
+            let (name, value) = pair.trim().split_once('=')?;
+            let value = value.trim();
+            let value = value
+                .strip_prefix('"')
+                .and_then(|v| v.strip_suffix('"'))
+                .unwrap_or(value);
+            Some((name.trim(), value))
+        })
+}
+
+#[derive(Debug)]
+/// Match **exactly** the value of a cookie sent by the client in the `Cookie` header.
+///
+/// ### Example:
+/// ```rust
+/// use wiremock::{MockServer, Mock, ResponseTemplate};
+/// use wiremock::matchers::cookie;
+///
+/// #[async_std::main]
+/// async fn main() {
+///     // Arrange
+///     let mock_server = MockServer::start().await;
+///
+///     Mock::given(cookie("session", "abc"))
+///         .respond_with(ResponseTemplate::new(200))
+///         .mount(&mock_server)
+///         .await;
+///
+///     // Act
+///     let status = surf::get(&mock_server.uri())
+///         .header("Cookie", "theme=dark; session=abc")
+///         .await
+///         .unwrap()
+///         .status();
+///
+///     // Assert
+///     assert_eq!(status, 200);
+/// }
+/// ```
+pub struct CookieExactMatcher(String, String);
+
+impl CookieExactMatcher {
+    /// Specify the expected value for a cookie.
+    pub fn new<K: Into<String>, V: Into<String>>(name: K, value: V) -> Self {
+        Self(name.into(), value.into())
+    }
+}
+
+/// Shorthand for [`CookieExactMatcher::new`].
+pub fn cookie<K, V>(name: K, value: V) -> CookieExactMatcher
+where
+    K: Into<String>,
+    V: Into<String>,
+{
+    CookieExactMatcher::new(name, value)
+}
+
+impl Match for CookieExactMatcher {
+    fn matches(&self, request: &Request) -> bool {
+        request_cookies(request).any(|(name, value)| name == self.0 && value == self.1)
+    }
+}
+
+#[derive(Debug)]
+/// Match requests that send a cookie with the specified name in the `Cookie` header,
+/// regardless of its value.
+///
+/// ### Example:
+/// ```rust
+/// use wiremock::{MockServer, Mock, ResponseTemplate};
+/// use wiremock::matchers::cookie_exists;
+///
+/// #[async_std::main]
+/// async fn main() {
+///     // Arrange
+///     let mock_server = MockServer::start().await;
+///
+///     Mock::given(cookie_exists("session"))
+///         .respond_with(ResponseTemplate::new(200))
+///         .mount(&mock_server)
+///         .await;
+///
+///     // Act
+///     let status = surf::get(&mock_server.uri())
+///         .header("Cookie", "session=whatever")
+///         .await
+///         .unwrap()
+///         .status();
+///
+///     // Assert
+///     assert_eq!(status, 200);
+/// }
+/// ```
+pub struct CookieExistsMatcher(String);
+
+impl CookieExistsMatcher {
+    /// Specify the name of the cookie that is expected to be present.
+    pub fn new<K: Into<String>>(name: K) -> Self {
+        Self(name.into())
+    }
+}
+
+/// Shorthand for [`CookieExistsMatcher::new`].
+pub fn cookie_exists<K>(name: K) -> CookieExistsMatcher
+where
+    K: Into<String>,
+{
+    CookieExistsMatcher::new(name)
+}
+
+impl Match for CookieExistsMatcher {
+    fn matches(&self, request: &Request) -> bool {
+        request_cookies(request).any(|(name, _)| name == self.0)
+    }
+}
+
+#[derive(Debug)]
+/// Match **exactly** the body of a request.
+///
+/// ### Example (string):
+/// ```rust
+/// use wiremock::{MockServer, Mock, ResponseTemplate};
+/// use wiremock::matchers::body_string;
+///
+/// #[async_std::main]
+/// async fn main() {
+///     // Arrange
+///     let mock_server = MockServer::start().await;
+///
+///     Mock::given(body_string("hello world!"))
+///         .respond_with(ResponseTemplate::new(200))
+///         .mount(&mock_server)
+///         .await;
+///
+///     // Act
+///     let status = surf::post(&mock_server.uri())
+///         .body("hello world!")
+///         .await
+///         .unwrap()
+///         .status();
+///
+///     // Assert
+///     assert_eq!(status, 200);
+/// }
+/// ```
+///
+/// ### Example (json):
+/// ```rust
+/// use wiremock::{MockServer, Mock, ResponseTemplate};
+/// use wiremock::matchers::body_json;
+/// use serde_json::json;
+///
+/// #[async_std::main]
+/// async fn main() {
+///     // Arrange
+///     let mock_server = MockServer::start().await;
+///
+///     let expected_body = json!({
+///         "hello": "world!"
+///     });
+///     Mock::given(body_json(&expected_body))
+///         .respond_with(ResponseTemplate::new(200))
+///         .mount(&mock_server)
+///         .await;
+///
+///     // Act
+///     let status = surf::post(&mock_server.uri())
+///         .body(expected_body)
+///         .await
+///         .unwrap()
+///         .status();
+///
+///     // Assert
+///     assert_eq!(status, 200);
+/// }
+/// ```
+pub struct BodyExactMatcher(Body);
+
+#[derive(Debug)]
+enum Body {
+    Bytes(Vec<u8>),
+    Json(Value),
+}
+
+impl BodyExactMatcher {
+    /// Specify the expected body as a string.
+    pub fn string<T: Into<String>>(body: T) -> Self {
+        let body = body.into();
+        Self(Body::Bytes(body.into_bytes()))
+    }
+
+    /// Specify the expected body as a vector of bytes.
+    pub fn bytes<T: Into<Vec<u8>>>(body: T) -> Self {
+        let body = body.into();
+        Self(Body::Bytes(body))
+    }
+
+    /// Specify something JSON-serializable as the expected body.
+    pub fn json<T: Serialize>(body: T) -> Self {
+        let bytes = serde_json::to_vec(&body).expect("Failed to serialize JSON body");
+        Self::json_string(bytes)
+    }
+
+    /// Specify a JSON string as the expected body.
+    pub fn json_string(body: impl AsRef<[u8]>) -> Self {
+        let body = serde_json::from_slice(body.as_ref()).expect("Failed to parse JSON string");
+        Self(Body::Json(body))
+    }
+}
+
+/// Shorthand for [`BodyExactMatcher::string`].
+pub fn body_string<T>(body: T) -> BodyExactMatcher
+where
+    T: Into<String>,
+{
+    BodyExactMatcher::string(body)
+}
+
+/// Shorthand for [`BodyExactMatcher::bytes`].
+pub fn body_bytes<T>(body: T) -> BodyExactMatcher
+where
+    T: Into<Vec<u8>>,
+{
+    BodyExactMatcher::bytes(body)
+}
+
+/// Shorthand for [`BodyExactMatcher::json`].
+pub fn body_json<T>(body: T) -> BodyExactMatcher
+where
+    T: Serialize,
+{
+    BodyExactMatcher::json(body)
+}
+
+/// Shorthand for [`BodyExactMatcher::json_string`].
+pub fn body_json_string(body: impl AsRef<[u8]>) -> BodyExactMatcher {
+    BodyExactMatcher::json_string(body)
+}
+
+impl Match for BodyExactMatcher {
+    fn matches(&self, request: &Request) -> bool {
+        match &self.0 {
+            Body::Bytes(bytes) => request.body == *bytes,
+            Body::Json(json) => {
+                if let Ok(body) = serde_json::from_slice::<Value>(&request.body) {
+                    body == *json
+                } else {
+                    false
+                }
+            }
+        }
+    }
+}
+
+#[derive(Debug)]
+/// Match part of the body of a request.
+///
+/// ### Example (string):
+/// ```rust
+/// use wiremock::{MockServer, Mock, ResponseTemplate};
+/// use wiremock::matchers::body_string_contains;
+///
+/// #[async_std::main]
+/// async fn main() {
+///     // Arrange
+///     let mock_server = MockServer::start().await;
+///
+///     Mock::given(body_string_contains("hello world"))
+///         .respond_with(ResponseTemplate::new(200))
+///         .mount(&mock_server)
+///         .await;
+///
+///     // Act
+///     let status = surf::post(&mock_server.uri())
+///         .body("this is a hello world example!")
+///         .await
+///         .unwrap()
+///         .status();
+///
+///     // Assert
+///     assert_eq!(status, 200);
+/// }
+/// ```
+pub struct BodyContainsMatcher(Vec<u8>);
+
+impl BodyContainsMatcher {
+    /// Specify the part of the body that should be matched as a string.
+    pub fn string<T: Into<String>>(body: T) -> Self {
+        Self(body.into().as_bytes().into())
+    }
+}
+
+/// Shorthand for [`BodyContainsMatcher::string`].
+pub fn body_string_contains<T>(body: T) -> BodyContainsMatcher
+where
+    T: Into<String>,
+{
+    BodyContainsMatcher::string(body)
+}
+
+impl Match for BodyContainsMatcher {
+    fn matches(&self, request: &Request) -> bool {
+        let body = match str::from_utf8(&request.body) {
+            Ok(body) => body.to_string(),
+            Err(err) => {
+                debug!("can't convert body from byte slice to string: {}", err);
+                return false;
+            }
+        };
+
+        let part = match str::from_utf8(&self.0) {
+            Ok(part) => part,
+            Err(err) => {
+                debug!(
+                    "can't convert expected part from byte slice to string: {}",
+                    err
+                );
+                return false;
+            }
+        };
+
+        body.contains(part)
+    }
+}
+
+#[derive(Debug)]
+/// Match part JSON body of a request.
+///
+/// ### Example:
+/// ```rust
+/// use wiremock::{MockServer, Mock, ResponseTemplate};
+/// use wiremock::matchers::body_partial_json;
+/// use serde_json::json;
+///
+/// #[async_std::main]
+/// async fn main() {
+///     // Arrange
+///     let mock_server = MockServer::start().await;
+///
+///     let expected_body = json!({
+///         "hello": "world!"
+///     });
+///     Mock::given(body_partial_json(&expected_body))
+///         .respond_with(ResponseTemplate::new(200))
+///         .mount(&mock_server)
+///         .await;
+///
+///     // Act
+///     let body = json!({
+///         "hello": "world!",
+///         "foo": "bar"
+///     });
+///     let status = surf::post(&mock_server.uri())
+///         .body(body)
+///         .await
+///         .unwrap()
+///         .status();
+///
+///     // Assert
+///     assert_eq!(status, 200);
+/// }
+/// ```
+pub struct BodyPartialJsonMatcher(Value);
+
+impl BodyPartialJsonMatcher {
+    /// Specify the part of the body that should be matched as a JSON value.
+    pub fn json<T: Serialize>(body: T) -> Self {
+        Self(serde_json::to_value(body).expect("Can't serialize to JSON"))
+    }
+
+    /// Specify the part of the body that should be matched as a string.
+    pub fn json_string(body: impl AsRef<str>) -> Self {
+        Self(serde_json::from_str(body.as_ref()).expect("Can't deserialize JSON"))
+    }
+}
+
+/// Shorthand for [`BodyPartialJsonMatcher::json`].
+pub fn body_partial_json<T: Serialize>(body: T) -> BodyPartialJsonMatcher {
+    BodyPartialJsonMatcher::json(body)
+}
+
+/// Shorthand for [`BodyPartialJsonMatcher::json_string`].
+pub fn body_partial_json_string(body: impl AsRef<str>) -> BodyPartialJsonMatcher {
+    BodyPartialJsonMatcher::json_string(body)
+}
+
+impl Match for BodyPartialJsonMatcher {
+    fn matches(&self, request: &Request) -> bool {
+        if let Ok(body) = serde_json::from_slice::<Value>(&request.body) {
+            let config = assert_json_diff::Config::new(CompareMode::Inclusive);
+            assert_json_matches_no_panic(&body, &self.0, config).is_ok()
+        } else {
+            false
+        }
+    }
+}
+
+#[derive(Debug)]
+/// Match requests whose body is a sequence of JSON values, one per line
+/// ([NDJSON](https://github.com/ndjson/ndjson-spec) or [JSON Lines](https://jsonlines.org/)),
+/// and at least one of the lines includes the expected JSON value - with the same semantics
+/// as [`body_partial_json`].
+///
+/// Blank lines are ignored, while a line that is not valid JSON makes the whole body invalid:
+/// the request does not match.
+///
+/// ### Example:
+/// ```rust
+/// use wiremock::{MockServer, Mock, ResponseTemplate};
+/// use wiremock::matchers::{body_ndjson_contains, method};
+/// use serde_json::json;
+///
+/// #[async_std::main]
+/// async fn main() {
+///     // Arrange
+///     let mock_server = MockServer::start().await;
+///     Mock::given(method("POST"))
+///         .and(body_ndjson_contains(json!({ "event": "signup" })))
+///         .respond_with(ResponseTemplate::new(202))
+///         .mount(&mock_server)
+///         .await;
+///
+///     // Act
+///     let events = "{\"event\":\"login\",\"user\":1}\n{\"event\":\"signup\",\"user\":2}\n";
+///     let status = surf::post(&mock_server.uri())
+///         .body(events)
+///         .await
+///         .unwrap()
+///         .status();
+///
+///     // Assert
+///     assert_eq!(status, 202);
+/// }
+/// ```
+pub struct BodyNdjsonContainsMatcher(Value);
+
+/// Shorthand for [`BodyNdjsonContainsMatcher`].
+pub fn body_ndjson_contains<T: Serialize>(line: T) -> BodyNdjsonContainsMatcher {
+    BodyNdjsonContainsMatcher(serde_json::to_value(line).expect("Can't serialize to JSON"))
+}
+
+impl Match for BodyNdjsonContainsMatcher {
+    fn matches(&self, request: &Request) -> bool {
+        let lines: Result<Vec<Value>, _> = request
+            .body
+            .split(|byte| *byte == b'\n')
+            .filter(|line| !line.trim_ascii().is_empty())
+            .map(serde_json::from_slice)
+            .collect();
+        lines.is_ok_and(|lines| {
+            lines.iter().any(|line| {
+                let config = assert_json_diff::Config::new(CompareMode::Inclusive);
+                assert_json_matches_no_panic(line, &self.0, config).is_ok()
+            })
+        })
+    }
+}
+
+#[derive(Debug)]
+/// Match **exactly** the JSON body of a request, except for a set of volatile fields
+/// (timestamps, generated identifiers, ...) pointed at by [JSONPath](https://goessner.net/articles/JsonPath/)
+/// expressions.
+///
+/// It sits between [`body_json`], which compares every field, and [`body_partial_json`],
+/// which ignores every field that is not in the expected body.
+/// The ignored fields are removed from both the expected and the received body before
+/// comparing them: they do not need to be present in either.
+///
+/// The supported JSONPath subset covers the root (`$`), children by name (`.name`,
+/// `['name']`), array elements by index (`[0]`), wildcards (`.*`, `[*]`) and recursive
+/// descent (`..name`). Ignored array elements are compared as `null`, to keep the position of
+/// their siblings.
+///
+/// ### Example:
+/// ```rust
+/// use wiremock::{MockServer, Mock, ResponseTemplate};
+/// use wiremock::matchers::body_json_ignoring;
+/// use serde_json::json;
+///
+/// #[async_std::main]
+/// async fn main() {
+///     // Arrange
+///     let mock_server = MockServer::start().await;
+///
+///     let expected_body = json!({
+///         "name": "Ferris",
+///         "created_at": "1970-01-01T00:00:00Z",
+///     });
+///     Mock::given(body_json_ignoring(&expected_body, &["$.created_at", "$.request_id"]))
+///         .respond_with(ResponseTemplate::new(200))
+///         .mount(&mock_server)
+///         .await;
+///
+///     // Act
+///     let body = json!({
+///         "name": "Ferris",
+///         "created_at": "2023-04-01T12:00:00Z",
+///         "request_id": "27d6c4e5",
+///     });
+///     let status = surf::post(&mock_server.uri())
+///         .body(body)
+///         .await
+///         .unwrap()
+///         .status();
+///
+///     // Assert
+///     assert_eq!(status, 200);
+/// }
+/// ```
+pub struct BodyJsonIgnoringMatcher {
+    expected: Value,
+    ignored: Vec<JsonPath>,
+}
+
+impl BodyJsonIgnoringMatcher {
+    /// Specify something JSON-serializable as the expected body, and the JSONPath expressions
+    /// of the fields to ignore.
+    ///
+    /// It panics if `body` cannot be serialized or if an expression is not supported.
+    pub fn json<T, I>(body: T, ignored: I) -> Self
+    where
+        T: Serialize,
+        I: IntoIterator,
+        I::Item: AsRef<str>,
+    {
+        let ignored: Vec<JsonPath> = ignored
+            .into_iter()
+            .map(|path| JsonPath::parse(path.as_ref()))
+            .collect();
+        let mut expected = serde_json::to_value(body).expect("Can't serialize to JSON");
+        for path in &ignored {
+            path.mask(&mut expected);
+        }
+        Self { expected, ignored }
+    }
+}
+
+/// Shorthand for [`BodyJsonIgnoringMatcher::json`].
+pub fn body_json_ignoring<T, I>(body: T, ignored: I) -> BodyJsonIgnoringMatcher
+where
+    T: Serialize,
+    I: IntoIterator,
+    I::Item: AsRef<str>,
+{
+    BodyJsonIgnoringMatcher::json(body, ignored)
+}
+
+impl Match for BodyJsonIgnoringMatcher {
+    fn matches(&self, request: &Request) -> bool {
+        let mut body = match serde_json::from_slice::<Value>(&request.body) {
+            Ok(body) => body,
+            Err(_) => return false,
+        };
+        for path in &self.ignored {
+            path.mask(&mut body);
+        }
+        body == self.expected
+    }
+}
+
+#[derive(Debug)]
+/// Match requests with a JSON body whose fields, pointed at by a
+/// [JSONPath](https://goessner.net/articles/JsonPath/) expression, match a regular expression.
+///
+/// It does not match if the expression does not select any field. If it selects more than
+/// one (e.g. `$.users[*].email`), all of them must match.
+/// Strings are matched against their content, other values against their JSON representation
+/// (e.g. `42`, `true`, `null`).
+///
+/// The regular expression is not anchored: use `^` and `$` to match the whole value.
+/// See [`body_json_ignoring`] for the supported JSONPath subset.
+///
+/// ### Example:
+/// ```rust
+/// use wiremock::{MockServer, Mock, ResponseTemplate};
+/// use wiremock::matchers::body_json_field_matches;
+/// use serde_json::json;
+///
+/// #[async_std::main]
+/// async fn main() {
+///     // Arrange
+///     let mock_server = MockServer::start().await;
+///
+///     Mock::given(body_json_field_matches("$.user.email", r"^.+@example\.com$"))
+///         .respond_with(ResponseTemplate::new(200))
+///         .mount(&mock_server)
+///         .await;
+///
+///     // Act
+///     let body = json!({ "user": { "email": "ferris@example.com" } });
+///     let status = surf::post(&mock_server.uri())
+///         .body(body)
+///         .await
+///         .unwrap()
+///         .status();
+///
+///     // Assert
+///     assert_eq!(status, 200);
+/// }
+/// ```
+pub struct BodyJsonFieldRegexMatcher {
+    path: JsonPath,
+    regex: Regex,
+}
+
+/// Match requests with a JSON body whose fields, pointed at by `path`, match the regular
+/// expression `value` - see [`BodyJsonFieldRegexMatcher`].
+///
+/// It panics if `path` or `value` are not valid.
+pub fn body_json_field_matches(path: &str, value: &str) -> BodyJsonFieldRegexMatcher {
+    BodyJsonFieldRegexMatcher {
+        path: JsonPath::parse(path),
+        regex: Regex::new(value).expect("Failed to create regex for JSON field matcher"),
+    }
+}
+
+impl Match for BodyJsonFieldRegexMatcher {
+    fn matches(&self, request: &Request) -> bool {
+        let body = match serde_json::from_slice::<Value>(&request.body) {
+            Ok(body) => body,
+            Err(_) => return false,
+        };
+        let fields = self.path.select(&body);
+        !fields.is_empty()
+            && fields.iter().all(|field| match field {
+                Value::String(s) => self.regex.is_match(s),
+                other => self.regex.is_match(&other.to_string()),
+            })
+    }
+}
+
+#[derive(Debug)]
+/// Match requests with a JSON body whose array, pointed at by a
+/// [JSONPath](https://goessner.net/articles/JsonPath/) expression, has a length within a range.
+///
+/// It does not match if the expression does not select any field, or if it selects something
+/// that is not an array. If it selects more than one array (e.g. `$.orders[*].items`), all of
+/// them must have a length within the range.
+/// See [`body_json_ignoring`] for the supported JSONPath subset.
+///
+/// ### Example:
+/// ```rust
+/// use wiremock::{MockServer, Mock, ResponseTemplate};
+/// use wiremock::matchers::body_json_array_len;
+/// use serde_json::json;
+///
+/// #[async_std::main]
+/// async fn main() {
+///     // Arrange
+///     let mock_server = MockServer::start().await;
+///
+///     Mock::given(body_json_array_len("$.items", 3..))
+///         .respond_with(ResponseTemplate::new(200))
+///         .mount(&mock_server)
+///         .await;
+///
+///     // Act
+///     let body = json!({ "items": [1, 2, 3, 4] });
+///     let status = surf::post(&mock_server.uri())
+///         .body(body)
+///         .await
+///         .unwrap()
+///         .status();
+///
+///     // Assert
+///     assert_eq!(status, 200);
+/// }
+/// ```
+pub struct BodyJsonArrayLenMatcher {
+    path: JsonPath,
+    len: (Bound<usize>, Bound<usize>),
+}
+
+/// Match requests with a JSON body whose array, pointed at by `path`, has a length within
+/// `len` - e.g. `3..`, `..=10` or `2..=2` - see [`BodyJsonArrayLenMatcher`].
+///
+/// It panics if `path` is not valid.
+pub fn body_json_array_len<R>(path: &str, len: R) -> BodyJsonArrayLenMatcher
+where
+    R: RangeBounds<usize>,
+{
+    BodyJsonArrayLenMatcher {
+        path: JsonPath::parse(path),
+        len: (len.start_bound().cloned(), len.end_bound().cloned()),
+    }
+}
+
+impl Match for BodyJsonArrayLenMatcher {
+    fn matches(&self, request: &Request) -> bool {
+        let body = match serde_json::from_slice::<Value>(&request.body) {
+            Ok(body) => body,
+            Err(_) => return false,
+        };
+        let fields = self.path.select(&body);
+        !fields.is_empty()
+            && fields.iter().all(|field| match field {
+                Value::Array(array) => self.len.contains(&array.len()),
+                _ => false,
+            })
+    }
+}
+
+#[derive(Debug)]
+/// Match requests whose body could not be read in full - e.g. because the client aborted
+/// the upload or closed the connection mid-way - see [`Request::body_error`].
+///
+/// Use it with [`Mock::expect`](crate::Mock::expect) to assert that your client gives up on
+/// an upload.
+///
+/// ### Example:
+/// ```rust
+/// use wiremock::{MockServer, Mock, ResponseTemplate};
+/// use wiremock::matchers::body_read_error;
+/// use std::io::Write;
+///
+/// #[async_std::main]
+/// async fn main() {
+///     // Arrange
+///     let mock_server = MockServer::start().await;
+///     Mock::given(body_read_error())
+///         .respond_with(ResponseTemplate::new(400))
+///         .expect(1)
+///         .mount(&mock_server)
+///         .await;
+///
+///     // Act - the client announces 100 bytes, but only sends 7 of them
+///     let mut stream = std::net::TcpStream::connect(mock_server.address()).unwrap();
+///     stream
+///         .write_all(b"POST / HTTP/1.1\r\nHost: localhost\r\nContent-Length: 100\r\n\r\npartial")
+///         .unwrap();
+///     stream.shutdown(std::net::Shutdown::Both).unwrap();
+///
+///     // Assert
+///     let request = loop {
+///         match mock_server.received_requests().await.unwrap().pop() {
+///             Some(request) => break request,
+///             None => async_std::task::sleep(std::time::Duration::from_millis(10)).await,
+///         }
+///     };
+///     assert_eq!(request.body.as_ref(), b"partial");
+///     assert!(request.body_error().is_some());
+/// }
+/// ```
+pub struct BodyReadErrorMatcher;
+
+/// Shorthand for [`BodyReadErrorMatcher`].
+pub fn body_read_error() -> BodyReadErrorMatcher {
+    BodyReadErrorMatcher
+}
+
+impl Match for BodyReadErrorMatcher {
+    fn matches(&self, request: &Request) -> bool {
+        request.body_error().is_some()
+    }
+}
+
+#[derive(Debug)]
+/// Match **exactly** the query parameter of a request.
+///
+/// ### Example:
+/// ```rust
+/// use wiremock::{MockServer, Mock, ResponseTemplate};
+/// use wiremock::matchers::query_param;
+///
+/// #[async_std::main]
+/// async fn main() {
+///     // Arrange
+///     let mock_server = MockServer::start().await;
+///
+///     Mock::given(query_param("hello", "world"))
+///         .respond_with(ResponseTemplate::new(200))
+///         .mount(&mock_server)
+///         .await;
+///
+///     // Act
+///     let status = surf::get(format!("{}?hello=world", &mock_server.uri()))
+///         .await
+///         .unwrap()
+///         .status();
+///
+///     // Assert
+///     assert_eq!(status, 200);
+/// }
+/// ```
+pub struct QueryParamExactMatcher(String, String);
+
+impl QueryParamExactMatcher {
+    /// Specify the expected value for a query parameter.
+    pub fn new<K: Into<String>, V: Into<String>>(key: K, value: V) -> Self {
+        let key = key.into();
+        let value = value.into();
+        Self(key, value)
+    }
+}
+
+/// Shorthand for [`QueryParamExactMatcher::new`].
+pub fn query_param<K, V>(key: K, value: V) -> QueryParamExactMatcher
+where
+    K: Into<String>,
+    V: Into<String>,
+{
+    QueryParamExactMatcher::new(key, value)
+}
+
+impl Match for QueryParamExactMatcher {
+    fn matches(&self, request: &Request) -> bool {
+        request
+            .url
+            .query_pairs()
+            .any(|q| q.0 == self.0.as_str() && q.1 == self.1.as_str())
+    }
+}
+
+#[derive(Debug)]
+/// Match when a query parameter contains the specified value as a substring.
+///
+/// ### Example:
+/// ```rust
+/// use wiremock::{MockServer, Mock, ResponseTemplate};
+/// use wiremock::matchers::query_param_contains;
+///
+/// #[async_std::main]
+/// async fn main() {
+///     // Arrange
+///     let mock_server = MockServer::start().await;
+///
+///     // It matches since "world" is a substring of "some_world".
+///     Mock::given(query_param_contains("hello", "world"))
+///         .respond_with(ResponseTemplate::new(200))
+///         .mount(&mock_server)
+///         .await;
+///
+///     // Act
+///     let status = surf::get(format!("{}?hello=some_world", &mock_server.uri()))
+///         .await
+///         .unwrap()
+///         .status();
+///
+///     // Assert
+///     assert_eq!(status, 200);
+/// }
+/// ```
+pub struct QueryParamContainsMatcher(String, String);
+
+impl QueryParamContainsMatcher {
+    /// Specify the substring that the query parameter should contain.
+    pub fn new<K: Into<String>, V: Into<String>>(key: K, value: V) -> Self {
+        let key = key.into();
+        let value = value.into();
+        Self(key, value)
+    }
+}
+
+/// Shorthand for [`QueryParamContainsMatcher::new`].
+pub fn query_param_contains<K, V>(key: K, value: V) -> QueryParamContainsMatcher
+where
+    K: Into<String>,
+    V: Into<String>,
+{
+    QueryParamContainsMatcher::new(key, value)
+}
+
+impl Match for QueryParamContainsMatcher {
+    fn matches(&self, request: &Request) -> bool {
+        request
+            .url
+            .query_pairs()
+            .any(|q| q.0 == self.0.as_str() && q.1.contains(self.1.as_str()))
+    }
+}
+
+#[derive(Debug)]
+/// Only match requests that do **not** contain a specified query parameter.
+///
+/// ### Example:
+/// ```rust
+/// use wiremock::{MockServer, Mock, ResponseTemplate};
+/// use wiremock::matchers::{method, query_param_is_missing};
+///
+/// #[async_std::main]
+/// async fn main() {
+///     // Arrange
+///     let mock_server = MockServer::start().await;
+///
+///     Mock::given(method("GET"))
+///         .and(query_param_is_missing("unexpected"))
+///         .respond_with(ResponseTemplate::new(200))
+///         .mount(&mock_server)
+///         .await;
+///
+///     // Act
+///     let ok_status = surf::get(mock_server.uri().to_string())
+///         .await
+///         .unwrap()
+///         .status();
+///
+///     // Assert
+///     assert_eq!(ok_status, 200);
+///
+///     // Act
+///     let err_status = surf::get(format!("{}?unexpected=foo", mock_server.uri()))
+///     .await.
+///     unwrap().status();
+///
+///     // Assert
+///     assert_eq!(err_status, 404);
+/// }
+/// ```
+pub struct QueryParamIsMissingMatcher(String);
+
+impl QueryParamIsMissingMatcher {
+    /// Specify the query parameter that is expected to not exist.
+    pub fn new<K: Into<String>>(key: K) -> Self {
+        let key = key.into();
+        Self(key)
+    }
+}
+
+/// Shorthand for [`QueryParamIsMissingMatcher::new`].
+pub fn query_param_is_missing<K>(key: K) -> QueryParamIsMissingMatcher
+where
+    K: Into<String>,
+{
+    QueryParamIsMissingMatcher::new(key)
+}
+
+impl Match for QueryParamIsMissingMatcher {
+    fn matches(&self, request: &Request) -> bool {
+        !request.url.query_pairs().any(|(k, _)| k == self.0)
+    }
+}
+
 #[derive(Debug)]
-/// Match part of the body of a request.
+/// Match **exactly** all the values of a repeated query parameter - e.g. `?id=1&id=2&id=3`.
 ///
-/// ### Example (string):
+/// Unlike [`query_param`], which is satisfied if *any* of the values matches, it requires the
+/// request to have exactly the specified values for the query parameter: no more, no less.
+/// Use [`query_param_values`] if the values must appear in the specified order,
+/// [`query_param_values_any_order`] otherwise.
+///
+/// ### Example:
 /// ```rust
 /// use wiremock::{MockServer, Mock, ResponseTemplate};
-/// use wiremock::matchers::body_string_contains;
+/// use wiremock::matchers::{query_param_values, query_param_values_any_order};
 ///
 /// #[async_std::main]
 /// async fn main() {
 ///     // Arrange
 ///     let mock_server = MockServer::start().await;
 ///
-///     Mock::given(body_string_contains("hello world"))
+///     Mock::given(query_param_values("id", ["1", "2"]))
 ///         .respond_with(ResponseTemplate::new(200))
 ///         .mount(&mock_server)
 ///         .await;
+///     Mock::given(query_param_values_any_order("id", ["1", "2"]))
+///         .respond_with(ResponseTemplate::new(202))
+///         .mount(&mock_server)
+///         .await;
 ///
 ///     // Act
-///     let status = surf::post(&mock_server.uri())
-///         .body("this is a hello world example!")
+///     let in_order = surf::get(format!("{}?id=1&id=2", &mock_server.uri()))
+///         .await
+///         .unwrap()
+///         .status();
+///     let any_order = surf::get(format!("{}?id=2&id=1", &mock_server.uri()))
+///         .await
+///         .unwrap()
+///         .status();
+///     let extra_value = surf::get(format!("{}?id=1&id=2&id=3", &mock_server.uri()))
 ///         .await
 ///         .unwrap()
 ///         .status();
 ///
 ///     // Assert
-///     assert_eq!(status, 200);
+///     assert_eq!(in_order, 200);
+///     assert_eq!(any_order, 202);
+///     assert_eq!(extra_value, 404);
 /// }
 /// ```
-pub struct BodyContainsMatcher(Vec<u8>);
+pub struct QueryParamValuesMatcher {
+    key: String,
+    values: Vec<String>,
+    ordered: bool,
+}
 
-impl BodyContainsMatcher {
-    /// Specify the part of the body that should be matched as a string.
-    pub fn string<T: Into<String>>(body: T) -> Self {
-        Self(body.into().as_bytes().into())
+impl QueryParamValuesMatcher {
+    /// Specify the expected values for a query parameter, in the order they must appear.
+    pub fn new<K, I, V>(key: K, values: I) -> Self
+    where
+        K: Into<String>,
+        I: IntoIterator<Item = V>,
+        V: Into<String>,
+    {
+        Self {
+            key: key.into(),
+            values: values.into_iter().map(Into::into).collect(),
+            ordered: true,
+        }
+    }
+
+    /// Accept the expected values in any order.
+    pub fn any_order(mut self) -> Self {
+        self.ordered = false;
+        self
     }
 }
 
-/// Shorthand for [`BodyContainsMatcher::string`].
-pub fn body_string_contains<T>(body: T) -> BodyContainsMatcher
+/// Shorthand for [`QueryParamValuesMatcher::new`].
+pub fn query_param_values<K, I, V>(key: K, values: I) -> QueryParamValuesMatcher
 where
-    T: Into<String>,
+    K: Into<String>,
+    I: IntoIterator<Item = V>,
+    V: Into<String>,
 {
-    BodyContainsMatcher::string(body)
+    QueryParamValuesMatcher::new(key, values)
 }
 
-impl Match for BodyContainsMatcher {
-    fn matches(&self, request: &Request) -> bool {
-        let body = match str::from_utf8(&request.body) {
-            Ok(body) => body.to_string(),
-            Err(err) => {
-                debug!("can't convert body from byte slice to string: {}", err);
-                return false;
-            }
-        };
-
-        let part = match str::from_utf8(&self.0) {
-            Ok(part) => part,
-            Err(err) => {
-                debug!(
-                    "can't convert expected part from byte slice to string: {}",
-                    err
-                );
-                return false;
-            }
-        };
+/// Shorthand for [`QueryParamValuesMatcher::new`] followed by
+/// [`QueryParamValuesMatcher::any_order`].
+pub fn query_param_values_any_order<K, I, V>(key: K, values: I) -> QueryParamValuesMatcher
+where
+    K: Into<String>,
+    I: IntoIterator<Item = V>,
+    V: Into<String>,
+{
+    QueryParamValuesMatcher::new(key, values).any_order()
+}
 
-        body.contains(part)
+impl Match for QueryParamValuesMatcher {
+    fn matches(&self, request: &Request) -> bool {
+        let mut actual: Vec<String> = request
+            .url
+            .query_pairs()
+            .filter(|(k, _)| k == self.key.as_str())
+            .map(|(_, v)| v.into_owned())
+            .collect();
+        if self.ordered {
+            actual == self.values
+        } else {
+            let mut expected = self.values.clone();
+            actual.sort();
+            expected.sort();
+            actual == expected
+        }
     }
 }
 
 #[derive(Debug)]
-/// Match part JSON body of a request.
+/// Match when a query parameter appears exactly the specified number of times, regardless of
+/// its values.
 ///
 /// ### Example:
 /// ```rust
 /// use wiremock::{MockServer, Mock, ResponseTemplate};
-/// use wiremock::matchers::body_partial_json;
-/// use serde_json::json;
+/// use wiremock::matchers::query_param_count;
 ///
 /// #[async_std::main]
 /// async fn main() {
 ///     // Arrange
 ///     let mock_server = MockServer::start().await;
 ///
-///     let expected_body = json!({
-///         "hello": "world!"
-///     });
-///     Mock::given(body_partial_json(&expected_body))
+///     Mock::given(query_param_count("id", 3))
 ///         .respond_with(ResponseTemplate::new(200))
 ///         .mount(&mock_server)
 ///         .await;
 ///
 ///     // Act
-///     let body = json!({
-///         "hello": "world!",
-///         "foo": "bar"
-///     });
-///     let status = surf::post(&mock_server.uri())
-///         .body(body)
+///     let status = surf::get(format!("{}?id=a&id=b&id=c", &mock_server.uri()))
 ///         .await
 ///         .unwrap()
 ///         .status();
@@ -778,216 +2347,246 @@ impl Match for BodyContainsMatcher {
 ///     assert_eq!(status, 200);
 /// }
 /// ```
-pub struct BodyPartialJsonMatcher(Value);
-
-impl BodyPartialJsonMatcher {
-    /// Specify the part of the body that should be matched as a JSON value.
-    pub fn json<T: Serialize>(body: T) -> Self {
-        Self(serde_json::to_value(body).expect("Can't serialize to JSON"))
-    }
+pub struct QueryParamCountMatcher(String, usize);
 
-    /// Specify the part of the body that should be matched as a string.
-    pub fn json_string(body: impl AsRef<str>) -> Self {
-        Self(serde_json::from_str(body.as_ref()).expect("Can't deserialize JSON"))
+impl QueryParamCountMatcher {
+    /// Specify how many times the query parameter must appear.
+    pub fn new<K: Into<String>>(key: K, count: usize) -> Self {
+        Self(key.into(), count)
     }
 }
 
-/// Shorthand for [`BodyPartialJsonMatcher::json`].
-pub fn body_partial_json<T: Serialize>(body: T) -> BodyPartialJsonMatcher {
-    BodyPartialJsonMatcher::json(body)
-}
-
-/// Shorthand for [`BodyPartialJsonMatcher::json_string`].
-pub fn body_partial_json_string(body: impl AsRef<str>) -> BodyPartialJsonMatcher {
-    BodyPartialJsonMatcher::json_string(body)
+/// Shorthand for [`QueryParamCountMatcher::new`].
+pub fn query_param_count<K>(key: K, count: usize) -> QueryParamCountMatcher
+where
+    K: Into<String>,
+{
+    QueryParamCountMatcher::new(key, count)
 }
 
-impl Match for BodyPartialJsonMatcher {
+impl Match for QueryParamCountMatcher {
     fn matches(&self, request: &Request) -> bool {
-        if let Ok(body) = serde_json::from_slice::<Value>(&request.body) {
-            let config = assert_json_diff::Config::new(CompareMode::Inclusive);
-            assert_json_matches_no_panic(&body, &self.0, config).is_ok()
-        } else {
-            false
-        }
+        request
+            .url
+            .query_pairs()
+            .filter(|(k, _)| k == self.0.as_str())
+            .count()
+            == self.1
     }
 }
 
 #[derive(Debug)]
-/// Match **exactly** the query parameter of a request.
+/// Match **exactly** the whole query string of a request.
+///
+/// The query strings are compared after percent-decoding, parameter by parameter: the request
+/// must have exactly the specified parameters, in the same order.
+/// An empty query string only matches requests without query parameters.
 ///
 /// ### Example:
 /// ```rust
 /// use wiremock::{MockServer, Mock, ResponseTemplate};
-/// use wiremock::matchers::query_param;
+/// use wiremock::matchers::query_string;
 ///
 /// #[async_std::main]
 /// async fn main() {
 ///     // Arrange
 ///     let mock_server = MockServer::start().await;
 ///
-///     Mock::given(query_param("hello", "world"))
+///     Mock::given(query_string("a=1&b=hello world"))
 ///         .respond_with(ResponseTemplate::new(200))
 ///         .mount(&mock_server)
 ///         .await;
 ///
 ///     // Act
-///     let status = surf::get(format!("{}?hello=world", &mock_server.uri()))
+///     let exact = surf::get(format!("{}?a=1&b=hello%20world", &mock_server.uri()))
+///         .await
+///         .unwrap()
+///         .status();
+///     let extra_param = surf::get(format!("{}?a=1&b=hello%20world&c=2", &mock_server.uri()))
 ///         .await
 ///         .unwrap()
 ///         .status();
 ///
 ///     // Assert
-///     assert_eq!(status, 200);
+///     assert_eq!(exact, 200);
+///     assert_eq!(extra_param, 404);
 /// }
 /// ```
-pub struct QueryParamExactMatcher(String, String);
+pub struct QueryStringMatcher(Vec<(String, String)>);
 
-impl QueryParamExactMatcher {
-    /// Specify the expected value for a query parameter.
-    pub fn new<K: Into<String>, V: Into<String>>(key: K, value: V) -> Self {
-        let key = key.into();
-        let value = value.into();
-        Self(key, value)
+impl QueryStringMatcher {
+    /// Specify the expected query string, without the leading `?`.
+    pub fn new<T: AsRef<str>>(query: T) -> Self {
+        let pairs = url::form_urlencoded::parse(query.as_ref().as_bytes())
+            .into_owned()
+            .collect();
+        Self(pairs)
     }
 }
 
-/// Shorthand for [`QueryParamExactMatcher::new`].
-pub fn query_param<K, V>(key: K, value: V) -> QueryParamExactMatcher
+/// Shorthand for [`QueryStringMatcher::new`].
+pub fn query_string<T>(query: T) -> QueryStringMatcher
 where
-    K: Into<String>,
-    V: Into<String>,
+    T: AsRef<str>,
 {
-    QueryParamExactMatcher::new(key, value)
+    QueryStringMatcher::new(query)
 }
 
-impl Match for QueryParamExactMatcher {
+impl Match for QueryStringMatcher {
     fn matches(&self, request: &Request) -> bool {
         request
             .url
             .query_pairs()
-            .any(|q| q.0 == self.0.as_str() && q.1 == self.1.as_str())
+            .into_owned()
+            .eq(self.0.iter().cloned())
     }
 }
 
 #[derive(Debug)]
-/// Match when a query parameter contains the specified value as a substring.
+/// Match **exactly** a query parameter against its raw, **undecoded** form.
+///
+/// The other query parameter matchers compare values after percent-decoding: `a%2Bb` and
+/// `a+b` are then indistinguishable from `a+b` and `a b`. Use this matcher when the encoding
+/// chosen by your client matters.
+/// It matches if any occurrence of the parameter has the expected value.
 ///
 /// ### Example:
 /// ```rust
 /// use wiremock::{MockServer, Mock, ResponseTemplate};
-/// use wiremock::matchers::query_param_contains;
+/// use wiremock::matchers::query_param_raw;
 ///
 /// #[async_std::main]
 /// async fn main() {
 ///     // Arrange
 ///     let mock_server = MockServer::start().await;
 ///
-///     // It matches since "world" is a substring of "some_world".
-///     Mock::given(query_param_contains("hello", "world"))
+///     Mock::given(query_param_raw("filter", "a%2Bb"))
 ///         .respond_with(ResponseTemplate::new(200))
 ///         .mount(&mock_server)
 ///         .await;
 ///
 ///     // Act
-///     let status = surf::get(format!("{}?hello=some_world", &mock_server.uri()))
+///     let encoded = surf::get(format!("{}?filter=a%2Bb", &mock_server.uri()))
+///         .await
+///         .unwrap()
+///         .status();
+///     let unencoded = surf::get(format!("{}?filter=a+b", &mock_server.uri()))
 ///         .await
 ///         .unwrap()
 ///         .status();
 ///
 ///     // Assert
-///     assert_eq!(status, 200);
+///     assert_eq!(encoded, 200);
+///     assert_eq!(unencoded, 404);
 /// }
 /// ```
-pub struct QueryParamContainsMatcher(String, String);
+pub struct QueryParamRawMatcher(String, String);
 
-impl QueryParamContainsMatcher {
-    /// Specify the substring that the query parameter should contain.
+impl QueryParamRawMatcher {
+    /// Specify the expected key and value, both as they appear in the URL.
     pub fn new<K: Into<String>, V: Into<String>>(key: K, value: V) -> Self {
-        let key = key.into();
-        let value = value.into();
-        Self(key, value)
+        Self(key.into(), value.into())
     }
 }
 
-/// Shorthand for [`QueryParamContainsMatcher::new`].
-pub fn query_param_contains<K, V>(key: K, value: V) -> QueryParamContainsMatcher
+/// Shorthand for [`QueryParamRawMatcher::new`].
+pub fn query_param_raw<K, V>(key: K, value: V) -> QueryParamRawMatcher
 where
     K: Into<String>,
     V: Into<String>,
 {
-    QueryParamContainsMatcher::new(key, value)
+    QueryParamRawMatcher::new(key, value)
 }
 
-impl Match for QueryParamContainsMatcher {
+impl Match for QueryParamRawMatcher {
     fn matches(&self, request: &Request) -> bool {
         request
             .url
-            .query_pairs()
-            .any(|q| q.0 == self.0.as_str() && q.1.contains(self.1.as_str()))
+            .query()
+            .unwrap_or_default()
+            .split('&')
+            .map(|pair| pair.split_once('=').unwrap_or((pair, "")))
+            .any(|(key, value)| key == self.0 && value == self.1)
     }
 }
 
 #[derive(Debug)]
-/// Only match requests that do **not** contain a specified query parameter.
+/// Match when the request has **exactly** the specified query parameters, in any order.
+///
+/// Unlike [`query_param`], it fails if any other parameter is present. Unlike
+/// [`query_string`], the order of the parameters does not matter.
+/// Repeated parameters must appear as many times as specified.
+/// Values are compared after percent-decoding.
 ///
 /// ### Example:
 /// ```rust
 /// use wiremock::{MockServer, Mock, ResponseTemplate};
-/// use wiremock::matchers::{method, query_param_is_missing};
+/// use wiremock::matchers::query_params_exact;
+/// use std::collections::HashMap;
 ///
 /// #[async_std::main]
 /// async fn main() {
 ///     // Arrange
 ///     let mock_server = MockServer::start().await;
 ///
-///     Mock::given(method("GET"))
-///         .and(query_param_is_missing("unexpected"))
+///     let params = HashMap::from([("page", "2"), ("sort", "name")]);
+///     Mock::given(query_params_exact(params))
 ///         .respond_with(ResponseTemplate::new(200))
 ///         .mount(&mock_server)
 ///         .await;
 ///
 ///     // Act
-///     let ok_status = surf::get(mock_server.uri().to_string())
+///     let exact = surf::get(format!("{}?sort=name&page=2", &mock_server.uri()))
+///         .await
+///         .unwrap()
+///         .status();
+///     let extra_param = surf::get(format!("{}?sort=name&page=2&debug=1", &mock_server.uri()))
 ///         .await
 ///         .unwrap()
 ///         .status();
 ///
 ///     // Assert
-///     assert_eq!(ok_status, 200);
-///
-///     // Act
-///     let err_status = surf::get(format!("{}?unexpected=foo", mock_server.uri()))
-///     .await.
-///     unwrap().status();
-///
-///     // Assert
-///     assert_eq!(err_status, 404);
+///     assert_eq!(exact, 200);
+///     assert_eq!(extra_param, 404);
 /// }
 /// ```
-pub struct QueryParamIsMissingMatcher(String);
+pub struct QueryParamsExactMatcher(Vec<(String, String)>);
 
-impl QueryParamIsMissingMatcher {
-    /// Specify the query parameter that is expected to not exist.
-    pub fn new<K: Into<String>>(key: K) -> Self {
-        let key = key.into();
-        Self(key)
+impl QueryParamsExactMatcher {
+    /// Specify the expected query parameters, as key-value pairs.
+    pub fn new<I, K, V>(params: I) -> Self
+    where
+        I: IntoIterator<Item = (K, V)>,
+        K: Into<String>,
+        V: Into<String>,
+    {
+        let mut params: Vec<(String, String)> = params
+            .into_iter()
+            .map(|(key, value)| (key.into(), value.into()))
+            .collect();
+        params.sort();
+        Self(params)
     }
 }
 
-/// Shorthand for [`QueryParamIsMissingMatcher::new`].
-pub fn query_param_is_missing<K>(key: K) -> QueryParamIsMissingMatcher
+/// Shorthand for [`QueryParamsExactMatcher::new`].
+pub fn query_params_exact<I, K, V>(params: I) -> QueryParamsExactMatcher
 where
+    I: IntoIterator<Item = (K, V)>,
     K: Into<String>,
+    V: Into<String>,
 {
-    QueryParamIsMissingMatcher::new(key)
+    QueryParamsExactMatcher::new(params)
 }
 
-impl Match for QueryParamIsMissingMatcher {
+impl Match for QueryParamsExactMatcher {
     fn matches(&self, request: &Request) -> bool {
-        !request.url.query_pairs().any(|(k, _)| k == self.0)
+        let mut actual: Vec<(String, String)> = request.url.query_pairs().into_owned().collect();
+        actual.sort();
+        actual == self.0
     }
 }
+
 /// Match an incoming request if its body is encoded as JSON and can be deserialized
 /// according to the specified schema.
 ///
@@ -1180,3 +2779,144 @@ where
 {
     BearerTokenMatcher::from_token(token)
 }
+
+#[derive(Debug)]
+/// Match requests received over TLS, with a protocol version at least as recent as the
+/// expected one.
+///
+/// Requests received over plain HTTP never match. See
+/// [`MockServerBuilder::tls`](crate::MockServerBuilder::tls) to serve mocks over HTTPS.
+///
+/// ### Example:
+/// ```rust
+/// use wiremock::{Match, Request};
+/// use wiremock::matchers::tls_version_at_least;
+/// use wiremock::tls::{TlsInfo, TlsVersion};
+///
+/// let tls_1_2 = Request::builder()
+///     .tls(TlsInfo::new(TlsVersion::Tls12, "TLS_ECDHE_ECDSA_WITH_AES_128_GCM_SHA256"))
+///     .build();
+/// let tls_1_3 = Request::builder()
+///     .tls(TlsInfo::new(TlsVersion::Tls13, "TLS13_AES_128_GCM_SHA256"))
+///     .build();
+///
+/// let matcher = tls_version_at_least(TlsVersion::Tls13);
+/// assert!(!matcher.matches(&tls_1_2));
+/// assert!(matcher.matches(&tls_1_3));
+/// assert!(!matcher.matches(&Request::builder().build()));
+/// ```
+pub struct TlsVersionMatcher(TlsVersion);
+
+/// Match requests received over TLS `version`, or a more recent one - see
+/// [`TlsVersionMatcher`].
+pub fn tls_version_at_least(version: TlsVersion) -> TlsVersionMatcher {
+    TlsVersionMatcher(version)
+}
+
+impl Match for TlsVersionMatcher {
+    fn matches(&self, request: &Request) -> bool {
+        request.tls().is_some_and(|tls| tls.version() >= self.0)
+    }
+}
+
+#[derive(Debug)]
+/// Match requests received over TLS using the expected cipher suite, identified by its IANA
+/// name - e.g. `TLS13_AES_256_GCM_SHA384`. Names are compared case-insensitively.
+///
+/// Requests received over plain HTTP never match. See
+/// [`MockServerBuilder::tls`](crate::MockServerBuilder::tls) to serve mocks over HTTPS.
+///
+/// ### Example:
+/// ```rust
+/// use wiremock::{Match, Request};
+/// use wiremock::matchers::tls_cipher_suite;
+/// use wiremock::tls::{TlsInfo, TlsVersion};
+///
+/// let request = Request::builder()
+///     .tls(TlsInfo::new(TlsVersion::Tls13, "TLS13_CHACHA20_POLY1305_SHA256"))
+///     .build();
+///
+/// assert!(tls_cipher_suite("TLS13_CHACHA20_POLY1305_SHA256").matches(&request));
+/// assert!(!tls_cipher_suite("TLS13_AES_128_GCM_SHA256").matches(&request));
+/// ```
+pub struct TlsCipherSuiteMatcher(String);
+
+/// Match requests received over TLS using the `cipher_suite` - see [`TlsCipherSuiteMatcher`].
+pub fn tls_cipher_suite<T: Into<String>>(cipher_suite: T) -> TlsCipherSuiteMatcher {
+    TlsCipherSuiteMatcher(cipher_suite.into())
+}
+
+impl Match for TlsCipherSuiteMatcher {
+    fn matches(&self, request: &Request) -> bool {
+        request
+            .tls()
+            .is_some_and(|tls| tls.cipher_suite().eq_ignore_ascii_case(&self.0))
+    }
+}
+
+#[derive(Debug)]
+/// Match requests received within a time window, measured by the clock of the
+/// [`MockServer`](crate::MockServer) - see [`Request::received_at`].
+///
+/// The window starts when the server is handed out by
+/// [`MockServer::start`](crate::MockServer::start), even if it is recycled from the pool of
+/// servers, or, if it is driven by a [`VirtualClock`](crate::VirtualClock), when the clock is
+/// created: combined with [`Mock::expect`](crate::Mock::expect), it checks the spacing of the
+/// retries of your client without post-processing the timestamps of the received requests.
+/// Requests built by hand, without an arrival time, never match.
+///
+/// ### Example:
+/// ```rust
+/// use std::time::Duration;
+/// use wiremock::{MockServer, Mock, ResponseTemplate, VirtualClock};
+/// use wiremock::matchers::received_within;
+///
+/// #[async_std::main]
+/// async fn main() {
+///     // Arrange
+///     let clock = VirtualClock::new();
+///     let mock_server = MockServer::builder().virtual_clock(clock.clone()).start().await;
+///     // The first attempt fails, the client must wait at least 2 seconds before retrying.
+///     Mock::given(received_within(..Duration::from_secs(1)))
+///         .respond_with(ResponseTemplate::new(503))
+///         .expect(1)
+///         .mount(&mock_server)
+///         .await;
+///     Mock::given(received_within(Duration::from_secs(2)..))
+///         .respond_with(ResponseTemplate::new(200))
+///         .expect(1)
+///         .mount(&mock_server)
+///         .await;
+///
+///     // Act
+///     let first = surf::get(&mock_server.uri()).await.unwrap();
+///     clock.advance(Duration::from_secs(2));
+///     let retry = surf::get(&mock_server.uri()).await.unwrap();
+///
+///     // Assert
+///     assert_eq!(first.status(), 503);
+///     assert_eq!(retry.status(), 200);
+/// }
+/// ```
+pub struct ReceivedWithinMatcher {
+    window: (Bound<Duration>, Bound<Duration>),
+}
+
+/// Match requests received within `window` - e.g. `..Duration::from_secs(1)` or
+/// `Duration::from_secs(2)..` - see [`ReceivedWithinMatcher`].
+pub fn received_within<R>(window: R) -> ReceivedWithinMatcher
+where
+    R: RangeBounds<Duration>,
+{
+    ReceivedWithinMatcher {
+        window: (window.start_bound().cloned(), window.end_bound().cloned()),
+    }
+}
+
+impl Match for ReceivedWithinMatcher {
+    fn matches(&self, request: &Request) -> bool {
+        request
+            .received_at()
+            .is_some_and(|received_at| self.window.contains(&received_at))
+    }
+}
@@ -0,0 +1,80 @@
+use crate::{Match, Request};
+use ciborium::Value;
+use serde::Serialize;
+
+/// Match the body of a request, decoded as CBOR ([RFC 8949](https://www.rfc-editor.org/rfc/rfc8949)),
+/// against an expected value.
+///
+/// The comparison is structural: the order of the keys of maps does not matter, and integers
+/// match regardless of the width they are encoded with.
+///
+/// This matcher is only available if the `cbor` feature is enabled.
+///
+/// ### Example:
+/// ```rust
+/// use wiremock::{MockServer, Mock, ResponseTemplate};
+/// use wiremock::matchers::{body_cbor, method};
+/// use serde_json::json;
+///
+/// #[async_std::main]
+/// async fn main() {
+///     // Arrange
+///     let mock_server = MockServer::start().await;
+///     let user = json!({ "name": "Jane", "age": 42 });
+///     Mock::given(method("POST"))
+///         .and(body_cbor(&user))
+///         .respond_with(ResponseTemplate::new(201))
+///         .mount(&mock_server)
+///         .await;
+///
+///     // Act
+///     let mut body = vec![];
+///     ciborium::into_writer(&user, &mut body).unwrap();
+///     let status = surf::post(&mock_server.uri())
+///         .header("Content-Type", "application/cbor")
+///         .body(body)
+///         .await
+///         .unwrap()
+///         .status();
+///
+///     // Assert
+///     assert_eq!(status, 201);
+/// }
+/// ```
+pub fn body_cbor<T: Serialize>(body: T) -> BodyCborMatcher {
+    BodyCborMatcher(Value::serialized(&body).expect("Failed to serialize CBOR body"))
+}
+
+/// Match the body of a request against an expected CBOR value - see [`body_cbor`].
+#[derive(Debug)]
+pub struct BodyCborMatcher(Value);
+
+impl Match for BodyCborMatcher {
+    fn matches(&self, request: &Request) -> bool {
+        decode(&request.body).is_some_and(|body| equivalent(&body, &self.0))
+    }
+}
+
+/// Decode a CBOR data item, or return `None` if `bytes` holds anything else.
+fn decode(mut bytes: &[u8]) -> Option<Value> {
+    let value = ciborium::from_reader(&mut bytes).ok()?;
+    bytes.is_empty().then_some(value)
+}
+
+/// Compare two values, ignoring the order of the keys of maps.
+fn equivalent(a: &Value, b: &Value) -> bool {
+    match (a, b) {
+        (Value::Map(a), Value::Map(b)) => {
+            a.len() == b.len()
+                && a.iter().all(|(key, a)| {
+                    b.iter()
+                        .any(|(other_key, b)| key == other_key && equivalent(a, b))
+                })
+        }
+        (Value::Array(a), Value::Array(b)) => {
+            a.len() == b.len() && a.iter().zip(b).all(|(a, b)| equivalent(a, b))
+        }
+        (Value::Tag(tag, a), Value::Tag(other_tag, b)) => tag == other_tag && equivalent(a, b),
+        _ => a == b,
+    }
+}
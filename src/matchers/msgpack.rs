@@ -0,0 +1,79 @@
+use crate::{Match, Request};
+use rmpv::Value;
+use serde::Serialize;
+
+/// Match the body of a request, decoded as MessagePack, against an expected value.
+///
+/// `body` is serialized using [`rmp_serde::to_vec_named`] - structs are encoded as maps, the
+/// same way [`ResponseTemplate::set_body_msgpack`](crate::ResponseTemplate::set_body_msgpack)
+/// does. The comparison is structural: the order of the keys of maps does not matter, and
+/// integers match regardless of the width they are encoded with.
+///
+/// This matcher is only available if the `msgpack` feature is enabled.
+///
+/// ### Example:
+/// ```rust
+/// use wiremock::{MockServer, Mock, ResponseTemplate};
+/// use wiremock::matchers::{body_msgpack, method};
+/// use serde_json::json;
+///
+/// #[async_std::main]
+/// async fn main() {
+///     // Arrange
+///     let mock_server = MockServer::start().await;
+///     let user = json!({ "name": "Jane", "age": 42 });
+///     Mock::given(method("POST"))
+///         .and(body_msgpack(&user))
+///         .respond_with(ResponseTemplate::new(201))
+///         .mount(&mock_server)
+///         .await;
+///
+///     // Act
+///     let status = surf::post(&mock_server.uri())
+///         .header("Content-Type", "application/msgpack")
+///         .body(rmp_serde::to_vec_named(&user).unwrap())
+///         .await
+///         .unwrap()
+///         .status();
+///
+///     // Assert
+///     assert_eq!(status, 201);
+/// }
+/// ```
+pub fn body_msgpack<T: Serialize>(body: T) -> BodyMsgpackMatcher {
+    let bytes = rmp_serde::to_vec_named(&body).expect("Failed to serialize MessagePack body");
+    BodyMsgpackMatcher(decode(&bytes).expect("A serialized MessagePack body is valid"))
+}
+
+/// Match the body of a request against an expected MessagePack value - see [`body_msgpack`].
+#[derive(Debug)]
+pub struct BodyMsgpackMatcher(Value);
+
+impl Match for BodyMsgpackMatcher {
+    fn matches(&self, request: &Request) -> bool {
+        decode(&request.body).is_some_and(|body| equivalent(&body, &self.0))
+    }
+}
+
+/// Decode a MessagePack document, or return `None` if `bytes` holds anything else.
+fn decode(mut bytes: &[u8]) -> Option<Value> {
+    let value = rmpv::decode::read_value(&mut bytes).ok()?;
+    bytes.is_empty().then_some(value)
+}
+
+/// Compare two values, ignoring the order of the keys of maps.
+fn equivalent(a: &Value, b: &Value) -> bool {
+    match (a, b) {
+        (Value::Map(a), Value::Map(b)) => {
+            a.len() == b.len()
+                && a.iter().all(|(key, a)| {
+                    b.iter()
+                        .any(|(other_key, b)| key == other_key && equivalent(a, b))
+                })
+        }
+        (Value::Array(a), Value::Array(b)) => {
+            a.len() == b.len() && a.iter().zip(b).all(|(a, b)| equivalent(a, b))
+        }
+        _ => a == b,
+    }
+}
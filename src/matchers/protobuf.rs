@@ -0,0 +1,232 @@
+use crate::{Match, Request};
+use prost::Message;
+use std::convert::{TryFrom, TryInto};
+use std::marker::PhantomData;
+
+/// Match the body of a request, decoded as a protobuf message of type `T`, against an
+/// expected message - e.g. for protobuf-over-HTTP APIs.
+///
+/// Messages are compared structurally: the match does not depend on how the client orders
+/// the fields on the wire. Requests whose body cannot be decoded as a `T` do not match.
+///
+/// This matcher is only available if the `protobuf` feature is enabled.
+///
+/// ### Example:
+/// ```rust
+/// use wiremock::{MockServer, Mock, ResponseTemplate};
+/// use wiremock::matchers::{body_protobuf, method};
+/// use prost::Message;
+///
+/// #[derive(Clone, PartialEq, Message)]
+/// struct CreateUser {
+///     #[prost(string, tag = "1")]
+///     name: String,
+///     #[prost(uint32, tag = "2")]
+///     age: u32,
+/// }
+///
+/// #[async_std::main]
+/// async fn main() {
+///     // Arrange
+///     let mock_server = MockServer::start().await;
+///     let user = CreateUser { name: "Jane".into(), age: 42 };
+///     Mock::given(method("POST"))
+///         .and(body_protobuf(user.clone()))
+///         .respond_with(ResponseTemplate::new(201))
+///         .mount(&mock_server)
+///         .await;
+///
+///     // Act
+///     let status = surf::post(&mock_server.uri())
+///         .header("Content-Type", "application/x-protobuf")
+///         .body(user.encode_to_vec())
+///         .await
+///         .unwrap()
+///         .status();
+///
+///     // Assert
+///     assert_eq!(status, 201);
+/// }
+/// ```
+pub fn body_protobuf<T>(expected: T) -> BodyProtobufMatcher<T>
+where
+    T: Message + Default + PartialEq,
+{
+    BodyProtobufMatcher(expected)
+}
+
+/// Match the body of a request against an expected protobuf message - see [`body_protobuf`].
+#[derive(Debug)]
+pub struct BodyProtobufMatcher<T>(T);
+
+impl<T> Match for BodyProtobufMatcher<T>
+where
+    T: Message + Default + PartialEq,
+{
+    fn matches(&self, request: &Request) -> bool {
+        T::decode(&request.body[..]).is_ok_and(|body| body == self.0)
+    }
+}
+
+/// Match requests whose body, decoded as a protobuf message of type `T`, contains all the
+/// fields set in `expected` - other fields are ignored.
+///
+/// Nested messages are compared the same way, while each element of a repeated field of
+/// `expected` must appear in the body. Repeated scalar fields are packed on the wire: they
+/// are compared as a whole.
+///
+/// The protobuf encoding omits the fields set to their default value - e.g. `0` or an empty
+/// string: `body_protobuf_partial` cannot check that they are not set. Use [`body_protobuf`]
+/// to match the whole message instead.
+///
+/// This matcher is only available if the `protobuf` feature is enabled.
+///
+/// ### Example:
+/// ```rust
+/// use wiremock::{MockServer, Mock, ResponseTemplate};
+/// use wiremock::matchers::{body_protobuf_partial, method};
+/// use prost::Message;
+///
+/// #[derive(Clone, PartialEq, Message)]
+/// struct CreateUser {
+///     #[prost(string, tag = "1")]
+///     name: String,
+///     #[prost(uint32, tag = "2")]
+///     age: u32,
+/// }
+///
+/// #[async_std::main]
+/// async fn main() {
+///     // Arrange
+///     let mock_server = MockServer::start().await;
+///     Mock::given(method("POST"))
+///         .and(body_protobuf_partial(CreateUser { name: "Jane".into(), ..Default::default() }))
+///         .respond_with(ResponseTemplate::new(201))
+///         .mount(&mock_server)
+///         .await;
+///
+///     // Act
+///     let user = CreateUser { name: "Jane".into(), age: 42 };
+///     let status = surf::post(&mock_server.uri())
+///         .body(user.encode_to_vec())
+///         .await
+///         .unwrap()
+///         .status();
+///
+///     // Assert
+///     assert_eq!(status, 201);
+/// }
+/// ```
+pub fn body_protobuf_partial<T>(expected: T) -> BodyProtobufPartialMatcher<T>
+where
+    T: Message + Default,
+{
+    BodyProtobufPartialMatcher {
+        expected: parse_fields(&expected.encode_to_vec())
+            .expect("A message encoded by prost is valid"),
+        message: PhantomData,
+    }
+}
+
+/// Match the fields of the body of a request against the ones of an expected protobuf
+/// message - see [`body_protobuf_partial`].
+#[derive(Debug)]
+pub struct BodyProtobufPartialMatcher<T> {
+    expected: Vec<Field>,
+    message: PhantomData<fn() -> T>,
+}
+
+impl<T> Match for BodyProtobufPartialMatcher<T>
+where
+    T: Message + Default,
+{
+    fn matches(&self, request: &Request) -> bool {
+        T::decode(&request.body[..]).is_ok()
+            && parse_fields(&request.body).is_some_and(|body| contains(&body, &self.expected))
+    }
+}
+
+/// A field of a protobuf message, as encoded on the wire.
+#[derive(Debug, PartialEq, Eq)]
+struct Field {
+    number: u32,
+    value: WireValue,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+enum WireValue {
+    Varint(u64),
+    Fixed64([u8; 8]),
+    LengthDelimited(Vec<u8>),
+    Fixed32([u8; 4]),
+}
+
+/// Split an encoded message into its fields, or return `None` if it is not a valid message.
+/// Groups, deprecated since proto2, are not supported.
+fn parse_fields(mut bytes: &[u8]) -> Option<Vec<Field>> {
+    let mut fields = vec![];
+    while !bytes.is_empty() {
+        let key = read_varint(&mut bytes)?;
+        let number = u32::try_from(key >> 3).ok().filter(|number| *number > 0)?;
+        let value = match key & 0b111 {
+            0 => WireValue::Varint(read_varint(&mut bytes)?),
+            1 => WireValue::Fixed64(take(&mut bytes, 8)?.try_into().ok()?),
+            2 => {
+                let length = usize::try_from(read_varint(&mut bytes)?).ok()?;
+                WireValue::LengthDelimited(take(&mut bytes, length)?.to_vec())
+            }
+            5 => WireValue::Fixed32(take(&mut bytes, 4)?.try_into().ok()?),
+            _ => return None,
+        };
+        fields.push(Field { number, value });
+    }
+    Some(fields)
+}
+
+fn read_varint(bytes: &mut &[u8]) -> Option<u64> {
+    let mut value = 0;
+    for shift in (0..64).step_by(7) {
+        let (byte, rest) = bytes.split_first()?;
+        *bytes = rest;
+        value |= u64::from(byte & 0x7f) << shift;
+        if byte & 0x80 == 0 {
+            return Some(value);
+        }
+    }
+    None
+}
+
+fn take<'a>(bytes: &mut &'a [u8], length: usize) -> Option<&'a [u8]> {
+    if bytes.len() < length {
+        return None;
+    }
+    let (head, tail) = bytes.split_at(length);
+    *bytes = tail;
+    Some(head)
+}
+
+/// Whether all the `expected` fields can be found in `actual`.
+fn contains(actual: &[Field], expected: &[Field]) -> bool {
+    expected.iter().all(|expected| {
+        actual.iter().any(|actual| {
+            actual.number == expected.number && value_contains(&actual.value, &expected.value)
+        })
+    })
+}
+
+fn value_contains(actual: &WireValue, expected: &WireValue) -> bool {
+    match (actual, expected) {
+        // Either bytes, a string or a nested message: the wire format does not tell.
+        (WireValue::LengthDelimited(actual), WireValue::LengthDelimited(expected))
+            if actual != expected =>
+        {
+            match (parse_fields(actual), parse_fields(expected)) {
+                (Some(actual), Some(expected)) if !expected.is_empty() => {
+                    contains(&actual, &expected)
+                }
+                _ => false,
+            }
+        }
+        _ => actual == expected,
+    }
+}
@@ -0,0 +1,211 @@
+use crate::MatchedMock;
+use std::fmt::Write;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Usage metrics for a mounted [`Mock`](crate::Mock).
+///
+/// Retrieve them using [`MockGuard::metrics`](crate::MockGuard::metrics) for a scoped mock or
+/// [`MockServer::metrics`](crate::MockServer::metrics) for all the mocks mounted on a server.
+#[derive(Debug, Clone)]
+pub struct MockMetrics {
+    /// The mock these metrics refer to.
+    pub mock: MatchedMock,
+    /// How many requests the mock has matched.
+    pub n_matches: u64,
+    /// How long it took to respond to each matched request, in the order the responses were
+    /// sent, measured from the moment the request was received.
+    ///
+    /// Latencies include any delay injected using
+    /// [`ResponseTemplate::set_delay`](crate::ResponseTemplate::set_delay).
+    /// Requests whose response is still being prepared are not included yet.
+    pub latencies: Vec<Duration>,
+    /// When the mock matched a request for the last time, if it ever did.
+    pub last_matched_at: Option<Instant>,
+}
+
+impl MockMetrics {
+    /// The average of [`MockMetrics::latencies`], `None` if no response has been sent yet.
+    pub fn mean_latency(&self) -> Option<Duration> {
+        if self.latencies.is_empty() {
+            return None;
+        }
+        let total: Duration = self.latencies.iter().sum();
+        Some(total / self.latencies.len() as u32)
+    }
+
+    /// The highest of [`MockMetrics::latencies`], `None` if no response has been sent yet.
+    pub fn max_latency(&self) -> Option<Duration> {
+        self.latencies.iter().max().copied()
+    }
+}
+
+/// Collects the latencies of the responses sent by a mock.
+///
+/// Responses are sent after the lock on the server state has been released, hence it is
+/// shared between the mock and the handlers of the requests it matched.
+#[derive(Debug, Default)]
+pub(crate) struct LatencyRecorder(Mutex<Vec<Duration>>);
+
+impl LatencyRecorder {
+    pub(crate) fn record(&self, latency: Duration) {
+        self.0.lock().unwrap().push(latency);
+    }
+
+    pub(crate) fn latencies(&self) -> Vec<Duration> {
+        self.0.lock().unwrap().clone()
+    }
+}
+
+/// The path of the metrics endpoint exposed by a [`MockServer`](crate::MockServer) when
+/// [`MockServerBuilder::enable_metrics_endpoint`](crate::MockServerBuilder::enable_metrics_endpoint)
+/// is used.
+pub(crate) const METRICS_ENDPOINT_PATH: &str = "/__admin/metrics";
+
+/// Render the metrics of a mock server in the
+/// [Prometheus text format](https://prometheus.io/docs/instrumenting/exposition_formats/).
+pub(crate) fn render_prometheus(
+    n_requests: u64,
+    n_unmatched_requests: u64,
+    mocks: &[MockMetrics],
+) -> String {
+    let mut output = String::new();
+
+    write_header(
+        &mut output,
+        "wiremock_requests_total",
+        "counter",
+        "Requests received by the mock server.",
+    );
+    writeln!(output, "wiremock_requests_total {}", n_requests).unwrap();
+
+    write_header(
+        &mut output,
+        "wiremock_unmatched_requests_total",
+        "counter",
+        "Requests that did not match any mock.",
+    );
+    writeln!(
+        output,
+        "wiremock_unmatched_requests_total {}",
+        n_unmatched_requests
+    )
+    .unwrap();
+
+    write_header(
+        &mut output,
+        "wiremock_mock_matches_total",
+        "counter",
+        "Requests matched by each mock.",
+    );
+    for mock in mocks {
+        writeln!(
+            output,
+            "wiremock_mock_matches_total{{{}}} {}",
+            labels(mock),
+            mock.n_matches
+        )
+        .unwrap();
+    }
+
+    write_header(
+        &mut output,
+        "wiremock_mock_response_latency_seconds",
+        "summary",
+        "Time taken by each mock to respond, including injected delays.",
+    );
+    for mock in mocks {
+        let sum: Duration = mock.latencies.iter().sum();
+        writeln!(
+            output,
+            "wiremock_mock_response_latency_seconds_sum{{{}}} {}",
+            labels(mock),
+            sum.as_secs_f64()
+        )
+        .unwrap();
+        writeln!(
+            output,
+            "wiremock_mock_response_latency_seconds_count{{{}}} {}",
+            labels(mock),
+            mock.latencies.len()
+        )
+        .unwrap();
+    }
+
+    output
+}
+
+fn write_header(output: &mut String, name: &str, kind: &str, help: &str) {
+    writeln!(output, "# HELP {} {}", name, help).unwrap();
+    writeln!(output, "# TYPE {} {}", name, kind).unwrap();
+}
+
+/// The labels identifying a mock in the Prometheus output.
+fn labels(metrics: &MockMetrics) -> String {
+    format!(
+        "mock=\"{}\",position=\"{}\"",
+        escape_label_value(&metrics.mock.to_string()),
+        metrics.mock.position
+    )
+}
+
+fn escape_label_value(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{MatchedMock, MockMetrics};
+    use std::time::Duration;
+
+    fn metrics(latencies: Vec<Duration>) -> MockMetrics {
+        MockMetrics {
+            mock: MatchedMock {
+                name: None,
+                position: 0,
+            },
+            n_matches: latencies.len() as u64,
+            latencies,
+            last_matched_at: None,
+        }
+    }
+
+    #[test]
+    fn latency_statistics_are_none_if_no_response_was_sent() {
+        let metrics = metrics(vec![]);
+
+        assert_eq!(metrics.mean_latency(), None);
+        assert_eq!(metrics.max_latency(), None);
+    }
+
+    #[test]
+    fn latency_statistics_are_computed_over_all_responses() {
+        let metrics = metrics(vec![
+            Duration::from_millis(10),
+            Duration::from_millis(30),
+            Duration::from_millis(20),
+        ]);
+
+        assert_eq!(metrics.mean_latency(), Some(Duration::from_millis(20)));
+        assert_eq!(metrics.max_latency(), Some(Duration::from_millis(30)));
+    }
+
+    #[test]
+    fn prometheus_output_escapes_mock_names() {
+        let mut metrics = metrics(vec![Duration::from_millis(500)]);
+        metrics.mock.name = Some("Say \"hi\"".into());
+
+        let output = super::render_prometheus(2, 1, &[metrics]);
+
+        assert!(output.contains("wiremock_requests_total 2\n"));
+        assert!(output.contains("wiremock_unmatched_requests_total 1\n"));
+        assert!(output
+            .contains("wiremock_mock_matches_total{mock=\"Say \\\"hi\\\"\",position=\"0\"} 1\n"));
+        assert!(output.contains(
+            "wiremock_mock_response_latency_seconds_sum{mock=\"Say \\\"hi\\\"\",position=\"0\"} 0.5\n"
+        ));
+    }
+}
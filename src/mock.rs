@@ -1,9 +1,21 @@
-use crate::respond::Respond;
-use crate::{MockGuard, MockServer, Request, ResponseTemplate};
+use crate::auth_challenge::AuthChallenge;
+use crate::lint::{fingerprint, Fingerprint};
+use crate::matchers::{AndMatcher, NotMatcher, OrMatcher};
+use crate::rate_limit::RateLimit;
+use crate::respond::{AsyncRespond, PendingResponse, Respond, Responder, ResponseContext};
+use crate::response_template::ExpectContinue;
+use crate::stub::Stub;
+use crate::{MockGuard, MockServer, Request, ResponseSequence, ResponseTemplate, WebhookTrigger};
+use std::any::Any;
+use std::collections::HashMap;
 use std::fmt::{Debug, Formatter};
+use std::future::Future;
 use std::ops::{
-    Range, RangeBounds, RangeFrom, RangeFull, RangeInclusive, RangeTo, RangeToInclusive,
+    Bound, Range, RangeBounds, RangeFrom, RangeFull, RangeInclusive, RangeTo, RangeToInclusive,
 };
+use std::panic::Location;
+use std::sync::Arc;
+use std::time::Duration;
 
 /// Anything that implements `Match` can be used to constrain when a [`Mock`] is activated.
 ///
@@ -104,8 +116,91 @@ pub trait Match: Send + Sync {
     /// Given a reference to a [`Request`], determine if it should match or not given
     /// a specific criterion.
     fn matches(&self, request: &Request) -> bool;
+
+    /// Add the path parameters captured by the matcher for `request` to `params` - see
+    /// [`path_template`](crate::matchers::path_template).
+    ///
+    /// It is only called if `request` matched. Matchers wrapping other matchers must forward
+    /// the call to the ones that matched, so that the parameters reach
+    /// [`ResponseContext::path_params`]. It does nothing by default.
+    fn capture_path_params(&self, request: &Request, params: &mut HashMap<String, String>) {
+        let _ = (request, params);
+    }
 }
 
+/// Combinators to build complex matchers out of simpler ones, available on every [`Match`]
+/// implementation.
+///
+/// The combined matcher is a single value: you can store it, reuse it across several
+/// [`Mock`]s and pass it to [`Mock::given`] or to
+/// [`RequestAssertions::received`](crate::RequestAssertions::received).
+/// Tuples of matchers implement [`Match`] as well, matching if all their elements match.
+///
+/// ### Example:
+/// ```rust
+/// use wiremock::{Match, MatchExt, Request};
+/// use wiremock::matchers::{header, header_exists, method, path};
+///
+/// let send_email = (method("POST"), path("/emails"));
+/// let authenticated = header_exists("authorization").or(header("x-api-key", "secret"));
+/// let unauthenticated_email = (send_email, authenticated.not());
+///
+/// let request = Request::builder().method("POST").path("/emails").build();
+/// assert!(unauthenticated_email.matches(&request));
+///
+/// let request = Request::builder()
+///     .method("POST")
+///     .path("/emails")
+///     .header("x-api-key", "secret")
+///     .build();
+/// assert!(!unauthenticated_email.matches(&request));
+/// ```
+pub trait MatchExt: Match + Sized {
+    /// Match if both `self` and `other` match.
+    fn and<M: Match>(self, other: M) -> AndMatcher<Self, M> {
+        AndMatcher(self, other)
+    }
+
+    /// Match if either `self` or `other` matches.
+    fn or<M: Match>(self, other: M) -> OrMatcher<Self, M> {
+        OrMatcher(self, other)
+    }
+
+    /// Match if `self` does not match.
+    fn not(self) -> NotMatcher<Self> {
+        NotMatcher(self)
+    }
+}
+
+impl<M: Match> MatchExt for M {}
+
+/// Tuples of matchers match if all their elements match, checked in order.
+macro_rules! impl_match_for_tuple {
+    ($($matcher:ident),+) => {
+        impl<$($matcher: Match),+> Match for ($($matcher,)+) {
+            fn matches(&self, request: &Request) -> bool {
+                #[allow(non_snake_case)]
+                let ($($matcher,)+) = self;
+                $($matcher.matches(request))&&+
+            }
+
+            fn capture_path_params(&self, request: &Request, params: &mut HashMap<String, String>) {
+                #[allow(non_snake_case)]
+                let ($($matcher,)+) = self;
+                $($matcher.capture_path_params(request, params);)+
+            }
+        }
+    };
+}
+
+impl_match_for_tuple!(A, B);
+impl_match_for_tuple!(A, B, C);
+impl_match_for_tuple!(A, B, C, D);
+impl_match_for_tuple!(A, B, C, D, E);
+impl_match_for_tuple!(A, B, C, D, E, F);
+impl_match_for_tuple!(A, B, C, D, E, F, G);
+impl_match_for_tuple!(A, B, C, D, E, F, G, H);
+
 /// Wrapper around a `Match` trait object.
 ///
 /// We need the wrapper to provide a (fake) implementation of `Debug`,
@@ -114,11 +209,28 @@ pub trait Match: Send + Sync {
 ///
 /// We wouldn't need this if `bastion` didn't require `Debug` as a trait bound for its Message trait
 /// or if Rust automatically implemented `Debug` for closures.
-pub(crate) struct Matcher(Box<dyn Match>);
+pub(crate) struct Matcher {
+    matcher: Box<dyn Match>,
+    /// Used to detect mocks shadowing each other - see [`crate::MockServer::lint_mocks`].
+    pub(crate) fingerprint: Option<Fingerprint>,
+}
+
+impl Matcher {
+    fn new<M: Match + 'static>(matcher: M) -> Self {
+        Self {
+            fingerprint: fingerprint(&matcher),
+            matcher: Box::new(matcher),
+        }
+    }
+}
 
 impl Match for Matcher {
     fn matches(&self, request: &Request) -> bool {
-        self.0.matches(request)
+        self.matcher.matches(request)
+    }
+
+    fn capture_path_params(&self, request: &Request, params: &mut HashMap<String, String>) {
+        self.matcher.capture_path_params(request, params)
     }
 }
 
@@ -256,13 +368,17 @@ impl Debug for Matcher {
 #[must_use = "`Mock`s have to be mounted or registered with a `MockServer` to become effective"]
 pub struct Mock {
     pub(crate) matchers: Vec<Matcher>,
-    pub(crate) response: Box<dyn Respond>,
+    pub(crate) response: Responder,
     /// Maximum number of times (inclusive) we should return a response from this Mock on
     /// matching requests.
     /// If `None`, there is no cap and we will respond to all incoming matching requests.
     /// If `Some(max_n_matches)`, when `max_n_matches` matching incoming requests have been processed,
     /// [`crate::mounted_mock::MountedMock::matches`] should start returning `false`, regardless of the incoming request.
     pub(crate) max_n_matches: Option<u64>,
+    /// If set, the mock only matches the requests that satisfy its matchers when their
+    /// position among them - starting from `1` - falls within this range.
+    /// See [`Mock::only_for_request_range`].
+    pub(crate) request_range: Option<(Bound<u64>, Bound<u64>)>,
     /// Allows prioritizing a Mock over another one.
     /// `1` is the highest priority, `255` the lowest, default to `5`.
     /// When priority is the same, it fallbacks to insertion order.
@@ -272,6 +388,35 @@ pub struct Mock {
     pub(crate) name: Option<String>,
     /// The expectation is satisfied if the number of incoming requests falls within `expectation_range`.
     pub(crate) expectation_range: Times,
+    /// If set, the expectations are not satisfied if two consecutive matching requests were
+    /// received closer than this - see [`Mock::expect_min_interval`].
+    pub(crate) min_interval: Option<Duration>,
+    /// If set, the expectations are not satisfied if the body of a response took longer than
+    /// this to be consumed - see [`Mock::expect_response_consumed_within`].
+    pub(crate) consumption_budget: Option<Duration>,
+    /// If set, matching requests beyond the allowed budget get a throttled response instead
+    /// of the one generated by `response`.
+    pub(crate) rate_limit: Option<RateLimit>,
+    /// If set, requests without valid credentials get a challenge instead of the response
+    /// generated by `response`.
+    pub(crate) auth_challenge: Option<AuthChallenge>,
+    /// How requests with an `Expect: 100-continue` header are handled, if the mock responds
+    /// with a [`ResponseTemplate`] that says so.
+    pub(crate) expect_continue: Option<ExpectContinue>,
+    /// The [`Stub`] this mock was built from, if any.
+    /// It allows the mock to be exported using [`MockServer::export_stubs`].
+    pub(crate) stub: Option<Stub>,
+    /// Where [`Mock::expect_never`] was called, if the mock must never match.
+    /// Used to point at the offending test line when the expectation is not satisfied.
+    pub(crate) forbidden_at: Option<&'static Location<'static>>,
+    /// Where the mock was mounted or registered.
+    /// Used in error messages to point at the test line that set up the mock.
+    pub(crate) registered_at: Option<&'static Location<'static>>,
+    /// The outbound requests fired every time the mock matches a request.
+    pub(crate) webhooks: Vec<WebhookTrigger>,
+    /// Whether the expectations are verified when the [`MockServer`] (or the guard of a scoped
+    /// mock) is dropped - see [`Mock::skip_drop_verification`].
+    pub(crate) verify_on_drop: bool,
 }
 
 /// A fluent builder to construct a [`Mock`] instance given matchers and a [`ResponseTemplate`].
@@ -285,9 +430,7 @@ impl Mock {
     ///
     /// It returns an instance of [`MockBuilder`].
     pub fn given<M: 'static + Match>(matcher: M) -> MockBuilder {
-        MockBuilder {
-            matchers: vec![Matcher(Box::new(matcher))],
-        }
+        MockBuilder { matchers: vec![] }.and(matcher)
     }
 
     /// Specify an upper limit to the number of times you would like this [`Mock`] to respond to
@@ -343,6 +486,67 @@ impl Mock {
         self
     }
 
+    /// Only match the requests that satisfy the matchers of this [`Mock`] if their position
+    /// among them falls within `range` - requests are numbered from `1`.
+    ///
+    /// E.g. with `only_for_request_range(2..=3)` the first request that satisfies the
+    /// matchers is ignored, the second and the third are matched, and all the following ones
+    /// are ignored again. Ignored requests are handled by the other mocks mounted on the
+    /// [`MockServer`], if any match, and they do not count towards the [`expect`] of this
+    /// mock.
+    ///
+    /// It lets you make the first call(s) behave differently without resorting to
+    /// sequences or stateful responders.
+    ///
+    /// It panics if `range` includes `0`.
+    ///
+    /// ### Example:
+    ///
+    /// ```rust
+    /// use wiremock::{MockServer, Mock, ResponseTemplate};
+    /// use wiremock::matchers::method;
+    ///
+    /// #[async_std::main]
+    /// async fn main() {
+    ///     // Arrange
+    ///     let mock_server = MockServer::start().await;
+    ///
+    ///     // The second and third requests fail...
+    ///     Mock::given(method("GET"))
+    ///         .respond_with(ResponseTemplate::new(503))
+    ///         .only_for_request_range(2..=3)
+    ///         .with_priority(1)
+    ///         .expect(2)
+    ///         .mount(&mock_server)
+    ///         .await;
+    ///     // ...all the others succeed.
+    ///     Mock::given(method("GET"))
+    ///         .respond_with(ResponseTemplate::new(200))
+    ///         .mount(&mock_server)
+    ///         .await;
+    ///
+    ///     // Act
+    ///     let mut statuses = vec![];
+    ///     for _ in 0..4 {
+    ///         let status = surf::get(&mock_server.uri()).await.unwrap().status();
+    ///         statuses.push(u16::from(status));
+    ///     }
+    ///
+    ///     // Assert
+    ///     assert_eq!(statuses, vec![200, 503, 503, 200]);
+    /// }
+    /// ```
+    ///
+    /// [`expect`]: Mock::expect
+    pub fn only_for_request_range<R: RangeBounds<u64>>(mut self, range: R) -> Mock {
+        assert!(
+            !range.contains(&0),
+            "Requests are numbered from 1: the range must not include 0!"
+        );
+        self.request_range = Some((range.start_bound().cloned(), range.end_bound().cloned()));
+        self
+    }
+
     /// Specify a priority for this [`Mock`].
     /// Use this when you mount many [`Mock`] in a [`MockServer`]
     /// and those mocks have interlaced request matching conditions
@@ -394,6 +598,175 @@ impl Mock {
         self
     }
 
+    /// Limit the rate at which this [`Mock`] responds: at most `max_requests` matching requests
+    /// are served within any time `window`.
+    /// Matching requests beyond the budget get `throttled_response` back (e.g. a `429 Too Many Requests`),
+    /// until enough time has passed for the budget to be replenished.
+    ///
+    /// Throttled requests still count as matches for the purpose of [`Mock::expect`] and
+    /// [`Mock::up_to_n_times`].
+    /// If you want clients to know when to retry, add a `Retry-After` header to `throttled_response`.
+    ///
+    /// ### Example:
+    ///
+    /// ```rust
+    /// use wiremock::{MockServer, Mock, ResponseTemplate};
+    /// use wiremock::matchers::method;
+    /// use std::time::Duration;
+    ///
+    /// #[async_std::main]
+    /// async fn main() {
+    ///     // Arrange
+    ///     let mock_server = MockServer::start().await;
+    ///
+    ///     Mock::given(method("GET"))
+    ///         .respond_with(ResponseTemplate::new(200))
+    ///         .with_rate_limit(
+    ///             2,
+    ///             Duration::from_secs(60),
+    ///             ResponseTemplate::new(429).insert_header("Retry-After", "60"),
+    ///         )
+    ///         .mount(&mock_server)
+    ///         .await;
+    ///
+    ///     // Act
+    ///     let mut statuses = vec![];
+    ///     for _ in 0..3 {
+    ///         let status = surf::get(&mock_server.uri()).await.unwrap().status();
+    ///         statuses.push(status);
+    ///     }
+    ///
+    ///     // Assert - the third request exceeds the budget
+    ///     assert_eq!(statuses, vec![200, 200, 429]);
+    /// }
+    /// ```
+    pub fn with_rate_limit(
+        mut self,
+        max_requests: u64,
+        window: Duration,
+        throttled_response: ResponseTemplate,
+    ) -> Mock {
+        assert!(
+            max_requests > 0,
+            "max_requests must be strictly greater than 0!"
+        );
+        self.rate_limit = Some(RateLimit {
+            max_requests,
+            window,
+            throttled_response,
+        });
+        self
+    }
+
+    /// Require clients to authenticate before getting the response of this [`Mock`].
+    ///
+    /// Matching requests without valid credentials get the challenge of the chosen
+    /// authentication scheme - e.g. a `401 Unauthorized` response with a `WWW-Authenticate`
+    /// header. Requests retried with valid credentials get the response of the mock.
+    /// Check out [`AuthChallenge`] for the supported schemes.
+    ///
+    /// Challenged requests still count as matches for the purpose of [`Mock::expect`] and
+    /// [`Mock::up_to_n_times`]: use [`matchers::header_exists`](crate::matchers::header_exists)
+    /// on `Authorization` if you only want to count authenticated requests.
+    ///
+    /// ### Example:
+    ///
+    /// ```rust
+    /// use wiremock::{AuthChallenge, MockServer, Mock, ResponseTemplate};
+    /// use wiremock::matchers::method;
+    ///
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     // Arrange
+    ///     let mock_server = MockServer::start().await;
+    ///
+    ///     Mock::given(method("GET"))
+    ///         .respond_with(ResponseTemplate::new(200))
+    ///         .with_auth_challenge(AuthChallenge::basic("api", "user", "password"))
+    ///         .mount(&mock_server)
+    ///         .await;
+    ///     let client = reqwest::Client::new();
+    ///
+    ///     // Act
+    ///     let challenge = client.get(mock_server.uri()).send().await.unwrap();
+    ///     let response = client
+    ///         .get(mock_server.uri())
+    ///         .basic_auth("user", Some("password"))
+    ///         .send()
+    ///         .await
+    ///         .unwrap();
+    ///
+    ///     // Assert
+    ///     assert_eq!(challenge.status(), 401);
+    ///     assert_eq!(
+    ///         challenge.headers()["WWW-Authenticate"],
+    ///         r#"Basic realm="api", charset="UTF-8""#
+    ///     );
+    ///     assert_eq!(response.status(), 200);
+    /// }
+    /// ```
+    pub fn with_auth_challenge(mut self, challenge: AuthChallenge) -> Mock {
+        self.auth_challenge = Some(challenge);
+        self
+    }
+
+    /// Fire `webhook` every time this [`Mock`] matches a request, once the response has been
+    /// sent.
+    ///
+    /// Call it multiple times to fire several webhooks for each matched request.
+    /// Check out [`WebhookTrigger`] for more details.
+    ///
+    /// ### Example:
+    ///
+    /// ```rust
+    /// use wiremock::{MockServer, Mock, ResponseTemplate, WebhookTrigger};
+    /// use wiremock::matchers::{method, path};
+    /// use serde_json::{json, Value};
+    /// use std::time::Duration;
+    ///
+    /// #[async_std::main]
+    /// async fn main() {
+    ///     // Arrange
+    ///     let payment_provider = MockServer::start().await;
+    ///     // Our application, waiting to be notified about the outcome of the payment.
+    ///     let application = MockServer::start().await;
+    ///
+    ///     Mock::given(method("POST"))
+    ///         .and(path("/payments"))
+    ///         .respond_with(ResponseTemplate::new(202))
+    ///         .with_webhook(
+    ///             WebhookTrigger::post(format!("{}/payments/callback", application.uri()))
+    ///                 .set_body_json(json!({"status": "succeeded"}))
+    ///                 .set_delay(Duration::from_millis(10)),
+    ///         )
+    ///         .mount(&payment_provider)
+    ///         .await;
+    ///
+    ///     // Act
+    ///     let status = surf::post(format!("{}/payments", payment_provider.uri()))
+    ///         .await
+    ///         .unwrap()
+    ///         .status();
+    ///     assert_eq!(status, 202);
+    ///
+    ///     // Assert - the webhook is fired in the background
+    ///     let mut callbacks = vec![];
+    ///     for _ in 0..100 {
+    ///         callbacks = application.received_requests().await.unwrap();
+    ///         if !callbacks.is_empty() {
+    ///             break;
+    ///         }
+    ///         async_std::task::sleep(Duration::from_millis(10)).await;
+    ///     }
+    ///     assert_eq!(callbacks[0].url.path(), "/payments/callback");
+    ///     assert_eq!(callbacks[0].body_json::<Value>().unwrap()["status"], "succeeded");
+    /// }
+    /// ```
+    pub fn with_webhook(mut self, webhook: WebhookTrigger) -> Mock {
+        self.webhooks.push(webhook);
+        self
+    }
+
     /// Set an expectation on the number of times this [`Mock`] should match in the current
     /// test case.
     /// Expectations are verified when the [`MockServer`] is shutting down: if the expectation
@@ -457,6 +830,182 @@ impl Mock {
         self
     }
 
+    /// Expect this [`Mock`] to never match in the current test case.
+    ///
+    /// It is equivalent to `.expect(0)`, but the panic message shown when the expectation is not
+    /// satisfied is more helpful: it includes a full dump of every request that matched the mock
+    /// and the location of the line in your test that called `expect_never`.
+    ///
+    /// ### Example:
+    ///
+    /// ```should_panic
+    /// use wiremock::{MockServer, Mock, ResponseTemplate};
+    /// use wiremock::matchers::method;
+    ///
+    /// #[async_std::main]
+    /// async fn main() {
+    ///     // Arrange
+    ///     let mock_server = MockServer::start().await;
+    ///
+    ///     Mock::given(method("DELETE"))
+    ///         .respond_with(ResponseTemplate::new(204))
+    ///         .expect_never()
+    ///         .named("No deletions")
+    ///         .mount(&mock_server)
+    ///         .await;
+    ///
+    ///     // Act
+    ///     surf::delete(&mock_server.uri()).await.unwrap();
+    ///
+    ///     // Assert
+    ///     // The `MockServer` panics on shutdown, showing the DELETE request
+    ///     // and where the mock was defined.
+    /// }
+    /// ```
+    #[track_caller]
+    pub fn expect_never(mut self) -> Self {
+        self.expectation_range = 0.into();
+        self.forbidden_at = Some(Location::caller());
+
+        self
+    }
+
+    /// Expect the requests matching this [`Mock`] to be spaced out by at least `interval` -
+    /// e.g. to check that your client honours its retry backoff.
+    ///
+    /// The interval between two consecutive matching requests is measured using their
+    /// arrival time, according to the clock of the [`MockServer`] - see
+    /// [`Request::received_at`]. Combine it with
+    /// [`MockServerBuilder::virtual_clock`](crate::MockServerBuilder::virtual_clock) to keep
+    /// your test fast.
+    ///
+    /// The expectation is verified alongside the one set with [`Mock::expect`]: the
+    /// [`MockServer`] panics on shutdown if it is not satisfied, pointing at the two requests
+    /// that were received too close to each other.
+    ///
+    /// ### Example:
+    ///
+    /// ```should_panic
+    /// use wiremock::{MockServer, Mock, ResponseTemplate};
+    /// use wiremock::matchers::method;
+    /// use std::time::Duration;
+    ///
+    /// #[async_std::main]
+    /// async fn main() {
+    ///     // Arrange
+    ///     let mock_server = MockServer::start().await;
+    ///
+    ///     Mock::given(method("GET"))
+    ///         .respond_with(ResponseTemplate::new(503))
+    ///         .expect(3)
+    ///         .expect_min_interval(Duration::from_millis(500))
+    ///         .named("Retries")
+    ///         .mount(&mock_server)
+    ///         .await;
+    ///
+    ///     // Act - the client retries straight away
+    ///     for _ in 0..3 {
+    ///         surf::get(&mock_server.uri()).await.unwrap();
+    ///     }
+    ///
+    ///     // Assert
+    ///     // The `MockServer` panics on shutdown: the requests were not spaced out
+    ///     // by at least 500ms.
+    /// }
+    /// ```
+    pub fn expect_min_interval(mut self, interval: Duration) -> Self {
+        self.min_interval = Some(interval);
+
+        self
+    }
+
+    /// Expect the client to finish reading the body of each response sent by this [`Mock`]
+    /// within `budget` - e.g. to catch clients that hold on to responses without consuming
+    /// their body, keeping the connection busy.
+    ///
+    /// A body counts as consumed once the [`MockServer`] has handed it over to the connection
+    /// in full. The budget is measured using the wall clock, from the moment the response is
+    /// ready to be sent - after its delay, if any.
+    /// A response whose connection is closed before its body has been sent in full - e.g.
+    /// because the client dropped it - is never consumed.
+    ///
+    /// The operating system buffers what is written to the connection: a client that does not
+    /// read the response only stalls the server once those buffers are full. Use bodies of a
+    /// few megabytes to detect clients that never read them.
+    /// `HEAD` requests are not checked, as their responses have no body.
+    ///
+    /// The expectation is verified alongside the one set with [`Mock::expect`]. Responses
+    /// that are still being sent, within the budget, do not fail the verification.
+    ///
+    /// ### Example:
+    ///
+    /// ```should_panic
+    /// use wiremock::{MockServer, Mock, ResponseTemplate};
+    /// use wiremock::matchers::method;
+    /// use std::time::Duration;
+    ///
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     // Arrange
+    ///     let mock_server = MockServer::start().await;
+    ///
+    ///     Mock::given(method("GET"))
+    ///         .respond_with(ResponseTemplate::new(200).set_body_bytes(vec![0; 16 * 1024 * 1024]))
+    ///         .expect_response_consumed_within(Duration::from_millis(200))
+    ///         .mount(&mock_server)
+    ///         .await;
+    ///
+    ///     // Act - the client holds on to the response without reading its body
+    ///     let _response = reqwest::get(mock_server.uri()).await.unwrap();
+    ///     tokio::time::sleep(Duration::from_millis(300)).await;
+    ///
+    ///     // Assert
+    ///     // The `MockServer` panics on shutdown: the body was not consumed in time.
+    /// }
+    /// ```
+    pub fn expect_response_consumed_within(mut self, budget: Duration) -> Self {
+        self.consumption_budget = Some(budget);
+
+        self
+    }
+
+    /// Do not verify the expectations of this [`Mock`] when the [`MockServer`] - or the
+    /// [`MockGuard`] of a scoped mock - is dropped.
+    ///
+    /// The expectations are still checked when you verify them explicitly, with
+    /// [`MockServer::verify`] or [`MockServer::shutdown`]: use it for mocks mounted on
+    /// long-lived shared servers, verified at the points of your choosing rather than when
+    /// the server goes out of scope.
+    /// Use [`MockServerBuilder::verify_on_drop`](crate::MockServerBuilder::verify_on_drop) to
+    /// disable the verification on drop for all the mocks of a server.
+    ///
+    /// ### Example:
+    ///
+    /// ```rust
+    /// use wiremock::{MockServer, Mock, ResponseTemplate};
+    /// use wiremock::matchers::method;
+    ///
+    /// #[async_std::main]
+    /// async fn main() {
+    ///     // Arrange
+    ///     let mock_server = MockServer::start().await;
+    ///
+    ///     Mock::given(method("POST"))
+    ///         .respond_with(ResponseTemplate::new(201))
+    ///         .expect(1)
+    ///         .skip_drop_verification()
+    ///         .mount(&mock_server)
+    ///         .await;
+    ///
+    ///     // Assert
+    ///     // No POST request was made, but the `MockServer` does not panic on shutdown.
+    /// }
+    /// ```
+    pub fn skip_drop_verification(mut self) -> Self {
+        self.verify_on_drop = false;
+        self
+    }
+
     /// Assign a name to your mock.  
     ///
     /// The mock name will be used in error messages (e.g. if the mock expectation
@@ -523,8 +1072,11 @@ impl Mock {
     ///
     /// [`register`]: MockServer::register
     /// [`mount`]: Mock::mount
-    pub async fn mount(self, server: &MockServer) {
-        server.register(self).await;
+    // `#[track_caller]` is not supported on `async fn`s: we return a future instead, to
+    // capture the caller location before it is polled.
+    #[track_caller]
+    pub fn mount(self, server: &MockServer) -> impl Future<Output = ()> + '_ {
+        server.register(self)
     }
 
     /// Mount a [`Mock`] as **scoped**  on an instance of [`MockServer`].
@@ -541,13 +1093,13 @@ impl Mock {
     ///
     /// When expectations of a scoped [`Mock`] are not verified, it will trigger a panic - just like a normal [`Mock`].
     /// Due to [limitations](https://internals.rust-lang.org/t/should-drop-glue-use-track-caller/13682) in Rust's [`Drop`] trait,
-    /// the panic message will not include the filename and the line location
-    /// where the corresponding [`MockGuard`] was dropped - it will point into `wiremock`'s source code.  
+    /// the location of the panic will not be the line where the corresponding [`MockGuard`] was dropped - it will
+    /// point into `wiremock`'s source code.
     ///
-    /// This can be an issue when you are using more than one scoped [`Mock`] in a single test - which of them panicked?  
-    /// To improve your debugging experience it is strongly recommended to use [`Mock::named`] to assign a unique
-    /// identifier to your scoped [`Mock`]s, which will in turn be referenced in the panic message if their expectations are
-    /// not met.
+    /// To help you find out which scoped [`Mock`] panicked, the panic message includes the filename and the
+    /// line location where the [`Mock`] was mounted. You can also use [`Mock::named`] to assign a unique
+    /// identifier to your scoped [`Mock`]s, which will in turn be referenced in the panic message if their
+    /// expectations are not met.
     ///
     /// # Example:
     ///
@@ -623,14 +1175,44 @@ impl Mock {
     /// ```
     ///
     /// [`mount`]: Mock::mount
-    pub async fn mount_as_scoped(self, server: &MockServer) -> MockGuard {
-        server.register_as_scoped(self).await
+    #[track_caller]
+    pub fn mount_as_scoped(self, server: &MockServer) -> impl Future<Output = MockGuard> + '_ {
+        server.register_as_scoped(self)
     }
 
     /// Given a [`Request`] build an instance a [`ResponseTemplate`] using
     /// the responder associated with the `Mock`.
-    pub(crate) fn response_template(&self, request: &Request) -> ResponseTemplate {
-        self.response.respond(request)
+    pub(crate) fn response_template(
+        &self,
+        request: &Request,
+        ctx: ResponseContext,
+    ) -> PendingResponse {
+        // Templates can depend on the conditional and range headers of the request - e.g.
+        // with `ResponseTemplate::with_etag`.
+        let (method, headers) = (request.method.clone(), request.headers.clone());
+        // Bodies read from a file are loaded off the runtime of the server, hence asynchronously.
+        match self.response.respond(request, ctx) {
+            PendingResponse::Ready(template) if !template.has_body_file() => {
+                PendingResponse::Ready(Box::new(template.negotiate(&method, &headers)))
+            }
+            PendingResponse::Ready(template) => PendingResponse::Deferred(Box::pin(async move {
+                template.load_body_file().await.negotiate(&method, &headers)
+            })),
+            PendingResponse::Deferred(template) => {
+                PendingResponse::Deferred(Box::pin(async move {
+                    template
+                        .await
+                        .load_body_file()
+                        .await
+                        .negotiate(&method, &headers)
+                }))
+            }
+        }
+    }
+
+    /// Add a request matcher to a mock that has already been built.
+    pub(crate) fn add_matcher<M: 'static + Match>(&mut self, matcher: M) {
+        self.matchers.push(Matcher::new(matcher));
     }
 }
 
@@ -641,7 +1223,7 @@ impl MockBuilder {
     ///
     /// [`matchers`]: crate::matchers
     pub fn and<M: Match + 'static>(mut self, matcher: M) -> Self {
-        self.matchers.push(Matcher(Box::new(matcher)));
+        self.matchers.push(Matcher::new(matcher));
         self
     }
 
@@ -654,13 +1236,122 @@ impl MockBuilder {
     /// [`register`]: MockServer::register
     /// [`mount`]: Mock::mount
     pub fn respond_with<R: Respond + 'static>(self, responder: R) -> Mock {
+        // The server decides how to answer `Expect: 100-continue` before generating the
+        // response: it can only know about it ahead of time for plain templates.
+        let expect_continue = (&responder as &dyn Any)
+            .downcast_ref::<ResponseTemplate>()
+            .and_then(ResponseTemplate::expect_continue);
+        let mut mock = self.into_mock(Responder::Sync(Arc::new(responder)));
+        mock.expect_continue = expect_continue;
+        mock
+    }
+
+    /// Establish what [`AsyncRespond`] should be used to generate a response when an incoming
+    /// request matches.
+    ///
+    /// It works like [`respond_with`], but the responder can `.await` while building the response -
+    /// check [`AsyncRespond`]'s documentation for more details and examples.
+    ///
+    /// [`respond_with`]: MockBuilder::respond_with
+    pub fn respond_with_async<R: AsyncRespond + 'static>(self, responder: R) -> Mock {
+        self.into_mock(Responder::Async(Arc::new(responder)))
+    }
+
+    /// Write `bytes` to the socket as the response to the matching requests, bypassing the
+    /// HTTP implementation of the [`MockServer`] - an escape hatch to test how the parser of
+    /// your client copes with malformed status lines, illegal headers or plain garbage.
+    ///
+    /// The bytes are sent as they are: nothing is added nor validated. The connection is
+    /// closed once they have been written - a body without a `Content-Length` header ends
+    /// there.
+    ///
+    /// Raw responses can only be sent over plain-text HTTP/1.1 connections: over HTTP/2 or
+    /// TLS, the connection is closed without writing anything.
+    ///
+    /// ### Example:
+    /// ```rust
+    /// use wiremock::{MockServer, Mock};
+    /// use wiremock::matchers::method;
+    ///
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     // Arrange
+    ///     let mock_server = MockServer::start().await;
+    ///     Mock::given(method("GET"))
+    ///         .respond_with_raw_bytes(b"HTTP/1.1 200 OK\r\nX-Broken header\r\n\r\n")
+    ///         .mount(&mock_server)
+    ///         .await;
+    ///
+    ///     // Act
+    ///     let outcome = reqwest::get(&mock_server.uri()).await;
+    ///
+    ///     // Assert - the header line has no colon
+    ///     assert!(outcome.is_err());
+    /// }
+    /// ```
+    pub fn respond_with_raw_bytes(self, bytes: impl AsRef<[u8]>) -> Mock {
+        self.respond_with(ResponseTemplate::raw(bytes.as_ref().to_vec()))
+    }
+
+    /// Respond to the matching requests with the responses of `sequence`, one after the other.
+    ///
+    /// Check [`ResponseSequence`]'s documentation for what happens once the sequence is
+    /// exhausted.
+    ///
+    /// ### Example:
+    /// ```rust
+    /// use wiremock::{MockServer, Mock, ResponseSequence, ResponseTemplate};
+    /// use wiremock::matchers::method;
+    ///
+    /// #[async_std::main]
+    /// async fn main() {
+    ///     // Arrange
+    ///     let mock_server = MockServer::start().await;
+    ///     Mock::given(method("GET"))
+    ///         .respond_with_sequence(
+    ///             ResponseSequence::new([ResponseTemplate::new(200), ResponseTemplate::new(304)])
+    ///                 .then_loop(),
+    ///         )
+    ///         .mount(&mock_server)
+    ///         .await;
+    ///
+    ///     // Act
+    ///     let mut statuses = vec![];
+    ///     for _ in 0..3 {
+    ///         let status = surf::get(&mock_server.uri()).await.unwrap().status();
+    ///         statuses.push(u16::from(status));
+    ///     }
+    ///
+    ///     // Assert
+    ///     assert_eq!(statuses, vec![200, 304, 200]);
+    /// }
+    /// ```
+    pub fn respond_with_sequence(self, sequence: ResponseSequence) -> Mock {
+        let max_n_matches = sequence.max_n_matches();
+        let mut mock = self.respond_with(sequence);
+        mock.max_n_matches = max_n_matches;
+        mock
+    }
+
+    fn into_mock(self, response: Responder) -> Mock {
         Mock {
             matchers: self.matchers,
-            response: Box::new(responder),
+            response,
             max_n_matches: None,
+            request_range: None,
             priority: 5,
             name: None,
             expectation_range: Times(TimesEnum::Unbounded(RangeFull)),
+            min_interval: None,
+            consumption_budget: None,
+            rate_limit: None,
+            auth_challenge: None,
+            expect_continue: None,
+            stub: None,
+            forbidden_at: None,
+            registered_at: None,
+            webhooks: vec![],
+            verify_on_drop: true,
         }
     }
 }
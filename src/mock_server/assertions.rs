@@ -0,0 +1,59 @@
+use crate::{Match, MockServer, Times};
+
+/// Assertions on the requests received by a [`MockServer`], returned by [`MockServer::assert`].
+///
+/// They let you check how your client behaved after the fact, spy-style, without setting
+/// expectations on the mounted [`Mock`](crate::Mock)s upfront.
+/// They require request recording to be enabled (the default) - see
+/// [`MockServerBuilder::disable_request_recording`](crate::MockServerBuilder::disable_request_recording).
+#[derive(Debug)]
+pub struct RequestAssertions<'a> {
+    server: &'a MockServer,
+}
+
+impl<'a> RequestAssertions<'a> {
+    pub(super) fn new(server: &'a MockServer) -> Self {
+        Self { server }
+    }
+
+    /// Assert that the number of received requests satisfying `matcher` falls within `times` -
+    /// either an exact number or a range, like [`Mock::expect`](crate::Mock::expect).
+    ///
+    /// If it does not, it panics with a message listing the requests that satisfied `matcher`
+    /// and those that did not, so that you can spot what your client got wrong.
+    ///
+    /// ### Example:
+    /// ```rust
+    /// use wiremock::{MockServer, Mock, Request, ResponseTemplate};
+    /// use wiremock::matchers::{any, path};
+    ///
+    /// #[async_std::main]
+    /// async fn main() {
+    ///     // Arrange
+    ///     let mock_server = MockServer::start().await;
+    ///     Mock::given(any())
+    ///         .respond_with(ResponseTemplate::new(202))
+    ///         .mount(&mock_server)
+    ///         .await;
+    ///
+    ///     // Act
+    ///     surf::post(format!("{}/emails", mock_server.uri())).await.unwrap();
+    ///     surf::get(format!("{}/emails", mock_server.uri())).await.unwrap();
+    ///
+    ///     // Assert
+    ///     mock_server.assert().received(2, path("/emails")).await;
+    ///     let is_post = |request: &Request| request.method == "POST";
+    ///     mock_server.assert().received(1, is_post).await;
+    ///     mock_server.assert().received(0, path("/unsubscribe")).await;
+    /// }
+    /// ```
+    pub async fn received<T, M>(&self, times: T, matcher: M)
+    where
+        T: Into<Times>,
+        M: Match,
+    {
+        if let Err(message) = self.server.check_received(&times.into(), &matcher).await {
+            panic!("{}", message);
+        }
+    }
+}
@@ -1,15 +1,36 @@
-use crate::mock_server::hyper::run_server;
+use crate::chaos::Chaos;
+use crate::events::{EventBus, ServerEvent};
+use crate::fixtures::{self, FixtureWatcher};
+use crate::har;
+use crate::in_flight::InFlightCounter;
+use crate::journal::MatchedMock;
+use crate::lint::MockLint;
+use crate::metrics::{MockMetrics, METRICS_ENDPOINT_PATH};
+use crate::mock_server::executor::Executor;
+use crate::mock_server::hyper::{run_redirect_server, run_server};
 use crate::mock_set::MockId;
-use crate::mock_set::MountedMockSet;
-use crate::request::BodyPrintLimit;
-use crate::{mock::Mock, verification::VerificationOutcome, Request};
-use http_body_util::Full;
-use hyper::body::Bytes;
+use crate::mock_set::{HandledRequest, MountedMockSet};
+use crate::request::{BodyPrintLimit, Redaction};
+use crate::respond::{PendingResponse, Responder};
+use crate::response_template::ExpectContinue;
+use crate::session::SessionKey;
+use crate::stub::Stub;
+use crate::tls::{CertificateAuthority, TlsAcceptor};
+use crate::{
+    mock::Mock,
+    verification::{VerificationOutcome, VerificationReport},
+    JournalEntry, Match, Request, Respond, ResponseTemplate, Times, VirtualClock,
+};
+use futures::Stream;
+use http::header::HeaderName;
+use std::collections::HashSet;
 use std::fmt::{Debug, Write};
 use std::net::{SocketAddr, TcpListener, TcpStream};
+use std::path::Path;
 use std::pin::pin;
 use std::sync::atomic::AtomicBool;
-use std::sync::Arc;
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::{Duration, Instant, SystemTime};
 use tokio::sync::Notify;
 use tokio::sync::RwLock;
 
@@ -22,82 +43,516 @@ use tokio::sync::RwLock;
 pub(crate) struct BareMockServer {
     state: Arc<RwLock<MockServerState>>,
     server_address: SocketAddr,
-    // When `_shutdown_trigger` gets dropped the listening server terminates gracefully.
-    _shutdown_trigger: tokio::sync::watch::Sender<()>,
+    /// `https` if the listeners serving the mocks terminate TLS, `http` otherwise.
+    scheme: &'static str,
+    /// Shared with the listeners serving the mocks, if they terminate TLS.
+    #[cfg(feature = "tls")]
+    tls: Option<TlsAcceptor>,
+    /// Issues the certificates presented in the tunnels, if TLS interception is enabled.
+    #[cfg(feature = "tls")]
+    proxy_authority: Option<Arc<CertificateAuthority>>,
+    /// The addresses of all the listeners, starting with `server_address`.
+    addresses: Vec<SocketAddr>,
+    /// The address of the listener redirecting to `server_address`, if any.
+    http_redirect_address: Option<SocketAddr>,
+    /// How many requests are currently being served, across all mocks.
+    in_flight: Arc<InFlightCounter>,
+    // When `shutdown_trigger` gets dropped the listening server terminates gracefully.
+    // Sending a grace period drains the in-flight requests first - see `BareMockServer::shutdown`.
+    shutdown_trigger: tokio::sync::watch::Sender<Option<Duration>>,
+    /// Set, once the server has stopped, to the number of requests that were still in-flight.
+    stopped: tokio::sync::watch::Receiver<Option<usize>>,
 }
 
 /// The elements of [`BareMockServer`] that are affected by each incoming request.
 /// By bundling them together, we can expose a unified `handle_request` that ensures
 /// they are kept in sync without having to leak logic across multiple corners of the `wiremock`'s codebase.
+///
+/// Incoming requests only need a shared reference: the server handles them under a read lock,
+/// concurrently.
 pub(super) struct MockServerState {
     mock_set: MountedMockSet,
-    received_requests: Option<Vec<Request>>,
+    request_journal: Option<Mutex<Vec<JournalEntry>>>,
     body_print_limit: BodyPrintLimit,
+    /// Sanitizes requests before they are recorded in the journal.
+    redaction: Redaction,
+    metrics_endpoint: MetricsEndpoint,
+    /// Requests with a longer body are rejected with a `413 Payload Too Large`.
+    max_request_body_size: Option<usize>,
+    /// Injects failures in the responses to matched requests, if enabled.
+    chaos: Option<Mutex<Chaos>>,
+    /// Drives the response delays instead of the wall clock, if set.
+    virtual_clock: Option<VirtualClock>,
+    /// The time seen by response templates instead of the current one, if set.
+    fixed_time: Option<SystemTime>,
+    /// HTTP/2 connections are shut down with a `GOAWAY` frame after this many requests, if set.
+    goaway_after: Option<usize>,
+    /// When the server started - or was last reset - to measure the arrival time of requests
+    /// without a virtual clock.
+    started_at: Instant,
+    /// Server-wide invariants on the received requests, checked at verification time.
+    global_expectations: Vec<GlobalExpectation>,
+    /// Notified when the server is reset, to release the requests left hanging by
+    /// [`ResponseTemplate::hang`](crate::ResponseTemplate::hang).
+    hang_release: tokio::sync::watch::Sender<()>,
+    /// Whether responses carry headers identifying the mock that produced them - see
+    /// [`MockServerBuilder::debug_headers`](crate::MockServerBuilder::debug_headers).
+    debug_headers: bool,
+    /// Where the events of the server are broadcast - see
+    /// [`MockServer::events`](crate::MockServer::events).
+    events: Arc<EventBus>,
+    /// Whether the debug UI is served - see
+    /// [`MockServerBuilder::enable_debug_ui`](crate::MockServerBuilder::enable_debug_ui).
+    #[cfg(feature = "debug-ui")]
+    debug_ui: bool,
 }
 
+/// A server-wide invariant on the requests received by a `BareMockServer` - see
+/// [`MockServer::expect_global`](crate::MockServer::expect_global).
+pub(crate) type GlobalExpectation = Box<dyn Fn(&[Request]) -> Result<(), String> + Send + Sync>;
+
 impl MockServerState {
+    /// Match `request` against the mounted mocks.
+    ///
+    /// If request recording is enabled and the response is ready, the request is recorded
+    /// in the journal straight away.
+    /// Responses generated by an asynchronous responder are not ready yet: the caller is
+    /// responsible for calling [`MockServerState::record`] once they are.
     pub(super) async fn handle_request(
+        &self,
+        request: &Request,
+        received_at: Instant,
+    ) -> HandledRequest {
+        let mut handled = self.mock_set.handle_request(request).await;
+        if handled.matched_mock.is_some() {
+            if let PendingResponse::Ready(template) = &mut handled.response {
+                **template = self.inject_chaos(std::mem::take(&mut **template));
+            }
+        }
+        if let PendingResponse::Ready(template) = &handled.response {
+            self.record(request, received_at, handled.matched_mock.clone(), template);
+        }
+        handled
+    }
+
+    /// If request recording is enabled, record the incoming request
+    /// by adding it to the journal, alongside the outcome of the matching process.
+    pub(super) fn record(
+        &self,
+        request: &Request,
+        received_at: Instant,
+        matched_mock: Option<MatchedMock>,
+        response: &ResponseTemplate,
+    ) {
+        if let Some(mut request_journal) = self.journal() {
+            request_journal.push(JournalEntry {
+                request: self.redaction.apply(request),
+                received_at,
+                matched_mock,
+                response_status: response.status(),
+                received_at_wall: SystemTime::now() - received_at.elapsed(),
+                response: response.clone(),
+            });
+        }
+    }
+
+    /// Inject failures in the response to a matched request, if chaos mode is enabled.
+    pub(super) fn inject_chaos(&self, template: ResponseTemplate) -> ResponseTemplate {
+        match &self.chaos {
+            Some(chaos) => chaos.lock().unwrap().inject(template),
+            None => template,
+        }
+    }
+
+    /// The journal of the received requests, if request recording is enabled.
+    fn journal(&self) -> Option<MutexGuard<'_, Vec<JournalEntry>>> {
+        self.request_journal
+            .as_ref()
+            .map(|journal| journal.lock().unwrap())
+    }
+
+    /// The clock driving the response delays, if it is not the wall clock.
+    pub(super) fn virtual_clock(&self) -> Option<&VirtualClock> {
+        self.virtual_clock.as_ref()
+    }
+
+    /// The time response templates are generated at, if it is not the current one.
+    pub(super) fn fixed_time(&self) -> Option<SystemTime> {
+        self.fixed_time
+    }
+
+    /// How many requests an HTTP/2 connection carries before being shut down, if limited.
+    pub(super) fn goaway_after(&self) -> Option<usize> {
+        self.goaway_after
+    }
+
+    /// How long the server has been running for, according to the wall clock.
+    pub(super) fn uptime(&self) -> Duration {
+        self.started_at.elapsed()
+    }
+
+    /// Resolves, through [`changed`](tokio::sync::watch::Receiver::changed), once the requests
+    /// received so far should stop hanging.
+    pub(super) fn hang_release(&self) -> tokio::sync::watch::Receiver<()> {
+        self.hang_release.subscribe()
+    }
+
+    /// Where the events of the server are broadcast.
+    pub(super) fn events(&self) -> &Arc<EventBus> {
+        &self.events
+    }
+
+    /// Whether responses carry headers identifying the mock that produced them.
+    pub(super) fn debug_headers(&self) -> bool {
+        self.debug_headers
+    }
+
+    /// How to handle `Expect: 100-continue` for the request with the head `request`, if a mock
+    /// says so.
+    pub(super) fn expect_continue(&self, request: &Request) -> Option<ExpectContinue> {
+        self.mock_set.expect_continue(request)
+    }
+
+    /// The maximum size of the body of incoming requests, if any.
+    pub(super) fn max_request_body_size(&self) -> Option<usize> {
+        self.max_request_body_size
+    }
+
+    /// If the metrics endpoint is enabled and `request` targets it, return the metrics of the
+    /// server in the Prometheus text format.
+    pub(super) fn metrics_endpoint(&self, request: &Request) -> Option<ResponseTemplate> {
+        match self.metrics_endpoint {
+            MetricsEndpoint::Enabled
+                if request.method == http::Method::GET
+                    && request.url.path() == METRICS_ENDPOINT_PATH =>
+            {
+                Some(ResponseTemplate::new(200).set_body_raw(
+                    self.mock_set.prometheus_metrics(),
+                    "text/plain; version=0.0.4",
+                ))
+            }
+            _ => None,
+        }
+    }
+
+    /// If the debug UI is enabled, serve `request` - see [`crate::debug_ui::DebugUiRequest::parse`].
+    #[cfg(feature = "debug-ui")]
+    pub(super) fn debug_ui(
         &mut self,
-        request: Request,
-    ) -> (hyper::Response<Full<Bytes>>, Option<tokio::time::Sleep>) {
-        // If request recording is enabled, record the incoming request
-        // by adding it to the `received_requests` stack
-        if let Some(received_requests) = &mut self.received_requests {
-            received_requests.push(request.clone());
+        request: crate::debug_ui::DebugUiRequest,
+    ) -> Option<ResponseTemplate> {
+        use crate::debug_ui::{self, DebugUiRequest};
+
+        if !self.debug_ui {
+            return None;
+        }
+        Some(match request {
+            DebugUiRequest::Page => debug_ui::page(),
+            DebugUiRequest::State => {
+                let request_journal = self.journal();
+                debug_ui::state(
+                    &self.mock_set.overview(),
+                    request_journal.as_deref().map(Vec::as_slice),
+                )
+            }
+            DebugUiRequest::Toggle(position) => debug_ui::toggled(self.mock_set.toggle(position)),
+        })
+    }
+
+    /// List the requests recorded in the journal, for inclusion in a panic message.
+    fn received_requests_message(&self) -> String {
+        if let Some(request_journal) = self.journal() {
+            if request_journal.is_empty() {
+                "The server did not receive any request.".into()
+            } else {
+                request_journal.iter().enumerate().fold(
+                    "Received requests:\n".to_string(),
+                    |mut message, (index, entry)| {
+                        _ = write!(message, "- Request #{}\n\t", index + 1,);
+                        _ = entry.request.print_with_limit(
+                            &mut message,
+                            self.body_print_limit,
+                            &self.redaction.headers,
+                        );
+                        message
+                    },
+                )
+            }
+        } else {
+            "Enable request recording on the mock server to get the list of incoming requests as part of the panic message.".into()
         }
-        self.mock_set.handle_request(request).await
+    }
+
+    /// Check that the number of recorded requests satisfying `matcher` falls within `times`,
+    /// returning a message listing the received requests if it does not.
+    ///
+    /// It panics if request recording is disabled.
+    fn check_received(&self, times: &Times, matcher: &dyn Match) -> Result<(), String> {
+        let request_journal = self.journal().expect(
+            "Enable request recording on the mock server to make assertions on the received requests.",
+        );
+        let (matching, other): (Vec<_>, Vec<_>) = request_journal
+            .iter()
+            .enumerate()
+            .partition(|(_, entry)| matcher.matches(&entry.request));
+        if times.contains(matching.len() as u64) {
+            return Ok(());
+        }
+        let mut message = format!(
+            "Expected the number of received requests satisfying the assertion to be {}, but {} did.\n",
+            times,
+            matching.len()
+        );
+        for (title, entries) in [("Matching requests", matching), ("Other requests", other)] {
+            if entries.is_empty() {
+                continue;
+            }
+            _ = writeln!(message, "{}:", title);
+            for (index, entry) in entries {
+                _ = write!(message, "- Request #{}\n\t", index + 1);
+                _ = entry.request.print_with_limit(
+                    &mut message,
+                    self.body_print_limit,
+                    &self.redaction.headers,
+                );
+            }
+        }
+        if request_journal.is_empty() {
+            message.push_str("The server did not receive any request.");
+        }
+        Err(message)
+    }
+
+    /// The requests recorded in the journal, if request recording is enabled.
+    fn received_requests(&self) -> Option<Vec<Request>> {
+        self.journal()
+            .map(|journal| journal.iter().map(|entry| entry.request.clone()).collect())
+    }
+
+    /// The number of distinct connections the recorded requests were received on.
+    fn connection_count(&self) -> Option<usize> {
+        self.journal().map(|journal| {
+            journal
+                .iter()
+                .filter_map(|entry| entry.request.connection_id())
+                .collect::<HashSet<_>>()
+                .len()
+        })
     }
 }
 
 impl BareMockServer {
     /// Start a new instance of a `BareMockServer` listening on the specified
-    /// [`TcpListener`].
-    pub(super) async fn start(
-        listener: TcpListener,
-        request_recording: RequestRecording,
-        body_print_limit: BodyPrintLimit,
-    ) -> Self {
-        let (shutdown_trigger, shutdown_receiver) = tokio::sync::watch::channel(());
-        let received_requests = match request_recording {
-            RequestRecording::Enabled => Some(Vec::new()),
+    /// [`TcpListener`]s, configured according to `config`.
+    pub(super) async fn start(listeners: Listeners, config: ServerConfig) -> Self {
+        let ServerConfig {
+            request_recording,
+            body_print_limit,
+            redaction,
+            metrics_endpoint,
+            proxy_mode,
+            max_request_body_size,
+            server_runtime,
+            chaos,
+            virtual_clock,
+            fixed_time,
+            goaway_after,
+            random_seed,
+            session_key,
+            deny_duplicate_mocks,
+            debug_headers,
+            #[cfg(feature = "debug-ui")]
+            debug_ui,
+        } = config;
+        let (shutdown_trigger, shutdown_receiver) = tokio::sync::watch::channel(None);
+        let (stopped_sender, stopped) = tokio::sync::watch::channel(None);
+        let request_journal = match request_recording {
+            RequestRecording::Enabled => Some(Mutex::new(Vec::new())),
             RequestRecording::Disabled => None,
         };
+        let mut mock_set = MountedMockSet::new(body_print_limit, redaction.clone());
+        if let Some(seed) = random_seed {
+            mock_set.seed_random(seed);
+        }
+        if let Some(key) = session_key {
+            mock_set.set_session_key(key);
+        }
+        if deny_duplicate_mocks {
+            mock_set.deny_duplicates();
+        }
         let state = Arc::new(RwLock::new(MockServerState {
-            mock_set: MountedMockSet::new(body_print_limit),
-            received_requests,
+            mock_set,
+            request_journal,
             body_print_limit,
+            redaction: redaction.clone(),
+            metrics_endpoint,
+            max_request_body_size,
+            chaos: chaos.map(Mutex::new),
+            virtual_clock,
+            fixed_time,
+            goaway_after,
+            started_at: Instant::now(),
+            global_expectations: vec![],
+            hang_release: tokio::sync::watch::channel(()).0,
+            debug_headers,
+            events: Arc::new(EventBus::new(redaction.clone())),
+            #[cfg(feature = "debug-ui")]
+            debug_ui,
         }));
-        let server_address = listener
-            .local_addr()
-            .expect("Failed to get server address.");
+        let addresses: Vec<SocketAddr> = listeners
+            .mocks
+            .iter()
+            .map(|listener| {
+                listener
+                    .local_addr()
+                    .expect("Failed to get server address.")
+            })
+            .collect();
+        let server_address = addresses[0];
+        let http_redirect_address = listeners.http_redirect.as_ref().map(|listener| {
+            listener
+                .local_addr()
+                .expect("Failed to get server address.")
+        });
 
-        let server_state = state.clone();
-        std::thread::spawn(move || {
-            let server_future = run_server(listener, server_state, shutdown_receiver);
+        // The dedicated runtime only starts running the server once it is fully assembled.
+        let (executor, dedicated_runtime) = match server_runtime {
+            ServerRuntime::Dedicated => {
+                let runtime = tokio::runtime::Builder::new_current_thread()
+                    .enable_all()
+                    .build()
+                    .expect("Cannot build local tokio runtime");
+                (Executor::Tokio(runtime.handle().clone()), Some(runtime))
+            }
+            ServerRuntime::Current => (Executor::current(), None),
+        };
+        let in_flight = Arc::new(InFlightCounter::default());
+        let scheme = if listeners.tls.is_some() {
+            "https"
+        } else {
+            "http"
+        };
+        #[cfg(feature = "tls")]
+        let tls = listeners.tls.clone();
+        #[cfg(feature = "tls")]
+        let proxy_authority = match &proxy_mode {
+            ProxyMode::Intercepting(authority) => Some(authority.clone()),
+            _ => None,
+        };
 
-            let runtime = tokio::runtime::Builder::new_current_thread()
-                .enable_all()
-                .build()
-                .expect("Cannot build local tokio runtime");
+        // All listeners share the same state: the mocks are served on each of them.
+        let redirect_shutdown_receiver = shutdown_receiver.clone();
+        let Listeners {
+            mocks,
+            http_redirect,
+            tls: tls_acceptor,
+        } = listeners;
+        let servers: Vec<_> = mocks
+            .into_iter()
+            .map(|listener| {
+                run_server(
+                    listener,
+                    tls_acceptor.clone(),
+                    state.clone(),
+                    in_flight.clone(),
+                    proxy_mode.clone(),
+                    executor.clone(),
+                    shutdown_receiver.clone(),
+                )
+            })
+            .collect();
+        let redirect_executor = executor.clone();
+        let redirect_server = async move {
+            if let Some(listener) = http_redirect {
+                let target = format!("{}://{}", scheme, server_address);
+                run_redirect_server(
+                    listener,
+                    target,
+                    redirect_executor,
+                    redirect_shutdown_receiver,
+                )
+                .await;
+            }
+        };
+        // The in-flight counter is shared as well: all servers report the same count.
+        let server_future = async move {
+            let (still_in_flight, ()) =
+                futures::future::join(futures::future::join_all(servers), redirect_server).await;
+            let still_in_flight = still_in_flight.into_iter().max().unwrap_or_default();
+            let _ = stopped_sender.send(Some(still_in_flight));
+        };
 
-            runtime.block_on(server_future);
-        });
+        match dedicated_runtime {
+            Some(runtime) => {
+                std::thread::spawn(move || runtime.block_on(server_future));
+            }
+            None => executor.spawn(server_future),
+        }
         for _ in 0..40 {
             if TcpStream::connect_timeout(&server_address, std::time::Duration::from_millis(25))
                 .is_ok()
             {
                 break;
             }
-            tokio::time::sleep(std::time::Duration::from_millis(25)).await;
+            executor
+                .build(|| tokio::time::sleep(std::time::Duration::from_millis(25)))
+                .await;
         }
 
         Self {
             state,
             server_address,
-            _shutdown_trigger: shutdown_trigger,
+            scheme,
+            #[cfg(feature = "tls")]
+            tls,
+            #[cfg(feature = "tls")]
+            proxy_authority,
+            addresses,
+            http_redirect_address,
+            in_flight,
+            shutdown_trigger,
+            stopped,
         }
     }
 
+    /// Subscribe to the events of the server.
+    pub(crate) async fn events(&self) -> impl Stream<Item = ServerEvent> + Send + 'static {
+        self.state.read().await.events.subscribe()
+    }
+
+    /// Broadcast that verifying the expectations of the mounted mocks failed.
+    pub(crate) async fn publish_verification_failure(&self, message: &str) {
+        self.state
+            .read()
+            .await
+            .events
+            .publish(ServerEvent::VerificationFailed {
+                message: message.to_owned(),
+            });
+    }
+
+    /// Serve the new connections with the certificate in `config`.
+    ///
+    /// It panics if the server does not terminate TLS.
+    #[cfg(feature = "tls")]
+    pub(crate) fn rotate_tls_cert(&self, config: &crate::tls::TlsConfig) {
+        match &self.tls {
+            Some(tls) => tls.rotate(config),
+            None => panic!(
+                "The mock server does not serve its mocks over TLS: enable it with `MockServerBuilder::tls`."
+            ),
+        }
+    }
+
+    /// The PEM-encoded certificate of the authority issuing the certificates presented in the
+    /// tunnels, if TLS interception is enabled.
+    #[cfg(feature = "tls")]
+    pub(crate) fn proxy_ca_certificate(&self) -> Option<String> {
+        self.proxy_authority
+            .as_ref()
+            .map(|authority| authority.certificate_pem())
+    }
+
     /// Register a `Mock` on an instance of `BareMockServer`.
     ///
     /// Be careful! `Mock`s are not effective until they are `mount`ed or `register`ed on a
@@ -106,6 +561,15 @@ impl BareMockServer {
         self.state.write().await.mock_set.register(mock);
     }
 
+    /// Register a batch of `Mock`s on an instance of `MockServer`, all at once: requests are
+    /// either matched against none of them or against all of them.
+    pub(crate) async fn register_many(&self, mocks: Vec<Mock>) {
+        let mut state = self.state.write().await;
+        for mock in mocks {
+            state.mock_set.register(mock);
+        }
+    }
+
     /// Register a **scoped** `Mock` on an instance of `MockServer`.
     ///
     /// When using `register`, your `Mock`s will be active until the `MockServer` is shut down.  
@@ -121,6 +585,22 @@ impl BareMockServer {
         }
     }
 
+    /// Register a group of **scoped** `Mock`s on an instance of `MockServer`.
+    ///
+    /// They are all registered at once and stay active as long as the returned `GroupGuard`
+    /// is not dropped.
+    pub(crate) async fn register_group_as_scoped(&self, mocks: Vec<Mock>) -> GroupGuard {
+        let mut state = self.state.write().await;
+        let mock_ids = mocks
+            .into_iter()
+            .map(|mock| state.mock_set.register(mock).1)
+            .collect();
+        GroupGuard {
+            mock_ids,
+            server_state: self.state.clone(),
+        }
+    }
+
     /// Drop all mounted `Mock`s from an instance of `BareMockServer`.
     /// Delete all recorded requests.
     ///
@@ -129,16 +609,190 @@ impl BareMockServer {
     pub(crate) async fn reset(&self) {
         let mut state = self.state.write().await;
         state.mock_set.reset();
-        if let Some(received_requests) = &mut state.received_requests {
-            received_requests.clear();
+        state.global_expectations.clear();
+        state.hang_release.send_replace(());
+        state.events.reset();
+        if let Some(request_journal) = &mut state.request_journal {
+            request_journal.get_mut().unwrap().clear();
+        }
+        self.in_flight.reset_max();
+        // A pooled server is handed out as a fresh one: arrival times start from scratch.
+        state.started_at = Instant::now();
+    }
+
+    /// Check `expectation` against the received requests every time the expectations of the
+    /// `BareMockServer` are verified.
+    ///
+    /// It panics if request recording is disabled.
+    pub(crate) async fn expect_global(&self, expectation: GlobalExpectation) {
+        let mut state = self.state.write().await;
+        if state.request_journal.is_none() {
+            panic!("Global expectations are checked against the received requests: they cannot be used if request recording is disabled.");
+        }
+        state.global_expectations.push(expectation);
+    }
+
+    /// Check the global expectations against the received requests, returning the error
+    /// messages of the failed ones.
+    pub(crate) async fn global_expectation_failures(&self) -> Vec<String> {
+        let state = self.state.read().await;
+        if state.global_expectations.is_empty() {
+            return vec![];
+        }
+        let received_requests = state.received_requests().unwrap_or_default();
+        state
+            .global_expectations
+            .iter()
+            .filter_map(|expectation| expectation(&received_requests).err())
+            .collect()
+    }
+
+    /// Zero the match counters of all the `Mock`s and clear the request journal, keeping the
+    /// mocks mounted.
+    pub(crate) async fn reset_expectations(&self) {
+        let mut state = self.state.write().await;
+        state.mock_set.reset_expectations();
+        if let Some(request_journal) = &mut state.request_journal {
+            request_journal.get_mut().unwrap().clear();
+        }
+    }
+
+    /// Unmount all the `Mock`s named `name`, leaving the other mocks untouched.
+    ///
+    /// It returns how many mocks have been unmounted.
+    pub(crate) async fn deactivate_by_name(&self, name: &str) -> usize {
+        self.state.write().await.mock_set.deactivate_by_name(name)
+    }
+
+    /// Mount the stubs stored in `dir` and, if `poll_interval` is set, keep them in sync with
+    /// the content of the directory.
+    pub(crate) async fn load_fixture_dir(
+        &self,
+        dir: &Path,
+        poll_interval: Option<Duration>,
+    ) -> Option<FixtureWatcher> {
+        let stubs = fixtures::load_stubs(dir);
+        let (generation, mut mock_ids): (u16, Vec<MockId>) = {
+            let mut state = self.state.write().await;
+            let mock_ids = stubs
+                .into_iter()
+                .map(|stub| state.mock_set.register(Mock::from(stub)).1)
+                .collect();
+            (state.mock_set.generation(), mock_ids)
+        };
+        let poll_interval = poll_interval?;
+
+        // Once the server has been reset - e.g. because it went back to the pool and is now
+        // used by another test - the stubs must not come back.
+        let watched_state = self.state.clone();
+        let is_attached = move || {
+            futures::executor::block_on(watched_state.read())
+                .mock_set
+                .generation()
+                == generation
+        };
+        let server_state = self.state.clone();
+        Some(FixtureWatcher::spawn(
+            dir.to_path_buf(),
+            poll_interval,
+            is_attached,
+            move |stubs| {
+                let mut state = futures::executor::block_on(server_state.write());
+                if state.mock_set.generation() != generation {
+                    return false;
+                }
+                state.mock_set.remove(&mock_ids);
+                mock_ids = stubs
+                    .into_iter()
+                    .map(|stub| state.mock_set.register(Mock::from(stub)).1)
+                    .collect();
+                true
+            },
+        ))
+    }
+
+    /// Return the problems found in the mounted `Mock`s.
+    pub(crate) async fn lint_mocks(&self) -> Vec<MockLint> {
+        self.state.read().await.mock_set.lint()
+    }
+
+    /// Return the usage metrics of the mounted `Mock`s.
+    pub(crate) async fn metrics(&self) -> Vec<MockMetrics> {
+        self.state.read().await.mock_set.metrics()
+    }
+
+    /// Write the requests received by the `BareMockServer`, alongside the responses that were
+    /// returned, to `path` as a HAR file.
+    ///
+    /// It returns `false` if request recording was disabled.
+    pub(crate) async fn export_har(&self, path: &Path) -> bool {
+        let request_journal = match self.request_journal().await {
+            Some(request_journal) => request_journal,
+            None => return false,
+        };
+        let har = har::to_har(&request_journal).await;
+        let har = serde_json::to_vec_pretty(&har).expect("Failed to serialize HAR");
+        std::fs::write(path, har)
+            .unwrap_or_else(|e| panic!("Failed to write HAR to `{}`: {}", path.display(), e));
+        true
+    }
+
+    /// Write the requests matched by the mounted `Mock`s, alongside the responses that were
+    /// returned, to `path` as a Pact contract between `consumer` and `provider`.
+    ///
+    /// It returns `false` if request recording was disabled.
+    #[cfg(feature = "pact")]
+    pub(crate) async fn write_pact(&self, consumer: &str, provider: &str, path: &Path) -> bool {
+        let request_journal = match self.request_journal().await {
+            Some(request_journal) => request_journal,
+            None => return false,
+        };
+        let pact = crate::pact::to_pact(consumer, provider, &request_journal).await;
+        let pact = serde_json::to_vec_pretty(&pact).expect("Failed to serialize the Pact");
+        std::fs::write(path, pact)
+            .unwrap_or_else(|e| panic!("Failed to write Pact to `{}`: {}", path.display(), e));
+        true
+    }
+
+    /// Return the stubs describing the mounted `Mock`s that were built from a `Stub`.
+    pub(crate) async fn stubs(&self) -> Vec<Stub> {
+        self.state.read().await.mock_set.stubs()
+    }
+
+    /// Delete all recorded requests, leaving the mounted `Mock`s untouched.
+    pub(crate) async fn reset_requests(&self) {
+        if let Some(request_journal) = &mut self.state.write().await.request_journal {
+            request_journal.get_mut().unwrap().clear();
         }
     }
 
     /// Verify that all mounted `Mock`s on this instance of `BareMockServer` have satisfied
     /// their expectations on their number of invocations.
-    pub(crate) async fn verify(&self) -> VerificationOutcome {
+    ///
+    /// If `on_drop` is set, the mocks that opted out of verification on drop are skipped.
+    pub(crate) async fn verify(&self, on_drop: bool) -> VerificationOutcome {
         let mock_set = &self.state.read().await.mock_set;
-        mock_set.verify_all()
+        mock_set.verify_all(on_drop)
+    }
+
+    /// Stop accepting connections and wait for the in-flight requests to complete, for up to
+    /// `grace_period`.
+    ///
+    /// It returns how many requests were still in-flight when the server stopped.
+    pub(crate) async fn shutdown(&self, grace_period: Duration) -> usize {
+        let _ = self.shutdown_trigger.send(Some(grace_period));
+        let mut stopped = self.stopped.clone();
+        let still_in_flight = match stopped.wait_for(Option::is_some).await {
+            Ok(still_in_flight) => still_in_flight.unwrap_or_default(),
+            // The server thread panicked.
+            Err(_) => 0,
+        };
+        still_in_flight
+    }
+
+    /// Whether the server is still accepting connections - i.e. it has not been shut down.
+    pub(crate) fn is_running(&self) -> bool {
+        self.stopped.borrow().is_none() && self.shutdown_trigger.borrow().is_none()
     }
 
     /// Return the base uri of this running instance of `BareMockServer`, e.g. `http://127.0.0.1:4372`.
@@ -146,7 +800,29 @@ impl BareMockServer {
     /// Use this method to compose uris when interacting with this instance of `BareMockServer` via
     /// an HTTP client.
     pub(crate) fn uri(&self) -> String {
-        format!("http://{}", self.server_address)
+        format!("{}://{}", self.scheme, self.server_address)
+    }
+
+    /// Return the base uris of all the listeners of this running instance of `BareMockServer`,
+    /// starting with the one returned by [`BareMockServer::uri`].
+    pub(crate) fn uris(&self) -> Vec<String> {
+        self.addresses
+            .iter()
+            .map(|address| format!("{}://{}", self.scheme, address))
+            .collect()
+    }
+
+    /// Return the base uri of the listener redirecting all requests to this running instance
+    /// of `BareMockServer`, if any.
+    pub(crate) fn http_redirect_uri(&self) -> Option<String> {
+        self.http_redirect_address
+            .map(|address| format!("http://{}", address))
+    }
+
+    /// Return the socket addresses of all the listeners of this running instance of
+    /// `BareMockServer`, starting with the one returned by [`BareMockServer::address`].
+    pub(crate) fn addresses(&self) -> &[SocketAddr] {
+        &self.addresses
     }
 
     /// Return the socket address of this running instance of `BareMockServer`, e.g. `127.0.0.1:4372`.
@@ -156,9 +832,24 @@ impl BareMockServer {
         &self.server_address
     }
 
-    /// Return the body print limit of this running instance of `BareMockServer`.
-    pub(crate) async fn body_print_limit(&self) -> BodyPrintLimit {
-        self.state.read().await.body_print_limit
+    /// List the requests received by this running instance of `BareMockServer`, for inclusion
+    /// in a panic message.
+    pub(crate) async fn received_requests_message(&self) -> String {
+        self.state.read().await.received_requests_message()
+    }
+
+    /// Check that the number of received requests satisfying `matcher` falls within `times`.
+    pub(crate) async fn check_received(
+        &self,
+        times: &Times,
+        matcher: &dyn Match,
+    ) -> Result<(), String> {
+        self.state.read().await.check_received(times, matcher)
+    }
+
+    /// Return the headers whose value is hidden when printing requests.
+    pub(crate) async fn redacted_headers(&self) -> Vec<HeaderName> {
+        self.state.read().await.redaction.headers.clone()
     }
 
     /// Return a vector with all the requests received by the `BareMockServer` since it started.  
@@ -167,7 +858,40 @@ impl BareMockServer {
     /// If request recording was disabled, it returns `None`.
     pub(crate) async fn received_requests(&self) -> Option<Vec<Request>> {
         let state = self.state.read().await;
-        state.received_requests.clone()
+        state.received_requests()
+    }
+
+    /// Return the number of connections the recorded requests were received on.
+    ///
+    /// It returns `None` if request recording is disabled.
+    pub(crate) async fn connection_count(&self) -> Option<usize> {
+        self.state.read().await.connection_count()
+    }
+
+    /// Replace the responder of all the in-scope `Mock`s named `name`.
+    ///
+    /// It returns how many mocks have been updated.
+    pub(crate) async fn set_responder_by_name(&self, name: &str, responder: Responder) -> usize {
+        self.state
+            .write()
+            .await
+            .mock_set
+            .set_responder_by_name(name, responder)
+    }
+
+    /// Return the highest number of requests that this `BareMockServer` has been serving
+    /// concurrently since it started.
+    pub(crate) fn max_concurrent_requests(&self) -> usize {
+        self.in_flight.max()
+    }
+
+    /// Return the journal of all the requests received by the `BareMockServer` since it started,
+    /// with their arrival time and matching outcome.
+    ///
+    /// If request recording was disabled, it returns `None`.
+    pub(crate) async fn request_journal(&self) -> Option<Vec<JournalEntry>> {
+        let state = self.state.read().await;
+        state.journal().map(|journal| journal.to_vec())
     }
 }
 
@@ -177,11 +901,83 @@ impl Debug for BareMockServer {
     }
 }
 
+/// The sockets a [`BareMockServer`] listens on.
+pub(super) struct Listeners {
+    /// The listeners serving the mocks - the first one is the primary listener.
+    pub(super) mocks: Vec<TcpListener>,
+    /// A listener redirecting all requests to the primary listener, if any.
+    pub(super) http_redirect: Option<TcpListener>,
+    /// Terminates TLS on the connections accepted by the listeners serving the mocks, if set.
+    pub(super) tls: Option<TlsAcceptor>,
+}
+
+/// How a [`BareMockServer`] behaves - everything set on the
+/// [`MockServerBuilder`](crate::MockServerBuilder) but the sockets it listens on.
+///
+/// It is handed over to [`BareMockServer::start`] as a whole: the server is fully configured
+/// before it accepts its first connection.
+pub(super) struct ServerConfig {
+    pub(super) request_recording: RequestRecording,
+    pub(super) body_print_limit: BodyPrintLimit,
+    /// Sanitizes requests before they are recorded in the journal.
+    pub(super) redaction: Redaction,
+    pub(super) metrics_endpoint: MetricsEndpoint,
+    pub(super) proxy_mode: ProxyMode,
+    /// Requests with a longer body are rejected with a `413 Payload Too Large`.
+    pub(super) max_request_body_size: Option<usize>,
+    pub(super) server_runtime: ServerRuntime,
+    /// Injects failures in the responses to matched requests, if set.
+    pub(super) chaos: Option<Chaos>,
+    /// Drives the response delays instead of the wall clock, if set.
+    pub(super) virtual_clock: Option<VirtualClock>,
+    /// The time seen by response templates instead of the current one, if set.
+    pub(super) fixed_time: Option<SystemTime>,
+    /// HTTP/2 connections are shut down with a `GOAWAY` frame after this many requests, if set.
+    pub(super) goaway_after: Option<usize>,
+    /// The seed of the source of random values used by responders and response templates.
+    pub(super) random_seed: Option<u64>,
+    /// How requests are grouped into sessions, if set.
+    pub(super) session_key: Option<SessionKey>,
+    /// Panic when a `Mock` with the same matchers as a mounted one is registered.
+    pub(super) deny_duplicate_mocks: bool,
+    /// Add headers identifying the mock that produced each response.
+    pub(super) debug_headers: bool,
+    /// Serve the debug UI.
+    #[cfg(feature = "debug-ui")]
+    pub(super) debug_ui: bool,
+}
+
 pub(super) enum RequestRecording {
     Enabled,
     Disabled,
 }
 
+pub(super) enum MetricsEndpoint {
+    Enabled,
+    Disabled,
+}
+
+/// Where the server runs - see
+/// [`MockServerBuilder::run_on_current_runtime`](crate::MockServerBuilder::run_on_current_runtime).
+pub(super) enum ServerRuntime {
+    /// A current-thread runtime on a dedicated thread.
+    Dedicated,
+    /// The runtime `start` is called from - `tokio`, or `async-std` with the `async-std`
+    /// feature.
+    Current,
+}
+
+/// Whether the server accepts `CONNECT` requests, acting as the target of a forward proxy.
+#[derive(Clone)]
+pub(super) enum ProxyMode {
+    Enabled,
+    /// The tunnels carry TLS, terminated with certificates issued by the authority for the
+    /// requested hosts.
+    #[cfg_attr(not(feature = "tls"), allow(dead_code))]
+    Intercepting(Arc<CertificateAuthority>),
+    Disabled,
+}
+
 /// You get a `MockGuard` when registering a **scoped** [`Mock`] using [`MockServer::register_as_scoped`](crate::MockServer::register_as_scoped)
 /// or [`Mock::mount_as_scoped`].
 ///
@@ -192,13 +988,13 @@ pub(super) enum RequestRecording {
 ///
 /// When expectations of a scoped [`Mock`] are not verified, it will trigger a panic - just like a normal [`Mock`].
 /// Due to [limitations](https://internals.rust-lang.org/t/should-drop-glue-use-track-caller/13682) in Rust's `Drop` trait,
-/// the panic message will not include the filename and the line location
-/// where the corresponding `MockGuard` was dropped - it will point into `wiremock`'s source code.
+/// the location of the panic will not be the line where the corresponding `MockGuard` was dropped - it will
+/// point into `wiremock`'s source code.
 ///
-/// This can be an issue when you are using more than one scoped [`Mock`] in a single test - which of them panicked?
-/// To improve your debugging experience it is strongly recommended to use [`Mock::named`] to assign a unique
-/// identifier to your scoped [`Mock`]s, which will in turn be referenced in the panic message if their expectations are
-/// not met.
+/// To help you find out which scoped [`Mock`] panicked, the panic message includes the filename and the
+/// line location where the [`Mock`] was mounted. You can also use [`Mock::named`] to assign a unique
+/// identifier to your scoped [`Mock`]s, which will in turn be referenced in the panic message if their
+/// expectations are not met.
 #[must_use = "All *_scoped methods return a `MockGuard`.
 This guard MUST be bound to a variable (e.g. _mock_guard), \
 otherwise the mock will immediately be unmounted (and its expectations checked).
@@ -221,6 +1017,227 @@ impl MockGuard {
         mounted_mock.received_requests()
     }
 
+    /// Return the sequence number of the first request matched by the corresponding
+    /// scoped [`Mock`], or `None` if it has not matched any request yet.
+    ///
+    /// Sequence numbers are assigned to incoming requests in the order they are received by the
+    /// [`MockServer`](crate::MockServer), starting from `0` - i.e. if request recording is enabled,
+    /// the sequence number is the index of the request in
+    /// [`MockServer::received_requests`](crate::MockServer::received_requests).
+    ///
+    /// Compare the sequence numbers of different scoped mocks to determine in which order they were
+    /// matched - or use [`MockServer::verify_sequence`](crate::MockServer::verify_sequence).
+    ///
+    /// ### Example:
+    /// ```rust
+    /// use wiremock::{MockServer, Mock, ResponseTemplate};
+    /// use wiremock::matchers::path;
+    ///
+    /// #[async_std::main]
+    /// async fn main() {
+    ///     // Arrange
+    ///     let mock_server = MockServer::start().await;
+    ///     let token_mock = Mock::given(path("/token"))
+    ///         .respond_with(ResponseTemplate::new(200))
+    ///         .mount_as_scoped(&mock_server)
+    ///         .await;
+    ///     let resource_mock = Mock::given(path("/resource"))
+    ///         .respond_with(ResponseTemplate::new(200))
+    ///         .mount_as_scoped(&mock_server)
+    ///         .await;
+    ///
+    ///     // Act
+    ///     surf::get(format!("{}/token", mock_server.uri())).await.unwrap();
+    ///     surf::get(format!("{}/resource", mock_server.uri())).await.unwrap();
+    ///
+    ///     // Assert
+    ///     assert_eq!(token_mock.first_matched_at().await, Some(0));
+    ///     assert_eq!(resource_mock.first_matched_at().await, Some(1));
+    /// }
+    /// ```
+    pub async fn first_matched_at(&self) -> Option<u64> {
+        let state = self.server_state.read().await;
+        let (mounted_mock, _) = &state.mock_set[self.mock_id];
+        mounted_mock.first_matched_at()
+    }
+
+    /// Return the highest number of matched requests that the corresponding scoped [`Mock`]
+    /// has been serving concurrently since it was mounted.
+    ///
+    /// A request is considered in-flight from the moment it has been matched until the response
+    /// is ready to be sent back to the client, including any delay configured via
+    /// [`ResponseTemplate::set_delay`](crate::ResponseTemplate::set_delay).
+    /// Requests are matched concurrently, but matching is quick: you will only reliably observe
+    /// concurrency above `1` if responses take some time to be produced - use a delay to
+    /// simulate a slow server.
+    ///
+    /// ### Example:
+    /// ```rust
+    /// use wiremock::{MockServer, Mock, ResponseTemplate};
+    /// use wiremock::matchers::method;
+    /// use std::time::Duration;
+    ///
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     // Arrange
+    ///     let mock_server = MockServer::start().await;
+    ///     let mock_guard = Mock::given(method("GET"))
+    ///         .respond_with(ResponseTemplate::new(200).set_delay(Duration::from_millis(200)))
+    ///         .mount_as_scoped(&mock_server)
+    ///         .await;
+    ///
+    ///     // Act - two requests at the same time
+    ///     let client = reqwest::Client::new();
+    ///     let (a, b) = tokio::join!(
+    ///         client.get(mock_server.uri()).send(),
+    ///         client.get(mock_server.uri()).send(),
+    ///     );
+    ///     a.unwrap();
+    ///     b.unwrap();
+    ///
+    ///     // Assert
+    ///     assert_eq!(mock_guard.max_concurrency().await, 2);
+    /// }
+    /// ```
+    pub async fn max_concurrency(&self) -> usize {
+        let state = self.server_state.read().await;
+        let (mounted_mock, _) = &state.mock_set[self.mock_id];
+        mounted_mock.in_flight().max()
+    }
+
+    /// Return the usage metrics of the corresponding scoped [`Mock`]: how many requests it
+    /// matched, how long it took to respond to them and when it matched for the last time.
+    ///
+    /// ### Example:
+    /// ```rust
+    /// use wiremock::{MockServer, Mock, ResponseTemplate};
+    /// use wiremock::matchers::method;
+    /// use std::time::Duration;
+    ///
+    /// #[async_std::main]
+    /// async fn main() {
+    ///     // Arrange
+    ///     let mock_server = MockServer::start().await;
+    ///     let delay = Duration::from_millis(50);
+    ///     let mock_guard = Mock::given(method("GET"))
+    ///         .respond_with(ResponseTemplate::new(200).set_delay(delay))
+    ///         .mount_as_scoped(&mock_server)
+    ///         .await;
+    ///
+    ///     // Act
+    ///     surf::get(&mock_server.uri()).await.unwrap();
+    ///     surf::get(&mock_server.uri()).await.unwrap();
+    ///
+    ///     // Assert
+    ///     let metrics = mock_guard.metrics().await;
+    ///     assert_eq!(metrics.n_matches, 2);
+    ///     assert_eq!(metrics.latencies.len(), 2);
+    ///     // Latencies include the injected delay
+    ///     assert!(metrics.mean_latency().unwrap() >= delay);
+    ///     assert!(metrics.last_matched_at.is_some());
+    /// }
+    /// ```
+    pub async fn metrics(&self) -> MockMetrics {
+        let state = self.server_state.read().await;
+        state.mock_set.mock_metrics(self.mock_id)
+    }
+
+    /// Change how the corresponding scoped [`Mock`] responds to the requests it matches.
+    ///
+    /// Unlike unmounting the mock and mounting a new one, the number of requests matched so far
+    /// and the expectations set on the mock are preserved.
+    ///
+    /// ### Example:
+    /// ```rust
+    /// use wiremock::{MockServer, Mock, ResponseTemplate};
+    /// use wiremock::matchers::path;
+    ///
+    /// #[async_std::main]
+    /// async fn main() {
+    ///     // Arrange
+    ///     let mock_server = MockServer::start().await;
+    ///     let mock_guard = Mock::given(path("/health"))
+    ///         .respond_with(ResponseTemplate::new(200))
+    ///         .expect(2)
+    ///         .mount_as_scoped(&mock_server)
+    ///         .await;
+    ///     let url = format!("{}/health", mock_server.uri());
+    ///
+    ///     // Act
+    ///     let before = surf::get(&url).await.unwrap();
+    ///     mock_guard.set_response(ResponseTemplate::new(503)).await;
+    ///     let after = surf::get(&url).await.unwrap();
+    ///
+    ///     // Assert
+    ///     assert_eq!(before.status(), 200);
+    ///     assert_eq!(after.status(), 503);
+    ///     // Both requests count towards the expectation set on the mock.
+    ///     assert_eq!(mock_guard.received_requests().await.len(), 2);
+    /// }
+    /// ```
+    pub async fn set_response<R: Respond + 'static>(&self, responder: R) {
+        let mut state = self.server_state.write().await;
+        state
+            .mock_set
+            .set_responder(self.mock_id, Responder::Sync(Arc::new(responder)));
+    }
+
+    /// Temporarily disable the corresponding scoped [`Mock`]: until [`MockGuard::resume`] is
+    /// called, incoming requests are matched against the other mounted mocks (or get a `404`)
+    /// as if this mock was not there.
+    ///
+    /// Requests received while the mock is paused do not count towards its expectations, but the
+    /// expectations are still verified when the guard is dropped.
+    ///
+    /// ### Example:
+    /// ```rust
+    /// use wiremock::{MockServer, Mock, ResponseTemplate};
+    /// use wiremock::matchers::path;
+    ///
+    /// #[async_std::main]
+    /// async fn main() {
+    ///     // Arrange
+    ///     let mock_server = MockServer::start().await;
+    ///     let mock_guard = Mock::given(path("/health"))
+    ///         .respond_with(ResponseTemplate::new(200))
+    ///         .mount_as_scoped(&mock_server)
+    ///         .await;
+    ///     let url = format!("{}/health", mock_server.uri());
+    ///
+    ///     // Act
+    ///     mock_guard.pause().await;
+    ///     let paused = surf::get(&url).await.unwrap();
+    ///     mock_guard.resume().await;
+    ///     let resumed = surf::get(&url).await.unwrap();
+    ///
+    ///     // Assert
+    ///     assert_eq!(paused.status(), 404);
+    ///     assert_eq!(resumed.status(), 200);
+    /// }
+    /// ```
+    pub async fn pause(&self) {
+        self.server_state.write().await.mock_set.pause(self.mock_id);
+    }
+
+    /// Re-enable the corresponding scoped [`Mock`] after it was paused using [`MockGuard::pause`].
+    ///
+    /// It does nothing if the mock is not paused.
+    pub async fn resume(&self) {
+        self.server_state
+            .write()
+            .await
+            .mock_set
+            .resume(self.mock_id);
+    }
+
+    /// Identify the corresponding scoped [`Mock`] and return the sequence number of the first
+    /// request it matched.
+    pub(crate) async fn first_match(&self) -> (MatchedMock, Option<u64>) {
+        let state = self.server_state.read().await;
+        let (mounted_mock, _) = &state.mock_set[self.mock_id];
+        (mounted_mock.identity(), mounted_mock.first_matched_at())
+    }
+
     /// This method doesn't return until the expectations set on the
     /// corresponding scoped [`Mock`] are satisfied.
     ///
@@ -228,6 +1245,11 @@ impl MockGuard {
     /// message queue consumer) and you don't have a good event that can be used
     /// to trigger the verification of the expectations set on the scoped [`Mock`].
     ///
+    /// The expectations are checked every time the mock matches a request: a response body
+    /// that ends up not being consumed within the budget set with
+    /// [`Mock::expect_response_consumed_within`] after that is only caught by the verification
+    /// on drop.
+    ///
     /// # Timeouts
     ///
     /// There is no default timeout for this method, so it will end up waiting
@@ -290,33 +1312,21 @@ impl Drop for MockGuard {
             let mut state = server_state.write().await;
             let report = state.mock_set.verify(*mock_id);
 
-            if !report.is_satisfied() {
-                let received_requests_message = if let Some(received_requests) =
-                    &state.received_requests
-                {
-                    if received_requests.is_empty() {
-                        "The server did not receive any request.".into()
-                    } else {
-                        received_requests.iter().enumerate().fold(
-                            "Received requests:\n".to_string(),
-                            |mut message, (index, request)| {
-                                _ = write!(message, "- Request #{}\n\t", index + 1,);
-                                _ = request.print_with_limit(&mut message, state.body_print_limit);
-                                message
-                            },
-                        )
-                    }
-                } else {
-                    "Enable request recording on the mock server to get the list of incoming requests as part of the panic message.".into()
-                };
+            if !report.is_satisfied_on_drop() {
+                let received_requests_message = state.received_requests_message();
 
-                let verifications_error = format!("- {}\n", report.error_message());
+                report.trace_failure();
+                let verifications_error =
+                    format!("- {}\n", report.error_message(&state.redaction.headers));
                 let error_message = format!(
                     "Verification failed for a scoped mock:\n{}\n{}",
                     verifications_error, received_requests_message
                 );
+                state.events.publish(ServerEvent::VerificationFailed {
+                    message: error_message.clone(),
+                });
                 if std::thread::panicking() {
-                    log::debug!("{}", &error_message);
+                    tracing::debug!("{}", &error_message);
                 } else {
                     panic!("{}", &error_message);
                 }
@@ -327,3 +1337,72 @@ impl Drop for MockGuard {
         futures::executor::block_on(future);
     }
 }
+
+/// A guard for a group of scoped [`Mock`]s, returned by
+/// [`MockServer::register_group_as_scoped`](crate::MockServer::register_group_as_scoped).
+///
+/// It behaves like a [`MockGuard`] for all the mocks in the group at once: they stay active as
+/// long as the `GroupGuard` is alive. When it is dropped, the expectations of all the mocks in the
+/// group are verified - if any of them is not satisfied, it panics listing all the failures.
+#[must_use = "All *_scoped methods return a guard.
+This guard MUST be bound to a variable (e.g. _mock_guard), \
+otherwise the mocks will immediately be unmounted (and their expectations checked).
+Check `wiremock`'s documentation on scoped mocks for more details."]
+pub struct GroupGuard {
+    mock_ids: Vec<MockId>,
+    server_state: Arc<RwLock<MockServerState>>,
+}
+
+impl GroupGuard {
+    /// Return all the requests that have been matched by any of the scoped [`Mock`]s in the
+    /// group since they were mounted, grouped by mock in the order the mocks were passed to
+    /// [`MockServer::register_group_as_scoped`](crate::MockServer::register_group_as_scoped).
+    pub async fn received_requests(&self) -> Vec<Vec<crate::Request>> {
+        let state = self.server_state.read().await;
+        self.mock_ids
+            .iter()
+            .map(|mock_id| state.mock_set[*mock_id].0.received_requests())
+            .collect()
+    }
+}
+
+impl Drop for GroupGuard {
+    fn drop(&mut self) {
+        let future = async move {
+            let mut state = self.server_state.write().await;
+            let failed_reports: Vec<VerificationReport> = self
+                .mock_ids
+                .iter()
+                .map(|mock_id| state.mock_set.verify(*mock_id))
+                .filter(|report| !report.is_satisfied_on_drop())
+                .collect();
+
+            if failed_reports.is_empty() {
+                for mock_id in &self.mock_ids {
+                    state.mock_set.deactivate(*mock_id);
+                }
+                return;
+            }
+
+            let received_requests_message = state.received_requests_message();
+            let verifications_errors = failed_reports.iter().fold(String::new(), |mut s, r| {
+                r.trace_failure();
+                _ = writeln!(s, "- {}", r.error_message(&state.redaction.headers));
+                s
+            });
+            let error_message = format!(
+                "Verification failed for a group of scoped mocks:\n{}\n{}",
+                verifications_errors, received_requests_message
+            );
+            state.events.publish(ServerEvent::VerificationFailed {
+                message: error_message.clone(),
+            });
+            if std::thread::panicking() {
+                tracing::debug!("{}", &error_message);
+            } else {
+                panic!("{}", &error_message);
+            }
+        };
+        futures::executor::block_on(future);
+    }
+}
@@ -1,16 +1,47 @@
-use crate::mock_server::bare_server::{BareMockServer, RequestRecording};
+use crate::chaos::{Chaos, ChaosConfig};
+use crate::mock_server::bare_server::{
+    BareMockServer, Listeners, MetricsEndpoint, ProxyMode, RequestRecording, ServerConfig,
+    ServerRuntime,
+};
 use crate::mock_server::exposed_server::InnerServer;
-use crate::request::{BodyPrintLimit, BODY_PRINT_LIMIT};
-use crate::MockServer;
+use crate::request::{BodyPrintLimit, Redaction, BODY_PRINT_LIMIT};
+#[cfg(feature = "tls")]
+use crate::tls::TlsConfig;
+use crate::{MockServer, Request, SessionKey, VirtualClock};
+use http::header::HeaderName;
 use std::env;
 use std::net::TcpListener;
+use std::sync::Arc;
+use std::time::SystemTime;
 
 /// A builder providing a fluent API to assemble a [`MockServer`] step-by-step.  
 /// Use [`MockServer::builder`] to get started.
 pub struct MockServerBuilder {
     listener: Option<TcpListener>,
+    additional_listeners: Vec<TcpListener>,
+    http_redirect: bool,
     record_incoming_requests: bool,
     body_print_limit: BodyPrintLimit,
+    redaction: Redaction,
+    metrics_endpoint: bool,
+    http_proxy: bool,
+    max_request_body_size: Option<usize>,
+    http2_goaway_after: Option<usize>,
+    run_on_current_runtime: bool,
+    verify_on_drop: bool,
+    chaos: Option<ChaosConfig>,
+    random_seed: Option<u64>,
+    session_key: Option<SessionKey>,
+    virtual_clock: Option<VirtualClock>,
+    fixed_time: Option<SystemTime>,
+    deny_duplicate_mocks: bool,
+    debug_headers: bool,
+    #[cfg(feature = "debug-ui")]
+    debug_ui: bool,
+    #[cfg(feature = "tls")]
+    tls: Option<TlsConfig>,
+    #[cfg(feature = "tls")]
+    intercept_tls: bool,
 }
 
 impl MockServerBuilder {
@@ -24,8 +55,30 @@ impl MockServerBuilder {
         };
         Self {
             listener: None,
+            additional_listeners: vec![],
+            http_redirect: false,
             record_incoming_requests: true,
             body_print_limit,
+            redaction: Redaction::default(),
+            metrics_endpoint: false,
+            http_proxy: false,
+            max_request_body_size: None,
+            http2_goaway_after: None,
+            run_on_current_runtime: false,
+            verify_on_drop: true,
+            chaos: None,
+            random_seed: None,
+            session_key: None,
+            virtual_clock: None,
+            fixed_time: None,
+            deny_duplicate_mocks: false,
+            debug_headers: false,
+            #[cfg(feature = "debug-ui")]
+            debug_ui: false,
+            #[cfg(feature = "tls")]
+            tls: None,
+            #[cfg(feature = "tls")]
+            intercept_tls: false,
         }
     }
 
@@ -58,6 +111,290 @@ impl MockServerBuilder {
         self
     }
 
+    /// Let the [`MockServer`] listen on an additional socket, on top of the one set with
+    /// [`MockServerBuilder::listener`] (or the random port picked for it).
+    ///
+    /// The same mocks are served on all the listeners, and the requests received on any of
+    /// them are recorded in the same journal. Use [`MockServer::uris`] to get the base uris of
+    /// all of them - e.g. to check that a client follows a redirect to a different port.
+    ///
+    /// All listeners serve plain HTTP, unless [TLS](MockServerBuilder::tls) is enabled.
+    ///
+    /// ### Example:
+    /// ```rust
+    /// use wiremock::{MockServer, Mock, ResponseTemplate};
+    /// use wiremock::matchers::method;
+    ///
+    /// #[async_std::main]
+    /// async fn main() {
+    ///     // Arrange
+    ///     let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    ///     let mock_server = MockServer::builder().add_listener(listener).start().await;
+    ///     Mock::given(method("GET"))
+    ///         .respond_with(ResponseTemplate::new(200))
+    ///         .expect(2)
+    ///         .mount(&mock_server)
+    ///         .await;
+    ///
+    ///     // Act
+    ///     let uris = mock_server.uris();
+    ///     for uri in &uris {
+    ///         let status = surf::get(uri).await.unwrap().status();
+    ///         assert_eq!(status, 200);
+    ///     }
+    ///
+    ///     // Assert
+    ///     assert_eq!(uris.len(), 2);
+    ///     assert_eq!(uris[0], mock_server.uri());
+    /// }
+    /// ```
+    pub fn add_listener(mut self, listener: TcpListener) -> Self {
+        self.additional_listeners.push(listener);
+        self
+    }
+
+    /// Enable chaos mode: randomly inject server errors, latency or dropped connections on
+    /// top of the responses of the mocks that match incoming requests, as described by
+    /// [`ChaosConfig`].
+    ///
+    /// It lets you soak-test the resilience of your client - retries, timeouts, circuit
+    /// breakers - without writing bespoke responders. Set a [seed](ChaosConfig::seed) to make
+    /// a failing run reproducible.
+    ///
+    /// ### Example:
+    /// ```rust
+    /// use wiremock::{MockServer, Mock, ResponseTemplate};
+    /// use wiremock::chaos::{ChaosConfig, Fault};
+    /// use wiremock::matchers::method;
+    ///
+    /// #[async_std::main]
+    /// async fn main() {
+    ///     // Arrange
+    ///     let chaos = ChaosConfig::new()
+    ///         .error_rate(0.5)
+    ///         .faults([Fault::Status(500), Fault::Status(503)])
+    ///         .seed(42);
+    ///     let mock_server = MockServer::builder().chaos(chaos).start().await;
+    ///     Mock::given(method("GET"))
+    ///         .respond_with(ResponseTemplate::new(200))
+    ///         .expect(20)
+    ///         .mount(&mock_server)
+    ///         .await;
+    ///
+    ///     // Act
+    ///     let mut statuses = vec![];
+    ///     for _ in 0..20 {
+    ///         statuses.push(surf::get(&mock_server.uri()).await.unwrap().status());
+    ///     }
+    ///
+    ///     // Assert - some requests succeeded, others failed
+    ///     assert!(statuses.contains(&surf::StatusCode::Ok));
+    ///     assert!(statuses.iter().any(|status| status.is_server_error()));
+    /// }
+    /// ```
+    pub fn chaos(mut self, config: ChaosConfig) -> Self {
+        self.chaos = Some(config);
+        self
+    }
+
+    /// Drive the response delays set with
+    /// [`ResponseTemplate::set_delay`](crate::ResponseTemplate::set_delay) using `clock` rather
+    /// than the wall clock: a delayed response is sent as soon as `clock` has been advanced past
+    /// its delay - see [`VirtualClock`].
+    ///
+    /// ### Example:
+    /// ```rust
+    /// use std::time::Duration;
+    /// use wiremock::{MockServer, Mock, ResponseTemplate, VirtualClock};
+    /// use wiremock::matchers::method;
+    ///
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     // Arrange
+    ///     let clock = VirtualClock::new();
+    ///     let mock_server = MockServer::builder().virtual_clock(clock.clone()).start().await;
+    ///     Mock::given(method("GET"))
+    ///         .respond_with(ResponseTemplate::new(200).set_delay(Duration::from_secs(60)))
+    ///         .mount(&mock_server)
+    ///         .await;
+    ///
+    ///     // Act
+    ///     let response = tokio::spawn(reqwest::get(mock_server.uri()));
+    ///     while mock_server.received_requests().await.unwrap().is_empty() {
+    ///         tokio::time::sleep(Duration::from_millis(10)).await;
+    ///     }
+    ///     clock.advance(Duration::from_secs(60));
+    ///
+    ///     // Assert - the response is sent without waiting for a minute
+    ///     assert_eq!(response.await.unwrap().unwrap().status(), 200);
+    /// }
+    /// ```
+    pub fn virtual_clock(mut self, clock: VirtualClock) -> Self {
+        self.virtual_clock = Some(clock);
+        self
+    }
+
+    /// Freeze the clock of the response templates at `time`, to get the same responses every
+    /// time your test runs - e.g. to compare them with golden files.
+    ///
+    /// `time` is used for:
+    /// - the `Date` header, set on every response (shifted by
+    ///   [`ResponseTemplate::set_date_offset`](crate::ResponseTemplate::set_date_offset), if
+    ///   any);
+    /// - the `{{now}}` template variable (see
+    ///   [`ResponseTemplate::render_templates`](crate::ResponseTemplate::render_templates)),
+    ///   including in the attributes of the cookies - e.g.
+    ///   `Expires={{now offset='+1h' format='http'}}`.
+    ///
+    /// Response delays are not affected: drive them with a [`VirtualClock`] instead.
+    ///
+    /// ### Example:
+    /// ```rust
+    /// use std::time::{Duration, UNIX_EPOCH};
+    /// use wiremock::{MockServer, Mock, ResponseTemplate};
+    /// use wiremock::matchers::method;
+    ///
+    /// #[async_std::main]
+    /// async fn main() {
+    ///     // Arrange
+    ///     let mock_server = MockServer::builder()
+    ///         .with_fixed_time(UNIX_EPOCH + Duration::from_secs(1_709_210_096))
+    ///         .start()
+    ///         .await;
+    ///     let template = ResponseTemplate::new(200)
+    ///         .set_body_string("{{now}}")
+    ///         .add_cookie("session", "abc", ["Expires={{now offset='+1d' format='http'}}"])
+    ///         .render_templates();
+    ///     Mock::given(method("GET"))
+    ///         .respond_with(template)
+    ///         .mount(&mock_server)
+    ///         .await;
+    ///
+    ///     // Act
+    ///     let mut res = surf::get(&mock_server.uri()).await.unwrap();
+    ///
+    ///     // Assert
+    ///     assert_eq!(res.header("Date").unwrap(), "Thu, 29 Feb 2024 12:34:56 GMT");
+    ///     assert_eq!(
+    ///         res.header("Set-Cookie").unwrap(),
+    ///         "session=abc; Expires=Fri, 01 Mar 2024 12:34:56 GMT"
+    ///     );
+    ///     assert_eq!(res.body_string().await.unwrap(), "2024-02-29T12:34:56Z");
+    /// }
+    /// ```
+    pub fn with_fixed_time(mut self, time: SystemTime) -> Self {
+        self.fixed_time = Some(time);
+        self
+    }
+
+    /// Seed the source of random values of the [`MockServer`] - see [`Random`](crate::Random).
+    ///
+    /// Responders get it via [`ResponseContext::random`](crate::ResponseContext::random) and
+    /// templates via the `{{random ..}}` variables (see
+    /// [`ResponseTemplate::render_templates`](crate::ResponseTemplate::render_templates)).
+    /// With a seed, they generate the same values every time your test runs - as long as
+    /// requests reach the server in the same order. Without, a random seed is used.
+    ///
+    /// ### Example:
+    /// ```rust
+    /// use wiremock::{MockServer, Mock, ResponseTemplate};
+    /// use wiremock::matchers::method;
+    ///
+    /// #[async_std::main]
+    /// async fn main() {
+    ///     async fn order_id(seed: u64) -> String {
+    ///         let mock_server = MockServer::builder().random_seed(seed).start().await;
+    ///         Mock::given(method("POST"))
+    ///             .respond_with(
+    ///                 ResponseTemplate::new(201)
+    ///                     .set_body_string("{{random uuid}}")
+    ///                     .render_templates(),
+    ///             )
+    ///             .mount(&mock_server)
+    ///             .await;
+    ///         surf::post(&mock_server.uri()).recv_string().await.unwrap()
+    ///     }
+    ///
+    ///     // Act
+    ///     let first = order_id(42).await;
+    ///     let second = order_id(42).await;
+    ///
+    ///     // Assert
+    ///     assert_eq!(first, second);
+    ///     assert_ne!(first, order_id(7).await);
+    /// }
+    /// ```
+    pub fn random_seed(mut self, seed: u64) -> Self {
+        self.random_seed = Some(seed);
+        self
+    }
+
+    /// Choose how requests are grouped into sessions - see [`SessionKey`].
+    ///
+    /// Responders access the state of the session of the current request using
+    /// [`ResponseContext::session`](crate::ResponseContext::session). By default, requests
+    /// received on the same connection share a session.
+    ///
+    /// ### Example:
+    /// ```rust
+    /// use wiremock::{MockServer, SessionKey};
+    ///
+    /// #[async_std::main]
+    /// async fn main() {
+    ///     // Requests sending the same `session` cookie share a session.
+    ///     let mock_server = MockServer::builder()
+    ///         .session_key(SessionKey::Cookie("session".into()))
+    ///         .start()
+    ///         .await;
+    /// }
+    /// ```
+    pub fn session_key(mut self, key: SessionKey) -> Self {
+        self.session_key = Some(key);
+        self
+    }
+
+    /// Start an auxiliary listener that redirects every request it receives to the
+    /// [`MockServer`], with a `301 Moved Permanently` response pointing at the same path and
+    /// query on [`MockServer::uri`]. Use [`MockServer::http_redirect_uri`] to get its base uri.
+    ///
+    /// It lets you test clients that are expected to follow the redirect a server sends when
+    /// it is contacted on the "wrong" port - e.g. the HTTP to HTTPS upgrade performed by most
+    /// websites. Requests hitting the auxiliary listener are neither matched against the
+    /// mounted mocks nor recorded.
+    ///
+    /// The auxiliary listener serves plain HTTP, while the redirect points at
+    /// [`MockServer::uri`]: enable [TLS](MockServerBuilder::tls) to emulate the upgrade
+    /// to HTTPS.
+    ///
+    /// ### Example:
+    /// ```rust
+    /// use wiremock::{MockServer, Mock, ResponseTemplate};
+    /// use wiremock::matchers::path;
+    ///
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     // Arrange
+    ///     let mock_server = MockServer::builder().with_http_redirect().start().await;
+    ///     Mock::given(path("/login"))
+    ///         .respond_with(ResponseTemplate::new(200))
+    ///         .expect(1)
+    ///         .mount(&mock_server)
+    ///         .await;
+    ///     let redirect_uri = mock_server.http_redirect_uri().unwrap();
+    ///
+    ///     // Act
+    ///     let response = reqwest::get(format!("{}/login", redirect_uri)).await.unwrap();
+    ///
+    ///     // Assert - the client followed the redirect
+    ///     assert_eq!(response.status(), 200);
+    ///     assert_eq!(response.url().as_str(), format!("{}/login", mock_server.uri()));
+    /// }
+    /// ```
+    pub fn with_http_redirect(mut self) -> Self {
+        self.http_redirect = true;
+        self
+    }
+
     /// By default, [`MockServer`] will record all incoming requests to display
     /// more meaningful error messages when your expectations are not verified.
     ///
@@ -99,6 +436,592 @@ impl MockServerBuilder {
         self
     }
 
+    /// Hide the value of the specified headers in the requests recorded by the [`MockServer`].
+    ///
+    /// Matchers and responders still see the original headers, but the value is replaced by
+    /// `<redacted>` before the request is stored - in the request journal (see
+    /// [`MockServer::received_requests`] and [`MockServer::export_har`]) or by the mock that
+    /// matched it (see [`MockGuard::received_requests`](crate::MockGuard::received_requests)) -
+    /// or printed - e.g. in the panic message shown when expectations are not satisfied.
+    ///
+    /// Use it to keep secrets, such as `Authorization` tokens, out of your CI logs.
+    /// Header names are case-insensitive. It panics if one of them is not a valid header name.
+    ///
+    /// ### Example:
+    /// ```rust
+    /// use wiremock::{MockServer, Mock, ResponseTemplate};
+    /// use wiremock::matchers::header;
+    ///
+    /// #[async_std::main]
+    /// async fn main() {
+    ///     // Arrange
+    ///     let mock_server = MockServer::builder()
+    ///         .redact_headers(["Authorization", "X-Api-Key"])
+    ///         .start()
+    ///         .await;
+    ///     Mock::given(header("Authorization", "Bearer secret"))
+    ///         .respond_with(ResponseTemplate::new(200))
+    ///         .mount(&mock_server)
+    ///         .await;
+    ///
+    ///     // Act
+    ///     let status = surf::get(mock_server.uri())
+    ///         .header("Authorization", "Bearer secret")
+    ///         .await
+    ///         .unwrap()
+    ///         .status();
+    ///
+    ///     // Assert
+    ///     assert_eq!(status, 200);
+    ///     let received_requests = mock_server.received_requests().await.unwrap();
+    ///     assert_eq!(received_requests[0].headers["Authorization"], "<redacted>");
+    /// }
+    /// ```
+    pub fn redact_headers<I, N>(mut self, names: I) -> Self
+    where
+        I: IntoIterator<Item = N>,
+        N: AsRef<str>,
+    {
+        for name in names {
+            let name = name.as_ref();
+            let name = HeaderName::from_bytes(name.as_bytes())
+                .unwrap_or_else(|_| panic!("`{}` is not a valid header name", name));
+            self.redaction.headers.push(name);
+        }
+        self
+    }
+
+    /// Rewrite the body of the requests recorded by the [`MockServer`].
+    ///
+    /// `redact` is called with each incoming request and returns the body to store in its
+    /// place - in the request journal or by the mock that matched it, just like
+    /// [`MockServerBuilder::redact_headers`].
+    /// Matchers and responders still see the original body.
+    ///
+    /// ### Example:
+    /// ```rust
+    /// use wiremock::{MockServer, Request};
+    ///
+    /// #[async_std::main]
+    /// async fn main() {
+    ///     // Arrange
+    ///     let mock_server = MockServer::builder()
+    ///         .redact_body(|request: &Request| {
+    ///             if request.url.path() == "/login" {
+    ///                 b"<redacted>".to_vec()
+    ///             } else {
+    ///                 request.body.to_vec()
+    ///             }
+    ///         })
+    ///         .start()
+    ///         .await;
+    ///
+    ///     // Act
+    ///     surf::post(format!("{}/login", mock_server.uri()))
+    ///         .body("password=hunter2")
+    ///         .await
+    ///         .unwrap();
+    ///
+    ///     // Assert
+    ///     let received_requests = mock_server.received_requests().await.unwrap();
+    ///     assert_eq!(received_requests[0].body.as_ref(), b"<redacted>");
+    /// }
+    /// ```
+    pub fn redact_body<F>(mut self, redact: F) -> Self
+    where
+        F: Fn(&Request) -> Vec<u8> + Send + Sync + 'static,
+    {
+        self.redaction.body = Some(Arc::new(redact));
+        self
+    }
+
+    /// Expose the metrics of the [`MockServer`] at `GET /__admin/metrics`, in the
+    /// [Prometheus text format](https://prometheus.io/docs/instrumenting/exposition_formats/).
+    ///
+    /// It is meant for long-lived servers (e.g. a fake dependency in a load test) whose traffic
+    /// you want to scrape. The endpoint reports how many requests were received, how many of
+    /// them did not match any mock and, for each mounted mock, how many requests it matched
+    /// and the total time it spent responding (see [`MockServer::metrics`]).
+    ///
+    /// Requests to the metrics endpoint are not matched against the mounted mocks and are not
+    /// recorded.
+    ///
+    /// ### Example:
+    ///
+    /// ```rust
+    /// use wiremock::{MockServer, Mock, ResponseTemplate};
+    /// use wiremock::matchers::method;
+    ///
+    /// #[async_std::main]
+    /// async fn main() {
+    ///     // Arrange
+    ///     let mock_server = MockServer::builder().enable_metrics_endpoint().start().await;
+    ///     Mock::given(method("POST"))
+    ///         .respond_with(ResponseTemplate::new(200))
+    ///         .named("Ingest")
+    ///         .mount(&mock_server)
+    ///         .await;
+    ///     surf::post(&mock_server.uri()).await.unwrap();
+    ///
+    ///     // Act
+    ///     let metrics = surf::get(format!("{}/__admin/metrics", mock_server.uri()))
+    ///         .recv_string()
+    ///         .await
+    ///         .unwrap();
+    ///
+    ///     // Assert
+    ///     assert!(metrics.contains("wiremock_requests_total 1"));
+    ///     assert!(metrics.contains(r#"wiremock_mock_matches_total{mock="Ingest",position="0"} 1"#));
+    /// }
+    /// ```
+    pub fn enable_metrics_endpoint(mut self) -> Self {
+        self.metrics_endpoint = true;
+        self
+    }
+
+    /// Let the [`MockServer`] act as a forward HTTP proxy, so that you can point a whole
+    /// application at it - e.g. using the `HTTP_PROXY` environment variable - and mock
+    /// several hosts at once.
+    ///
+    /// Requests sent to a proxy use the absolute form (e.g. `GET http://api.example.com/users`),
+    /// which the [`MockServer`] always accepts. With `as_http_proxy`, it also accepts `CONNECT`
+    /// requests: the connection becomes a tunnel and the requests sent through it are matched
+    /// against the mounted mocks as if they had been sent to the requested host.
+    /// Use [`host`](crate::matchers::host) to tell the target hosts apart.
+    ///
+    /// The tunnel carries plain HTTP: the [`MockServer`] does not terminate TLS, hence clients
+    /// must not attempt a TLS handshake through it - unless
+    /// [`intercept_tls`](MockServerBuilder::intercept_tls) is enabled.
+    ///
+    /// ### Example:
+    /// ```rust
+    /// use wiremock::{MockServer, Mock, ResponseTemplate};
+    /// use wiremock::matchers::{host, path};
+    ///
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     // Arrange
+    ///     let mock_server = MockServer::builder().as_http_proxy().start().await;
+    ///     Mock::given(host("users.example.com"))
+    ///         .and(path("/users/1"))
+    ///         .respond_with(ResponseTemplate::new(200))
+    ///         .mount(&mock_server)
+    ///         .await;
+    ///     Mock::given(host("orders.example.com"))
+    ///         .respond_with(ResponseTemplate::new(204))
+    ///         .mount(&mock_server)
+    ///         .await;
+    ///     let client = reqwest::Client::builder()
+    ///         .proxy(reqwest::Proxy::http(mock_server.uri()).unwrap())
+    ///         .build()
+    ///         .unwrap();
+    ///
+    ///     // Act
+    ///     let user = client.get("http://users.example.com/users/1").send().await.unwrap();
+    ///     let orders = client.get("http://orders.example.com/").send().await.unwrap();
+    ///
+    ///     // Assert
+    ///     assert_eq!(user.status(), 200);
+    ///     assert_eq!(orders.status(), 204);
+    /// }
+    /// ```
+    pub fn as_http_proxy(mut self) -> Self {
+        self.http_proxy = true;
+        self
+    }
+
+    /// Act as a forward HTTP proxy - see [`MockServerBuilder::as_http_proxy`] - terminating
+    /// TLS in the `CONNECT` tunnels, so that requests sent to `https://` URLs can be mocked too.
+    ///
+    /// The [`MockServer`] generates a certificate authority when it starts, and presents in
+    /// each tunnel a certificate it issued for the requested host. Add the certificate of the
+    /// authority - see [`MockServer::proxy_ca_certificate`] - to the root certificates trusted
+    /// by your client.
+    /// Every tunnel is expected to carry TLS. It requires the `tls` feature.
+    ///
+    /// ### Example:
+    /// ```rust
+    /// use wiremock::{MockServer, Mock, ResponseTemplate};
+    /// use wiremock::matchers::{host, path};
+    ///
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     // Arrange
+    ///     let mock_server = MockServer::builder().intercept_tls().start().await;
+    ///     Mock::given(host("api.example.com"))
+    ///         .and(path("/users/1"))
+    ///         .respond_with(ResponseTemplate::new(200))
+    ///         .mount(&mock_server)
+    ///         .await;
+    ///     let ca_certificate = mock_server.proxy_ca_certificate().unwrap();
+    ///     let client = reqwest::Client::builder()
+    ///         .proxy(reqwest::Proxy::https(mock_server.uri()).unwrap())
+    ///         .add_root_certificate(reqwest::Certificate::from_pem(ca_certificate.as_bytes()).unwrap())
+    ///         .build()
+    ///         .unwrap();
+    ///
+    ///     // Act
+    ///     let user = client.get("https://api.example.com/users/1").send().await.unwrap();
+    ///
+    ///     // Assert
+    ///     assert_eq!(user.status(), 200);
+    /// }
+    /// ```
+    ///
+    /// [`MockServer::proxy_ca_certificate`]: crate::MockServer::proxy_ca_certificate
+    #[cfg(feature = "tls")]
+    pub fn intercept_tls(mut self) -> Self {
+        self.http_proxy = true;
+        self.intercept_tls = true;
+        self
+    }
+
+    /// By default the [`MockServer`] reads the whole body of incoming requests in memory,
+    /// however large it is.
+    ///
+    /// With `max_request_body_size`, requests with a body longer than `bytes` are rejected
+    /// with a `413 Payload Too Large` response - without being matched against the mounted
+    /// mocks - and the connection is closed. Only the first `bytes` bytes of their body are
+    /// read and recorded: it protects your test host when fuzzing clients that might send
+    /// huge payloads.
+    ///
+    /// ### Example:
+    /// ```rust
+    /// use wiremock::{MockServer, Mock, ResponseTemplate};
+    /// use wiremock::matchers::method;
+    ///
+    /// #[async_std::main]
+    /// async fn main() {
+    ///     // Arrange
+    ///     let mock_server = MockServer::builder()
+    ///         .max_request_body_size(8)
+    ///         .start()
+    ///         .await;
+    ///     Mock::given(method("POST"))
+    ///         .respond_with(ResponseTemplate::new(201))
+    ///         .mount(&mock_server)
+    ///         .await;
+    ///
+    ///     // Act
+    ///     let small = surf::post(&mock_server.uri()).body("tiny").await.unwrap();
+    ///     let large = surf::post(&mock_server.uri()).body("far too large").await.unwrap();
+    ///
+    ///     // Assert
+    ///     assert_eq!(small.status(), 201);
+    ///     assert_eq!(large.status(), 413);
+    ///     let received_requests = mock_server.received_requests().await.unwrap();
+    ///     assert_eq!(received_requests[1].body.as_ref(), b"far too ");
+    /// }
+    /// ```
+    pub fn max_request_body_size(mut self, bytes: usize) -> Self {
+        self.max_request_body_size = Some(bytes);
+        self
+    }
+
+    /// Gracefully shut down each HTTP/2 connection with a `GOAWAY` frame once it has carried
+    /// `n_streams` requests - to test how your client moves its requests to a new connection.
+    ///
+    /// The requests already received on the connection are still answered: clients must open
+    /// a new connection for the following ones. HTTP/1.x connections are not affected.
+    ///
+    /// It panics if `n_streams` is 0.
+    ///
+    /// ### Example:
+    /// ```rust
+    /// use wiremock::{MockServer, Mock, ResponseTemplate};
+    /// use wiremock::matchers::method;
+    ///
+    /// #[tokio::main(flavor = "current_thread")]
+    /// async fn main() {
+    ///     // Arrange
+    ///     let mock_server = MockServer::builder()
+    ///         .http2_goaway_after(2)
+    ///         .start()
+    ///         .await;
+    ///     Mock::given(method("GET"))
+    ///         .respond_with(ResponseTemplate::new(200))
+    ///         .mount(&mock_server)
+    ///         .await;
+    ///     let client = reqwest::Client::builder()
+    ///         .http2_prior_knowledge()
+    ///         .build()
+    ///         .unwrap();
+    ///
+    ///     // Act
+    ///     for _ in 0..4 {
+    ///         let response = client.get(&mock_server.uri()).send().await.unwrap();
+    ///         assert_eq!(response.status(), 200);
+    ///     }
+    ///
+    ///     // Assert
+    ///     assert_eq!(mock_server.connection_count().await, Some(2));
+    /// }
+    /// ```
+    pub fn http2_goaway_after(mut self, n_streams: usize) -> Self {
+        assert!(n_streams > 0, "n_streams must be strictly greater than 0!");
+        self.http2_goaway_after = Some(n_streams);
+        self
+    }
+
+    /// By default the [`MockServer`] runs on its own thread, with its own `tokio` runtime.
+    ///
+    /// With `run_on_current_runtime`, the [`MockServer`] is spawned as a task on the `tokio`
+    /// runtime [`start`](MockServerBuilder::start) is called from instead: no thread is
+    /// spawned and the response delays follow the clock of your runtime - e.g. they elapse
+    /// instantly when time is paused with `tokio::time::pause`.
+    /// The [`MockServer`] stops serving requests when the runtime shuts down, or when it is
+    /// dropped: the connections still open are closed, dropping the requests they carry. On a
+    /// current-thread runtime, it only makes progress while your test is awaiting: blocking
+    /// calls waiting for one of its responses hang forever.
+    ///
+    /// With the `async-std` feature, [`start`](MockServerBuilder::start) can also be called
+    /// from an `async-std` task: the [`MockServer`] then runs on the `async-std` executor,
+    /// while its IO and timers are driven by a `tokio` runtime on a background thread, shared
+    /// by all such servers. Otherwise, [`start`](MockServerBuilder::start) panics if it is not
+    /// called from within a `tokio` runtime.
+    ///
+    /// ### Example:
+    /// ```rust
+    /// use std::time::Duration;
+    /// use wiremock::{MockServer, Mock, ResponseTemplate};
+    /// use wiremock::matchers::method;
+    ///
+    /// #[tokio::main(flavor = "current_thread", start_paused = true)]
+    /// async fn main() {
+    ///     // Arrange
+    ///     let mock_server = MockServer::builder().run_on_current_runtime().start().await;
+    ///     let delay = Duration::from_secs(60 * 60);
+    ///     Mock::given(method("GET"))
+    ///         .respond_with(ResponseTemplate::new(200).set_delay(delay))
+    ///         .mount(&mock_server)
+    ///         .await;
+    ///
+    ///     // Act - time is paused: the runtime skips the delay instead of waiting an hour
+    ///     let started_at = tokio::time::Instant::now();
+    ///     let response = reqwest::get(&mock_server.uri()).await.unwrap();
+    ///
+    ///     // Assert
+    ///     assert_eq!(response.status(), 200);
+    ///     assert!(started_at.elapsed() >= delay);
+    /// }
+    /// ```
+    pub fn run_on_current_runtime(mut self) -> Self {
+        self.run_on_current_runtime = true;
+        self
+    }
+
+    /// By default the [`MockServer`] verifies the expectations of its mocks when it is dropped,
+    /// panicking if they are not satisfied.
+    ///
+    /// With `verify_on_drop(false)`, the [`MockServer`] - and the guards of the scoped mocks
+    /// mounted on it - skip the verification on drop: expectations are only checked when you
+    /// call [`MockServer::verify`] or [`MockServer::shutdown`].
+    /// It comes in handy for long-lived servers - e.g. shared across tests or used as a fake
+    /// during development - that are verified at chosen points rather than when they go out
+    /// of scope.
+    /// Use [`Mock::skip_drop_verification`](crate::Mock::skip_drop_verification) to opt out
+    /// for a single mock.
+    ///
+    /// ### Example:
+    /// ```rust
+    /// use wiremock::{MockServer, Mock, ResponseTemplate};
+    /// use wiremock::matchers::method;
+    ///
+    /// #[async_std::main]
+    /// async fn main() {
+    ///     // Arrange
+    ///     let mock_server = MockServer::builder().verify_on_drop(false).start().await;
+    ///     Mock::given(method("GET"))
+    ///         .respond_with(ResponseTemplate::new(200))
+    ///         .expect(1)
+    ///         .mount(&mock_server)
+    ///         .await;
+    ///
+    ///     // Act
+    ///     surf::get(&mock_server.uri()).await.unwrap();
+    ///
+    ///     // Assert - verify at a chosen point...
+    ///     mock_server.verify().await;
+    ///     // ...the unmet expectations of the mocks mounted later on are not checked on drop.
+    ///     Mock::given(method("POST"))
+    ///         .respond_with(ResponseTemplate::new(201))
+    ///         .expect(1)
+    ///         .mount(&mock_server)
+    ///         .await;
+    /// }
+    /// ```
+    pub fn verify_on_drop(mut self, verify: bool) -> Self {
+        self.verify_on_drop = verify;
+        self
+    }
+
+    /// Panic when a [`Mock`](crate::Mock) with the same matchers as a mock that is already
+    /// mounted is mounted on the [`MockServer`].
+    ///
+    /// Duplicated mocks - e.g. mounted twice by a test helper - are usually a bug: only one of
+    /// them ever matches, silently changing the call counts you expect. By default, mounting a
+    /// duplicate logs a warning.
+    ///
+    /// Only the mocks built out of the built-in matchers comparing against a fixed value
+    /// (e.g. [`method`](crate::matchers::method), [`path`](crate::matchers::path) or
+    /// [`header`](crate::matchers::header)) can be recognised as duplicates. Mocks that match a
+    /// limited number of requests (see [`Mock::up_to_n_times`](crate::Mock::up_to_n_times))
+    /// are never considered duplicates: that is how you return different responses over time.
+    ///
+    /// ### Example:
+    /// ```rust,should_panic
+    /// use wiremock::{MockServer, Mock, ResponseTemplate};
+    /// use wiremock::matchers::{method, path};
+    ///
+    /// #[async_std::main]
+    /// async fn main() {
+    ///     let mock_server = MockServer::builder().deny_duplicate_mocks().start().await;
+    ///     let mock = || Mock::given(method("GET")).and(path("/users")).respond_with(ResponseTemplate::new(200));
+    ///     mock().mount(&mock_server).await;
+    ///
+    ///     // Panics!
+    ///     mock().mount(&mock_server).await;
+    /// }
+    /// ```
+    pub fn deny_duplicate_mocks(mut self) -> Self {
+        self.deny_duplicate_mocks = true;
+        self
+    }
+
+    /// Add headers to every response, identifying the [`Mock`](crate::Mock) that produced it:
+    ///
+    /// - `x-wiremock-matched-mock`, set to the name of the mock (see
+    ///   [`Mock::named`](crate::Mock::named)) or to its position (e.g. `Mock #2`) if it has
+    ///   no name;
+    /// - `x-wiremock-unmatched: true`, if no mock matched the request.
+    ///
+    /// They show up in the logs of the client you are testing, telling you straight away which
+    /// mock produced which response. Disabled by default.
+    ///
+    /// ### Example:
+    /// ```rust
+    /// use wiremock::{MockServer, Mock, ResponseTemplate};
+    /// use wiremock::matchers::path;
+    ///
+    /// #[async_std::main]
+    /// async fn main() {
+    ///     // Arrange
+    ///     let mock_server = MockServer::builder().debug_headers(true).start().await;
+    ///     Mock::given(path("/users"))
+    ///         .respond_with(ResponseTemplate::new(200))
+    ///         .named("List users")
+    ///         .mount(&mock_server)
+    ///         .await;
+    ///
+    ///     // Act
+    ///     let matched = surf::get(format!("{}/users", mock_server.uri())).await.unwrap();
+    ///     let unmatched = surf::get(format!("{}/orders", mock_server.uri())).await.unwrap();
+    ///
+    ///     // Assert
+    ///     assert_eq!(matched.header("x-wiremock-matched-mock").unwrap(), "List users");
+    ///     assert_eq!(unmatched.header("x-wiremock-unmatched").unwrap(), "true");
+    /// }
+    /// ```
+    pub fn debug_headers(mut self, enabled: bool) -> Self {
+        self.debug_headers = enabled;
+        self
+    }
+
+    /// Serve the mocks over HTTPS, presenting the certificate in `config`.
+    ///
+    /// [`MockServer::uri`] then uses the `https` scheme. The protocol version and the cipher
+    /// suite negotiated with the client are recorded on each [`Request`] - see
+    /// [`Request::tls`] - and can be matched with
+    /// [`matchers::tls_version_at_least`](crate::matchers::tls_version_at_least) and
+    /// [`matchers::tls_cipher_suite`](crate::matchers::tls_cipher_suite).
+    /// Interim responses - see
+    /// [`ResponseTemplate::append_interim_response`](crate::ResponseTemplate::append_interim_response) -
+    /// are not sent over TLS. It requires the `tls` feature.
+    ///
+    /// ### Example:
+    /// ```rust
+    /// use wiremock::{MockServer, Mock, ResponseTemplate};
+    /// use wiremock::matchers::tls_version_at_least;
+    /// use wiremock::tls::{TlsConfig, TlsVersion};
+    ///
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     // Arrange
+    ///     let cert_chain = std::fs::read("tests/fixtures/tls/localhost.pem").unwrap();
+    ///     let private_key = std::fs::read("tests/fixtures/tls/localhost.key").unwrap();
+    ///     let mock_server = MockServer::builder()
+    ///         .tls(TlsConfig::from_pem(cert_chain, private_key))
+    ///         .start()
+    ///         .await;
+    ///     Mock::given(tls_version_at_least(TlsVersion::Tls12))
+    ///         .respond_with(ResponseTemplate::new(200))
+    ///         .mount(&mock_server)
+    ///         .await;
+    ///
+    ///     // Act
+    ///     let client = reqwest::Client::builder()
+    ///         .danger_accept_invalid_certs(true)
+    ///         .build()
+    ///         .unwrap();
+    ///     let response = client.get(mock_server.uri()).send().await.unwrap();
+    ///
+    ///     // Assert
+    ///     assert!(mock_server.uri().starts_with("https://"));
+    ///     assert_eq!(response.status(), 200);
+    /// }
+    /// ```
+    ///
+    /// [`Request`]: crate::Request
+    /// [`Request::tls`]: crate::Request::tls
+    #[cfg(feature = "tls")]
+    pub fn tls(mut self, config: TlsConfig) -> Self {
+        self.tls = Some(config);
+        self
+    }
+
+    /// Serve a web page at `/__admin/ui` to inspect the [`MockServer`] while it runs: it lists
+    /// the mounted mocks, how many requests each of them matched and the latest received
+    /// requests - alongside the mock that matched them - refreshing every second.
+    ///
+    /// Each mock can be paused and resumed from the page, e.g. to see how the frontend you are
+    /// developing against the [`MockServer`] copes with an endpoint going missing.
+    ///
+    /// Requests to the page are neither matched against the mocks nor recorded.
+    /// It requires the `debug-ui` feature.
+    ///
+    /// ### Example:
+    /// ```rust
+    /// use wiremock::{MockServer, Mock, ResponseTemplate};
+    /// use wiremock::matchers::path;
+    ///
+    /// #[async_std::main]
+    /// async fn main() {
+    ///     // Arrange
+    ///     let mock_server = MockServer::builder().enable_debug_ui().start().await;
+    ///     Mock::given(path("/users"))
+    ///         .respond_with(ResponseTemplate::new(200))
+    ///         .mount(&mock_server)
+    ///         .await;
+    ///
+    ///     // Act
+    ///     let url = format!("{}/__admin/ui", mock_server.uri());
+    ///     let mut page = surf::get(&url).await.unwrap();
+    ///     let toggled = surf::post(format!("{}/mocks/0/toggle", url)).await.unwrap();
+    ///     let users = surf::get(format!("{}/users", mock_server.uri())).await.unwrap();
+    ///
+    ///     // Assert
+    ///     assert!(page.body_string().await.unwrap().contains("Mounted mocks"));
+    ///     assert_eq!(toggled.status(), 204);
+    ///     // The mock has been paused.
+    ///     assert_eq!(users.status(), 404);
+    /// }
+    /// ```
+    #[cfg(feature = "debug-ui")]
+    pub fn enable_debug_ui(mut self) -> Self {
+        self.debug_ui = true;
+        self
+    }
+
     /// Finalise the builder to get an instance of a [`BareMockServer`].
     pub(super) async fn build_bare(self) -> BareMockServer {
         let listener = if let Some(listener) = self.listener {
@@ -106,16 +1029,73 @@ impl MockServerBuilder {
         } else {
             TcpListener::bind("127.0.0.1:0").expect("Failed to bind an OS port for a mock server.")
         };
+        let mut mock_listeners = vec![listener];
+        mock_listeners.extend(self.additional_listeners);
+        let http_redirect = if self.http_redirect {
+            Some(
+                TcpListener::bind("127.0.0.1:0")
+                    .expect("Failed to bind an OS port for the redirect listener."),
+            )
+        } else {
+            None
+        };
+        #[cfg(feature = "tls")]
+        let tls = self.tls.as_ref().map(TlsConfig::acceptor);
+        #[cfg(not(feature = "tls"))]
+        let tls = None;
+        let listeners = Listeners {
+            mocks: mock_listeners,
+            http_redirect,
+            tls,
+        };
         let recording = if self.record_incoming_requests {
             RequestRecording::Enabled
         } else {
             RequestRecording::Disabled
         };
-        BareMockServer::start(listener, recording, self.body_print_limit).await
+        let metrics_endpoint = if self.metrics_endpoint {
+            MetricsEndpoint::Enabled
+        } else {
+            MetricsEndpoint::Disabled
+        };
+        let proxy_mode = match self.http_proxy {
+            #[cfg(feature = "tls")]
+            true if self.intercept_tls => {
+                ProxyMode::Intercepting(Arc::new(crate::tls::CertificateAuthority::generate()))
+            }
+            true => ProxyMode::Enabled,
+            false => ProxyMode::Disabled,
+        };
+        let server_runtime = if self.run_on_current_runtime {
+            ServerRuntime::Current
+        } else {
+            ServerRuntime::Dedicated
+        };
+        let config = ServerConfig {
+            request_recording: recording,
+            body_print_limit: self.body_print_limit,
+            redaction: self.redaction,
+            metrics_endpoint,
+            proxy_mode,
+            max_request_body_size: self.max_request_body_size,
+            server_runtime,
+            chaos: self.chaos.map(Chaos::new),
+            virtual_clock: self.virtual_clock,
+            fixed_time: self.fixed_time,
+            goaway_after: self.http2_goaway_after,
+            random_seed: self.random_seed,
+            session_key: self.session_key,
+            deny_duplicate_mocks: self.deny_duplicate_mocks,
+            debug_headers: self.debug_headers,
+            #[cfg(feature = "debug-ui")]
+            debug_ui: self.debug_ui,
+        };
+        BareMockServer::start(listeners, config).await
     }
 
     /// Finalise the builder and launch the [`MockServer`] instance!
     pub async fn start(self) -> MockServer {
-        MockServer::new(InnerServer::Bare(self.build_bare().await))
+        let verify_on_drop = self.verify_on_drop;
+        MockServer::new(InnerServer::Bare(self.build_bare().await), verify_on_drop)
     }
 }
@@ -0,0 +1,112 @@
+//! Direct access to the socket of a connection, next to `hyper`, to send what `hyper` does not
+//! let us send - e.g. interim `1xx` responses or raw, possibly malformed, responses.
+use http::{HeaderMap, StatusCode};
+use std::io;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{ready, Context, Poll};
+use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
+use tokio::net::TcpStream;
+
+/// The IO of a connection served by `hyper`, sharing its socket with [`Connection`].
+pub(super) struct SharedStream(Arc<TcpStream>);
+
+/// A handle on the socket of the connection a request was received on, inserted in the
+/// extensions of the request.
+#[derive(Clone)]
+pub(super) struct Connection(Arc<TcpStream>);
+
+/// Wrap `stream` for `hyper`, returning a handle to write to it directly.
+pub(super) fn share(stream: TcpStream) -> (SharedStream, Connection) {
+    let stream = Arc::new(stream);
+    (SharedStream(stream.clone()), Connection(stream))
+}
+
+impl Connection {
+    /// Send an interim response - e.g. `103 Early Hints` - ahead of the final one.
+    ///
+    /// It must only be used on HTTP/1.1 connections, while `hyper` is waiting for the
+    /// response to the current request: it is then not writing to the socket itself.
+    pub(super) async fn send_interim_response(
+        &self,
+        status: StatusCode,
+        headers: &HeaderMap,
+    ) -> io::Result<()> {
+        let mut head = format!(
+            "HTTP/1.1 {} {}\r\n",
+            status.as_u16(),
+            status.canonical_reason().unwrap_or_default()
+        )
+        .into_bytes();
+        for (name, value) in headers {
+            head.extend_from_slice(name.as_str().as_bytes());
+            head.extend_from_slice(b": ");
+            head.extend_from_slice(value.as_bytes());
+            head.extend_from_slice(b"\r\n");
+        }
+        head.extend_from_slice(b"\r\n");
+        self.write_all(&head).await
+    }
+
+    /// Write `bytes` to the socket, as they are.
+    ///
+    /// The same restrictions as for [`Connection::send_interim_response`] apply.
+    pub(super) async fn write_all(&self, bytes: &[u8]) -> io::Result<()> {
+        let mut written = 0;
+        while written < bytes.len() {
+            self.0.writable().await?;
+            match self.0.try_write(&bytes[written..]) {
+                Ok(n) => written += n,
+                Err(e) if e.kind() == io::ErrorKind::WouldBlock => continue,
+                Err(e) => return Err(e),
+            }
+        }
+        Ok(())
+    }
+}
+
+impl AsyncRead for SharedStream {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        loop {
+            ready!(self.0.poll_read_ready(cx))?;
+            match self.0.try_read(buf.initialize_unfilled()) {
+                Ok(n) => {
+                    buf.advance(n);
+                    return Poll::Ready(Ok(()));
+                }
+                Err(e) if e.kind() == io::ErrorKind::WouldBlock => continue,
+                Err(e) => return Poll::Ready(Err(e)),
+            }
+        }
+    }
+}
+
+impl AsyncWrite for SharedStream {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        loop {
+            ready!(self.0.poll_write_ready(cx))?;
+            match self.0.try_write(buf) {
+                Err(e) if e.kind() == io::ErrorKind::WouldBlock => continue,
+                result => return Poll::Ready(result),
+            }
+        }
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        // Writes go straight to the socket.
+        Poll::Ready(Ok(()))
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        // The socket is closed once both `hyper` and the in-flight requests are done with it.
+        Poll::Ready(Ok(()))
+    }
+}
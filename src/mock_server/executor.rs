@@ -0,0 +1,122 @@
+//! Where the tasks of a running `BareMockServer` are executed - see `ServerRuntime`.
+use std::future::Future;
+#[cfg(feature = "async-std")]
+use std::pin::Pin;
+#[cfg(feature = "async-std")]
+use std::task::{Context, Poll};
+
+/// Spawns the tasks of a server: its listeners and the connections they accept.
+#[derive(Clone)]
+pub(super) enum Executor {
+    /// A `tokio` runtime - either the dedicated one of the server or the caller's one.
+    Tokio(tokio::runtime::Handle),
+    /// The `async-std` executor of the caller.
+    ///
+    /// The server is built on `tokio`'s IO and timers: they are driven by a `tokio` runtime
+    /// running on a background thread, entered whenever one of the tasks is polled.
+    #[cfg(feature = "async-std")]
+    AsyncStd(tokio::runtime::Handle),
+}
+
+impl Executor {
+    /// The runtime the caller is running on.
+    ///
+    /// It panics if it is neither a `tokio` runtime, nor an `async-std` one with the
+    /// `async-std` feature enabled.
+    pub(super) fn current() -> Self {
+        if let Ok(handle) = tokio::runtime::Handle::try_current() {
+            return Executor::Tokio(handle);
+        }
+        #[cfg(feature = "async-std")]
+        if async_std::task::try_current().is_some() {
+            return Executor::AsyncStd(io_driver());
+        }
+        panic!(
+            "A MockServer running on the current runtime must be started within a tokio runtime, or within an async-std one with the `async-std` feature enabled"
+        )
+    }
+
+    /// Run `future` in the background.
+    pub(super) fn spawn<F>(&self, future: F)
+    where
+        F: Future<Output = ()> + Send + 'static,
+    {
+        match self {
+            Executor::Tokio(handle) => {
+                handle.spawn(future);
+            }
+            #[cfg(feature = "async-std")]
+            Executor::AsyncStd(io_driver) => {
+                async_std::task::spawn(WithIoDriver {
+                    io_driver: io_driver.clone(),
+                    future: Box::pin(future),
+                });
+            }
+        }
+    }
+
+    /// Build a `future` that uses `tokio`'s IO or timers - e.g. `tokio::time::sleep`.
+    pub(super) fn build<F: Future>(&self, build: impl FnOnce() -> F) -> F {
+        match self {
+            Executor::Tokio(handle) => {
+                let _entered = handle.enter();
+                build()
+            }
+            #[cfg(feature = "async-std")]
+            Executor::AsyncStd(io_driver) => {
+                let _entered = io_driver.enter();
+                build()
+            }
+        }
+    }
+}
+
+/// `hyper` spawns background tasks to serve HTTP/2 connections.
+impl<F> hyper::rt::Executor<F> for Executor
+where
+    F: Future + Send + 'static,
+    F::Output: Send + 'static,
+{
+    fn execute(&self, future: F) {
+        self.spawn(async move {
+            future.await;
+        });
+    }
+}
+
+/// A process-wide `tokio` runtime, only driving IO and timers for the servers running on
+/// `async-std`.
+#[cfg(feature = "async-std")]
+fn io_driver() -> tokio::runtime::Handle {
+    static IO_DRIVER: once_cell::sync::Lazy<tokio::runtime::Handle> =
+        once_cell::sync::Lazy::new(|| {
+            let runtime = tokio::runtime::Builder::new_current_thread()
+                .enable_all()
+                .build()
+                .expect("Cannot build the tokio runtime driving IO and timers");
+            let handle = runtime.handle().clone();
+            std::thread::Builder::new()
+                .name("wiremock-io-driver".into())
+                .spawn(move || runtime.block_on(std::future::pending::<()>()))
+                .expect("Cannot spawn the thread driving IO and timers");
+            handle
+        });
+    IO_DRIVER.clone()
+}
+
+/// Enter the `tokio` runtime driving IO and timers every time `future` is polled.
+#[cfg(feature = "async-std")]
+struct WithIoDriver {
+    io_driver: tokio::runtime::Handle,
+    future: Pin<Box<dyn Future<Output = ()> + Send>>,
+}
+
+#[cfg(feature = "async-std")]
+impl Future for WithIoDriver {
+    type Output = ();
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        let _entered = self.io_driver.enter();
+        self.future.as_mut().poll(cx)
+    }
+}
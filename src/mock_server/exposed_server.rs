@@ -1,11 +1,30 @@
+use crate::events::ServerEvent;
+use crate::fixtures::FixtureWatcher;
+use crate::har::{self, MatchStrategy};
+use crate::lint::MockLint;
+use crate::metrics::MockMetrics;
 use crate::mock_server::bare_server::BareMockServer;
-use crate::mock_server::pool::{get_pooled_mock_server, PooledMockServer};
+use crate::mock_server::pool::{
+    default_mocks, get_pooled_mock_server, pooling_enabled, PooledMockServer,
+};
 use crate::mock_server::MockServerBuilder;
-use crate::{mock::Mock, verification::VerificationOutcome, MockGuard, Request};
-use log::debug;
+use crate::respond::Responder;
+use crate::stub::Stub;
+use crate::{
+    mock::Mock, verification::VerificationOutcome, GroupGuard, JournalEntry, Match, MockGuard,
+    Request, RequestAssertions, Times, VirtualHost,
+};
+use crate::{RedirectChain, Respond};
+use futures::Stream;
 use std::fmt::{Debug, Write};
+use std::future::Future;
 use std::net::SocketAddr;
 use std::ops::Deref;
+use std::panic::Location;
+use std::path::Path;
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::debug;
 
 /// An HTTP web-server running in the background to behave as one of your dependencies using [`Mock`]s
 /// for testing purposes.
@@ -28,8 +47,49 @@ use std::ops::Deref;
 /// instead of [`MockServer::register`].
 ///
 /// You can register as many [`Mock`]s as your scenario requires on a `MockServer`.
+pub struct MockServer {
+    inner: InnerServer,
+    /// Set by [`MockServer::shutdown`]: expectations have already been verified.
+    shut_down: bool,
+    /// Whether expectations are verified when the server is dropped - see
+    /// [`MockServerBuilder::verify_on_drop`].
+    verify_on_drop: bool,
+}
+
+impl Debug for MockServer {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("MockServer").field(&self.inner).finish()
+    }
+}
+
+/// The error returned by [`MockServer::shutdown`].
 #[derive(Debug)]
-pub struct MockServer(InnerServer);
+#[non_exhaustive]
+pub enum ShutdownError {
+    /// Some requests were still being served when the grace period expired.
+    Timeout {
+        /// How many requests were still in-flight.
+        in_flight: usize,
+    },
+    /// Some mocks did not satisfy their expectations: the message details which ones, as
+    /// [`MockServer::verify`] would.
+    VerificationFailed(String),
+}
+
+impl std::fmt::Display for ShutdownError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ShutdownError::Timeout { in_flight } => write!(
+                f,
+                "{} request(s) were still in-flight when the mock server shut down",
+                in_flight
+            ),
+            ShutdownError::VerificationFailed(message) => write!(f, "{}", message),
+        }
+    }
+}
+
+impl std::error::Error for ShutdownError {}
 
 /// `MockServer` is either a wrapper around a `BareMockServer` retrieved from an
 /// object pool or a wrapper around an exclusive `BareMockServer`.
@@ -56,8 +116,12 @@ impl Deref for InnerServer {
 }
 
 impl MockServer {
-    pub(super) fn new(server: InnerServer) -> Self {
-        Self(server)
+    pub(super) fn new(server: InnerServer, verify_on_drop: bool) -> Self {
+        Self {
+            inner: server,
+            shut_down: false,
+            verify_on_drop,
+        }
     }
 
     /// You can use `MockServer::builder` if you need to specify custom configuration - e.g.
@@ -112,7 +176,14 @@ impl MockServer {
     /// }
     /// ```
     pub async fn start() -> Self {
-        Self(InnerServer::Pooled(get_pooled_mock_server().await))
+        let mock_server = if pooling_enabled() {
+            Self::new(InnerServer::Pooled(get_pooled_mock_server().await), true)
+        } else {
+            Self::builder().start().await
+        };
+        // Not through `register_many`: the default mocks already know where they come from.
+        mock_server.inner.register_many(default_mocks()).await;
+        mock_server
     }
 
     /// Register a [`Mock`] on an instance of `MockServer`.  
@@ -159,8 +230,54 @@ impl MockServer {
     /// ```
     ///
     /// [`mount`]: Mock::mount
-    pub async fn register(&self, mock: Mock) {
-        self.0.register(mock).await;
+    // `#[track_caller]` is not supported on `async fn`s: we return a future instead, to
+    // capture the caller location before it is polled.
+    #[track_caller]
+    pub fn register(&self, mut mock: Mock) -> impl Future<Output = ()> + '_ {
+        mock.registered_at = Some(Location::caller());
+        self.inner.register(mock)
+    }
+
+    /// Register a batch of [`Mock`]s on an instance of `MockServer`, atomically.
+    ///
+    /// Registering mocks one at a time leaves a window where a request can be handled while
+    /// only some of them are mounted. `register_many` mounts all of them in one go: a request
+    /// sees either none of them or all of them.
+    /// Use [`MockServer::register_group_as_scoped`] if you want them to be scoped.
+    ///
+    /// ### Example:
+    /// ```rust
+    /// use wiremock::{MockServer, Mock, ResponseTemplate};
+    /// use wiremock::matchers::path;
+    ///
+    /// #[async_std::main]
+    /// async fn main() {
+    ///     // Arrange
+    ///     let mock_server = MockServer::start().await;
+    ///     mock_server
+    ///         .register_many(vec![
+    ///             Mock::given(path("/v2/catalog")).respond_with(ResponseTemplate::new(200)),
+    ///             Mock::given(path("/v1/catalog")).respond_with(ResponseTemplate::new(410)),
+    ///         ])
+    ///         .await;
+    ///
+    ///     // Act
+    ///     let status = surf::get(format!("{}/v1/catalog", mock_server.uri()))
+    ///         .await
+    ///         .unwrap()
+    ///         .status();
+    ///
+    ///     // Assert
+    ///     assert_eq!(status, 410);
+    /// }
+    /// ```
+    #[track_caller]
+    pub fn register_many(&self, mut mocks: Vec<Mock>) -> impl Future<Output = ()> + '_ {
+        let location = Location::caller();
+        for mock in &mut mocks {
+            mock.registered_at = Some(location);
+        }
+        self.inner.register_many(mocks)
     }
 
     /// Register a **scoped** [`Mock`] on an instance of `MockServer`.
@@ -177,13 +294,13 @@ impl MockServer {
     ///
     /// When expectations of a scoped [`Mock`] are not verified, it will trigger a panic - just like a normal [`Mock`].
     /// Due to [limitations](https://internals.rust-lang.org/t/should-drop-glue-use-track-caller/13682) in Rust's `Drop` trait,
-    /// the panic message will not include the filename and the line location
-    /// where the corresponding [`MockGuard`] was dropped - it will point into `wiremock`'s source code.  
+    /// the location of the panic will not be the line where the corresponding [`MockGuard`] was dropped - it will
+    /// point into `wiremock`'s source code.
     ///
-    /// This can be an issue when you are using more than one scoped [`Mock`] in a single test - which of them panicked?  
-    /// To improve your debugging experience it is strongly recommended to use [`Mock::named`] to assign a unique
-    /// identifier to your scoped [`Mock`]s, which will in turn be referenced in the panic message if their expectations are
-    /// not met.
+    /// To help you find out which scoped [`Mock`] panicked, the panic message includes the filename and the
+    /// line location where the [`Mock`] was mounted. You can also use [`Mock::named`] to assign a unique
+    /// identifier to your scoped [`Mock`]s, which will in turn be referenced in the panic message if their
+    /// expectations are not met.
     ///
     /// # Example:
     ///
@@ -255,8 +372,168 @@ impl MockServer {
     ///     assert_eq!(status, 404);
     /// }
     /// ```
-    pub async fn register_as_scoped(&self, mock: Mock) -> MockGuard {
-        self.0.register_as_scoped(mock).await
+    #[track_caller]
+    pub fn register_as_scoped(&self, mut mock: Mock) -> impl Future<Output = MockGuard> + '_ {
+        mock.registered_at = Some(Location::caller());
+        // The guard verifies the mock on drop, unless the server opted out.
+        mock.verify_on_drop &= self.verify_on_drop;
+        self.inner.register_as_scoped(mock)
+    }
+
+    /// Register a group of **scoped** [`Mock`]s on an instance of `MockServer`, with a single
+    /// guard for all of them.
+    ///
+    /// It works like [`MockServer::register_as_scoped`], but the mocks in the group are all
+    /// unmounted (and their expectations verified) together when the returned [`GroupGuard`]
+    /// is dropped. Like [`MockServer::register_many`], they are mounted atomically. It is handy in test helpers that set up several related mocks - e.g. all the
+    /// endpoints involved in a login flow.
+    ///
+    /// ### Example:
+    /// ```rust
+    /// use wiremock::{GroupGuard, Mock, MockServer, ResponseTemplate};
+    /// use wiremock::matchers::path;
+    ///
+    /// async fn mock_login_flow(mock_server: &MockServer) -> GroupGuard {
+    ///     mock_server
+    ///         .register_group_as_scoped(vec![
+    ///             Mock::given(path("/authorize"))
+    ///                 .respond_with(ResponseTemplate::new(302))
+    ///                 .named("Authorize"),
+    ///             Mock::given(path("/token"))
+    ///                 .respond_with(ResponseTemplate::new(200))
+    ///                 .expect(1)
+    ///                 .named("Token"),
+    ///         ])
+    ///         .await
+    /// }
+    ///
+    /// #[async_std::main]
+    /// async fn main() {
+    ///     // Arrange
+    ///     let mock_server = MockServer::start().await;
+    ///     {
+    ///         let _login_flow = mock_login_flow(&mock_server).await;
+    ///
+    ///         // Act
+    ///         let status = surf::post(format!("{}/token", mock_server.uri()))
+    ///             .await
+    ///             .unwrap()
+    ///             .status();
+    ///         assert_eq!(status, 200);
+    ///     }
+    ///
+    ///     // Assert - the mocks have been unmounted
+    ///     let status = surf::post(format!("{}/token", mock_server.uri()))
+    ///         .await
+    ///         .unwrap()
+    ///         .status();
+    ///     assert_eq!(status, 404);
+    /// }
+    /// ```
+    #[track_caller]
+    pub fn register_group_as_scoped(
+        &self,
+        mut mocks: Vec<Mock>,
+    ) -> impl Future<Output = GroupGuard> + '_ {
+        let location = Location::caller();
+        for mock in &mut mocks {
+            mock.registered_at = Some(location);
+            mock.verify_on_drop &= self.verify_on_drop;
+        }
+        self.inner.register_group_as_scoped(mocks)
+    }
+
+    /// Serve a virtual host on this `MockServer`.
+    ///
+    /// The [`Mock`]s registered through the returned [`VirtualHost`] only match requests
+    /// addressed to `host` - i.e. whose `Host` header or absolute-form request target carries
+    /// `host`, compared case-insensitively.
+    /// A single `MockServer` can then emulate several upstream services when the client derives
+    /// their hostnames from configuration: point all of them at the address of the `MockServer`.
+    ///
+    /// ### Example:
+    /// ```rust
+    /// use wiremock::{MockServer, Mock, ResponseTemplate};
+    /// use wiremock::matchers::path;
+    ///
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     // Arrange
+    ///     let mock_server = MockServer::start().await;
+    ///     mock_server
+    ///         .for_host("billing.internal")
+    ///         .register(Mock::given(path("/status")).respond_with(ResponseTemplate::new(200)))
+    ///         .await;
+    ///     mock_server
+    ///         .for_host("users.internal")
+    ///         .register(Mock::given(path("/status")).respond_with(ResponseTemplate::new(503)))
+    ///         .await;
+    ///
+    ///     // Both hostnames resolve to the mock server.
+    ///     let address = *mock_server.address();
+    ///     let client = reqwest::Client::builder()
+    ///         .resolve("billing.internal", address)
+    ///         .resolve("users.internal", address)
+    ///         .build()
+    ///         .unwrap();
+    ///
+    ///     // Act
+    ///     let billing = client
+    ///         .get(format!("http://billing.internal:{}/status", address.port()))
+    ///         .send()
+    ///         .await
+    ///         .unwrap();
+    ///     let users = client
+    ///         .get(format!("http://users.internal:{}/status", address.port()))
+    ///         .send()
+    ///         .await
+    ///         .unwrap();
+    ///
+    ///     // Assert
+    ///     assert_eq!(billing.status(), 200);
+    ///     assert_eq!(users.status(), 503);
+    /// }
+    /// ```
+    pub fn for_host<T: Into<String>>(&self, host: T) -> VirtualHost<'_> {
+        VirtualHost::new(self, host.into())
+    }
+
+    /// Mount a chain of redirects on this `MockServer`.
+    ///
+    /// Every path in `paths` responds with a `302 Found` redirect pointing to the next path
+    /// in the list, on this `MockServer`. The last path responds using `final_response`.
+    ///
+    /// Check out [`RedirectChain`] if you need a different redirect status code or
+    /// want to simulate a redirect loop.
+    ///
+    /// ### Example:
+    /// ```rust
+    /// use wiremock::{MockServer, ResponseTemplate};
+    ///
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     // Arrange
+    ///     let mock_server = MockServer::start().await;
+    ///     mock_server
+    ///         .mount_redirect_chain(&["/a", "/b", "/final"], ResponseTemplate::new(200))
+    ///         .await;
+    ///
+    ///     // Act
+    ///     let response = reqwest::get(format!("{}/a", mock_server.uri())).await.unwrap();
+    ///
+    ///     // Assert
+    ///     assert_eq!(response.status(), 200);
+    ///     assert_eq!(response.url().path(), "/final");
+    /// }
+    /// ```
+    pub async fn mount_redirect_chain<R: Respond + 'static>(
+        &self,
+        paths: &[&str],
+        final_response: R,
+    ) {
+        RedirectChain::new(paths.iter().copied())
+            .mount(self, final_response)
+            .await;
     }
 
     /// Drop all mounted [`Mock`]s from an instance of [`MockServer`].
@@ -322,171 +599,1257 @@ impl MockServer {
     /// }
     /// ```
     pub async fn reset(&self) {
-        self.0.reset().await;
-    }
-
-    /// Verify that all mounted [`Mock`]s on this instance of `MockServer` have satisfied
-    /// their expectations on their number of invocations. Panics otherwise.
-    pub async fn verify(&self) {
-        debug!("Verify mock expectations.");
-        let body_print_limit = self.0.body_print_limit().await;
-        if let VerificationOutcome::Failure(failed_verifications) = self.0.verify().await {
-            let received_requests_message = if let Some(received_requests) =
-                self.0.received_requests().await
-            {
-                if received_requests.is_empty() {
-                    "The server did not receive any request.".into()
-                } else {
-                    received_requests.iter().enumerate().fold(
-                        "Received requests:\n".to_string(),
-                        |mut message, (index, request)| {
-                            _ = write!(message, "- Request #{}\n\t", index + 1,);
-                            _ = request.print_with_limit(&mut message, body_print_limit);
-                            message
-                        },
-                    )
-                }
-            } else {
-                "Enable request recording on the mock server to get the list of incoming requests as part of the panic message.".into()
-            };
-            let verifications_errors: String =
-                failed_verifications.iter().fold(String::new(), |mut s, m| {
-                    _ = writeln!(s, "- {}", m.error_message());
-                    s
-                });
-            let error_message = format!(
-                "Verifications failed:\n{verifications_errors}\n{received_requests_message}",
-            );
-            if std::thread::panicking() {
-                debug!("{}", &error_message);
-            } else {
-                panic!("{}", &error_message);
-            }
-        }
+        self.inner.reset().await;
     }
 
-    /// Return the base uri of this running instance of `MockServer`, e.g. `http://127.0.0.1:4372`.
-    ///
-    /// Use this method to compose uris when interacting with this instance of `MockServer` via
-    /// an HTTP client.
+    /// Make assertions on the requests received by the `MockServer` so far - see
+    /// [`RequestAssertions`].
     ///
     /// ### Example:
     /// ```rust
     /// use wiremock::MockServer;
+    /// use wiremock::matchers::method;
     ///
     /// #[async_std::main]
     /// async fn main() {
-    ///     // Arrange - no mocks mounted
+    ///     // Arrange
+    ///     let mock_server = MockServer::start().await;
+    ///
+    ///     // Act
+    ///     surf::delete(mock_server.uri()).await.unwrap();
+    ///
+    ///     // Assert
+    ///     mock_server.assert().received(1, method("DELETE")).await;
+    /// }
+    /// ```
+    pub fn assert(&self) -> RequestAssertions<'_> {
+        RequestAssertions::new(self)
+    }
+
+    /// Check that the number of received requests satisfying `matcher` falls within `times`,
+    /// returning a description of the received requests if it does not.
+    pub(super) async fn check_received(
+        &self,
+        times: &Times,
+        matcher: &dyn Match,
+    ) -> Result<(), String> {
+        self.inner.check_received(times, matcher).await
+    }
+
+    /// Set a server-wide expectation on all the requests received by the `MockServer`, checked
+    /// alongside the expectations of the mounted [`Mock`]s - when the `MockServer` is dropped,
+    /// on [`MockServer::verify`] and on [`MockServer::shutdown`].
+    ///
+    /// `expectation` gets the requests received so far, in the order they arrived, and returns
+    /// an error message if the invariant does not hold - e.g. "no request was sent without an
+    /// `Authorization` header" or "the client sent fewer than 50 requests", which cannot be
+    /// expressed by the expectations of a single mock.
+    ///
+    /// Global expectations are removed by [`MockServer::reset`]. It panics if request recording
+    /// is disabled.
+    ///
+    /// ### Example:
+    /// ```rust,should_panic
+    /// use wiremock::{MockServer, Mock, ResponseTemplate};
+    /// use wiremock::matchers::method;
     ///
+    /// #[async_std::main]
+    /// async fn main() {
+    ///     // Arrange
     ///     let mock_server = MockServer::start().await;
+    ///     Mock::given(method("GET"))
+    ///         .respond_with(ResponseTemplate::new(200))
+    ///         .mount(&mock_server)
+    ///         .await;
+    ///     mock_server
+    ///         .expect_global(|requests| {
+    ///             match requests.iter().find(|r| !r.headers.contains_key("Authorization")) {
+    ///                 Some(r) => Err(format!("{} was sent without credentials", r.url)),
+    ///                 None => Ok(()),
+    ///             }
+    ///         })
+    ///         .await;
+    ///
     ///     // Act
-    ///     let uri = format!("{}/health_check", &mock_server.uri());
-    ///     let status = surf::get(uri).await.unwrap().status();
+    ///     surf::get(mock_server.uri()).await.unwrap();
     ///
-    ///     // Assert - default response
-    ///     assert_eq!(status, 404);
+    ///     // Assert
+    ///     // The request did not have an `Authorization` header: verification fails.
+    ///     mock_server.verify().await;
     /// }
     /// ```
-    pub fn uri(&self) -> String {
-        self.0.uri()
+    pub async fn expect_global<F>(&self, expectation: F)
+    where
+        F: Fn(&[Request]) -> Result<(), String> + Send + Sync + 'static,
+    {
+        self.inner.expect_global(Box::new(expectation)).await;
     }
 
-    /// Return the socket address of this running instance of `MockServer`, e.g. `127.0.0.1:4372`.
+    /// Zero the number of requests matched by each [`Mock`] and forget all the requests received
+    /// so far, while keeping all the mocks mounted.
     ///
-    /// Use this method to interact with the `MockServer` using [`TcpStream`]s.
+    /// It lets you reuse the same stubs across the phases of a test - or across the scenarios
+    /// sharing a cucumber `Background` - verifying the expectations of each phase independently.
+    /// Mocks capped with [`Mock::up_to_n_times`] match requests again, and scoped mocks are
+    /// verified against the requests they matched after the reset when their [`MockGuard`]
+    /// is dropped.
     ///
     /// ### Example:
     /// ```rust
-    /// use wiremock::MockServer;
-    /// use std::net::TcpStream;
+    /// use wiremock::{MockServer, Mock, ResponseTemplate};
+    /// use wiremock::matchers::method;
     ///
     /// #[async_std::main]
     /// async fn main() {
-    ///     // Act - the server is started
+    ///     // Arrange
     ///     let mock_server = MockServer::start().await;
+    ///     Mock::given(method("GET"))
+    ///         .respond_with(ResponseTemplate::new(200))
+    ///         .expect(1)
+    ///         .mount(&mock_server)
+    ///         .await;
     ///
-    ///     // Assert - we can connect to it
-    ///     assert!(TcpStream::connect(mock_server.address()).is_ok());
+    ///     // First phase
+    ///     surf::get(mock_server.uri()).await.unwrap();
+    ///     mock_server.verify().await;
+    ///
+    ///     // Act
+    ///     mock_server.reset_expectations().await;
+    ///
+    ///     // Second phase - the mock is still mounted and expects one more request
+    ///     assert!(mock_server.received_requests().await.unwrap().is_empty());
+    ///     let response = surf::get(mock_server.uri()).await.unwrap();
+    ///     assert_eq!(response.status(), 200);
+    ///     mock_server.verify().await;
     /// }
     /// ```
-    ///
-    /// [`TcpStream`]: std::net::TcpStream
-    pub fn address(&self) -> &SocketAddr {
-        self.0.address()
+    pub async fn reset_expectations(&self) {
+        self.inner.reset_expectations().await;
     }
 
-    /// Return a vector with all the requests received by the `MockServer` since it started.
-    /// If no request has been served, it returns an empty vector.
+    /// Unmount the [`Mock`]s named `name` (see [`Mock::named`]), leaving all other mocks in place.
     ///
-    /// If request recording has been disabled using [`MockServerBuilder::disable_request_recording`],
-    /// it returns `None`.
+    /// The removed mocks stop matching incoming requests and their expectations are no longer
+    /// verified - unless they are scoped mocks, which are still verified when their [`MockGuard`]
+    /// is dropped. Requests received so far are not forgotten - use
+    /// [`MockServer::reset_requests`] for that.
     ///
-    /// ### Example:
+    /// It panics if there is no mounted mock named `name`.
     ///
+    /// ### Example:
     /// ```rust
-    /// use wiremock::MockServer;
-    /// use http::Method;
+    /// use wiremock::{MockServer, Mock, ResponseTemplate};
+    /// use wiremock::matchers::path;
     ///
     /// #[async_std::main]
     /// async fn main() {
     ///     // Arrange
     ///     let mock_server = MockServer::start().await;
+    ///     Mock::given(path("/login"))
+    ///         .respond_with(ResponseTemplate::new(200))
+    ///         .named("Login")
+    ///         .mount(&mock_server)
+    ///         .await;
+    ///     Mock::given(path("/health"))
+    ///         .respond_with(ResponseTemplate::new(200))
+    ///         .mount(&mock_server)
+    ///         .await;
     ///
     ///     // Act
-    ///     surf::get(&mock_server.uri()).await.unwrap();
+    ///     mock_server.remove_mock("Login").await;
     ///
     ///     // Assert
-    ///     let received_requests = mock_server.received_requests().await.unwrap();
-    ///     assert_eq!(received_requests.len(), 1);
-    ///
-    ///     let received_request = &received_requests[0];
-    ///     assert_eq!(received_request.method, Method::GET);
-    ///     assert_eq!(received_request.url.path(), "/");
-    ///     assert!(received_request.body.is_empty());
+    ///     let login = surf::get(format!("{}/login", mock_server.uri())).await.unwrap();
+    ///     let health = surf::get(format!("{}/health", mock_server.uri())).await.unwrap();
+    ///     assert_eq!(login.status(), 404);
+    ///     assert_eq!(health.status(), 200);
     /// }
     /// ```
+    pub async fn remove_mock(&self, name: &str) {
+        if self.inner.deactivate_by_name(name).await == 0 {
+            panic!("There is no mounted mock named `{}`.", name);
+        }
+    }
+
+    /// Forget all the requests received so far, leaving the mounted [`Mock`]s untouched.
     ///
-    /// ### Example (No request served):
+    /// Only the request recording is cleared (see [`MockServer::received_requests`] and
+    /// [`MockServer::request_journal`]): the number of requests matched by each mock, and
+    /// therefore the verification of its expectations, is not affected.
     ///
+    /// ### Example:
     /// ```rust
-    /// use wiremock::MockServer;
+    /// use wiremock::{MockServer, Mock, ResponseTemplate};
+    /// use wiremock::matchers::method;
     ///
     /// #[async_std::main]
     /// async fn main() {
     ///     // Arrange
     ///     let mock_server = MockServer::start().await;
+    ///     Mock::given(method("GET"))
+    ///         .respond_with(ResponseTemplate::new(200))
+    ///         .mount(&mock_server)
+    ///         .await;
+    ///     surf::get(mock_server.uri()).await.unwrap();
+    ///
+    ///     // Act
+    ///     mock_server.reset_requests().await;
     ///
     ///     // Assert
-    ///     let received_requests = mock_server.received_requests().await.unwrap();
-    ///     assert_eq!(received_requests.len(), 0);
+    ///     assert!(mock_server.received_requests().await.unwrap().is_empty());
+    ///     // The mock is still there
+    ///     let response = surf::get(mock_server.uri()).await.unwrap();
+    ///     assert_eq!(response.status(), 200);
     /// }
     /// ```
+    pub async fn reset_requests(&self) {
+        self.inner.reset_requests().await;
+    }
+
+    /// Verify that all mounted [`Mock`]s on this instance of `MockServer` have satisfied
+    /// their expectations on their number of invocations. Panics otherwise.
+    pub async fn verify(&self) {
+        if let Some(error_message) = self.verification_error(false).await {
+            fail_verification(&error_message);
+        }
+    }
+
+    /// Verify the expectations of the mounted [`Mock`]s, returning the error message
+    /// detailing the failed ones, if any.
     ///
-    /// ### Example (Request recording disabled):
+    /// If `on_drop` is set, the mocks that opted out of verification on drop are skipped.
+    async fn verification_error(&self, on_drop: bool) -> Option<String> {
+        debug!("Verify mock expectations.");
+        let failed_verifications = match self.inner.verify(on_drop).await {
+            VerificationOutcome::Failure(failed_verifications) => failed_verifications,
+            VerificationOutcome::Success => vec![],
+        };
+        let failed_global_expectations = self.inner.global_expectation_failures().await;
+        if failed_verifications.is_empty() && failed_global_expectations.is_empty() {
+            return None;
+        }
+        let received_requests_message = self.inner.received_requests_message().await;
+        let redacted_headers = self.inner.redacted_headers().await;
+        for report in &failed_verifications {
+            report.trace_failure();
+        }
+        let mut verifications_errors: String =
+            failed_verifications.iter().fold(String::new(), |mut s, m| {
+                _ = writeln!(s, "- {}", m.error_message(&redacted_headers));
+                s
+            });
+        for error in &failed_global_expectations {
+            _ = writeln!(
+                verifications_errors,
+                "- Global expectation failed: {}",
+                error
+            );
+        }
+        let error_message =
+            format!("Verifications failed:\n{verifications_errors}\n{received_requests_message}");
+        self.inner
+            .publish_verification_failure(&error_message)
+            .await;
+        Some(error_message)
+    }
+
+    /// Shut down the `MockServer` explicitly, instead of relying on [`Drop`].
+    ///
+    /// The server stops accepting connections straight away, then waits for the requests it is
+    /// serving to complete - for up to `grace_period` - and verifies the expectations of the
+    /// mounted [`Mock`]s.
+    /// Failures are returned rather than triggering a panic: it fails with
+    /// [`ShutdownError::Timeout`] if some requests were still in-flight when the grace period
+    /// expired, and with [`ShutdownError::VerificationFailed`] if some expectations were not
+    /// satisfied.
     ///
+    /// Dropping a `MockServer` verifies expectations by blocking the current thread, which
+    /// can deadlock on some async runtimes: `shutdown` does not.
+    ///
+    /// ### Example:
     /// ```rust
-    /// use wiremock::MockServer;
+    /// use wiremock::{MockServer, Mock, ResponseTemplate};
+    /// use wiremock::matchers::method;
+    /// use std::time::Duration;
     ///
     /// #[async_std::main]
     /// async fn main() {
     ///     // Arrange
-    ///     let mock_server = MockServer::builder().disable_request_recording().start().await;
+    ///     let mock_server = MockServer::start().await;
+    ///     Mock::given(method("GET"))
+    ///         .respond_with(ResponseTemplate::new(200))
+    ///         .expect(1)
+    ///         .mount(&mock_server)
+    ///         .await;
+    ///     let uri = mock_server.uri();
+    ///     surf::get(&uri).await.unwrap();
+    ///
+    ///     // Act
+    ///     let outcome = mock_server.shutdown(Duration::from_secs(1)).await;
     ///
     ///     // Assert
-    ///     let received_requests = mock_server.received_requests().await;
-    ///     assert!(received_requests.is_none());
+    ///     assert!(outcome.is_ok());
+    ///     assert!(surf::get(&uri).await.is_err());
     /// }
     /// ```
-    pub async fn received_requests(&self) -> Option<Vec<Request>> {
-        self.0.received_requests().await
+    pub async fn shutdown(mut self, grace_period: Duration) -> Result<(), ShutdownError> {
+        debug!("Shutting down the mock server.");
+        let in_flight = self.inner.shutdown(grace_period).await;
+        self.shut_down = true;
+        if in_flight > 0 {
+            return Err(ShutdownError::Timeout { in_flight });
+        }
+        match self.verification_error(false).await {
+            Some(error_message) => Err(ShutdownError::VerificationFailed(error_message)),
+            None => Ok(()),
+        }
     }
-}
 
-impl Drop for MockServer {
-    // Clean up when the `MockServer` instance goes out of scope.
-    fn drop(&mut self) {
-        futures::executor::block_on(self.verify());
-        // The sender half of the channel, `shutdown_trigger`, gets dropped here
-        // Triggering the graceful shutdown of the server itself.
+    /// Verify that the scoped [`Mock`]s behind `guards` were matched, for the first time,
+    /// in the order they are listed. Panics otherwise.
+    ///
+    /// Each mock must have matched at least one request and its first match must have happened
+    /// after the first match of the mock that precedes it in `guards`.
+    /// Check out [`MockGuard::first_matched_at`] if you need finer-grained assertions.
+    ///
+    /// ### Example:
+    /// ```rust
+    /// use wiremock::{MockServer, Mock, ResponseTemplate};
+    /// use wiremock::matchers::path;
+    ///
+    /// #[async_std::main]
+    /// async fn main() {
+    ///     // Arrange
+    ///     let mock_server = MockServer::start().await;
+    ///     let token_mock = Mock::given(path("/token"))
+    ///         .respond_with(ResponseTemplate::new(200))
+    ///         .named("Token endpoint")
+    ///         .mount_as_scoped(&mock_server)
+    ///         .await;
+    ///     let resource_mock = Mock::given(path("/resource"))
+    ///         .respond_with(ResponseTemplate::new(200))
+    ///         .named("Resource endpoint")
+    ///         .mount_as_scoped(&mock_server)
+    ///         .await;
+    ///
+    ///     // Act
+    ///     surf::get(format!("{}/token", mock_server.uri())).await.unwrap();
+    ///     surf::get(format!("{}/resource", mock_server.uri())).await.unwrap();
+    ///
+    ///     // Assert - the token was fetched before the resource
+    ///     mock_server.verify_sequence(&[&token_mock, &resource_mock]).await;
+    /// }
+    /// ```
+    pub async fn verify_sequence(&self, guards: &[&MockGuard]) {
+        let mut first_matches = Vec::with_capacity(guards.len());
+        for guard in guards {
+            first_matches.push(guard.first_match().await);
+        }
+
+        let in_order = first_matches
+            .windows(2)
+            .all(|pair| matches!((pair[0].1, pair[1].1), (Some(a), Some(b)) if a < b))
+            && first_matches.iter().all(|(_, first)| first.is_some());
+        if in_order {
+            return;
+        }
+
+        let sequence = first_matches
+            .iter()
+            .fold(String::new(), |mut s, (mock, first)| {
+                match first {
+                    Some(n) => {
+                        _ = writeln!(s, "- {}: first matched at sequence number {}", mock, n)
+                    }
+                    None => _ = writeln!(s, "- {}: never matched", mock),
+                }
+                s
+            });
+        let error_message = format!("Mocks were not matched in the expected order:\n{sequence}");
+        tracing::error!(%sequence, "Mocks were not matched in the expected order");
+        if std::thread::panicking() {
+            debug!("{}", &error_message);
+        } else {
+            panic!("{}", &error_message);
+        }
+    }
+
+    /// Return the base uri of this running instance of `MockServer`, e.g. `http://127.0.0.1:4372`.
+    ///
+    /// Use this method to compose uris when interacting with this instance of `MockServer` via
+    /// an HTTP client.
+    ///
+    /// ### Example:
+    /// ```rust
+    /// use wiremock::MockServer;
+    ///
+    /// #[async_std::main]
+    /// async fn main() {
+    ///     // Arrange - no mocks mounted
+    ///
+    ///     let mock_server = MockServer::start().await;
+    ///     // Act
+    ///     let uri = format!("{}/health_check", &mock_server.uri());
+    ///     let status = surf::get(uri).await.unwrap().status();
+    ///
+    ///     // Assert - default response
+    ///     assert_eq!(status, 404);
+    /// }
+    /// ```
+    pub fn uri(&self) -> String {
+        self.inner.uri()
+    }
+
+    /// Return the base uris of all the listeners of this running instance of `MockServer` - see
+    /// [`MockServerBuilder::add_listener`].
+    ///
+    /// The first one is always the one returned by [`MockServer::uri`].
+    pub fn uris(&self) -> Vec<String> {
+        self.inner.uris()
+    }
+
+    /// Return the base uri of the auxiliary listener redirecting all requests to this
+    /// `MockServer`, if it was started using [`MockServerBuilder::with_http_redirect`].
+    pub fn http_redirect_uri(&self) -> Option<String> {
+        self.inner.http_redirect_uri()
+    }
+
+    /// Swap the certificate presented by this `MockServer`, without restarting it - see
+    /// [`MockServerBuilder::tls`].
+    ///
+    /// New connections are served with the new certificate, while the connections that are
+    /// already established keep using the previous one - like a real server reloading its
+    /// certificate. Use it to test how your client copes with a certificate rotation, e.g.
+    /// whether it pins the certificate it saw first.
+    ///
+    /// It panics if the `MockServer` does not serve its mocks over TLS.
+    /// It requires the `tls` feature.
+    ///
+    /// ### Example:
+    /// ```rust
+    /// use wiremock::{MockServer, Mock, ResponseTemplate};
+    /// use wiremock::matchers::any;
+    /// use wiremock::tls::TlsConfig;
+    ///
+    /// fn tls_config(name: &str) -> TlsConfig {
+    ///     let cert_chain = std::fs::read(format!("tests/fixtures/tls/{}.pem", name)).unwrap();
+    ///     let private_key = std::fs::read(format!("tests/fixtures/tls/{}.key", name)).unwrap();
+    ///     TlsConfig::from_pem(cert_chain, private_key)
+    /// }
+    ///
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     // Arrange
+    ///     let mock_server = MockServer::builder()
+    ///         .tls(tls_config("localhost"))
+    ///         .start()
+    ///         .await;
+    ///     Mock::given(any())
+    ///         .respond_with(ResponseTemplate::new(200))
+    ///         .mount(&mock_server)
+    ///         .await;
+    ///
+    ///     // Act
+    ///     mock_server.rotate_tls_cert(tls_config("localhost-rotated"));
+    ///
+    ///     // Assert - the server keeps serving requests on the same address
+    ///     let client = reqwest::Client::builder()
+    ///         .danger_accept_invalid_certs(true)
+    ///         .build()
+    ///         .unwrap();
+    ///     let response = client.get(mock_server.uri()).send().await.unwrap();
+    ///     assert_eq!(response.status(), 200);
+    /// }
+    /// ```
+    #[cfg(feature = "tls")]
+    pub fn rotate_tls_cert(&self, config: crate::tls::TlsConfig) {
+        self.inner.rotate_tls_cert(&config);
+    }
+
+    /// The PEM-encoded certificate of the authority issuing the certificates presented in the
+    /// `CONNECT` tunnels, for your client to trust them - see
+    /// [`MockServerBuilder::intercept_tls`].
+    ///
+    /// It is `None` unless TLS interception is enabled. It requires the `tls` feature.
+    #[cfg(feature = "tls")]
+    pub fn proxy_ca_certificate(&self) -> Option<String> {
+        self.inner.proxy_ca_certificate()
+    }
+
+    /// Return the socket addresses of all the listeners of this running instance of
+    /// `MockServer` - see [`MockServerBuilder::add_listener`].
+    ///
+    /// The first one is always the one returned by [`MockServer::address`].
+    pub fn addresses(&self) -> &[SocketAddr] {
+        self.inner.addresses()
+    }
+
+    /// Return the socket address of this running instance of `MockServer`, e.g. `127.0.0.1:4372`.
+    ///
+    /// Use this method to interact with the `MockServer` using [`TcpStream`]s.
+    ///
+    /// ### Example:
+    /// ```rust
+    /// use wiremock::MockServer;
+    /// use std::net::TcpStream;
+    ///
+    /// #[async_std::main]
+    /// async fn main() {
+    ///     // Act - the server is started
+    ///     let mock_server = MockServer::start().await;
+    ///
+    ///     // Assert - we can connect to it
+    ///     assert!(TcpStream::connect(mock_server.address()).is_ok());
+    /// }
+    /// ```
+    ///
+    /// [`TcpStream`]: std::net::TcpStream
+    pub fn address(&self) -> &SocketAddr {
+        self.inner.address()
+    }
+
+    /// Return a vector with all the requests received by the `MockServer` since it started.
+    /// If no request has been served, it returns an empty vector.
+    ///
+    /// If request recording has been disabled using [`MockServerBuilder::disable_request_recording`],
+    /// it returns `None`.
+    ///
+    /// ### Example:
+    ///
+    /// ```rust
+    /// use wiremock::MockServer;
+    /// use http::Method;
+    ///
+    /// #[async_std::main]
+    /// async fn main() {
+    ///     // Arrange
+    ///     let mock_server = MockServer::start().await;
+    ///
+    ///     // Act
+    ///     surf::get(&mock_server.uri()).await.unwrap();
+    ///
+    ///     // Assert
+    ///     let received_requests = mock_server.received_requests().await.unwrap();
+    ///     assert_eq!(received_requests.len(), 1);
+    ///
+    ///     let received_request = &received_requests[0];
+    ///     assert_eq!(received_request.method, Method::GET);
+    ///     assert_eq!(received_request.url.path(), "/");
+    ///     assert!(received_request.body.is_empty());
+    /// }
+    /// ```
+    ///
+    /// ### Example (No request served):
+    ///
+    /// ```rust
+    /// use wiremock::MockServer;
+    ///
+    /// #[async_std::main]
+    /// async fn main() {
+    ///     // Arrange
+    ///     let mock_server = MockServer::start().await;
+    ///
+    ///     // Assert
+    ///     let received_requests = mock_server.received_requests().await.unwrap();
+    ///     assert_eq!(received_requests.len(), 0);
+    /// }
+    /// ```
+    ///
+    /// ### Example (Request recording disabled):
+    ///
+    /// ```rust
+    /// use wiremock::MockServer;
+    ///
+    /// #[async_std::main]
+    /// async fn main() {
+    ///     // Arrange
+    ///     let mock_server = MockServer::builder().disable_request_recording().start().await;
+    ///
+    ///     // Assert
+    ///     let received_requests = mock_server.received_requests().await;
+    ///     assert!(received_requests.is_none());
+    /// }
+    /// ```
+    pub async fn received_requests(&self) -> Option<Vec<Request>> {
+        self.inner.received_requests().await
+    }
+
+    /// Mount a [`Stub`] on this `MockServer` - a shorthand for `Mock::from(stub).mount(&server)`.
+    ///
+    /// Check out the [`stub`](crate::stub) module for more details and an example.
+    pub async fn mount_stub(&self, stub: Stub) {
+        Mock::from(stub).mount(self).await;
+    }
+
+    /// Mount all the [`Stub`]s stored in the fixture files found in `dir`.
+    ///
+    /// Fixture files are recognised by their extension: `.json` files are parsed as JSON,
+    /// `.yaml` and `.yml` files as YAML - the latter requires the `yaml` feature. Each file
+    /// contains either a single stub or a list of stubs. Files are loaded in alphabetical order, other files and sub-directories are
+    /// ignored.
+    ///
+    /// It panics if `dir` cannot be read or if one of the fixture files is not a valid stub.
+    /// Use [`MockServer::watch_fixture_dir`] to reload the stubs when the files change.
+    ///
+    /// ### Example:
+    /// ```rust,no_run
+    /// use wiremock::MockServer;
+    ///
+    /// #[async_std::main]
+    /// async fn main() {
+    ///     let mock_server = MockServer::start().await;
+    ///     mock_server.load_fixture_dir("tests/fixtures/payments").await;
+    ///
+    ///     // Exercise the code that calls the payments service...
+    /// }
+    /// ```
+    pub async fn load_fixture_dir<P: AsRef<Path>>(&self, dir: P) {
+        self.inner.load_fixture_dir(dir.as_ref(), None).await;
+    }
+
+    /// Same as [`MockServer::load_fixture_dir`], but the fixture files keep being watched for
+    /// changes: every `poll_interval` the directory is checked and, if a file has been added,
+    /// removed or modified, all the stubs loaded from `dir` are replaced with its new content.
+    ///
+    /// It is meant for using `wiremock` as a local fake while developing against a dependency:
+    /// edit the fixtures and the running server picks up the changes, no restart required.
+    /// If a fixture file becomes invalid, the stubs loaded last stay mounted and a warning is
+    /// logged.
+    ///
+    /// The directory is watched as long as the returned [`FixtureWatcher`] is alive and the
+    /// server is not [reset](MockServer::reset): the stubs of the previous version of the
+    /// fixtures are unmounted on every reload.
+    ///
+    /// ### Example:
+    /// ```rust,no_run
+    /// use wiremock::MockServer;
+    /// use std::time::Duration;
+    ///
+    /// #[async_std::main]
+    /// async fn main() {
+    ///     let mock_server = MockServer::builder()
+    ///         .listener(std::net::TcpListener::bind("127.0.0.1:8080").unwrap())
+    ///         .start()
+    ///         .await;
+    ///     let _watcher = mock_server
+    ///         .watch_fixture_dir("fixtures", Duration::from_millis(500))
+    ///         .await;
+    ///
+    ///     // Serve requests until the process is killed.
+    ///     futures::future::pending::<()>().await;
+    /// }
+    /// ```
+    pub async fn watch_fixture_dir<P: AsRef<Path>>(
+        &self,
+        dir: P,
+        poll_interval: Duration,
+    ) -> FixtureWatcher {
+        self.inner
+            .load_fixture_dir(dir.as_ref(), Some(poll_interval))
+            .await
+            .expect("A watcher is always returned when a poll interval is set")
+    }
+
+    /// Return the [`Stub`]s describing the [`Mock`]s currently mounted on this `MockServer`, in
+    /// the order they were mounted.
+    ///
+    /// Only mocks built from a [`Stub`] (e.g. via [`MockServer::mount_stub`]) can be exported:
+    /// mocks using custom matchers or responders are skipped, as well as mocks whose responder
+    /// was replaced after they were mounted.
+    ///
+    /// Serialize the returned stubs to share them with other test suites or to save them as
+    /// fixture files.
+    pub async fn export_stubs(&self) -> Vec<Stub> {
+        self.inner.stubs().await
+    }
+
+    /// Change how the mounted [`Mock`]s named `name` (see [`Mock::named`]) respond to the requests
+    /// they match.
+    ///
+    /// Unlike resetting the server and mounting new mocks, the number of requests matched so far
+    /// and the expectations set on the mocks are preserved.
+    /// Use [`MockGuard::set_response`] for scoped mocks.
+    ///
+    /// It panics if there is no mounted mock named `name`.
+    ///
+    /// ### Example:
+    /// ```rust
+    /// use wiremock::{MockServer, Mock, ResponseTemplate};
+    /// use wiremock::matchers::path;
+    ///
+    /// #[async_std::main]
+    /// async fn main() {
+    ///     // Arrange
+    ///     let mock_server = MockServer::start().await;
+    ///     Mock::given(path("/health"))
+    ///         .respond_with(ResponseTemplate::new(200))
+    ///         .named("Health check")
+    ///         .mount(&mock_server)
+    ///         .await;
+    ///     let url = format!("{}/health", mock_server.uri());
+    ///
+    ///     // Act
+    ///     let before = surf::get(&url).await.unwrap();
+    ///     mock_server
+    ///         .update_mock("Health check", ResponseTemplate::new(503))
+    ///         .await;
+    ///     let after = surf::get(&url).await.unwrap();
+    ///
+    ///     // Assert
+    ///     assert_eq!(before.status(), 200);
+    ///     assert_eq!(after.status(), 503);
+    /// }
+    /// ```
+    pub async fn update_mock<R: Respond + 'static>(&self, name: &str, responder: R) {
+        let n_updated = self
+            .inner
+            .set_responder_by_name(name, Responder::Sync(Arc::new(responder)))
+            .await;
+        if n_updated == 0 {
+            panic!("There is no mounted mock named `{}`.", name);
+        }
+    }
+
+    /// Return the highest number of requests that this `MockServer` has been serving
+    /// concurrently since it started (or since the last call to [`MockServer::reset`]).
+    ///
+    /// A request is considered in-flight from the moment it reaches the `MockServer` until the
+    /// response is ready to be sent back to the client, including any delay configured via
+    /// [`ResponseTemplate::set_delay`](crate::ResponseTemplate::set_delay).
+    /// You can use it to verify that your client limits the number of parallel requests it issues
+    /// (e.g. via a connection pool or a semaphore).
+    ///
+    /// Use [`MockGuard::max_concurrency`] to get the same information for a specific [`Mock`].
+    ///
+    /// ### Example:
+    /// ```rust
+    /// use wiremock::{MockServer, Mock, ResponseTemplate};
+    /// use wiremock::matchers::method;
+    /// use std::time::Duration;
+    ///
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     // Arrange
+    ///     let mock_server = MockServer::start().await;
+    ///     Mock::given(method("GET"))
+    ///         .respond_with(ResponseTemplate::new(200).set_delay(Duration::from_millis(100)))
+    ///         .mount(&mock_server)
+    ///         .await;
+    ///
+    ///     // Act - requests are sent one after the other
+    ///     for _ in 0..3 {
+    ///         reqwest::get(mock_server.uri()).await.unwrap();
+    ///     }
+    ///
+    ///     // Assert
+    ///     assert_eq!(mock_server.max_concurrent_requests(), 1);
+    /// }
+    /// ```
+    pub fn max_concurrent_requests(&self) -> usize {
+        self.inner.max_concurrent_requests()
+    }
+
+    /// Return the number of connections this `MockServer` received requests on since it
+    /// started (or since the last call to [`MockServer::reset`]) - e.g. to check that your
+    /// client reuses its keep-alive connections rather than opening a new one for each request.
+    ///
+    /// Connections that were closed without carrying a request are not counted.
+    /// Use [`Request::connection_id`] to find out which connection a request was received on,
+    /// in [`MockServer::received_requests`] or in the [journal](MockServer::request_journal).
+    ///
+    /// It returns `None` if request recording was disabled - see
+    /// [`MockServerBuilder::disable_request_recording`](crate::MockServerBuilder::disable_request_recording).
+    ///
+    /// ### Example:
+    /// ```rust
+    /// use wiremock::{MockServer, Mock, ResponseTemplate};
+    /// use wiremock::matchers::method;
+    ///
+    /// #[async_std::main]
+    /// async fn main() {
+    ///     // Arrange
+    ///     let mock_server = MockServer::start().await;
+    ///     Mock::given(method("GET"))
+    ///         .respond_with(ResponseTemplate::new(200))
+    ///         .mount(&mock_server)
+    ///         .await;
+    ///
+    ///     // Act - the client keeps its connection alive between requests
+    ///     let client = surf::Client::new();
+    ///     for _ in 0..10 {
+    ///         client.get(mock_server.uri()).recv_bytes().await.unwrap();
+    ///     }
+    ///
+    ///     // Assert
+    ///     assert_eq!(mock_server.connection_count().await, Some(1));
+    /// }
+    /// ```
+    pub async fn connection_count(&self) -> Option<usize> {
+        self.inner.connection_count().await
+    }
+
+    /// Write all the requests received by this `MockServer`, alongside the responses that were
+    /// returned, to `path` as a [HAR (HTTP Archive)](http://www.softwareishard.com/blog/har-12-spec/)
+    /// file.
+    ///
+    /// HAR files can be opened in the network tab of most browser devtools, as well as in many
+    /// HTTP tools: export one when a test fails to see exactly what the client sent - e.g. as a
+    /// CI artifact.
+    ///
+    /// It panics if request recording has been disabled using
+    /// [`MockServerBuilder::disable_request_recording`] or if the file cannot be written.
+    ///
+    /// ### Example:
+    /// ```rust
+    /// use wiremock::{MockServer, Mock, ResponseTemplate};
+    /// use wiremock::matchers::method;
+    ///
+    /// #[async_std::main]
+    /// async fn main() {
+    ///     // Arrange
+    ///     let mock_server = MockServer::start().await;
+    ///     Mock::given(method("GET"))
+    ///         .respond_with(ResponseTemplate::new(200).set_body_string("Hello!"))
+    ///         .mount(&mock_server)
+    ///         .await;
+    ///     surf::get(format!("{}/hello?name=Ferris", mock_server.uri())).await.unwrap();
+    ///
+    ///     // Act
+    ///     let path = std::env::temp_dir().join("wiremock-example.har");
+    ///     mock_server.export_har(&path).await;
+    ///
+    ///     // Assert
+    ///     let har: serde_json::Value =
+    ///         serde_json::from_slice(&std::fs::read(&path).unwrap()).unwrap();
+    ///     let entry = &har["log"]["entries"][0];
+    ///     assert_eq!(entry["request"]["method"], "GET");
+    ///     assert_eq!(entry["request"]["queryString"][0]["value"], "Ferris");
+    ///     assert_eq!(entry["response"]["content"]["text"], "Hello!");
+    /// }
+    /// ```
+    pub async fn export_har<P: AsRef<Path>>(&self, path: P) {
+        if !self.inner.export_har(path.as_ref()).await {
+            panic!("Request recording is disabled: there are no requests to export as HAR.");
+        }
+    }
+
+    /// Write a [Pact](https://docs.pact.io/) v3 contract between `consumer` - the client you are
+    /// testing - and `provider` - the service this `MockServer` stands in for - to `path`,
+    /// to feed your contract testing pipeline straight from your mock-based tests.
+    ///
+    /// Each request matched by a [`Mock`] becomes an interaction, alongside the response it got.
+    /// Unmatched requests are left out, as well as duplicated interactions. Headers describing
+    /// how messages were transferred (e.g. `Host`, `User-Agent` or `Content-Length`) are left
+    /// out as well. Interactions are described using the name of the mock that matched them,
+    /// the method and the path of the request.
+    ///
+    /// Call it at the end of your test, once your client has sent all its requests.
+    /// It panics if request recording has been disabled using
+    /// [`MockServerBuilder::disable_request_recording`] or if the file cannot be written.
+    /// It requires the `pact` feature.
+    ///
+    /// ### Example:
+    /// ```rust
+    /// use serde_json::json;
+    /// use wiremock::{MockServer, Mock, ResponseTemplate};
+    /// use wiremock::matchers::{method, path};
+    ///
+    /// #[async_std::main]
+    /// async fn main() {
+    ///     // Arrange
+    ///     let mock_server = MockServer::start().await;
+    ///     Mock::given(method("GET"))
+    ///         .and(path("/users/1"))
+    ///         .respond_with(ResponseTemplate::new(200).set_body_json(json!({ "name": "Ferris" })))
+    ///         .named("Get user")
+    ///         .mount(&mock_server)
+    ///         .await;
+    ///     surf::get(format!("{}/users/1", mock_server.uri())).await.unwrap();
+    ///
+    ///     // Act
+    ///     let path = std::env::temp_dir().join("wiremock-example-pact.json");
+    ///     mock_server.write_pact("frontend", "users-service", &path).await;
+    ///
+    ///     // Assert
+    ///     let pact: serde_json::Value =
+    ///         serde_json::from_slice(&std::fs::read(&path).unwrap()).unwrap();
+    ///     let interaction = &pact["interactions"][0];
+    ///     assert_eq!(pact["provider"]["name"], "users-service");
+    ///     assert_eq!(interaction["description"], "Get user - GET /users/1");
+    ///     assert_eq!(interaction["response"]["body"], json!({ "name": "Ferris" }));
+    /// }
+    /// ```
+    #[cfg(feature = "pact")]
+    pub async fn write_pact<P: AsRef<Path>>(&self, consumer: &str, provider: &str, path: P) {
+        if !self
+            .inner
+            .write_pact(consumer, provider, path.as_ref())
+            .await
+        {
+            panic!("Request recording is disabled: there are no requests to write a Pact from.");
+        }
+    }
+
+    /// Mount a [`Mock`] for each of the interactions of the [Pact](https://docs.pact.io/)
+    /// contract at `path`, standing in for the provider - so that you can develop a consumer
+    /// against exactly the payloads of the contract you share with the provider team.
+    ///
+    /// Each mock matches the method, the path, the query parameters (exactly), the headers and
+    /// the body of the request of its interaction, and returns the response of the interaction.
+    /// The mocks are named after the interaction they stand for - e.g.
+    /// `Pact interaction #0 - Get user`.
+    ///
+    /// Both Pact v2 and v3 contracts are supported, but matching rules and provider states are
+    /// ignored: requests must match the example request of the interaction, and interactions
+    /// matching the same requests are tried in the order they appear in the contract.
+    ///
+    /// It panics if the file cannot be read or is not a valid Pact contract.
+    /// It requires the `pact` feature.
+    ///
+    /// ### Example:
+    /// ```rust
+    /// use serde_json::json;
+    /// use wiremock::MockServer;
+    ///
+    /// #[async_std::main]
+    /// async fn main() {
+    ///     // Arrange
+    ///     let mock_server = MockServer::start().await;
+    ///     let pact = json!({
+    ///         "consumer": { "name": "frontend" },
+    ///         "provider": { "name": "users-service" },
+    ///         "interactions": [{
+    ///             "description": "Get user",
+    ///             "request": { "method": "GET", "path": "/users/1" },
+    ///             "response": { "status": 200, "body": { "name": "Ferris" } }
+    ///         }]
+    ///     });
+    ///     let path = std::env::temp_dir().join("wiremock-example-mount-pact.json");
+    ///     std::fs::write(&path, pact.to_string()).unwrap();
+    ///
+    ///     // Act
+    ///     mock_server.mount_pact(&path).await;
+    ///
+    ///     // Assert
+    ///     let user: serde_json::Value = surf::get(format!("{}/users/1", mock_server.uri()))
+    ///         .recv_json()
+    ///         .await
+    ///         .unwrap();
+    ///     assert_eq!(user, json!({ "name": "Ferris" }));
+    /// }
+    /// ```
+    #[cfg(feature = "pact")]
+    pub async fn mount_pact<P: AsRef<Path>>(&self, path: P) {
+        let path = path.as_ref();
+        let pact = std::fs::read(path)
+            .unwrap_or_else(|e| panic!("Failed to read Pact file `{}`: {}", path.display(), e));
+        let pact: serde_json::Value = serde_json::from_slice(&pact)
+            .unwrap_or_else(|e| panic!("Invalid Pact file `{}`: {}", path.display(), e));
+        for mock in crate::pact::mocks_from_pact(&pact) {
+            self.register(mock).await;
+        }
+    }
+
+    /// Mount a [`Mock`] for each of the entries recorded in the HAR file at `path`, replaying
+    /// the recorded responses.
+    ///
+    /// `strategy` determines which parts of the recorded requests incoming requests have to
+    /// match - see [`MatchStrategy`]. If several entries match the same requests, their responses
+    /// are returned in the order they were recorded, the last one being repeated once the others
+    /// have been used.
+    ///
+    /// The mocks are named after the entry they replay - e.g. `HAR entry #0 - GET https://example.com/`.
+    /// Headers describing how the recorded response was transferred (e.g. `Content-Encoding`) are
+    /// dropped, since HAR files store decoded bodies.
+    ///
+    /// It panics if the file cannot be read or it is not a valid HAR file.
+    ///
+    /// ### Example:
+    /// ```rust
+    /// use wiremock::{MockServer, Mock, ResponseTemplate};
+    /// use wiremock::har::MatchStrategy;
+    /// use wiremock::matchers::method;
+    ///
+    /// #[async_std::main]
+    /// async fn main() {
+    ///     // Arrange - record some traffic
+    ///     let recording_server = MockServer::start().await;
+    ///     Mock::given(method("GET"))
+    ///         .respond_with(ResponseTemplate::new(200).set_body_string("Recorded!"))
+    ///         .mount(&recording_server)
+    ///         .await;
+    ///     surf::get(format!("{}/hello", recording_server.uri())).await.unwrap();
+    ///     let path = std::env::temp_dir().join("wiremock-replay-example.har");
+    ///     recording_server.export_har(&path).await;
+    ///
+    ///     // Act
+    ///     let mock_server = MockServer::start().await;
+    ///     mock_server.mount_har(&path, MatchStrategy::MethodAndPath).await;
+    ///
+    ///     // Assert
+    ///     let mut response = surf::get(format!("{}/hello", mock_server.uri())).await.unwrap();
+    ///     assert_eq!(response.status(), 200);
+    ///     assert_eq!(response.body_string().await.unwrap(), "Recorded!");
+    /// }
+    /// ```
+    pub async fn mount_har<P: AsRef<Path>>(&self, path: P, strategy: MatchStrategy) {
+        let path = path.as_ref();
+        let har = std::fs::read(path)
+            .unwrap_or_else(|e| panic!("Failed to read HAR file `{}`: {}", path.display(), e));
+        let har: serde_json::Value = serde_json::from_slice(&har)
+            .unwrap_or_else(|e| panic!("Invalid HAR file `{}`: {}", path.display(), e));
+        for mock in har::mocks_from_har(&har, strategy) {
+            self.register(mock).await;
+        }
+    }
+
+    /// Look for [`Mock`]s mounted on this `MockServer` that can never match a request, because
+    /// a mock tried before them - due to its priority or because it was mounted earlier - matches
+    /// all the requests they would match. E.g. a catch-all mock using [`any`](crate::matchers::any)
+    /// swallows all the mocks with the same or a lower priority mounted after it.
+    ///
+    /// A mock shadows another one if all its matchers are also matchers of the other one.
+    /// Only the built-in matchers comparing against a fixed value - e.g. [`method`](crate::matchers::method),
+    /// [`path`](crate::matchers::path) or [`header`](crate::matchers::header) - are understood:
+    /// closures and other custom matchers are opaque, so mocks using them are never reported as
+    /// shadowing others. Mocks that stop matching after a while (see [`Mock::up_to_n_times`])
+    /// do not shadow anything either.
+    ///
+    /// The warnings are sorted by the position of the unreachable mock.
+    ///
+    /// ### Example:
+    /// ```rust
+    /// use wiremock::{MockLint, MockServer, Mock, ResponseTemplate};
+    /// use wiremock::matchers::{any, method, path};
+    ///
+    /// #[async_std::main]
+    /// async fn main() {
+    ///     // Arrange
+    ///     let mock_server = MockServer::start().await;
+    ///     Mock::given(any())
+    ///         .respond_with(ResponseTemplate::new(500))
+    ///         .named("Catch-all")
+    ///         .mount(&mock_server)
+    ///         .await;
+    ///     Mock::given(method("GET"))
+    ///         .and(path("/users"))
+    ///         .respond_with(ResponseTemplate::new(200))
+    ///         .named("List users")
+    ///         .mount(&mock_server)
+    ///         .await;
+    ///
+    ///     // Act
+    ///     let lints = mock_server.lint_mocks().await;
+    ///
+    ///     // Assert
+    ///     assert_eq!(lints.len(), 1);
+    ///     assert_eq!(
+    ///         lints[0].to_string(),
+    ///         "List users can never match: Catch-all is tried first and matches all the requests it would match"
+    ///     );
+    /// }
+    /// ```
+    pub async fn lint_mocks(&self) -> Vec<MockLint> {
+        self.inner.lint_mocks().await
+    }
+
+    /// Subscribe to the events of this `MockServer`, as they happen: requests being received,
+    /// mocks matching them, responses being sent and verifications failing - see
+    /// [`ServerEvent`].
+    ///
+    /// It lets test frameworks and debugging tools follow what the server is doing in real time,
+    /// instead of polling [`MockServer::received_requests`].
+    /// The stream only yields the events emitted after `events` was called. A subscriber that
+    /// falls too far behind misses the oldest events rather than slowing the server down.
+    ///
+    /// ### Example:
+    /// ```rust
+    /// use futures::StreamExt;
+    /// use wiremock::{MockServer, Mock, ResponseTemplate, ServerEvent};
+    /// use wiremock::matchers::path;
+    ///
+    /// #[async_std::main]
+    /// async fn main() {
+    ///     // Arrange
+    ///     let mock_server = MockServer::start().await;
+    ///     Mock::given(path("/hello"))
+    ///         .respond_with(ResponseTemplate::new(200))
+    ///         .named("Hello")
+    ///         .mount(&mock_server)
+    ///         .await;
+    ///     let mut events = Box::pin(mock_server.events().await);
+    ///
+    ///     // Act
+    ///     surf::get(format!("{}/hello", mock_server.uri())).await.unwrap();
+    ///
+    ///     // Assert
+    ///     match events.next().await.unwrap() {
+    ///         ServerEvent::RequestReceived { request, .. } => assert_eq!(request.url.path(), "/hello"),
+    ///         event => panic!("Unexpected event: {:?}", event),
+    ///     }
+    ///     match events.next().await.unwrap() {
+    ///         ServerEvent::MockMatched { mock, .. } => assert_eq!(mock.to_string(), "Hello"),
+    ///         event => panic!("Unexpected event: {:?}", event),
+    ///     }
+    ///     match events.next().await.unwrap() {
+    ///         ServerEvent::ResponseSent { status, .. } => assert_eq!(status, 200),
+    ///         event => panic!("Unexpected event: {:?}", event),
+    ///     }
+    /// }
+    /// ```
+    pub async fn events(&self) -> impl Stream<Item = ServerEvent> + Send + 'static {
+        self.inner.events().await
+    }
+
+    /// Return the usage metrics of all the [`Mock`]s currently mounted on this `MockServer`, in
+    /// the order they were mounted: how many requests each of them matched, how long it took
+    /// to respond and when it matched for the last time.
+    ///
+    /// Use [`MockGuard::metrics`] to get the metrics of a specific scoped [`Mock`].
+    ///
+    /// ### Example:
+    /// ```rust
+    /// use wiremock::{MockServer, Mock, ResponseTemplate};
+    /// use wiremock::matchers::path;
+    ///
+    /// #[async_std::main]
+    /// async fn main() {
+    ///     // Arrange
+    ///     let mock_server = MockServer::start().await;
+    ///     Mock::given(path("/cached"))
+    ///         .respond_with(ResponseTemplate::new(200))
+    ///         .named("Cached resource")
+    ///         .mount(&mock_server)
+    ///         .await;
+    ///
+    ///     // Act - the client is expected to cache the response
+    ///     surf::get(format!("{}/cached", mock_server.uri())).await.unwrap();
+    ///
+    ///     // Assert
+    ///     let metrics = mock_server.metrics().await;
+    ///     assert_eq!(metrics[0].mock.to_string(), "Cached resource");
+    ///     assert_eq!(metrics[0].n_matches, 1);
+    /// }
+    /// ```
+    pub async fn metrics(&self) -> Vec<MockMetrics> {
+        self.inner.metrics().await
+    }
+
+    /// Return how many requests each of the [`Mock`]s currently mounted on this `MockServer`
+    /// has matched so far, alongside its name (see [`Mock::named`]), in the order they were
+    /// mounted.
+    ///
+    /// It lets you assert intermediate call counts in the middle of a test without turning
+    /// your mocks into scoped ones. Use [`MockServer::metrics`] for more details.
+    ///
+    /// ### Example:
+    /// ```rust
+    /// use wiremock::{MockServer, Mock, ResponseTemplate};
+    /// use wiremock::matchers::{method, path};
+    ///
+    /// #[async_std::main]
+    /// async fn main() {
+    ///     // Arrange
+    ///     let mock_server = MockServer::start().await;
+    ///     Mock::given(path("/login"))
+    ///         .respond_with(ResponseTemplate::new(200))
+    ///         .named("Login")
+    ///         .mount(&mock_server)
+    ///         .await;
+    ///     Mock::given(method("GET"))
+    ///         .respond_with(ResponseTemplate::new(404))
+    ///         .mount(&mock_server)
+    ///         .await;
+    ///
+    ///     // Act
+    ///     surf::get(format!("{}/login", mock_server.uri())).await.unwrap();
+    ///
+    ///     // Assert
+    ///     assert_eq!(
+    ///         mock_server.mock_call_counts().await,
+    ///         vec![(Some("Login".to_string()), 1), (None, 0)]
+    ///     );
+    /// }
+    /// ```
+    pub async fn mock_call_counts(&self) -> Vec<(Option<String>, u64)> {
+        self.metrics()
+            .await
+            .into_iter()
+            .map(|metrics| (metrics.mock.name, metrics.n_matches))
+            .collect()
+    }
+
+    /// Return the journal of all the requests received by the `MockServer` since it started,
+    /// in the order they were received.
+    ///
+    /// Each [`JournalEntry`] records, on top of the request itself, when the request arrived,
+    /// which [`Mock`] (if any) matched it and the status code of the response that was returned.
+    /// You can use it to assert on the ordering of calls or on the time elapsed between them.
+    ///
+    /// Requests handled by an [`AsyncRespond`](crate::AsyncRespond) responder are added to the
+    /// journal once their response is ready.
+    ///
+    /// If request recording has been disabled using [`MockServerBuilder::disable_request_recording`],
+    /// it returns `None`.
+    ///
+    /// ### Example:
+    ///
+    /// ```rust
+    /// use wiremock::{MockServer, Mock, ResponseTemplate};
+    /// use wiremock::matchers::path;
+    ///
+    /// #[async_std::main]
+    /// async fn main() {
+    ///     // Arrange
+    ///     let mock_server = MockServer::start().await;
+    ///     Mock::given(path("/token"))
+    ///         .respond_with(ResponseTemplate::new(200))
+    ///         .named("Token endpoint")
+    ///         .mount(&mock_server)
+    ///         .await;
+    ///
+    ///     // Act
+    ///     surf::post(format!("{}/token", mock_server.uri())).await.unwrap();
+    ///     surf::get(format!("{}/missing", mock_server.uri())).await.unwrap();
+    ///
+    ///     // Assert
+    ///     let journal = mock_server.request_journal().await.unwrap();
+    ///     assert_eq!(journal.len(), 2);
+    ///
+    ///     let matched = journal[0].matched_mock.as_ref().unwrap();
+    ///     assert_eq!(matched.name.as_deref(), Some("Token endpoint"));
+    ///     assert_eq!(journal[0].response_status, 200);
+    ///
+    ///     assert!(journal[1].matched_mock.is_none());
+    ///     assert_eq!(journal[1].response_status, 404);
+    ///     assert!(journal[1].received_at >= journal[0].received_at);
+    /// }
+    /// ```
+    pub async fn request_journal(&self) -> Option<Vec<JournalEntry>> {
+        self.inner.request_journal().await
+    }
+}
+
+impl Drop for MockServer {
+    // Clean up when the `MockServer` instance goes out of scope.
+    fn drop(&mut self) {
+        if self.shut_down || !self.verify_on_drop {
+            return;
+        }
+        if let Some(error_message) = futures::executor::block_on(self.verification_error(true)) {
+            fail_verification(&error_message);
+        }
+        // The sender half of the channel, `shutdown_trigger`, gets dropped here
+        // Triggering the graceful shutdown of the server itself.
+    }
+}
+
+/// Panic with `error_message`, unless we are already panicking - a double panic would abort
+/// the process, hiding the original failure.
+fn fail_verification(error_message: &str) {
+    if std::thread::panicking() {
+        debug!("{}", error_message);
+    } else {
+        panic!("{}", error_message);
     }
 }
@@ -1,47 +1,71 @@
-use crate::mock_server::bare_server::MockServerState;
+use crate::events::ServerEvent;
+use crate::in_flight::InFlightCounter;
+use crate::mock_server::bare_server::{MockServerState, ProxyMode};
+use crate::mock_server::connection::{self, Connection};
+use crate::mock_server::executor::Executor;
+use crate::mock_set::HandledRequest;
+use crate::request::ConnectionId;
+use crate::respond::PendingResponse;
+use crate::response_body::ResponseBody;
+use crate::response_template::ExpectContinue;
+use crate::tls::{self, TlsAcceptor};
+use crate::{MatchedMock, ResponseTemplate};
+use http::uri::{Authority, Scheme};
+use http::{HeaderName, HeaderValue};
 use hyper::service::service_fn;
 use hyper_util::rt::TokioIo;
+use std::future::Future;
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::io::{AsyncRead, AsyncWrite};
 use tokio::net::TcpListener;
-use tokio::sync::RwLock;
+use tokio::sync::{Notify, RwLock};
+use tracing::Instrument;
 
 /// The actual HTTP server responding to incoming requests according to the specified mocks.
+///
+/// It stops accepting connections when `shutdown_signal` changes or its sender is dropped.
+/// If the signal carries a grace period, it waits for the in-flight requests to complete
+/// for up to that long before returning: the connections still open are then dropped.
+/// It returns how many requests were still in-flight when it stopped.
+/// Connections are served over TLS if `tls` is set, on tasks spawned by `executor`.
 pub(super) async fn run_server(
     listener: std::net::TcpListener,
+    tls: Option<TlsAcceptor>,
     server_state: Arc<RwLock<MockServerState>>,
-    mut shutdown_signal: tokio::sync::watch::Receiver<()>,
-) {
+    in_flight: Arc<InFlightCounter>,
+    proxy_mode: ProxyMode,
+    executor: Executor,
+    mut shutdown_signal: tokio::sync::watch::Receiver<Option<Duration>>,
+) -> usize {
     listener
         .set_nonblocking(true)
         .expect("Cannot set non-blocking mode on TcpListener");
+    // Rendered in place of `{{base_url}}` in the responses served by this listener.
+    let scheme = if tls.is_some() { "https" } else { "http" };
+    let base_url: Option<Arc<str>> = listener
+        .local_addr()
+        .ok()
+        .map(|address| format!("{}://{}", scheme, address).into());
     let listener = TcpListener::from_std(listener).expect("Cannot upgrade TcpListener");
-
-    let request_handler = move |request| {
-        let server_state = server_state.clone();
-        async move {
-            let wiremock_request = crate::Request::from_hyper(request).await;
-            let (response, delay) = server_state
-                .write()
-                .await
-                .handle_request(wiremock_request)
-                .await;
-
-            // We do not wait for the delay within the handler otherwise we would be
-            // holding on to the write-side of the `RwLock` on `mock_set`.
-            // Holding on the lock while waiting prevents us from handling other requests until
-            // we have waited the whole duration specified in the delay.
-            // In particular, we cannot perform even perform read-only operation -
-            // e.g. check that mock assumptions have been verified.
-            // Using long delays in tests without handling the delay as we are doing here
-            // caused tests to hang (see https://github.com/seanmonstar/reqwest/issues/1147)
-            if let Some(delay) = delay {
-                delay.await;
-            }
-
-            Ok::<_, &'static str>(response)
-        }
+    // Dropped when we return.
+    let (_stop_connections, stopped) = tokio::sync::watch::channel(());
+    let tasks = ConnectionTasks {
+        executor,
+        shutdown_signal: shutdown_signal.clone(),
+        stopped,
     };
 
+    let request_handler = request_handler(
+        server_state.clone(),
+        in_flight.clone(),
+        proxy_mode,
+        base_url,
+        tasks.clone(),
+        None,
+    );
+
     loop {
         let (stream, _) = tokio::select! { biased;
             accepted = listener.accept() => {
@@ -51,26 +75,633 @@ pub(super) async fn run_server(
                 }
             },
             _ = shutdown_signal.changed() => {
-                log::info!("Mock server shutting down");
+                tracing::info!("Mock server shutting down");
                 break;
             }
         };
-        let io = TokioIo::new(stream);
+        let (stream, connection) = connection::share(stream);
 
         let request_handler = request_handler.clone();
-        let mut shutdown_signal = shutdown_signal.clone();
-        tokio::task::spawn(async move {
-            let http_server =
-                hyper_util::server::conn::auto::Builder::new(hyper_util::rt::TokioExecutor::new());
-            let conn = http_server.serve_connection_with_upgrades(io, service_fn(request_handler));
+        let executor = tasks.executor.clone();
+        let mut shutdown_signal = tasks.shutdown_signal.clone();
+        let mut stopped = tasks.stopped.clone();
+        let tls = tls.clone();
+        let connection_id = ConnectionId::next();
+        let server_state = server_state.clone();
+        tasks.executor.spawn(async move {
+            let goaway_after = server_state.read().await.goaway_after();
+            // Notified once the connection has carried `goaway_after` HTTP/2 requests.
+            let goaway = Arc::new(Notify::new());
+            // Notified once the `GOAWAY` frame is queued: the last response is held back
+            // until then, so that clients never see it before the `GOAWAY` frame.
+            let goaway_queued = Arc::new(Notify::new());
+            let http2_requests = AtomicUsize::new(0);
+            let (notify_goaway, wait_for_goaway) = (goaway.clone(), goaway_queued.clone());
+            // Interim responses are written straight to the socket: they cannot be sent
+            // over TLS.
+            let (io, connection, tls_info): (Box<dyn Io>, _, _) = match &tls {
+                None => (Box::new(stream), Some(connection), None),
+                Some(acceptor) => match tls::accept(acceptor, stream).await {
+                    Ok((stream, tls_info)) => (Box::new(stream), None, Some(tls_info)),
+                    Err(e) => {
+                        tracing::debug!("TLS handshake failed: {}", e);
+                        return;
+                    }
+                },
+            };
+            let io = TokioIo::new(io);
+            let http_server = hyper_util::server::conn::auto::Builder::new(executor);
+            let service = service_fn(move |mut request: hyper::Request<hyper::body::Incoming>| {
+                request.extensions_mut().insert(connection_id);
+                if let Some(connection) = &connection {
+                    request.extensions_mut().insert(connection.clone());
+                }
+                if let Some(tls_info) = &tls_info {
+                    request.extensions_mut().insert(tls_info.clone());
+                }
+                let last_stream = goaway_after.is_some_and(|limit| {
+                    request.version() == http::Version::HTTP_2
+                        && http2_requests.fetch_add(1, Ordering::SeqCst) + 1 == limit
+                });
+                let response = request_handler(request);
+                if !last_stream {
+                    return response;
+                }
+                notify_goaway.notify_one();
+                let wait_for_goaway = wait_for_goaway.clone();
+                Box::pin(async move {
+                    wait_for_goaway.notified().await;
+                    response.await
+                })
+            });
+            let conn = http_server.serve_connection_with_upgrades(io, service);
             tokio::pin!(conn);
 
+            // The signal is only awaited once: it keeps resolving after its sender is dropped.
+            let mut shutting_down = false;
             loop {
                 tokio::select! {
                     _ = conn.as_mut() => break,
-                    _ = shutdown_signal.changed() => conn.as_mut().graceful_shutdown(),
+                    _ = shutdown_signal.changed(), if !shutting_down => {
+                        shutting_down = true;
+                        conn.as_mut().graceful_shutdown();
+                    }
+                    // The server is gone: drop the requests still being served - e.g. hanging
+                    // ones.
+                    _ = stopped.changed() => break,
+                    _ = goaway.notified() => {
+                        tracing::debug!("Sending GOAWAY after {:?} requests", goaway_after);
+                        conn.as_mut().graceful_shutdown();
+                        goaway_queued.notify_one();
+                    }
                 }
             }
         });
     }
+    // Stop accepting connections before draining the in-flight requests.
+    drop(listener);
+
+    let grace_period = *shutdown_signal.borrow();
+    if let Some(grace_period) = grace_period {
+        let _ = tokio::time::timeout(grace_period, in_flight.wait_until_idle()).await;
+    }
+    in_flight.current()
+}
+
+/// The IO of a connection, with or without TLS.
+trait Io: AsyncRead + AsyncWrite + Send + Unpin {}
+
+impl<T: AsyncRead + AsyncWrite + Send + Unpin> Io for T {}
+
+/// The tasks serving the connections accepted by a server, and how they are stopped.
+#[derive(Clone)]
+struct ConnectionTasks {
+    executor: Executor,
+    /// Changes, or its sender is dropped, when the server starts shutting down: connections
+    /// are then shut down gracefully.
+    shutdown_signal: tokio::sync::watch::Receiver<Option<Duration>>,
+    /// Its sender is dropped once the server stops waiting for in-flight requests: the
+    /// connections still open are then dropped.
+    stopped: tokio::sync::watch::Receiver<()>,
+}
+
+/// A server redirecting all the requests it receives to `target`, with a `301 Moved Permanently`
+/// response, until `shutdown_signal` changes or its sender is dropped.
+pub(super) async fn run_redirect_server(
+    listener: std::net::TcpListener,
+    target: String,
+    executor: Executor,
+    mut shutdown_signal: tokio::sync::watch::Receiver<Option<Duration>>,
+) {
+    listener
+        .set_nonblocking(true)
+        .expect("Cannot set non-blocking mode on TcpListener");
+    let listener = TcpListener::from_std(listener).expect("Cannot upgrade TcpListener");
+    let target = Arc::new(target);
+    // Dropped when we return.
+    let (_stop_connections, stopped) = tokio::sync::watch::channel(());
+
+    loop {
+        let (stream, _) = tokio::select! { biased;
+            accepted = listener.accept() => {
+                match accepted {
+                    Ok(accepted) => accepted,
+                    Err(_) => break,
+                }
+            },
+            _ = shutdown_signal.changed() => break,
+        };
+        let target = target.clone();
+        let redirect = service_fn(move |request: hyper::Request<hyper::body::Incoming>| {
+            let path_and_query = request.uri().path_and_query().map_or("/", |p| p.as_str());
+            let location = format!("{}{}", target, path_and_query);
+            tracing::debug!(%location, "Redirecting request");
+            let response = hyper::Response::builder()
+                .status(http::StatusCode::MOVED_PERMANENTLY)
+                .header(http::header::LOCATION, location)
+                .body(ResponseBody::default());
+            async move { response }
+        });
+        let http_server = hyper_util::server::conn::auto::Builder::new(executor.clone());
+        let mut stopped = stopped.clone();
+        executor.spawn(async move {
+            tokio::select! {
+                _ = http_server.serve_connection(TokioIo::new(stream), redirect) => {}
+                _ = stopped.changed() => {}
+            }
+        });
+    }
+}
+
+type HandlerResult = Result<hyper::Response<ResponseBody>, HandlerError>;
+
+/// Why a request is not answered: `hyper` drops the connection, or resets the stream over
+/// HTTP/2 with the error code found in the source of the error.
+#[derive(Debug)]
+enum HandlerError {
+    Abort(&'static str),
+    ResetStream(h2::Error),
+}
+
+impl std::fmt::Display for HandlerError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            HandlerError::Abort(reason) => f.write_str(reason),
+            HandlerError::ResetStream(e) => write!(f, "the stream was reset: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for HandlerError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            HandlerError::Abort(_) => None,
+            HandlerError::ResetStream(e) => Some(e),
+        }
+    }
+}
+
+/// Build the function handling the requests received by the server.
+///
+/// `base_url` is the base URL of the listener the requests are received on.
+/// `tunnel` is the scheme and the authority requested by the client if the requests are
+/// received through a `CONNECT` tunnel - see [`ProxyMode`].
+fn request_handler(
+    server_state: Arc<RwLock<MockServerState>>,
+    in_flight: Arc<InFlightCounter>,
+    proxy_mode: ProxyMode,
+    base_url: Option<Arc<str>>,
+    tasks: ConnectionTasks,
+    tunnel: Option<(Scheme, Authority)>,
+) -> impl Fn(
+    hyper::Request<hyper::body::Incoming>,
+) -> std::pin::Pin<Box<dyn Future<Output = HandlerResult> + Send>>
+       + Clone
+       + Send
+       + 'static {
+    move |mut request: hyper::Request<hyper::body::Incoming>| {
+        let server_state = server_state.clone();
+        let in_flight = in_flight.clone();
+        let base_url = base_url.clone();
+        if let Some((scheme, authority)) = &tunnel {
+            // Requests sent through a tunnel are in origin form: we restore the authority the
+            // client asked to connect to.
+            *request.uri_mut() = absolute_uri(scheme, authority, request.uri());
+        }
+        if !matches!(proxy_mode, ProxyMode::Disabled) && request.method() == http::Method::CONNECT {
+            let proxy_mode = proxy_mode.clone();
+            let tasks = tasks.clone();
+            return Box::pin(async move {
+                Ok(open_tunnel(
+                    request,
+                    server_state,
+                    in_flight,
+                    proxy_mode,
+                    base_url,
+                    tasks,
+                ))
+            });
+        }
+        // The matched mock and the response status are recorded once they are known.
+        let span = tracing::info_span!(
+            "wiremock.request",
+            method = %request.method(),
+            path = %request.uri().path(),
+            mock = tracing::field::Empty,
+            status = tracing::field::Empty,
+        );
+        // Interim responses are written straight to the socket: it is only safe over HTTP/1.1,
+        // where nothing else is written to it while we prepare the response.
+        let version = request.version();
+        let connection = request
+            .extensions_mut()
+            .remove::<Connection>()
+            .filter(|_| request.version() == http::Version::HTTP_11);
+        let future = async move {
+            let _in_flight = in_flight.enter();
+            let received_at = Instant::now();
+            let (max_body_size, virtual_clock, mut hang_release, debug_headers, events) = {
+                let server_state = server_state.read().await;
+                // Delays are measured from the (virtual) time the request was received at.
+                let virtual_clock = server_state
+                    .virtual_clock()
+                    .map(|clock| (clock.clone(), clock.elapsed()));
+                (
+                    server_state.max_request_body_size(),
+                    virtual_clock,
+                    server_state.hang_release(),
+                    server_state.debug_headers(),
+                    server_state.events().clone(),
+                )
+            };
+            // The arrival time seen by matchers is measured by the server clock.
+            let server_time = match &virtual_clock {
+                Some((_, elapsed)) => *elapsed,
+                None => server_state.read().await.uptime(),
+            };
+            // `hyper` sends `100 Continue` as soon as we start reading the body: we must decide
+            // whether to before reading it.
+            let mut rejection = None;
+            if expects_continue(&request) {
+                let head = crate::Request::from_head(&request, server_time);
+                match server_state.read().await.expect_continue(&head) {
+                    Some(ExpectContinue::Delay(delay)) => tokio::time::sleep(delay).await,
+                    Some(ExpectContinue::Reject(status)) => rejection = Some((head, status)),
+                    None => {}
+                }
+            }
+            let rejected_with = rejection.as_ref().map(|(_, status)| *status);
+            let (wiremock_request, truncated) = match rejection {
+                Some((head, _)) => (head, false),
+                None => crate::Request::from_hyper(request, max_body_size, server_time).await,
+            };
+            let request_id = events.request_received(&wiremock_request);
+            let response_sent = |mock: Option<MatchedMock>, status| {
+                events.publish(ServerEvent::ResponseSent {
+                    request_id,
+                    mock,
+                    status,
+                    latency: received_at.elapsed(),
+                })
+            };
+            // The body is not read: the connection cannot be reused.
+            if let Some(status) = rejected_with {
+                tracing::debug!("Rejecting the body announced with `Expect: 100-continue`");
+                let response_template = ResponseTemplate::new(status).close_connection();
+                server_state.read().await.record(
+                    &wiremock_request,
+                    received_at,
+                    None,
+                    &response_template,
+                );
+                let response = response_template.generate_response();
+                response_sent(None, response.status());
+                return Ok(response);
+            }
+            // The rest of the body is not read: the connection cannot be reused.
+            if truncated {
+                tracing::debug!("The request body exceeds the maximum size");
+                let response_template = ResponseTemplate::new(413).close_connection();
+                server_state.read().await.record(
+                    &wiremock_request,
+                    received_at,
+                    None,
+                    &response_template,
+                );
+                let response = response_template.generate_response();
+                response_sent(None, response.status());
+                return Ok(response);
+            }
+            // Requests to the metrics endpoint are not matched against mocks nor recorded.
+            if let Some(metrics) = server_state
+                .read()
+                .await
+                .metrics_endpoint(&wiremock_request)
+            {
+                let response = metrics.generate_response();
+                response_sent(None, response.status());
+                return Ok(response);
+            }
+            // Nor are the requests to the debug UI. Only they take the write lock on the
+            // server state: the other requests are matched concurrently, under a read lock.
+            #[cfg(feature = "debug-ui")]
+            if let Some(debug_ui_request) =
+                crate::debug_ui::DebugUiRequest::parse(&wiremock_request)
+            {
+                if let Some(page) = server_state.write().await.debug_ui(debug_ui_request) {
+                    let response = page.generate_response();
+                    response_sent(None, response.status());
+                    return Ok(response);
+                }
+            }
+            let HandledRequest {
+                response,
+                matched_mock,
+                in_flight: _mock_in_flight,
+                latencies,
+                consumption,
+                webhooks,
+                random,
+            } = server_state
+                .read()
+                .await
+                .handle_request(&wiremock_request, received_at)
+                .await;
+            match &matched_mock {
+                Some(mock) => {
+                    tracing::Span::current().record("mock", tracing::field::display(mock));
+                    events.publish(ServerEvent::MockMatched {
+                        request_id,
+                        mock: mock.clone(),
+                    });
+                }
+                None => tracing::debug!("No mock matched the request"),
+            }
+            let debug_header = debug_headers.then(|| debug_header(matched_mock.as_ref()));
+            let responding_mock = matched_mock.clone();
+
+            // Asynchronous responders are awaited without holding on to the lock on the
+            // server state, for the same reasons outlined below for delays.
+            let response_template = match response {
+                PendingResponse::Ready(response_template) => *response_template,
+                PendingResponse::Deferred(response_template) => {
+                    let response_template = response_template.await;
+                    let server_state = server_state.read().await;
+                    let response_template = server_state.inject_chaos(response_template);
+                    server_state.record(
+                        &wiremock_request,
+                        received_at,
+                        matched_mock,
+                        &response_template,
+                    );
+                    response_template
+                }
+            };
+
+            let interim_responses = response_template.interim_responses();
+            if !interim_responses.is_empty() {
+                match &connection {
+                    Some(connection) => {
+                        for (status, headers) in interim_responses {
+                            if let Err(e) = connection.send_interim_response(*status, headers).await
+                            {
+                                tracing::debug!("Failed to send an interim response: {}", e);
+                            }
+                        }
+                    }
+                    None => tracing::debug!(
+                        "Interim responses are only sent over HTTP/1.1 connections, skipping them"
+                    ),
+                }
+            }
+
+            if response_template.hangs() {
+                tracing::debug!("Hanging until the server is reset");
+                // It errors if the server state is gone: there is nothing left to wait for.
+                let _ = hang_release.changed().await;
+                return Err(HandlerError::Abort("the hanging request was released"));
+            }
+
+            // We do not wait for the delay within the handler otherwise we would be
+            // holding on to the write-side of the `RwLock` on `mock_set`.
+            // Holding on the lock while waiting prevents us from handling other requests until
+            // we have waited the whole duration specified in the delay.
+            // In particular, we cannot perform even perform read-only operation -
+            // e.g. check that mock assumptions have been verified.
+            // Using long delays in tests without handling the delay as we are doing here
+            // caused tests to hang (see https://github.com/seanmonstar/reqwest/issues/1147)
+            if let Some(delay) = response_template.delay() {
+                match &virtual_clock {
+                    Some((clock, received_at)) => clock.sleep_until(*received_at + *delay).await,
+                    None => tokio::time::sleep(*delay).await,
+                }
+            }
+            if response_template.drops_connection() {
+                tracing::debug!("Dropping the connection");
+                return Err(HandlerError::Abort(
+                    "the response template drops the connection",
+                ));
+            }
+            if let Some(reset) = response_template.stream_reset() {
+                tracing::debug!("Resetting the stream with {:?}", reset);
+                return Err(HandlerError::ResetStream(reset.reason().into()));
+            }
+            // `hyper` must not write anything after the raw response: we drop the connection.
+            if let Some(raw_response) = response_template.raw_response() {
+                match &connection {
+                    Some(connection) => {
+                        if let Err(e) = connection.write_all(raw_response).await {
+                            tracing::debug!("Failed to send a raw response: {}", e);
+                        }
+                    }
+                    None => tracing::debug!(
+                        "Raw responses are only sent over HTTP/1.1 connections, skipping it"
+                    ),
+                }
+                return Err(HandlerError::Abort(
+                    "the raw response was written to the socket",
+                ));
+            }
+
+            let fixed_time = server_state.read().await.fixed_time();
+            let mut response = response_template.generate_seeded_response(
+                &random,
+                base_url.as_deref(),
+                fixed_time,
+            );
+            if let Some((name, value)) = debug_header {
+                response.headers_mut().insert(name, value);
+            }
+            if let Some(limit) = response_template.abort_after_bytes() {
+                response = response.map(|body| body.abort_after(limit));
+            }
+            if let Some(bytes_per_sec) = response_template.throttle() {
+                response = response.map(|body| body.throttle(bytes_per_sec));
+            }
+            if let Some(delay) = response_template.line_delay() {
+                response = response.map(|body| body.line_delay(delay));
+            }
+            response = response_template.frame(response, version);
+            if let Some(consumption) = consumption {
+                if wiremock_request.method != http::Method::HEAD {
+                    let consumption = consumption.start();
+                    response = response.map(|body| body.track_consumption(consumption));
+                }
+            }
+            if let Some(latencies) = latencies {
+                latencies.record(received_at.elapsed());
+            }
+            tracing::Span::current().record("status", response.status().as_u16());
+            response_sent(responding_mock, response.status());
+            tracing::debug!("Responded to request");
+            if !webhooks.is_empty() {
+                // The body is dropped by the connection task, on the runtime of the server.
+                let runtime = tokio::runtime::Handle::current();
+                response = response.map(|body| {
+                    body.on_sent(move || {
+                        let _entered = runtime.enter();
+                        for webhook in &webhooks {
+                            webhook.spawn(&wiremock_request);
+                        }
+                    })
+                });
+            }
+            Ok(response)
+        }
+        .instrument(span);
+        Box::pin(future)
+    }
+}
+
+/// Whether `request` waits for `100 Continue` before sending its body - only HTTP/1.1 has
+/// the handshake.
+fn expects_continue<B>(request: &hyper::Request<B>) -> bool {
+    request.version() == http::Version::HTTP_11
+        && request
+            .headers()
+            .get(http::header::EXPECT)
+            .is_some_and(|expect| expect.as_bytes().eq_ignore_ascii_case(b"100-continue"))
+}
+
+/// The header identifying the mock that produced a response, when debug headers are enabled -
+/// see [`MockServerBuilder::debug_headers`](crate::MockServerBuilder::debug_headers).
+fn debug_header(matched_mock: Option<&MatchedMock>) -> (HeaderName, HeaderValue) {
+    match matched_mock {
+        Some(mock) => {
+            // Names that cannot be used as a header value fall back to the position.
+            let value = HeaderValue::from_str(&mock.to_string()).unwrap_or_else(|_| {
+                HeaderValue::from_str(&format!("Mock #{}", mock.position)).unwrap()
+            });
+            (HeaderName::from_static("x-wiremock-matched-mock"), value)
+        }
+        None => (
+            HeaderName::from_static("x-wiremock-unmatched"),
+            HeaderValue::from_static("true"),
+        ),
+    }
+}
+
+/// Accept a `CONNECT` request: once the client receives the `200 OK` response, the connection
+/// becomes a tunnel and the requests sent through it are served as if they had been sent
+/// to the requested authority.
+///
+/// The tunnel carries TLS if `proxy_mode` intercepts it. It is shut down, as any other
+/// connection, according to `tasks`.
+fn open_tunnel(
+    request: hyper::Request<hyper::body::Incoming>,
+    server_state: Arc<RwLock<MockServerState>>,
+    in_flight: Arc<InFlightCounter>,
+    proxy_mode: ProxyMode,
+    base_url: Option<Arc<str>>,
+    tasks: ConnectionTasks,
+) -> hyper::Response<ResponseBody> {
+    let authority = match request.uri().authority() {
+        Some(authority) => authority.clone(),
+        None => {
+            return hyper::Response::builder()
+                .status(400)
+                .body(ResponseBody::from(
+                    "CONNECT requests must target an authority",
+                ))
+                .unwrap()
+        }
+    };
+    // The requests sent through the tunnel are received on the connection of the `CONNECT`
+    // request.
+    let connection_id = request.extensions().get::<ConnectionId>().copied();
+    let connection = request.extensions().get::<Connection>().cloned();
+    tracing::debug!(%authority, "Opening CONNECT tunnel");
+    let executor = tasks.executor.clone();
+    executor.spawn(async move {
+        let upgraded = match hyper::upgrade::on(request).await {
+            Ok(upgraded) => TokioIo::new(upgraded),
+            Err(e) => {
+                tracing::warn!("Failed to open CONNECT tunnel: {}", e);
+                return;
+            }
+        };
+        // Interim responses are written straight to the socket: they cannot be sent over TLS.
+        let (io, scheme, connection, tls_info): (Box<dyn Io>, _, _, _) = match &proxy_mode {
+            ProxyMode::Intercepting(certificate_authority) => {
+                let host = authority.host();
+                match tls::accept(&certificate_authority.acceptor_for(host), upgraded).await {
+                    Ok((stream, tls_info)) => {
+                        (Box::new(stream), Scheme::HTTPS, None, Some(tls_info))
+                    }
+                    Err(e) => {
+                        tracing::debug!("TLS handshake failed in CONNECT tunnel: {}", e);
+                        return;
+                    }
+                }
+            }
+            _ => (Box::new(upgraded), Scheme::HTTP, connection, None),
+        };
+        let (mut shutdown_signal, mut stopped) =
+            (tasks.shutdown_signal.clone(), tasks.stopped.clone());
+        let http_server = hyper_util::server::conn::auto::Builder::new(tasks.executor.clone());
+        let request_handler = request_handler(
+            server_state,
+            in_flight,
+            proxy_mode,
+            base_url,
+            tasks,
+            Some((scheme, authority)),
+        );
+        let service = service_fn(move |mut request: hyper::Request<hyper::body::Incoming>| {
+            if let Some(connection_id) = connection_id {
+                request.extensions_mut().insert(connection_id);
+            }
+            if let Some(connection) = &connection {
+                request.extensions_mut().insert(connection.clone());
+            }
+            if let Some(tls_info) = &tls_info {
+                request.extensions_mut().insert(tls_info.clone());
+            }
+            request_handler(request)
+        });
+        let conn = http_server.serve_connection(TokioIo::new(io), service);
+        tokio::pin!(conn);
+        // The signal is only awaited once: it keeps resolving after its sender is dropped.
+        let mut shutting_down = false;
+        loop {
+            tokio::select! {
+                _ = conn.as_mut() => break,
+                _ = shutdown_signal.changed(), if !shutting_down => {
+                    shutting_down = true;
+                    conn.as_mut().graceful_shutdown();
+                }
+                _ = stopped.changed() => break,
+            }
+        }
+    });
+    hyper::Response::new(ResponseBody::default())
+}
+
+/// Turn the origin-form `uri` of a request received through a tunnel to `authority` into
+/// an absolute URI.
+fn absolute_uri(scheme: &Scheme, authority: &Authority, uri: &http::Uri) -> http::Uri {
+    let path_and_query = uri.path_and_query().map_or("/", |p| p.as_str());
+    format!("{}://{}{}", scheme, authority, path_and_query)
+        .parse()
+        .unwrap_or_else(|_| uri.clone())
 }
@@ -10,12 +10,18 @@
 //! We use the pool when the user does not care about the port the mock server listens to, while
 //! we provision a dedicated one if they specify their own `TcpListener` with `start_on`.
 //! Check the `pool` submodule for more details on our pooling strategy.
+mod assertions;
 mod bare_server;
 mod builder;
+mod connection;
+mod executor;
 mod exposed_server;
 mod hyper;
-mod pool;
+pub mod pool;
+mod virtual_host;
 
-pub use bare_server::MockGuard;
+pub use assertions::RequestAssertions;
+pub use bare_server::{GroupGuard, MockGuard};
 pub use builder::MockServerBuilder;
-pub use exposed_server::MockServer;
+pub use exposed_server::{MockServer, ShutdownError};
+pub use virtual_host::VirtualHost;
@@ -1,9 +1,230 @@
+//! Configuration of the pool of servers backing [`MockServer::start`].
+//!
+//! [`MockServer::start`] does not spin up a brand new server every time: it borrows one from a
+//! pool, returning it once the [`MockServer`] is dropped. This minimises the number of ports
+//! opened and closed by large test suites.
+//!
+//! The pool can be tuned using [`configure`] or the following environment variables:
+//! - `WIREMOCK_POOL_MAX_SIZE`: the maximum number of servers in the pool;
+//! - `WIREMOCK_POOL_IDLE_TIMEOUT`: how many seconds a server can sit unused in the pool before
+//!   being shut down;
+//! - `WIREMOCK_POOL_DISABLED`: set it to `true` or `1` to start a dedicated server every time.
+//!
+//! Servers started using [`MockServer::builder`] are never pooled.
 use crate::mock_server::bare_server::BareMockServer;
-use crate::MockServer;
+use crate::{Mock, MockServer};
 use async_trait::async_trait;
 use deadpool::managed::{Metrics, Object, Pool};
-use once_cell::sync::Lazy;
+use once_cell::sync::{Lazy, OnceCell};
 use std::convert::Infallible;
+use std::env;
+use std::fmt::{Debug, Formatter};
+use std::panic::Location;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// The configuration of the pool of servers backing [`MockServer::start`] - see [`configure`].
+#[derive(Debug, Clone)]
+pub struct PoolConfig {
+    max_size: usize,
+    idle_timeout: Option<Duration>,
+    enabled: bool,
+    default_mocks: Option<DefaultMocks>,
+}
+
+/// Builds the mocks mounted on every server returned by [`MockServer::start`].
+#[derive(Clone)]
+struct DefaultMocks {
+    build: Arc<dyn Fn() -> Vec<Mock> + Send + Sync>,
+    /// Where [`PoolConfig::with_default_mocks`] was called: the default mocks are reported as
+    /// mounted there, rather than inside `wiremock`.
+    configured_at: &'static Location<'static>,
+}
+
+impl Debug for DefaultMocks {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str("DefaultMocks")
+    }
+}
+
+impl PoolConfig {
+    /// The default configuration: up to 1000 servers, never shut down while idle.
+    pub fn new() -> Self {
+        Self {
+            max_size: 1000,
+            idle_timeout: None,
+            enabled: true,
+            default_mocks: None,
+        }
+    }
+
+    /// The default configuration, overridden by the `WIREMOCK_POOL_*` environment variables -
+    /// see the [module documentation](self).
+    ///
+    /// It is the configuration used if [`configure`] is never called.
+    /// It panics if the environment variables have invalid values.
+    pub fn from_env() -> Self {
+        let mut config = Self::new();
+        if let Ok(max_size) = env::var("WIREMOCK_POOL_MAX_SIZE") {
+            let max_size = max_size
+                .parse()
+                .expect("`WIREMOCK_POOL_MAX_SIZE` must be a positive integer");
+            config = config.max_size(max_size);
+        }
+        if let Ok(idle_timeout) = env::var("WIREMOCK_POOL_IDLE_TIMEOUT") {
+            let idle_timeout = idle_timeout
+                .parse()
+                .expect("`WIREMOCK_POOL_IDLE_TIMEOUT` must be a number of seconds");
+            config = config.idle_timeout(Duration::from_secs(idle_timeout));
+        }
+        if let Ok(disabled) = env::var("WIREMOCK_POOL_DISABLED") {
+            if disabled == "1" || disabled.eq_ignore_ascii_case("true") {
+                config = config.disable_pooling();
+            }
+        }
+        config
+    }
+
+    /// Keep at most `max_size` servers in the pool.
+    ///
+    /// Once all of them are in use, [`MockServer::start`] waits for one of them to be
+    /// returned to the pool: keep it above the number of tests you run concurrently.
+    ///
+    /// It panics if `max_size` is `0` - use [`PoolConfig::disable_pooling`] instead.
+    pub fn max_size(mut self, max_size: usize) -> Self {
+        assert!(
+            max_size > 0,
+            "The maximum size of the pool must be positive."
+        );
+        self.max_size = max_size;
+        self
+    }
+
+    /// Shut down the servers that have not been used for longer than `idle_timeout`, releasing
+    /// their port.
+    ///
+    /// Idle servers are checked every time a server is requested from the pool.
+    pub fn idle_timeout(mut self, idle_timeout: Duration) -> Self {
+        self.idle_timeout = Some(idle_timeout);
+        self
+    }
+
+    /// Start a dedicated server for each [`MockServer::start`] call, shut down when the
+    /// [`MockServer`] is dropped.
+    pub fn disable_pooling(mut self) -> Self {
+        self.enabled = false;
+        self
+    }
+
+    /// Mount the mocks returned by `default_mocks` on every server returned by
+    /// [`MockServer::start`], pooled or not - e.g. health checks or authentication endpoints
+    /// that all your tests rely on.
+    ///
+    /// `default_mocks` is called every time a server is handed out, as pooled servers are reset
+    /// when they are returned to the pool. The default mocks are mounted before any other
+    /// mock: give them a lower priority using [`Mock::with_priority`] if your tests need to
+    /// override them. Like any other mock, they are removed by [`MockServer::reset`].
+    ///
+    /// If the expectations of a default mock are not met, the panic message points at the line
+    /// where `with_default_mocks` was called.
+    ///
+    /// ### Example:
+    /// ```rust
+    /// use wiremock::pool::{configure, PoolConfig};
+    /// use wiremock::matchers::path;
+    /// use wiremock::{Mock, MockServer, ResponseTemplate};
+    ///
+    /// #[async_std::main]
+    /// async fn main() {
+    ///     configure(PoolConfig::new().with_default_mocks(|| {
+    ///         vec![Mock::given(path("/health"))
+    ///             .respond_with(ResponseTemplate::new(200))
+    ///             .with_priority(u8::MAX)]
+    ///     }));
+    ///
+    ///     let mock_server = MockServer::start().await;
+    ///
+    ///     let status = surf::get(format!("{}/health", mock_server.uri()))
+    ///         .await
+    ///         .unwrap()
+    ///         .status();
+    ///     assert_eq!(status, 200);
+    /// }
+    /// ```
+    #[track_caller]
+    pub fn with_default_mocks<F>(mut self, default_mocks: F) -> Self
+    where
+        F: Fn() -> Vec<Mock> + Send + Sync + 'static,
+    {
+        self.default_mocks = Some(DefaultMocks {
+            build: Arc::new(default_mocks),
+            configured_at: Location::caller(),
+        });
+        self
+    }
+}
+
+impl Default for PoolConfig {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+static POOL_CONFIG: OnceCell<PoolConfig> = OnceCell::new();
+
+/// Set the configuration of the pool of servers backing [`MockServer::start`].
+///
+/// It must be called before the first [`MockServer`] is started - e.g. in a shared test
+/// setup function - and at most once: it panics otherwise.
+///
+/// ### Example:
+/// ```rust
+/// use wiremock::pool::{configure, PoolConfig};
+/// use wiremock::MockServer;
+/// use std::time::Duration;
+///
+/// #[async_std::main]
+/// async fn main() {
+///     configure(
+///         PoolConfig::new()
+///             .max_size(16)
+///             .idle_timeout(Duration::from_secs(30)),
+///     );
+///
+///     let mock_server = MockServer::start().await;
+/// }
+/// ```
+pub fn configure(config: PoolConfig) {
+    if POOL_CONFIG.set(config).is_err() {
+        panic!("The pool configuration can only be set once, before the first `MockServer` is started.");
+    }
+}
+
+/// The configuration of the pool, initialised from the environment if [`configure`] was
+/// not called.
+fn pool_config() -> &'static PoolConfig {
+    POOL_CONFIG.get_or_init(PoolConfig::from_env)
+}
+
+/// Whether [`MockServer::start`] should borrow a server from the pool.
+pub(crate) fn pooling_enabled() -> bool {
+    pool_config().enabled
+}
+
+/// The mocks to mount on the servers returned by [`MockServer::start`] - see
+/// [`PoolConfig::with_default_mocks`].
+pub(crate) fn default_mocks() -> Vec<Mock> {
+    match &pool_config().default_mocks {
+        Some(default_mocks) => {
+            let mut mocks = (default_mocks.build)();
+            for mock in &mut mocks {
+                mock.registered_at = Some(default_mocks.configured_at);
+            }
+            mocks
+        }
+        None => Vec::new(),
+    }
+}
 
 /// A pool of `BareMockServer`s.
 ///
@@ -23,12 +244,10 @@ use std::convert::Infallible;
 /// the absolute time is so small (<1 ms) that it does not make a material difference in a real
 /// world test suite.
 static MOCK_SERVER_POOL: Lazy<Pool<MockServerPoolManager>> = Lazy::new(|| {
-    // We are choosing an arbitrarily high max_size because we never want a test to "wait" for
-    // a `BareMockServer` instance to become available.
-    //
-    // We might expose in the future a way for a crate user to tune this value.
+    // We are choosing an arbitrarily high max_size by default because we never want a test
+    // to "wait" for a `BareMockServer` instance to become available.
     Pool::builder(MockServerPoolManager)
-        .max_size(1000)
+        .max_size(pool_config().max_size)
         .build()
         .expect("Building a server pool is not expected to fail. Please report an issue")
 });
@@ -38,6 +257,9 @@ pub(crate) type PooledMockServer = Object<MockServerPoolManager>;
 /// Retrieve a `BareMockServer` from the pool.
 /// The operation should never fail.
 pub(crate) async fn get_pooled_mock_server() -> PooledMockServer {
+    if let Some(idle_timeout) = pool_config().idle_timeout {
+        MOCK_SERVER_POOL.retain(|_, metrics| metrics.last_used() < idle_timeout);
+    }
     MOCK_SERVER_POOL
         .get()
         .await
@@ -67,6 +289,12 @@ impl deadpool::managed::Manager for MockServerPoolManager {
         mock_server: &mut BareMockServer,
         _metrics: &Metrics,
     ) -> deadpool::managed::RecycleResult<Infallible> {
+        // A server that was shut down cannot serve other tests.
+        if !mock_server.is_running() {
+            return Err(deadpool::managed::RecycleError::StaticMessage(
+                "The mock server was shut down",
+            ));
+        }
         // Remove all existing settings - we want to start clean when the mock server
         // is picked up again from the pool.
         mock_server.reset().await;
@@ -0,0 +1,71 @@
+use crate::matchers::host;
+use crate::{GroupGuard, Mock, MockGuard, MockServer};
+use std::future::Future;
+
+/// A view over a [`MockServer`] that only serves requests addressed to a specific host.
+///
+/// It is returned by [`MockServer::for_host`]: every [`Mock`] registered through a
+/// `VirtualHost` only matches requests whose `Host` header (or absolute-form request target)
+/// is the virtual host name - see [`matchers::host`](crate::matchers::host).
+/// A single `MockServer` can then emulate several upstream services, as long as the client
+/// resolves all their hostnames to the address of the `MockServer`.
+#[derive(Debug)]
+pub struct VirtualHost<'a> {
+    server: &'a MockServer,
+    host: String,
+}
+
+impl<'a> VirtualHost<'a> {
+    pub(super) fn new(server: &'a MockServer, host: String) -> Self {
+        Self { server, host }
+    }
+
+    /// The host name served by this `VirtualHost`.
+    pub fn host(&self) -> &str {
+        &self.host
+    }
+
+    /// Register a [`Mock`] that only matches requests addressed to this virtual host.
+    ///
+    /// Check out [`MockServer::register`] for more details.
+    #[track_caller]
+    pub fn register(&self, mock: Mock) -> impl Future<Output = ()> + 'a {
+        self.server.register(self.scope(mock))
+    }
+
+    /// Register a batch of [`Mock`]s that only match requests addressed to this virtual host,
+    /// atomically.
+    ///
+    /// Check out [`MockServer::register_many`] for more details.
+    #[track_caller]
+    pub fn register_many(&self, mocks: Vec<Mock>) -> impl Future<Output = ()> + 'a {
+        let mocks = mocks.into_iter().map(|mock| self.scope(mock)).collect();
+        self.server.register_many(mocks)
+    }
+
+    /// Register a **scoped** [`Mock`] that only matches requests addressed to this virtual host.
+    ///
+    /// Check out [`MockServer::register_as_scoped`] for more details.
+    #[track_caller]
+    pub fn register_as_scoped(&self, mock: Mock) -> impl Future<Output = MockGuard> + 'a {
+        self.server.register_as_scoped(self.scope(mock))
+    }
+
+    /// Register a group of **scoped** [`Mock`]s that only match requests addressed to this
+    /// virtual host.
+    ///
+    /// Check out [`MockServer::register_group_as_scoped`] for more details.
+    #[track_caller]
+    pub fn register_group_as_scoped(
+        &self,
+        mocks: Vec<Mock>,
+    ) -> impl Future<Output = GroupGuard> + 'a {
+        let mocks = mocks.into_iter().map(|mock| self.scope(mock)).collect();
+        self.server.register_group_as_scoped(mocks)
+    }
+
+    fn scope(&self, mut mock: Mock) -> Mock {
+        mock.add_matcher(host(self.host.as_str()));
+        mock
+    }
+}
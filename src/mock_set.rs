@@ -1,18 +1,28 @@
-use crate::request::BodyPrintLimit;
+use crate::in_flight::InFlightGuard;
+use crate::journal::MatchedMock;
+use crate::lint::{equivalent, subsumes, Fingerprint, MockLint};
+use crate::metrics::{render_prometheus, LatencyRecorder, MockMetrics};
+use crate::random::Random;
+use crate::request::{BodyPrintLimit, Redaction};
+use crate::respond::{PendingResponse, Responder};
+use crate::response_body::ConsumptionRecorder;
+use crate::response_template::ExpectContinue;
+use crate::session::{SessionKey, Sessions};
+use crate::stub::Stub;
 use crate::{
     mounted_mock::MountedMock,
     verification::{VerificationOutcome, VerificationReport},
 };
-use crate::{Mock, Request, ResponseTemplate};
-use http_body_util::Full;
-use hyper::body::Bytes;
-use log::debug;
+use crate::{Mock, Request, ResponseTemplate, WebhookTrigger};
 use std::{
     ops::{Index, IndexMut},
-    sync::{atomic::AtomicBool, Arc},
+    sync::{
+        atomic::{AtomicBool, AtomicU64, Ordering},
+        Arc,
+    },
 };
 use tokio::sync::Notify;
-use tokio::time::{sleep, Sleep};
+use tracing::{debug, warn};
 
 /// The collection of mocks used by a `MockServer` instance to match against
 /// incoming requests.
@@ -20,15 +30,63 @@ use tokio::time::{sleep, Sleep};
 /// New mocks are added to `MountedMockSet` every time [`MockServer::register`](crate::MockServer::register),
 /// [`MockServer::register_as_scoped`](crate::MockServer::register_as_scoped) or
 /// [`Mock::mount`](crate::Mock::mount) are called.
+///
+/// Incoming requests are handled through a shared reference: the server matches them under
+/// a read lock, concurrently.
 pub(crate) struct MountedMockSet {
+    /// The mocks, in the order they were mounted: a [`MockId`] holds the position of a mock,
+    /// which is looked up in this vector.
     mocks: Vec<(MountedMock, MountedMockState)>,
+    /// How many mocks have been registered since the set was created (or last reset), including
+    /// the removed ones: the position of the next mock.
+    n_registered_mocks: usize,
+    /// The indexes of `mocks`, in the order they are matched against incoming requests - by
+    /// priority, then by position.
+    match_order: Vec<usize>,
     /// A counter that keeps track of how many times [`MountedMockSet::reset`] has been called.
     /// It starts at `0` and gets incremented for each invocation.
     ///
     /// We need `generation` to know if a [`MockId`] points to an [`MountedMock`] that has been
     /// removed via [`MountedMockSet::reset`].
+    /// Mocks removed via [`MountedMockSet::remove`] are simply no longer found.
     generation: u16,
+    /// How many requests have been handled since the set was created (or last reset).
+    ///
+    /// It is used to assign a monotonically increasing sequence number to each incoming
+    /// request, allowing us to determine in which order mocks were matched.
+    n_handled_requests: AtomicU64,
+    /// How many of the handled requests did not match any mock.
+    n_unmatched_requests: AtomicU64,
     body_print_limit: BodyPrintLimit,
+    /// Sanitizes requests before they are stored by the mocks that matched them.
+    redaction: Redaction,
+    /// The seed of `random`, if one was set explicitly - see [`MountedMockSet::seed_random`].
+    seed: Option<u64>,
+    /// The source of random values for responders and response templates.
+    random: Random,
+    /// The state of the sessions, shared by the responders of all mocks.
+    sessions: Sessions,
+    /// Whether registering a mock with the same matchers as a mounted one panics, rather than
+    /// logging a warning - see [`MountedMockSet::deny_duplicates`].
+    deny_duplicates: bool,
+}
+
+/// The outcome of matching an incoming request against the mocks in a [`MountedMockSet`].
+pub(crate) struct HandledRequest {
+    pub(crate) response: PendingResponse,
+    /// The mock that matched the request, if any.
+    pub(crate) matched_mock: Option<MatchedMock>,
+    /// The request counts as in-flight for the matched mock until this guard is dropped.
+    pub(crate) in_flight: Option<InFlightGuard>,
+    /// Where to record how long it took to respond, if a mock matched the request.
+    pub(crate) latencies: Option<Arc<LatencyRecorder>>,
+    /// Where to record how long it took to send the body of the response, if the matched mock
+    /// has a budget for it.
+    pub(crate) consumption: Option<Arc<ConsumptionRecorder>>,
+    /// The webhooks to fire once the response has been sent.
+    pub(crate) webhooks: Vec<WebhookTrigger>,
+    /// Where to draw the values of the `{{random ..}}` template variables from.
+    pub(crate) random: Random,
 }
 
 /// A `MockId` is an opaque index that uniquely identifies an [`MountedMock`] inside an [`MountedMockSet`].
@@ -36,7 +94,8 @@ pub(crate) struct MountedMockSet {
 /// The only way to create a `MockId` is calling [`MountedMockSet::register`].
 #[derive(Copy, Clone)]
 pub(crate) struct MockId {
-    index: usize,
+    /// The position of the mock in the set, i.e. how many mocks were registered before it.
+    position: usize,
     /// The generation of [`MountedMockSet`] when [`MountedMockSet::register`] was called.
     /// It allows [`MountedMockSet`] to check that the [`MountedMock`] our [`MockId`] points to is still in
     /// the set (i.e. the set has not been wiped by a [`MountedMockSet::reset`] call).
@@ -45,56 +104,168 @@ pub(crate) struct MockId {
 
 impl MountedMockSet {
     /// Create a new instance of `MountedMockSet`.
-    pub(crate) fn new(body_print_limit: BodyPrintLimit) -> MountedMockSet {
+    pub(crate) fn new(body_print_limit: BodyPrintLimit, redaction: Redaction) -> MountedMockSet {
         MountedMockSet {
             mocks: vec![],
+            n_registered_mocks: 0,
+            match_order: vec![],
             generation: 0,
+            n_handled_requests: AtomicU64::new(0),
+            n_unmatched_requests: AtomicU64::new(0),
             body_print_limit,
+            redaction,
+            seed: None,
+            random: Random::unseeded(),
+            sessions: Sessions::default(),
+            deny_duplicates: false,
         }
     }
 
-    pub(crate) async fn handle_request(
-        &mut self,
-        request: Request,
-    ) -> (hyper::Response<Full<Bytes>>, Option<Sleep>) {
+    /// Panic when a mock with the same matchers as a mounted one is registered, instead of
+    /// logging a warning.
+    pub(crate) fn deny_duplicates(&mut self) {
+        self.deny_duplicates = true;
+    }
+
+    /// Look for a mounted mock with the same matchers as `mock`.
+    ///
+    /// Mocks that only match a limited number of requests (see [`Mock::up_to_n_times`]) are
+    /// not considered duplicates: mounting several of them for the same requests is the way
+    /// to return different responses over time.
+    fn find_duplicate(&self, mock: &Mock) -> Option<&MountedMock> {
+        let is_limited = |mock: &Mock| mock.max_n_matches.is_some() || mock.request_range.is_some();
+        if is_limited(mock) {
+            return None;
+        }
+        let fingerprints = |mock: &Mock| -> Vec<Option<Fingerprint>> {
+            mock.matchers
+                .iter()
+                .map(|matcher| matcher.fingerprint.clone())
+                .collect()
+        };
+        let new = fingerprints(mock);
+        self.mocks
+            .iter()
+            .filter(|(_, state)| *state != MountedMockState::OutOfScope)
+            .map(|(mounted, _)| mounted)
+            .find(|mounted| {
+                !is_limited(&mounted.specification)
+                    && equivalent(&fingerprints(&mounted.specification), &new)
+            })
+    }
+
+    /// Seed the source of random values: the same seed yields the same values, as long as
+    /// requests are received in the same order.
+    pub(crate) fn seed_random(&mut self, seed: u64) {
+        self.seed = Some(seed);
+        self.random = Random::seeded(seed);
+    }
+
+    /// Group requests into sessions according to `key`, dropping the state of the current
+    /// sessions.
+    pub(crate) fn set_session_key(&mut self, key: SessionKey) {
+        self.sessions = Sessions::new(key);
+    }
+
+    /// Find the first mock matching `request` and generate the corresponding response.
+    pub(crate) async fn handle_request(&self, request: &Request) -> HandledRequest {
         debug!("Handling request.");
-        let mut response_template: Option<ResponseTemplate> = None;
-        self.mocks.sort_by_key(|(m, _)| m.specification.priority);
-        for (mock, mock_state) in &mut self.mocks {
-            if *mock_state == MountedMockState::OutOfScope {
-                continue;
-            }
-            if mock.matches(&request) {
-                response_template = Some(mock.response_template(&request));
+        let sequence_number = self.n_handled_requests.fetch_add(1, Ordering::SeqCst);
+        let mut matched: Option<(PendingResponse, &MountedMock)> = None;
+        for mock in self.candidates() {
+            if let Some(n_previous_matches) =
+                mock.matches(request, sequence_number, &self.redaction)
+            {
+                let response = mock.response_template(
+                    request,
+                    n_previous_matches,
+                    sequence_number,
+                    &self.random,
+                    &self.sessions,
+                );
+                matched = Some((response, mock));
                 break;
             }
         }
-        if let Some(response_template) = response_template {
-            let delay = response_template.delay().map(sleep);
-            (response_template.generate_response(), delay)
+        if let Some((response, mock)) = matched {
+            HandledRequest {
+                response,
+                matched_mock: Some(mock.identity()),
+                in_flight: Some(mock.in_flight().enter()),
+                latencies: Some(mock.latencies().clone()),
+                consumption: mock.consumption().cloned(),
+                webhooks: mock.specification.webhooks.clone(),
+                random: self.random.clone(),
+            }
         } else {
+            self.n_unmatched_requests.fetch_add(1, Ordering::SeqCst);
             let mut msg = "Got unexpected request:\n".to_string();
-            _ = request.print_with_limit(&mut msg, self.body_print_limit);
+            _ = request.print_with_limit(&mut msg, self.body_print_limit, &self.redaction.headers);
             debug!("{}", msg);
-            (
-                hyper::Response::builder()
-                    .status(hyper::StatusCode::NOT_FOUND)
-                    .body(Full::default())
-                    .unwrap(),
-                None,
-            )
+            HandledRequest {
+                response: PendingResponse::Ready(Box::new(ResponseTemplate::new(404))),
+                matched_mock: None,
+                in_flight: None,
+                latencies: None,
+                consumption: None,
+                webhooks: vec![],
+                random: self.random.clone(),
+            }
         }
     }
 
+    /// How the first mock that could match `request` handles `Expect: 100-continue`, if it
+    /// says so - see [`ResponseTemplate::reject_expect_continue`].
+    ///
+    /// Nothing is recorded: `request` is only the head of the actual request, whose body has
+    /// not been read yet.
+    pub(crate) fn expect_continue(&self, request: &Request) -> Option<ExpectContinue> {
+        self.candidates()
+            .find(|mock| mock.could_match(request))
+            .and_then(|mock| mock.specification.expect_continue)
+    }
+
+    /// The mocks that incoming requests are matched against, in the order they are tried - see
+    /// [`MountedMockSet::handle_request`].
+    fn candidates(&self) -> impl Iterator<Item = &MountedMock> {
+        self.match_order
+            .iter()
+            .map(move |&index| &self.mocks[index])
+            .filter(|(_, state)| *state == MountedMockState::InScope)
+            .map(|(mock, _)| mock)
+    }
+
     pub(crate) fn register(&mut self, mock: Mock) -> (Arc<(Notify, AtomicBool)>, MockId) {
-        let n_registered_mocks = self.mocks.len();
+        let n_registered_mocks = self.n_registered_mocks;
+        if let Some(duplicate) = self.find_duplicate(&mock) {
+            let new = MatchedMock {
+                name: mock.name.clone(),
+                position: n_registered_mocks,
+            };
+            let message = format!(
+                "{} has the same matchers as {}, which is already mounted: one of them will never match a request",
+                new,
+                duplicate.identity()
+            );
+            if self.deny_duplicates {
+                panic!("{}", message);
+            }
+            warn!("{}", message);
+        }
         let active_mock = MountedMock::new(mock, n_registered_mocks);
+        self.n_registered_mocks += 1;
         let notify = active_mock.notify();
+        // Mocks with the same priority are matched in the order they were mounted.
+        let priority = active_mock.specification.priority;
+        let rank = self
+            .match_order
+            .partition_point(|&index| self.mocks[index].0.specification.priority <= priority);
+        self.match_order.insert(rank, self.mocks.len());
         self.mocks.push((active_mock, MountedMockState::InScope));
         (
             notify,
             MockId {
-                index: self.mocks.len() - 1,
+                position: n_registered_mocks,
                 generation: self.generation,
             },
         )
@@ -102,7 +273,65 @@ impl MountedMockSet {
 
     pub(crate) fn reset(&mut self) {
         self.mocks = vec![];
+        self.n_registered_mocks = 0;
+        self.match_order = vec![];
         self.generation += 1;
+        *self.n_handled_requests.get_mut() = 0;
+        *self.n_unmatched_requests.get_mut() = 0;
+        self.random = match self.seed {
+            Some(seed) => Random::seeded(seed),
+            None => Random::unseeded(),
+        };
+        self.sessions.clear();
+    }
+
+    /// Forget the requests matched by each mock so far, keeping the mocks in the set.
+    ///
+    /// Unlike [`MountedMockSet::reset`], the [`MockId`]s issued so far remain valid.
+    pub(crate) fn reset_expectations(&mut self) {
+        for (mock, _) in &mut self.mocks {
+            mock.reset_counters();
+        }
+        *self.n_handled_requests.get_mut() = 0;
+        *self.n_unmatched_requests.get_mut() = 0;
+    }
+
+    /// The index in `mocks` of the mock corresponding to `mock_id`, if it is still in the set.
+    fn find(&self, mock_id: MockId) -> Option<usize> {
+        if mock_id.generation != self.generation {
+            return None;
+        }
+        // Mocks are stored in the order they were registered, hence by position.
+        self.mocks
+            .binary_search_by_key(&mock_id.position, |(mock, _)| mock.identity().position)
+            .ok()
+    }
+
+    /// The number of times [`MountedMockSet::reset`] has been called.
+    pub(crate) fn generation(&self) -> u16 {
+        self.generation
+    }
+
+    /// Remove the mocks corresponding to `mock_ids` from the set, e.g. because they have been
+    /// replaced.
+    ///
+    /// Unlike deactivated mocks, removed mocks are dropped: they are not verified, nor listed
+    /// in the metrics. The ids of the other mocks remain valid.
+    pub(crate) fn remove(&mut self, mock_ids: &[MockId]) {
+        let mut removed: Vec<usize> = mock_ids.iter().filter_map(|&id| self.find(id)).collect();
+        if removed.is_empty() {
+            return;
+        }
+        removed.sort_unstable();
+        let mut index = 0;
+        self.mocks.retain(|_| {
+            index += 1;
+            removed.binary_search(&(index - 1)).is_err()
+        });
+        // Indexes have shifted: sort them again, by priority then by position.
+        let mut match_order: Vec<usize> = (0..self.mocks.len()).collect();
+        match_order.sort_by_key(|&index| self.mocks[index].0.specification.priority);
+        self.match_order = match_order;
     }
 
     /// Mark one of the mocks in the set as out of scope.
@@ -113,14 +342,215 @@ impl MountedMockSet {
         mock.1 = MountedMockState::OutOfScope;
     }
 
+    /// Mark all the mocks named `name` as out of scope.
+    ///
+    /// It returns how many mocks have been deactivated.
+    pub(crate) fn deactivate_by_name(&mut self, name: &str) -> usize {
+        let mut n_deactivated = 0;
+        for (mock, state) in &mut self.mocks {
+            if *state != MountedMockState::OutOfScope
+                && mock.specification.name.as_deref() == Some(name)
+            {
+                *state = MountedMockState::OutOfScope;
+                n_deactivated += 1;
+            }
+        }
+        n_deactivated
+    }
+
+    /// Temporarily stop matching incoming requests against one of the mocks in the set.
+    ///
+    /// Out of scope mocks stay out of scope.
+    pub(crate) fn pause(&mut self, mock_id: MockId) {
+        let mock = &mut self[mock_id];
+        if mock.1 == MountedMockState::InScope {
+            mock.1 = MountedMockState::Paused;
+        }
+    }
+
+    /// Start matching incoming requests against a paused mock again.
+    pub(crate) fn resume(&mut self, mock_id: MockId) {
+        let mock = &mut self[mock_id];
+        if mock.1 == MountedMockState::Paused {
+            mock.1 = MountedMockState::InScope;
+        }
+    }
+
+    /// Describe the mocks in the set, in the order they were mounted, for the debug UI.
+    #[cfg(feature = "debug-ui")]
+    pub(crate) fn overview(&self) -> Vec<crate::debug_ui::MockOverview> {
+        let mut overview: Vec<_> = self
+            .mocks
+            .iter()
+            .filter(|(_, state)| *state != MountedMockState::OutOfScope)
+            .map(|(mock, state)| crate::debug_ui::MockOverview {
+                mock: mock.identity(),
+                priority: mock.specification.priority,
+                paused: *state == MountedMockState::Paused,
+                n_matches: mock.metrics().n_matches,
+                matchers: mock
+                    .specification
+                    .matchers
+                    .iter()
+                    .map(|matcher| match &matcher.fingerprint {
+                        Some(fingerprint) => fingerprint.to_string(),
+                        None => "Custom matcher".to_string(),
+                    })
+                    .collect(),
+            })
+            .collect();
+        overview.sort_by_key(|overview| overview.mock.position);
+        overview
+    }
+
+    /// Pause the mock mounted at `position`, or resume it if it is paused.
+    ///
+    /// It returns `false` if there is no such mock, or if it is out of scope.
+    #[cfg(feature = "debug-ui")]
+    pub(crate) fn toggle(&mut self, position: usize) -> bool {
+        let mock = self
+            .mocks
+            .iter_mut()
+            .find(|(mock, _)| mock.identity().position == position);
+        match mock {
+            Some((_, state)) if *state == MountedMockState::InScope => {
+                *state = MountedMockState::Paused;
+                true
+            }
+            Some((_, state)) if *state == MountedMockState::Paused => {
+                *state = MountedMockState::InScope;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Replace the responder of the mock corresponding to the specified [`MockId`].
+    ///
+    /// Match counts and expectations are left untouched.
+    pub(crate) fn set_responder(&mut self, mock_id: MockId, responder: Responder) {
+        let (mock, _) = &mut self[mock_id];
+        mock.specification.response = responder;
+        // The stub no longer describes how the mock responds.
+        mock.specification.stub = None;
+    }
+
+    /// Replace the responder of all the in-scope (or paused) mocks named `name`.
+    ///
+    /// It returns how many mocks have been updated.
+    pub(crate) fn set_responder_by_name(&mut self, name: &str, responder: Responder) -> usize {
+        let mut n_updated = 0;
+        for (mock, state) in &mut self.mocks {
+            if *state != MountedMockState::OutOfScope
+                && mock.specification.name.as_deref() == Some(name)
+            {
+                mock.specification.response = responder.clone();
+                mock.specification.stub = None;
+                n_updated += 1;
+            }
+        }
+        n_updated
+    }
+
+    /// Return the [`Stub`]s describing the mounted mocks, in the order they were mounted.
+    ///
+    /// Mocks that were not built from a [`Stub`] are skipped.
+    pub(crate) fn stubs(&self) -> Vec<Stub> {
+        self.mocks
+            .iter()
+            .filter(|(_, state)| *state != MountedMockState::OutOfScope)
+            .filter_map(|(mock, _)| {
+                let mock = &mock.specification;
+                mock.stub.clone().map(|stub| Stub {
+                    name: mock.name.clone(),
+                    priority: Some(mock.priority).filter(|p| *p != 5),
+                    ..stub
+                })
+            })
+            .collect()
+    }
+
+    /// Look for mocks that can never match a request, because a mock tried before them matches
+    /// all the requests they would match.
+    pub(crate) fn lint(&self) -> Vec<MockLint> {
+        let mut in_scope: Vec<&MountedMock> = self
+            .mocks
+            .iter()
+            .filter(|(_, state)| *state == MountedMockState::InScope)
+            .map(|(mock, _)| mock)
+            .collect();
+        // Same order as `handle_request`: by priority, then by position.
+        in_scope.sort_by_key(|mock| (mock.specification.priority, mock.identity().position));
+        let fingerprints = |mock: &MountedMock| -> Vec<Option<Fingerprint>> {
+            mock.specification
+                .matchers
+                .iter()
+                .map(|matcher| matcher.fingerprint.clone())
+                .collect()
+        };
+
+        let mut lints = vec![];
+        for (i, mock) in in_scope.iter().enumerate() {
+            let later = fingerprints(mock);
+            let shadowed_by = in_scope[..i].iter().find(|earlier| {
+                // Mocks that stop matching at some point cannot shadow anything for good.
+                let spec = &earlier.specification;
+                spec.max_n_matches.is_none()
+                    && spec.request_range.is_none()
+                    && subsumes(&fingerprints(earlier), &later)
+            });
+            if let Some(shadowed_by) = shadowed_by {
+                let lint = MockLint::Unreachable {
+                    mock: mock.identity(),
+                    shadowed_by: shadowed_by.identity(),
+                };
+                lints.push((mock.identity().position, lint));
+            }
+        }
+        lints.sort_by_key(|(position, _)| *position);
+        lints.into_iter().map(|(_, lint)| lint).collect()
+    }
+
+    /// Return the metrics of the mounted mocks, in the order they were mounted.
+    pub(crate) fn metrics(&self) -> Vec<MockMetrics> {
+        self.mocks
+            .iter()
+            .filter(|(_, state)| *state != MountedMockState::OutOfScope)
+            .map(|(mock, _)| mock.metrics())
+            .collect()
+    }
+
+    /// Render the metrics of the set in the Prometheus text format.
+    pub(crate) fn prometheus_metrics(&self) -> String {
+        render_prometheus(
+            self.n_handled_requests.load(Ordering::SeqCst),
+            self.n_unmatched_requests.load(Ordering::SeqCst),
+            &self.metrics(),
+        )
+    }
+
+    /// Return the metrics of the mock corresponding to the specified [`MockId`].
+    pub(crate) fn mock_metrics(&self, mock_id: MockId) -> MockMetrics {
+        let (mock, _) = &self[mock_id];
+        mock.metrics()
+    }
+
     /// Verify that expectations have been met for **all** [`MountedMock`]s in the set.
-    pub(crate) fn verify_all(&self) -> VerificationOutcome {
+    ///
+    /// If `on_drop` is set, the mocks that opted out of verification on drop are skipped.
+    pub(crate) fn verify_all(&self, on_drop: bool) -> VerificationOutcome {
         let failed_verifications: Vec<VerificationReport> = self
             .mocks
             .iter()
-            .filter(|(_, state)| *state == MountedMockState::InScope)
+            .filter(|(_, state)| *state != MountedMockState::OutOfScope)
             .map(|(m, _)| m.verify())
-            .filter(|verification_report| !verification_report.is_satisfied())
+            .filter(|verification_report| {
+                if on_drop {
+                    !verification_report.is_satisfied_on_drop()
+                } else {
+                    !verification_report.is_satisfied()
+                }
+            })
             .collect();
         if failed_verifications.is_empty() {
             VerificationOutcome::Success
@@ -138,10 +568,8 @@ impl MountedMockSet {
 
 impl IndexMut<MockId> for MountedMockSet {
     fn index_mut(&mut self, index: MockId) -> &mut Self::Output {
-        if index.generation != self.generation {
-            panic!("The mock you are trying to access is no longer active. It has been deleted from the active set via `reset` - you should not hold on to a `MockId` after you call `reset`!.")
-        }
-        &mut self.mocks[index.index]
+        let index = self.find(index).unwrap_or_else(|| panic_on_missing_mock());
+        &mut self.mocks[index]
     }
 }
 
@@ -149,32 +577,37 @@ impl Index<MockId> for MountedMockSet {
     type Output = (MountedMock, MountedMockState);
 
     fn index(&self, index: MockId) -> &Self::Output {
-        if index.generation != self.generation {
-            panic!("The mock you are trying to access is no longer active. It has been deleted from the active set via `reset` - you should not hold on to a `MockId` after you call `reset`!.")
-        }
-        &self.mocks[index.index]
+        let index = self.find(index).unwrap_or_else(|| panic_on_missing_mock());
+        &self.mocks[index]
     }
 }
 
+fn panic_on_missing_mock() -> ! {
+    panic!("The mock you are trying to access is no longer active. It has been deleted from the active set via `reset` - you should not hold on to a `MockId` after you call `reset`!.")
+}
+
 /// A [`MountedMock`] can either be global (i.e. registered using [`crate::MockServer::register`]) or
 /// scoped (i.e. registered using [`crate::MockServer::register_as_scoped`]).
 ///
 /// [`MountedMock`]s must currently be in scope to be matched against incoming requests.
 /// Out of scope [`MountedMock`]s are skipped when trying to match an incoming request.
 ///
+/// A [`MountedMock`] can also be paused (see [`crate::MockGuard::pause`]): it is skipped when
+/// matching, like an out of scope mock, but its expectations are still verified.
+///
 /// # Implementation Rationale
 ///
 /// An alternative approach would be removing a [`MountedMock`] from the [`MountedMockSet`] when it goes
 /// out of scope.
-/// This would create an issue for the stability of [`MockId`]s: removing an element from the vector
-/// of [`MountedMock`]s in [`MountedMockSet`] would invalidate the ids of all mocks registered after
-/// the removed one.
+/// [`MockId`]s would stay valid, since they hold the position of the mock rather than its index in
+/// the vector, but a test mounts a bounded number of scoped mocks: flagging them is enough.
 ///
-/// Attaching a state to the mocks in the vector, instead, allows us to ensure id stability while
-/// achieving the desired behaviour.
+/// Mocks that are replaced over and over, e.g. when fixture files are reloaded, are removed via
+/// [`MountedMockSet::remove`] instead, so that they do not pile up.
 #[derive(Debug, PartialEq, Eq, Copy, Clone)]
 pub(crate) enum MountedMockState {
     InScope,
+    Paused,
     OutOfScope,
 }
 
@@ -182,11 +615,11 @@ pub(crate) enum MountedMockState {
 mod tests {
     use crate::matchers::path;
     use crate::mock_set::{MountedMockSet, MountedMockState};
-    use crate::request::BodyPrintLimit;
-    use crate::{Mock, ResponseTemplate};
+    use crate::request::{BodyPrintLimit, Redaction};
+    use crate::{Mock, Request, ResponseTemplate};
 
     fn test_mock_set() -> MountedMockSet {
-        MountedMockSet::new(BodyPrintLimit::Unlimited)
+        MountedMockSet::new(BodyPrintLimit::Unlimited, Redaction::default())
     }
 
     #[test]
@@ -233,4 +666,71 @@ mod tests {
         let second_mock = &set[second_mock_id];
         assert_eq!(second_mock.1, MountedMockState::InScope);
     }
+
+    #[test]
+    fn removing_a_mock_does_not_invalidate_other_ids() {
+        // Arrange
+        let mut set = test_mock_set();
+        let first_mock = Mock::given(path("/")).respond_with(ResponseTemplate::new(200));
+        let second_mock = Mock::given(path("/hello")).respond_with(ResponseTemplate::new(500));
+        let (_, first_mock_id) = set.register(first_mock);
+        let (_, second_mock_id) = set.register(second_mock);
+
+        // Act
+        set.remove(&[first_mock_id]);
+        let third_mock = Mock::given(path("/bye")).respond_with(ResponseTemplate::new(204));
+        let (_, third_mock_id) = set.register(third_mock);
+
+        // Assert
+        assert!(set.find(first_mock_id).is_none());
+        assert_eq!(set.mocks.len(), 2);
+        assert_eq!(set[second_mock_id].0.identity().position, 1);
+        assert_eq!(set[third_mock_id].0.identity().position, 2);
+        let request = Request::builder().path("/bye").build();
+        let handled = futures::executor::block_on(set.handle_request(&request));
+        assert_eq!(handled.matched_mock.unwrap().position, 2);
+    }
+
+    #[test]
+    fn paused_mocks_can_be_resumed_but_out_of_scope_mocks_cannot() {
+        // Arrange
+        let mut set = test_mock_set();
+        let first_mock = Mock::given(path("/")).respond_with(ResponseTemplate::new(200));
+        let second_mock = Mock::given(path("/hello")).respond_with(ResponseTemplate::new(500));
+        let (_, first_mock_id) = set.register(first_mock);
+        let (_, second_mock_id) = set.register(second_mock);
+
+        // Act
+        set.pause(first_mock_id);
+        set.deactivate(second_mock_id);
+        set.pause(second_mock_id);
+        assert_eq!(set[first_mock_id].1, MountedMockState::Paused);
+        set.resume(first_mock_id);
+        set.resume(second_mock_id);
+
+        // Assert
+        assert_eq!(set[first_mock_id].1, MountedMockState::InScope);
+        assert_eq!(set[second_mock_id].1, MountedMockState::OutOfScope);
+    }
+
+    #[test]
+    fn mocks_are_matched_by_priority_without_invalidating_their_ids() {
+        // Arrange
+        let mut set = test_mock_set();
+        let fallback = Mock::given(path("/")).respond_with(ResponseTemplate::new(404));
+        let preferred = Mock::given(path("/"))
+            .respond_with(ResponseTemplate::new(200))
+            .with_priority(1);
+        let (_, fallback_id) = set.register(fallback.with_priority(10));
+        let (_, preferred_id) = set.register(preferred);
+        let request = Request::builder().path("/").build();
+
+        // Act
+        let handled = futures::executor::block_on(set.handle_request(&request));
+
+        // Assert
+        assert_eq!(handled.matched_mock.unwrap().position, 1);
+        assert_eq!(set.verify(preferred_id).n_matched_requests, 1);
+        assert_eq!(set.verify(fallback_id).n_matched_requests, 0);
+    }
 }
@@ -1,14 +1,37 @@
-use std::sync::{atomic::AtomicBool, Arc};
+use std::collections::HashMap;
+use std::ops::RangeBounds;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
 
 use tokio::sync::Notify;
 
-use crate::{verification::VerificationReport, Match, Mock, Request, ResponseTemplate};
+use crate::{
+    in_flight::InFlightCounter,
+    journal::MatchedMock,
+    metrics::{LatencyRecorder, MockMetrics},
+    random::Random,
+    rate_limit::RateLimiter,
+    request::Redaction,
+    respond::{PendingResponse, ResponseContext},
+    response_body::ConsumptionRecorder,
+    session::Sessions,
+    verification::{ConsumptionViolation, ForbiddenMock, IntervalViolation, VerificationReport},
+    Match, Mock, Request,
+};
 
 /// Given the behaviour specification as a [`Mock`], keep track of runtime information
 /// concerning this mock - e.g. how many times it matched on a incoming request.
+///
+/// Incoming requests are matched through a shared reference, possibly concurrently: the
+/// runtime information is kept in atomics and mutexes.
 pub(crate) struct MountedMock {
     pub(crate) specification: Mock,
-    n_matched_requests: u64,
+    n_matched_requests: AtomicU64,
+    /// How many requests satisfied the matchers of this mock, including those that were
+    /// ignored because they fell outside of its request range - see
+    /// [`Mock::only_for_request_range`].
+    n_candidate_requests: AtomicU64,
     /// The position occupied by this mock within the parent [`MountedMockSet`](crate::mock_set::MountedMockSet)
     /// collection of `MountedMock`s.
     ///
@@ -16,76 +39,271 @@ pub(crate) struct MountedMock {
     /// if it is the second, etc.
     position_in_set: usize,
 
-    // matched requests:
-    matched_requests: Vec<crate::Request>,
+    history: Mutex<MatchHistory>,
+    /// How many matched requests are currently being served by this mock.
+    in_flight: Arc<InFlightCounter>,
+    /// Enforces the rate limit set on the mock, if any.
+    rate_limiter: Mutex<RateLimiter>,
+    /// The latencies of the responses sent by this mock.
+    latencies: Arc<LatencyRecorder>,
+    /// How long it took to send the bodies of the responses of this mock.
+    consumption: Arc<ConsumptionRecorder>,
 
     notify: Arc<(Notify, AtomicBool)>,
 }
 
+/// The requests matched by a [`MountedMock`], and when it matched them.
+#[derive(Default)]
+struct MatchHistory {
+    requests: Vec<crate::Request>,
+    /// The sequence number of the first incoming request matched by this mock, if any.
+    /// See [`MountedMockSet`](crate::mock_set::MountedMockSet) for how sequence numbers are assigned.
+    first_matched_at: Option<u64>,
+    /// When this mock matched an incoming request for the last time.
+    last_matched_at: Option<Instant>,
+}
+
 impl MountedMock {
     pub(crate) fn new(specification: Mock, position_in_set: usize) -> Self {
         Self {
             specification,
-            n_matched_requests: 0,
+            n_matched_requests: AtomicU64::new(0),
+            n_candidate_requests: AtomicU64::new(0),
             position_in_set,
-            matched_requests: Vec::new(),
+            history: Mutex::default(),
+            in_flight: Arc::new(InFlightCounter::default()),
+            rate_limiter: Mutex::default(),
+            latencies: Arc::new(LatencyRecorder::default()),
+            consumption: Arc::new(ConsumptionRecorder::default()),
             notify: Arc::new((Notify::new(), AtomicBool::new(false))),
         }
     }
 
+    /// Whether the mock would match `request`, without recording it - the requests matched by
+    /// [`Mock::up_to_n_times`](crate::Mock::up_to_n_times) aside.
+    ///
+    /// It runs the same checks as [`MountedMock::matches`], including
+    /// [`Mock::only_for_request_range`](crate::Mock::only_for_request_range).
+    pub(crate) fn could_match(&self, request: &Request) -> bool {
+        if self.is_exhausted() || !self.matchers_match(request) {
+            return false;
+        }
+        self.specification
+            .request_range
+            .as_ref()
+            .is_none_or(|range| {
+                range.contains(&(self.n_candidate_requests.load(Ordering::SeqCst) + 1))
+            })
+    }
+
+    fn is_exhausted(&self) -> bool {
+        Some(self.n_matched_requests.load(Ordering::SeqCst)) == self.specification.max_n_matches
+    }
+
+    fn matchers_match(&self, request: &Request) -> bool {
+        self.specification
+            .matchers
+            .iter()
+            .all(|matcher| matcher.matches(request))
+    }
+
     /// This is NOT the same of `matches` from the `Match` trait!
-    /// Key difference: we are talking a mutable reference to `self` in order to capture
-    /// additional information (e.g. how many requests we matched so far) or change behaviour
-    /// after a certain threshold has been crossed (e.g. start returning `false` for all requests
-    /// once enough requests have been matched according to `max_n_matches`).
-    pub(crate) fn matches(&mut self, request: &Request) -> bool {
-        if Some(self.n_matched_requests) == self.specification.max_n_matches {
-            // Skip the actual check if we are already at our maximum of matched requests.
-            false
-        } else {
-            let matched = self
-                .specification
-                .matchers
-                .iter()
-                .all(|matcher| matcher.matches(request));
-
-            if matched {
-                // Increase match count
-                self.n_matched_requests += 1;
-                // Keep track of request
-                self.matched_requests.push(request.clone());
-
-                // notification of satisfaction
-                if self.verify().is_satisfied() {
-                    // always set the satisfaction flag **before** raising the event
-                    self.notify
-                        .1
-                        .store(true, std::sync::atomic::Ordering::Release);
-                    self.notify.0.notify_waiters();
-                }
+    /// Key difference: we capture additional information (e.g. how many requests we matched
+    /// so far) and change behaviour after a certain threshold has been crossed (e.g. start
+    /// returning `None` for all requests once enough requests have been matched according to
+    /// `max_n_matches`).
+    ///
+    /// `sequence_number` is the position of `request` among all the requests received by the
+    /// server, used to keep track of when this mock matched for the first time.
+    ///
+    /// If `request` matches, it returns how many requests the mock matched before it.
+    /// Matched requests are stored after applying `redaction`.
+    pub(crate) fn matches(
+        &self,
+        request: &Request,
+        sequence_number: u64,
+        redaction: &Redaction,
+    ) -> Option<u64> {
+        // Skip the actual check if we are already at our maximum of matched requests.
+        if self.is_exhausted() || !self.matchers_match(request) {
+            return None;
+        }
+        if let Some(range) = &self.specification.request_range {
+            let n_candidate_requests = self.n_candidate_requests.fetch_add(1, Ordering::SeqCst) + 1;
+            if !range.contains(&n_candidate_requests) {
+                return None;
             }
+        }
+        // Concurrent requests may have exhausted the mock since we checked.
+        let n_previous_matches = self
+            .n_matched_requests
+            .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |n| {
+                (Some(n) != self.specification.max_n_matches).then_some(n + 1)
+            })
+            .ok()?;
+        let is_satisfied = {
+            let mut history = self.history.lock().unwrap();
+            history.requests.push(redaction.apply(request));
+            // Requests matched concurrently may be recorded out of order.
+            history.first_matched_at = Some(
+                history
+                    .first_matched_at
+                    .map_or(sequence_number, |first| first.min(sequence_number)),
+            );
+            history.last_matched_at = Some(Instant::now());
+            self.is_satisfied(n_previous_matches + 1, &history.requests)
+        };
 
-            matched
+        // notification of satisfaction - the full report is only built by `verify`, on demand
+        if is_satisfied {
+            // always set the satisfaction flag **before** raising the event
+            self.notify.1.store(true, Ordering::Release);
+            self.notify.0.notify_waiters();
         }
+        Some(n_previous_matches)
+    }
+
+    /// Run the same checks as [`VerificationReport::is_satisfied`], without building the
+    /// report: `requests` are the `n_matched_requests` requests matched so far.
+    fn is_satisfied(&self, n_matched_requests: u64, requests: &[Request]) -> bool {
+        let specification = &self.specification;
+        specification.expectation_range.contains(n_matched_requests)
+            && specification.min_interval.is_none_or(|min_interval| {
+                IntervalViolation::find(requests, min_interval).is_none()
+            })
+            && specification.consumption_budget.is_none_or(|budget| {
+                ConsumptionViolation::find(&self.consumption.bodies(), budget).is_none()
+            })
+    }
+
+    /// The path parameters captured by the matchers of the mock for `request`, which matched -
+    /// see [`path_template`](crate::matchers::path_template).
+    fn path_params(&self, request: &Request) -> HashMap<String, String> {
+        let mut params = HashMap::new();
+        for matcher in &self.specification.matchers {
+            matcher.capture_path_params(request, &mut params);
+        }
+        params
+    }
+
+    /// Forget the requests matched so far, as if the mock had just been mounted.
+    pub(crate) fn reset_counters(&mut self) {
+        *self.n_matched_requests.get_mut() = 0;
+        *self.n_candidate_requests.get_mut() = 0;
+        self.history = Mutex::default();
+        self.rate_limiter = Mutex::default();
+        self.latencies = Arc::new(LatencyRecorder::default());
+        self.consumption = Arc::new(ConsumptionRecorder::default());
+        self.notify.1.store(false, Ordering::Release);
     }
 
     /// Verify if this mock has verified the expectations set at creation time
     /// over the number of invocations.
     pub(crate) fn verify(&self) -> VerificationReport {
+        let history = self.history.lock().unwrap();
         VerificationReport {
             mock_name: self.specification.name.clone(),
-            n_matched_requests: self.n_matched_requests,
+            n_matched_requests: self.n_matched_requests.load(Ordering::SeqCst),
+            interval_violation: self
+                .specification
+                .min_interval
+                .and_then(|min_interval| IntervalViolation::find(&history.requests, min_interval)),
+            consumption_violation: self
+                .specification
+                .consumption_budget
+                .and_then(|budget| ConsumptionViolation::find(&self.consumption.bodies(), budget)),
             expectation_range: self.specification.expectation_range.clone(),
             position_in_set: self.position_in_set,
+            registered_at: self.specification.registered_at,
+            forbidden: self
+                .specification
+                .forbidden_at
+                .map(|location| ForbiddenMock {
+                    location,
+                    matched_requests: history.requests.clone(),
+                }),
+            verify_on_drop: self.specification.verify_on_drop,
+        }
+    }
+
+    /// Identify this mock in the request journal.
+    pub(crate) fn identity(&self) -> MatchedMock {
+        MatchedMock {
+            name: self.specification.name.clone(),
+            position: self.position_in_set,
         }
     }
 
-    pub(crate) fn response_template(&self, request: &Request) -> ResponseTemplate {
-        self.specification.response_template(request)
+    /// Build the response for a request that matched this mock.
+    ///
+    /// If the mock requires authentication and the request does not carry valid credentials,
+    /// the challenge is returned instead of invoking the responder. The same goes for the
+    /// throttled response, if the mock is rate-limited and the request exceeds the budget.
+    ///
+    /// It must be called right after [`MountedMock::matches`] returned `n_previous_matches`
+    /// for `request`.
+    pub(crate) fn response_template(
+        &self,
+        request: &Request,
+        n_previous_matches: u64,
+        sequence_number: u64,
+        random: &Random,
+        sessions: &Sessions,
+    ) -> PendingResponse {
+        if let Some(challenge) = &self.specification.auth_challenge {
+            if let Some(response) = challenge.challenge(request) {
+                return PendingResponse::Ready(Box::new(response));
+            }
+        }
+        if let Some(rate_limit) = &self.specification.rate_limit {
+            let mut rate_limiter = self.rate_limiter.lock().unwrap();
+            if !rate_limiter.try_acquire(rate_limit, Instant::now()) {
+                return PendingResponse::Ready(Box::new(rate_limit.throttled_response.clone()));
+            }
+        }
+        let ctx = ResponseContext {
+            n_previous_matches,
+            mock_name: self.specification.name.clone(),
+            sequence_number,
+            path_params: self.path_params(request),
+            random: random.clone(),
+            session_id: sessions.session_id(request),
+            sessions: sessions.clone(),
+        };
+        self.specification.response_template(request, ctx)
     }
 
     pub(crate) fn received_requests(&self) -> Vec<crate::Request> {
-        self.matched_requests.clone()
+        self.history.lock().unwrap().requests.clone()
+    }
+
+    pub(crate) fn first_matched_at(&self) -> Option<u64> {
+        self.history.lock().unwrap().first_matched_at
+    }
+
+    pub(crate) fn in_flight(&self) -> &Arc<InFlightCounter> {
+        &self.in_flight
+    }
+
+    pub(crate) fn latencies(&self) -> &Arc<LatencyRecorder> {
+        &self.latencies
+    }
+
+    /// Where to record how long it takes to send the bodies of the responses, if the mock has
+    /// a budget for it.
+    pub(crate) fn consumption(&self) -> Option<&Arc<ConsumptionRecorder>> {
+        self.specification
+            .consumption_budget
+            .map(|_| &self.consumption)
+    }
+
+    pub(crate) fn metrics(&self) -> MockMetrics {
+        MockMetrics {
+            mock: self.identity(),
+            n_matches: self.n_matched_requests.load(Ordering::SeqCst),
+            latencies: self.latencies.latencies(),
+            last_matched_at: self.history.lock().unwrap().last_matched_at,
+        }
     }
 
     pub(crate) fn notify(&self) -> Arc<(Notify, AtomicBool)> {
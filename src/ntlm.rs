@@ -0,0 +1,168 @@
+//! The messages of the NTLM handshake, as specified in
+//! [MS-NLMP](https://learn.microsoft.com/en-us/openspecs/windows_protocols/ms-nlmp/).
+//!
+//! They are used to emulate servers requiring NTLM or Negotiate authentication - see
+//! [`AuthChallenge::ntlm`](crate::AuthChallenge::ntlm). No cryptography is involved: the
+//! response of the client to the server challenge is not verified.
+
+const SIGNATURE: &[u8; 8] = b"NTLMSSP\0";
+
+const NEGOTIATE_UNICODE: u32 = 0x0000_0001;
+const REQUEST_TARGET: u32 = 0x0000_0004;
+const NEGOTIATE_NTLM: u32 = 0x0000_0200;
+const NEGOTIATE_ALWAYS_SIGN: u32 = 0x0000_8000;
+const TARGET_TYPE_DOMAIN: u32 = 0x0001_0000;
+const NEGOTIATE_EXTENDED_SESSION_SECURITY: u32 = 0x0008_0000;
+const NEGOTIATE_TARGET_INFO: u32 = 0x0080_0000;
+
+/// A message sent by the client.
+#[derive(Debug, PartialEq, Eq)]
+pub(crate) enum ClientMessage {
+    /// The first message of the handshake (type 1).
+    Negotiate,
+    /// The last message of the handshake (type 3), answering the challenge of the server.
+    Authenticate { domain: String, username: String },
+}
+
+impl ClientMessage {
+    /// Parse the NTLM message in `token`.
+    ///
+    /// The message can be wrapped in a SPNEGO token, as sent with the `Negotiate` scheme.
+    pub(crate) fn parse(token: &[u8]) -> Option<Self> {
+        let start = token
+            .windows(SIGNATURE.len())
+            .position(|window| window == SIGNATURE)?;
+        let message = &token[start..];
+        match read_u32(message, 8)? {
+            1 => Some(ClientMessage::Negotiate),
+            3 => {
+                let unicode = read_u32(message, 60)? & NEGOTIATE_UNICODE != 0;
+                Some(ClientMessage::Authenticate {
+                    domain: read_field(message, 28, unicode)?,
+                    username: read_field(message, 36, unicode)?,
+                })
+            }
+            _ => None,
+        }
+    }
+}
+
+/// The challenge message (type 2) sent by the server in answer to a negotiate message, for a
+/// server in `domain`.
+pub(crate) fn challenge_message(domain: &str, server_challenge: [u8; 8]) -> Vec<u8> {
+    const HEADER_LENGTH: usize = 48;
+
+    let target_name = utf16(domain);
+    // The NetBIOS domain name, followed by the end of the list.
+    let mut target_info = vec![];
+    target_info.extend_from_slice(&2u16.to_le_bytes());
+    target_info.extend_from_slice(&(target_name.len() as u16).to_le_bytes());
+    target_info.extend_from_slice(&target_name);
+    target_info.extend_from_slice(&[0; 4]);
+
+    let flags = NEGOTIATE_UNICODE
+        | REQUEST_TARGET
+        | NEGOTIATE_NTLM
+        | NEGOTIATE_ALWAYS_SIGN
+        | TARGET_TYPE_DOMAIN
+        | NEGOTIATE_EXTENDED_SESSION_SECURITY
+        | NEGOTIATE_TARGET_INFO;
+    let mut message = SIGNATURE.to_vec();
+    message.extend_from_slice(&2u32.to_le_bytes());
+    push_field_header(&mut message, target_name.len(), HEADER_LENGTH);
+    message.extend_from_slice(&flags.to_le_bytes());
+    message.extend_from_slice(&server_challenge);
+    // Reserved.
+    message.extend_from_slice(&[0; 8]);
+    push_field_header(
+        &mut message,
+        target_info.len(),
+        HEADER_LENGTH + target_name.len(),
+    );
+    message.extend_from_slice(&target_name);
+    message.extend_from_slice(&target_info);
+    message
+}
+
+fn utf16(value: &str) -> Vec<u8> {
+    value.encode_utf16().flat_map(u16::to_le_bytes).collect()
+}
+
+/// Variable-length fields are described by their length, their maximum length and their
+/// offset from the start of the message.
+fn push_field_header(message: &mut Vec<u8>, length: usize, offset: usize) {
+    message.extend_from_slice(&(length as u16).to_le_bytes());
+    message.extend_from_slice(&(length as u16).to_le_bytes());
+    message.extend_from_slice(&(offset as u32).to_le_bytes());
+}
+
+/// Read the variable-length field described at `offset`.
+fn read_field(message: &[u8], offset: usize, unicode: bool) -> Option<String> {
+    let length = read_u16(message, offset)? as usize;
+    let start = read_u32(message, offset + 4)? as usize;
+    let bytes = message.get(start..start.checked_add(length)?)?;
+    if unicode {
+        let units: Vec<u16> = bytes
+            .chunks_exact(2)
+            .map(|unit| u16::from_le_bytes([unit[0], unit[1]]))
+            .collect();
+        String::from_utf16(&units).ok()
+    } else {
+        Some(String::from_utf8_lossy(bytes).into_owned())
+    }
+}
+
+fn read_u16(message: &[u8], offset: usize) -> Option<u16> {
+    let bytes = message.get(offset..offset + 2)?;
+    Some(u16::from_le_bytes([bytes[0], bytes[1]]))
+}
+
+fn read_u32(message: &[u8], offset: usize) -> Option<u32> {
+    let bytes = message.get(offset..offset + 4)?;
+    Some(u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{challenge_message, push_field_header, utf16, ClientMessage, SIGNATURE};
+
+    #[test]
+    fn authenticate_messages_are_parsed() {
+        let (domain, username) = (utf16("CORP"), utf16("alice"));
+        let mut message = SIGNATURE.to_vec();
+        message.extend_from_slice(&3u32.to_le_bytes());
+        // The LM and NT responses are empty.
+        push_field_header(&mut message, 0, 64);
+        push_field_header(&mut message, 0, 64);
+        push_field_header(&mut message, domain.len(), 64);
+        push_field_header(&mut message, username.len(), 64 + domain.len());
+        // The workstation name and the session key are empty.
+        push_field_header(&mut message, 0, 64);
+        push_field_header(&mut message, 0, 64);
+        message.extend_from_slice(&1u32.to_le_bytes());
+        message.extend_from_slice(&domain);
+        message.extend_from_slice(&username);
+
+        assert_eq!(
+            ClientMessage::parse(&message),
+            Some(ClientMessage::Authenticate {
+                domain: "CORP".into(),
+                username: "alice".into()
+            })
+        );
+        assert_eq!(ClientMessage::parse(&message[..40]), None);
+    }
+
+    #[test]
+    fn negotiate_messages_are_found_in_spnego_tokens() {
+        let mut token = vec![0x60, 0x28, 0x06, 0x06];
+        token.extend_from_slice(SIGNATURE);
+        token.extend_from_slice(&1u32.to_le_bytes());
+
+        assert_eq!(ClientMessage::parse(&token), Some(ClientMessage::Negotiate));
+        assert_eq!(
+            ClientMessage::parse(&challenge_message("CORP", [0; 8])),
+            None
+        );
+    }
+}
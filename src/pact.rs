@@ -0,0 +1,244 @@
+//! Generation of [Pact](https://docs.pact.io/) contracts out of the traffic received by a
+//! [`MockServer`](crate::MockServer) - see [`MockServer::write_pact`](crate::MockServer::write_pact) -
+//! and the other way around - see [`MockServer::mount_pact`](crate::MockServer::mount_pact).
+use crate::matchers::{body_json, body_string, headers, method, path, query_params_exact};
+use crate::{JournalEntry, Mock, ResponseTemplate};
+use http::header::{
+    HeaderName, CONNECTION, CONTENT_LENGTH, CONTENT_TYPE, DATE, HOST, TE, TRANSFER_ENCODING,
+    USER_AGENT,
+};
+use http::{HeaderMap, HeaderValue};
+use http_body_util::BodyExt;
+use serde_json::{json, Map, Value};
+use std::collections::BTreeMap;
+
+/// Headers describing how a message was transferred rather than what it contains: they are
+/// left out of the contract.
+const TRANSPORT_HEADERS: [HeaderName; 7] = [
+    CONNECTION,
+    CONTENT_LENGTH,
+    DATE,
+    HOST,
+    TE,
+    TRANSFER_ENCODING,
+    USER_AGENT,
+];
+
+/// Build a Pact v3 contract out of the entries of the request journal.
+///
+/// Each request matched by a mock becomes an interaction, alongside the response it got.
+/// Unmatched requests are left out, as well as duplicated interactions.
+pub(crate) async fn to_pact(consumer: &str, provider: &str, entries: &[JournalEntry]) -> Value {
+    let mut interactions: Vec<Value> = Vec::new();
+    let mut descriptions: BTreeMap<String, usize> = BTreeMap::new();
+    for entry in entries {
+        let mock = match &entry.matched_mock {
+            Some(mock) => mock,
+            None => continue,
+        };
+        let request = pact_request(entry);
+        let response = pact_response(entry).await;
+        if interactions
+            .iter()
+            .any(|i| i["request"] == request && i["response"] == response)
+        {
+            continue;
+        }
+        // Descriptions must be unique within a contract.
+        let description = format!(
+            "{} - {} {}",
+            mock,
+            entry.request.method,
+            entry.request.url.path()
+        );
+        let n = descriptions.entry(description.clone()).or_insert(0);
+        *n += 1;
+        let description = match n {
+            1 => description,
+            n => format!("{} #{}", description, n),
+        };
+        interactions.push(json!({
+            "description": description,
+            "request": request,
+            "response": response,
+        }));
+    }
+    json!({
+        "consumer": { "name": consumer },
+        "provider": { "name": provider },
+        "interactions": interactions,
+        "metadata": {
+            "pactSpecification": { "version": "3.0.0" },
+            "wiremock": { "version": env!("CARGO_PKG_VERSION") },
+        },
+    })
+}
+
+/// Build a mock for each interaction of a Pact contract, standing in for the provider.
+///
+/// It panics if `pact` is not a valid Pact contract.
+pub(crate) fn mocks_from_pact(pact: &Value) -> Vec<Mock> {
+    pact["interactions"]
+        .as_array()
+        .expect("Invalid Pact: `interactions` must be an array")
+        .iter()
+        .enumerate()
+        .map(|(i, interaction)| mock_from_interaction(interaction, i))
+        .collect()
+}
+
+fn mock_from_interaction(interaction: &Value, index: usize) -> Mock {
+    let invalid = |reason: &str| -> ! { panic!("Invalid Pact: interaction #{} {}", index, reason) };
+    let request = &interaction["request"];
+    let request_method = request["method"]
+        .as_str()
+        .unwrap_or_else(|| invalid("has no request method"));
+    let request_path = request["path"]
+        .as_str()
+        .unwrap_or_else(|| invalid("has no request path"));
+
+    // Pact expects the query to match exactly.
+    let query: Vec<(String, String)> = match &request["query"] {
+        // Pact v3: a map from names to lists of values.
+        Value::Object(query) => query
+            .iter()
+            .flat_map(|(name, values)| {
+                let values: Vec<String> = match values {
+                    Value::Array(values) => values
+                        .iter()
+                        .map(|value| value.as_str().unwrap_or_default().to_owned())
+                        .collect(),
+                    value => vec![value.as_str().unwrap_or_default().to_owned()],
+                };
+                values.into_iter().map(move |value| (name.clone(), value))
+            })
+            .collect(),
+        // Pact v2: a query string.
+        Value::String(query) => url::form_urlencoded::parse(query.as_bytes())
+            .into_owned()
+            .collect(),
+        _ => vec![],
+    };
+    let mut builder = Mock::given(method(request_method))
+        .and(path(request_path))
+        .and(query_params_exact(query));
+    for (name, value) in request["headers"].as_object().into_iter().flatten() {
+        if let Some(value) = value.as_str() {
+            // Pact joins the values of a header with commas, as the header matchers expect.
+            let values = value.split(',').map(str::trim).collect();
+            builder = builder.and(headers(name.as_str(), values));
+        }
+    }
+    match &request["body"] {
+        Value::Null => {}
+        Value::String(body) if !is_json(&request["headers"]) => {
+            builder = builder.and(body_string(body.clone()));
+        }
+        body => builder = builder.and(body_json(body)),
+    }
+
+    let description = interaction["description"]
+        .as_str()
+        .map(ToOwned::to_owned)
+        .unwrap_or_else(|| format!("{} {}", request_method, request_path));
+    builder
+        .respond_with(response_from_interaction(&interaction["response"]))
+        .named(format!("Pact interaction #{} - {}", index, description))
+}
+
+fn response_from_interaction(response: &Value) -> ResponseTemplate {
+    let status = response["status"].as_u64().unwrap_or(200);
+    let mut template = ResponseTemplate::new(status as u16);
+    match &response["body"] {
+        Value::Null => {}
+        Value::String(body) if !is_json(&response["headers"]) => {
+            template = template.set_body_string(body.clone());
+        }
+        body => template = template.set_body_json(body),
+    }
+    // Headers come last, for the `Content-Type` of the contract to take precedence over the
+    // one set alongside the body.
+    for (name, value) in response["headers"].as_object().into_iter().flatten() {
+        if let Some(value) = value.as_str() {
+            template = template.insert_header(name.as_str(), value);
+        }
+    }
+    template
+}
+
+/// Whether the headers of a Pact request or response declare a JSON body.
+fn is_json(headers: &Value) -> bool {
+    headers
+        .as_object()
+        .into_iter()
+        .flatten()
+        .any(|(name, value)| {
+            name.eq_ignore_ascii_case("content-type")
+                && value.as_str().is_some_and(|value| value.contains("json"))
+        })
+}
+
+fn pact_request(entry: &JournalEntry) -> Value {
+    let request = &entry.request;
+    let mut pact_request = json!({
+        "method": request.method.as_str(),
+        "path": request.url.path(),
+    });
+    let mut query: BTreeMap<String, Vec<String>> = BTreeMap::new();
+    for (name, value) in request.url.query_pairs() {
+        query
+            .entry(name.into_owned())
+            .or_default()
+            .push(value.into_owned());
+    }
+    if !query.is_empty() {
+        pact_request["query"] = json!(query);
+    }
+    insert_headers_and_body(&mut pact_request, &request.headers, &request.body);
+    pact_request
+}
+
+async fn pact_response(entry: &JournalEntry) -> Value {
+    let (parts, body) = entry.response.generate_response().into_parts();
+    let body = body
+        .collect()
+        .await
+        .expect("Reading an in-memory body cannot fail")
+        .to_bytes();
+    let mut pact_response = json!({ "status": parts.status.as_u16() });
+    insert_headers_and_body(&mut pact_response, &parts.headers, &body);
+    pact_response
+}
+
+/// Add `headers` and `body` to a Pact request or response, if they are not empty.
+///
+/// JSON bodies are embedded as JSON, other bodies as strings.
+fn insert_headers_and_body(message: &mut Value, headers: &HeaderMap, body: &[u8]) {
+    let mut pact_headers = Map::new();
+    for name in headers.keys() {
+        if TRANSPORT_HEADERS.contains(name) {
+            continue;
+        }
+        let values: Vec<_> = headers
+            .get_all(name)
+            .iter()
+            .map(|value| String::from_utf8_lossy(value.as_bytes()).into_owned())
+            .collect();
+        pact_headers.insert(name.as_str().to_owned(), json!(values.join(", ")));
+    }
+    if !pact_headers.is_empty() {
+        message["headers"] = Value::Object(pact_headers);
+    }
+
+    if body.is_empty() {
+        return;
+    }
+    let is_json = headers
+        .get(CONTENT_TYPE)
+        .and_then(|value: &HeaderValue| value.to_str().ok())
+        .is_some_and(|content_type| content_type.contains("json"));
+    message["body"] = match serde_json::from_slice::<Value>(body) {
+        Ok(json) if is_json => json,
+        _ => json!(String::from_utf8_lossy(body)),
+    };
+}
@@ -0,0 +1,216 @@
+//! A small seedable pseudo-random number generator, without pulling in a random number
+//! generation library.
+//!
+//! It is **not** cryptographically secure: it is meant to generate reproducible test data.
+use std::collections::hash_map::RandomState;
+use std::convert::TryFrom;
+use std::hash::{BuildHasher, Hasher};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// A source of random-looking, yet reproducible, data for responders and response templates.
+///
+/// Each [`MockServer`](crate::MockServer) owns one, seeded with
+/// [`MockServerBuilder::random_seed`](crate::MockServerBuilder::random_seed) (or with a random
+/// seed if none was set). It is passed to responders via
+/// [`ResponseContext::random`](crate::ResponseContext::random) and it backs the `{{random ..}}`
+/// variables of [`ResponseTemplate::render_templates`](crate::ResponseTemplate::render_templates).
+///
+/// Clones share the same sequence of values. It is **not** cryptographically secure.
+///
+/// ### Example:
+/// ```rust
+/// use wiremock::Random;
+///
+/// let first = Random::seeded(42);
+/// let second = Random::seeded(42);
+///
+/// assert_eq!(first.uuid(), second.uuid());
+/// assert_eq!(first.int_between(1, 6), second.int_between(1, 6));
+/// ```
+#[derive(Debug, Clone)]
+pub struct Random {
+    rng: Arc<Mutex<Rng>>,
+}
+
+impl Random {
+    /// A source producing the same sequence of values for the same `seed`.
+    pub fn seeded(seed: u64) -> Self {
+        Self {
+            rng: Arc::new(Mutex::new(Rng::seeded(seed))),
+        }
+    }
+
+    /// A source seeded with a random seed.
+    pub(crate) fn unseeded() -> Self {
+        Self::seeded(Rng::random_seed())
+    }
+
+    /// A random `u64`.
+    pub fn u64(&self) -> u64 {
+        self.rng.lock().unwrap().next_u64()
+    }
+
+    /// A random integer between `min` and `max`, both included.
+    ///
+    /// It panics if `min` is greater than `max`.
+    pub fn int_between(&self, min: i64, max: i64) -> i64 {
+        assert!(
+            min <= max,
+            "The lower bound ({}) is greater than the upper bound ({}).",
+            min,
+            max
+        );
+        let span = (i128::from(max) - i128::from(min) + 1) as u128;
+        match u64::try_from(span) {
+            Ok(span) => (i128::from(min) + i128::from(self.rng.lock().unwrap().below(span))) as i64,
+            // The whole range of `i64`.
+            Err(_) => self.u64() as i64,
+        }
+    }
+
+    /// A random version 4 UUID, in its hyphenated form -
+    /// e.g. `67e55044-10b1-426f-9247-bb680e5fe0c8`.
+    pub fn uuid(&self) -> String {
+        let (high, low) = {
+            let mut rng = self.rng.lock().unwrap();
+            (rng.next_u64(), rng.next_u64())
+        };
+        // Set the version (4) and the variant (RFC 4122) bits.
+        let high = (high & !0xF000) | 0x4000;
+        let low = (low & !(0b11 << 62)) | (0b10 << 62);
+        format!(
+            "{:08x}-{:04x}-{:04x}-{:04x}-{:012x}",
+            high >> 32,
+            (high >> 16) & 0xFFFF,
+            high & 0xFFFF,
+            low >> 48,
+            low & 0xFFFF_FFFF_FFFF
+        )
+    }
+
+    /// A random string of `length` ASCII letters and digits.
+    pub fn alphanumeric(&self, length: usize) -> String {
+        const CHARSET: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789";
+        let mut rng = self.rng.lock().unwrap();
+        (0..length)
+            .map(|_| CHARSET[rng.below(CHARSET.len() as u64) as usize] as char)
+            .collect()
+    }
+
+    /// A random string of `length` lowercase hexadecimal digits.
+    pub fn hex(&self, length: usize) -> String {
+        let mut rng = self.rng.lock().unwrap();
+        (0..length)
+            .map(|_| char::from_digit(rng.below(16) as u32, 16).unwrap())
+            .collect()
+    }
+}
+
+/// A [SplitMix64](https://prng.di.unimi.it/splitmix64.c) generator.
+#[derive(Debug, Clone)]
+pub(crate) struct Rng {
+    state: u64,
+}
+
+impl Rng {
+    /// A generator producing the same sequence of values for the same `seed`.
+    pub(crate) fn seeded(seed: u64) -> Self {
+        Self { state: seed }
+    }
+
+    /// A seed that changes every time it is generated.
+    pub(crate) fn random_seed() -> u64 {
+        // `RandomState` is seeded by the OS and perturbed every time it is created.
+        RandomState::new().build_hasher().finish()
+    }
+
+    pub(crate) fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// A value uniformly distributed in `[0, 1)`.
+    pub(crate) fn next_f64(&mut self) -> f64 {
+        // The 53 most significant bits fill the mantissa of an `f64`.
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    /// A value uniformly distributed in `[0, n)`. `n` must not be zero.
+    pub(crate) fn below(&mut self, n: u64) -> u64 {
+        // The bias is negligible for the small ranges we deal with.
+        self.next_u64() % n
+    }
+
+    /// A duration uniformly distributed in `[min, max]`.
+    pub(crate) fn duration_between(&mut self, min: Duration, max: Duration) -> Duration {
+        if max <= min {
+            return min;
+        }
+        let range = (max - min).as_nanos() as f64;
+        min + Duration::from_nanos((range * self.next_f64()).round() as u64)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Random, Rng};
+    use std::time::Duration;
+
+    #[test]
+    fn the_same_seed_yields_the_same_sequence() {
+        let sequence = |seed| {
+            let mut rng = Rng::seeded(seed);
+            (0..10).map(|_| rng.next_u64()).collect::<Vec<_>>()
+        };
+
+        assert_eq!(sequence(42), sequence(42));
+        assert_ne!(sequence(42), sequence(43));
+    }
+
+    #[test]
+    fn uuids_are_well_formed() {
+        let random = Random::seeded(1);
+
+        for _ in 0..100 {
+            let uuid = random.uuid();
+            let groups: Vec<&str> = uuid.split('-').collect();
+            assert_eq!(
+                groups.iter().map(|g| g.len()).collect::<Vec<_>>(),
+                [8, 4, 4, 4, 12]
+            );
+            assert!(groups[2].starts_with('4'));
+            assert!(matches!(
+                groups[3].chars().next(),
+                Some('8' | '9' | 'a' | 'b')
+            ));
+        }
+    }
+
+    #[test]
+    fn integers_cover_the_whole_range() {
+        let random = Random::seeded(3);
+
+        let values: Vec<i64> = (0..1_000).map(|_| random.int_between(-1, 1)).collect();
+        assert!(values.iter().all(|value| (-1..=1).contains(value)));
+        assert!(values.contains(&-1) && values.contains(&0) && values.contains(&1));
+        random.int_between(i64::MIN, i64::MAX);
+    }
+
+    #[test]
+    fn values_stay_within_bounds() {
+        let mut rng = Rng::seeded(7);
+        let (min, max) = (Duration::from_millis(10), Duration::from_millis(20));
+
+        for _ in 0..1_000 {
+            let value = rng.next_f64();
+            assert!((0.0..1.0).contains(&value));
+            assert!(rng.below(3) < 3);
+            let duration = rng.duration_between(min, max);
+            assert!(duration >= min && duration <= max);
+        }
+    }
+}
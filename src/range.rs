@@ -0,0 +1,103 @@
+//! Parsing of the `Range` header, limited to a single range of bytes - e.g. `bytes=0-499`.
+//! See [RFC 9110](https://www.rfc-editor.org/rfc/rfc9110#name-range-requests).
+
+/// A single range of bytes requested by a client.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum ByteRange {
+    /// `bytes=<first>-<last>`, both included.
+    Bounded { first: u64, last: u64 },
+    /// `bytes=<first>-`: from `first` to the end.
+    From { first: u64 },
+    /// `bytes=-<length>`: the last `length` bytes.
+    Suffix { length: u64 },
+}
+
+impl ByteRange {
+    /// Parse the value of a `Range` header.
+    ///
+    /// It returns `None` if the header is invalid, does not use the `bytes` unit or asks for
+    /// more than one range.
+    pub(crate) fn parse(value: &str) -> Option<Self> {
+        let (unit, ranges) = value.trim().split_once('=')?;
+        if !unit.trim().eq_ignore_ascii_case("bytes") || ranges.contains(',') {
+            return None;
+        }
+        let (first, last) = ranges.trim().split_once('-')?;
+        let (first, last) = (first.trim(), last.trim());
+        match (first.is_empty(), last.is_empty()) {
+            (false, false) => {
+                let (first, last) = (first.parse().ok()?, last.parse().ok()?);
+                (first <= last).then_some(ByteRange::Bounded { first, last })
+            }
+            (false, true) => Some(ByteRange::From {
+                first: first.parse().ok()?,
+            }),
+            (true, false) => Some(ByteRange::Suffix {
+                length: last.parse().ok()?,
+            }),
+            (true, true) => None,
+        }
+    }
+
+    /// The offsets of the first and last byte of the range within a representation of
+    /// `length` bytes, or `None` if the range cannot be satisfied.
+    pub(crate) fn resolve(self, length: u64) -> Option<(u64, u64)> {
+        if length == 0 {
+            return None;
+        }
+        match self {
+            ByteRange::Bounded { first, last } if first < length => {
+                Some((first, last.min(length - 1)))
+            }
+            ByteRange::From { first } if first < length => Some((first, length - 1)),
+            ByteRange::Suffix { length: suffix } if suffix > 0 => {
+                Some((length.saturating_sub(suffix), length - 1))
+            }
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ByteRange;
+
+    #[test]
+    fn single_ranges_are_parsed() {
+        assert_eq!(
+            ByteRange::parse("bytes=0-499"),
+            Some(ByteRange::Bounded {
+                first: 0,
+                last: 499
+            })
+        );
+        assert_eq!(
+            ByteRange::parse("bytes=500-"),
+            Some(ByteRange::From { first: 500 })
+        );
+        assert_eq!(
+            ByteRange::parse("bytes=-200"),
+            Some(ByteRange::Suffix { length: 200 })
+        );
+        for invalid in [
+            "bytes=0-1,4-5",
+            "items=0-4",
+            "bytes=5-1",
+            "bytes=-",
+            "bytes=a-b",
+        ] {
+            assert_eq!(ByteRange::parse(invalid), None, "{}", invalid);
+        }
+    }
+
+    #[test]
+    fn ranges_are_clamped_to_the_representation() {
+        let bounded = ByteRange::Bounded { first: 2, last: 99 };
+        assert_eq!(bounded.resolve(10), Some((2, 9)));
+        assert_eq!(bounded.resolve(2), None);
+        assert_eq!(ByteRange::From { first: 9 }.resolve(10), Some((9, 9)));
+        assert_eq!(ByteRange::Suffix { length: 20 }.resolve(10), Some((0, 9)));
+        assert_eq!(ByteRange::Suffix { length: 0 }.resolve(10), None);
+        assert_eq!(ByteRange::From { first: 0 }.resolve(0), None);
+    }
+}
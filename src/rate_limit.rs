@@ -0,0 +1,93 @@
+use crate::ResponseTemplate;
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+/// The rate limit set on a [`Mock`](crate::Mock) via [`Mock::with_rate_limit`](crate::Mock::with_rate_limit).
+#[derive(Debug, Clone)]
+pub(crate) struct RateLimit {
+    /// How many requests can be served within `window`.
+    pub(crate) max_requests: u64,
+    pub(crate) window: Duration,
+    /// The response returned to requests that exceed the budget.
+    pub(crate) throttled_response: ResponseTemplate,
+}
+
+/// Keeps track of the requests served within the current window to enforce a [`RateLimit`].
+///
+/// It uses a sliding window: a request is throttled if `max_requests` requests have already
+/// been served in the `window` preceding its arrival.
+/// Throttled requests do not consume the budget.
+#[derive(Debug, Default)]
+pub(crate) struct RateLimiter {
+    /// When the requests served within the current window arrived, oldest first.
+    served_at: VecDeque<Instant>,
+}
+
+impl RateLimiter {
+    /// Returns `true` if a request arriving at `now` is within the budget, `false` if
+    /// it should be throttled.
+    pub(crate) fn try_acquire(&mut self, limit: &RateLimit, now: Instant) -> bool {
+        while let Some(oldest) = self.served_at.front() {
+            if now.duration_since(*oldest) >= limit.window {
+                self.served_at.pop_front();
+            } else {
+                break;
+            }
+        }
+        if (self.served_at.len() as u64) < limit.max_requests {
+            self.served_at.push_back(now);
+            true
+        } else {
+            false
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::rate_limit::{RateLimit, RateLimiter};
+    use crate::ResponseTemplate;
+    use std::time::{Duration, Instant};
+
+    fn limit(max_requests: u64, window: Duration) -> RateLimit {
+        RateLimit {
+            max_requests,
+            window,
+            throttled_response: ResponseTemplate::new(429),
+        }
+    }
+
+    #[test]
+    fn requests_beyond_the_budget_are_throttled() {
+        let limit = limit(2, Duration::from_secs(1));
+        let mut limiter = RateLimiter::default();
+        let now = Instant::now();
+
+        assert!(limiter.try_acquire(&limit, now));
+        assert!(limiter.try_acquire(&limit, now));
+        assert!(!limiter.try_acquire(&limit, now));
+    }
+
+    #[test]
+    fn budget_is_replenished_once_the_window_has_elapsed() {
+        let limit = limit(1, Duration::from_secs(1));
+        let mut limiter = RateLimiter::default();
+        let now = Instant::now();
+
+        assert!(limiter.try_acquire(&limit, now));
+        assert!(!limiter.try_acquire(&limit, now + Duration::from_millis(999)));
+        assert!(limiter.try_acquire(&limit, now + Duration::from_secs(1)));
+    }
+
+    #[test]
+    fn throttled_requests_do_not_consume_the_budget() {
+        let limit = limit(1, Duration::from_secs(1));
+        let mut limiter = RateLimiter::default();
+        let now = Instant::now();
+
+        assert!(limiter.try_acquire(&limit, now));
+        // Throttled, it must not push the window forward.
+        assert!(!limiter.try_acquire(&limit, now + Duration::from_millis(500)));
+        assert!(limiter.try_acquire(&limit, now + Duration::from_millis(1000)));
+    }
+}
@@ -0,0 +1,173 @@
+use crate::matchers::path;
+use crate::{Mock, MockServer, Respond, ResponseTemplate};
+use http::StatusCode;
+use std::convert::TryInto;
+
+/// A scripted sequence of redirects, mounted on a [`MockServer`] as a set of [`Mock`]s.
+///
+/// Each path in the chain responds with a redirect whose `Location` header points to the
+/// next path in the chain, on the same [`MockServer`]. The last path in the chain either
+/// returns your final response ([`RedirectChain::mount`]) or redirects back to the first path,
+/// creating a redirect loop ([`RedirectChain::mount_as_loop`]).
+///
+/// Use [`MockServer::mount_redirect_chain`] for the common case (`302 Found` redirects
+/// terminating with a final response).
+///
+/// Each path should appear at most once in the chain - loops are handled by
+/// [`RedirectChain::mount_as_loop`].
+///
+/// ### Example:
+/// ```rust
+/// use wiremock::{MockServer, RedirectChain, ResponseTemplate};
+///
+/// #[tokio::main]
+/// async fn main() {
+///     // Arrange
+///     let mock_server = MockServer::start().await;
+///     RedirectChain::new(["/a", "/b", "/final"])
+///         .with_status(307)
+///         .mount(&mock_server, ResponseTemplate::new(200).set_body_string("done"))
+///         .await;
+///
+///     // Act
+///     let response = reqwest::get(format!("{}/a", mock_server.uri())).await.unwrap();
+///
+///     // Assert
+///     assert_eq!(response.url().path(), "/final");
+///     assert_eq!(response.text().await.unwrap(), "done");
+/// }
+/// ```
+///
+/// ### Example (max redirects):
+///
+/// ```rust
+/// use wiremock::{MockServer, RedirectChain, ResponseTemplate};
+///
+/// #[tokio::main]
+/// async fn main() {
+///     // Arrange
+///     let mock_server = MockServer::start().await;
+///     let paths: Vec<String> = (0..5).map(|i| format!("/hop/{}", i)).collect();
+///     RedirectChain::new(paths)
+///         .mount(&mock_server, ResponseTemplate::new(200))
+///         .await;
+///     let client = reqwest::Client::builder()
+///         .redirect(reqwest::redirect::Policy::limited(3))
+///         .build()
+///         .unwrap();
+///
+///     // Act
+///     let outcome = client.get(format!("{}/hop/0", mock_server.uri())).send().await;
+///
+///     // Assert - four redirects are too many for our client
+///     assert!(outcome.unwrap_err().is_redirect());
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct RedirectChain {
+    paths: Vec<String>,
+    status: StatusCode,
+}
+
+impl RedirectChain {
+    /// Start building a redirect chain going through the specified `paths`, in order.
+    ///
+    /// Redirects use `302 Found` by default - see [`RedirectChain::with_status`] to change it.
+    ///
+    /// It panics if `paths` is empty.
+    pub fn new<I, S>(paths: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        let paths: Vec<String> = paths.into_iter().map(Into::into).collect();
+        assert!(
+            !paths.is_empty(),
+            "A redirect chain must contain at least one path!"
+        );
+        Self {
+            paths,
+            status: StatusCode::FOUND,
+        }
+    }
+
+    /// Set the status code used by all the redirects in the chain (e.g. `301`, `302`, `307` or `308`).
+    ///
+    /// It panics if the status code is not a redirection (`3xx`).
+    pub fn with_status<S>(mut self, status: S) -> Self
+    where
+        S: TryInto<StatusCode>,
+        <S as TryInto<StatusCode>>::Error: std::fmt::Debug,
+    {
+        let status = status
+            .try_into()
+            .expect("Failed to convert into status code.");
+        assert!(
+            status.is_redirection(),
+            "The status code of a redirect must be in the 3xx range, got {}",
+            status
+        );
+        self.status = status;
+        self
+    }
+
+    /// Mount the chain on `server`: every path redirects to the next one, while the last path
+    /// responds using `final_response`.
+    pub async fn mount<R: Respond + 'static>(self, server: &MockServer, final_response: R) {
+        let (last, hops) = self
+            .paths
+            .split_last()
+            .expect("A redirect chain is never empty");
+        for (from, to) in hops.iter().zip(self.paths.iter().skip(1)) {
+            self.redirect(server, from, to).mount(server).await;
+        }
+        Mock::given(path(last.as_str()))
+            .respond_with(final_response)
+            .named(format!("Redirect chain - {}", last))
+            .mount(server)
+            .await;
+    }
+
+    /// Mount the chain on `server` as a loop: every path redirects to the next one, while
+    /// the last path redirects back to the first one.
+    ///
+    /// ### Example:
+    /// ```rust
+    /// use wiremock::{MockServer, RedirectChain};
+    ///
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     // Arrange
+    ///     let mock_server = MockServer::start().await;
+    ///     RedirectChain::new(["/ping", "/pong"])
+    ///         .mount_as_loop(&mock_server)
+    ///         .await;
+    ///
+    ///     // Act
+    ///     let outcome = reqwest::get(format!("{}/ping", mock_server.uri())).await;
+    ///
+    ///     // Assert - the client detects the loop and bails out
+    ///     assert!(outcome.unwrap_err().is_redirect());
+    /// }
+    /// ```
+    pub async fn mount_as_loop(self, server: &MockServer) {
+        let targets = self.paths.iter().skip(1).chain(self.paths.first());
+        for (from, to) in self.paths.iter().zip(targets) {
+            self.redirect(server, from, to).mount(server).await;
+        }
+    }
+
+    /// Build the [`Mock`] redirecting requests on `from` to `to`.
+    fn redirect(&self, server: &MockServer, from: &str, to: &str) -> Mock {
+        let to = if to.starts_with('/') {
+            to.to_string()
+        } else {
+            format!("/{}", to)
+        };
+        let template = ResponseTemplate::new(self.status)
+            .insert_header(http::header::LOCATION, format!("{}{}", server.uri(), to));
+        Mock::given(path(from))
+            .respond_with(template)
+            .named(format!("Redirect chain - {} -> {}", from, to))
+    }
+}
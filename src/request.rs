@@ -1,8 +1,17 @@
 use std::fmt;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
 
-use http::{HeaderMap, Method};
+use crate::tls::TlsInfo;
+use bytes::{Bytes, BytesMut};
+use http::header::{Entry, HeaderName};
+use http::{HeaderMap, HeaderValue, Method};
 use http_body_util::BodyExt;
 use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::Value;
+use std::convert::TryInto;
 use url::Url;
 
 pub const BODY_PRINT_LIMIT: usize = 10_000;
@@ -10,11 +19,25 @@ pub const BODY_PRINT_LIMIT: usize = 10_000;
 /// Specifies limitations on printing request bodies when logging requests. For some mock servers
 /// the bodies may be too large to reasonably print and it may be desirable to limit them.
 #[derive(Debug, Copy, Clone)]
+#[non_exhaustive]
 pub enum BodyPrintLimit {
     /// Maximum length of a body to print in bytes.
     Limited(usize),
     /// There is no limit to the size of a body that may be printed.
     Unlimited,
+    /// Pretty-print JSON bodies. Objects and arrays nested more than `max_depth` levels deep
+    /// are replaced by a `"{...}"` or `"[...]"` placeholder.
+    /// The pretty-printed output is truncated after [`BODY_PRINT_LIMIT`] bytes.
+    ///
+    /// Bodies that are not valid JSON are printed as with [`BodyPrintLimit::Limited`], using
+    /// the default limit.
+    PrettyJson {
+        /// How many levels of nested objects and arrays are printed.
+        max_depth: usize,
+    },
+    /// Print at most the given number of bytes, showing binary (i.e. invalid utf-8) bodies
+    /// as a hex dump rather than omitting them.
+    HexDump(usize),
 }
 
 /// An incoming request to an instance of [`MockServer`].
@@ -40,44 +63,213 @@ pub struct Request {
     pub url: Url,
     pub method: Method,
     pub headers: HeaderMap,
-    pub body: Vec<u8>,
+    /// Cloning it is cheap: the journal and the mocks that matched the request share the
+    /// same buffer.
+    pub body: Bytes,
+    /// Set if the body could not be read in full - e.g. the client aborted mid-upload.
+    body_error: Option<String>,
+    /// Sent after a chunked (HTTP/1.1) or HTTP/2 body.
+    trailers: HeaderMap,
+    /// Set if the request was received over TLS.
+    tls: Option<TlsInfo>,
+    /// The connection the request was received on, unless it was built by hand.
+    connection_id: Option<u64>,
+    /// When the request was received, according to the server clock.
+    received_at: Option<Duration>,
 }
 
 impl Request {
+    /// Start building a `Request` by hand - e.g. to unit-test a custom
+    /// [`Match`](crate::Match) implementation without starting a
+    /// [`MockServer`](crate::MockServer).
+    ///
+    /// It defaults to a `GET` request to `http://localhost/`, without headers nor body.
+    ///
+    /// ### Example:
+    /// ```rust
+    /// use wiremock::{Match, Request};
+    /// use wiremock::matchers::{body_json, header, method, path};
+    /// use serde_json::json;
+    ///
+    /// let request = Request::builder()
+    ///     .method("POST")
+    ///     .path("/emails")
+    ///     .header("x-api-key", "secret")
+    ///     .body_json(json!({ "to": "jane@example.com" }))
+    ///     .build();
+    ///
+    /// assert!(method("POST").matches(&request));
+    /// assert!(path("/emails").matches(&request));
+    /// assert!(header("content-type", "application/json").matches(&request));
+    /// assert!(body_json(json!({ "to": "jane@example.com" })).matches(&request));
+    /// ```
+    pub fn builder() -> RequestBuilder {
+        RequestBuilder::new()
+    }
+
     pub fn body_json<T: DeserializeOwned>(&self) -> Result<T, serde_json::Error> {
         serde_json::from_slice(&self.body)
     }
 
-    pub(crate) async fn from_hyper(request: hyper::Request<hyper::body::Incoming>) -> Request {
-        let (parts, body) = request.into_parts();
-        let url = match parts.uri.authority() {
-            Some(_) => parts.uri.to_string(),
-            None => format!("http://localhost{}", parts.uri),
+    /// The error encountered while reading the body of the request, if any - e.g. because
+    /// the client aborted the upload or closed the connection before sending the whole body.
+    ///
+    /// [`body`](#structfield.body) holds the part of the body that was received before
+    /// the error.
+    pub fn body_error(&self) -> Option<&str> {
+        self.body_error.as_deref()
+    }
+
+    /// The trailers sent by the client after the body - e.g. a checksum computed while
+    /// streaming it.
+    ///
+    /// Trailers can only be sent with a chunked body over HTTP/1.1, or over HTTP/2: the map is
+    /// empty for all other requests.
+    pub fn trailers(&self) -> &HeaderMap {
+        &self.trailers
+    }
+
+    /// The protocol version and the cipher suite negotiated with the client, if the request
+    /// was received over TLS - see [`MockServerBuilder::tls`](crate::MockServerBuilder::tls).
+    pub fn tls(&self) -> Option<&TlsInfo> {
+        self.tls.as_ref()
+    }
+
+    /// An identifier of the connection the request was received on - e.g. to check that a
+    /// client reuses its keep-alive connections.
+    ///
+    /// Identifiers are unique across all the [`MockServer`](crate::MockServer)s of the
+    /// process. Requests sent over HTTP/2 share the identifier of their connection.
+    /// It is `None` for requests built by hand.
+    pub fn connection_id(&self) -> Option<u64> {
+        self.connection_id
+    }
+
+    /// When the request was received, measured by the clock of the
+    /// [`MockServer`](crate::MockServer) since it was handed out by
+    /// [`MockServer::start`](crate::MockServer::start), even if it was recycled from the pool of
+    /// servers - or, if the server is driven by a [`VirtualClock`](crate::VirtualClock), since
+    /// the clock was created.
+    ///
+    /// It is `None` for requests built by hand - see [`received_within`](crate::matchers::received_within).
+    pub fn received_at(&self) -> Option<Duration> {
+        self.received_at
+    }
+
+    /// The host the request is addressed to, without the port.
+    ///
+    /// It is taken from the request target if it is in absolute form - e.g. when the client
+    /// uses the mock server as a forward proxy - and from the `Host` header otherwise.
+    pub(crate) fn host(&self) -> Option<String> {
+        // Requests in origin form are assigned a `localhost` URL - see `Request::from_hyper`.
+        let url_host = self.url.host_str().filter(|host| *host != "localhost");
+        if let Some(host) = url_host {
+            return Some(host.to_string());
+        }
+        match self.headers.get(http::header::HOST) {
+            Some(value) => {
+                let authority: http::uri::Authority = value.to_str().ok()?.parse().ok()?;
+                Some(authority.host().to_string())
+            }
+            None => self.url.host_str().map(ToString::to_string),
+        }
+    }
+
+    /// Convert a request received by the server, reading its body in memory.
+    ///
+    /// If the body is longer than `max_body_size`, only its first `max_body_size` bytes are
+    /// read and the returned flag is set.
+    /// `received_at` is the time the request was received at, according to the server clock.
+    pub(crate) async fn from_hyper(
+        request: hyper::Request<hyper::body::Incoming>,
+        max_body_size: Option<usize>,
+        received_at: Duration,
+    ) -> (Request, bool) {
+        let mut head = Self::from_head(&request, received_at);
+        let mut body = request.into_body();
+        let mut bytes = BytesMut::new();
+        let mut truncated = false;
+        let mut body_error = None;
+        let mut trailers = HeaderMap::new();
+        while let Some(frame) = body.frame().await {
+            let frame = match frame {
+                Ok(frame) => frame,
+                Err(e) => {
+                    tracing::debug!("Failed to read the request body: {}", e);
+                    body_error = Some(e.to_string());
+                    break;
+                }
+            };
+            let data = match frame.into_data() {
+                Ok(data) => data,
+                // Trailers are not part of the body.
+                Err(frame) => {
+                    if let Ok(frame_trailers) = frame.into_trailers() {
+                        trailers.extend(frame_trailers);
+                    }
+                    continue;
+                }
+            };
+            if let Some(max_body_size) = max_body_size {
+                if bytes.len() + data.len() > max_body_size {
+                    bytes.extend_from_slice(&data[..max_body_size - bytes.len()]);
+                    truncated = true;
+                    break;
+                }
+            }
+            bytes.extend_from_slice(&data);
         }
-        .parse()
-        .unwrap();
 
-        let body = body
-            .collect()
-            .await
-            .expect("Failed to read request body.")
-            .to_bytes();
+        head.body = bytes.freeze();
+        head.body_error = body_error;
+        head.trailers = trailers;
+        (head, truncated)
+    }
 
+    /// A request with the head of `request` and an empty body - e.g. to decide what to do
+    /// with the body before reading it.
+    pub(crate) fn from_head(
+        request: &hyper::Request<hyper::body::Incoming>,
+        received_at: Duration,
+    ) -> Request {
+        let tls = request.extensions().get::<TlsInfo>().cloned();
+        let connection_id = request.extensions().get::<ConnectionId>().map(|id| id.0);
+        let uri = request.uri();
+        let url = match uri.authority() {
+            Some(_) => uri.to_string(),
+            None if tls.is_some() => format!("https://localhost{}", uri),
+            None => format!("http://localhost{}", uri),
+        }
+        .parse()
+        .unwrap();
         Self {
             url,
-            method: parts.method,
-            headers: parts.headers,
-            body: body.to_vec(),
+            method: request.method().clone(),
+            headers: request.headers().clone(),
+            body: Bytes::new(),
+            body_error: None,
+            trailers: HeaderMap::new(),
+            tls,
+            connection_id,
+            received_at: Some(received_at),
         }
     }
 
+    /// Print the request for diagnostic purposes - e.g. in panic messages.
+    ///
+    /// The values of the headers listed in `redacted_headers` are replaced by a placeholder.
     pub(crate) fn print_with_limit(
         &self,
         mut buffer: impl fmt::Write,
         body_print_limit: BodyPrintLimit,
+        redacted_headers: &[HeaderName],
     ) -> fmt::Result {
         writeln!(buffer, "{} {}", self.method, self.url)?;
         for name in self.headers.keys() {
+            if redacted_headers.contains(name) {
+                writeln!(buffer, "{}: {}", name, REDACTED)?;
+                continue;
+            }
             let values = self
                 .headers
                 .get_all(name)
@@ -87,8 +279,70 @@ impl Request {
             let values = values.join(",");
             writeln!(buffer, "{}: {}", name, values)?;
         }
+        if let Some(body_error) = &self.body_error {
+            writeln!(buffer, "The body could not be read in full: {}", body_error)?;
+        }
+
+        self.print_body(&mut buffer, body_print_limit)?;
+        if !self.trailers.is_empty() {
+            writeln!(buffer, "Trailers:")?;
+            for (name, value) in &self.trailers {
+                let value = if redacted_headers.contains(name) {
+                    REDACTED.into()
+                } else {
+                    String::from_utf8_lossy(value.as_bytes())
+                };
+                writeln!(buffer, "{}: {}", name, value)?;
+            }
+        }
+        Ok(())
+    }
 
+    fn print_body(
+        &self,
+        mut buffer: impl fmt::Write,
+        body_print_limit: BodyPrintLimit,
+    ) -> fmt::Result {
         match body_print_limit {
+            BodyPrintLimit::PrettyJson { max_depth } => {
+                match serde_json::from_slice::<Value>(&self.body) {
+                    Ok(json) => {
+                        let json = truncate_json(json, max_depth);
+                        let json = serde_json::to_string_pretty(&json)
+                            .expect("Serializing a JSON value cannot fail");
+                        if json.len() <= BODY_PRINT_LIMIT {
+                            return writeln!(buffer, "{}", json);
+                        }
+                        let end = (0..=BODY_PRINT_LIMIT)
+                            .rev()
+                            .find(|&end| json.is_char_boundary(end))
+                            .unwrap_or(0);
+                        writeln!(buffer, "{}", &json[..end])?;
+                        writeln!(
+                            buffer,
+                            "We truncated the body because it was too large: {} bytes once pretty-printed (limit: {} bytes)",
+                            json.len(),
+                            BODY_PRINT_LIMIT
+                        )
+                    }
+                    Err(_) => self.print_body(buffer, BodyPrintLimit::Limited(BODY_PRINT_LIMIT)),
+                }
+            }
+            BodyPrintLimit::HexDump(limit) => {
+                if std::str::from_utf8(&self.body).is_ok() {
+                    return self.print_body(buffer, BodyPrintLimit::Limited(limit));
+                }
+                write_hex_dump(&mut buffer, &self.body[..self.body.len().min(limit)])?;
+                if self.body.len() > limit {
+                    writeln!(
+                        buffer,
+                        "We truncated the body because it was too large: {} bytes (limit: {} bytes)",
+                        self.body.len(),
+                        limit
+                    )?;
+                }
+                Ok(())
+            }
             BodyPrintLimit::Limited(limit) if self.body.len() > limit => {
                 let mut written = false;
                 for end_byte in limit..(limit + 4).max(self.body.len()) {
@@ -131,3 +385,392 @@ impl Request {
         }
     }
 }
+
+/// A builder to assemble a [`Request`] step-by-step, outside of a [`MockServer`].
+/// Use [`Request::builder`] to get started.
+///
+/// All methods panic if their input cannot be converted - e.g. an invalid header name.
+///
+/// [`MockServer`]: crate::MockServer
+#[derive(Debug, Clone)]
+pub struct RequestBuilder {
+    request: Request,
+}
+
+impl RequestBuilder {
+    fn new() -> Self {
+        Self {
+            request: Request {
+                url: "http://localhost/".parse().unwrap(),
+                method: Method::GET,
+                headers: HeaderMap::new(),
+                body: Bytes::new(),
+                body_error: None,
+                trailers: HeaderMap::new(),
+                tls: None,
+                connection_id: None,
+                received_at: None,
+            },
+        }
+    }
+
+    /// Set the method of the request.
+    pub fn method<T>(mut self, method: T) -> Self
+    where
+        T: TryInto<Method>,
+        <T as TryInto<Method>>::Error: std::fmt::Debug,
+    {
+        self.request.method = method
+            .try_into()
+            .expect("Failed to convert into HTTP method.");
+        self
+    }
+
+    /// Set the full URL of the request - e.g. `https://example.com/emails?page=2`.
+    pub fn url<T: AsRef<str>>(mut self, url: T) -> Self {
+        let url = url.as_ref();
+        self.request.url = url
+            .parse()
+            .unwrap_or_else(|e| panic!("`{}` is not a valid URL: {}", url, e));
+        self
+    }
+
+    /// Set the path of the URL of the request, leaving the rest of the URL untouched.
+    pub fn path<T: AsRef<str>>(mut self, path: T) -> Self {
+        self.request.url.set_path(path.as_ref());
+        self
+    }
+
+    /// Append a parameter to the query string of the URL of the request.
+    pub fn query_param<K: AsRef<str>, V: AsRef<str>>(mut self, key: K, value: V) -> Self {
+        self.request
+            .url
+            .query_pairs_mut()
+            .append_pair(key.as_ref(), value.as_ref());
+        self
+    }
+
+    /// Append a header to the request. Existing values of the same header are kept.
+    pub fn header<K, V>(mut self, key: K, value: V) -> Self
+    where
+        K: TryInto<HeaderName>,
+        <K as TryInto<HeaderName>>::Error: std::fmt::Debug,
+        V: TryInto<HeaderValue>,
+        <V as TryInto<HeaderValue>>::Error: std::fmt::Debug,
+    {
+        let key = key.try_into().expect("Failed to convert into header name.");
+        let value = value
+            .try_into()
+            .expect("Failed to convert into header value.");
+        self.request.headers.append(key, value);
+        self
+    }
+
+    /// Append a trailer, sent after the body.
+    pub fn trailer<K, V>(mut self, key: K, value: V) -> Self
+    where
+        K: TryInto<HeaderName>,
+        <K as TryInto<HeaderName>>::Error: std::fmt::Debug,
+        V: TryInto<HeaderValue>,
+        <V as TryInto<HeaderValue>>::Error: std::fmt::Debug,
+    {
+        let key = key
+            .try_into()
+            .expect("Failed to convert into trailer name.");
+        let value = value
+            .try_into()
+            .expect("Failed to convert into trailer value.");
+        self.request.trailers.append(key, value);
+        self
+    }
+
+    /// Mark the request as received over TLS, with the parameters negotiated in `tls`.
+    pub fn tls(mut self, tls: TlsInfo) -> Self {
+        self.request.tls = Some(tls);
+        self
+    }
+
+    /// Mark the request as received on the connection identified by `connection_id` - see
+    /// [`Request::connection_id`].
+    pub fn connection_id(mut self, connection_id: u64) -> Self {
+        self.request.connection_id = Some(connection_id);
+        self
+    }
+
+    /// Mark the request as received at `received_at`, according to the server clock - see
+    /// [`Request::received_at`].
+    pub fn received_at(mut self, received_at: Duration) -> Self {
+        self.request.received_at = Some(received_at);
+        self
+    }
+
+    /// Set the body of the request - e.g. a `Vec<u8>`, a `String`, a `&'static str` or an
+    /// existing [`Bytes`] buffer, without copying it.
+    pub fn body<B: Into<Bytes>>(mut self, body: B) -> Self {
+        self.request.body = body.into();
+        self
+    }
+
+    /// Set the body of the request to the JSON serialization of `body`.
+    ///
+    /// It sets the `Content-Type` header to `application/json`, unless it was already set.
+    pub fn body_json<B: Serialize>(mut self, body: B) -> Self {
+        self.request.body = serde_json::to_vec(&body)
+            .expect("Failed to serialize the body.")
+            .into();
+        self.request
+            .headers
+            .entry(http::header::CONTENT_TYPE)
+            .or_insert(HeaderValue::from_static("application/json"));
+        self
+    }
+
+    /// Finalise the builder and get the [`Request`].
+    pub fn build(self) -> Request {
+        self.request
+    }
+}
+
+/// Convert an [`http::Request`] into a [`Request`] - e.g. to check that the requests your
+/// client builds satisfy the matchers of your mocks.
+///
+/// Requests with a relative URI are assigned a `http://localhost` base URL, like the requests
+/// received by a [`MockServer`](crate::MockServer).
+impl From<http::Request<Vec<u8>>> for Request {
+    fn from(request: http::Request<Vec<u8>>) -> Self {
+        let (parts, body) = request.into_parts();
+        let url = match parts.uri.authority() {
+            Some(_) => parts.uri.to_string(),
+            None => format!("http://localhost{}", parts.uri),
+        };
+        Self {
+            url: url
+                .parse()
+                .unwrap_or_else(|e| panic!("`{}` is not a valid URL: {}", url, e)),
+            method: parts.method,
+            headers: parts.headers,
+            body: body.into(),
+            body_error: None,
+            trailers: HeaderMap::new(),
+            tls: None,
+            connection_id: None,
+            received_at: None,
+        }
+    }
+}
+
+/// The identifier of the connection a request was received on, inserted in the extensions of
+/// the request by the server.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct ConnectionId(u64);
+
+impl ConnectionId {
+    /// A new identifier, unique across all the mock servers of the process.
+    pub(crate) fn next() -> Self {
+        static NEXT: AtomicU64 = AtomicU64::new(1);
+        Self(NEXT.fetch_add(1, Ordering::Relaxed))
+    }
+}
+
+/// The placeholder used instead of the value of a redacted header.
+const REDACTED: &str = "<redacted>";
+
+/// A function computing the body to record for a request - see
+/// [`MockServerBuilder::redact_body`](crate::MockServerBuilder::redact_body).
+pub(crate) type BodyRedactor = dyn Fn(&Request) -> Vec<u8> + Send + Sync;
+
+/// Sanitizes requests before they are recorded by a mock server, so that secrets do not end up
+/// in panic messages or exported files.
+#[derive(Clone, Default)]
+pub(crate) struct Redaction {
+    /// The headers whose value is replaced by a placeholder.
+    pub(crate) headers: Vec<HeaderName>,
+    /// Computes the body to record, if set.
+    pub(crate) body: Option<Arc<BodyRedactor>>,
+}
+
+impl Redaction {
+    /// Return a copy of `request` with the configured redactions applied.
+    pub(crate) fn apply(&self, request: &Request) -> Request {
+        let mut redacted = request.clone();
+        for name in &self.headers {
+            if let Entry::Occupied(mut entry) = redacted.headers.entry(name) {
+                entry.insert(HeaderValue::from_static(REDACTED));
+            }
+            if let Entry::Occupied(mut entry) = redacted.trailers.entry(name) {
+                entry.insert(HeaderValue::from_static(REDACTED));
+            }
+        }
+        if let Some(redact_body) = &self.body {
+            redacted.body = redact_body(request).into();
+        }
+        redacted
+    }
+}
+
+/// Replace the objects and arrays nested more than `max_depth` levels deep with a placeholder.
+fn truncate_json(value: Value, max_depth: usize) -> Value {
+    match value {
+        Value::Object(map) if max_depth == 0 && !map.is_empty() => Value::from("{...}"),
+        Value::Array(items) if max_depth == 0 && !items.is_empty() => Value::from("[...]"),
+        Value::Object(map) => Value::Object(
+            map.into_iter()
+                .map(|(key, value)| (key, truncate_json(value, max_depth.saturating_sub(1))))
+                .collect(),
+        ),
+        Value::Array(items) => Value::Array(
+            items
+                .into_iter()
+                .map(|value| truncate_json(value, max_depth.saturating_sub(1)))
+                .collect(),
+        ),
+        value => value,
+    }
+}
+
+/// Print `bytes` in the canonical hex dump format: offset, 16 bytes in hexadecimal and
+/// their printable ASCII characters.
+fn write_hex_dump(mut buffer: impl fmt::Write, bytes: &[u8]) -> fmt::Result {
+    for (i, chunk) in bytes.chunks(16).enumerate() {
+        write!(buffer, "{:08x} ", i * 16)?;
+        for byte in chunk {
+            write!(buffer, " {:02x}", byte)?;
+        }
+        let padding = 3 * (16 - chunk.len());
+        let ascii: String = chunk
+            .iter()
+            .map(|&b| {
+                if b.is_ascii_graphic() || b == b' ' {
+                    b as char
+                } else {
+                    '.'
+                }
+            })
+            .collect();
+        writeln!(buffer, "{:padding$}  |{}|", "", ascii, padding = padding)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{BodyPrintLimit, Redaction, Request, BODY_PRINT_LIMIT};
+    use http::header::{HeaderName, AUTHORIZATION};
+    use http::{HeaderMap, HeaderValue, Method};
+    use std::sync::Arc;
+
+    fn request(body: &[u8]) -> Request {
+        let mut headers = HeaderMap::new();
+        headers.insert(AUTHORIZATION, HeaderValue::from_static("Bearer secret"));
+        Request {
+            url: "http://localhost/".parse().unwrap(),
+            method: Method::POST,
+            headers,
+            body: body.to_vec().into(),
+            body_error: None,
+            trailers: HeaderMap::new(),
+            tls: None,
+            connection_id: None,
+            received_at: None,
+        }
+    }
+
+    #[test]
+    fn http_requests_with_a_relative_uri_are_assigned_a_localhost_url() {
+        let request = http::Request::post("/emails?page=2")
+            .header("x-api-key", "secret")
+            .body(b"hello".to_vec())
+            .unwrap();
+
+        let request = Request::from(request);
+
+        assert_eq!(request.url.as_str(), "http://localhost/emails?page=2");
+        assert_eq!(request.method, Method::POST);
+        assert_eq!(request.headers["x-api-key"], "secret");
+        assert_eq!(request.body.as_ref(), b"hello");
+        assert_eq!(
+            Request::builder()
+                .url("http://localhost/emails")
+                .query_param("page", "2")
+                .build()
+                .url,
+            request.url
+        );
+    }
+
+    fn print(request: &Request, limit: BodyPrintLimit, redacted: &[HeaderName]) -> String {
+        let mut output = String::new();
+        request
+            .print_with_limit(&mut output, limit, redacted)
+            .unwrap();
+        output
+    }
+
+    #[test]
+    fn redacted_headers_are_not_printed() {
+        let request = request(b"");
+
+        let output = print(&request, BodyPrintLimit::Unlimited, &[AUTHORIZATION]);
+
+        assert!(output.contains("authorization: <redacted>\n"));
+        assert!(!output.contains("secret"));
+    }
+
+    #[test]
+    fn redaction_hides_headers_and_rewrites_the_body() {
+        let request = request(b"password=hunter2");
+        let redaction = Redaction {
+            headers: vec![AUTHORIZATION],
+            body: Some(Arc::new(|_: &Request| b"password=***".to_vec())),
+        };
+
+        let redacted = redaction.apply(&request);
+
+        assert_eq!(redacted.headers[AUTHORIZATION], "<redacted>");
+        assert_eq!(redacted.body.as_ref(), b"password=***");
+    }
+
+    #[test]
+    fn json_bodies_are_pretty_printed_up_to_max_depth() {
+        let request = request(br#"{"a":{"b":{"c":1}},"d":[]}"#);
+
+        let output = print(&request, BodyPrintLimit::PrettyJson { max_depth: 2 }, &[]);
+
+        assert!(output.ends_with("{\n  \"a\": {\n    \"b\": \"{...}\"\n  },\n  \"d\": []\n}\n"));
+    }
+
+    #[test]
+    fn bodies_set_from_bytes_are_not_copied() {
+        let body = bytes::Bytes::from(b"hello".to_vec());
+
+        let request = Request::builder().body(body.clone()).build();
+
+        assert_eq!(request.body.as_ptr(), body.as_ptr());
+    }
+
+    #[test]
+    fn pretty_printed_json_bodies_are_capped() {
+        let body = serde_json::to_vec(&vec!["wiremock"; BODY_PRINT_LIMIT]).unwrap();
+        let request = request(&body);
+
+        let output = print(&request, BodyPrintLimit::PrettyJson { max_depth: 2 }, &[]);
+
+        assert!(output.len() < 2 * BODY_PRINT_LIMIT);
+        assert!(output.contains("too large: 140002 bytes once pretty-printed (limit: 10000 bytes)"));
+    }
+
+    #[test]
+    fn binary_bodies_are_printed_as_a_hex_dump() {
+        let mut body = b"hello".to_vec();
+        body.extend([0xff; 14]);
+        let request = request(&body);
+
+        let output = print(&request, BodyPrintLimit::HexDump(18), &[]);
+
+        assert!(output.contains(
+            "00000000  68 65 6c 6c 6f ff ff ff ff ff ff ff ff ff ff ff  |hello...........|\n\
+             00000010  ff ff                                            |..|\n"
+        ));
+        assert!(output.contains("too large: 19 bytes (limit: 18 bytes)"));
+    }
+}
@@ -1,4 +1,10 @@
-use crate::{Request, ResponseTemplate};
+use crate::session::Sessions;
+use crate::{Random, Request, ResponseTemplate};
+use async_trait::async_trait;
+use futures::future::BoxFuture;
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::{Arc, Mutex};
 
 /// Anything that implements `Respond` can be used to reply to an incoming request when a
 /// [`Mock`] is activated.
@@ -56,7 +62,7 @@ use crate::{Request, ResponseTemplate};
 ///
 ///     Mock::given(path("/echo"))
 ///         .respond_with(|req: &Request| {
-///             let body_string = String::from_utf8(req.body.clone()).unwrap();
+///             let body_string = String::from_utf8(req.body.to_vec()).unwrap();
 ///             ResponseTemplate::new(200).set_body_string(body_string)
 ///         })
 ///         .mount(&mock_server)
@@ -119,6 +125,54 @@ use crate::{Request, ResponseTemplate};
 /// }
 /// ```
 ///
+/// ## Stateful responses
+///
+/// Implement [`Respond::respond_with_ctx`] instead of [`Respond::respond`] if the response
+/// depends on how many times the [`Mock`] matched before - e.g. to fail the first call and
+/// succeed on retries. The [`ResponseContext`] passed alongside the request tells you which
+/// invocation you are handling, no external counter required:
+///
+/// ```rust
+/// use wiremock::{MockServer, Mock, Request, ResponseContext, ResponseTemplate, Respond};
+/// use wiremock::matchers::path;
+///
+/// /// Fails the first request with a `503`, succeeds afterwards.
+/// struct FailOnce;
+///
+/// impl Respond for FailOnce {
+///     fn respond(&self, _request: &Request) -> ResponseTemplate {
+///         ResponseTemplate::new(200)
+///     }
+///
+///     fn respond_with_ctx(&self, request: &Request, ctx: &ResponseContext) -> ResponseTemplate {
+///         if ctx.n_previous_matches == 0 {
+///             ResponseTemplate::new(503)
+///         } else {
+///             self.respond(request)
+///         }
+///     }
+/// }
+///
+/// #[async_std::main]
+/// async fn main() {
+///     // Arrange
+///     let mock_server = MockServer::start().await;
+///
+///     Mock::given(path("/flaky"))
+///         .respond_with(FailOnce)
+///         .mount(&mock_server)
+///         .await;
+///
+///     // Act
+///     let first = surf::get(format!("{}/flaky", &mock_server.uri())).await.unwrap();
+///     let second = surf::get(format!("{}/flaky", &mock_server.uri())).await.unwrap();
+///
+///     // Assert
+///     assert_eq!(first.status(), 503);
+///     assert_eq!(second.status(), 200);
+/// }
+/// ```
+///
 /// [`Mock`]: crate::Mock
 /// [`ResponseTemplate`]: crate::ResponseTemplate
 pub trait Respond: Send + Sync {
@@ -129,6 +183,129 @@ pub trait Respond: Send + Sync {
     /// [`MockServer`]: crate::MockServer
     /// [`ResponseTemplate`]: crate::ResponseTemplate
     fn respond(&self, request: &Request) -> ResponseTemplate;
+
+    /// Same as [`Respond::respond`], but it also receives a [`ResponseContext`] with information
+    /// about the [`Mock`](crate::Mock) that matched `request` - e.g. how many requests it
+    /// matched before this one.
+    ///
+    /// This is the method invoked by the [`MockServer`](crate::MockServer).
+    /// It defaults to calling [`Respond::respond`], ignoring the context.
+    fn respond_with_ctx(&self, request: &Request, ctx: &ResponseContext) -> ResponseTemplate {
+        let _ = ctx;
+        self.respond(request)
+    }
+}
+
+/// Information about the [`Mock`](crate::Mock) that matched an incoming request, passed to
+/// [`Respond::respond_with_ctx`] and [`AsyncRespond::respond_with_ctx`].
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub struct ResponseContext {
+    /// How many requests the mock matched before the current one.
+    ///
+    /// E.g. `0` when responding to the first matching request, `1` for the second, etc.
+    pub n_previous_matches: u64,
+    /// The name assigned to the mock using [`Mock::named`](crate::Mock::named), if any.
+    pub mock_name: Option<String>,
+    /// The position of the current request among all the requests received by the
+    /// [`MockServer`](crate::MockServer), starting from `0`.
+    pub sequence_number: u64,
+    /// The values captured by the placeholders of the
+    /// [`path_template`](crate::matchers::path_template) matcher of the mock, keyed by name.
+    ///
+    /// It is empty if the mock does not use `path_template`.
+    pub path_params: HashMap<String, String>,
+    /// The source of random values of the [`MockServer`](crate::MockServer), seeded with
+    /// [`MockServerBuilder::random_seed`](crate::MockServerBuilder::random_seed).
+    ///
+    /// Use it to generate random-looking data - e.g. identifiers - that stay the same from
+    /// one run of your test to the next.
+    pub random: Random,
+    /// The identifier of the session the request belongs to, if any.
+    pub(crate) session_id: Option<String>,
+    /// The sessions of the [`MockServer`](crate::MockServer).
+    pub(crate) sessions: Sessions,
+}
+
+impl ResponseContext {
+    /// The identifier of the session the current request belongs to, if any - see
+    /// [`SessionKey`](crate::SessionKey).
+    pub fn session_id(&self) -> Option<&str> {
+        self.session_id.as_deref()
+    }
+
+    /// The state of type `T` of the session the current request belongs to, or `None` if it
+    /// does not belong to any - see [`SessionKey`](crate::SessionKey).
+    ///
+    /// The state is initialised with its default value the first time it is accessed, and it
+    /// is shared by all the mocks of the [`MockServer`](crate::MockServer): use it to model
+    /// stateful interactions - e.g. log in, use the session cookie, log out - without keeping
+    /// shared state in your test code.
+    ///
+    /// ### Example:
+    /// ```rust
+    /// use wiremock::{MockServer, Mock, Request, Respond, ResponseContext, ResponseTemplate};
+    /// use wiremock::matchers::method;
+    ///
+    /// /// Adds an item to the cart of the session, returning how many items it holds.
+    /// struct AddToCart;
+    ///
+    /// impl Respond for AddToCart {
+    ///     fn respond(&self, _request: &Request) -> ResponseTemplate {
+    ///         unreachable!("The server always calls `respond_with_ctx`")
+    ///     }
+    ///
+    ///     fn respond_with_ctx(&self, request: &Request, ctx: &ResponseContext) -> ResponseTemplate {
+    ///         let Some(cart) = ctx.session::<Vec<Vec<u8>>>() else {
+    ///             return ResponseTemplate::new(401);
+    ///         };
+    ///         let mut cart = cart.lock().unwrap();
+    ///         cart.push(request.body.to_vec());
+    ///         ResponseTemplate::new(200).set_body_string(cart.len().to_string())
+    ///     }
+    /// }
+    ///
+    /// #[async_std::main]
+    /// async fn main() {
+    ///     // Arrange - requests on the same connection share a session
+    ///     let mock_server = MockServer::start().await;
+    ///     Mock::given(method("POST"))
+    ///         .respond_with(AddToCart)
+    ///         .mount(&mock_server)
+    ///         .await;
+    ///     let client = surf::client();
+    ///
+    ///     // Act
+    ///     let mut sizes = vec![];
+    ///     for item in ["apple", "pear"] {
+    ///         let size = client.post(&mock_server.uri()).body(item).recv_string().await;
+    ///         sizes.push(size.unwrap());
+    ///     }
+    ///
+    ///     // Assert
+    ///     assert_eq!(sizes, vec!["1", "2"]);
+    /// }
+    /// ```
+    pub fn session<T: Default + Send + 'static>(&self) -> Option<Arc<Mutex<T>>> {
+        let session_id = self.session_id.as_deref()?;
+        Some(self.sessions.state(session_id))
+    }
+
+    /// The state of type `T` of the session identified by `session_id` - e.g. to set up the
+    /// session handed over to the client in the response to a login request.
+    ///
+    /// It behaves like [`ResponseContext::session`] otherwise.
+    pub fn session_by_id<T: Default + Send + 'static>(&self, session_id: &str) -> Arc<Mutex<T>> {
+        self.sessions.state(session_id)
+    }
+
+    /// Drop all the state of the session the current request belongs to, if any - e.g. when
+    /// the client logs out.
+    pub fn end_session(&self) {
+        if let Some(session_id) = &self.session_id {
+            self.sessions.end(session_id);
+        }
+    }
 }
 
 /// A `ResponseTemplate` is the simplest `Respond` implementation: it returns a clone of itself
@@ -147,3 +324,159 @@ where
         (self)(request)
     }
 }
+
+/// The asynchronous counterpart of [`Respond`]: anything that implements `AsyncRespond` can be
+/// used to reply to an incoming request when a [`Mock`] is activated, using
+/// [`MockBuilder::respond_with_async`].
+///
+/// Use it when building the response requires awaiting something - e.g. reading a fixture
+/// file, receiving a message from a channel or calling an asynchronous library.
+///
+/// The [`MockServer`] keeps serving other requests while an asynchronous responder is busy:
+/// a slow responder only delays the response to the request it is handling.
+///
+/// ## Closures
+///
+/// Functions that take an owned [`Request`] and return a future resolving to a
+/// [`ResponseTemplate`] implement `AsyncRespond`:
+///
+/// ```rust
+/// use wiremock::{MockServer, Mock, Request, ResponseTemplate};
+/// use wiremock::matchers::path;
+///
+/// #[async_std::main]
+/// async fn main() {
+///     // Arrange
+///     let mock_server = MockServer::start().await;
+///
+///     Mock::given(path("/fixture"))
+///         .respond_with_async(|_request: Request| async {
+///             // E.g. `tokio::fs::read_to_string("fixtures/user.json").await`
+///             let body = async { "Ferris".to_string() }.await;
+///             ResponseTemplate::new(200).set_body_string(body)
+///         })
+///         .mount(&mock_server)
+///         .await;
+///
+///     // Act
+///     let mut response = surf::get(format!("{}/fixture", &mock_server.uri()))
+///         .await
+///         .unwrap();
+///
+///     // Assert
+///     assert_eq!(response.status(), 200);
+///     assert_eq!(response.body_string().await.unwrap(), "Ferris");
+/// }
+/// ```
+///
+/// ## Implementing `AsyncRespond`
+///
+/// `AsyncRespond` is an [`async_trait`](https://docs.rs/async-trait), so you can implement it
+/// on your own types using the `#[async_trait]` attribute:
+///
+/// ```rust
+/// use async_trait::async_trait;
+/// use wiremock::{AsyncRespond, MockServer, Mock, Request, ResponseTemplate};
+/// use wiremock::matchers::path;
+///
+/// /// Responds with the next message received on a channel.
+/// struct ChannelResponder(async_std::channel::Receiver<String>);
+///
+/// #[async_trait]
+/// impl AsyncRespond for ChannelResponder {
+///     async fn respond(&self, _request: &Request) -> ResponseTemplate {
+///         let message = self.0.recv().await.unwrap();
+///         ResponseTemplate::new(200).set_body_string(message)
+///     }
+/// }
+///
+/// #[async_std::main]
+/// async fn main() {
+///     // Arrange
+///     let mock_server = MockServer::start().await;
+///     let (sender, receiver) = async_std::channel::unbounded();
+///
+///     Mock::given(path("/next"))
+///         .respond_with_async(ChannelResponder(receiver))
+///         .mount(&mock_server)
+///         .await;
+///
+///     // Act
+///     sender.send("hello".to_string()).await.unwrap();
+///     let mut response = surf::get(format!("{}/next", &mock_server.uri()))
+///         .await
+///         .unwrap();
+///
+///     // Assert
+///     assert_eq!(response.body_string().await.unwrap(), "hello");
+/// }
+/// ```
+///
+/// [`Mock`]: crate::Mock
+/// [`MockBuilder::respond_with_async`]: crate::MockBuilder::respond_with_async
+/// [`MockServer`]: crate::MockServer
+#[async_trait]
+pub trait AsyncRespond: Send + Sync {
+    /// Given a reference to a [`Request`] return a [`ResponseTemplate`] that will be used
+    /// by the [`MockServer`] as blueprint for the response returned to the client.
+    ///
+    /// [`Request`]: crate::Request
+    /// [`MockServer`]: crate::MockServer
+    /// [`ResponseTemplate`]: crate::ResponseTemplate
+    async fn respond(&self, request: &Request) -> ResponseTemplate;
+
+    /// Same as [`AsyncRespond::respond`], but it also receives a [`ResponseContext`] with
+    /// information about the [`Mock`](crate::Mock) that matched `request`.
+    ///
+    /// It defaults to calling [`AsyncRespond::respond`], ignoring the context.
+    async fn respond_with_ctx(&self, request: &Request, ctx: &ResponseContext) -> ResponseTemplate {
+        let _ = ctx;
+        self.respond(request).await
+    }
+}
+
+#[async_trait]
+impl<F, Fut> AsyncRespond for F
+where
+    F: Send + Sync + Fn(Request) -> Fut,
+    Fut: Future<Output = ResponseTemplate> + Send,
+{
+    async fn respond(&self, request: &Request) -> ResponseTemplate {
+        (self)(request.clone()).await
+    }
+}
+
+/// The responder attached to a [`Mock`](crate::Mock), either synchronous or asynchronous.
+#[derive(Clone)]
+pub(crate) enum Responder {
+    Sync(Arc<dyn Respond>),
+    Async(Arc<dyn AsyncRespond>),
+}
+
+impl Responder {
+    /// Start generating the response to `request`.
+    pub(crate) fn respond(&self, request: &Request, ctx: ResponseContext) -> PendingResponse {
+        match self {
+            Responder::Sync(responder) => {
+                PendingResponse::Ready(Box::new(responder.respond_with_ctx(request, &ctx)))
+            }
+            Responder::Async(responder) => {
+                let responder = responder.clone();
+                let request = request.clone();
+                PendingResponse::Deferred(Box::pin(async move {
+                    responder.respond_with_ctx(&request, &ctx).await
+                }))
+            }
+        }
+    }
+}
+
+/// The response to a matched request, which might not be available yet if it is
+/// generated by an [`AsyncRespond`] implementation.
+///
+/// Deferred responses are awaited by the server *after* it has released the lock on its state,
+/// to avoid blocking other requests while the responder is busy.
+pub(crate) enum PendingResponse {
+    Ready(Box<ResponseTemplate>),
+    Deferred(BoxFuture<'static, ResponseTemplate>),
+}
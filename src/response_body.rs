@@ -0,0 +1,383 @@
+use http::HeaderMap;
+use hyper::body::{Body, Bytes, Frame, SizeHint};
+use std::fmt::{Display, Formatter};
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::task::{ready, Context, Poll};
+use std::time::{Duration, Instant};
+
+/// The body of the responses sent by the [`MockServer`](crate::MockServer): the bytes
+/// generated out of a [`ResponseTemplate`](crate::ResponseTemplate), optionally followed by
+/// trailers.
+#[derive(Debug, Default)]
+pub(crate) struct ResponseBody {
+    data: Option<Bytes>,
+    trailers: Option<HeaderMap>,
+    /// Drop the connection once this many bytes of the body have been sent.
+    abort_after: Option<usize>,
+    /// Whether we gave the server a chance to flush what was sent before the body ends or the
+    /// connection is dropped.
+    flushed: bool,
+    /// Paces the data, if set.
+    throttle: Option<Throttle>,
+    /// Pauses between the lines of the data, if set.
+    line_delay: Option<LineDelay>,
+    /// Hide the length of the data from the server, leaving the framing to the headers.
+    unknown_length: bool,
+    /// Told when the body has been handed over to the connection in full, if set.
+    consumption: Option<ConsumptionHandle>,
+    /// Run once the body is done with, if set.
+    on_sent: Option<SentHook>,
+}
+
+/// The size of the frames of a body whose consumption is tracked.
+const CONSUMPTION_CHUNK_SIZE: usize = 64 * 1024;
+
+/// Keeps track of how long it took to write the bodies of the responses sent by a mock -
+/// see [`Mock::expect_response_consumed_within`](crate::Mock::expect_response_consumed_within).
+#[derive(Debug, Default)]
+pub(crate) struct ConsumptionRecorder(Mutex<Vec<BodyConsumption>>);
+
+/// How far the server got in writing the body of a response.
+#[derive(Debug, Clone, Copy)]
+pub(crate) enum BodyConsumption {
+    /// The body is still being written, since the given instant.
+    InProgress(Instant),
+    /// The body was written in full, in the given time.
+    Consumed(Duration),
+    /// The connection was closed before the body was written in full, after the given time.
+    Abandoned(Duration),
+}
+
+impl ConsumptionRecorder {
+    /// Start tracking the body of a response that is about to be sent.
+    pub(crate) fn start(self: &Arc<Self>) -> ConsumptionHandle {
+        let mut bodies = self.0.lock().unwrap();
+        bodies.push(BodyConsumption::InProgress(Instant::now()));
+        ConsumptionHandle {
+            recorder: self.clone(),
+            index: bodies.len() - 1,
+            consumed: false,
+        }
+    }
+
+    /// The bodies tracked so far, in the order their responses were sent.
+    pub(crate) fn bodies(&self) -> Vec<BodyConsumption> {
+        self.0.lock().unwrap().clone()
+    }
+
+    fn finish(&self, index: usize, consumed: bool) {
+        let mut bodies = self.0.lock().unwrap();
+        if let BodyConsumption::InProgress(started_at) = bodies[index] {
+            let elapsed = started_at.elapsed();
+            bodies[index] = if consumed {
+                BodyConsumption::Consumed(elapsed)
+            } else {
+                BodyConsumption::Abandoned(elapsed)
+            };
+        }
+    }
+}
+
+/// The link between a [`ResponseBody`] and the [`ConsumptionRecorder`] tracking it.
+#[derive(Debug)]
+pub(crate) struct ConsumptionHandle {
+    recorder: Arc<ConsumptionRecorder>,
+    index: usize,
+    /// Set once the last frame of the body has been handed over to the connection.
+    consumed: bool,
+}
+
+// hyper drops the body as soon as it has been sent, or when the connection is closed.
+impl Drop for ConsumptionHandle {
+    fn drop(&mut self) {
+        self.recorder.finish(self.index, self.consumed);
+    }
+}
+
+/// A callback run once a [`ResponseBody`] has been handed over to the connection, or dropped
+/// before that - see [`ResponseBody::on_sent`].
+pub(crate) struct SentHook(Option<Box<dyn FnOnce() + Send + Sync>>);
+
+impl std::fmt::Debug for SentHook {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str("SentHook")
+    }
+}
+
+// hyper drops the body as soon as it has been sent, or when the connection is closed.
+impl Drop for SentHook {
+    fn drop(&mut self) {
+        if let Some(hook) = self.0.take() {
+            hook();
+        }
+    }
+}
+
+/// Limits the throughput of a body by sending its data in chunks, pausing after each of them.
+#[derive(Debug)]
+struct Throttle {
+    bytes_per_sec: u64,
+    pause: Option<Pin<Box<tokio::time::Sleep>>>,
+}
+
+impl Throttle {
+    /// The pause after each chunk is a tenth of a second: short enough for the throughput
+    /// to look steady, long enough not to flood the server with timers.
+    fn chunk_size(&self) -> usize {
+        (self.bytes_per_sec / 10).max(1) as usize
+    }
+
+    /// Wait for the pause after the previous chunk to elapse.
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<()> {
+        if let Some(pause) = &mut self.pause {
+            ready!(pause.as_mut().poll(cx));
+            self.pause = None;
+        }
+        Poll::Ready(())
+    }
+
+    /// Pause for as long as it takes to send `n` bytes at the configured throughput.
+    fn sent(&mut self, n: usize) {
+        let pause = Duration::from_secs_f64(n as f64 / self.bytes_per_sec as f64);
+        self.pause = Some(Box::pin(tokio::time::sleep(pause)));
+    }
+}
+
+/// Sends each line of a body in its own chunk, pausing before all of them but the first.
+#[derive(Debug)]
+struct LineDelay {
+    delay: Duration,
+    pause: Option<Pin<Box<tokio::time::Sleep>>>,
+}
+
+impl LineDelay {
+    /// Wait for the pause after the previous line to elapse.
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<()> {
+        if let Some(pause) = &mut self.pause {
+            ready!(pause.as_mut().poll(cx));
+            self.pause = None;
+        }
+        Poll::Ready(())
+    }
+}
+
+/// The error returned by a [`ResponseBody`] to make the server drop the connection.
+#[derive(Debug)]
+pub(crate) struct ConnectionDropped;
+
+impl Display for ConnectionDropped {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "the connection was dropped as requested by the response template"
+        )
+    }
+}
+
+impl std::error::Error for ConnectionDropped {}
+
+impl ResponseBody {
+    /// Send `trailers` once the data has been sent.
+    pub(crate) fn with_trailers(mut self, trailers: HeaderMap) -> Self {
+        self.trailers = Some(trailers);
+        self
+    }
+
+    /// Drop the connection once `limit` bytes of the body have been sent, without sending
+    /// the rest of the body nor the trailers.
+    ///
+    /// The size hint is left untouched: the client is told to expect the whole body.
+    pub(crate) fn abort_after(mut self, limit: usize) -> Self {
+        self.abort_after = Some(limit);
+        self
+    }
+
+    /// Send the data at `bytes_per_sec` bytes per second at most. It must not be zero.
+    pub(crate) fn throttle(mut self, bytes_per_sec: u64) -> Self {
+        self.throttle = Some(Throttle {
+            bytes_per_sec,
+            pause: None,
+        });
+        self
+    }
+
+    /// Do not tell the server the length of the data: it is then framed according to the
+    /// `Content-Length` header, if any, or sent with the `chunked` transfer encoding.
+    pub(crate) fn unknown_length(mut self) -> Self {
+        self.unknown_length = true;
+        self
+    }
+
+    /// Report to `consumption` when the body has been handed over to the connection in full,
+    /// or dropped before that. The data is then sent in small frames.
+    pub(crate) fn track_consumption(mut self, mut consumption: ConsumptionHandle) -> Self {
+        // hyper does not poll empty bodies.
+        consumption.consumed = self.is_exhausted();
+        self.consumption = Some(consumption);
+        self
+    }
+
+    /// Run `hook` once the body has been handed over to the connection in full, or dropped
+    /// before that - e.g. because the client closed the connection.
+    pub(crate) fn on_sent<F>(mut self, hook: F) -> Self
+    where
+        F: FnOnce() + Send + Sync + 'static,
+    {
+        self.on_sent = Some(SentHook(Some(Box::new(hook))));
+        self
+    }
+
+    /// Whether there is nothing left to send.
+    fn is_exhausted(&self) -> bool {
+        self.data.as_ref().is_none_or(Bytes::is_empty) && self.trailers.is_none()
+    }
+
+    /// Pause for `delay` before sending each line of the data but the first.
+    pub(crate) fn line_delay(mut self, delay: Duration) -> Self {
+        self.line_delay = Some(LineDelay { delay, pause: None });
+        self
+    }
+
+    /// The next chunk of data to send, if any, paced by the throttle and the line delay.
+    fn poll_data(&mut self, cx: &mut Context<'_>) -> Poll<Option<Bytes>> {
+        if self.data.as_ref().is_some_and(Bytes::is_empty) {
+            self.data = None;
+        }
+        let data = match &mut self.data {
+            Some(data) => data,
+            None => return Poll::Ready(None),
+        };
+        let mut length = data.len();
+        // hyper only asks for the next frame once it has room for it: small frames let us
+        // tell when the data has actually been written.
+        if self.consumption.is_some() {
+            length = length.min(CONSUMPTION_CHUNK_SIZE);
+        }
+        if let Some(throttle) = &mut self.throttle {
+            ready!(throttle.poll_ready(cx));
+            length = length.min(throttle.chunk_size());
+        }
+        let mut end_of_line = false;
+        if let Some(line_delay) = &mut self.line_delay {
+            ready!(line_delay.poll_ready(cx));
+            if let Some(newline) = data[..length].iter().position(|byte| *byte == b'\n') {
+                length = newline + 1;
+                end_of_line = true;
+            }
+        }
+        let chunk = data.split_to(length);
+        if let Some(throttle) = &mut self.throttle {
+            throttle.sent(chunk.len());
+        }
+        if let Some(line_delay) = &mut self.line_delay {
+            if end_of_line && !data.is_empty() {
+                line_delay.pause = Some(Box::pin(tokio::time::sleep(line_delay.delay)));
+            }
+        }
+        Poll::Ready(Some(chunk))
+    }
+
+    /// The next frame of the body - see [`Body::poll_frame`].
+    fn poll_next_frame(
+        &mut self,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Frame<Bytes>, ConnectionDropped>>> {
+        if let Some(limit) = self.abort_after {
+            // A no-op once the first `limit` bytes have started to be sent.
+            if let Some(data) = &mut self.data {
+                data.truncate(limit);
+            }
+            if let Some(data) = ready!(self.poll_data(cx)) {
+                return Poll::Ready(Some(Ok(Frame::data(data))));
+            }
+            // hyper drops the connection as soon as the body fails, discarding what it has
+            // buffered: we yield once to let it flush the headers and the partial body.
+            if !self.flushed {
+                self.flushed = true;
+                cx.waker().wake_by_ref();
+                return Poll::Pending;
+            }
+            return Poll::Ready(Some(Err(ConnectionDropped)));
+        }
+        if let Some(data) = ready!(self.poll_data(cx)) {
+            return Poll::Ready(Some(Ok(Frame::data(data))));
+        }
+        // The body might be shorter than the `Content-Length` header: hyper would then drop
+        // the connection, discarding what it has buffered.
+        if self.unknown_length && !self.flushed {
+            self.flushed = true;
+            cx.waker().wake_by_ref();
+            return Poll::Pending;
+        }
+        Poll::Ready(
+            self.trailers
+                .take()
+                .map(|trailers| Ok(Frame::trailers(trailers))),
+        )
+    }
+}
+
+impl Body for ResponseBody {
+    type Data = Bytes;
+    type Error = ConnectionDropped;
+
+    fn poll_frame(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Frame<Self::Data>, Self::Error>>> {
+        let this = self.get_mut();
+        let frame = this.poll_next_frame(cx);
+        if frame.is_ready() && this.is_exhausted() {
+            if let Some(consumption) = &mut this.consumption {
+                consumption.consumed = true;
+            }
+        }
+        frame
+    }
+
+    fn is_end_stream(&self) -> bool {
+        self.data.is_none() && self.trailers.is_none() && self.abort_after.is_none()
+    }
+
+    fn size_hint(&self) -> SizeHint {
+        let length = self.data.as_ref().map_or(0, |data| data.len() as u64);
+        if self.trailers.is_none() && !self.unknown_length {
+            return SizeHint::with_exact(length);
+        }
+        // An exact size hint would make hyper send a `Content-Length` header, while HTTP/1.1
+        // trailers can only be sent with a chunked body - and an unknown length is framed
+        // according to the headers of the response.
+        let mut size_hint = SizeHint::new();
+        size_hint.set_lower(length);
+        size_hint
+    }
+}
+
+impl From<Bytes> for ResponseBody {
+    fn from(data: Bytes) -> Self {
+        Self {
+            data: Some(data).filter(|data| !data.is_empty()),
+            ..Self::default()
+        }
+    }
+}
+
+impl From<Vec<u8>> for ResponseBody {
+    fn from(data: Vec<u8>) -> Self {
+        Bytes::from(data).into()
+    }
+}
+
+impl From<String> for ResponseBody {
+    fn from(data: String) -> Self {
+        Bytes::from(data).into()
+    }
+}
+
+impl From<&'static str> for ResponseBody {
+    fn from(data: &'static str) -> Self {
+        Bytes::from_static(data.as_bytes()).into()
+    }
+}
@@ -0,0 +1,124 @@
+use crate::respond::{Respond, ResponseContext};
+use crate::{Request, ResponseTemplate};
+
+/// A list of responses returned one after the other by a [`Mock`](crate::Mock), mounted with
+/// [`MockBuilder::respond_with_sequence`](crate::MockBuilder::respond_with_sequence).
+///
+/// The first matching request gets the first response, the second request gets the second
+/// response, etc. What happens once all the responses have been returned is up to you:
+///
+/// - [`then_repeat_last`](ResponseSequence::then_repeat_last) (the default) keeps returning the
+///   last response;
+/// - [`then_loop`](ResponseSequence::then_loop) starts over from the first response;
+/// - [`then_unmatched`](ResponseSequence::then_unmatched) stops matching: the following requests
+///   are handled by the other mocks mounted on the [`MockServer`](crate::MockServer), or get a
+///   `404 Not Found` if none matches.
+///
+/// ### Example:
+/// ```rust
+/// use wiremock::{MockServer, Mock, ResponseSequence, ResponseTemplate};
+/// use wiremock::matchers::method;
+///
+/// #[async_std::main]
+/// async fn main() {
+///     // Arrange
+///     let mock_server = MockServer::start().await;
+///     Mock::given(method("GET"))
+///         .respond_with_sequence(
+///             ResponseSequence::new([ResponseTemplate::new(503), ResponseTemplate::new(429)])
+///                 .then_unmatched(),
+///         )
+///         .mount(&mock_server)
+///         .await;
+///
+///     // Act
+///     let mut statuses = vec![];
+///     for _ in 0..3 {
+///         let status = surf::get(&mock_server.uri()).await.unwrap().status();
+///         statuses.push(u16::from(status));
+///     }
+///
+///     // Assert
+///     assert_eq!(statuses, vec![503, 429, 404]);
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct ResponseSequence {
+    responses: Vec<ResponseTemplate>,
+    exhausted: Exhausted,
+}
+
+/// What a [`ResponseSequence`] does once all its responses have been returned.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Exhausted {
+    RepeatLast,
+    Loop,
+    Unmatched,
+}
+
+impl ResponseSequence {
+    /// Build a sequence returning `responses`, in order.
+    ///
+    /// Once they have all been returned, the last one is repeated - see
+    /// [`ResponseSequence`] for the alternatives.
+    ///
+    /// It panics if `responses` is empty.
+    pub fn new<I>(responses: I) -> Self
+    where
+        I: IntoIterator<Item = ResponseTemplate>,
+    {
+        let responses: Vec<_> = responses.into_iter().collect();
+        assert!(
+            !responses.is_empty(),
+            "A response sequence must contain at least one response!"
+        );
+        Self {
+            responses,
+            exhausted: Exhausted::RepeatLast,
+        }
+    }
+
+    /// Keep returning the last response once the sequence is exhausted.
+    pub fn then_repeat_last(mut self) -> Self {
+        self.exhausted = Exhausted::RepeatLast;
+        self
+    }
+
+    /// Start over from the first response once the sequence is exhausted.
+    pub fn then_loop(mut self) -> Self {
+        self.exhausted = Exhausted::Loop;
+        self
+    }
+
+    /// Stop matching requests once the sequence is exhausted, leaving them to the other mocks
+    /// mounted on the [`MockServer`](crate::MockServer).
+    ///
+    /// It is equivalent to [`Mock::up_to_n_times`](crate::Mock::up_to_n_times) with the length
+    /// of the sequence. It requires mounting the sequence with
+    /// [`MockBuilder::respond_with_sequence`](crate::MockBuilder::respond_with_sequence): when
+    /// used as a plain [`Respond`], the sequence repeats its last response instead.
+    pub fn then_unmatched(mut self) -> Self {
+        self.exhausted = Exhausted::Unmatched;
+        self
+    }
+
+    /// The maximum number of requests the mock responding with this sequence should match.
+    pub(crate) fn max_n_matches(&self) -> Option<u64> {
+        (self.exhausted == Exhausted::Unmatched).then_some(self.responses.len() as u64)
+    }
+}
+
+impl Respond for ResponseSequence {
+    fn respond(&self, _request: &Request) -> ResponseTemplate {
+        self.responses[0].clone()
+    }
+
+    fn respond_with_ctx(&self, _request: &Request, ctx: &ResponseContext) -> ResponseTemplate {
+        let len = self.responses.len() as u64;
+        let index = match self.exhausted {
+            Exhausted::Loop => ctx.n_previous_matches % len,
+            Exhausted::RepeatLast | Exhausted::Unmatched => ctx.n_previous_matches.min(len - 1),
+        };
+        self.responses[index as usize].clone()
+    }
+}
@@ -1,9 +1,13 @@
-use http::{HeaderMap, HeaderName, HeaderValue, Response, StatusCode};
-use http_body_util::Full;
-use hyper::body::Bytes;
+use crate::date::{http_date, parse_http_date};
+use crate::random::Random;
+use crate::range::ByteRange;
+use crate::response_body::ResponseBody;
+use crate::templating;
+use http::{HeaderMap, HeaderName, HeaderValue, Method, Response, StatusCode};
 use serde::Serialize;
 use std::convert::TryInto;
-use std::time::Duration;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
 
 /// The blueprint for the response returned by a [`MockServer`] when a [`Mock`] matches on an incoming request.
 ///
@@ -11,11 +15,105 @@ use std::time::Duration;
 /// [`MockServer`]: crate::MockServer
 #[derive(Clone, Debug)]
 pub struct ResponseTemplate {
+    /// The `Content-Type` implied by the body setters.
+    /// It is only used if no `Content-Type` header has been set explicitly.
     mime: String,
     status_code: StatusCode,
     headers: HeaderMap,
-    body: Option<Vec<u8>>,
+    body: Option<Body>,
     delay: Option<Duration>,
+    /// Render the template variables in the body and in the header values.
+    render_templates: bool,
+    /// The headers whose template variables are rendered even if `render_templates` is unset.
+    templated_headers: Vec<HeaderName>,
+    /// Set the `Date` header to the current time shifted by this offset - e.g. `-5m`.
+    date_offset: Option<String>,
+    /// The `charset` parameter added to the `Content-Type` header.
+    charset: Option<String>,
+    /// Sent after the body.
+    trailers: HeaderMap,
+    /// Drop the connection once this many bytes of the body have been sent.
+    abort_after_bytes: Option<usize>,
+    /// Drop the connection without sending a response - see [`crate::chaos::Fault`].
+    drop_connection: bool,
+    /// Sent before the response, e.g. `103 Early Hints`.
+    interim_responses: Vec<(StatusCode, HeaderMap)>,
+    /// Never send the response.
+    hang: bool,
+    /// Send the body at this many bytes per second at most.
+    throttle_bytes_per_sec: Option<u64>,
+    /// Pause for this long before sending each line of the body but the first.
+    line_delay: Option<Duration>,
+    /// How the client learns where the body ends, if not left to the server.
+    framing: Option<Framing>,
+    /// How requests with an `Expect: 100-continue` header are handled.
+    expect_continue: Option<ExpectContinue>,
+    /// Write these bytes to the socket instead of the response - see
+    /// [`MockBuilder::respond_with_raw_bytes`](crate::MockBuilder::respond_with_raw_bytes).
+    raw_response: Option<Vec<u8>>,
+    /// Send the part of the body requested with a `Range` header, if any.
+    serve_ranges: bool,
+    /// Set an `ETag` header and honour the conditional headers of the requests.
+    etag: bool,
+    /// Reset the HTTP/2 stream with this error code instead of sending the response.
+    reset_stream: Option<StreamReset>,
+}
+
+/// The body of a [`ResponseTemplate`].
+#[derive(Clone, Debug)]
+enum Body {
+    Bytes(Vec<u8>),
+    /// The body is read from a file when the response is generated.
+    File {
+        path: PathBuf,
+        /// Check that the content of the file is valid JSON before using it as body.
+        json: bool,
+    },
+    /// The file of a `File` body could not be read: the error is reported when the response
+    /// is generated.
+    Unreadable(String),
+}
+
+/// How a [`ResponseTemplate`] handles the requests asking for confirmation before sending
+/// their body, with an `Expect: 100-continue` header.
+#[derive(Clone, Copy, Debug)]
+pub(crate) enum ExpectContinue {
+    /// Wait before sending `100 Continue`.
+    Delay(Duration),
+    /// Respond with this status code instead of `100 Continue`, without reading the body.
+    Reject(StatusCode),
+}
+
+/// The error codes an HTTP/2 stream can be reset with - see [`ResponseTemplate::reset_stream`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum StreamReset {
+    /// `REFUSED_STREAM`: the request was not processed, it is safe to retry it.
+    RefusedStream,
+    /// `CANCEL`: the stream is no longer needed.
+    Cancel,
+    /// `INTERNAL_ERROR`: the server hit an unexpected error.
+    InternalError,
+}
+
+impl StreamReset {
+    /// The HTTP/2 error code sent in the `RST_STREAM` frame.
+    pub(crate) fn reason(self) -> h2::Reason {
+        match self {
+            StreamReset::RefusedStream => h2::Reason::REFUSED_STREAM,
+            StreamReset::Cancel => h2::Reason::CANCEL,
+            StreamReset::InternalError => h2::Reason::INTERNAL_ERROR,
+        }
+    }
+}
+
+/// How the client learns where the body of a [`ResponseTemplate`] ends.
+#[derive(Clone, Copy, Debug)]
+enum Framing {
+    Chunked,
+    CloseDelimited,
+    /// A `Content-Length` header with this value, whatever the length of the body.
+    ContentLength(u64),
 }
 
 // `wiremock` is a crate meant for testing - failures are most likely not handled/temporary mistakes.
@@ -40,9 +138,128 @@ impl ResponseTemplate {
             mime: String::new(),
             body: None,
             delay: None,
+            render_templates: false,
+            templated_headers: Vec::new(),
+            date_offset: None,
+            charset: None,
+            trailers: HeaderMap::new(),
+            abort_after_bytes: None,
+            drop_connection: false,
+            interim_responses: Vec::new(),
+            hang: false,
+            throttle_bytes_per_sec: None,
+            line_delay: None,
+            framing: None,
+            expect_continue: None,
+            raw_response: None,
+            serve_ranges: false,
+            etag: false,
+            reset_stream: None,
         }
     }
 
+    /// Start building a `200 OK` response that honours the `Range` header of the requests,
+    /// to test clients resuming interrupted downloads or fetching files in parts.
+    ///
+    /// For each request:
+    /// - without a `Range` header, the whole `full_body` is sent with a `200 OK`;
+    /// - with a satisfiable range of bytes - e.g. `bytes=0-499`, `bytes=500-` or
+    ///   `bytes=-200` - the corresponding slice of `full_body` is sent with a
+    ///   `206 Partial Content` and a `Content-Range` header;
+    /// - with a range outside of `full_body`, an empty `416 Range Not Satisfiable` is sent,
+    ///   with a `Content-Range: bytes */<length>` header.
+    ///
+    /// Invalid `Range` headers, and requests for more than one range, are ignored: the whole
+    /// body is sent, as a server is allowed to. An `Accept-Ranges: bytes` header is added to
+    /// all responses.
+    ///
+    /// Ranges are only applied if the status code is still `200 OK` when the response is sent,
+    /// to the body of the response at that point: use
+    /// [`set_body_from_file`](#method.set_body_from_file) to serve the parts of a file instead.
+    ///
+    /// ### Example:
+    /// ```rust
+    /// use wiremock::{MockServer, Mock, ResponseTemplate};
+    /// use wiremock::matchers::method;
+    ///
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     // Arrange
+    ///     let mock_server = MockServer::start().await;
+    ///     Mock::given(method("GET"))
+    ///         .respond_with(ResponseTemplate::respond_with_range_of("Hello, world!"))
+    ///         .mount(&mock_server)
+    ///         .await;
+    ///
+    ///     // Act
+    ///     let res = reqwest::Client::new()
+    ///         .get(&mock_server.uri())
+    ///         .header("Range", "bytes=7-")
+    ///         .send()
+    ///         .await
+    ///         .unwrap();
+    ///
+    ///     // Assert
+    ///     assert_eq!(res.status(), 206);
+    ///     assert_eq!(res.headers()["Content-Range"], "bytes 7-12/13");
+    ///     assert_eq!(res.text().await.unwrap(), "world!");
+    /// }
+    /// ```
+    pub fn respond_with_range_of<B>(full_body: B) -> Self
+    where
+        B: TryInto<Vec<u8>>,
+        <B as TryInto<Vec<u8>>>::Error: std::fmt::Debug,
+    {
+        let mut template = Self::new(200)
+            .set_body_bytes(full_body)
+            .insert_header(http::header::ACCEPT_RANGES, "bytes");
+        template.serve_ranges = true;
+        template
+    }
+
+    /// Change the status code of the response.
+    ///
+    /// It comes in handy when a helper function builds a base template - e.g. with your
+    /// API's headers and error body - and each test adjusts the status code it needs.
+    ///
+    /// ### Example:
+    /// ```rust
+    /// use wiremock::{MockServer, Mock, ResponseTemplate};
+    /// use wiremock::matchers::method;
+    /// use serde_json::json;
+    ///
+    /// fn api_error() -> ResponseTemplate {
+    ///     ResponseTemplate::default()
+    ///         .insert_header("X-Request-Id", "42")
+    ///         .set_body_json(json!({"error": "Something went wrong"}))
+    /// }
+    ///
+    /// #[async_std::main]
+    /// async fn main() {
+    ///     // Arrange
+    ///     let mock_server = MockServer::start().await;
+    ///     Mock::given(method("GET"))
+    ///         .respond_with(api_error().set_status(503))
+    ///         .mount(&mock_server)
+    ///         .await;
+    ///
+    ///     // Act
+    ///     let res = surf::get(&mock_server.uri()).await.unwrap();
+    ///
+    ///     // Assert
+    ///     assert_eq!(res.status(), 503);
+    ///     assert_eq!(res.header("X-Request-Id").unwrap(), "42");
+    /// }
+    /// ```
+    pub fn set_status<S>(mut self, s: S) -> Self
+    where
+        S: TryInto<StatusCode>,
+        <S as TryInto<StatusCode>>::Error: std::fmt::Debug,
+    {
+        self.status_code = s.try_into().expect("Failed to convert into status code.");
+        self
+    }
+
     /// Append a header `value` to list of headers with `key` as header name.
     ///
     /// Unlike `insert_header`, this function will not override the contents of a header:
@@ -114,6 +331,61 @@ impl ResponseTemplate {
         self
     }
 
+    /// Insert a header whose value is a template, rendered every time a response is generated -
+    /// see [`render_templates`](#method.render_templates) for the supported variables.
+    ///
+    /// Unlike `render_templates`, it leaves the body and the other headers untouched.
+    /// It comes in handy to point at the [`MockServer`] itself - e.g. in a `Location` header -
+    /// with `{{base_url}}`, whose value is only known once the server is running.
+    ///
+    /// ### Example:
+    /// ```rust
+    /// use wiremock::{MockServer, Mock, ResponseTemplate};
+    /// use wiremock::matchers::method;
+    ///
+    /// #[async_std::main]
+    /// async fn main() {
+    ///     // Arrange
+    ///     let mock_server = MockServer::start().await;
+    ///     let template = ResponseTemplate::new(201)
+    ///         .insert_header_templated("Location", "{{base_url}}/items/42");
+    ///     Mock::given(method("POST"))
+    ///         .respond_with(template)
+    ///         .mount(&mock_server)
+    ///         .await;
+    ///
+    ///     // Act
+    ///     let res = surf::post(format!("{}/items", mock_server.uri()))
+    ///         .await
+    ///         .unwrap();
+    ///
+    ///     // Assert
+    ///     assert_eq!(
+    ///         res.header("Location").unwrap().as_str(),
+    ///         format!("{}/items/42", mock_server.uri())
+    ///     );
+    /// }
+    /// ```
+    ///
+    /// [`MockServer`]: crate::mock_server::MockServer
+    pub fn insert_header_templated<K, V>(mut self, key: K, value: V) -> Self
+    where
+        K: TryInto<HeaderName>,
+        <K as TryInto<HeaderName>>::Error: std::fmt::Debug,
+        V: TryInto<HeaderValue>,
+        <V as TryInto<HeaderValue>>::Error: std::fmt::Debug,
+    {
+        let key = key.try_into().expect("Failed to convert into header name.");
+        let value = value
+            .try_into()
+            .expect("Failed to convert into header value.");
+        if !self.templated_headers.contains(&key) {
+            self.templated_headers.push(key.clone());
+        }
+        self.headers.insert(key, value);
+        self
+    }
+
     /// Append multiple header key-value pairs.
     ///
     /// Existing header values will not be overridden.
@@ -167,165 +439,1555 @@ impl ResponseTemplate {
         self
     }
 
-    /// Set the response body with bytes.
+    /// Add a `Set-Cookie` header to the response, setting a cookie with the specified `name`
+    /// and `value`.
     ///
-    /// It sets "Content-Type" to "application/octet-stream".
+    /// `attributes` are appended as-is after the `name=value` pair, separated by `; ` -
+    /// e.g. `["Path=/", "HttpOnly", "Max-Age=3600"]`.
+    /// Use [`ResponseTemplate::add_cookie_pair`] if the cookie does not need any attribute.
     ///
-    /// To set a body with bytes but a different "Content-Type"
-    /// [`set_body_raw`](#method.set_body_raw) can be used.
-    pub fn set_body_bytes<B>(mut self, body: B) -> Self
+    /// `name` must be a token and `value` a sequence of cookie octets, optionally wrapped in
+    /// double quotes, as specified by [RFC 6265](https://www.rfc-editor.org/rfc/rfc6265#section-4.1.1).
+    /// Template variables in `value` (e.g. `{{now format='epoch'}}`) are only rendered if
+    /// [`render_templates`](#method.render_templates) was called before `add_cookie`: they
+    /// are not checked in that case, since they are rendered when a response is generated.
+    /// Otherwise `value` is sent as-is, and `{{`, `}}` and their content must be valid cookie
+    /// octets like the rest of it.
+    ///
+    /// ### Panics
+    ///
+    /// `add_cookie` will panic if `name` or `value` are not valid according to RFC 6265 -
+    /// e.g. if `value` contains whitespace or a `;`.
+    ///
+    /// Existing `Set-Cookie` headers are not overridden: you can call `add_cookie` multiple times
+    /// to set more than one cookie.
+    ///
+    /// ### Example:
+    /// ```rust
+    /// use wiremock::{MockServer, Mock, ResponseTemplate};
+    /// use wiremock::matchers::method;
+    ///
+    /// #[async_std::main]
+    /// async fn main() {
+    ///     // Arrange
+    ///     let mock_server = MockServer::start().await;
+    ///     let template = ResponseTemplate::new(200)
+    ///         .add_cookie("session", "abc", ["Path=/", "HttpOnly"])
+    ///         .add_cookie_pair("theme", "dark");
+    ///     Mock::given(method("GET"))
+    ///         .respond_with(template)
+    ///         .mount(&mock_server)
+    ///         .await;
+    ///
+    ///     // Act
+    ///     let res = surf::get(&mock_server.uri())
+    ///         .await
+    ///         .unwrap();
+    ///
+    ///     // Assert
+    ///     let cookies: Vec<_> = res.header("Set-Cookie").unwrap().iter().map(|v| v.as_str()).collect();
+    ///     assert_eq!(cookies, vec!["session=abc; Path=/; HttpOnly", "theme=dark"]);
+    /// }
+    /// ```
+    pub fn add_cookie<I, A>(self, name: &str, value: &str, attributes: I) -> Self
     where
-        B: TryInto<Vec<u8>>,
-        <B as TryInto<Vec<u8>>>::Error: std::fmt::Debug,
+        I: IntoIterator<Item = A>,
+        A: AsRef<str>,
     {
-        let body = body.try_into().expect("Failed to convert into body.");
-        self.body = Some(body);
-        self
+        assert!(
+            is_cookie_name(name),
+            "`{}` is not a valid cookie name: it must be a non-empty token, without whitespace or separators such as `=` and `;`.",
+            name
+        );
+        assert!(
+            is_cookie_value(value, self.render_templates),
+            "`{}` is not a valid cookie value: it must not contain whitespace, control characters, `\"`, `,`, `;`, `\\` or unclosed template variables.",
+            value
+        );
+        let mut cookie = format!("{}={}", name, value);
+        for attribute in attributes {
+            cookie.push_str("; ");
+            cookie.push_str(attribute.as_ref());
+        }
+        self.append_header(http::header::SET_COOKIE, cookie)
     }
 
-    /// Set the response body from a JSON-serializable value.
+    /// Add a `Set-Cookie` header to the response, setting a cookie with the specified `name`
+    /// and `value` and no attributes.
     ///
-    /// It sets "Content-Type" to "application/json".
-    pub fn set_body_json<B: Serialize>(mut self, body: B) -> Self {
-        let body = serde_json::to_vec(&body).expect("Failed to convert into body.");
-
-        self.body = Some(body);
-        self.mime = "application/json".to_string();
-        self
+    /// It is a shorthand for [`ResponseTemplate::add_cookie`] with an empty list of attributes,
+    /// and it will panic on the same invalid inputs.
+    pub fn add_cookie_pair(self, name: &str, value: &str) -> Self {
+        self.add_cookie(name, value, std::iter::empty::<&str>())
     }
 
-    /// Set the response body to a string.
+    /// Append a trailer `value` with `key` as name: trailers are sent after the body, e.g. to
+    /// communicate the outcome of a streaming call as gRPC does with `grpc-status`.
     ///
-    /// It sets "Content-Type" to "text/plain".
-    pub fn set_body_string<T>(mut self, body: T) -> Self
-    where
-        T: TryInto<String>,
-        <T as TryInto<String>>::Error: std::fmt::Debug,
-    {
-        let body = body.try_into().expect("Failed to convert into body.");
-
-        self.body = Some(body.into_bytes());
-        self.mime = "text/plain".to_string();
-        self
-    }
-
-    /// Set a raw response body. The mime type needs to be set because the
-    /// raw body could be of any type.
+    /// A `Trailer` header listing the names of the trailers is added to the response, unless
+    /// it was set explicitly. Over HTTP/1.1 the body is sent with the chunked transfer encoding,
+    /// since trailers cannot be sent alongside a `Content-Length`.
     ///
     /// ### Example:
     /// ```rust
-    /// use surf::http::mime;
     /// use wiremock::{MockServer, Mock, ResponseTemplate};
     /// use wiremock::matchers::method;
     ///
-    /// mod external {
-    ///     // This could be a method of a struct that is
-    ///     // implemented in another crate and the struct
-    ///     // does not implement Serialize.
-    ///     pub fn body() -> Vec<u8>{
-    ///         r#"{"hello": "world"}"#.as_bytes().to_owned()
-    ///     }
-    /// }
-    ///
     /// #[async_std::main]
     /// async fn main() {
     ///     // Arrange
     ///     let mock_server = MockServer::start().await;
-    ///     let template = ResponseTemplate::new(200).set_body_raw(
-    ///         external::body(),
-    ///         "application/json"
-    ///     );
-    ///     Mock::given(method("GET"))
+    ///     let template = ResponseTemplate::new(200)
+    ///         .set_body_raw(vec![0, 0, 0, 0, 0], "application/grpc")
+    ///         .append_trailer("grpc-status", "0")
+    ///         .append_trailer("grpc-message", "OK");
+    ///     Mock::given(method("POST"))
     ///         .respond_with(template)
     ///         .mount(&mock_server)
     ///         .await;
     ///
     ///     // Act
-    ///     let mut res = surf::get(&mock_server.uri())
-    ///         .await
-    ///         .unwrap();
-    ///     let body = res.body_string()
-    ///         .await
-    ///         .unwrap();
+    ///     let res = surf::post(&mock_server.uri()).await.unwrap();
     ///
     ///     // Assert
-    ///     assert_eq!(body, r#"{"hello": "world"}"#);
-    ///     assert_eq!(res.content_type(), Some(mime::JSON));
+    ///     assert_eq!(res.header("Trailer").unwrap(), "grpc-status, grpc-message");
     /// }
     /// ```
-    pub fn set_body_raw<B>(mut self, body: B, mime: &str) -> Self
+    pub fn append_trailer<K, V>(mut self, key: K, value: V) -> Self
     where
-        B: TryInto<Vec<u8>>,
-        <B as TryInto<Vec<u8>>>::Error: std::fmt::Debug,
+        K: TryInto<HeaderName>,
+        <K as TryInto<HeaderName>>::Error: std::fmt::Debug,
+        V: TryInto<HeaderValue>,
+        <V as TryInto<HeaderValue>>::Error: std::fmt::Debug,
     {
-        let body = body.try_into().expect("Failed to convert into body.");
-        self.body = Some(body);
-        self.mime = mime.to_string();
+        let key = key.try_into().expect("Failed to convert into header name.");
+        let value = value
+            .try_into()
+            .expect("Failed to convert into header value.");
+        self.trailers.append(key, value);
         self
     }
 
-    /// By default the [`MockServer`] tries to fulfill incoming requests as fast as possible.
+    /// Send an interim (`1xx`) response before this one - e.g. `103 Early Hints` - to test
+    /// how your client handles them.
     ///
-    /// You can use `set_delay` to introduce an artificial delay to simulate the behaviour of
-    /// a real server with a non-negligible latency.
+    /// Interim responses are sent in the order they were appended, as soon as the request has
+    /// been received: before the [delay](ResponseTemplate::set_delay) of the response.
+    /// They are only sent over HTTP/1.1 connections, and skipped otherwise.
     ///
-    /// In particular, you can use it to test the behaviour of your timeout policies.
+    /// `100 Continue` is already sent automatically to clients that ask for it with an
+    /// `Expect: 100-continue` header - use
+    /// [`reject_expect_continue`](ResponseTemplate::reject_expect_continue) or
+    /// [`delay_continue`](ResponseTemplate::delay_continue) to change that.
+    ///
+    /// It panics if `status` is not a `1xx` status code, or if it is `101 Switching Protocols`.
     ///
     /// ### Example:
     /// ```rust
     /// use wiremock::{MockServer, Mock, ResponseTemplate};
     /// use wiremock::matchers::method;
-    /// use std::time::Duration;
-    /// use async_std::prelude::FutureExt;
     ///
-    /// #[async_std::main]
+    /// #[tokio::main]
     /// async fn main() {
     ///     // Arrange
     ///     let mock_server = MockServer::start().await;
-    ///     let delay = Duration::from_secs(1);
-    ///     let template = ResponseTemplate::new(200).set_delay(delay);
+    ///     let template = ResponseTemplate::new(200)
+    ///         .append_interim_response(103, [("link", "</style.css>; rel=preload; as=style")])
+    ///         .set_body_string("<html></html>");
     ///     Mock::given(method("GET"))
     ///         .respond_with(template)
     ///         .mount(&mock_server)
     ///         .await;
     ///
     ///     // Act
-    ///     let mut res = async_std::future::timeout(
-    ///         // Shorter than the response delay!
-    ///         delay / 3,
-    ///         surf::get(&mock_server.uri())
-    ///     )
-    ///     .await;
+    ///     let res = reqwest::get(&mock_server.uri()).await.unwrap();
     ///
-    ///     // Assert - Timeout error!
-    ///     assert!(res.is_err());
+    ///     // Assert - the client skipped the interim response
+    ///     assert_eq!(res.status(), 200);
     /// }
     /// ```
-    ///
-    /// [`MockServer`]: crate::mock_server::MockServer
-    pub fn set_delay(mut self, delay: Duration) -> Self {
-        self.delay = Some(delay);
-
-        self
-    }
-
-    /// Generate a response from the template.
-    pub(crate) fn generate_response(&self) -> Response<Full<Bytes>> {
-        let mut response = Response::builder().status(self.status_code);
-
-        let mut headers = self.headers.clone();
-        // Set content-type, if needed
-        if !self.mime.is_empty() {
-            headers.insert(http::header::CONTENT_TYPE, self.mime.parse().unwrap());
+    pub fn append_interim_response<S, K, V, I>(mut self, status: S, headers: I) -> Self
+    where
+        S: TryInto<StatusCode>,
+        <S as TryInto<StatusCode>>::Error: std::fmt::Debug,
+        K: TryInto<HeaderName>,
+        <K as TryInto<HeaderName>>::Error: std::fmt::Debug,
+        V: TryInto<HeaderValue>,
+        <V as TryInto<HeaderValue>>::Error: std::fmt::Debug,
+        I: IntoIterator<Item = (K, V)>,
+    {
+        let status = status
+            .try_into()
+            .expect("Failed to convert into status code.");
+        assert!(
+            status.is_informational() && status != StatusCode::SWITCHING_PROTOCOLS,
+            "{} is not a valid interim response status code.",
+            status
+        );
+        let mut header_map = HeaderMap::new();
+        for (key, value) in headers {
+            let key = key.try_into().expect("Failed to convert into header name.");
+            let value = value
+                .try_into()
+                .expect("Failed to convert into header value.");
+            header_map.append(key, value);
         }
-        *response.headers_mut().unwrap() = headers;
-
-        let body = self.body.clone().unwrap_or_default();
-        response.body(body.into()).unwrap()
+        self.interim_responses.push((status, header_map));
+        self
     }
 
-    /// Retrieve the response delay.
-    pub(crate) fn delay(&self) -> &Option<Duration> {
-        &self.delay
+    /// Refuse the body of the requests asking for confirmation before sending it, with an
+    /// `Expect: 100-continue` header: respond with `status` - typically
+    /// `417 Expectation Failed` - instead of `100 Continue`, to test the unhappy path of
+    /// upload clients implementing the handshake.
+    ///
+    /// The decision is taken before the body is received: the mock is selected using the
+    /// method, the URL and the headers of the request, and body matchers do not match.
+    /// The request is recorded without its body and does not count towards the expectations
+    /// of the mock. The connection is closed after the rejection, as the body is not read.
+    ///
+    /// It only applies to HTTP/1.1 requests and to templates passed directly to
+    /// [`MockBuilder::respond_with`](crate::MockBuilder::respond_with). Requests without an
+    /// `Expect: 100-continue` header get the response as usual.
+    ///
+    /// It panics if `status` is a `1xx` status code.
+    ///
+    /// ### Example:
+    /// ```rust
+    /// use wiremock::{MockServer, Mock, ResponseTemplate};
+    /// use wiremock::matchers::method;
+    /// use std::io::{Read, Write};
+    /// use std::net::TcpStream;
+    ///
+    /// #[async_std::main]
+    /// async fn main() {
+    ///     // Arrange
+    ///     let mock_server = MockServer::start().await;
+    ///     Mock::given(method("PUT"))
+    ///         .respond_with(ResponseTemplate::new(201).reject_expect_continue(417))
+    ///         .mount(&mock_server)
+    ///         .await;
+    ///
+    ///     // Act - announce a body, and wait for the go-ahead before sending it
+    ///     let mut stream = TcpStream::connect(mock_server.address()).unwrap();
+    ///     stream
+    ///         .write_all(b"PUT /upload HTTP/1.1\r\nHost: localhost\r\nContent-Length: 1000000\r\nExpect: 100-continue\r\n\r\n")
+    ///         .unwrap();
+    ///     let mut response = String::new();
+    ///     stream.read_to_string(&mut response).unwrap();
+    ///
+    ///     // Assert
+    ///     assert!(response.starts_with("HTTP/1.1 417 Expectation Failed\r\n"));
+    /// }
+    /// ```
+    pub fn reject_expect_continue<S>(mut self, status: S) -> Self
+    where
+        S: TryInto<StatusCode>,
+        <S as TryInto<StatusCode>>::Error: std::fmt::Debug,
+    {
+        let status = status
+            .try_into()
+            .expect("Failed to convert into status code.");
+        assert!(
+            !status.is_informational(),
+            "{} cannot be used to reject a request body.",
+            status
+        );
+        self.expect_continue = Some(ExpectContinue::Reject(status));
+        self
+    }
+
+    /// Wait for `delay` before sending `100 Continue` to the requests with an
+    /// `Expect: 100-continue` header - e.g. to check that your client sends the body anyway
+    /// once it gets tired of waiting, as it should.
+    ///
+    /// The response is generated as usual once the body has been received. The same
+    /// restrictions as for [`reject_expect_continue`](ResponseTemplate::reject_expect_continue)
+    /// apply.
+    pub fn delay_continue(mut self, delay: Duration) -> Self {
+        self.expect_continue = Some(ExpectContinue::Delay(delay));
+        self
+    }
+
+    /// Set the response body with bytes.
+    ///
+    /// It sets "Content-Type" to "application/octet-stream".
+    ///
+    /// To set a body with bytes but a different "Content-Type"
+    /// [`set_body_raw`](#method.set_body_raw) can be used.
+    pub fn set_body_bytes<B>(mut self, body: B) -> Self
+    where
+        B: TryInto<Vec<u8>>,
+        <B as TryInto<Vec<u8>>>::Error: std::fmt::Debug,
+    {
+        let body = body.try_into().expect("Failed to convert into body.");
+        self.body = Some(Body::Bytes(body));
+        self
+    }
+
+    /// Set the response body from a JSON-serializable value.
+    ///
+    /// It sets "Content-Type" to "application/json", unless a different "Content-Type" has
+    /// been set explicitly - see [`set_content_type`](#method.set_content_type).
+    pub fn set_body_json<B: Serialize>(mut self, body: B) -> Self {
+        let body = serde_json::to_vec(&body).expect("Failed to convert into body.");
+
+        self.body = Some(Body::Bytes(body));
+        self.mime = "application/json".to_string();
+        self
+    }
+
+    /// Set the response body from a value serialized as MessagePack, using
+    /// [`rmp_serde::to_vec_named`]: structs are encoded as maps.
+    ///
+    /// It sets "Content-Type" to "application/msgpack", unless a different "Content-Type" has
+    /// been set explicitly - see [`set_content_type`](#method.set_content_type).
+    ///
+    /// This method is only available if the `msgpack` feature is enabled.
+    #[cfg(feature = "msgpack")]
+    pub fn set_body_msgpack<B: Serialize>(mut self, body: B) -> Self {
+        let body = rmp_serde::to_vec_named(&body).expect("Failed to convert into body.");
+
+        self.body = Some(Body::Bytes(body));
+        self.mime = "application/msgpack".to_string();
+        self
+    }
+
+    /// Set the response body from a value serialized as CBOR ([RFC 8949](https://www.rfc-editor.org/rfc/rfc8949)).
+    ///
+    /// It sets "Content-Type" to "application/cbor", unless a different "Content-Type" has
+    /// been set explicitly - see [`set_content_type`](#method.set_content_type).
+    ///
+    /// This method is only available if the `cbor` feature is enabled.
+    #[cfg(feature = "cbor")]
+    pub fn set_body_cbor<B: Serialize>(mut self, body: B) -> Self {
+        let mut bytes = vec![];
+        ciborium::into_writer(&body, &mut bytes).expect("Failed to convert into body.");
+
+        self.body = Some(Body::Bytes(bytes));
+        self.mime = "application/cbor".to_string();
+        self
+    }
+
+    /// Set the response body to a sequence of JSON values, one per line - the
+    /// [NDJSON](https://github.com/ndjson/ndjson-spec) (or [JSON Lines](https://jsonlines.org/))
+    /// format used by streaming APIs and bulk exports.
+    ///
+    /// It sets "Content-Type" to "application/x-ndjson", unless a different "Content-Type" has
+    /// been set explicitly - see [`set_content_type`](#method.set_content_type).
+    /// Combine it with [`set_line_delay`](#method.set_line_delay) to send the lines one at a
+    /// time, to test clients parsing the body incrementally.
+    ///
+    /// ### Example:
+    /// ```rust
+    /// use wiremock::{MockServer, Mock, ResponseTemplate};
+    /// use wiremock::matchers::path;
+    /// use serde_json::{json, Value};
+    /// use std::time::Duration;
+    ///
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     // Arrange
+    ///     let mock_server = MockServer::start().await;
+    ///     let template = ResponseTemplate::new(200)
+    ///         .set_body_ndjson((1..=3).map(|id| json!({ "id": id })))
+    ///         .set_line_delay(Duration::from_millis(50));
+    ///     Mock::given(path("/export"))
+    ///         .respond_with(template)
+    ///         .mount(&mock_server)
+    ///         .await;
+    ///
+    ///     // Act
+    ///     let mut response = reqwest::get(format!("{}/export", mock_server.uri()))
+    ///         .await
+    ///         .unwrap();
+    ///     let mut chunks = vec![];
+    ///     while let Some(chunk) = response.chunk().await.unwrap() {
+    ///         chunks.push(serde_json::from_slice::<Value>(&chunk).unwrap());
+    ///     }
+    ///
+    ///     // Assert - each line arrived on its own
+    ///     assert_eq!(chunks, vec![json!({ "id": 1 }), json!({ "id": 2 }), json!({ "id": 3 })]);
+    /// }
+    /// ```
+    pub fn set_body_ndjson<I>(mut self, lines: I) -> Self
+    where
+        I: IntoIterator,
+        I::Item: Serialize,
+    {
+        let mut body = vec![];
+        for line in lines {
+            serde_json::to_writer(&mut body, &line).expect("Failed to convert into body.");
+            body.push(b'\n');
+        }
+
+        self.body = Some(Body::Bytes(body));
+        self.mime = "application/x-ndjson".to_string();
+        self
+    }
+
+    /// Send each line of the body in its own chunk, pausing for `delay` before each of them
+    /// but the first - e.g. to simulate a server streaming [NDJSON](#method.set_body_ndjson)
+    /// records or log lines as they are produced.
+    ///
+    /// The status code and the headers are sent straight away (after the
+    /// [delay](ResponseTemplate::set_delay), if any). Lines end with `\n`: a body without any
+    /// newline is sent in one go.
+    pub fn set_line_delay(mut self, delay: Duration) -> Self {
+        self.line_delay = Some(delay);
+        self
+    }
+
+    /// Set the response body to a string.
+    ///
+    /// It sets "Content-Type" to "text/plain", unless a different "Content-Type" has
+    /// been set explicitly - see [`set_content_type`](#method.set_content_type).
+    pub fn set_body_string<T>(mut self, body: T) -> Self
+    where
+        T: TryInto<String>,
+        <T as TryInto<String>>::Error: std::fmt::Debug,
+    {
+        let body = body.try_into().expect("Failed to convert into body.");
+
+        self.body = Some(Body::Bytes(body.into_bytes()));
+        self.mime = "text/plain".to_string();
+        self
+    }
+
+    /// Set a raw response body. The mime type needs to be set because the
+    /// raw body could be of any type.
+    ///
+    /// It sets "Content-Type" to `mime`, unless a different "Content-Type" has been set
+    /// explicitly - see [`set_content_type`](#method.set_content_type).
+    ///
+    /// ### Example:
+    /// ```rust
+    /// use surf::http::mime;
+    /// use wiremock::{MockServer, Mock, ResponseTemplate};
+    /// use wiremock::matchers::method;
+    ///
+    /// mod external {
+    ///     // This could be a method of a struct that is
+    ///     // implemented in another crate and the struct
+    ///     // does not implement Serialize.
+    ///     pub fn body() -> Vec<u8>{
+    ///         r#"{"hello": "world"}"#.as_bytes().to_owned()
+    ///     }
+    /// }
+    ///
+    /// #[async_std::main]
+    /// async fn main() {
+    ///     // Arrange
+    ///     let mock_server = MockServer::start().await;
+    ///     let template = ResponseTemplate::new(200).set_body_raw(
+    ///         external::body(),
+    ///         "application/json"
+    ///     );
+    ///     Mock::given(method("GET"))
+    ///         .respond_with(template)
+    ///         .mount(&mock_server)
+    ///         .await;
+    ///
+    ///     // Act
+    ///     let mut res = surf::get(&mock_server.uri())
+    ///         .await
+    ///         .unwrap();
+    ///     let body = res.body_string()
+    ///         .await
+    ///         .unwrap();
+    ///
+    ///     // Assert
+    ///     assert_eq!(body, r#"{"hello": "world"}"#);
+    ///     assert_eq!(res.content_type(), Some(mime::JSON));
+    /// }
+    /// ```
+    pub fn set_body_raw<B>(mut self, body: B, mime: &str) -> Self
+    where
+        B: TryInto<Vec<u8>>,
+        <B as TryInto<Vec<u8>>>::Error: std::fmt::Debug,
+    {
+        let body = body.try_into().expect("Failed to convert into body.");
+        self.body = Some(Body::Bytes(body));
+        self.mime = mime.to_string();
+        self
+    }
+
+    /// Set the "Content-Type" of the response - e.g. `application/problem+json` or
+    /// `text/plain; charset=iso-8859-1`.
+    ///
+    /// An explicit "Content-Type" takes precedence over the one implied by the body setters,
+    /// regardless of the order they are called in. It is equivalent to calling
+    /// [`insert_header`](#method.insert_header) with the `Content-Type` header.
+    ///
+    /// ### Example:
+    /// ```rust
+    /// use wiremock::{MockServer, Mock, ResponseTemplate};
+    /// use wiremock::matchers::method;
+    /// use serde_json::json;
+    ///
+    /// #[async_std::main]
+    /// async fn main() {
+    ///     // Arrange
+    ///     let mock_server = MockServer::start().await;
+    ///     let template = ResponseTemplate::new(404)
+    ///         .set_content_type("application/problem+json")
+    ///         .set_body_json(json!({"title": "Not Found", "status": 404}));
+    ///     Mock::given(method("GET"))
+    ///         .respond_with(template)
+    ///         .mount(&mock_server)
+    ///         .await;
+    ///
+    ///     // Act
+    ///     let res = surf::get(&mock_server.uri()).await.unwrap();
+    ///
+    ///     // Assert
+    ///     assert_eq!(res.header("Content-Type").unwrap(), "application/problem+json");
+    /// }
+    /// ```
+    pub fn set_content_type<V>(self, content_type: V) -> Self
+    where
+        V: TryInto<HeaderValue>,
+        <V as TryInto<HeaderValue>>::Error: std::fmt::Debug,
+    {
+        self.insert_header(http::header::CONTENT_TYPE, content_type)
+    }
+
+    /// Add a `charset` parameter to the "Content-Type" of the response - e.g. `utf-8` or
+    /// `iso-8859-1` - replacing any existing one.
+    ///
+    /// It applies to both an explicit "Content-Type" and the one implied by the body setters.
+    /// The body is sent as is: it is up to you to encode it according to `charset`, using
+    /// [`set_body_bytes`](#method.set_body_bytes) if needed.
+    ///
+    /// ### Example:
+    /// ```rust
+    /// use wiremock::{MockServer, Mock, ResponseTemplate};
+    /// use wiremock::matchers::method;
+    ///
+    /// #[async_std::main]
+    /// async fn main() {
+    ///     // Arrange
+    ///     let mock_server = MockServer::start().await;
+    ///     let template = ResponseTemplate::new(200)
+    ///         .set_body_string("Hello world!")
+    ///         .set_charset("utf-8");
+    ///     Mock::given(method("GET"))
+    ///         .respond_with(template)
+    ///         .mount(&mock_server)
+    ///         .await;
+    ///
+    ///     // Act
+    ///     let res = surf::get(&mock_server.uri()).await.unwrap();
+    ///
+    ///     // Assert
+    ///     assert_eq!(res.header("Content-Type").unwrap(), "text/plain; charset=utf-8");
+    /// }
+    /// ```
+    pub fn set_charset<T: Into<String>>(mut self, charset: T) -> Self {
+        self.charset = Some(charset.into());
+        self
+    }
+
+    /// Set the response body to the content of the file at `path`.
+    ///
+    /// The file is read every time a response is generated, not when the template is built:
+    /// changes to the file are picked up by the following responses and large fixtures are
+    /// not loaded into memory upfront.
+    /// The whole file is then read into memory - on a blocking thread, not on the runtime of the
+    /// [`MockServer`] - before the response is sent: the body is not streamed from disk.
+    /// If the file cannot be read, the [`MockServer`] returns a `500 Internal Server Error`
+    /// response explaining what went wrong.
+    ///
+    /// "Content-Type" is inferred from the extension of the file - e.g. "application/json" for
+    /// `.json` files, "text/html" for `.html` files. It defaults to "application/octet-stream" if
+    /// the extension is unknown: use [`insert_header`](#method.insert_header) to override it.
+    ///
+    /// ### Example:
+    /// ```rust
+    /// use wiremock::{MockServer, Mock, ResponseTemplate};
+    /// use wiremock::matchers::method;
+    ///
+    /// #[async_std::main]
+    /// async fn main() {
+    ///     // Arrange
+    ///     let mock_server = MockServer::start().await;
+    ///     let template = ResponseTemplate::new(200).set_body_from_file("Cargo.toml");
+    ///     Mock::given(method("GET"))
+    ///         .respond_with(template)
+    ///         .mount(&mock_server)
+    ///         .await;
+    ///
+    ///     // Act
+    ///     let mut res = surf::get(&mock_server.uri())
+    ///         .await
+    ///         .unwrap();
+    ///
+    ///     // Assert
+    ///     let body = res.body_string().await.unwrap();
+    ///     assert!(body.contains("name = \"wiremock\""));
+    ///     assert_eq!(res.content_type().unwrap().essence(), "application/toml");
+    /// }
+    /// ```
+    ///
+    /// [`MockServer`]: crate::mock_server::MockServer
+    pub fn set_body_from_file<P: AsRef<Path>>(mut self, path: P) -> Self {
+        let path = path.as_ref().to_path_buf();
+        self.mime = mime_from_extension(&path).to_string();
+        self.body = Some(Body::File { path, json: false });
+        self
+    }
+
+    /// Set the response body to the content of the JSON file at `path`.
+    ///
+    /// Just like [`set_body_from_file`](#method.set_body_from_file), the file is read every time
+    /// a response is generated. On top of that, its content is checked to be valid JSON: if it
+    /// is not, a `500 Internal Server Error` response is returned.
+    ///
+    /// It sets "Content-Type" to "application/json", regardless of the extension of the file.
+    pub fn set_body_json_file<P: AsRef<Path>>(mut self, path: P) -> Self {
+        let path = path.as_ref().to_path_buf();
+        self.mime = "application/json".to_string();
+        self.body = Some(Body::File { path, json: true });
+        self
+    }
+
+    /// Ask the client to close the connection once the response has been received, by
+    /// setting the `Connection: close` header. The [`MockServer`] closes the connection on its
+    /// side as well: the client must open a new connection for its next request.
+    ///
+    /// It is a no-op for HTTP/2 connections, which do not support the `Connection` header.
+    ///
+    /// [`MockServer`]: crate::mock_server::MockServer
+    pub fn close_connection(self) -> Self {
+        self.insert_header(http::header::CONNECTION, "close")
+    }
+
+    /// Drop the connection right after sending the status code and the headers of the
+    /// response, without sending its body.
+    ///
+    /// It is a shorthand for [`close_connection_after_bytes(0)`](#method.close_connection_after_bytes).
+    pub fn drop_connection_after_headers(self) -> Self {
+        self.close_connection_after_bytes(0)
+    }
+
+    /// Drop the connection abruptly once the first `n` bytes of the body have been sent.
+    ///
+    /// The headers of the response - e.g. `Content-Length` - still describe the whole body:
+    /// use it to test how your client handles truncated responses, retrying the request or
+    /// resuming the download with a `Range` request.
+    /// Trailers are never sent. Over HTTP/2 the stream is reset instead.
+    ///
+    /// ### Example:
+    /// ```rust
+    /// use wiremock::{MockServer, Mock, ResponseTemplate};
+    /// use wiremock::matchers::method;
+    ///
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     // Arrange
+    ///     let mock_server = MockServer::start().await;
+    ///     let template = ResponseTemplate::new(200)
+    ///         .set_body_string("a very long download")
+    ///         .close_connection_after_bytes(6);
+    ///     Mock::given(method("GET"))
+    ///         .respond_with(template)
+    ///         .mount(&mock_server)
+    ///         .await;
+    ///
+    ///     // Act
+    ///     let res = reqwest::get(&mock_server.uri()).await.unwrap();
+    ///
+    ///     // Assert - the headers are received, but the body is incomplete
+    ///     assert_eq!(res.status(), 200);
+    ///     assert!(res.text().await.is_err());
+    /// }
+    /// ```
+    pub fn close_connection_after_bytes(mut self, n: usize) -> Self {
+        self.abort_after_bytes = Some(n);
+        self
+    }
+
+    /// Reset the HTTP/2 stream of the request with a `RST_STREAM` frame carrying `reason`,
+    /// instead of sending the response - to test how your client retries requests refused or
+    /// cancelled by the server. The other streams of the connection are not affected.
+    ///
+    /// Over HTTP/1.x, which has no streams, the connection is dropped instead.
+    ///
+    /// ### Example:
+    /// ```rust
+    /// use wiremock::{MockServer, Mock, ResponseTemplate, StreamReset};
+    /// use wiremock::matchers::method;
+    ///
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     // Arrange
+    ///     let mock_server = MockServer::start().await;
+    ///     Mock::given(method("GET"))
+    ///         .respond_with(ResponseTemplate::new(200).reset_stream(StreamReset::Cancel))
+    ///         .mount(&mock_server)
+    ///         .await;
+    ///
+    ///     // Act
+    ///     let client = reqwest::Client::builder()
+    ///         .http2_prior_knowledge()
+    ///         .build()
+    ///         .unwrap();
+    ///     let outcome = client.get(&mock_server.uri()).send().await;
+    ///
+    ///     // Assert
+    ///     assert!(outcome.is_err());
+    /// }
+    /// ```
+    pub fn reset_stream(mut self, reason: StreamReset) -> Self {
+        self.reset_stream = Some(reason);
+        self
+    }
+
+    /// Set an `ETag` header, computed from the body, and honour the conditional headers of
+    /// the requests - to test how your client caches responses.
+    ///
+    /// For each request:
+    /// - if its `If-None-Match` header lists the `ETag` of the response (or is `*`), a
+    ///   `304 Not Modified` without body is sent to `GET` and `HEAD` requests, a
+    ///   `412 Precondition Failed` to the others;
+    /// - otherwise, if it has no `If-None-Match` header, the response has a `Last-Modified`
+    ///   header and the `If-Modified-Since` header of the request is not older than it, a
+    ///   `304 Not Modified` is sent to `GET` and `HEAD` requests;
+    /// - otherwise the response is sent as usual.
+    ///
+    /// `ETag`s are compared with the weak comparison function: `W/"a"` matches `"a"`.
+    /// If you set the `ETag` header yourself, it is used instead of the computed one.
+    /// Only successful (`2xx`) responses are affected. Bodies read from a file - see
+    /// [`set_body_from_file`](#method.set_body_from_file) - are hashed as they are when the
+    /// response is generated.
+    ///
+    /// ### Example:
+    /// ```rust
+    /// use wiremock::{MockServer, Mock, ResponseTemplate};
+    /// use wiremock::matchers::method;
+    ///
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     // Arrange
+    ///     let mock_server = MockServer::start().await;
+    ///     Mock::given(method("GET"))
+    ///         .respond_with(ResponseTemplate::new(200).set_body_string("v1").with_etag())
+    ///         .mount(&mock_server)
+    ///         .await;
+    ///     let client = reqwest::Client::new();
+    ///
+    ///     // Act
+    ///     let first = client.get(&mock_server.uri()).send().await.unwrap();
+    ///     let etag = first.headers()["ETag"].clone();
+    ///     let second = client
+    ///         .get(&mock_server.uri())
+    ///         .header("If-None-Match", etag)
+    ///         .send()
+    ///         .await
+    ///         .unwrap();
+    ///
+    ///     // Assert
+    ///     assert_eq!(first.status(), 200);
+    ///     assert_eq!(second.status(), 304);
+    /// }
+    /// ```
+    pub fn with_etag(mut self) -> Self {
+        self.etag = true;
+        self
+    }
+
+    /// Send the body of the response at `bytes_per_sec` bytes per second at most, to simulate
+    /// a slow-but-alive server - e.g. to test download progress reporting, resumable
+    /// transfers or read timeouts.
+    ///
+    /// The status code and the headers are sent straight away (after the
+    /// [delay](ResponseTemplate::set_delay), if any), then the body follows in small chunks
+    /// (a tenth of `bytes_per_sec`), with a pause after each of them.
+    /// Each response is throttled on its own.
+    ///
+    /// It panics if `bytes_per_sec` is zero - use [`hang`](ResponseTemplate::hang) to
+    /// simulate a server that never responds.
+    ///
+    /// ### Example:
+    /// ```rust
+    /// use wiremock::{MockServer, Mock, ResponseTemplate};
+    /// use wiremock::matchers::method;
+    /// use std::time::{Duration, Instant};
+    ///
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     // Arrange
+    ///     let mock_server = MockServer::start().await;
+    ///     let template = ResponseTemplate::new(200)
+    ///         .set_body_bytes(vec![0; 2048])
+    ///         .throttle_bytes_per_sec(8192);
+    ///     Mock::given(method("GET"))
+    ///         .respond_with(template)
+    ///         .mount(&mock_server)
+    ///         .await;
+    ///
+    ///     // Act
+    ///     let start = Instant::now();
+    ///     let body = reqwest::get(&mock_server.uri()).await.unwrap().bytes().await.unwrap();
+    ///
+    ///     // Assert - 2 KiB at 8 KiB/s
+    ///     assert_eq!(body.len(), 2048);
+    ///     assert!(start.elapsed() >= Duration::from_millis(150));
+    /// }
+    /// ```
+    pub fn throttle_bytes_per_sec(mut self, bytes_per_sec: u64) -> Self {
+        assert!(
+            bytes_per_sec > 0,
+            "The throughput must be greater than zero."
+        );
+        self.throttle_bytes_per_sec = Some(bytes_per_sec);
+        self
+    }
+
+    /// Send the body of the response with the `chunked` transfer encoding over HTTP/1.1,
+    /// instead of a `Content-Length` header - e.g. to check that your client does not rely on
+    /// knowing the size of the body upfront.
+    ///
+    /// Over HTTP/2, the `Content-Length` header is omitted.
+    ///
+    /// ### Example:
+    /// ```rust
+    /// use wiremock::{MockServer, Mock, ResponseTemplate};
+    /// use wiremock::matchers::method;
+    ///
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     // Arrange
+    ///     let mock_server = MockServer::start().await;
+    ///     Mock::given(method("GET"))
+    ///         .respond_with(ResponseTemplate::new(200).set_body_string("hello").force_chunked())
+    ///         .mount(&mock_server)
+    ///         .await;
+    ///
+    ///     // Act
+    ///     let response = reqwest::get(&mock_server.uri()).await.unwrap();
+    ///
+    ///     // Assert
+    ///     assert_eq!(response.headers()["Transfer-Encoding"], "chunked");
+    ///     assert_eq!(response.content_length(), None);
+    ///     assert_eq!(response.text().await.unwrap(), "hello");
+    /// }
+    /// ```
+    pub fn force_chunked(mut self) -> Self {
+        self.framing = Some(Framing::Chunked);
+        self
+    }
+
+    /// Send the body of the response without a `Content-Length` header nor the `chunked`
+    /// transfer encoding: the end of the body is signalled by closing the connection, as
+    /// some legacy servers do.
+    ///
+    /// Over HTTP/1.1, the response is sent with an `HTTP/1.0` status line - the only version
+    /// the underlying server allows to delimit a body this way. Over HTTP/2, the
+    /// `Content-Length` header is omitted and the stream ends with the body.
+    pub fn omit_content_length(mut self) -> Self {
+        self.framing = Some(Framing::CloseDelimited);
+        self
+    }
+
+    /// Send a `Content-Length` header set to `length`, whatever the actual length of the
+    /// body - a deliberately broken response, to check how your client copes with it:
+    /// - if `length` is shorter than the body, the body is cut after `length` bytes;
+    /// - if `length` is longer than the body, the connection is closed once the body has been
+    ///   sent, before the announced length is reached.
+    ///
+    /// It only applies to HTTP/1.1: over HTTP/2, the body is sent with its actual length.
+    ///
+    /// ### Example:
+    /// ```rust
+    /// use wiremock::{MockServer, Mock, ResponseTemplate};
+    /// use wiremock::matchers::method;
+    ///
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     // Arrange
+    ///     let mock_server = MockServer::start().await;
+    ///     let template = ResponseTemplate::new(200)
+    ///         .set_body_string("hello")
+    ///         .override_content_length(100);
+    ///     Mock::given(method("GET"))
+    ///         .respond_with(template)
+    ///         .mount(&mock_server)
+    ///         .await;
+    ///
+    ///     // Act
+    ///     let response = reqwest::get(&mock_server.uri()).await.unwrap();
+    ///
+    ///     // Assert - the connection was closed before the whole body was received
+    ///     assert_eq!(response.content_length(), Some(100));
+    ///     assert!(response.bytes().await.is_err());
+    /// }
+    /// ```
+    pub fn override_content_length(mut self, length: u64) -> Self {
+        self.framing = Some(Framing::ContentLength(length));
+        self
+    }
+
+    /// Never respond: the connection is accepted and the request is read, but no response
+    /// is ever sent - like an unresponsive upstream.
+    ///
+    /// Unlike a very long [delay](ResponseTemplate::set_delay), it is not a matter of how
+    /// long your client is willing to wait: it is bound to give up on its own.
+    /// The connection stays open until the [`MockServer`] is [reset](crate::MockServer::reset),
+    /// returned to the pool or shut down: it is then closed without a response.
+    /// The request is still recorded, and it counts towards the expectations of the mock.
+    ///
+    /// Interim responses set with [`append_interim_response`](ResponseTemplate::append_interim_response)
+    /// are still sent.
+    ///
+    /// ### Example:
+    /// ```rust
+    /// use wiremock::{MockServer, Mock, ResponseTemplate};
+    /// use wiremock::matchers::method;
+    /// use std::time::Duration;
+    ///
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     // Arrange
+    ///     let mock_server = MockServer::start().await;
+    ///     Mock::given(method("GET"))
+    ///         .respond_with(ResponseTemplate::new(200).hang())
+    ///         .mount(&mock_server)
+    ///         .await;
+    ///
+    ///     // Act
+    ///     let client = reqwest::Client::builder()
+    ///         .timeout(Duration::from_millis(100))
+    ///         .build()
+    ///         .unwrap();
+    ///     let error = client.get(&mock_server.uri()).send().await.unwrap_err();
+    ///
+    ///     // Assert
+    ///     assert!(error.is_timeout());
+    /// }
+    /// ```
+    ///
+    /// [`MockServer`]: crate::mock_server::MockServer
+    pub fn hang(mut self) -> Self {
+        self.hang = true;
+        self
+    }
+
+    /// By default the [`MockServer`] tries to fulfill incoming requests as fast as possible.
+    ///
+    /// You can use `set_delay` to introduce an artificial delay to simulate the behaviour of
+    /// a real server with a non-negligible latency.
+    ///
+    /// In particular, you can use it to test the behaviour of your timeout policies.
+    /// Use a [`VirtualClock`](crate::VirtualClock) to avoid waiting for long delays to elapse.
+    ///
+    /// ### Example:
+    /// ```rust
+    /// use wiremock::{MockServer, Mock, ResponseTemplate};
+    /// use wiremock::matchers::method;
+    /// use std::time::Duration;
+    /// use async_std::prelude::FutureExt;
+    ///
+    /// #[async_std::main]
+    /// async fn main() {
+    ///     // Arrange
+    ///     let mock_server = MockServer::start().await;
+    ///     let delay = Duration::from_secs(1);
+    ///     let template = ResponseTemplate::new(200).set_delay(delay);
+    ///     Mock::given(method("GET"))
+    ///         .respond_with(template)
+    ///         .mount(&mock_server)
+    ///         .await;
+    ///
+    ///     // Act
+    ///     let mut res = async_std::future::timeout(
+    ///         // Shorter than the response delay!
+    ///         delay / 3,
+    ///         surf::get(&mock_server.uri())
+    ///     )
+    ///     .await;
+    ///
+    ///     // Assert - Timeout error!
+    ///     assert!(res.is_err());
+    /// }
+    /// ```
+    ///
+    /// [`MockServer`]: crate::mock_server::MockServer
+    pub fn set_delay(mut self, delay: Duration) -> Self {
+        self.delay = Some(delay);
+
+        self
+    }
+
+    /// Render the template variables in the body and in the header values of the response,
+    /// every time a response is generated.
+    ///
+    /// Use it when responses must contain timestamps relative to the current time - e.g. the
+    /// expiry of a token - or unique identifiers, rather than values baked into your fixtures.
+    /// The following variables are supported:
+    ///
+    /// - `{{now}}`: the current time, as an RFC 3339 date-time (e.g. `2024-02-29T12:34:56Z`) -
+    ///   or the time set with [`MockServerBuilder::with_fixed_time`](crate::MockServerBuilder::with_fixed_time).
+    ///   It accepts two optional arguments:
+    ///   - `offset`: shift the time, e.g. `offset='-5m'` or `offset='+1h30m'`.
+    ///     Supported units are `ms`, `s`, `m`, `h` and `d`;
+    ///   - `format`: one of `rfc3339` (the default), `rfc3339_millis`, `http` (the format of
+    ///     the `Date` header, e.g. `Thu, 29 Feb 2024 12:34:56 GMT`), `epoch` (seconds since the
+    ///     Unix epoch) and `epoch_millis`.
+    /// - `{{random uuid}}`, `{{random int <min> <max>}}` (both bounds included),
+    ///   `{{random alphanumeric <length>}}` and `{{random hex <length>}}`: random-looking values,
+    ///   reproducible across runs if the [`MockServer`] was started with a
+    ///   [seed](crate::MockServerBuilder::random_seed) - see [`Random`].
+    /// - `{{base_url}}`: the base URL of the [`MockServer`] listener that received the request,
+    ///   e.g. `http://127.0.0.1:4372` - handy for `Location` headers and pagination links.
+    ///   See [`insert_header_templated`](#method.insert_header_templated) to render it in
+    ///   a single header.
+    ///
+    /// Placeholders referring to other variables are left untouched, as well as bodies that
+    /// are not valid UTF-8. If a placeholder is invalid, the [`MockServer`] returns a
+    /// `500 Internal Server Error` response explaining what went wrong.
+    ///
+    /// ### Example:
+    /// ```rust
+    /// use wiremock::{MockServer, Mock, ResponseTemplate};
+    /// use wiremock::matchers::method;
+    /// use serde_json::{json, Value};
+    /// use std::time::{SystemTime, UNIX_EPOCH};
+    ///
+    /// #[async_std::main]
+    /// async fn main() {
+    ///     // Arrange
+    ///     let mock_server = MockServer::start().await;
+    ///     let template = ResponseTemplate::new(200)
+    ///         .set_body_json(json!({
+    ///             "access_token": "secret",
+    ///             "expires_at": "{{now offset='+1h' format='epoch'}}"
+    ///         }))
+    ///         .insert_header("Last-Modified", "{{now offset='-1d' format='http'}}")
+    ///         .render_templates();
+    ///     Mock::given(method("POST"))
+    ///         .respond_with(template)
+    ///         .mount(&mock_server)
+    ///         .await;
+    ///
+    ///     // Act
+    ///     let mut res = surf::post(&mock_server.uri()).await.unwrap();
+    ///
+    ///     // Assert
+    ///     assert!(res.header("Last-Modified").unwrap().as_str().ends_with(" GMT"));
+    ///     let body: Value = res.body_json().await.unwrap();
+    ///     let expires_at: u64 = body["expires_at"].as_str().unwrap().parse().unwrap();
+    ///     let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs();
+    ///     assert!(expires_at > now + 3500);
+    /// }
+    /// ```
+    ///
+    /// [`MockServer`]: crate::mock_server::MockServer
+    pub fn render_templates(mut self) -> Self {
+        self.render_templates = true;
+        self
+    }
+
+    /// Set the `Date` header of the response to the current time, shifted by `offset` - e.g.
+    /// `-5m` or `+1h30m`, with the same syntax used by the `offset` argument of the `{{now}}`
+    /// template variable (see [`render_templates`](#method.render_templates)).
+    ///
+    /// Use it to simulate a server whose clock is skewed with respect to yours.
+    /// Without an offset, the [`MockServer`] sets the `Date` header to the current time - or to
+    /// the time set with [`MockServerBuilder::with_fixed_time`](crate::MockServerBuilder::with_fixed_time).
+    ///
+    /// It panics if `offset` is not valid.
+    ///
+    /// ### Example:
+    /// ```rust
+    /// use wiremock::{MockServer, Mock, ResponseTemplate};
+    /// use wiremock::matchers::method;
+    ///
+    /// #[async_std::main]
+    /// async fn main() {
+    ///     // Arrange
+    ///     let mock_server = MockServer::start().await;
+    ///     let template = ResponseTemplate::new(200).set_date_offset("-10m");
+    ///     Mock::given(method("GET"))
+    ///         .respond_with(template)
+    ///         .mount(&mock_server)
+    ///         .await;
+    ///
+    ///     // Act
+    ///     let res = surf::get(&mock_server.uri()).await.unwrap();
+    ///
+    ///     // Assert
+    ///     let date = res.header("Date").unwrap().as_str();
+    ///     assert!(date.ends_with(" GMT"));
+    /// }
+    /// ```
+    ///
+    /// [`MockServer`]: crate::mock_server::MockServer
+    pub fn set_date_offset<T: Into<String>>(mut self, offset: T) -> Self {
+        let offset = offset.into();
+        if let Err(e) = templating::apply_offset(SystemTime::now(), &offset) {
+            panic!("{}", e);
+        }
+        self.date_offset = Some(offset);
+        self
+    }
+
+    /// Generate a response from the template, drawing the values of the `{{random ..}}`
+    /// template variables from a randomly seeded source.
+    pub(crate) fn generate_response(&self) -> Response<ResponseBody> {
+        self.generate_seeded_response(&Random::unseeded(), None, None)
+    }
+
+    /// Generate a response from the template, drawing the values of the `{{random ..}}`
+    /// template variables from `random` and rendering `{{base_url}}` as `base_url`, if known.
+    ///
+    /// If `fixed_time` is set, it is used in place of the current time - including for the
+    /// `Date` header.
+    pub(crate) fn generate_seeded_response(
+        &self,
+        random: &Random,
+        base_url: Option<&str>,
+        fixed_time: Option<SystemTime>,
+    ) -> Response<ResponseBody> {
+        match self.try_generate_response(random, base_url, fixed_time) {
+            Ok(response) => response,
+            Err(e) => {
+                tracing::error!("{}", e);
+                Response::builder()
+                    .status(StatusCode::INTERNAL_SERVER_ERROR)
+                    .header(http::header::CONTENT_TYPE, "text/plain")
+                    .body(e.into())
+                    .unwrap()
+            }
+        }
+    }
+
+    fn try_generate_response(
+        &self,
+        random: &Random,
+        base_url: Option<&str>,
+        fixed_time: Option<SystemTime>,
+    ) -> Result<Response<ResponseBody>, String> {
+        let now = fixed_time.unwrap_or_else(SystemTime::now);
+        let mut response = Response::builder().status(self.status_code);
+
+        let mut headers = self.headers.clone();
+        // Set content-type, if needed
+        if !self.mime.is_empty() && !headers.contains_key(http::header::CONTENT_TYPE) {
+            headers.insert(http::header::CONTENT_TYPE, self.mime.parse().unwrap());
+        }
+        if let Some(charset) = &self.charset {
+            if let Some(content_type) = headers.get(http::header::CONTENT_TYPE) {
+                let content_type = with_charset(content_type.to_str().unwrap_or_default(), charset);
+                let content_type = content_type.parse().map_err(|_| {
+                    format!("wiremock: `{}` is not a valid Content-Type", content_type)
+                })?;
+                headers.insert(http::header::CONTENT_TYPE, content_type);
+            }
+        }
+        if let Some(offset) = &self.date_offset {
+            let date = http_date(templating::apply_offset(now, offset)?);
+            headers.insert(http::header::DATE, date.parse().unwrap());
+        } else if fixed_time.is_some() && !headers.contains_key(http::header::DATE) {
+            // Otherwise `hyper` sets it to the current time.
+            headers.insert(http::header::DATE, http_date(now).parse().unwrap());
+        }
+        for (name, value) in headers.iter_mut() {
+            if !self.render_templates && !self.templated_headers.contains(name) {
+                continue;
+            }
+            if let Ok(template) = value.to_str() {
+                let rendered = render_template(template, now, random, base_url)?;
+                *value = rendered.parse().map_err(|_| {
+                    format!(
+                        "wiremock: `{}` is not a valid header value once rendered",
+                        rendered
+                    )
+                })?;
+            }
+        }
+        if !self.trailers.is_empty() && !headers.contains_key(http::header::TRAILER) {
+            let names: Vec<&str> = self.trailers.keys().map(HeaderName::as_str).collect();
+            headers.insert(http::header::TRAILER, names.join(", ").parse().unwrap());
+        }
+        *response.headers_mut().unwrap() = headers;
+
+        let mut body = match &self.body {
+            None => vec![],
+            Some(Body::Bytes(body)) => body.clone(),
+            Some(Body::File { path, json }) => read_body_file(path, *json)?,
+            Some(Body::Unreadable(e)) => return Err(e.clone()),
+        };
+        if self.render_templates {
+            if let Ok(template) = std::str::from_utf8(&body) {
+                body = render_template(template, now, random, base_url)?.into_bytes();
+            }
+        }
+        let mut body = ResponseBody::from(body);
+        if !self.trailers.is_empty() {
+            body = body.with_trailers(self.trailers.clone());
+        }
+        Ok(response.body(body).unwrap())
+    }
+
+    /// Retrieve the status code of the response.
+    pub(crate) fn status(&self) -> StatusCode {
+        self.status_code
+    }
+
+    /// Retrieve the response delay.
+    pub(crate) fn delay(&self) -> &Option<Duration> {
+        &self.delay
+    }
+
+    /// Retrieve the number of bytes of the body to send before dropping the connection.
+    pub(crate) fn abort_after_bytes(&self) -> Option<usize> {
+        self.abort_after_bytes
+    }
+
+    /// Retrieve the interim responses to send before the response.
+    pub(crate) fn interim_responses(&self) -> &[(StatusCode, HeaderMap)] {
+        &self.interim_responses
+    }
+
+    /// Drop the connection instead of sending the response, once the delay has elapsed.
+    pub(crate) fn drop_connection(mut self) -> Self {
+        self.drop_connection = true;
+        self
+    }
+
+    /// Adapt the response to the conditional and range headers of the request - see
+    /// [`ResponseTemplate::with_etag`] and [`ResponseTemplate::respond_with_range_of`].
+    ///
+    /// A body read from a file must have been loaded first - see
+    /// [`ResponseTemplate::load_body_file`].
+    pub(crate) fn negotiate(self, method: &Method, headers: &HeaderMap) -> Self {
+        self.evaluate_preconditions(method, headers)
+            .select_range(headers.get(http::header::RANGE))
+    }
+
+    /// Whether the body has to be read from a file - see [`ResponseTemplate::load_body_file`].
+    pub(crate) fn has_body_file(&self) -> bool {
+        matches!(self.body, Some(Body::File { .. }))
+    }
+
+    /// Read the body from its file, on a blocking thread: the file is then read once for the
+    /// whole response, whether it is needed to compute the `ETag`, to select a range or just
+    /// to be sent.
+    /// If it cannot be read, the error is reported when the response is generated.
+    pub(crate) async fn load_body_file(mut self) -> Self {
+        if let Some(Body::File { path, json }) = &self.body {
+            let (path, json) = (path.clone(), *json);
+            let read = tokio::task::spawn_blocking(move || read_body_file(&path, json)).await;
+            self.body = Some(match read {
+                Ok(Ok(body)) => Body::Bytes(body),
+                Ok(Err(e)) => Body::Unreadable(e),
+                Err(e) => Body::Unreadable(format!(
+                    "wiremock: failed to read the response body from a file: {}",
+                    e
+                )),
+            });
+        }
+        self
+    }
+
+    /// Set the `ETag` header and answer with a `304 Not Modified` (or a
+    /// `412 Precondition Failed`) if the preconditions of the request are not met.
+    fn evaluate_preconditions(mut self, method: &Method, headers: &HeaderMap) -> Self {
+        if !self.etag || !self.status_code.is_success() {
+            return self;
+        }
+        let body = match &self.body {
+            Some(Body::Bytes(body)) => body.as_slice(),
+            None => &[],
+            // The file could not be read: the response is a `500 Internal Server Error`.
+            Some(Body::File { .. } | Body::Unreadable(_)) => return self,
+        };
+        if !self.headers.contains_key(http::header::ETAG) {
+            let etag = format!("\"{:016x}\"", fnv1a(body));
+            self = self.insert_header(http::header::ETAG, etag);
+        }
+        let is_safe = method == Method::GET || method == Method::HEAD;
+        let not_modified = match headers.get(http::header::IF_NONE_MATCH) {
+            Some(if_none_match) => {
+                let etag = &self.headers[http::header::ETAG];
+                let matches = etag_matches(if_none_match, etag);
+                if matches && !is_safe {
+                    self.status_code = StatusCode::PRECONDITION_FAILED;
+                    self.body = None;
+                    return self;
+                }
+                matches
+            }
+            // `If-Modified-Since` is ignored when `If-None-Match` is present.
+            None => {
+                let since = headers
+                    .get(http::header::IF_MODIFIED_SINCE)
+                    .and_then(|value| value.to_str().ok())
+                    .and_then(parse_http_date);
+                let last_modified = self
+                    .headers
+                    .get(http::header::LAST_MODIFIED)
+                    .and_then(|value| value.to_str().ok())
+                    .and_then(parse_http_date);
+                match (since, last_modified) {
+                    (Some(since), Some(last_modified)) => is_safe && last_modified <= since,
+                    _ => false,
+                }
+            }
+        };
+        if not_modified {
+            self.status_code = StatusCode::NOT_MODIFIED;
+            self.body = None;
+            self.serve_ranges = false;
+        }
+        self
+    }
+
+    /// Keep the part of the body requested by a `range` header - see
+    /// [`ResponseTemplate::respond_with_range_of`].
+    fn select_range(mut self, range: Option<&HeaderValue>) -> Self {
+        if !self.serve_ranges || self.status_code != StatusCode::OK {
+            return self;
+        }
+        let range = match range
+            .and_then(|range| range.to_str().ok())
+            .and_then(ByteRange::parse)
+        {
+            Some(range) => range,
+            None => return self,
+        };
+        let body = match &self.body {
+            Some(Body::Bytes(body)) => body,
+            None => &Vec::new(),
+            // The file could not be read: the response is a `500 Internal Server Error`.
+            Some(Body::File { .. } | Body::Unreadable(_)) => return self,
+        };
+        let length = body.len() as u64;
+        match range.resolve(length) {
+            Some((first, last)) => {
+                let part = body[first as usize..=last as usize].to_vec();
+                self.body = Some(Body::Bytes(part));
+                self.status_code = StatusCode::PARTIAL_CONTENT;
+                self.insert_header(
+                    http::header::CONTENT_RANGE,
+                    format!("bytes {}-{}/{}", first, last, length),
+                )
+            }
+            None => {
+                self.body = None;
+                self.status_code = StatusCode::RANGE_NOT_SATISFIABLE;
+                self.insert_header(http::header::CONTENT_RANGE, format!("bytes */{}", length))
+            }
+        }
+    }
+
+    /// Retrieve the pause between the lines of the body, if any.
+    pub(crate) fn line_delay(&self) -> Option<Duration> {
+        self.line_delay
+    }
+
+    /// Retrieve the maximum throughput of the body, if any.
+    pub(crate) fn throttle(&self) -> Option<u64> {
+        self.throttle_bytes_per_sec
+    }
+
+    /// Whether the response should never be sent.
+    pub(crate) fn hangs(&self) -> bool {
+        self.hang
+    }
+
+    /// Whether the connection should be dropped instead of sending the response.
+    pub(crate) fn drops_connection(&self) -> bool {
+        self.drop_connection
+    }
+
+    /// Retrieve the error code to reset the HTTP/2 stream with, if set.
+    pub(crate) fn stream_reset(&self) -> Option<StreamReset> {
+        self.reset_stream
+    }
+
+    /// Retrieve how requests with an `Expect: 100-continue` header are handled, if set.
+    pub(crate) fn expect_continue(&self) -> Option<ExpectContinue> {
+        self.expect_continue
+    }
+
+    /// A template writing `bytes` to the socket instead of a response.
+    pub(crate) fn raw(bytes: Vec<u8>) -> Self {
+        let mut template = Self::new(200);
+        template.raw_response = Some(bytes);
+        template
+    }
+
+    /// Retrieve the bytes to write to the socket instead of the response, if any.
+    pub(crate) fn raw_response(&self) -> Option<&[u8]> {
+        self.raw_response.as_deref()
+    }
+
+    /// Apply the framing set with [`ResponseTemplate::force_chunked`],
+    /// [`ResponseTemplate::omit_content_length`] or
+    /// [`ResponseTemplate::override_content_length`] to a response sent over `version`.
+    pub(crate) fn frame(
+        &self,
+        response: Response<ResponseBody>,
+        version: http::Version,
+    ) -> Response<ResponseBody> {
+        let framing = match self.framing {
+            Some(framing) => framing,
+            None => return response,
+        };
+        // The server derives the framing from the headers when the length of the body is
+        // unknown.
+        let mut response = response.map(ResponseBody::unknown_length);
+        let headers = response.headers_mut();
+        headers.remove(http::header::CONTENT_LENGTH);
+        headers.remove(http::header::TRANSFER_ENCODING);
+        match framing {
+            Framing::Chunked => {}
+            Framing::CloseDelimited => {
+                if version == http::Version::HTTP_11 {
+                    *response.version_mut() = http::Version::HTTP_10;
+                }
+            }
+            Framing::ContentLength(length) => {
+                if version < http::Version::HTTP_2 {
+                    response
+                        .headers_mut()
+                        .insert(http::header::CONTENT_LENGTH, length.into());
+                }
+            }
+        }
+        response
+    }
+}
+
+/// The 64-bit [FNV-1a](http://www.isthe.com/chongo/tech/comp/fnv/) hash of `bytes`: stable
+/// across runs and platforms, unlike the hashers of the standard library.
+pub(crate) fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+        (hash ^ u64::from(*byte)).wrapping_mul(0x0100_0000_01b3)
+    })
+}
+
+/// Whether the `If-None-Match` header of a request lists `etag`, using the weak comparison
+/// function.
+fn etag_matches(if_none_match: &HeaderValue, etag: &HeaderValue) -> bool {
+    let (if_none_match, etag) = match (if_none_match.to_str(), etag.to_str()) {
+        (Ok(if_none_match), Ok(etag)) => (if_none_match, etag),
+        _ => return false,
+    };
+    let weak = |tag: &str| tag.trim().trim_start_matches("W/").to_owned();
+    if_none_match
+        .split(',')
+        .any(|tag| tag.trim() == "*" || weak(tag) == weak(etag))
+}
+
+/// Replace the `charset` parameter of `content_type`, or add it if missing.
+fn with_charset(content_type: &str, charset: &str) -> String {
+    let parameters: Vec<&str> = content_type
+        .split(';')
+        .filter(|p| !p.trim().to_ascii_lowercase().starts_with("charset="))
+        .collect();
+    format!("{}; charset={}", parameters.join(";").trim_end(), charset)
+}
+
+/// A `200 OK` response, with no headers and an empty body.
+impl Default for ResponseTemplate {
+    fn default() -> Self {
+        Self::new(StatusCode::OK)
+    }
+}
+
+fn render_template(
+    template: &str,
+    now: SystemTime,
+    random: &Random,
+    base_url: Option<&str>,
+) -> Result<String, String> {
+    templating::render(template, now, random, base_url)
+        .map_err(|e| format!("wiremock: failed to render the response template: {}", e))
+}
+
+/// Read the body of a response from a file, checking that it is valid JSON if `json` is set.
+fn read_body_file(path: &Path, json: bool) -> Result<Vec<u8>, String> {
+    let body = std::fs::read(path).map_err(|e| {
+        format!(
+            "wiremock: failed to read the response body from `{}`: {}",
+            path.display(),
+            e
+        )
+    })?;
+    if json {
+        if let Err(e) = serde_json::from_slice::<serde::de::IgnoredAny>(&body) {
+            return Err(format!(
+                "wiremock: the response body in `{}` is not valid JSON: {}",
+                path.display(),
+                e
+            ));
+        }
+    }
+    Ok(body)
+}
+
+/// Guess the mime type of a file from its extension.
+fn mime_from_extension(path: &Path) -> &'static str {
+    let extension = path
+        .extension()
+        .and_then(|e| e.to_str())
+        .map(|e| e.to_ascii_lowercase());
+    match extension.as_deref() {
+        Some("json") => "application/json",
+        Some("xml") => "application/xml",
+        Some("yaml") | Some("yml") => "application/yaml",
+        Some("toml") => "application/toml",
+        Some("pdf") => "application/pdf",
+        Some("js") => "text/javascript",
+        Some("html") | Some("htm") => "text/html",
+        Some("css") => "text/css",
+        Some("csv") => "text/csv",
+        Some("txt") => "text/plain",
+        Some("png") => "image/png",
+        Some("jpg") | Some("jpeg") => "image/jpeg",
+        Some("gif") => "image/gif",
+        Some("svg") => "image/svg+xml",
+        _ => "application/octet-stream",
+    }
+}
+
+/// Check that `name` is a cookie name, i.e. a token as defined by RFC 6265.
+fn is_cookie_name(name: &str) -> bool {
+    !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_graphic() && !"()<>@,;:\\\"/[]?={}".contains(c))
+}
+
+/// Check that `value` is a cookie value as defined by RFC 6265, optionally wrapped in double
+/// quotes.
+/// If `templated` is set, template variables are skipped - they are only rendered when a response
+/// is generated - but they must be closed.
+fn is_cookie_value(value: &str, templated: bool) -> bool {
+    let unquoted = value
+        .strip_prefix('"')
+        .and_then(|v| v.strip_suffix('"'))
+        .unwrap_or(value);
+    let is_literal = |literal: &str| {
+        literal
+            .chars()
+            .all(|c| c.is_ascii_graphic() && !"\",;\\".contains(c))
+    };
+    if !templated {
+        return is_literal(unquoted);
+    }
+    let mut rest = unquoted;
+    while let Some((literal, after)) = rest.split_once("{{") {
+        let Some((_, after)) = after.split_once("}}") else {
+            return false;
+        };
+        if !is_literal(literal) {
+            return false;
+        }
+        rest = after;
     }
+    is_literal(rest)
 }
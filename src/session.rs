@@ -0,0 +1,107 @@
+use crate::Request;
+use http::HeaderName;
+use std::any::{Any, TypeId};
+use std::collections::HashMap;
+use std::fmt::{Debug, Formatter};
+use std::sync::{Arc, Mutex};
+
+/// How a [`MockServer`](crate::MockServer) groups requests into sessions - see
+/// [`MockServerBuilder::session_key`](crate::MockServerBuilder::session_key).
+///
+/// Responders access the state of the session of the current request using
+/// [`ResponseContext::session`](crate::ResponseContext::session).
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+#[non_exhaustive]
+pub enum SessionKey {
+    /// Requests received on the same connection share a session - see
+    /// [`Request::connection_id`](crate::Request::connection_id). This is the default.
+    #[default]
+    Connection,
+    /// Requests carrying the same value for this header share a session - e.g. the same
+    /// `Authorization` header.
+    /// Requests without the header do not belong to any session.
+    Header(HeaderName),
+    /// Requests sending the same value for this cookie share a session - e.g. a session
+    /// cookie set in the response to a login request.
+    /// Requests without the cookie do not belong to any session.
+    Cookie(String),
+}
+
+impl SessionKey {
+    /// The identifier of the session `request` belongs to, if any.
+    fn session_id(&self, request: &Request) -> Option<String> {
+        match self {
+            SessionKey::Connection => request.connection_id().map(|id| id.to_string()),
+            SessionKey::Header(name) => request
+                .headers
+                .get(name)
+                .and_then(|value| value.to_str().ok())
+                .map(str::to_owned),
+            SessionKey::Cookie(name) => request
+                .headers
+                .get_all(http::header::COOKIE)
+                .iter()
+                .filter_map(|value| value.to_str().ok())
+                .flat_map(|value| value.split(';'))
+                .filter_map(|pair| pair.trim().split_once('='))
+                .find(|(cookie, _)| cookie.trim() == name)
+                .map(|(_, value)| value.trim().trim_matches('"').to_owned()),
+        }
+    }
+}
+
+/// The state of each session, keyed by session identifier and by type.
+type States = HashMap<String, HashMap<TypeId, Arc<dyn Any + Send + Sync>>>;
+
+/// The sessions of a mock server, shared by all its mocks.
+#[derive(Clone, Default)]
+pub(crate) struct Sessions {
+    key: SessionKey,
+    states: Arc<Mutex<States>>,
+}
+
+impl Sessions {
+    pub(crate) fn new(key: SessionKey) -> Self {
+        Self {
+            key,
+            states: Default::default(),
+        }
+    }
+
+    /// The identifier of the session `request` belongs to, if any.
+    pub(crate) fn session_id(&self, request: &Request) -> Option<String> {
+        self.key.session_id(request)
+    }
+
+    /// The state of type `T` of session `id`, initialised with its default value.
+    pub(crate) fn state<T: Default + Send + 'static>(&self, id: &str) -> Arc<Mutex<T>> {
+        let mut states = self.states.lock().unwrap();
+        let state = states
+            .entry(id.to_owned())
+            .or_default()
+            .entry(TypeId::of::<T>())
+            .or_insert_with(|| Arc::new(Mutex::new(T::default())))
+            .clone();
+        state
+            .downcast()
+            .expect("The state is stored under the identifier of its type")
+    }
+
+    /// Drop all the state of session `id`.
+    pub(crate) fn end(&self, id: &str) {
+        self.states.lock().unwrap().remove(id);
+    }
+
+    /// Drop the state of all sessions.
+    pub(crate) fn clear(&self) {
+        self.states.lock().unwrap().clear();
+    }
+}
+
+impl Debug for Sessions {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Sessions")
+            .field("key", &self.key)
+            .finish_non_exhaustive()
+    }
+}
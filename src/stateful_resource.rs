@@ -0,0 +1,235 @@
+use crate::matchers::{method, path, path_regex};
+use crate::{Mock, MockServer, Request, ResponseTemplate};
+use serde::Serialize;
+use serde_json::{Map, Value};
+use std::collections::BTreeMap;
+use std::sync::{Arc, Mutex};
+
+/// A fake REST resource backed by an in-memory JSON store.
+///
+/// Given a path prefix (e.g. `/users`), [`StatefulResource::mount`] registers on a [`MockServer`]
+/// the [`Mock`]s required to create, read, update and delete items of that resource:
+///
+/// | Request                | Behaviour                                                                       |
+/// |------------------------|---------------------------------------------------------------------------------|
+/// | `POST /users`          | Store the JSON object in the body, assigning it a new id. Returns `201` with the stored item. |
+/// | `GET /users`           | Returns `200` with a JSON array of all stored items, sorted by id.             |
+/// | `GET /users/{id}`      | Returns `200` with the item, `404` if there is no item with that id.           |
+/// | `PUT /users/{id}`      | Replace the item with the JSON object in the body. Returns `200` with the stored item, `404` if there is no item with that id. |
+/// | `DELETE /users/{id}`   | Remove the item. Returns `204`, `404` if there is no item with that id.        |
+///
+/// Ids are positive integers, assigned sequentially starting from `1`. They are stored in the
+/// `id` field of each item - use [`StatefulResource::id_field`] to pick a different field name.
+/// Bodies of `POST` and `PUT` requests must be JSON objects, otherwise `400` is returned.
+///
+/// `StatefulResource` is cheap to clone: all clones share the same store, so you can keep one
+/// around to inspect the state of the resource at the end of your test.
+///
+/// ### Example:
+/// ```rust
+/// use wiremock::{MockServer, StatefulResource};
+/// use serde_json::json;
+///
+/// #[async_std::main]
+/// async fn main() {
+///     // Arrange
+///     let mock_server = MockServer::start().await;
+///     let users = StatefulResource::new("/users");
+///     users.mount(&mock_server).await;
+///
+///     // Act
+///     let mut response = surf::post(format!("{}/users", mock_server.uri()))
+///         .body(json!({"name": "Ferris"}))
+///         .await
+///         .unwrap();
+///     assert_eq!(response.status(), 201);
+///     let created: serde_json::Value = response.body_json().await.unwrap();
+///
+///     let mut response = surf::get(format!("{}/users/{}", mock_server.uri(), created["id"]))
+///         .await
+///         .unwrap();
+///
+///     // Assert
+///     assert_eq!(response.status(), 200);
+///     let fetched: serde_json::Value = response.body_json().await.unwrap();
+///     assert_eq!(fetched, json!({"id": 1, "name": "Ferris"}));
+///     assert_eq!(users.items(), vec![fetched]);
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct StatefulResource {
+    path: String,
+    id_field: String,
+    store: Arc<Mutex<ResourceStore>>,
+}
+
+#[derive(Debug, Default)]
+struct ResourceStore {
+    items: BTreeMap<u64, Map<String, Value>>,
+    /// The last id we assigned to an item.
+    last_id: u64,
+}
+
+impl ResourceStore {
+    fn insert(&mut self, id_field: &str, mut item: Map<String, Value>) -> Value {
+        self.last_id += 1;
+        item.insert(id_field.to_string(), Value::from(self.last_id));
+        self.items.insert(self.last_id, item.clone());
+        Value::Object(item)
+    }
+}
+
+impl StatefulResource {
+    /// Create a new, empty, resource served under `path` (e.g. `/users`).
+    pub fn new<T: Into<String>>(path: T) -> Self {
+        let path = path.into();
+        let path = path.trim_end_matches('/');
+        let path = if path.starts_with('/') {
+            path.to_string()
+        } else {
+            format!("/{}", path)
+        };
+        Self {
+            path,
+            id_field: "id".to_string(),
+            store: Arc::new(Mutex::new(ResourceStore::default())),
+        }
+    }
+
+    /// Set the name of the field used to store the id of each item. It defaults to `id`.
+    pub fn id_field<T: Into<String>>(mut self, id_field: T) -> Self {
+        self.id_field = id_field.into();
+        self
+    }
+
+    /// Seed the resource with an item, as if it had been created via a `POST` request.
+    ///
+    /// It panics if `item` does not serialize to a JSON object.
+    pub fn with_item<T: Serialize>(self, item: T) -> Self {
+        let item = match serde_json::to_value(item).expect("Failed to serialize item") {
+            Value::Object(item) => item,
+            other => panic!("Items must be JSON objects, got {}", other),
+        };
+        self.store.lock().unwrap().insert(&self.id_field, item);
+        self
+    }
+
+    /// Return all the items currently stored, sorted by id.
+    pub fn items(&self) -> Vec<Value> {
+        self.store
+            .lock()
+            .unwrap()
+            .items
+            .values()
+            .cloned()
+            .map(Value::Object)
+            .collect()
+    }
+
+    /// Return the item with the specified id, if it exists.
+    pub fn get(&self, id: u64) -> Option<Value> {
+        self.store
+            .lock()
+            .unwrap()
+            .items
+            .get(&id)
+            .cloned()
+            .map(Value::Object)
+    }
+
+    /// Mount the [`Mock`]s backing this resource on `server`.
+    pub async fn mount(&self, server: &MockServer) {
+        let item_path = format!("^{}/([^/]+)$", regex::escape(&self.path));
+
+        let resource = self.clone();
+        Mock::given(method("POST"))
+            .and(path(self.path.as_str()))
+            .respond_with(move |request: &Request| {
+                let item = match json_object(request) {
+                    Some(item) => item,
+                    None => return ResponseTemplate::new(400),
+                };
+                let mut store = resource.store.lock().unwrap();
+                ResponseTemplate::new(201).set_body_json(store.insert(&resource.id_field, item))
+            })
+            .named(format!("StatefulResource - POST {}", self.path))
+            .mount(server)
+            .await;
+
+        let resource = self.clone();
+        Mock::given(method("GET"))
+            .and(path(self.path.as_str()))
+            .respond_with(move |_: &Request| {
+                ResponseTemplate::new(200).set_body_json(resource.items())
+            })
+            .named(format!("StatefulResource - GET {}", self.path))
+            .mount(server)
+            .await;
+
+        let resource = self.clone();
+        Mock::given(method("GET"))
+            .and(path_regex(item_path.as_str()))
+            .respond_with(move |request: &Request| {
+                match item_id(request).and_then(|id| resource.get(id)) {
+                    Some(item) => ResponseTemplate::new(200).set_body_json(item),
+                    None => ResponseTemplate::new(404),
+                }
+            })
+            .named(format!("StatefulResource - GET {}/{{id}}", self.path))
+            .mount(server)
+            .await;
+
+        let resource = self.clone();
+        Mock::given(method("PUT"))
+            .and(path_regex(item_path.as_str()))
+            .respond_with(move |request: &Request| {
+                let mut item = match json_object(request) {
+                    Some(item) => item,
+                    None => return ResponseTemplate::new(400),
+                };
+                let mut store = resource.store.lock().unwrap();
+                match item_id(request).and_then(|id| store.items.get_mut(&id).map(|i| (id, i))) {
+                    Some((id, stored)) => {
+                        item.insert(resource.id_field.clone(), Value::from(id));
+                        *stored = item.clone();
+                        ResponseTemplate::new(200).set_body_json(Value::Object(item))
+                    }
+                    None => ResponseTemplate::new(404),
+                }
+            })
+            .named(format!("StatefulResource - PUT {}/{{id}}", self.path))
+            .mount(server)
+            .await;
+
+        let resource = self.clone();
+        Mock::given(method("DELETE"))
+            .and(path_regex(item_path.as_str()))
+            .respond_with(move |request: &Request| {
+                let mut store = resource.store.lock().unwrap();
+                match item_id(request).and_then(|id| store.items.remove(&id)) {
+                    Some(_) => ResponseTemplate::new(204),
+                    None => ResponseTemplate::new(404),
+                }
+            })
+            .named(format!("StatefulResource - DELETE {}/{{id}}", self.path))
+            .mount(server)
+            .await;
+    }
+}
+
+/// Parse the body of `request` as a JSON object.
+fn json_object(request: &Request) -> Option<Map<String, Value>> {
+    match request.body_json::<Value>() {
+        Ok(Value::Object(item)) => Some(item),
+        _ => None,
+    }
+}
+
+/// Extract the item id from the last segment of the request path.
+fn item_id(request: &Request) -> Option<u64> {
+    request
+        .url
+        .path_segments()
+        .and_then(|mut segments| segments.next_back())
+        .and_then(|id| id.parse().ok())
+}
@@ -0,0 +1,318 @@
+//! Declarative, serializable mock definitions.
+//!
+//! A [`Stub`] describes a [`Mock`] as plain data - matchers are expressed as a [`RequestPattern`]
+//! rather than as closures, the response as a [`ResponseDefinition`] rather than as a
+//! [`Respond`](crate::Respond) implementation.
+//! Stubs implement `Serialize` and `Deserialize`: you can keep them in fixture files, share them
+//! between test suites and mount them using [`MockServer::mount_stub`].
+//!
+//! ```rust
+//! use wiremock::MockServer;
+//! use wiremock::stub::Stub;
+//!
+//! #[async_std::main]
+//! async fn main() {
+//!     // Arrange
+//!     let mock_server = MockServer::start().await;
+//!     let stub: Stub = serde_json::from_str(r#"{
+//!         "request": { "method": "GET", "path": "/hello" },
+//!         "response": { "status": 200, "body": "Hello world!" }
+//!     }"#).unwrap();
+//!     mock_server.mount_stub(stub.clone()).await;
+//!
+//!     // Act
+//!     let mut response = surf::get(format!("{}/hello", mock_server.uri())).await.unwrap();
+//!
+//!     // Assert
+//!     assert_eq!(response.status(), 200);
+//!     assert_eq!(response.body_string().await.unwrap(), "Hello world!");
+//!     assert_eq!(mock_server.export_stubs().await, vec![stub]);
+//! }
+//! ```
+//!
+//!
+//! Matching logic specific to your project can be referenced by name in a stub, once it has
+//! been registered with [`register_matcher`].
+//!
+//! [`MockServer::mount_stub`]: crate::MockServer::mount_stub
+use crate::matchers::{
+    any, body_json, body_partial_json, body_string, header, method, path, path_regex, query_param,
+};
+use crate::{Match, Mock, MockBuilder, Request, ResponseTemplate};
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+
+/// A serializable description of a [`Mock`]: which requests it matches and how it responds.
+///
+/// Convert it into a [`Mock`] using `Mock::from` or mount it directly using
+/// [`MockServer::mount_stub`](crate::MockServer::mount_stub).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Stub {
+    /// The requests matched by the mock.
+    #[serde(default)]
+    pub request: RequestPattern,
+    /// The response returned to matching requests.
+    pub response: ResponseDefinition,
+    /// The name of the mock - see [`Mock::named`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    /// The priority of the mock - see [`Mock::with_priority`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub priority: Option<u8>,
+}
+
+/// The conditions an incoming request must satisfy to be matched by a [`Stub`].
+///
+/// All the specified conditions must be satisfied. A pattern with no conditions matches
+/// every request.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct RequestPattern {
+    /// The HTTP method, e.g. `GET` - see [`method`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub method: Option<String>,
+    /// The exact path, e.g. `/hello` - see [`path`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub path: Option<String>,
+    /// A regular expression the path must match - see [`path_regex`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub path_regex: Option<String>,
+    /// Headers that must be present with the specified value - see [`header`].
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub headers: BTreeMap<String, String>,
+    /// Query parameters that must be present with the specified value - see [`query_param`].
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub query_params: BTreeMap<String, String>,
+    /// The exact body, as a string - see [`body_string`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub body: Option<String>,
+    /// The exact body, as JSON - see [`body_json`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub body_json: Option<Value>,
+    /// A subset of the JSON body - see [`body_partial_json`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub body_partial_json: Option<Value>,
+    /// Matchers registered with [`register_matcher`], referenced by name.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub custom: Vec<CustomMatcher>,
+}
+
+/// A reference to a matcher registered with [`register_matcher`], in a [`RequestPattern`].
+///
+/// In JSON, it looks like `{ "name": "valid-hmac", "params": { "key": "secret" } }`.
+/// `params` is optional: it is passed as is to the [`MatcherFactory`] registered under `name`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CustomMatcher {
+    /// The name the matcher was registered under.
+    pub name: String,
+    /// The parameters of the matcher, `null` if not specified.
+    #[serde(default, skip_serializing_if = "Value::is_null")]
+    pub params: Value,
+}
+
+/// Builds a matcher out of the parameters of a [`CustomMatcher`] - see [`register_matcher`].
+///
+/// It is implemented for all closures taking the parameters as a `&serde_json::Value` and
+/// returning a [`Match`] implementation.
+pub trait MatcherFactory: Send + Sync + 'static {
+    /// Build the matcher. It should panic, with a descriptive message, if `params` are not
+    /// valid.
+    fn build(&self, params: &Value) -> Box<dyn Match>;
+}
+
+impl<F, M> MatcherFactory for F
+where
+    F: Fn(&Value) -> M + Send + Sync + 'static,
+    M: Match + 'static,
+{
+    fn build(&self, params: &Value) -> Box<dyn Match> {
+        Box::new(self(params))
+    }
+}
+
+/// The factories registered with [`register_matcher`], by name.
+static MATCHER_REGISTRY: Lazy<RwLock<HashMap<String, Arc<dyn MatcherFactory>>>> =
+    Lazy::new(Default::default);
+
+/// Register a matcher under `name`, so that [`Stub`]s can reference it in the `custom` field of
+/// their [`RequestPattern`] - e.g. to keep project-specific matching logic, like checking a
+/// request signature, in Rust while your stubs live in fixture files.
+///
+/// The registry is shared by the whole process: register your matchers before converting or
+/// mounting the stubs that use them - e.g. in a helper called at the start of each test.
+/// Registering a matcher under a name that is already taken replaces the previous one.
+///
+/// Converting a stub that references a matcher that has not been registered panics.
+///
+/// ### Example:
+/// ```rust
+/// use wiremock::{MockServer, Request};
+/// use wiremock::stub::{register_matcher, Stub};
+///
+/// #[async_std::main]
+/// async fn main() {
+///     // Arrange
+///     register_matcher("has-header", |params: &serde_json::Value| {
+///         let name = params["name"].as_str().expect("`name` must be a string").to_owned();
+///         move |request: &Request| request.headers.contains_key(name.as_str())
+///     });
+///     let mock_server = MockServer::start().await;
+///     let stub: Stub = serde_json::from_str(r#"{
+///         "request": {
+///             "path": "/hello",
+///             "custom": [{ "name": "has-header", "params": { "name": "x-signature" } }]
+///         },
+///         "response": { "status": 200 }
+///     }"#).unwrap();
+///     mock_server.mount_stub(stub).await;
+///
+///     // Act
+///     let url = format!("{}/hello", mock_server.uri());
+///     let signed = surf::get(&url).header("x-signature", "abc").await.unwrap();
+///     let unsigned = surf::get(&url).await.unwrap();
+///
+///     // Assert
+///     assert_eq!(signed.status(), 200);
+///     assert_eq!(unsigned.status(), 404);
+/// }
+/// ```
+pub fn register_matcher<F: MatcherFactory>(name: impl Into<String>, factory: F) {
+    MATCHER_REGISTRY
+        .write()
+        .unwrap()
+        .insert(name.into(), Arc::new(factory));
+}
+
+impl CustomMatcher {
+    /// Build the matcher using the factory registered under its name.
+    fn build(&self) -> Result<Box<dyn Match>, String> {
+        let factory = MATCHER_REGISTRY.read().unwrap().get(&self.name).cloned();
+        match factory {
+            Some(factory) => Ok(factory.build(&self.params)),
+            None => Err(format!(
+                "No matcher named `{}` has been registered - see `wiremock::stub::register_matcher`.",
+                self.name
+            )),
+        }
+    }
+}
+
+impl Stub {
+    /// Check that the custom matchers referenced by the stub have been registered.
+    pub(crate) fn check_custom_matchers(&self) -> Result<(), String> {
+        let registry = MATCHER_REGISTRY.read().unwrap();
+        match self
+            .request
+            .custom
+            .iter()
+            .find(|custom| !registry.contains_key(&custom.name))
+        {
+            Some(custom) => Err(format!(
+                "no matcher named `{}` has been registered",
+                custom.name
+            )),
+            None => Ok(()),
+        }
+    }
+}
+
+/// The response returned by a [`Stub`] to the requests it matches.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ResponseDefinition {
+    /// The status code of the response.
+    pub status: u16,
+    /// The headers of the response.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub headers: BTreeMap<String, String>,
+    /// The body of the response, as a string.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub body: Option<String>,
+    /// The body of the response, as JSON. It sets the `Content-Type` header to
+    /// `application/json` - see [`ResponseTemplate::set_body_json`].
+    ///
+    /// It takes precedence over `body` if both are set.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub json_body: Option<Value>,
+    /// How long to wait before sending the response, in milliseconds - see
+    /// [`ResponseTemplate::set_delay`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub delay_ms: Option<u64>,
+}
+
+impl RequestPattern {
+    /// Build a [`MockBuilder`] with a matcher for each of the conditions in the pattern.
+    fn into_mock_builder(self) -> MockBuilder {
+        let mut builder = MockBuilder { matchers: vec![] };
+        if let Some(m) = self.method {
+            builder = builder.and(method(m.as_str()));
+        }
+        if let Some(p) = self.path {
+            builder = builder.and(path(p));
+        }
+        if let Some(p) = self.path_regex {
+            builder = builder.and(path_regex(p));
+        }
+        for (key, value) in self.headers {
+            builder = builder.and(header(key.as_str(), value.as_str()));
+        }
+        for (key, value) in self.query_params {
+            builder = builder.and(query_param(key, value));
+        }
+        if let Some(body) = self.body {
+            builder = builder.and(body_string(body));
+        }
+        if let Some(body) = self.body_json {
+            builder = builder.and(body_json(body));
+        }
+        if let Some(body) = self.body_partial_json {
+            builder = builder.and(body_partial_json(body));
+        }
+        for custom in self.custom {
+            let matcher = custom.build().unwrap_or_else(|e| panic!("{}", e));
+            builder = builder.and(move |request: &Request| matcher.matches(request));
+        }
+        if builder.matchers.is_empty() {
+            builder = builder.and(any());
+        }
+        builder
+    }
+}
+
+impl ResponseDefinition {
+    fn to_response_template(&self) -> ResponseTemplate {
+        let mut template = ResponseTemplate::new(self.status);
+        for (key, value) in &self.headers {
+            template = template.insert_header(key.as_str(), value.as_str());
+        }
+        if let Some(body) = &self.json_body {
+            template = template.set_body_json(body);
+        } else if let Some(body) = &self.body {
+            template = template.set_body_string(body.as_str());
+        }
+        if let Some(delay_ms) = self.delay_ms {
+            template = template.set_delay(Duration::from_millis(delay_ms));
+        }
+        template
+    }
+}
+
+impl From<Stub> for Mock {
+    fn from(stub: Stub) -> Self {
+        let mut mock = stub
+            .request
+            .clone()
+            .into_mock_builder()
+            .respond_with(stub.response.to_response_template());
+        if let Some(name) = &stub.name {
+            mock = mock.named(name.as_str());
+        }
+        if let Some(priority) = stub.priority {
+            mock = mock.with_priority(priority);
+        }
+        mock.stub = Some(stub);
+        mock
+    }
+}
@@ -0,0 +1,263 @@
+//! Rendering of the template variables in response bodies and headers - see
+//! [`ResponseTemplate::render_templates`](crate::ResponseTemplate::render_templates).
+use crate::date::{http_date, iso8601, rfc3339};
+use crate::random::Random;
+use std::convert::TryFrom;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Replace the template variables in `input` with their value at `now`, drawing random values
+/// from `random`. `base_url` is the base URL of the listener the request was received on, if
+/// known.
+///
+/// Placeholders referring to unknown variables are left untouched.
+pub(crate) fn render(
+    input: &str,
+    now: SystemTime,
+    random: &Random,
+    base_url: Option<&str>,
+) -> Result<String, String> {
+    let mut output = String::with_capacity(input.len());
+    let mut rest = input;
+    while let Some(start) = rest.find("{{") {
+        let end = match rest[start..].find("}}") {
+            Some(end) => start + end,
+            None => break,
+        };
+        output.push_str(&rest[..start]);
+        let placeholder = &rest[start + 2..end];
+        match render_placeholder(placeholder.trim(), now, random, base_url)? {
+            Some(value) => output.push_str(&value),
+            None => output.push_str(&rest[start..end + 2]),
+        }
+        rest = &rest[end + 2..];
+    }
+    output.push_str(rest);
+    Ok(output)
+}
+
+/// Render a single placeholder, e.g. `now offset='-5m' format='http'` or `random int 1 100`.
+/// It returns `None` if the placeholder does not refer to a known variable.
+fn render_placeholder(
+    placeholder: &str,
+    now: SystemTime,
+    random: &Random,
+    base_url: Option<&str>,
+) -> Result<Option<String>, String> {
+    let (name, arguments) = match placeholder.split_once(char::is_whitespace) {
+        Some((name, arguments)) => (name, arguments),
+        None => (placeholder, ""),
+    };
+    match name {
+        "now" => render_now(placeholder, arguments, now).map(Some),
+        "random" => render_random(arguments, random).map(Some),
+        "base_url" if arguments.is_empty() => Ok(base_url.map(str::to_owned)),
+        _ => Ok(None),
+    }
+}
+
+/// Render `{{now}}`, shifted and formatted according to its `arguments`.
+fn render_now(placeholder: &str, arguments: &str, now: SystemTime) -> Result<String, String> {
+    let mut time = now;
+    let mut format = "rfc3339";
+    for (key, value) in parse_arguments(arguments)
+        .ok_or_else(|| format!("invalid arguments in `{{{{{}}}}}`", placeholder))?
+    {
+        match key {
+            "offset" => time = apply_offset(time, value)?,
+            "format" => format = value,
+            _ => return Err(format!("unknown argument `{}` for `now`", key)),
+        }
+    }
+
+    let since_epoch = time
+        .duration_since(UNIX_EPOCH)
+        .map_err(|_| "`now` cannot be rendered before 1970".to_string())?;
+    let value = match format {
+        "rfc3339" => rfc3339(time),
+        "rfc3339_millis" => iso8601(time),
+        "http" | "rfc1123" => http_date(time),
+        "epoch" => since_epoch.as_secs().to_string(),
+        "epoch_millis" => since_epoch.as_millis().to_string(),
+        _ => return Err(format!("unknown format `{}` for `now`", format)),
+    };
+    Ok(value)
+}
+
+/// Render `{{random ..}}`: `uuid`, `int <min> <max>`, `alphanumeric <length>` or `hex <length>`.
+fn render_random(arguments: &str, random: &Random) -> Result<String, String> {
+    let arguments: Vec<&str> = arguments.split_whitespace().collect();
+    let parse_number = |value: &str| {
+        value
+            .parse::<i64>()
+            .map_err(|_| format!("`{}` is not a valid number for `random`", value))
+    };
+    let parse_length = |value: &str| {
+        value
+            .parse::<usize>()
+            .map_err(|_| format!("`{}` is not a valid length for `random`", value))
+    };
+    match arguments.as_slice() {
+        ["uuid"] => Ok(random.uuid()),
+        ["int", min, max] => {
+            let (min, max) = (parse_number(min)?, parse_number(max)?);
+            if min > max {
+                return Err(format!("empty range `{} {}` for `random int`", min, max));
+            }
+            Ok(random.int_between(min, max).to_string())
+        }
+        ["alphanumeric", length] => Ok(random.alphanumeric(parse_length(length)?)),
+        ["hex", length] => Ok(random.hex(parse_length(length)?)),
+        _ => Err(format!(
+            "invalid arguments `{}` for `random`: expected `uuid`, `int <min> <max>`, \
+             `alphanumeric <length>` or `hex <length>`",
+            arguments.join(" ")
+        )),
+    }
+}
+
+/// Parse a list of `key='value'` (or `key="value"`) arguments.
+fn parse_arguments(mut arguments: &str) -> Option<Vec<(&str, &str)>> {
+    let mut parsed = vec![];
+    loop {
+        arguments = arguments.trim_start();
+        if arguments.is_empty() {
+            return Some(parsed);
+        }
+        let (key, rest) = arguments.split_once('=')?;
+        let quote = rest.chars().next().filter(|c| *c == '\'' || *c == '"')?;
+        let (value, rest) = rest[1..].split_once(quote)?;
+        parsed.push((key.trim(), value));
+        arguments = rest;
+    }
+}
+
+/// Shift `time` by an offset such as `-5m`, `+1h30m` or `2d`.
+pub(crate) fn apply_offset(time: SystemTime, offset: &str) -> Result<SystemTime, String> {
+    let invalid = || {
+        format!(
+            "invalid offset `{}`: expected something like `-5m` or `1h30m`",
+            offset
+        )
+    };
+    let (negative, mut rest) = match offset.trim().strip_prefix('-') {
+        Some(rest) => (true, rest),
+        None => (false, offset.trim().trim_start_matches('+')),
+    };
+    if rest.is_empty() {
+        return Err(invalid());
+    }
+    let mut total = Duration::ZERO;
+    while !rest.is_empty() {
+        let digits = rest
+            .find(|c: char| !c.is_ascii_digit())
+            .ok_or_else(invalid)?;
+        let amount: u64 = rest[..digits].parse().map_err(|_| invalid())?;
+        rest = &rest[digits..];
+        let unit_length = rest
+            .find(|c: char| c.is_ascii_digit())
+            .unwrap_or(rest.len());
+        let unit = match &rest[..unit_length] {
+            "ms" => Duration::from_millis(1),
+            "s" => Duration::from_secs(1),
+            "m" => Duration::from_secs(60),
+            "h" => Duration::from_secs(3_600),
+            "d" => Duration::from_secs(86_400),
+            _ => return Err(invalid()),
+        };
+        total += unit * u32::try_from(amount).map_err(|_| invalid())?;
+        rest = &rest[unit_length..];
+    }
+    let shifted = if negative {
+        time.checked_sub(total)
+    } else {
+        time.checked_add(total)
+    };
+    shifted.ok_or_else(invalid)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::render;
+    use crate::random::Random;
+    use std::time::{Duration, UNIX_EPOCH};
+
+    #[test]
+    fn now_is_rendered_with_offset_and_format() {
+        let now = UNIX_EPOCH + Duration::from_secs(1_709_210_096);
+
+        let rendered = render(
+            r#"{"issued": "{{now}}", "expires": {{ now offset='+1h30m' format='epoch' }}}"#,
+            now,
+            &Random::seeded(0),
+            None,
+        )
+        .unwrap();
+        assert_eq!(
+            rendered,
+            r#"{"issued": "2024-02-29T12:34:56Z", "expires": 1709215496}"#
+        );
+
+        let rendered = render(
+            "{{now offset=\"-5m\" format=\"http\"}}",
+            now,
+            &Random::seeded(0),
+            None,
+        )
+        .unwrap();
+        assert_eq!(rendered, "Thu, 29 Feb 2024 12:29:56 GMT");
+    }
+
+    #[test]
+    fn unknown_variables_and_unterminated_placeholders_are_left_untouched() {
+        let now = UNIX_EPOCH;
+
+        let rendered = render("{{name}} {{now", now, &Random::seeded(0), None).unwrap();
+        assert_eq!(rendered, "{{name}} {{now");
+    }
+
+    #[test]
+    fn base_url_is_rendered_when_known() {
+        let template = "{{base_url}}/items/42";
+
+        let rendered = render(
+            template,
+            UNIX_EPOCH,
+            &Random::seeded(0),
+            Some("http://127.0.0.1:4000"),
+        )
+        .unwrap();
+        assert_eq!(rendered, "http://127.0.0.1:4000/items/42");
+
+        let rendered = render(template, UNIX_EPOCH, &Random::seeded(0), None).unwrap();
+        assert_eq!(rendered, template);
+    }
+
+    #[test]
+    fn invalid_arguments_are_rejected() {
+        let now = UNIX_EPOCH + Duration::from_secs(60);
+
+        assert!(render("{{now offset='5 minutes'}}", now, &Random::seeded(0), None).is_err());
+        assert!(render("{{now format='iso'}}", now, &Random::seeded(0), None).is_err());
+        assert!(render("{{now timezone='CET'}}", now, &Random::seeded(0), None).is_err());
+        assert!(render("{{now offset='-2m'}}", now, &Random::seeded(0), None).is_err());
+    }
+
+    #[test]
+    fn random_values_are_reproducible() {
+        let now = UNIX_EPOCH;
+        let template =
+            "{{random uuid}} {{random int 1 100}} {{random alphanumeric 8}} {{random hex 4}}";
+
+        let first = render(template, now, &Random::seeded(42), None).unwrap();
+        let second = render(template, now, &Random::seeded(42), None).unwrap();
+        assert_eq!(first, second);
+        let values: Vec<&str> = first.split(' ').collect();
+        assert_eq!(values[0].len(), 36);
+        assert!((1..=100).contains(&values[1].parse::<i64>().unwrap()));
+        assert_eq!(values[2].len(), 8);
+        assert_eq!(values[3].len(), 4);
+
+        assert!(render("{{random int 5 1}}", now, &Random::seeded(0), None).is_err());
+        assert!(render("{{random float}}", now, &Random::seeded(0), None).is_err());
+    }
+}
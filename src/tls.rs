@@ -0,0 +1,234 @@
+//! Serving mocks over HTTPS, behind the `tls` feature flag.
+//!
+//! Use [`MockServerBuilder::tls`](crate::MockServerBuilder::tls) to let a [`MockServer`]
+//! terminate TLS with the certificate of your choice. The protocol version and the cipher
+//! suite negotiated with the client are then available on each [`Request`] - see
+//! [`Request::tls`] - and can be matched using
+//! [`matchers::tls_version_at_least`](crate::matchers::tls_version_at_least) and
+//! [`matchers::tls_cipher_suite`](crate::matchers::tls_cipher_suite), to check that your
+//! client is configured as securely as you expect.
+//!
+//! [`MockServer`]: crate::MockServer
+//! [`Request`]: crate::Request
+//! [`Request::tls`]: crate::Request::tls
+use std::fmt::{Display, Formatter};
+use std::io;
+use tokio::io::{AsyncRead, AsyncWrite};
+
+#[cfg(feature = "tls")]
+mod authority;
+
+#[cfg(feature = "tls")]
+pub(crate) use authority::CertificateAuthority;
+
+/// A version of the TLS protocol.
+///
+/// Versions are ordered from the oldest to the most recent one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[non_exhaustive]
+pub enum TlsVersion {
+    /// TLS 1.2.
+    Tls12,
+    /// TLS 1.3.
+    Tls13,
+}
+
+impl Display for TlsVersion {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TlsVersion::Tls12 => f.write_str("TLSv1.2"),
+            TlsVersion::Tls13 => f.write_str("TLSv1.3"),
+        }
+    }
+}
+
+/// The parameters negotiated during the TLS handshake of the connection a [`Request`] was
+/// received on - see [`Request::tls`].
+///
+/// [`Request`]: crate::Request
+/// [`Request::tls`]: crate::Request::tls
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TlsInfo {
+    version: TlsVersion,
+    cipher_suite: String,
+}
+
+impl TlsInfo {
+    /// Describe a TLS session - e.g. to build a [`Request`](crate::Request) by hand using
+    /// [`RequestBuilder::tls`](crate::RequestBuilder::tls).
+    ///
+    /// `cipher_suite` is the IANA name of the cipher suite, e.g. `TLS13_AES_128_GCM_SHA256`.
+    pub fn new<T: Into<String>>(version: TlsVersion, cipher_suite: T) -> Self {
+        Self {
+            version,
+            cipher_suite: cipher_suite.into(),
+        }
+    }
+
+    /// The negotiated version of the protocol.
+    pub fn version(&self) -> TlsVersion {
+        self.version
+    }
+
+    /// The IANA name of the negotiated cipher suite - e.g. `TLS13_AES_128_GCM_SHA256`.
+    pub fn cipher_suite(&self) -> &str {
+        &self.cipher_suite
+    }
+}
+
+/// The certificate presented by a [`MockServer`] serving mocks over HTTPS - see
+/// [`MockServerBuilder::tls`](crate::MockServerBuilder::tls).
+///
+/// TLS 1.2 and TLS 1.3 are supported, as well as HTTP/2 (negotiated using ALPN).
+///
+/// [`MockServer`]: crate::MockServer
+#[cfg(feature = "tls")]
+#[derive(Clone)]
+pub struct TlsConfig {
+    server_config: std::sync::Arc<rustls::ServerConfig>,
+}
+
+#[cfg(feature = "tls")]
+impl TlsConfig {
+    /// Use the PEM-encoded certificate chain and private key - e.g. a self-signed certificate
+    /// for `localhost`, generated for your test suite.
+    ///
+    /// The certificate chain starts with the certificate of the server, followed by the
+    /// intermediate certificates, if any.
+    /// It panics if the certificates or the private key cannot be parsed, or if they do not
+    /// match.
+    pub fn from_pem<C, K>(cert_chain: C, private_key: K) -> Self
+    where
+        C: AsRef<[u8]>,
+        K: AsRef<[u8]>,
+    {
+        use rustls::pki_types::pem::PemObject;
+        use rustls::pki_types::{CertificateDer, PrivateKeyDer};
+
+        let cert_chain = CertificateDer::pem_slice_iter(cert_chain.as_ref())
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap_or_else(|e| panic!("Failed to parse the certificate chain: {:?}", e));
+        assert!(
+            !cert_chain.is_empty(),
+            "The certificate chain does not contain any PEM-encoded certificate."
+        );
+        let private_key = PrivateKeyDer::from_pem_slice(private_key.as_ref())
+            .unwrap_or_else(|e| panic!("Failed to parse the private key: {:?}", e));
+        Self::from_der(cert_chain, private_key)
+    }
+
+    /// Same as [`TlsConfig::from_pem`], with certificates and private key already decoded.
+    pub(crate) fn from_der(
+        cert_chain: Vec<rustls::pki_types::CertificateDer<'static>>,
+        private_key: rustls::pki_types::PrivateKeyDer<'static>,
+    ) -> Self {
+        let provider = std::sync::Arc::new(rustls::crypto::ring::default_provider());
+        let mut server_config = rustls::ServerConfig::builder_with_provider(provider)
+            .with_safe_default_protocol_versions()
+            .expect("The default protocol versions are supported by the crypto provider")
+            .with_no_client_auth()
+            .with_single_cert(cert_chain, private_key)
+            .unwrap_or_else(|e| panic!("Invalid certificate or private key: {}", e));
+        server_config.alpn_protocols = vec![b"h2".to_vec(), b"http/1.1".to_vec()];
+        Self {
+            server_config: std::sync::Arc::new(server_config),
+        }
+    }
+
+    pub(crate) fn acceptor(&self) -> TlsAcceptor {
+        TlsAcceptor(std::sync::Arc::new(std::sync::RwLock::new(
+            self.server_config.clone(),
+        )))
+    }
+}
+
+#[cfg(feature = "tls")]
+impl std::fmt::Debug for TlsConfig {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TlsConfig").finish_non_exhaustive()
+    }
+}
+
+/// Terminates TLS on the connections accepted by a listener.
+///
+/// Clones share the same configuration: once it is [rotated](TlsAcceptor::rotate), new
+/// connections are served with the new certificate, while established ones keep using the
+/// certificate they were opened with.
+#[cfg(feature = "tls")]
+#[derive(Clone)]
+pub(crate) struct TlsAcceptor(
+    std::sync::Arc<std::sync::RwLock<std::sync::Arc<rustls::ServerConfig>>>,
+);
+
+#[cfg(feature = "tls")]
+impl TlsAcceptor {
+    /// Serve new connections with the certificate in `config`.
+    pub(crate) fn rotate(&self, config: &TlsConfig) {
+        // The lock is never held across a panic: it cannot be poisoned.
+        *self.0.write().unwrap() = config.server_config.clone();
+    }
+
+    fn current(&self) -> tokio_rustls::TlsAcceptor {
+        tokio_rustls::TlsAcceptor::from(self.0.read().unwrap().clone())
+    }
+}
+
+/// Without the `tls` feature, no listener terminates TLS: it cannot be built.
+#[cfg(not(feature = "tls"))]
+#[derive(Clone)]
+pub(crate) enum TlsAcceptor {}
+
+/// Without the `tls` feature, the TLS connections tunnelled through a proxy cannot be
+/// intercepted: it cannot be built.
+#[cfg(not(feature = "tls"))]
+pub(crate) enum CertificateAuthority {}
+
+#[cfg(not(feature = "tls"))]
+impl CertificateAuthority {
+    pub(crate) fn acceptor_for(&self, _host: &str) -> TlsAcceptor {
+        match *self {}
+    }
+}
+
+#[cfg(feature = "tls")]
+pub(crate) type TlsStream<IO> = tokio_rustls::server::TlsStream<IO>;
+#[cfg(not(feature = "tls"))]
+pub(crate) type TlsStream<IO> = IO;
+
+/// Perform the TLS handshake on a freshly accepted connection.
+#[cfg(feature = "tls")]
+pub(crate) async fn accept<IO>(
+    acceptor: &TlsAcceptor,
+    stream: IO,
+) -> io::Result<(TlsStream<IO>, TlsInfo)>
+where
+    IO: AsyncRead + AsyncWrite + Unpin,
+{
+    let stream = acceptor.current().accept(stream).await?;
+    let connection = stream.get_ref().1;
+    let version = match connection.protocol_version() {
+        Some(rustls::ProtocolVersion::TLSv1_3) => TlsVersion::Tls13,
+        _ => TlsVersion::Tls12,
+    };
+    let cipher_suite = connection
+        .negotiated_cipher_suite()
+        .map(|suite| {
+            let suite = suite.suite();
+            suite
+                .as_str()
+                .map_or_else(|| format!("{:?}", suite), ToOwned::to_owned)
+        })
+        .unwrap_or_default();
+    Ok((stream, TlsInfo::new(version, cipher_suite)))
+}
+
+#[cfg(not(feature = "tls"))]
+pub(crate) async fn accept<IO>(
+    acceptor: &TlsAcceptor,
+    _stream: IO,
+) -> io::Result<(TlsStream<IO>, TlsInfo)>
+where
+    IO: AsyncRead + AsyncWrite + Unpin,
+{
+    match *acceptor {}
+}
@@ -0,0 +1,208 @@
+//! A certificate authority issuing certificates on the fly, to terminate the TLS connections
+//! tunnelled through a [`MockServer`](crate::MockServer) acting as a proxy - see
+//! [`MockServerBuilder::intercept_tls`](crate::MockServerBuilder::intercept_tls).
+//!
+//! Certificates are encoded by hand: we only need a handful of fixed DER structures, signed
+//! with ECDSA over P-256.
+use super::{TlsAcceptor, TlsConfig};
+use base64::prelude::{Engine as _, BASE64_STANDARD};
+use p256::ecdsa::signature::Signer;
+use p256::ecdsa::{Signature, SigningKey};
+use p256::pkcs8::{EncodePrivateKey, EncodePublicKey};
+use rand_core::{OsRng, RngCore};
+use rustls::pki_types::{CertificateDer, PrivateKeyDer, PrivatePkcs8KeyDer};
+use std::net::IpAddr;
+
+const BOOLEAN: u8 = 0x01;
+const INTEGER: u8 = 0x02;
+const BIT_STRING: u8 = 0x03;
+const OCTET_STRING: u8 = 0x04;
+const OBJECT_IDENTIFIER: u8 = 0x06;
+const UTF8_STRING: u8 = 0x0c;
+const UTC_TIME: u8 = 0x17;
+const GENERALIZED_TIME: u8 = 0x18;
+const SEQUENCE: u8 = 0x30;
+const SET: u8 = 0x31;
+
+/// 1.2.840.10045.4.3.2
+const ECDSA_WITH_SHA256: &[u8] = &[0x2a, 0x86, 0x48, 0xce, 0x3d, 0x04, 0x03, 0x02];
+/// 2.5.4.3
+const COMMON_NAME: &[u8] = &[0x55, 0x04, 0x03];
+/// 2.5.29.15
+const KEY_USAGE: &[u8] = &[0x55, 0x1d, 0x0f];
+/// 2.5.29.17
+const SUBJECT_ALT_NAME: &[u8] = &[0x55, 0x1d, 0x11];
+/// 2.5.29.19
+const BASIC_CONSTRAINTS: &[u8] = &[0x55, 0x1d, 0x13];
+/// 2.5.29.37
+const EXTENDED_KEY_USAGE: &[u8] = &[0x55, 0x1d, 0x25];
+/// 1.3.6.1.5.5.7.3.1
+const SERVER_AUTH: &[u8] = &[0x2b, 0x06, 0x01, 0x05, 0x05, 0x07, 0x03, 0x01];
+
+/// A self-signed certificate authority, generated when the [`MockServer`](crate::MockServer)
+/// starts.
+pub(crate) struct CertificateAuthority {
+    key: SigningKey,
+    /// The DER-encoded name of the authority, the issuer of all the certificates it signs.
+    name: Vec<u8>,
+    /// The DER-encoded certificate of the authority.
+    certificate: Vec<u8>,
+}
+
+impl CertificateAuthority {
+    /// Generate a new key pair and the matching self-signed certificate.
+    pub(crate) fn generate() -> Self {
+        let key = SigningKey::random(&mut OsRng);
+        let name = name("wiremock CA");
+        let extensions = [
+            // A certificate authority, allowed to sign certificates.
+            extension(
+                BASIC_CONSTRAINTS,
+                true,
+                &der(SEQUENCE, &der(BOOLEAN, &[0xff])),
+            ),
+            extension(KEY_USAGE, true, &der(BIT_STRING, &[0x01, 0x06])),
+        ];
+        let certificate = sign(&key, tbs_certificate(&key, &name, &name, &extensions));
+        Self {
+            key,
+            name,
+            certificate,
+        }
+    }
+
+    /// The PEM-encoded certificate of the authority, for clients to trust it.
+    pub(crate) fn certificate_pem(&self) -> String {
+        let encoded = BASE64_STANDARD.encode(&self.certificate);
+        let mut pem = String::from("-----BEGIN CERTIFICATE-----\n");
+        for line in encoded.as_bytes().chunks(64) {
+            // Base64 is plain ASCII.
+            pem.push_str(std::str::from_utf8(line).unwrap());
+            pem.push('\n');
+        }
+        pem.push_str("-----END CERTIFICATE-----\n");
+        pem
+    }
+
+    /// Terminate TLS with a certificate issued for `host`.
+    pub(crate) fn acceptor_for(&self, host: &str) -> TlsAcceptor {
+        self.issue(host).acceptor()
+    }
+
+    /// Issue a certificate for `host` - a domain name or an IP address - with a new key pair.
+    fn issue(&self, host: &str) -> TlsConfig {
+        let key = SigningKey::random(&mut OsRng);
+        // IPv6 addresses are bracketed in authorities.
+        let host = host.trim_start_matches('[').trim_end_matches(']');
+        let alt_name = match host.parse::<IpAddr>() {
+            Ok(IpAddr::V4(ip)) => der(0x87, &ip.octets()),
+            Ok(IpAddr::V6(ip)) => der(0x87, &ip.octets()),
+            Err(_) => der(0x82, host.as_bytes()),
+        };
+        let extensions = [
+            extension(SUBJECT_ALT_NAME, false, &der(SEQUENCE, &alt_name)),
+            // Digital signature only.
+            extension(KEY_USAGE, true, &der(BIT_STRING, &[0x07, 0x80])),
+            extension(
+                EXTENDED_KEY_USAGE,
+                false,
+                &der(SEQUENCE, &der(OBJECT_IDENTIFIER, SERVER_AUTH)),
+            ),
+        ];
+        let certificate = sign(
+            &self.key,
+            tbs_certificate(&key, &self.name, &name(host), &extensions),
+        );
+        let private_key = key
+            .to_pkcs8_der()
+            .expect("A P-256 private key can always be encoded");
+        TlsConfig::from_der(
+            vec![
+                CertificateDer::from(certificate),
+                CertificateDer::from(self.certificate.clone()),
+            ],
+            PrivateKeyDer::Pkcs8(PrivatePkcs8KeyDer::from(private_key.as_bytes().to_vec())),
+        )
+    }
+}
+
+/// The part of a certificate covered by the signature of its issuer.
+fn tbs_certificate(
+    subject_key: &SigningKey,
+    issuer: &[u8],
+    subject: &[u8],
+    extensions: &[Vec<u8>],
+) -> Vec<u8> {
+    let mut serial_number = [0; 16];
+    OsRng.fill_bytes(&mut serial_number);
+    // Positive, without leading zeroes.
+    serial_number[0] = serial_number[0] & 0x7f | 0x40;
+    let public_key = subject_key
+        .verifying_key()
+        .to_public_key_der()
+        .expect("A P-256 public key can always be encoded");
+    let validity = [
+        der(UTC_TIME, b"200101000000Z"),
+        // No well-defined expiration date, as per RFC 5280.
+        der(GENERALIZED_TIME, b"99991231235959Z"),
+    ]
+    .concat();
+    let fields = [
+        // Version 3.
+        der(0xa0, &der(INTEGER, &[0x02])),
+        der(INTEGER, &serial_number),
+        der(SEQUENCE, &der(OBJECT_IDENTIFIER, ECDSA_WITH_SHA256)),
+        issuer.to_vec(),
+        der(SEQUENCE, &validity),
+        subject.to_vec(),
+        public_key.as_bytes().to_vec(),
+        der(0xa3, &der(SEQUENCE, &extensions.concat())),
+    ];
+    der(SEQUENCE, &fields.concat())
+}
+
+/// Assemble a certificate out of its `tbs_certificate`, signed with `issuer_key`.
+fn sign(issuer_key: &SigningKey, tbs_certificate: Vec<u8>) -> Vec<u8> {
+    let signature: Signature = issuer_key.sign(&tbs_certificate);
+    let mut signature_bits = vec![0x00];
+    signature_bits.extend_from_slice(signature.to_der().as_bytes());
+    let fields = [
+        tbs_certificate,
+        der(SEQUENCE, &der(OBJECT_IDENTIFIER, ECDSA_WITH_SHA256)),
+        der(BIT_STRING, &signature_bits),
+    ];
+    der(SEQUENCE, &fields.concat())
+}
+
+/// A distinguished name made of a single common name.
+fn name(common_name: &str) -> Vec<u8> {
+    let attribute = [
+        der(OBJECT_IDENTIFIER, COMMON_NAME),
+        der(UTF8_STRING, common_name.as_bytes()),
+    ];
+    der(SEQUENCE, &der(SET, &der(SEQUENCE, &attribute.concat())))
+}
+
+fn extension(id: &[u8], critical: bool, value: &[u8]) -> Vec<u8> {
+    let mut fields = der(OBJECT_IDENTIFIER, id);
+    if critical {
+        fields.extend(der(BOOLEAN, &[0xff]));
+    }
+    fields.extend(der(OCTET_STRING, value));
+    der(SEQUENCE, &fields)
+}
+
+/// Encode a DER element - a `tag`, followed by the length of `contents` and `contents`.
+fn der(tag: u8, contents: &[u8]) -> Vec<u8> {
+    let mut element = vec![tag];
+    if contents.len() < 0x80 {
+        element.push(contents.len() as u8);
+    } else {
+        let length = contents.len().to_be_bytes();
+        let length = &length[length.iter().take_while(|&&byte| byte == 0).count()..];
+        element.push(0x80 | length.len() as u8);
+        element.extend_from_slice(length);
+    }
+    element.extend_from_slice(contents);
+    element
+}
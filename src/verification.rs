@@ -1,4 +1,11 @@
 use crate::mock::Times;
+use crate::request::BodyPrintLimit;
+use crate::response_body::BodyConsumption;
+use crate::Request;
+use http::header::HeaderName;
+use std::fmt::Write;
+use std::panic::Location;
+use std::time::Duration;
 
 /// A report returned by an `MountedMock` detailing what the user expectations were and
 /// how many calls were actually received since the mock was mounted on the server.
@@ -10,17 +17,123 @@ pub(crate) struct VerificationReport {
     pub(crate) expectation_range: Times,
     /// Actual number of received requests that matched the specification
     pub(crate) n_matched_requests: u64,
+    /// Set if two consecutive matching requests were received closer than allowed by
+    /// [`Mock::expect_min_interval`](crate::Mock::expect_min_interval).
+    pub(crate) interval_violation: Option<IntervalViolation>,
+    /// Set if the body of a response was not consumed within the budget set by
+    /// [`Mock::expect_response_consumed_within`](crate::Mock::expect_response_consumed_within).
+    pub(crate) consumption_violation: Option<ConsumptionViolation>,
     /// The position occupied by the mock that generated the report within its parent
     /// [`MountedMockSet`](crate::mock_set::MountedMockSet) collection of `MountedMock`s.
     ///
     /// E.g. `0` if it is the first mock that we try to match against an incoming request, `1`
     /// if it is the second, etc.
     pub(crate) position_in_set: usize,
+    /// Where the mock was mounted or registered, if known.
+    pub(crate) registered_at: Option<&'static Location<'static>>,
+    /// Set if the mock was configured using [`Mock::expect_never`](crate::Mock::expect_never).
+    pub(crate) forbidden: Option<ForbiddenMock>,
+    /// Unset if the mock was configured using
+    /// [`Mock::skip_drop_verification`](crate::Mock::skip_drop_verification).
+    pub(crate) verify_on_drop: bool,
+}
+
+/// The details reported for a mock that must never match.
+#[derive(Clone)]
+pub(crate) struct ForbiddenMock {
+    /// Where `expect_never` was called.
+    pub(crate) location: &'static Location<'static>,
+    /// The requests that matched the mock, in the order they were received.
+    pub(crate) matched_requests: Vec<Request>,
+}
+
+/// The closest pair of consecutive requests matched by a mock, received at a shorter interval
+/// than the minimum one.
+#[derive(Clone)]
+pub(crate) struct IntervalViolation {
+    /// The minimum interval set on the mock.
+    pub(crate) min_interval: Duration,
+    /// The position of the second request of the pair among the matched ones, starting from `1`.
+    pub(crate) request_number: usize,
+    /// How far apart the two requests were received.
+    pub(crate) interval: Duration,
+}
+
+impl IntervalViolation {
+    /// Find the closest pair of consecutive requests received less than `min_interval` apart,
+    /// if any.
+    /// Requests without an arrival time - i.e. built by hand - are ignored.
+    pub(crate) fn find(requests: &[Request], min_interval: Duration) -> Option<Self> {
+        let arrivals: Vec<(usize, Duration)> = requests
+            .iter()
+            .enumerate()
+            .filter_map(|(index, request)| Some((index + 1, request.received_at()?)))
+            .collect();
+        arrivals
+            .windows(2)
+            .map(|pair| (pair[1].0, pair[1].1.saturating_sub(pair[0].1)))
+            .filter(|(_, interval)| *interval < min_interval)
+            .min_by_key(|(_, interval)| *interval)
+            .map(|(request_number, interval)| IntervalViolation {
+                min_interval,
+                request_number,
+                interval,
+            })
+    }
+}
+
+/// The first response sent by a mock whose body was not consumed within the budget.
+#[derive(Clone)]
+pub(crate) struct ConsumptionViolation {
+    /// The budget set on the mock.
+    pub(crate) budget: Duration,
+    /// The position of the response among the ones sent by the mock, starting from `1`.
+    pub(crate) response_number: usize,
+    /// How far the server got in writing the body.
+    pub(crate) body: BodyConsumption,
+}
+
+impl ConsumptionViolation {
+    /// Find the first body in `bodies` that was not consumed within `budget`, if any.
+    /// Bodies that are still being written, within the budget, are fine.
+    pub(crate) fn find(bodies: &[BodyConsumption], budget: Duration) -> Option<Self> {
+        bodies
+            .iter()
+            .position(|body| match body {
+                BodyConsumption::InProgress(started_at) => started_at.elapsed() > budget,
+                BodyConsumption::Consumed(elapsed) => *elapsed > budget,
+                BodyConsumption::Abandoned(_) => true,
+            })
+            .map(|index| ConsumptionViolation {
+                budget,
+                response_number: index + 1,
+                body: bodies[index],
+            })
+    }
+
+    fn describe(&self) -> String {
+        match self.body {
+            BodyConsumption::InProgress(started_at) => {
+                format!("not consumed yet, after {:?}", started_at.elapsed())
+            }
+            BodyConsumption::Consumed(elapsed) => format!("consumed in {:?}", elapsed),
+            BodyConsumption::Abandoned(elapsed) => format!(
+                "abandoned after {:?}: the connection was closed before it was sent in full",
+                elapsed
+            ),
+        }
+    }
 }
 
 impl VerificationReport {
-    pub(crate) fn error_message(&self) -> String {
-        if let Some(ref mock_name) = self.mock_name {
+    /// Describe the failed verification.
+    ///
+    /// The values of the headers listed in `redacted_headers` are hidden in request dumps.
+    pub(crate) fn error_message(&self, redacted_headers: &[HeaderName]) -> String {
+        if let Some(forbidden) = &self.forbidden {
+            return self.forbidden_error_message(forbidden, redacted_headers);
+        }
+        let mut message = if let Some(ref mock_name) = self.mock_name {
             format!(
                 "{}.\n\tExpected range of matching incoming requests: {}\n\tNumber of matched incoming requests: {}",
                 mock_name, self.expectation_range, self.n_matched_requests
@@ -30,11 +143,90 @@ impl VerificationReport {
                 "Mock #{}.\n\tExpected range of matching incoming requests: {}\n\tNumber of matched incoming requests: {}",
                 self.position_in_set, self.expectation_range, self.n_matched_requests
             )
+        };
+        if let Some(violation) = &self.interval_violation {
+            _ = write!(
+                message,
+                "\n\tExpected minimum interval between matching incoming requests: {:?}\n\tShortest interval: {:?}, between requests #{} and #{}",
+                violation.min_interval,
+                violation.interval,
+                violation.request_number - 1,
+                violation.request_number
+            );
+        }
+        if let Some(violation) = &self.consumption_violation {
+            _ = write!(
+                message,
+                "\n\tExpected response bodies to be consumed within: {:?}\n\tBody of response #{}: {}",
+                violation.budget,
+                violation.response_number,
+                violation.describe()
+            );
+        }
+        self.write_registered_at(&mut message);
+        message
+    }
+
+    fn write_registered_at(&self, message: &mut String) {
+        if let Some(location) = self.registered_at {
+            _ = write!(message, "\n\tMounted at: {}", location);
+        }
+    }
+
+    fn forbidden_error_message(
+        &self,
+        forbidden: &ForbiddenMock,
+        redacted_headers: &[HeaderName],
+    ) -> String {
+        let mut message = match &self.mock_name {
+            Some(mock_name) => format!("{}.", mock_name),
+            None => format!("Mock #{}.", self.position_in_set),
+        };
+        _ = write!(
+            message,
+            "\n\tExpected to never match (see `expect_never` at {})\n\tNumber of matched incoming requests: {}",
+            forbidden.location, self.n_matched_requests
+        );
+        self.write_registered_at(&mut message);
+        _ = write!(message, "\n\tMatched requests:");
+        for (index, request) in forbidden.matched_requests.iter().enumerate() {
+            _ = write!(message, "\n\t- Request #{}\n\t", index + 1);
+            _ = request.print_with_limit(&mut message, BodyPrintLimit::Unlimited, redacted_headers);
+        }
+        message
+    }
+
+    /// Emit a `tracing` event describing the failed verification.
+    pub(crate) fn trace_failure(&self) {
+        match &self.mock_name {
+            Some(mock_name) => tracing::error!(
+                mock.name = %mock_name,
+                mock.position = self.position_in_set,
+                mock.mounted_at = self.registered_at.map(tracing::field::display),
+                expected = %self.expectation_range,
+                matched = self.n_matched_requests,
+                "Mock expectations were not satisfied"
+            ),
+            None => tracing::error!(
+                mock.position = self.position_in_set,
+                mock.mounted_at = self.registered_at.map(tracing::field::display),
+                expected = %self.expectation_range,
+                matched = self.n_matched_requests,
+                "Mock expectations were not satisfied"
+            ),
         }
     }
 
     pub(crate) fn is_satisfied(&self) -> bool {
         self.expectation_range.contains(self.n_matched_requests)
+            && self.interval_violation.is_none()
+            && self.consumption_violation.is_none()
+    }
+
+    /// Whether the verification passes when it is triggered by a `Drop` implementation:
+    /// mocks that opted out of it always pass.
+    pub(crate) fn is_satisfied_on_drop(&self) -> bool {
+        !self.verify_on_drop || self.is_satisfied()
     }
 }
 
@@ -0,0 +1,197 @@
+use crate::Request;
+use http::{HeaderMap, HeaderName, HeaderValue, Method};
+use http_body_util::Full;
+use hyper::body::Bytes;
+use hyper_util::rt::TokioIo;
+use serde::Serialize;
+use std::convert::TryInto;
+use std::fmt::{Debug, Formatter};
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::{debug, warn};
+
+type FromRequest<T> = Arc<dyn Fn(&Request) -> T + Send + Sync>;
+
+/// A value that is either fixed or computed out of the request that triggered a webhook.
+#[derive(Clone)]
+enum Template<T> {
+    Fixed(T),
+    FromRequest(FromRequest<T>),
+}
+
+impl<T: Clone> Template<T> {
+    fn render(&self, request: &Request) -> T {
+        match self {
+            Template::Fixed(value) => value.clone(),
+            Template::FromRequest(f) => f(request),
+        }
+    }
+}
+
+/// An outbound HTTP request fired by the [`MockServer`](crate::MockServer) every time a
+/// [`Mock`](crate::Mock) matches an incoming request - see [`Mock::with_webhook`](crate::Mock::with_webhook).
+///
+/// Use it to simulate services that call you back asynchronously: payment providers
+/// notifying the outcome of a payment, job schedulers reporting completion, etc.
+///
+/// The target URL and the body of the webhook can be fixed or derived from the request that
+/// triggered it - e.g. to call back the URL that the client provided in its request.
+///
+/// Webhooks are fired in the background, once the response to the triggering request has
+/// been sent - body included: their outcome does not affect the mock server. Failures are
+/// logged as `WARN` [`tracing`](https://docs.rs/tracing) events.
+/// Only plain `http` target URLs are supported.
+#[derive(Clone)]
+pub struct WebhookTrigger {
+    method: Method,
+    url: Template<String>,
+    headers: HeaderMap,
+    body: Template<Vec<u8>>,
+    delay: Option<Duration>,
+}
+
+impl Debug for WebhookTrigger {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("WebhookTrigger")
+            .field("method", &self.method)
+            .field("headers", &self.headers)
+            .field("delay", &self.delay)
+            .finish()
+    }
+}
+
+impl WebhookTrigger {
+    /// A webhook sending a request with the specified method to `url`, with an empty body.
+    pub fn new<M, U>(method: M, url: U) -> Self
+    where
+        M: TryInto<Method>,
+        <M as TryInto<Method>>::Error: std::fmt::Debug,
+        U: Into<String>,
+    {
+        Self {
+            method: method.try_into().expect("Failed to convert into method."),
+            url: Template::Fixed(url.into()),
+            headers: HeaderMap::new(),
+            body: Template::Fixed(vec![]),
+            delay: None,
+        }
+    }
+
+    /// A webhook sending a `POST` request to `url` - the most common flavour.
+    pub fn post<U: Into<String>>(url: U) -> Self {
+        Self::new(Method::POST, url)
+    }
+
+    /// Compute the target URL out of the request that triggered the webhook, instead of using
+    /// the URL passed to [`WebhookTrigger::new`].
+    pub fn url_from_request<F>(mut self, url: F) -> Self
+    where
+        F: Fn(&Request) -> String + Send + Sync + 'static,
+    {
+        self.url = Template::FromRequest(Arc::new(url));
+        self
+    }
+
+    /// Add a header to the webhook request, replacing any existing value.
+    pub fn insert_header<K, V>(mut self, key: K, value: V) -> Self
+    where
+        K: TryInto<HeaderName>,
+        <K as TryInto<HeaderName>>::Error: std::fmt::Debug,
+        V: TryInto<HeaderValue>,
+        <V as TryInto<HeaderValue>>::Error: std::fmt::Debug,
+    {
+        let key = key.try_into().expect("Failed to convert into header name.");
+        let value = value
+            .try_into()
+            .expect("Failed to convert into header value.");
+        self.headers.insert(key, value);
+        self
+    }
+
+    /// Set the body of the webhook request to the specified bytes.
+    pub fn set_body_bytes<B: Into<Vec<u8>>>(mut self, body: B) -> Self {
+        self.body = Template::Fixed(body.into());
+        self
+    }
+
+    /// Set the body of the webhook request to the specified string, with a
+    /// `Content-Type: text/plain` header.
+    pub fn set_body_string<T: Into<String>>(self, body: T) -> Self {
+        self.insert_header(http::header::CONTENT_TYPE, "text/plain")
+            .set_body_bytes(body.into())
+    }
+
+    /// Set the body of the webhook request to the JSON serialization of `body`, with a
+    /// `Content-Type: application/json` header.
+    ///
+    /// It panics if `body` cannot be serialized to JSON.
+    pub fn set_body_json<B: Serialize>(self, body: B) -> Self {
+        let body = serde_json::to_vec(&body).expect("Failed to convert into body.");
+        self.insert_header(http::header::CONTENT_TYPE, "application/json")
+            .set_body_bytes(body)
+    }
+
+    /// Compute the body of the webhook request out of the request that triggered it.
+    pub fn body_from_request<F>(mut self, body: F) -> Self
+    where
+        F: Fn(&Request) -> Vec<u8> + Send + Sync + 'static,
+    {
+        self.body = Template::FromRequest(Arc::new(body));
+        self
+    }
+
+    /// Wait for `delay` after the response to the triggering request has been sent before
+    /// firing the webhook.
+    pub fn set_delay(mut self, delay: Duration) -> Self {
+        self.delay = Some(delay);
+        self
+    }
+
+    /// Fire the webhook in the background, on the runtime of the mock server.
+    pub(crate) fn spawn(&self, request: &Request) {
+        let url = self.url.render(request);
+        let body = self.body.render(request);
+        let webhook = self.clone();
+        tokio::spawn(async move {
+            if let Some(delay) = webhook.delay {
+                tokio::time::sleep(delay).await;
+            }
+            match webhook.send(&url, body).await {
+                Ok(status) => debug!("Webhook {} {} returned {}", webhook.method, url, status),
+                Err(e) => warn!("Webhook {} {} failed: {}", webhook.method, url, e),
+            }
+        });
+    }
+
+    async fn send(&self, url: &str, body: Vec<u8>) -> Result<http::StatusCode, String> {
+        let url: url::Url = url.parse().map_err(|e| format!("invalid URL: {}", e))?;
+        if url.scheme() != "http" {
+            return Err(format!("unsupported scheme `{}`", url.scheme()));
+        }
+        let host = url.host_str().ok_or("the URL has no host")?;
+        let port = url.port_or_known_default().unwrap_or(80);
+        let stream = tokio::net::TcpStream::connect((host, port))
+            .await
+            .map_err(|e| e.to_string())?;
+        let (mut sender, connection) = hyper::client::conn::http1::handshake(TokioIo::new(stream))
+            .await
+            .map_err(|e| e.to_string())?;
+        tokio::spawn(connection);
+
+        let mut request = hyper::Request::builder()
+            .method(self.method.clone())
+            .uri(&url[url::Position::BeforePath..])
+            .header(
+                http::header::HOST,
+                &url[url::Position::BeforeHost..url::Position::AfterPort],
+            )
+            .body(Full::new(Bytes::from(body)))
+            .map_err(|e| e.to_string())?;
+        request.headers_mut().extend(self.headers.clone());
+        let response = sender
+            .send_request(request)
+            .await
+            .map_err(|e| e.to_string())?;
+        Ok(response.status())
+    }
+}
@@ -0,0 +1,131 @@
+#![cfg(feature = "aws")]
+use wiremock::fakes::s3::ListObjectsV2;
+use wiremock::matchers::{aws_sigv4_valid, AwsCredentials};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+// The example request of the AWS documentation, signed for the IAM service.
+async fn send_list_users(mock_server: &MockServer, body: &str) -> surf::Response {
+    surf::get(format!(
+        "{}/?Action=ListUsers&Version=2010-05-08",
+        mock_server.uri()
+    ))
+    .header("Host", "iam.amazonaws.com")
+    .header(
+        "Content-Type",
+        "application/x-www-form-urlencoded; charset=utf-8",
+    )
+    .header("X-Amz-Date", "20150830T123600Z")
+    .header(
+        "Authorization",
+        "AWS4-HMAC-SHA256 Credential=AKIDEXAMPLE/20150830/us-east-1/iam/aws4_request, \
+         SignedHeaders=content-type;host;x-amz-date, \
+         Signature=5d672d79c15b13162d9279b0855cfba6789a8edb4c82c400e06b5924a6f2b5d7",
+    )
+    .body_string(body.into())
+    .await
+    .unwrap()
+}
+
+#[async_std::test]
+async fn sigv4_signatures_are_verified_against_the_expected_credentials() {
+    // Arrange
+    let mock_server = MockServer::start().await;
+    let secret = "wJalrXUtnFEMI/K7MDENG+bPxRfiCYEXAMPLEKEY";
+    Mock::given(aws_sigv4_valid(
+        "us-east-1",
+        "iam",
+        AwsCredentials::new("AKIDEXAMPLE", secret),
+    ))
+    .respond_with(ResponseTemplate::new(200))
+    .mount(&mock_server)
+    .await;
+
+    // Act
+    let valid = send_list_users(&mock_server, "").await;
+    let tampered_body = send_list_users(&mock_server, "Action=DeleteUser").await;
+
+    // Assert
+    assert_eq!(valid.status(), 200);
+    assert_eq!(tampered_body.status(), 404);
+}
+
+#[async_std::test]
+async fn sigv4_signatures_computed_with_other_credentials_or_scope_do_not_match() {
+    // Arrange
+    let mock_server = MockServer::start().await;
+    let secret = "wJalrXUtnFEMI/K7MDENG+bPxRfiCYEXAMPLEKEY";
+    Mock::given(aws_sigv4_valid(
+        "us-east-1",
+        "iam",
+        AwsCredentials::new("AKIDEXAMPLE", "another-secret"),
+    ))
+    .respond_with(ResponseTemplate::new(200))
+    .mount(&mock_server)
+    .await;
+    Mock::given(aws_sigv4_valid(
+        "eu-west-1",
+        "iam",
+        AwsCredentials::new("AKIDEXAMPLE", secret),
+    ))
+    .respond_with(ResponseTemplate::new(200))
+    .mount(&mock_server)
+    .await;
+    Mock::given(aws_sigv4_valid(
+        "us-east-1",
+        "iam",
+        AwsCredentials::new("AKIDEXAMPLE", secret).with_session_token("token"),
+    ))
+    .respond_with(ResponseTemplate::new(200))
+    .mount(&mock_server)
+    .await;
+
+    // Act
+    let response = send_list_users(&mock_server, "").await;
+
+    // Assert
+    assert_eq!(response.status(), 404);
+}
+
+#[async_std::test]
+async fn list_objects_v2_paginates_and_rolls_up_common_prefixes() {
+    // Arrange
+    let mock_server = MockServer::start().await;
+    Mock::given(wiremock::matchers::path("/bucket"))
+        .respond_with(
+            ListObjectsV2::new("bucket")
+                .object("a.txt", "a")
+                .object("logs/1.log", "1")
+                .object("logs/2.log", "2")
+                .object("z.txt", "z"),
+        )
+        .mount(&mock_server)
+        .await;
+    let uri = format!(
+        "{}/bucket?list-type=2&delimiter=/&max-keys=2",
+        mock_server.uri()
+    );
+
+    // Act
+    let first_page = surf::get(&uri).await.unwrap().body_string().await.unwrap();
+    let token = first_page
+        .split("<NextContinuationToken>")
+        .nth(1)
+        .and_then(|rest| rest.split('<').next())
+        .unwrap();
+    let second_page = surf::get(format!("{}&continuation-token={}", uri, token))
+        .await
+        .unwrap()
+        .body_string()
+        .await
+        .unwrap();
+
+    // Assert
+    assert!(first_page.contains("<IsTruncated>true</IsTruncated>"));
+    assert!(first_page.contains("<Key>a.txt</Key>"));
+    assert!(first_page.contains("<CommonPrefixes><Prefix>logs/</Prefix></CommonPrefixes>"));
+    assert!(!first_page.contains("logs/1.log"));
+
+    assert!(second_page.contains("<IsTruncated>false</IsTruncated>"));
+    assert!(second_page.contains("<KeyCount>1</KeyCount>"));
+    assert!(second_page.contains("<Key>z.txt</Key>"));
+}
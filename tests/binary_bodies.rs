@@ -0,0 +1,105 @@
+#![cfg(any(feature = "msgpack", feature = "cbor"))]
+use serde::{Deserialize, Serialize};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+struct User {
+    name: String,
+    age: u32,
+    tags: Vec<String>,
+}
+
+fn jane() -> User {
+    User {
+        name: "Jane".into(),
+        age: 42,
+        tags: vec!["admin".into()],
+    }
+}
+
+#[cfg(feature = "msgpack")]
+#[async_std::test]
+async fn msgpack_bodies_are_matched_structurally_and_served_with_their_content_type() {
+    use wiremock::matchers::body_msgpack;
+
+    // Arrange
+    let mock_server = MockServer::start().await;
+    Mock::given(body_msgpack(jane()))
+        .respond_with(ResponseTemplate::new(200).set_body_msgpack(jane()))
+        .mount(&mock_server)
+        .await;
+    let reordered = rmpv::Value::Map(vec![
+        ("tags".into(), vec![rmpv::Value::from("admin")].into()),
+        ("age".into(), 42.into()),
+        ("name".into(), "Jane".into()),
+    ]);
+    let mut reordered_body = vec![];
+    rmpv::encode::write_value(&mut reordered_body, &reordered).unwrap();
+    let older = User { age: 43, ..jane() };
+
+    // Act
+    let mut response = surf::post(mock_server.uri())
+        .body(reordered_body)
+        .await
+        .unwrap();
+    let different = surf::post(mock_server.uri())
+        .body(rmp_serde::to_vec_named(&older).unwrap())
+        .await
+        .unwrap();
+
+    // Assert
+    assert_eq!(response.status(), 200);
+    assert_eq!(
+        response.header("Content-Type").unwrap().as_str(),
+        "application/msgpack"
+    );
+    let body = response.body_bytes().await.unwrap();
+    assert_eq!(rmp_serde::from_slice::<User>(&body).unwrap(), jane());
+    assert_eq!(different.status(), 404);
+}
+
+#[cfg(feature = "cbor")]
+#[async_std::test]
+async fn cbor_bodies_are_matched_structurally_and_served_with_their_content_type() {
+    use wiremock::matchers::body_cbor;
+
+    // Arrange
+    let mock_server = MockServer::start().await;
+    Mock::given(body_cbor(jane()))
+        .respond_with(ResponseTemplate::new(200).set_body_cbor(jane()))
+        .mount(&mock_server)
+        .await;
+    let reordered = ciborium::Value::Map(vec![
+        ("tags".into(), vec![ciborium::Value::from("admin")].into()),
+        ("age".into(), 42.into()),
+        ("name".into(), "Jane".into()),
+    ]);
+    let mut reordered_body = vec![];
+    ciborium::into_writer(&reordered, &mut reordered_body).unwrap();
+    let mut trailing_garbage = vec![];
+    ciborium::into_writer(&jane(), &mut trailing_garbage).unwrap();
+    trailing_garbage.push(0xff);
+
+    // Act
+    let mut response = surf::post(mock_server.uri())
+        .body(reordered_body)
+        .await
+        .unwrap();
+    let invalid = surf::post(mock_server.uri())
+        .body(trailing_garbage)
+        .await
+        .unwrap();
+
+    // Assert
+    assert_eq!(response.status(), 200);
+    assert_eq!(
+        response.header("Content-Type").unwrap().as_str(),
+        "application/cbor"
+    );
+    let body = response.body_bytes().await.unwrap();
+    assert_eq!(
+        ciborium::from_reader::<User, _>(body.as_slice()).unwrap(),
+        jane()
+    );
+    assert_eq!(invalid.status(), 404);
+}
@@ -0,0 +1,81 @@
+#![cfg(feature = "debug-ui")]
+use serde_json::Value;
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+#[async_std::test]
+async fn debug_ui_lists_mocks_and_requests() {
+    // Arrange
+    let mock_server = MockServer::builder().enable_debug_ui().start().await;
+    Mock::given(method("GET"))
+        .and(path("/users"))
+        .respond_with(ResponseTemplate::new(200))
+        .named("List users")
+        .mount(&mock_server)
+        .await;
+    surf::get(format!("{}/users", mock_server.uri()))
+        .await
+        .unwrap();
+    surf::get(format!("{}/orders", mock_server.uri()))
+        .await
+        .unwrap();
+
+    // Act
+    let state: Value = surf::get(format!("{}/__admin/ui/state", mock_server.uri()))
+        .recv_json()
+        .await
+        .unwrap();
+
+    // Assert
+    let mock = &state["mocks"][0];
+    assert_eq!(mock["name"], "List users");
+    assert_eq!(mock["n_matches"], 1);
+    assert_eq!(mock["paused"], false);
+    assert_eq!(mock["matchers"].as_array().unwrap().len(), 2);
+    // Most recent first, without the requests to the UI itself.
+    let requests = state["requests"].as_array().unwrap();
+    assert_eq!(requests.len(), 2);
+    assert_eq!(requests[0]["status"], 404);
+    assert_eq!(requests[0]["matched_mock"], Value::Null);
+    assert_eq!(requests[1]["matched_mock"], "List users");
+}
+
+#[async_std::test]
+async fn mocks_can_be_paused_and_resumed_from_the_debug_ui() {
+    // Arrange
+    let mock_server = MockServer::builder().enable_debug_ui().start().await;
+    Mock::given(path("/users"))
+        .respond_with(ResponseTemplate::new(200))
+        .mount(&mock_server)
+        .await;
+    let toggle = format!("{}/__admin/ui/mocks/0/toggle", mock_server.uri());
+    let users = format!("{}/users", mock_server.uri());
+
+    // Act
+    surf::post(&toggle).await.unwrap();
+    let paused = surf::get(&users).await.unwrap();
+    surf::post(&toggle).await.unwrap();
+    let resumed = surf::get(&users).await.unwrap();
+    let missing = surf::post(format!("{}/__admin/ui/mocks/7/toggle", mock_server.uri()))
+        .await
+        .unwrap();
+
+    // Assert
+    assert_eq!(paused.status(), 404);
+    assert_eq!(resumed.status(), 200);
+    assert_eq!(missing.status(), 404);
+}
+
+#[async_std::test]
+async fn the_debug_ui_is_disabled_by_default() {
+    // Arrange
+    let mock_server = MockServer::start().await;
+
+    // Act
+    let response = surf::get(format!("{}/__admin/ui", mock_server.uri()))
+        .await
+        .unwrap();
+
+    // Assert
+    assert_eq!(response.status(), 404);
+}
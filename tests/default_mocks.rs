@@ -0,0 +1,45 @@
+use wiremock::matchers::{method, path};
+use wiremock::pool::{configure, PoolConfig};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+// Each integration test file runs in its own process: the pool configuration set here does not
+// affect the other test suites.
+#[async_std::test]
+async fn default_mocks_are_mounted_on_every_started_server() {
+    // Arrange
+    configure(PoolConfig::new().with_default_mocks(|| {
+        vec![Mock::given(path("/health"))
+            .respond_with(ResponseTemplate::new(200))
+            .with_priority(u8::MAX)]
+    }));
+
+    for _ in 0..2 {
+        let mock_server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/health"))
+            .respond_with(ResponseTemplate::new(503))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        // Act
+        let overridden = surf::get(format!("{}/health", mock_server.uri()))
+            .await
+            .unwrap();
+        let default = surf::post(format!("{}/health", mock_server.uri()))
+            .await
+            .unwrap();
+
+        // Assert
+        assert_eq!(overridden.status(), 503);
+        assert_eq!(default.status(), 200);
+    }
+
+    // Servers started with a builder are left untouched.
+    let mock_server = MockServer::builder().start().await;
+    let status = surf::get(format!("{}/health", mock_server.uri()))
+        .await
+        .unwrap()
+        .status();
+    assert_eq!(status, 404);
+}
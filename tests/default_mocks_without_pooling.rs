@@ -0,0 +1,28 @@
+use wiremock::matchers::path;
+use wiremock::pool::{configure, PoolConfig};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+// Each integration test file runs in its own process: the pool configuration set here does not
+// affect the other test suites.
+#[async_std::test]
+#[should_panic(expected = "Mounted at: tests/default_mocks_without_pooling.rs")]
+async fn default_mocks_are_mounted_on_dedicated_servers_too() {
+    // Arrange
+    configure(PoolConfig::new().disable_pooling().with_default_mocks(|| {
+        vec![Mock::given(path("/token"))
+            .respond_with(ResponseTemplate::new(200))
+            .expect(2)]
+    }));
+    let mock_server = MockServer::start().await;
+
+    // Act
+    let status = surf::post(format!("{}/token", mock_server.uri()))
+        .await
+        .unwrap()
+        .status();
+
+    // Assert
+    assert_eq!(status, 200);
+    // The default mock expected a second request: verifying it on drop panics, pointing at
+    // the `with_default_mocks` call above.
+}
@@ -0,0 +1,109 @@
+use serde_json::json;
+use std::path::PathBuf;
+use wiremock::har::MatchStrategy;
+use wiremock::MockServer;
+
+fn har_entry(method: &str, url: &str, status: u16, body: &str) -> serde_json::Value {
+    json!({
+        "request": { "method": method, "url": url, "headers": [] },
+        "response": {
+            "status": status,
+            "headers": [
+                { "name": "Content-Type", "value": "text/plain" },
+                { "name": "Content-Encoding", "value": "gzip" }
+            ],
+            "content": { "size": body.len(), "text": body }
+        }
+    })
+}
+
+fn write_har(name: &str, entries: Vec<serde_json::Value>) -> PathBuf {
+    let path = std::env::temp_dir().join(format!("wiremock-{}-{}.har", name, std::process::id()));
+    let har = json!({ "log": { "version": "1.2", "entries": entries } });
+    std::fs::write(&path, serde_json::to_vec(&har).unwrap()).unwrap();
+    path
+}
+
+#[async_std::test]
+async fn entries_matching_the_same_requests_are_replayed_in_order() {
+    // Arrange
+    let path = write_har(
+        "in-order",
+        vec![
+            har_entry("GET", "https://example.com/job", 202, "pending"),
+            har_entry("GET", "https://example.com/job", 200, "done"),
+        ],
+    );
+    let mock_server = MockServer::start().await;
+    mock_server
+        .mount_har(&path, MatchStrategy::MethodAndPath)
+        .await;
+    let url = format!("{}/job", mock_server.uri());
+
+    // Act
+    let mut first = surf::get(&url).await.unwrap();
+    let mut second = surf::get(&url).await.unwrap();
+    let mut third = surf::get(&url).await.unwrap();
+
+    // Assert
+    assert_eq!(first.status(), 202);
+    assert_eq!(first.body_string().await.unwrap(), "pending");
+    assert_eq!(second.body_string().await.unwrap(), "done");
+    // The last entry keeps responding
+    assert_eq!(third.body_string().await.unwrap(), "done");
+    // Transfer headers are not replayed
+    assert!(first.header("Content-Encoding").is_none());
+}
+
+#[async_std::test]
+async fn query_parameters_are_matched_when_requested() {
+    // Arrange
+    let path = write_har(
+        "query",
+        vec![
+            har_entry("GET", "https://example.com/users?page=1", 200, "first page"),
+            har_entry(
+                "GET",
+                "https://example.com/users?page=2",
+                200,
+                "second page",
+            ),
+        ],
+    );
+    let mock_server = MockServer::start().await;
+    mock_server
+        .mount_har(&path, MatchStrategy::MethodPathAndQuery)
+        .await;
+
+    // Act
+    let second = surf::get(format!("{}/users?page=2", mock_server.uri()))
+        .recv_string()
+        .await
+        .unwrap();
+    let first = surf::get(format!("{}/users?page=1", mock_server.uri()))
+        .recv_string()
+        .await
+        .unwrap();
+    let missing = surf::get(format!("{}/users?page=3", mock_server.uri()))
+        .await
+        .unwrap();
+
+    // Assert
+    assert_eq!(first, "first page");
+    assert_eq!(second, "second page");
+    assert_eq!(missing.status(), 404);
+}
+
+#[async_std::test]
+#[should_panic(expected = "Invalid HAR: `log.entries` must be an array")]
+async fn mounting_an_invalid_har_file_panics() {
+    // Arrange
+    let path = std::env::temp_dir().join(format!("wiremock-invalid-{}.har", std::process::id()));
+    std::fs::write(&path, "{}").unwrap();
+    let mock_server = MockServer::start().await;
+
+    // Act
+    mock_server
+        .mount_har(&path, MatchStrategy::MethodAndPath)
+        .await;
+}
@@ -4,8 +4,12 @@ use serde_json::json;
 use std::net::TcpStream;
 use std::time::Duration;
 use surf::StatusCode;
-use wiremock::matchers::{body_json, body_partial_json, method, path, PathExactMatcher};
-use wiremock::{Mock, MockServer, ResponseTemplate};
+use wiremock::matchers::{
+    body_json, body_partial_json, header, method, path, path_template, PathExactMatcher,
+};
+use wiremock::{
+    Mock, MockServer, RedirectChain, Request, Respond, ResponseContext, ResponseTemplate,
+};
 
 #[async_std::test]
 async fn new_starts_the_server() {
@@ -22,7 +26,7 @@ async fn returns_404_if_nothing_matches() {
     let mock_server = MockServer::start().await;
 
     // Act
-    let status = surf::get(&mock_server.uri()).await.unwrap().status();
+    let status = surf::get(mock_server.uri()).await.unwrap().status();
 
     // Assert
     assert_eq!(status, 404);
@@ -49,6 +53,7 @@ async fn panics_if_the_expectation_is_not_satisfied() {
 - Mock #0.
 \tExpected range of matching incoming requests: 1 <= x
 \tNumber of matched incoming requests: 0
+\tMounted at: tests/mocks.rs:67:10
 
 The server did not receive any request.")]
 async fn no_received_request_line_is_printed_in_the_panic_message_if_expectations_are_not_verified()
@@ -70,6 +75,7 @@ async fn no_received_request_line_is_printed_in_the_panic_message_if_expectation
 - Mock #0.
 \tExpected range of matching incoming requests: 1 <= x
 \tNumber of matched incoming requests: 0
+\tMounted at: tests/mocks.rs:90:10
 
 Received requests:
 - Request #1
@@ -85,7 +91,7 @@ async fn received_request_are_printed_as_panic_message_if_expectations_are_not_v
         .await;
 
     // Act - we sent a request that does not match (GET)
-    surf::get(&mock_server.uri()).await.unwrap();
+    surf::get(mock_server.uri()).await.unwrap();
 
     // Assert - verified on drop
 }
@@ -365,3 +371,2993 @@ async fn debug_prints_mock_server_variants() {
         format!("{:?}", bare_mock_server)
     );
 }
+
+#[async_std::test]
+async fn redirect_chain_points_back_at_the_mock_server() {
+    // Arrange
+    let mock_server = MockServer::start().await;
+    RedirectChain::new(["/old", "/new"])
+        .with_status(301)
+        .mount(&mock_server, ResponseTemplate::new(200))
+        .await;
+
+    // Act - surf does not follow redirects out of the box
+    let response = surf::get(format!("{}/old", mock_server.uri()))
+        .await
+        .unwrap();
+
+    // Assert
+    assert_eq!(response.status(), StatusCode::MovedPermanently);
+    assert_eq!(
+        response.header("Location").unwrap().as_str(),
+        format!("{}/new", mock_server.uri())
+    );
+}
+
+#[async_std::test]
+#[should_panic(expected = "must be in the 3xx range")]
+async fn redirect_chain_rejects_non_redirect_status_codes() {
+    let _ = RedirectChain::new(["/a", "/b"]).with_status(200);
+}
+
+#[async_std::test]
+async fn request_journal_is_none_if_recording_is_disabled() {
+    // Arrange
+    let mock_server = MockServer::builder()
+        .disable_request_recording()
+        .start()
+        .await;
+
+    // Act
+    surf::get(mock_server.uri()).await.unwrap();
+
+    // Assert
+    assert!(mock_server.request_journal().await.is_none());
+}
+
+#[async_std::test]
+async fn request_journal_is_cleared_on_reset() {
+    // Arrange
+    let mock_server = MockServer::start().await;
+    surf::get(mock_server.uri()).await.unwrap();
+
+    // Act
+    mock_server.reset().await;
+
+    // Assert
+    assert!(mock_server.request_journal().await.unwrap().is_empty());
+}
+
+#[async_std::test]
+#[should_panic(expected = "Mocks were not matched in the expected order:
+- Token endpoint: first matched at sequence number 1
+- Resource endpoint: first matched at sequence number 0
+")]
+async fn verify_sequence_panics_if_mocks_are_matched_out_of_order() {
+    // Arrange
+    let mock_server = MockServer::start().await;
+    let token_mock = Mock::given(path("/token"))
+        .respond_with(ResponseTemplate::new(200))
+        .named("Token endpoint")
+        .mount_as_scoped(&mock_server)
+        .await;
+    let resource_mock = Mock::given(path("/resource"))
+        .respond_with(ResponseTemplate::new(200))
+        .named("Resource endpoint")
+        .mount_as_scoped(&mock_server)
+        .await;
+
+    // Act
+    surf::get(format!("{}/resource", mock_server.uri()))
+        .await
+        .unwrap();
+    surf::get(format!("{}/token", mock_server.uri()))
+        .await
+        .unwrap();
+
+    // Assert
+    mock_server
+        .verify_sequence(&[&token_mock, &resource_mock])
+        .await;
+}
+
+#[async_std::test]
+#[should_panic(expected = "- Mock #0: never matched")]
+async fn verify_sequence_panics_if_a_mock_never_matched() {
+    // Arrange
+    let mock_server = MockServer::start().await;
+    let mock = Mock::given(path("/token"))
+        .respond_with(ResponseTemplate::new(200))
+        .mount_as_scoped(&mock_server)
+        .await;
+
+    // Assert
+    mock_server.verify_sequence(&[&mock]).await;
+}
+
+struct ContextEcho;
+
+impl Respond for ContextEcho {
+    fn respond(&self, _request: &Request) -> ResponseTemplate {
+        unreachable!("The server always calls `respond_with_ctx`")
+    }
+
+    fn respond_with_ctx(&self, _request: &Request, ctx: &ResponseContext) -> ResponseTemplate {
+        ResponseTemplate::new(200).set_body_json(json!({
+            "n_previous_matches": ctx.n_previous_matches,
+            "mock_name": ctx.mock_name,
+            "sequence_number": ctx.sequence_number,
+        }))
+    }
+}
+
+#[async_std::test]
+async fn responders_receive_the_match_context() {
+    // Arrange
+    let mock_server = MockServer::start().await;
+    Mock::given(path("/echo"))
+        .respond_with(ContextEcho)
+        .named("Echo")
+        .mount(&mock_server)
+        .await;
+
+    // Act
+    surf::get(format!("{}/unmatched", mock_server.uri()))
+        .await
+        .unwrap();
+    let first: serde_json::Value = surf::get(format!("{}/echo", mock_server.uri()))
+        .recv_json()
+        .await
+        .unwrap();
+    let second: serde_json::Value = surf::get(format!("{}/echo", mock_server.uri()))
+        .recv_json()
+        .await
+        .unwrap();
+
+    // Assert
+    assert_eq!(
+        first,
+        json!({"n_previous_matches": 0, "mock_name": "Echo", "sequence_number": 1})
+    );
+    assert_eq!(
+        second,
+        json!({"n_previous_matches": 1, "mock_name": "Echo", "sequence_number": 2})
+    );
+}
+
+#[async_std::test]
+async fn swapping_the_response_of_a_scoped_mock_preserves_its_expectations() {
+    // Arrange
+    let mock_server = MockServer::start().await;
+    let mock_guard = Mock::given(method("GET"))
+        .respond_with(ResponseTemplate::new(200))
+        .up_to_n_times(2)
+        .expect(2)
+        .mount_as_scoped(&mock_server)
+        .await;
+
+    // Act
+    let first = surf::get(mock_server.uri()).await.unwrap();
+    mock_guard.set_response(ResponseTemplate::new(418)).await;
+    let second = surf::get(mock_server.uri()).await.unwrap();
+    let third = surf::get(mock_server.uri()).await.unwrap();
+
+    // Assert
+    assert_eq!(first.status(), StatusCode::Ok);
+    assert_eq!(second.status(), StatusCode::ImATeapot);
+    // `up_to_n_times` still applies to the swapped response.
+    assert_eq!(third.status(), StatusCode::NotFound);
+}
+
+#[async_std::test]
+#[should_panic(expected = "There is no mounted mock named `Missing`.")]
+async fn updating_a_mock_that_does_not_exist_panics() {
+    // Arrange
+    let mock_server = MockServer::start().await;
+
+    // Act
+    mock_server
+        .update_mock("Missing", ResponseTemplate::new(200))
+        .await;
+}
+
+#[async_std::test]
+async fn requests_fall_through_to_lower_priority_mocks_while_a_mock_is_paused() {
+    // Arrange
+    let mock_server = MockServer::start().await;
+    let mock_guard = Mock::given(method("GET"))
+        .respond_with(ResponseTemplate::new(200))
+        .with_priority(1)
+        .expect(1)
+        .mount_as_scoped(&mock_server)
+        .await;
+    Mock::given(method("GET"))
+        .respond_with(ResponseTemplate::new(503))
+        .mount(&mock_server)
+        .await;
+
+    // Act
+    mock_guard.pause().await;
+    let paused = surf::get(mock_server.uri()).await.unwrap();
+    mock_guard.resume().await;
+    let resumed = surf::get(mock_server.uri()).await.unwrap();
+
+    // Assert
+    assert_eq!(paused.status(), StatusCode::ServiceUnavailable);
+    assert_eq!(resumed.status(), StatusCode::Ok);
+    // Only the request received while the mock was active counts towards its expectations.
+    assert_eq!(mock_guard.received_requests().await.len(), 1);
+}
+
+#[async_std::test]
+async fn removed_mocks_are_not_verified() {
+    // Arrange
+    let mock_server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .respond_with(ResponseTemplate::new(200))
+        .named("Never called")
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+
+    // Act
+    mock_server.remove_mock("Never called").await;
+
+    // Assert - dropping the server does not panic
+    mock_server.verify().await;
+}
+
+#[async_std::test]
+async fn body_files_are_read_when_the_response_is_generated() {
+    // Arrange
+    let mock_server = MockServer::start().await;
+    let fixture = std::env::temp_dir().join(format!("wiremock-body-{}.json", std::process::id()));
+    let _ = std::fs::remove_file(&fixture);
+    Mock::given(method("GET"))
+        .respond_with(ResponseTemplate::new(200).set_body_json_file(&fixture))
+        .mount(&mock_server)
+        .await;
+
+    // Act
+    let mut missing = surf::get(mock_server.uri()).await.unwrap();
+    std::fs::write(&fixture, "not json").unwrap();
+    let mut invalid = surf::get(mock_server.uri()).await.unwrap();
+    std::fs::write(&fixture, r#"{"hello": "world"}"#).unwrap();
+    let mut valid = surf::get(mock_server.uri()).await.unwrap();
+
+    // Assert
+    assert_eq!(missing.status(), StatusCode::InternalServerError);
+    assert!(missing
+        .body_string()
+        .await
+        .unwrap()
+        .contains("failed to read the response body"));
+    assert_eq!(invalid.status(), StatusCode::InternalServerError);
+    assert!(invalid
+        .body_string()
+        .await
+        .unwrap()
+        .contains("is not valid JSON"));
+    assert_eq!(valid.status(), StatusCode::Ok);
+    assert_eq!(
+        valid.body_json::<serde_json::Value>().await.unwrap(),
+        json!({"hello": "world"})
+    );
+}
+
+#[async_std::test]
+async fn the_metrics_endpoint_is_disabled_by_default() {
+    // Arrange
+    let mock_server = MockServer::start().await;
+
+    // Act
+    let response = surf::get(format!("{}/__admin/metrics", mock_server.uri()))
+        .await
+        .unwrap();
+
+    // Assert
+    assert_eq!(response.status(), StatusCode::NotFound);
+}
+
+#[async_std::test]
+async fn requests_to_the_metrics_endpoint_are_not_recorded() {
+    // Arrange
+    let mock_server = MockServer::builder()
+        .enable_metrics_endpoint()
+        .start()
+        .await;
+    surf::get(format!("{}/missing", mock_server.uri()))
+        .await
+        .unwrap();
+
+    // Act
+    let metrics = surf::get(format!("{}/__admin/metrics", mock_server.uri()))
+        .recv_string()
+        .await
+        .unwrap();
+
+    // Assert
+    assert!(metrics.contains("wiremock_requests_total 1\n"));
+    assert!(metrics.contains("wiremock_unmatched_requests_total 1\n"));
+    assert_eq!(mock_server.received_requests().await.unwrap().len(), 1);
+}
+
+#[async_std::test]
+#[should_panic(expected = "Request recording is disabled")]
+async fn exporting_har_panics_if_request_recording_is_disabled() {
+    // Arrange
+    let mock_server = MockServer::builder()
+        .disable_request_recording()
+        .start()
+        .await;
+
+    // Act
+    mock_server
+        .export_har(std::env::temp_dir().join("wiremock-disabled.har"))
+        .await;
+}
+
+#[async_std::test]
+async fn a_group_of_scoped_mocks_is_unmounted_when_its_guard_is_dropped() {
+    // Arrange
+    let mock_server = MockServer::start().await;
+    let group = mock_server
+        .register_group_as_scoped(vec![
+            Mock::given(path("first")).respond_with(ResponseTemplate::new(200)),
+            Mock::given(path("second"))
+                .respond_with(ResponseTemplate::new(201))
+                .expect(1),
+        ])
+        .await;
+
+    // Act
+    let uri = mock_server.uri();
+    let first = surf::get(format!("{uri}/first")).await.unwrap();
+    let second = surf::get(format!("{uri}/second")).await.unwrap();
+    let received_requests = group.received_requests().await;
+    drop(group);
+    let after_drop = surf::get(format!("{uri}/first")).await.unwrap();
+
+    // Assert
+    assert_eq!(first.status(), StatusCode::Ok);
+    assert_eq!(second.status(), StatusCode::Created);
+    assert_eq!(received_requests.len(), 2);
+    assert_eq!(received_requests[1].len(), 1);
+    assert_eq!(after_drop.status(), StatusCode::NotFound);
+}
+
+#[async_std::test]
+async fn a_batch_of_mocks_is_registered_at_once() {
+    // Arrange
+    let mock_server = MockServer::start().await;
+    mock_server
+        .register_many(vec![
+            Mock::given(path("first")).respond_with(ResponseTemplate::new(200)),
+            Mock::given(path("second"))
+                .respond_with(ResponseTemplate::new(201))
+                .expect(1),
+        ])
+        .await;
+
+    // Act
+    let uri = mock_server.uri();
+    let first = surf::get(format!("{uri}/first")).await.unwrap();
+    let second = surf::get(format!("{uri}/second")).await.unwrap();
+
+    // Assert
+    assert_eq!(first.status(), StatusCode::Ok);
+    assert_eq!(second.status(), StatusCode::Created);
+    mock_server.verify().await;
+}
+
+#[async_std::test]
+#[should_panic(expected = "Verification failed for a group of scoped mocks:
+- Mock #0.
+\tExpected range of matching incoming requests: == 1
+\tNumber of matched incoming requests: 0")]
+async fn a_group_of_scoped_mocks_panics_on_drop_if_an_expectation_is_not_satisfied() {
+    // Arrange
+    let mock_server = MockServer::start().await;
+    let _group = mock_server
+        .register_group_as_scoped(vec![
+            Mock::given(path("never"))
+                .respond_with(ResponseTemplate::new(200))
+                .expect(1),
+            Mock::given(path("optional")).respond_with(ResponseTemplate::new(200)),
+        ])
+        .await;
+}
+
+#[async_std::test]
+#[should_panic(expected = "No deletions.
+\tExpected to never match (see `expect_never` at tests/mocks.rs:")]
+async fn expect_never_reports_where_the_mock_was_defined() {
+    // Arrange
+    let mock_server = MockServer::start().await;
+    Mock::given(method("DELETE"))
+        .respond_with(ResponseTemplate::new(204))
+        .expect_never()
+        .named("No deletions")
+        .mount(&mock_server)
+        .await;
+
+    // Act
+    surf::delete(format!("{}/users/42", mock_server.uri()))
+        .await
+        .unwrap();
+}
+
+#[async_std::test]
+#[should_panic(expected = "Verification failed for a scoped mock:
+- Mock #0.
+\tExpected range of matching incoming requests: == 1
+\tNumber of matched incoming requests: 0
+\tMounted at: tests/mocks.rs:")]
+async fn scoped_mock_panics_point_to_where_the_mock_was_mounted() {
+    // Arrange
+    let mock_server = MockServer::start().await;
+
+    // Act
+    let _guard = Mock::given(method("GET"))
+        .respond_with(ResponseTemplate::new(200))
+        .expect(1)
+        .mount_as_scoped(&mock_server)
+        .await;
+}
+
+#[async_std::test]
+#[should_panic(expected = "authorization: <redacted>")]
+async fn redacted_headers_are_hidden_in_panic_messages() {
+    // Arrange
+    let mock_server = MockServer::builder()
+        .redact_headers(["Authorization"])
+        .start()
+        .await;
+    Mock::given(method("POST"))
+        .respond_with(ResponseTemplate::new(200))
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+
+    // Act
+    surf::get(mock_server.uri())
+        .header("Authorization", "Bearer secret")
+        .await
+        .unwrap();
+}
+
+#[async_std::test]
+async fn redacted_headers_are_not_recorded_but_can_be_matched() {
+    // Arrange
+    let mock_server = MockServer::builder()
+        .redact_headers(["x-api-key"])
+        .start()
+        .await;
+    let guard = Mock::given(header("x-api-key", "secret"))
+        .respond_with(ResponseTemplate::new(200))
+        .mount_as_scoped(&mock_server)
+        .await;
+
+    // Act
+    let response = surf::get(mock_server.uri())
+        .header("X-Api-Key", "secret")
+        .await
+        .unwrap();
+
+    // Assert
+    assert_eq!(response.status(), StatusCode::Ok);
+    let journal = mock_server.received_requests().await.unwrap();
+    assert_eq!(journal[0].headers["x-api-key"], "<redacted>");
+    let matched = guard.received_requests().await;
+    assert_eq!(matched[0].headers["x-api-key"], "<redacted>");
+}
+
+#[async_std::test]
+async fn path_templates_match_one_segment_per_placeholder() {
+    // Arrange
+    let mock_server = MockServer::start().await;
+    Mock::given(path_template("/users/{id}/posts"))
+        .respond_with(ResponseTemplate::new(200))
+        .mount(&mock_server)
+        .await;
+    let uri = mock_server.uri();
+
+    // Act
+    let matching = surf::get(format!("{uri}/users/42/posts")).await.unwrap();
+    let empty_segment = surf::get(format!("{uri}/users//posts")).await.unwrap();
+    let two_segments = surf::get(format!("{uri}/users/42/43/posts")).await.unwrap();
+
+    // Assert
+    assert_eq!(matching.status(), StatusCode::Ok);
+    assert_eq!(empty_segment.status(), StatusCode::NotFound);
+    assert_eq!(two_segments.status(), StatusCode::NotFound);
+}
+
+#[async_std::test]
+async fn path_params_are_captured_by_templates_wrapped_in_combinators() {
+    use wiremock::MatchExt;
+
+    struct PathParamsEcho;
+
+    impl Respond for PathParamsEcho {
+        fn respond(&self, _request: &Request) -> ResponseTemplate {
+            unreachable!("The server always calls `respond_with_ctx`")
+        }
+
+        fn respond_with_ctx(&self, _request: &Request, ctx: &ResponseContext) -> ResponseTemplate {
+            ResponseTemplate::new(200).set_body_json(&ctx.path_params)
+        }
+    }
+
+    // Arrange
+    let mock_server = MockServer::start().await;
+    Mock::given(
+        path_template("/users/{user_id}")
+            .or(path_template("/accounts/{account_id}"))
+            .and(path_template("/{kind}/{id}").not().not()),
+    )
+    .and((method("GET"), path_template("/{collection}/{_}")))
+    .respond_with(PathParamsEcho)
+    .mount(&mock_server)
+    .await;
+    let uri = mock_server.uri();
+
+    // Act
+    let user: serde_json::Value = surf::get(format!("{uri}/users/42"))
+        .recv_json()
+        .await
+        .unwrap();
+    let account: serde_json::Value = surf::get(format!("{uri}/accounts/7"))
+        .recv_json()
+        .await
+        .unwrap();
+
+    // Assert
+    assert_eq!(
+        user,
+        json!({"user_id": "42", "collection": "users", "_": "42"})
+    );
+    assert_eq!(
+        account,
+        json!({"account_id": "7", "collection": "accounts", "_": "7"})
+    );
+}
+
+#[async_std::test]
+async fn host_matcher_falls_back_to_the_host_header() {
+    // Arrange
+    let mock_server = MockServer::start().await;
+    Mock::given(wiremock::matchers::host("api.example.com"))
+        .respond_with(ResponseTemplate::new(200))
+        .mount(&mock_server)
+        .await;
+
+    // Act
+    let matching = surf::get(mock_server.uri())
+        .header("Host", "API.example.com:8080")
+        .await
+        .unwrap();
+    let other_host = surf::get(mock_server.uri()).await.unwrap();
+
+    // Assert
+    assert_eq!(matching.status(), StatusCode::Ok);
+    assert_eq!(other_host.status(), StatusCode::NotFound);
+}
+
+#[async_std::test]
+async fn requests_sent_through_a_connect_tunnel_are_matched_against_the_target_host() {
+    use std::io::{Read, Write};
+
+    // Arrange
+    let mock_server = MockServer::builder().as_http_proxy().start().await;
+    Mock::given(wiremock::matchers::host("api.example.com"))
+        .and(path("/users"))
+        .respond_with(ResponseTemplate::new(200).set_body_string("tunnelled"))
+        .mount(&mock_server)
+        .await;
+    let mut stream = TcpStream::connect(mock_server.address()).unwrap();
+    stream
+        .set_read_timeout(Some(Duration::from_secs(5)))
+        .unwrap();
+
+    // Act
+    stream
+        .write_all(b"CONNECT api.example.com:80 HTTP/1.1\r\nHost: api.example.com:80\r\n\r\n")
+        .unwrap();
+    let mut buffer = [0; 1024];
+    let n = stream.read(&mut buffer).unwrap();
+    let connect_response = String::from_utf8_lossy(&buffer[..n]).to_string();
+    stream
+        .write_all(b"GET /users HTTP/1.1\r\nHost: api.example.com\r\nConnection: close\r\n\r\n")
+        .unwrap();
+    let mut response = String::new();
+    stream.read_to_string(&mut response).unwrap();
+
+    // Assert
+    assert!(connect_response.starts_with("HTTP/1.1 200"));
+    assert!(response.starts_with("HTTP/1.1 200"));
+    assert!(response.ends_with("tunnelled"));
+    let received_requests = mock_server.received_requests().await.unwrap();
+    assert_eq!(
+        received_requests[0].url.as_str(),
+        "http://api.example.com/users"
+    );
+    assert!(received_requests[0].connection_id().is_some());
+}
+
+#[async_std::test]
+async fn scoped_mocks_on_a_virtual_host_only_match_requests_for_that_host() {
+    // Arrange
+    let mock_server = MockServer::start().await;
+    let billing = mock_server.for_host("billing.internal");
+    let guard = billing
+        .register_as_scoped(
+            Mock::given(method("GET"))
+                .respond_with(ResponseTemplate::new(200))
+                .expect(1),
+        )
+        .await;
+
+    // Act
+    let matching = surf::get(mock_server.uri())
+        .header("Host", "billing.internal")
+        .await
+        .unwrap();
+    let other_host = surf::get(mock_server.uri())
+        .header("Host", "users.internal")
+        .await
+        .unwrap();
+    drop(guard);
+    let after_drop = surf::get(mock_server.uri())
+        .header("Host", "billing.internal")
+        .await
+        .unwrap();
+
+    // Assert
+    assert_eq!(billing.host(), "billing.internal");
+    assert_eq!(matching.status(), StatusCode::Ok);
+    assert_eq!(other_host.status(), StatusCode::NotFound);
+    assert_eq!(after_drop.status(), StatusCode::NotFound);
+}
+
+#[async_std::test]
+async fn webhooks_can_call_back_the_url_provided_in_the_request() {
+    // Arrange
+    let mock_server = MockServer::start().await;
+    let callback_server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .respond_with(ResponseTemplate::new(202))
+        .with_webhook(
+            wiremock::WebhookTrigger::new("PUT", "http://unused")
+                .url_from_request(|request: &Request| {
+                    let body: serde_json::Value = request.body_json().unwrap();
+                    body["callback_url"].as_str().unwrap().to_string()
+                })
+                .body_from_request(|request: &Request| request.body.to_vec())
+                .insert_header("x-signature", "secret"),
+        )
+        .mount(&mock_server)
+        .await;
+    let payload = serde_json::json!({
+        "callback_url": format!("{}/jobs/1/done", callback_server.uri())
+    });
+
+    // Act
+    let status = surf::post(mock_server.uri())
+        .body(payload.clone())
+        .await
+        .unwrap()
+        .status();
+    let mut callbacks = vec![];
+    for _ in 0..100 {
+        callbacks = callback_server.received_requests().await.unwrap();
+        if !callbacks.is_empty() {
+            break;
+        }
+        async_std::task::sleep(Duration::from_millis(10)).await;
+    }
+
+    // Assert
+    assert_eq!(status, StatusCode::Accepted);
+    assert_eq!(callbacks.len(), 1);
+    assert_eq!(callbacks[0].method, http::Method::PUT);
+    assert_eq!(callbacks[0].url.path(), "/jobs/1/done");
+    assert_eq!(callbacks[0].headers["x-signature"], "secret");
+    assert_eq!(
+        callbacks[0].body_json::<serde_json::Value>().unwrap(),
+        payload
+    );
+}
+
+#[async_std::test]
+async fn skewed_date_header_is_consistent_with_rendered_templates() {
+    // Arrange
+    let mock_server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .set_body_string("{{now offset='-1d' format='http'}}")
+                .set_date_offset("-1d")
+                .render_templates(),
+        )
+        .mount(&mock_server)
+        .await;
+
+    // Act
+    let mut response = surf::get(mock_server.uri()).await.unwrap();
+
+    // Assert
+    let date = response.header("Date").unwrap().as_str().to_string();
+    assert_eq!(response.body_string().await.unwrap(), date);
+}
+
+#[async_std::test]
+async fn invalid_template_variables_return_a_500() {
+    // Arrange
+    let mock_server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .set_body_string("{{now format='iso'}}")
+                .render_templates(),
+        )
+        .mount(&mock_server)
+        .await;
+
+    // Act
+    let mut response = surf::get(mock_server.uri()).await.unwrap();
+
+    // Assert
+    assert_eq!(response.status(), StatusCode::InternalServerError);
+    assert!(response
+        .body_string()
+        .await
+        .unwrap()
+        .contains("unknown format `iso`"));
+}
+
+#[async_std::test]
+async fn an_explicit_content_type_is_not_overwritten_by_body_setters() {
+    // Arrange
+    let mock_server = MockServer::start().await;
+    Mock::given(path("/problem"))
+        .respond_with(
+            ResponseTemplate::new(400)
+                .insert_header("content-type", "application/problem+json")
+                .set_body_json(serde_json::json!({"title": "Bad Request"})),
+        )
+        .mount(&mock_server)
+        .await;
+    Mock::given(path("/latin1"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .set_body_string("caf\u{e9}")
+                .set_content_type("text/plain; charset=utf-8")
+                .set_charset("iso-8859-1"),
+        )
+        .mount(&mock_server)
+        .await;
+
+    // Act
+    let problem = surf::get(format!("{}/problem", mock_server.uri()))
+        .await
+        .unwrap();
+    let latin1 = surf::get(format!("{}/latin1", mock_server.uri()))
+        .await
+        .unwrap();
+
+    // Assert
+    assert_eq!(
+        problem.header("Content-Type").unwrap(),
+        "application/problem+json"
+    );
+    assert_eq!(
+        latin1.header("Content-Type").unwrap(),
+        "text/plain; charset=iso-8859-1"
+    );
+}
+
+#[async_std::test]
+async fn the_last_body_setter_sets_the_content_type_of_a_raw_body() {
+    // Arrange
+    let mock_server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .set_body_raw("<p>hello</p>", "text/html")
+                .set_body_json(serde_json::json!({"hello": "world"})),
+        )
+        .mount(&mock_server)
+        .await;
+
+    // Act
+    let response = surf::get(mock_server.uri()).await.unwrap();
+
+    // Assert
+    assert_eq!(response.header("Content-Type").unwrap(), "application/json");
+}
+
+#[tokio::test]
+async fn trailers_are_sent_after_a_chunked_body() {
+    use http_body_util::BodyExt;
+
+    // Arrange
+    let mock_server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .set_body_string("streamed")
+                .append_trailer("grpc-status", "0")
+                .append_trailer("grpc-message", "OK"),
+        )
+        .mount(&mock_server)
+        .await;
+
+    // Act
+    let stream = tokio::net::TcpStream::connect(mock_server.address())
+        .await
+        .unwrap();
+    let (mut sender, connection) =
+        hyper::client::conn::http1::handshake(hyper_util::rt::TokioIo::new(stream))
+            .await
+            .unwrap();
+    tokio::spawn(connection);
+    let request = http::Request::get("/")
+        .header("host", mock_server.address().to_string())
+        .header("te", "trailers")
+        .body(http_body_util::Empty::<hyper::body::Bytes>::new())
+        .unwrap();
+    let response = sender.send_request(request).await.unwrap();
+
+    // Assert
+    assert_eq!(response.headers()["transfer-encoding"], "chunked");
+    assert!(!response.headers().contains_key("content-length"));
+    let body = response.into_body().collect().await.unwrap();
+    let trailers = body.trailers().unwrap().clone();
+    assert_eq!(body.to_bytes(), "streamed");
+    assert_eq!(trailers["grpc-status"], "0");
+    assert_eq!(trailers["grpc-message"], "OK");
+}
+
+#[tokio::test]
+async fn connections_can_be_closed_or_dropped_mid_response() {
+    // Arrange
+    let mock_server = MockServer::start().await;
+    Mock::given(path("/close"))
+        .respond_with(ResponseTemplate::new(200).close_connection())
+        .mount(&mock_server)
+        .await;
+    Mock::given(path("/headers"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .set_body_string("never sent")
+                .drop_connection_after_headers(),
+        )
+        .mount(&mock_server)
+        .await;
+    Mock::given(path("/partial"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .set_body_string("Hello world!")
+                .close_connection_after_bytes(5),
+        )
+        .mount(&mock_server)
+        .await;
+
+    // Act
+    let closed = reqwest::get(format!("{}/close", mock_server.uri()))
+        .await
+        .unwrap();
+    let headers_only = reqwest::get(format!("{}/headers", mock_server.uri()))
+        .await
+        .unwrap();
+    let mut partial = reqwest::get(format!("{}/partial", mock_server.uri()))
+        .await
+        .unwrap();
+
+    // Assert
+    assert_eq!(closed.headers()["connection"], "close");
+    assert_eq!(headers_only.status(), 200);
+    assert!(headers_only.bytes().await.is_err());
+    assert_eq!(partial.headers()["content-length"], "12");
+    assert_eq!(partial.chunk().await.unwrap().unwrap(), "Hello");
+    assert!(partial.chunk().await.is_err());
+}
+
+#[tokio::test]
+async fn request_bodies_beyond_the_size_limit_are_rejected_and_truncated() {
+    // Arrange
+    let mock_server = MockServer::builder()
+        .max_request_body_size(1024)
+        .start()
+        .await;
+    Mock::given(method("POST"))
+        .respond_with(ResponseTemplate::new(200))
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+    let client = reqwest::Client::new();
+
+    // Act
+    let accepted = client
+        .post(mock_server.uri())
+        .body(vec![b'a'; 1024])
+        .send()
+        .await
+        .unwrap();
+    let rejected = client
+        .post(mock_server.uri())
+        .body(vec![b'b'; 64 * 1024])
+        .send()
+        .await
+        .unwrap();
+
+    // Assert
+    assert_eq!(accepted.status(), 200);
+    assert_eq!(rejected.status(), 413);
+    let received_requests = mock_server.received_requests().await.unwrap();
+    assert_eq!(received_requests.len(), 2);
+    assert_eq!(received_requests[1].body, vec![b'b'; 1024]);
+}
+
+#[async_std::test]
+async fn aborted_uploads_do_not_take_down_the_server() {
+    use std::io::Write;
+
+    // Arrange
+    let mock_server = MockServer::start().await;
+    Mock::given(wiremock::matchers::body_read_error())
+        .respond_with(ResponseTemplate::new(400))
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+    Mock::given(method("POST"))
+        .respond_with(ResponseTemplate::new(200))
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+
+    // Act
+    let mut stream = std::net::TcpStream::connect(mock_server.address()).unwrap();
+    stream
+        .write_all(b"POST / HTTP/1.1\r\nHost: localhost\r\nContent-Length: 10\r\n\r\nabc")
+        .unwrap();
+    stream.shutdown(std::net::Shutdown::Both).unwrap();
+    let request = loop {
+        match mock_server.received_requests().await.unwrap().pop() {
+            Some(request) => break request,
+            None => async_std::task::sleep(Duration::from_millis(10)).await,
+        }
+    };
+    let status = surf::post(mock_server.uri())
+        .body("complete")
+        .await
+        .unwrap()
+        .status();
+
+    // Assert
+    assert_eq!(request.body.as_ref(), b"abc");
+    assert!(request.body_error().is_some());
+    assert_eq!(status, 200);
+}
+
+#[async_std::test]
+async fn drop_verification_can_be_skipped() {
+    // Arrange
+    let mock_server = MockServer::builder().verify_on_drop(false).start().await;
+    Mock::given(method("GET"))
+        .respond_with(ResponseTemplate::new(200))
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+    let _guard = Mock::given(method("POST"))
+        .respond_with(ResponseTemplate::new(201))
+        .expect(1)
+        .mount_as_scoped(&mock_server)
+        .await;
+    let pooled_mock_server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .respond_with(ResponseTemplate::new(200))
+        .expect(1)
+        .skip_drop_verification()
+        .mount(&pooled_mock_server)
+        .await;
+
+    // Act
+    let outcome = std::panic::AssertUnwindSafe(pooled_mock_server.verify())
+        .catch_unwind()
+        .await;
+
+    // Assert
+    // Explicit verification still checks the expectations...
+    assert!(outcome.is_err());
+    // ...but dropping the servers and the guard does not panic.
+}
+
+#[tokio::test]
+async fn http_redirect_listener_redirects_to_the_mock_server() {
+    // Arrange
+    let mock_server = wiremock::MockServer::builder()
+        .with_http_redirect()
+        .start()
+        .await;
+    let redirect_uri = mock_server.http_redirect_uri().unwrap();
+    let client = reqwest::Client::builder()
+        .redirect(reqwest::redirect::Policy::none())
+        .build()
+        .unwrap();
+
+    // Act
+    let response = client
+        .get(format!("{}/hello?name=world", redirect_uri))
+        .send()
+        .await
+        .unwrap();
+
+    // Assert
+    assert_eq!(response.status(), 301);
+    assert_eq!(
+        response.headers()["location"],
+        format!("{}/hello?name=world", mock_server.uri()).as_str()
+    );
+    assert!(mock_server.received_requests().await.unwrap().is_empty());
+    assert!(MockServer::start().await.http_redirect_uri().is_none());
+}
+
+#[tokio::test]
+async fn chaos_mode_is_reproducible_with_a_seed() {
+    use wiremock::chaos::{ChaosConfig, Fault};
+
+    async fn statuses(seed: u64) -> Vec<Option<u16>> {
+        let chaos = ChaosConfig::new()
+            .error_rate(0.5)
+            .faults([Fault::Status(502), Fault::DropConnection])
+            .seed(seed);
+        let mock_server = MockServer::builder().chaos(chaos).start().await;
+        Mock::given(method("GET"))
+            .respond_with(ResponseTemplate::new(200))
+            .expect(30)
+            .mount(&mock_server)
+            .await;
+        let mut statuses = vec![];
+        for _ in 0..30 {
+            let response = reqwest::get(mock_server.uri()).await;
+            statuses.push(response.ok().map(|r| r.status().as_u16()));
+        }
+        statuses
+    }
+
+    // Act
+    let first = statuses(7).await;
+    let second = statuses(7).await;
+
+    // Assert
+    assert_eq!(first, second);
+    assert!(first.contains(&Some(200)));
+    assert!(first.contains(&Some(502)));
+    assert!(first.contains(&None));
+}
+
+#[async_std::test]
+async fn responders_draw_from_the_seeded_random_source() {
+    struct RandomId;
+
+    impl wiremock::Respond for RandomId {
+        fn respond(&self, _request: &wiremock::Request) -> ResponseTemplate {
+            unreachable!()
+        }
+
+        fn respond_with_ctx(
+            &self,
+            _request: &wiremock::Request,
+            ctx: &wiremock::ResponseContext,
+        ) -> ResponseTemplate {
+            ResponseTemplate::new(200)
+                .set_body_string(ctx.random.int_between(0, 1_000_000).to_string())
+        }
+    }
+
+    async fn ids(seed: u64) -> Vec<String> {
+        let mock_server = MockServer::builder().random_seed(seed).start().await;
+        Mock::given(method("GET"))
+            .respond_with(RandomId)
+            .mount(&mock_server)
+            .await;
+        let mut ids = vec![];
+        for _ in 0..3 {
+            ids.push(surf::get(mock_server.uri()).recv_string().await.unwrap());
+        }
+        ids
+    }
+
+    // Act
+    let first = ids(42).await;
+    let second = ids(42).await;
+
+    // Assert
+    assert_eq!(first, second);
+    assert_ne!(first[0], first[1]);
+}
+
+#[async_std::test]
+async fn requests_outside_of_the_request_range_are_not_matched() {
+    // Arrange
+    let mock_server = MockServer::start().await;
+    Mock::given(path("/flaky"))
+        .respond_with(ResponseTemplate::new(200))
+        .only_for_request_range(3..)
+        .expect(2)
+        .mount(&mock_server)
+        .await;
+
+    // Act
+    let mut statuses = vec![];
+    for _ in 0..4 {
+        let status = surf::get(format!("{}/flaky", mock_server.uri()))
+            .await
+            .unwrap()
+            .status();
+        statuses.push(status);
+    }
+
+    // Assert
+    assert_eq!(
+        statuses,
+        vec![
+            StatusCode::NotFound,
+            StatusCode::NotFound,
+            StatusCode::Ok,
+            StatusCode::Ok
+        ]
+    );
+}
+
+#[test]
+#[should_panic(expected = "Requests are numbered from 1")]
+fn request_ranges_cannot_include_zero() {
+    let _ = Mock::given(method("GET"))
+        .respond_with(ResponseTemplate::new(200))
+        .only_for_request_range(0..2);
+}
+
+#[tokio::test]
+async fn delays_are_driven_by_the_virtual_clock() {
+    // Arrange
+    let clock = wiremock::VirtualClock::new();
+    let mock_server = MockServer::builder()
+        .virtual_clock(clock.clone())
+        .start()
+        .await;
+    Mock::given(method("GET"))
+        .respond_with(ResponseTemplate::new(200).set_delay(Duration::from_secs(60)))
+        .mount(&mock_server)
+        .await;
+    let response = tokio::spawn(reqwest::get(mock_server.uri()));
+    while mock_server.received_requests().await.unwrap().is_empty() {
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+
+    // Act
+    clock.advance(Duration::from_secs(30));
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    let finished_early = response.is_finished();
+    clock.advance(Duration::from_secs(30));
+
+    // Assert
+    assert!(!finished_early);
+    assert_eq!(response.await.unwrap().unwrap().status(), 200);
+}
+
+#[async_std::test]
+async fn reset_expectations_keeps_the_mocks_mounted() {
+    // Arrange
+    let mock_server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .respond_with(ResponseTemplate::new(200))
+        .up_to_n_times(1)
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+    surf::get(mock_server.uri()).await.unwrap();
+    let status = surf::get(mock_server.uri()).await.unwrap().status();
+    assert_eq!(status, StatusCode::NotFound);
+
+    // Act
+    mock_server.reset_expectations().await;
+
+    // Assert
+    assert_eq!(mock_server.mock_call_counts().await, vec![(None, 0)]);
+    assert!(mock_server.received_requests().await.unwrap().is_empty());
+    let status = surf::get(mock_server.uri()).await.unwrap().status();
+    assert_eq!(status, StatusCode::Ok);
+}
+
+#[async_std::test]
+async fn global_expectations_are_checked_at_verification_time() {
+    // Arrange
+    let mock_server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .respond_with(ResponseTemplate::new(200))
+        .mount(&mock_server)
+        .await;
+    mock_server
+        .expect_global(|requests| {
+            if requests.len() < 2 {
+                Ok(())
+            } else {
+                Err(format!(
+                    "Expected fewer than 2 requests, got {}",
+                    requests.len()
+                ))
+            }
+        })
+        .await;
+    surf::get(mock_server.uri()).await.unwrap();
+    mock_server.verify().await;
+
+    // Act
+    surf::get(mock_server.uri()).await.unwrap();
+    let outcome = mock_server.shutdown(Duration::from_secs(1)).await;
+
+    // Assert
+    match outcome {
+        Err(wiremock::ShutdownError::VerificationFailed(message)) => {
+            assert!(message
+                .contains("Global expectation failed: Expected fewer than 2 requests, got 2"))
+        }
+        outcome => panic!("Unexpected outcome: {:?}", outcome),
+    }
+}
+
+#[async_std::test]
+#[should_panic(
+    expected = "Expected the number of received requests satisfying the assertion to be == 1, but 0 did.\nOther requests:\n- Request #1\n\tGET"
+)]
+async fn failed_assertions_list_the_received_requests() {
+    // Arrange
+    let mock_server = MockServer::start().await;
+    surf::get(format!("{}/email", mock_server.uri()))
+        .await
+        .unwrap();
+
+    // Assert
+    mock_server.assert().received(1, path("/emails")).await;
+}
+
+#[async_std::test]
+async fn combined_matchers_can_be_reused_across_mocks() {
+    use wiremock::matchers::header_exists;
+    use wiremock::MatchExt;
+
+    // Arrange
+    let mock_server = MockServer::start().await;
+    let emails = || (method("POST"), path("/emails"));
+    let authenticated = header_exists("authorization").or(header_exists("x-api-key"));
+    Mock::given((emails(), authenticated.not()))
+        .respond_with(ResponseTemplate::new(401))
+        .mount(&mock_server)
+        .await;
+    Mock::given(emails())
+        .respond_with(ResponseTemplate::new(202))
+        .mount(&mock_server)
+        .await;
+
+    // Act
+    let url = format!("{}/emails", mock_server.uri());
+    let unauthenticated = surf::post(&url).await.unwrap();
+    let authenticated = surf::post(&url)
+        .header("x-api-key", "secret")
+        .await
+        .unwrap();
+
+    // Assert
+    assert_eq!(unauthenticated.status(), StatusCode::Unauthorized);
+    assert_eq!(authenticated.status(), StatusCode::Accepted);
+}
+
+#[async_std::test]
+async fn body_json_ignoring_still_compares_the_other_fields() {
+    use wiremock::matchers::body_json_ignoring;
+
+    // Arrange
+    let mock_server = MockServer::start().await;
+    let expected = json!({ "name": "Ferris", "meta": { "created_at": 0 } });
+    Mock::given(body_json_ignoring(
+        &expected,
+        &["$.meta.created_at", "$..id"],
+    ))
+    .respond_with(ResponseTemplate::new(200))
+    .mount(&mock_server)
+    .await;
+
+    // Act
+    let matching = json!({ "name": "Ferris", "meta": { "created_at": 42 }, "id": 1 });
+    let extra_field = json!({ "name": "Ferris", "meta": { "created_at": 42, "version": 2 } });
+    let wrong_value = json!({ "name": "Crab", "meta": { "created_at": 0 } });
+    let mut statuses = vec![];
+    for body in [matching, extra_field, wrong_value] {
+        let response = surf::post(mock_server.uri()).body(body).await.unwrap();
+        statuses.push(response.status());
+    }
+
+    // Assert
+    assert_eq!(
+        statuses,
+        [StatusCode::Ok, StatusCode::NotFound, StatusCode::NotFound]
+    );
+}
+
+#[async_std::test]
+async fn json_field_matchers_check_every_selected_field() {
+    use wiremock::matchers::{body_json_array_len, body_json_field_matches};
+
+    // Arrange
+    let mock_server = MockServer::start().await;
+    Mock::given(body_json_field_matches(
+        "$.users[*].email",
+        r"@example\.com$",
+    ))
+    .and(body_json_array_len("$.users", 2..=3))
+    .respond_with(ResponseTemplate::new(200))
+    .mount(&mock_server)
+    .await;
+
+    // Act
+    let user = |email: &str| json!({ "email": email });
+    let valid = json!({ "users": [user("a@example.com"), user("b@example.com")] });
+    let invalid_email = json!({ "users": [user("a@example.com"), user("b@example.org")] });
+    let too_short = json!({ "users": [user("a@example.com")] });
+    let missing = json!({ "accounts": [user("a@example.com"), user("b@example.com")] });
+    let mut statuses = vec![];
+    for body in [valid, invalid_email, too_short, missing] {
+        let response = surf::post(mock_server.uri()).body(body).await.unwrap();
+        statuses.push(response.status());
+    }
+
+    // Assert
+    assert_eq!(
+        statuses,
+        [
+            StatusCode::Ok,
+            StatusCode::NotFound,
+            StatusCode::NotFound,
+            StatusCode::NotFound
+        ]
+    );
+}
+
+#[async_std::test]
+async fn query_param_raw_distinguishes_encodings() {
+    use wiremock::matchers::{query_param_raw, query_params_exact};
+
+    // Arrange
+    let mock_server = MockServer::start().await;
+    Mock::given(query_param_raw("q", "a%20b"))
+        .and(query_params_exact([("q", "a b"), ("page", "1")]))
+        .respond_with(ResponseTemplate::new(200))
+        .mount(&mock_server)
+        .await;
+
+    // Act
+    let mut statuses = vec![];
+    for query in [
+        "page=1&q=a%20b",
+        "page=1&q=a+b",
+        "q=a%20b",
+        "q=a%20b&page=1&page=2",
+    ] {
+        let url = format!("{}?{}", mock_server.uri(), query);
+        statuses.push(surf::get(url).await.unwrap().status());
+    }
+
+    // Assert
+    assert_eq!(
+        statuses,
+        [
+            StatusCode::Ok,
+            StatusCode::NotFound,
+            StatusCode::NotFound,
+            StatusCode::NotFound
+        ]
+    );
+}
+
+#[async_std::test]
+async fn query_param_values_in_any_order_count_duplicates() {
+    use wiremock::matchers::query_param_values;
+
+    // Arrange
+    let mock_server = MockServer::start().await;
+    Mock::given(query_param_values("id", ["1", "2", "1"]).any_order())
+        .respond_with(ResponseTemplate::new(200))
+        .mount(&mock_server)
+        .await;
+
+    // Act
+    let mut statuses = vec![];
+    for query in [
+        "id=2&id=1&id=1",
+        "id=1&id=2&id=2",
+        "id=1&id=2",
+        "id=1&id=1&id=2&id=1",
+    ] {
+        let url = format!("{}?{}", mock_server.uri(), query);
+        statuses.push(surf::get(url).await.unwrap().status());
+    }
+
+    // Assert
+    assert_eq!(
+        statuses,
+        [
+            StatusCode::Ok,
+            StatusCode::NotFound,
+            StatusCode::NotFound,
+            StatusCode::NotFound
+        ]
+    );
+}
+
+#[async_std::test]
+async fn a_query_param_count_of_zero_matches_absent_keys() {
+    use wiremock::matchers::query_param_count;
+
+    // Arrange
+    let mock_server = MockServer::start().await;
+    Mock::given(query_param_count("debug", 0))
+        .respond_with(ResponseTemplate::new(200))
+        .mount(&mock_server)
+        .await;
+
+    // Act
+    let mut statuses = vec![];
+    for query in ["", "?page=1", "?debug=1", "?debug"] {
+        let url = format!("{}{}", mock_server.uri(), query);
+        statuses.push(surf::get(url).await.unwrap().status());
+    }
+
+    // Assert
+    assert_eq!(
+        statuses,
+        [
+            StatusCode::Ok,
+            StatusCode::Ok,
+            StatusCode::NotFound,
+            StatusCode::NotFound
+        ]
+    );
+}
+
+#[async_std::test]
+async fn query_string_requires_the_parameters_in_the_same_order() {
+    use wiremock::matchers::query_string;
+
+    // Arrange
+    let mock_server = MockServer::start().await;
+    Mock::given(query_string("sort=name&page=2"))
+        .respond_with(ResponseTemplate::new(200))
+        .mount(&mock_server)
+        .await;
+
+    // Act
+    let mut statuses = vec![];
+    for query in ["sort=name&page=2", "sort=%6Eame&page=2", "page=2&sort=name"] {
+        let url = format!("{}?{}", mock_server.uri(), query);
+        statuses.push(surf::get(url).await.unwrap().status());
+    }
+
+    // Assert
+    assert_eq!(
+        statuses,
+        [StatusCode::Ok, StatusCode::Ok, StatusCode::NotFound]
+    );
+}
+
+#[async_std::test]
+async fn request_trailers_can_be_matched() {
+    use std::io::{Read, Write};
+    use wiremock::matchers::trailer;
+
+    // Arrange
+    let mock_server = MockServer::start().await;
+    Mock::given(trailer("x-checksum", "5d41402a"))
+        .respond_with(ResponseTemplate::new(200))
+        .mount(&mock_server)
+        .await;
+    let mut stream = TcpStream::connect(mock_server.address()).unwrap();
+    stream
+        .set_read_timeout(Some(Duration::from_secs(5)))
+        .unwrap();
+
+    // Act
+    stream
+        .write_all(
+            b"POST / HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\nTransfer-Encoding: chunked\r\nTrailer: x-checksum\r\n\r\n5\r\nhello\r\n0\r\nx-checksum: 5d41402a\r\n\r\n",
+        )
+        .unwrap();
+    let mut response = String::new();
+    stream.read_to_string(&mut response).unwrap();
+
+    // Assert
+    assert!(response.starts_with("HTTP/1.1 200"), "{}", response);
+    let received_requests = mock_server.received_requests().await.unwrap();
+    assert_eq!(received_requests[0].body.as_ref(), b"hello");
+    assert_eq!(received_requests[0].trailers()["x-checksum"], "5d41402a");
+}
+
+#[async_std::test]
+async fn interim_responses_are_sent_before_the_response() {
+    use std::io::{Read, Write};
+
+    // Arrange
+    let mock_server = MockServer::start().await;
+    let template = ResponseTemplate::new(200)
+        .append_interim_response(103, [("link", "</style.css>; rel=preload")])
+        .set_delay(Duration::from_millis(50));
+    Mock::given(method("GET"))
+        .respond_with(template)
+        .mount(&mock_server)
+        .await;
+    let mut stream = TcpStream::connect(mock_server.address()).unwrap();
+    stream
+        .set_read_timeout(Some(Duration::from_secs(5)))
+        .unwrap();
+
+    // Act
+    stream
+        .write_all(b"GET / HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n")
+        .unwrap();
+    let mut response = String::new();
+    stream.read_to_string(&mut response).unwrap();
+
+    // Assert
+    assert!(
+        response.starts_with(
+            "HTTP/1.1 103 Early Hints\r\nlink: </style.css>; rel=preload\r\n\r\nHTTP/1.1 200 OK\r\n"
+        ),
+        "{}",
+        response
+    );
+}
+
+#[async_std::test]
+async fn body_framing_can_be_matched() {
+    use std::io::{Read, Write};
+    use wiremock::matchers::{has_content_length, is_chunked};
+
+    // Arrange
+    let mock_server = MockServer::start().await;
+    Mock::given(is_chunked())
+        .respond_with(ResponseTemplate::new(411))
+        .mount(&mock_server)
+        .await;
+    Mock::given(has_content_length(5))
+        .respond_with(ResponseTemplate::new(200))
+        .mount(&mock_server)
+        .await;
+    let mut stream = TcpStream::connect(mock_server.address()).unwrap();
+    stream
+        .set_read_timeout(Some(Duration::from_secs(5)))
+        .unwrap();
+
+    // Act
+    let with_length = surf::post(mock_server.uri()).body("hello").await.unwrap();
+    stream
+        .write_all(
+            b"POST / HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\nTransfer-Encoding: chunked\r\n\r\n5\r\nhello\r\n0\r\n\r\n",
+        )
+        .unwrap();
+    let mut chunked = String::new();
+    stream.read_to_string(&mut chunked).unwrap();
+
+    // Assert
+    assert_eq!(with_length.status(), StatusCode::Ok);
+    assert!(chunked.starts_with("HTTP/1.1 411"), "{}", chunked);
+}
+
+#[async_std::test]
+async fn hanging_requests_are_released_when_the_server_is_reset() {
+    use std::io::{Read, Write};
+
+    // Arrange
+    let mock_server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .respond_with(ResponseTemplate::new(200).hang())
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+    let mut stream = TcpStream::connect(mock_server.address()).unwrap();
+    stream
+        .set_read_timeout(Some(Duration::from_millis(200)))
+        .unwrap();
+    stream
+        .write_all(b"GET / HTTP/1.1\r\nHost: localhost\r\n\r\n")
+        .unwrap();
+
+    // Act
+    let mut buffer = [0; 1024];
+    let before_reset = stream.read(&mut buffer);
+    mock_server.verify().await;
+    mock_server.reset().await;
+    stream
+        .set_read_timeout(Some(Duration::from_secs(5)))
+        .unwrap();
+    let after_reset = stream.read(&mut buffer);
+
+    // Assert - no response, then the connection is closed
+    assert!(before_reset.is_err());
+    assert_eq!(after_reset.unwrap(), 0);
+}
+
+#[tokio::test]
+async fn throttled_bodies_are_sent_in_full() {
+    use std::time::Instant;
+
+    // Arrange
+    let mock_server = MockServer::start().await;
+    let body: Vec<u8> = (0..=255).cycle().take(3000).collect();
+    let template = ResponseTemplate::new(200)
+        .set_body_bytes(body.clone())
+        .throttle_bytes_per_sec(10_000);
+    Mock::given(method("GET"))
+        .respond_with(template)
+        .mount(&mock_server)
+        .await;
+
+    // Act
+    let start = Instant::now();
+    let response = reqwest::get(mock_server.uri()).await.unwrap();
+    let content_length = response.content_length();
+    let received = response.bytes().await.unwrap();
+
+    // Assert - 3 chunks of 1000 bytes, with a 100ms pause after each of them
+    assert!(start.elapsed() >= Duration::from_millis(200));
+    assert_eq!(content_length, Some(3000));
+    assert_eq!(received, body);
+}
+
+#[tokio::test]
+async fn range_requests_get_the_requested_part_of_the_body() {
+    use wiremock::matchers::range_header;
+
+    // Arrange
+    let mock_server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(range_header(4..8))
+        .respond_with(ResponseTemplate::respond_with_range_of("0123456789"))
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+    Mock::given(method("GET"))
+        .respond_with(ResponseTemplate::respond_with_range_of("0123456789"))
+        .mount(&mock_server)
+        .await;
+    let client = reqwest::Client::new();
+    let get = |range: Option<&'static str>| {
+        let mut request = client.get(mock_server.uri());
+        if let Some(range) = range {
+            request = request.header("Range", range);
+        }
+        async move {
+            let response = request.send().await.unwrap();
+            let content_range = response
+                .headers()
+                .get("Content-Range")
+                .map(|value| value.to_str().unwrap().to_owned());
+            (
+                response.status().as_u16(),
+                content_range,
+                response.text().await.unwrap(),
+            )
+        }
+    };
+
+    // Act
+    let full = get(None).await;
+    let bounded = get(Some("bytes=4-7")).await;
+    let suffix = get(Some("bytes=-3")).await;
+    let unsatisfiable = get(Some("bytes=10-")).await;
+    let multiple = get(Some("bytes=0-1,4-5")).await;
+
+    // Assert
+    assert_eq!(full, (200, None, "0123456789".into()));
+    assert_eq!(bounded, (206, Some("bytes 4-7/10".into()), "4567".into()));
+    assert_eq!(suffix, (206, Some("bytes 7-9/10".into()), "789".into()));
+    assert_eq!(unsatisfiable, (416, Some("bytes */10".into()), "".into()));
+    assert_eq!(multiple, (200, None, "0123456789".into()));
+}
+
+#[tokio::test]
+async fn responses_with_an_etag_honour_conditional_requests() {
+    use wiremock::matchers::if_none_match;
+    use wiremock::Match;
+
+    // Arrange
+    let mock_server = MockServer::start().await;
+    let template = ResponseTemplate::new(200)
+        .set_body_string("v1")
+        .insert_header("Last-Modified", "Thu, 29 Feb 2024 12:34:56 GMT")
+        .with_etag();
+    Mock::given(method("GET"))
+        .respond_with(template.clone())
+        .mount(&mock_server)
+        .await;
+    Mock::given(method("PUT"))
+        .respond_with(template)
+        .mount(&mock_server)
+        .await;
+    let client = reqwest::Client::new();
+
+    // Act
+    let first = client.get(mock_server.uri()).send().await.unwrap();
+    let etag = first.headers()["ETag"].to_str().unwrap().to_owned();
+    let conditional_get = |header: &'static str, value: String| {
+        let request = client.get(mock_server.uri()).header(header, value);
+        async move { request.send().await.unwrap().status().as_u16() }
+    };
+    let revalidated = conditional_get("If-None-Match", format!("W/{}", etag)).await;
+    let changed = conditional_get("If-None-Match", "\"v0\"".into()).await;
+    let not_modified_since =
+        conditional_get("If-Modified-Since", "Fri, 01 Mar 2024 00:00:00 GMT".into()).await;
+    let modified_since =
+        conditional_get("If-Modified-Since", "Wed, 28 Feb 2024 00:00:00 GMT".into()).await;
+    let conditional_put = client
+        .put(mock_server.uri())
+        .header("If-None-Match", "*")
+        .send()
+        .await
+        .unwrap();
+
+    // Assert
+    assert_eq!(first.text().await.unwrap(), "v1");
+    assert_eq!(revalidated, 304);
+    assert_eq!(changed, 200);
+    assert_eq!(not_modified_since, 304);
+    assert_eq!(modified_since, 200);
+    assert_eq!(conditional_put.status(), 412);
+    let received_requests = mock_server.received_requests().await.unwrap();
+    assert!(if_none_match(etag).matches(&received_requests[1]));
+}
+
+#[async_std::test]
+async fn response_sequences_apply_their_exhaust_policy() {
+    use wiremock::ResponseSequence;
+
+    // Arrange
+    let mock_server = MockServer::start().await;
+    let sequence =
+        || ResponseSequence::new([ResponseTemplate::new(503), ResponseTemplate::new(200)]);
+    Mock::given(path("/repeat"))
+        .respond_with_sequence(sequence())
+        .mount(&mock_server)
+        .await;
+    Mock::given(path("/loop"))
+        .respond_with_sequence(sequence().then_loop())
+        .mount(&mock_server)
+        .await;
+    Mock::given(path("/unmatched"))
+        .respond_with_sequence(sequence().then_unmatched())
+        .expect(2)
+        .mount(&mock_server)
+        .await;
+    Mock::given(path("/unmatched"))
+        .respond_with(ResponseTemplate::new(418))
+        .with_priority(6)
+        .mount(&mock_server)
+        .await;
+
+    // Act
+    let mut statuses = vec![];
+    for p in ["/repeat", "/loop", "/unmatched"] {
+        let mut path_statuses = vec![];
+        for _ in 0..3 {
+            let response = surf::get(format!("{}{}", mock_server.uri(), p))
+                .await
+                .unwrap();
+            path_statuses.push(u16::from(response.status()));
+        }
+        statuses.push(path_statuses);
+    }
+
+    // Assert
+    assert_eq!(
+        statuses,
+        vec![
+            vec![503, 200, 200],
+            vec![503, 200, 503],
+            vec![503, 200, 418]
+        ]
+    );
+}
+
+#[async_std::test]
+async fn deterministic_responses_depend_only_on_the_request() {
+    use wiremock::DeterministicByRequest;
+
+    // Arrange
+    let mock_server = MockServer::start().await;
+    let responder = DeterministicByRequest::new(ResponseTemplate::new(200))
+        .with_status(200, 1)
+        .with_status(500, 1)
+        .with_body_template("{{random hex 16}}");
+    Mock::given(method("GET"))
+        .respond_with(responder)
+        .mount(&mock_server)
+        .await;
+
+    // Act
+    let mut responses = vec![];
+    for i in 0..20 {
+        let url = format!("{}/items/{}", mock_server.uri(), i % 10);
+        let mut response = surf::get(url).await.unwrap();
+        let body = response.body_string().await.unwrap();
+        responses.push((u16::from(response.status()), body));
+    }
+
+    // Assert
+    assert_eq!(responses[..10], responses[10..]);
+    assert!(responses.iter().any(|(status, _)| *status == 200));
+    assert!(responses.iter().any(|(status, _)| *status == 500));
+    assert!(responses.iter().all(|(_, body)| body.len() == 16));
+    assert_ne!(responses[0].1, responses[1].1);
+}
+
+#[async_std::test]
+async fn lint_mocks_reports_mocks_shadowed_by_earlier_ones() {
+    use wiremock::{MockLint, Request};
+
+    // Arrange
+    let mock_server = MockServer::start().await;
+    let mount = |mock: Mock| async { mock.mount(&mock_server).await };
+    // #0: tried before the catch-all, thanks to its priority.
+    mount(
+        Mock::given(path("/health"))
+            .respond_with(ResponseTemplate::new(200))
+            .with_priority(1),
+    )
+    .await;
+    // #1: the catch-all for GET requests.
+    mount(Mock::given(method("GET")).respond_with(ResponseTemplate::new(500))).await;
+    // #2: shadowed by #1.
+    mount(
+        Mock::given(path("/users"))
+            .and(method("GET"))
+            .respond_with(ResponseTemplate::new(200)),
+    )
+    .await;
+    // #3: not a GET, hence reachable.
+    mount(
+        Mock::given(path("/users"))
+            .and(method("POST"))
+            .respond_with(ResponseTemplate::new(201)),
+    )
+    .await;
+    // #4: only matches twice, hence it cannot shadow #5.
+    mount(
+        Mock::given(method("DELETE"))
+            .respond_with(ResponseTemplate::new(503))
+            .up_to_n_times(2),
+    )
+    .await;
+    mount(Mock::given(method("DELETE")).respond_with(ResponseTemplate::new(204))).await;
+    // #6: opaque matchers never shadow anything.
+    mount(
+        Mock::given(|request: &Request| request.url.path() == "/")
+            .respond_with(ResponseTemplate::new(200)),
+    )
+    .await;
+    mount(Mock::given(path("/")).respond_with(ResponseTemplate::new(200))).await;
+
+    // Act
+    let lints = mock_server.lint_mocks().await;
+
+    // Assert
+    assert_eq!(lints.len(), 1);
+    match &lints[0] {
+        MockLint::Unreachable { mock, shadowed_by } => {
+            assert_eq!(mock.position, 2);
+            assert_eq!(shadowed_by.position, 1);
+        }
+        lint => panic!("Unexpected lint: {}", lint),
+    }
+}
+
+#[async_std::test]
+#[should_panic(expected = "has the same matchers as Users, which is already mounted")]
+async fn mounting_a_duplicate_mock_panics_if_denied() {
+    // Arrange
+    let mock_server = MockServer::builder().deny_duplicate_mocks().start().await;
+    Mock::given(method("GET"))
+        .and(path("/users"))
+        .respond_with(ResponseTemplate::new(200))
+        .named("Users")
+        .mount(&mock_server)
+        .await;
+    // Limited mocks are not duplicates.
+    Mock::given(path("/users"))
+        .and(method("GET"))
+        .respond_with(ResponseTemplate::new(503))
+        .up_to_n_times(1)
+        .mount(&mock_server)
+        .await;
+
+    // Act
+    Mock::given(path("/users"))
+        .and(method("GET"))
+        .respond_with(ResponseTemplate::new(500))
+        .mount(&mock_server)
+        .await;
+}
+
+#[async_std::test]
+async fn debug_headers_identify_unnamed_mocks_by_position() {
+    // Arrange
+    let mock_server = MockServer::builder().debug_headers(true).start().await;
+    Mock::given(path("/a"))
+        .respond_with(ResponseTemplate::new(200))
+        .mount(&mock_server)
+        .await;
+    Mock::given(path("/b"))
+        .respond_with(ResponseTemplate::new(200))
+        .mount(&mock_server)
+        .await;
+    let default_server = MockServer::start().await;
+
+    // Act
+    let response = surf::get(format!("{}/b", mock_server.uri())).await.unwrap();
+    let default_response = surf::get(default_server.uri()).await.unwrap();
+
+    // Assert
+    assert_eq!(
+        response.header("x-wiremock-matched-mock").unwrap(),
+        "Mock #1"
+    );
+    assert!(response.header("x-wiremock-unmatched").is_none());
+    assert!(default_response.header("x-wiremock-unmatched").is_none());
+}
+
+#[async_std::test]
+async fn events_report_unmatched_requests_and_verification_failures() {
+    use futures::{FutureExt, StreamExt};
+    use wiremock::ServerEvent;
+
+    // Arrange
+    let mock_server = MockServer::start().await;
+    Mock::given(path("/expected"))
+        .respond_with(ResponseTemplate::new(200))
+        .expect(1)
+        .named("Expected")
+        .mount(&mock_server)
+        .await;
+    let mut events = Box::pin(mock_server.events().await);
+
+    // Act
+    surf::get(format!("{}/unexpected", mock_server.uri()))
+        .await
+        .unwrap();
+    let verification = std::panic::AssertUnwindSafe(mock_server.verify())
+        .catch_unwind()
+        .await;
+
+    // Assert
+    assert!(verification.is_err());
+    match events.next().await.unwrap() {
+        ServerEvent::RequestReceived {
+            request_id,
+            request,
+        } => {
+            assert_eq!(request_id, 0);
+            assert_eq!(request.url.path(), "/unexpected");
+        }
+        event => panic!("Unexpected event: {:?}", event),
+    }
+    match events.next().await.unwrap() {
+        ServerEvent::ResponseSent {
+            request_id,
+            mock,
+            status,
+            ..
+        } => {
+            assert_eq!(request_id, 0);
+            assert_eq!(mock, None);
+            assert_eq!(status, 404);
+        }
+        event => panic!("Unexpected event: {:?}", event),
+    }
+    match events.next().await.unwrap() {
+        ServerEvent::VerificationFailed { message } => assert!(message.contains("Expected")),
+        event => panic!("Unexpected event: {:?}", event),
+    }
+
+    // The server must not panic when dropped: the expectation is now satisfied.
+    surf::get(format!("{}/expected", mock_server.uri()))
+        .await
+        .unwrap();
+}
+
+#[async_std::test]
+async fn base_url_is_rendered_in_templated_headers_and_bodies() {
+    // Arrange
+    let mock_server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .respond_with(
+            ResponseTemplate::new(201)
+                .insert_header_templated("Location", "{{base_url}}/items/42")
+                .insert_header("X-Untouched", "{{base_url}}"),
+        )
+        .mount(&mock_server)
+        .await;
+    Mock::given(method("GET"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .set_body_string(r#"{"next": "{{base_url}}/items?page=2"}"#)
+                .render_templates(),
+        )
+        .mount(&mock_server)
+        .await;
+
+    // Act
+    let created = surf::post(format!("{}/items", mock_server.uri()))
+        .await
+        .unwrap();
+    let mut page = surf::get(format!("{}/items", mock_server.uri()))
+        .await
+        .unwrap();
+
+    // Assert
+    assert_eq!(
+        created.header("Location").unwrap().as_str(),
+        format!("{}/items/42", mock_server.uri())
+    );
+    assert_eq!(
+        created.header("X-Untouched").unwrap().as_str(),
+        "{{base_url}}"
+    );
+    assert_eq!(
+        page.body_string().await.unwrap(),
+        format!(r#"{{"next": "{}/items?page=2"}}"#, mock_server.uri())
+    );
+}
+
+#[async_std::test]
+async fn digest_auth_challenges_are_answered_before_responding() {
+    // Arrange - the example of RFC 2617, section 3.5
+    let mock_server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .respond_with(ResponseTemplate::new(200))
+        .with_auth_challenge(
+            wiremock::AuthChallenge::digest("testrealm@host.com", "Mufasa", "Circle Of Life")
+                .nonce("dcd98b7102dd2f0e8b11d0f600bfb0c093"),
+        )
+        .expect(2)
+        .mount(&mock_server)
+        .await;
+    let uri = format!("{}/dir/index.html", mock_server.uri());
+
+    // Act
+    let challenge = surf::get(&uri).await.unwrap();
+    let response = surf::get(&uri)
+        .header(
+            "Authorization",
+            "Digest username=\"Mufasa\", realm=\"testrealm@host.com\", \
+             nonce=\"dcd98b7102dd2f0e8b11d0f600bfb0c093\", uri=\"/dir/index.html\", \
+             qop=auth, nc=00000001, cnonce=\"0a4f113b\", \
+             response=\"6629fae49393a05397450978507c4ef1\"",
+        )
+        .await
+        .unwrap();
+
+    // Assert
+    assert_eq!(challenge.status(), StatusCode::Unauthorized);
+    let www_authenticate = challenge.header("WWW-Authenticate").unwrap().as_str();
+    assert!(www_authenticate.starts_with(
+        "Digest realm=\"testrealm@host.com\", qop=\"auth\", algorithm=MD5, \
+         nonce=\"dcd98b7102dd2f0e8b11d0f600bfb0c093\""
+    ));
+    assert_eq!(response.status(), StatusCode::Ok);
+}
+
+#[async_std::test]
+async fn proxy_auth_challenges_use_the_proxy_headers() {
+    // Arrange
+    let mock_server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .respond_with(ResponseTemplate::new(200))
+        .with_auth_challenge(wiremock::AuthChallenge::bearer("corp", "secret").for_proxy())
+        .mount(&mock_server)
+        .await;
+
+    // Act
+    let wrong_header = surf::get(mock_server.uri())
+        .header("Authorization", "Bearer secret")
+        .await
+        .unwrap();
+    let wrong_token = surf::get(mock_server.uri())
+        .header("Proxy-Authorization", "Bearer guess")
+        .await
+        .unwrap();
+    let authenticated = surf::get(mock_server.uri())
+        .header("Proxy-Authorization", "Bearer secret")
+        .await
+        .unwrap();
+
+    // Assert
+    assert_eq!(
+        wrong_header.status(),
+        StatusCode::ProxyAuthenticationRequired
+    );
+    assert_eq!(
+        wrong_header.header("Proxy-Authenticate").unwrap().as_str(),
+        "Bearer realm=\"corp\""
+    );
+    assert_eq!(
+        wrong_token.header("Proxy-Authenticate").unwrap().as_str(),
+        "Bearer realm=\"corp\", error=\"invalid_token\""
+    );
+    assert_eq!(authenticated.status(), StatusCode::Ok);
+}
+
+/// A bare NTLM message of `message_type`, with `fields` as its variable-length fields.
+fn ntlm_message(message_type: u32, fields: &[&str]) -> String {
+    use base64::prelude::{Engine as _, BASE64_STANDARD};
+
+    let header_length = 12 + 8 * fields.len() + 4;
+    let mut header = b"NTLMSSP\0".to_vec();
+    header.extend_from_slice(&message_type.to_le_bytes());
+    let mut payload = vec![];
+    for field in fields {
+        let bytes: Vec<u8> = field.encode_utf16().flat_map(u16::to_le_bytes).collect();
+        let offset = (header_length + payload.len()) as u32;
+        header.extend_from_slice(&(bytes.len() as u16).to_le_bytes());
+        header.extend_from_slice(&(bytes.len() as u16).to_le_bytes());
+        header.extend_from_slice(&offset.to_le_bytes());
+        payload.extend(bytes);
+    }
+    // Negotiate Unicode.
+    header.extend_from_slice(&1u32.to_le_bytes());
+    header.extend(payload);
+    BASE64_STANDARD.encode(header)
+}
+
+#[tokio::test]
+async fn ntlm_handshakes_authenticate_the_connection() {
+    // Arrange
+    let mock_server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .respond_with(ResponseTemplate::new(200))
+        .with_auth_challenge(wiremock::AuthChallenge::ntlm("CORP", "alice"))
+        .mount(&mock_server)
+        .await;
+    let negotiate = format!("NTLM {}", ntlm_message(1, &[]));
+    // The LM and NT responses, the domain, the user name, the workstation and the session key.
+    let authenticate = format!(
+        "NTLM {}",
+        ntlm_message(3, &["", "", "corp", "Alice", "WORKSTATION", ""])
+    );
+    let client = reqwest::Client::new();
+    let send = |authorization: Option<&str>| {
+        let mut request = client.get(mock_server.uri());
+        if let Some(authorization) = authorization {
+            request = request.header("Authorization", authorization);
+        }
+        async move {
+            let response = request.send().await.unwrap();
+            let status = response.status().as_u16();
+            let challenge = response
+                .headers()
+                .get("WWW-Authenticate")
+                .map(|value| value.to_str().unwrap().to_owned());
+            response.bytes().await.unwrap();
+            (status, challenge)
+        }
+    };
+
+    // Act
+    let (anonymous, offer) = send(None).await;
+    let (negotiated, challenge) = send(Some(&negotiate)).await;
+    let (authenticated, _) = send(Some(&authenticate)).await;
+    let (follow_up, _) = send(None).await;
+    let other_connection = reqwest::Client::new()
+        .get(mock_server.uri())
+        .header("Authorization", &authenticate)
+        .send()
+        .await
+        .unwrap();
+
+    // Assert
+    assert_eq!((anonymous, offer.as_deref()), (401, Some("NTLM")));
+    assert_eq!(negotiated, 401);
+    assert!(challenge.unwrap().starts_with("NTLM TlRMTVNTUAACAAAA"));
+    assert_eq!((authenticated, follow_up), (200, 200));
+    assert_eq!(other_connection.status(), 401);
+    let received = mock_server.received_requests().await.unwrap();
+    assert!(received[..4]
+        .iter()
+        .all(|request| request.connection_id() == received[0].connection_id()));
+    assert_ne!(received[4].connection_id(), received[0].connection_id());
+}
+
+#[tokio::test]
+async fn long_polls_are_released_by_their_trigger_or_time_out() {
+    use std::sync::Arc;
+    use tokio::sync::Notify;
+
+    // Arrange
+    let mock_server = MockServer::start().await;
+    let trigger = Arc::new(Notify::new());
+    Mock::given(path("/poll"))
+        .respond_with_async(
+            wiremock::respond_when(trigger.clone(), ResponseTemplate::new(200))
+                .or_timeout(Duration::from_millis(300), ResponseTemplate::new(204)),
+        )
+        .mount(&mock_server)
+        .await;
+    let poll = || {
+        let url = format!("{}/poll", mock_server.uri());
+        tokio::spawn(async move { reqwest::get(url).await.unwrap().status().as_u16() })
+    };
+
+    // Act
+    // Fired before the request arrives: the notification is kept for the next poll.
+    trigger.notify_one();
+    let early = poll().await.unwrap();
+    let pending = poll();
+    // Give the request the time to start waiting.
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    trigger.notify_one();
+    let released = pending.await.unwrap();
+    let timed_out = poll().await.unwrap();
+
+    // Assert
+    assert_eq!(early, 200);
+    assert_eq!(released, 200);
+    assert_eq!(timed_out, 204);
+}
+
+#[tokio::test]
+async fn ndjson_lines_are_streamed_one_at_a_time() {
+    use std::time::Instant;
+
+    // Arrange
+    let mock_server = MockServer::start().await;
+    let delay = Duration::from_millis(100);
+    let template = ResponseTemplate::new(200)
+        .set_body_ndjson((1..=3).map(|id| serde_json::json!({ "id": id })))
+        .set_line_delay(delay);
+    Mock::given(method("GET"))
+        .respond_with(template)
+        .mount(&mock_server)
+        .await;
+
+    // Act
+    let start = Instant::now();
+    let mut response = reqwest::get(mock_server.uri()).await.unwrap();
+    let headers_at = start.elapsed();
+    let content_type = response.headers()["Content-Type"].clone();
+    let mut arrivals = vec![];
+    while let Some(chunk) = response.chunk().await.unwrap() {
+        arrivals.push((chunk, start.elapsed()));
+    }
+
+    // Assert - no pause before the first line, one before each of the others.
+    // Arrival times are compared with each other rather than with a fixed bound: setting up
+    // the connection can take a while when many tests run in parallel.
+    assert_eq!(content_type, "application/x-ndjson");
+    let lines: Vec<_> = arrivals.iter().map(|(chunk, _)| chunk.as_ref()).collect();
+    assert_eq!(
+        lines,
+        vec![&b"{\"id\":1}\n"[..], b"{\"id\":2}\n", b"{\"id\":3}\n"]
+    );
+    assert!(arrivals[0].1 < headers_at + delay / 2);
+    assert!(arrivals[0].1 < arrivals[1].1 - delay / 2);
+    assert!(arrivals[2].1 >= 2 * delay);
+}
+
+#[async_std::test]
+async fn body_ndjson_contains_looks_for_a_matching_line() {
+    use wiremock::matchers::body_ndjson_contains;
+
+    // Arrange
+    let mock_server = MockServer::start().await;
+    Mock::given(body_ndjson_contains(
+        serde_json::json!({ "event": "signup" }),
+    ))
+    .respond_with(ResponseTemplate::new(202))
+    .mount(&mock_server)
+    .await;
+
+    // Act
+    let mut statuses = vec![];
+    for body in [
+        "{\"event\":\"login\"}\n\n{\"event\":\"signup\",\"user\":2}\n",
+        "{\"event\":\"login\"}\n{\"event\":\"logout\"}\n",
+        "{\"event\":\"signup\"}\nnot json\n",
+    ] {
+        let status: u16 = surf::post(mock_server.uri())
+            .body(body)
+            .await
+            .unwrap()
+            .status()
+            .into();
+        statuses.push(status);
+    }
+
+    // Assert
+    assert_eq!(statuses, vec![202, 404, 404]);
+}
+
+#[async_std::test]
+async fn response_framing_can_be_chosen() {
+    use std::io::{Read, Write};
+
+    // Arrange
+    let mock_server = MockServer::start().await;
+    let hello = || ResponseTemplate::new(200).set_body_string("hello");
+    for (route, template) in [
+        ("/chunked", hello().force_chunked()),
+        ("/close-delimited", hello().omit_content_length()),
+        ("/too-short", hello().override_content_length(3)),
+    ] {
+        Mock::given(path(route))
+            .respond_with(template)
+            .mount(&mock_server)
+            .await;
+    }
+    let get = |route: &str, connection: &str| {
+        let mut stream = TcpStream::connect(mock_server.address()).unwrap();
+        stream
+            .set_read_timeout(Some(Duration::from_secs(5)))
+            .unwrap();
+        write!(
+            stream,
+            "GET {} HTTP/1.1\r\nHost: localhost\r\n{}\r\n",
+            route, connection
+        )
+        .unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).unwrap();
+        response
+    };
+
+    // Act
+    let chunked = get("/chunked", "Connection: close\r\n");
+    // Without `Connection: close`: the server closes the connection to end the body.
+    let close_delimited = get("/close-delimited", "");
+    let too_short = get("/too-short", "Connection: close\r\n");
+
+    // Assert
+    assert!(
+        chunked.contains("transfer-encoding: chunked\r\n"),
+        "{}",
+        chunked
+    );
+    assert!(
+        chunked.ends_with("\r\n\r\n5\r\nhello\r\n0\r\n\r\n"),
+        "{}",
+        chunked
+    );
+
+    assert!(
+        close_delimited.starts_with("HTTP/1.0 200 OK\r\n"),
+        "{}",
+        close_delimited
+    );
+    assert!(
+        !close_delimited.contains("content-length"),
+        "{}",
+        close_delimited
+    );
+    assert!(
+        !close_delimited.contains("transfer-encoding"),
+        "{}",
+        close_delimited
+    );
+    assert!(
+        close_delimited.ends_with("\r\n\r\nhello"),
+        "{}",
+        close_delimited
+    );
+
+    assert!(too_short.contains("content-length: 3\r\n"), "{}", too_short);
+    assert!(too_short.ends_with("\r\n\r\nhel"), "{}", too_short);
+}
+
+#[async_std::test]
+async fn raw_responses_are_written_to_the_socket_as_they_are() {
+    use std::io::{Read, Write};
+
+    // Arrange
+    let mock_server = MockServer::start().await;
+    let raw_response = b"HTTP/1.1 999 Who Knows\r\nX Illegal: header\r\n\r\n\x00\xffgarbage";
+    Mock::given(method("GET"))
+        .respond_with_raw_bytes(raw_response)
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+    let mut stream = TcpStream::connect(mock_server.address()).unwrap();
+    stream
+        .set_read_timeout(Some(Duration::from_secs(5)))
+        .unwrap();
+
+    // Act - the connection is kept alive by default, but the server closes it
+    stream
+        .write_all(b"GET / HTTP/1.1\r\nHost: localhost\r\n\r\n")
+        .unwrap();
+    let mut response = vec![];
+    stream.read_to_end(&mut response).unwrap();
+
+    // Assert
+    assert_eq!(response, raw_response);
+}
+
+#[async_std::test]
+async fn expect_continue_can_be_rejected_or_delayed() {
+    use std::io::{Read, Write};
+    use std::time::Instant;
+
+    // Arrange
+    let mock_server = MockServer::start().await;
+    Mock::given(path("/rejected"))
+        .respond_with(ResponseTemplate::new(201).reject_expect_continue(417))
+        .mount(&mock_server)
+        .await;
+    Mock::given(path("/delayed"))
+        .respond_with(ResponseTemplate::new(201).delay_continue(Duration::from_millis(200)))
+        .mount(&mock_server)
+        .await;
+    let connect = || {
+        let stream = TcpStream::connect(mock_server.address()).unwrap();
+        stream
+            .set_read_timeout(Some(Duration::from_secs(5)))
+            .unwrap();
+        stream
+    };
+    let upload = |route: &str, expect: &str| {
+        format!(
+            "PUT {} HTTP/1.1\r\nHost: localhost\r\nContent-Length: 5\r\n{}\r\n",
+            route, expect
+        )
+    };
+
+    // Act
+    let mut stream = connect();
+    write!(
+        stream,
+        "{}",
+        upload("/rejected", "Expect: 100-continue\r\n")
+    )
+    .unwrap();
+    let mut rejected = String::new();
+    stream.read_to_string(&mut rejected).unwrap();
+
+    let mut stream = connect();
+    write!(
+        stream,
+        "{}hello",
+        upload("/rejected", "Connection: close\r\n")
+    )
+    .unwrap();
+    let mut without_expect = String::new();
+    stream.read_to_string(&mut without_expect).unwrap();
+
+    let mut stream = connect();
+    let start = Instant::now();
+    write!(stream, "{}", upload("/delayed", "Expect: 100-continue\r\n")).unwrap();
+    let mut interim = [0; 25];
+    stream.read_exact(&mut interim).unwrap();
+    let waited = start.elapsed();
+    write!(stream, "hello").unwrap();
+    let mut delayed = [0; 12];
+    stream.read_exact(&mut delayed).unwrap();
+
+    // Assert
+    assert!(
+        rejected.starts_with("HTTP/1.1 417 Expectation Failed\r\n"),
+        "{}",
+        rejected
+    );
+    assert!(without_expect.starts_with("HTTP/1.1 201 Created\r\n"));
+    assert_eq!(&interim, b"HTTP/1.1 100 Continue\r\n\r\n");
+    assert!(waited >= Duration::from_millis(200));
+    assert_eq!(&delayed, b"HTTP/1.1 201");
+
+    let received = mock_server.received_requests().await.unwrap();
+    assert_eq!(received.len(), 3);
+    assert!(received[0].body.is_empty());
+}
+
+#[async_std::test]
+async fn expect_continue_is_handled_by_the_mock_that_answers_the_request() {
+    use std::io::{Read, Write};
+
+    // Arrange
+    let mock_server = MockServer::start().await;
+    Mock::given(path("/upload"))
+        .respond_with(ResponseTemplate::new(503).reject_expect_continue(417))
+        .only_for_request_range(2..)
+        .mount(&mock_server)
+        .await;
+    Mock::given(path("/upload"))
+        .respond_with(ResponseTemplate::new(201))
+        .with_priority(10)
+        .mount(&mock_server)
+        .await;
+    let mut stream = TcpStream::connect(mock_server.address()).unwrap();
+    stream
+        .set_read_timeout(Some(Duration::from_secs(5)))
+        .unwrap();
+
+    // Act
+    write!(
+        stream,
+        "PUT /upload HTTP/1.1\r\nHost: localhost\r\nContent-Length: 5\r\nExpect: 100-continue\r\n\r\n"
+    )
+    .unwrap();
+    let mut interim = [0; 25];
+    stream.read_exact(&mut interim).unwrap();
+    write!(stream, "hello").unwrap();
+    let mut response = [0; 12];
+    stream.read_exact(&mut response).unwrap();
+
+    // Assert
+    assert_eq!(&interim, b"HTTP/1.1 100 Continue\r\n\r\n");
+    assert_eq!(&response, b"HTTP/1.1 201");
+}
+
+#[async_std::test]
+async fn a_fixed_time_makes_complete_responses_reproducible() {
+    use std::io::{Read, Write};
+    use std::time::UNIX_EPOCH;
+
+    // Arrange
+    let mock_server = MockServer::builder()
+        .with_fixed_time(UNIX_EPOCH + Duration::from_secs(1_709_210_096))
+        .start()
+        .await;
+    Mock::given(path("/token"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .set_body_string("{{now offset='+1h' format='epoch'}}")
+                .add_cookie(
+                    "session",
+                    "abc",
+                    ["Expires={{now offset='+1d' format='http'}}"],
+                )
+                .render_templates(),
+        )
+        .mount(&mock_server)
+        .await;
+    Mock::given(path("/skewed"))
+        .respond_with(ResponseTemplate::new(200).set_date_offset("-10m"))
+        .mount(&mock_server)
+        .await;
+    let get = |path: &str| {
+        let mut stream = TcpStream::connect(mock_server.address()).unwrap();
+        write!(
+            stream,
+            "GET {} HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n",
+            path
+        )
+        .unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).unwrap();
+        response
+    };
+
+    // Act
+    let first = get("/token");
+    async_std::task::sleep(Duration::from_millis(1100)).await;
+    let second = get("/token");
+    let skewed = get("/skewed");
+
+    // Assert
+    assert_eq!(first, second);
+    assert!(
+        first.contains("\r\ndate: Thu, 29 Feb 2024 12:34:56 GMT\r\n"),
+        "{}",
+        first
+    );
+    assert!(
+        first.contains("\r\nset-cookie: session=abc; Expires=Fri, 01 Mar 2024 12:34:56 GMT\r\n"),
+        "{}",
+        first
+    );
+    assert!(first.ends_with("\r\n\r\n1709213696"), "{}", first);
+    assert!(
+        skewed.contains("\r\ndate: Thu, 29 Feb 2024 12:24:56 GMT\r\n"),
+        "{}",
+        skewed
+    );
+}
+
+#[async_std::test]
+async fn requests_are_matched_against_their_arrival_time() {
+    use wiremock::matchers::received_within;
+    use wiremock::VirtualClock;
+
+    // Arrange
+    let clock = VirtualClock::new();
+    let mock_server = MockServer::builder()
+        .virtual_clock(clock.clone())
+        .start()
+        .await;
+    Mock::given(received_within(..Duration::from_secs(1)))
+        .respond_with(ResponseTemplate::new(503))
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+    Mock::given(received_within(Duration::from_secs(1)..))
+        .respond_with(ResponseTemplate::new(200))
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+
+    // Act
+    let first = surf::get(mock_server.uri()).await.unwrap();
+    clock.advance(Duration::from_millis(1500));
+    let retry = surf::get(mock_server.uri()).await.unwrap();
+
+    // Assert
+    assert_eq!(first.status(), 503);
+    assert_eq!(retry.status(), 200);
+    let arrivals: Vec<_> = mock_server
+        .received_requests()
+        .await
+        .unwrap()
+        .iter()
+        .map(|request| request.received_at().unwrap())
+        .collect();
+    assert_eq!(arrivals, vec![Duration::ZERO, Duration::from_millis(1500)]);
+}
+
+#[async_std::test]
+#[should_panic(
+    expected = "\tExpected minimum interval between matching incoming requests: 1s
+\tShortest interval: 200ms, between requests #2 and #3"
+)]
+async fn panics_if_matching_requests_are_closer_than_the_min_interval() {
+    use wiremock::VirtualClock;
+
+    // Arrange
+    let clock = VirtualClock::new();
+    let mock_server = MockServer::builder()
+        .virtual_clock(clock.clone())
+        .start()
+        .await;
+    Mock::given(method("GET"))
+        .respond_with(ResponseTemplate::new(503))
+        .expect(3)
+        .expect_min_interval(Duration::from_secs(1))
+        .mount(&mock_server)
+        .await;
+
+    // Act
+    for backoff in [Duration::from_secs(1), Duration::from_millis(200)] {
+        surf::get(mock_server.uri()).await.unwrap();
+        clock.advance(backoff);
+    }
+    surf::get(mock_server.uri()).await.unwrap();
+
+    // Assert
+    mock_server.verify().await;
+}
+
+#[async_std::test]
+async fn scoped_mocks_violating_the_min_interval_are_not_satisfied() {
+    // Arrange
+    let mock_server = MockServer::start().await;
+    let guard = Mock::given(method("GET"))
+        .respond_with(ResponseTemplate::new(503))
+        .expect(2)
+        .expect_min_interval(Duration::from_secs(1))
+        .skip_drop_verification()
+        .mount_as_scoped(&mock_server)
+        .await;
+
+    // Act
+    surf::get(mock_server.uri()).await.unwrap();
+    surf::get(mock_server.uri()).await.unwrap();
+
+    // Assert
+    guard
+        .wait_until_satisfied()
+        .now_or_never()
+        .ok_or(())
+        .expect_err("should not be satisfied");
+    assert_eq!(guard.received_requests().await.len(), 2);
+}
+
+#[async_std::test]
+async fn requests_spaced_out_by_the_min_interval_satisfy_the_expectation() {
+    use wiremock::VirtualClock;
+
+    // Arrange
+    let clock = VirtualClock::new();
+    let mock_server = MockServer::builder()
+        .virtual_clock(clock.clone())
+        .start()
+        .await;
+    Mock::given(method("GET"))
+        .respond_with(ResponseTemplate::new(503))
+        .expect(3)
+        .expect_min_interval(Duration::from_secs(1))
+        .mount(&mock_server)
+        .await;
+
+    // Act
+    for backoff in [Duration::from_secs(1), Duration::from_secs(2)] {
+        surf::get(mock_server.uri()).await.unwrap();
+        clock.advance(backoff);
+    }
+    surf::get(mock_server.uri()).await.unwrap();
+
+    // Assert
+    mock_server.verify().await;
+}
+
+#[async_std::test]
+async fn response_bodies_read_in_full_are_consumed_within_the_budget() {
+    // Arrange
+    let mock_server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .respond_with(ResponseTemplate::new(200).set_body_bytes(vec![0; 8 * 1024 * 1024]))
+        .expect(1)
+        .expect_response_consumed_within(Duration::from_secs(10))
+        .mount(&mock_server)
+        .await;
+
+    // Act
+    let body = surf::get(mock_server.uri())
+        .await
+        .unwrap()
+        .body_bytes()
+        .await
+        .unwrap();
+
+    // Assert
+    assert_eq!(body.len(), 8 * 1024 * 1024);
+    mock_server.verify().await;
+}
+
+#[async_std::test]
+#[should_panic(expected = "\tExpected response bodies to be consumed within: 10s
+\tBody of response #1: abandoned after")]
+async fn response_bodies_dropped_by_the_client_are_never_consumed() {
+    // Arrange
+    let mock_server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .respond_with(ResponseTemplate::new(200).set_body_bytes(vec![0; 8 * 1024 * 1024]))
+        .expect_response_consumed_within(Duration::from_secs(10))
+        .mount(&mock_server)
+        .await;
+
+    // Act - the client reads the head of the response and closes the connection
+    {
+        use std::io::{Read, Write};
+
+        let mut stream = TcpStream::connect(mock_server.address()).unwrap();
+        stream
+            .write_all(b"GET / HTTP/1.1\r\nHost: localhost\r\n\r\n")
+            .unwrap();
+        let mut head = [0; 12];
+        stream.read_exact(&mut head).unwrap();
+    }
+    async_std::task::sleep(Duration::from_millis(200)).await;
+
+    // Assert
+    mock_server.verify().await;
+}
+
+#[async_std::test]
+async fn connection_count_only_counts_connections_carrying_requests() {
+    use std::io::{Read, Write};
+
+    // Arrange
+    let mock_server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .respond_with(ResponseTemplate::new(200))
+        .mount(&mock_server)
+        .await;
+    let get = |stream: &mut TcpStream| {
+        stream
+            .write_all(b"GET / HTTP/1.1\r\nHost: localhost\r\n\r\n")
+            .unwrap();
+        // A status line, `content-length` and `date` headers, and no body.
+        let mut response = [0; 75];
+        stream.read_exact(&mut response).unwrap();
+        assert!(response.ends_with(b" GMT\r\n\r\n"));
+    };
+
+    // Act
+    let mut keep_alive = TcpStream::connect(mock_server.address()).unwrap();
+    for _ in 0..3 {
+        get(&mut keep_alive);
+    }
+    get(&mut TcpStream::connect(mock_server.address()).unwrap());
+    let idle = TcpStream::connect(mock_server.address()).unwrap();
+    let before_reset = mock_server.connection_count().await;
+    mock_server.reset().await;
+    let after_reset = mock_server.connection_count().await;
+    drop(idle);
+
+    // Assert
+    assert_eq!(before_reset, Some(2));
+    assert_eq!(after_reset, Some(0));
+    let without_recording = MockServer::builder()
+        .disable_request_recording()
+        .start()
+        .await;
+    assert_eq!(without_recording.connection_count().await, None);
+}
+
+#[async_std::test]
+async fn the_journal_and_the_matched_mock_share_the_request_body() {
+    // Arrange
+    let mock_server = MockServer::start().await;
+    let guard = Mock::given(method("POST"))
+        .respond_with(ResponseTemplate::new(201))
+        .mount_as_scoped(&mock_server)
+        .await;
+
+    // Act
+    let status = surf::post(mock_server.uri())
+        .body(vec![7u8; 1024 * 1024])
+        .await
+        .unwrap()
+        .status();
+
+    // Assert
+    assert_eq!(status, 201);
+    let from_journal = mock_server.received_requests().await.unwrap();
+    let from_mock = guard.received_requests().await;
+    assert_eq!(from_journal[0].body.as_ref(), vec![7u8; 1024 * 1024]);
+    // The body is not copied: both point to the same buffer.
+    assert_eq!(from_journal[0].body.as_ptr(), from_mock[0].body.as_ptr());
+}
+
+#[async_std::test]
+async fn cookie_values_can_be_quoted_or_templated() {
+    // Arrange
+    let mock_server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .render_templates()
+                .add_cookie_pair("greeting", "\"hello-world\"")
+                .add_cookie("nonce", "n-{{random hex 8}}", ["Path=/"]),
+        )
+        .mount(&mock_server)
+        .await;
+
+    // Act
+    let response = surf::get(mock_server.uri()).await.unwrap();
+
+    // Assert
+    let cookies: Vec<_> = response
+        .header("Set-Cookie")
+        .unwrap()
+        .iter()
+        .map(|v| v.as_str())
+        .collect();
+    assert_eq!(cookies[0], "greeting=\"hello-world\"");
+    let nonce = cookies[1]
+        .strip_prefix("nonce=n-")
+        .and_then(|c| c.strip_suffix("; Path=/"))
+        .unwrap();
+    assert_eq!(nonce.len(), 8);
+    assert!(nonce.chars().all(|c| c.is_ascii_hexdigit()));
+}
+
+#[test]
+#[should_panic(expected = "`session id` is not a valid cookie name")]
+fn cookie_names_must_be_tokens() {
+    let _ = ResponseTemplate::new(200).add_cookie_pair("session id", "abc");
+}
+
+#[test]
+#[should_panic(expected = "`abc; Path=/` is not a valid cookie value")]
+fn cookie_values_must_not_contain_separators() {
+    let _ = ResponseTemplate::new(200).add_cookie_pair("session", "abc; Path=/");
+}
+
+#[test]
+#[should_panic(expected = "`abc{{ ; Path=/` is not a valid cookie value")]
+fn cookie_values_must_not_contain_unclosed_template_variables() {
+    let _ = ResponseTemplate::new(200)
+        .render_templates()
+        .add_cookie_pair("session", "abc{{ ; Path=/");
+}
+
+#[test]
+#[should_panic(expected = "`{{now format='epoch'}}` is not a valid cookie value")]
+fn cookie_values_are_not_templated_without_render_templates() {
+    let _ = ResponseTemplate::new(200).add_cookie_pair("expires", "{{now format='epoch'}}");
+}
+
+#[tokio::test]
+async fn body_files_honour_range_and_conditional_requests() {
+    // Arrange
+    let mock_server = MockServer::start().await;
+    let fixture = std::env::temp_dir().join(format!("wiremock-range-{}.txt", std::process::id()));
+    std::fs::write(&fixture, "0123456789").unwrap();
+    Mock::given(method("GET"))
+        .respond_with(
+            ResponseTemplate::respond_with_range_of(Vec::new())
+                .set_body_from_file(&fixture)
+                .with_etag(),
+        )
+        .mount(&mock_server)
+        .await;
+    let client = reqwest::Client::new();
+
+    // Act
+    let first = client.get(mock_server.uri()).send().await.unwrap();
+    let etag = first.headers()["ETag"].clone();
+    let part = client
+        .get(mock_server.uri())
+        .header("Range", "bytes=4-7")
+        .send()
+        .await
+        .unwrap();
+    let revalidated = client
+        .get(mock_server.uri())
+        .header("If-None-Match", etag.clone())
+        .send()
+        .await
+        .unwrap();
+    std::fs::write(&fixture, "9876543210").unwrap();
+    let changed = client
+        .get(mock_server.uri())
+        .header("If-None-Match", etag)
+        .send()
+        .await
+        .unwrap();
+    let _ = std::fs::remove_file(&fixture);
+
+    // Assert
+    assert_eq!(first.text().await.unwrap(), "0123456789");
+    assert_eq!(part.status(), 206);
+    assert_eq!(part.headers()["Content-Range"], "bytes 4-7/10");
+    assert_eq!(part.text().await.unwrap(), "4567");
+    assert_eq!(revalidated.status(), 304);
+    assert_eq!(changed.status(), 200);
+    assert_eq!(changed.text().await.unwrap(), "9876543210");
+}
+
+#[tokio::test]
+async fn webhooks_are_fired_once_the_response_body_has_been_sent() {
+    // Arrange
+    let mock_server = MockServer::start().await;
+    let callback_server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .set_body_string("hello")
+                .throttle_bytes_per_sec(10),
+        )
+        .with_webhook(wiremock::WebhookTrigger::post(format!(
+            "{}/done",
+            callback_server.uri()
+        )))
+        .mount(&mock_server)
+        .await;
+
+    // Act
+    let response = reqwest::get(mock_server.uri()).await.unwrap();
+    tokio::time::sleep(Duration::from_millis(200)).await;
+    let callbacks_while_sending = callback_server.received_requests().await.unwrap();
+    let body = response.text().await.unwrap();
+    let mut callbacks = vec![];
+    for _ in 0..100 {
+        callbacks = callback_server.received_requests().await.unwrap();
+        if !callbacks.is_empty() {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+
+    // Assert
+    assert_eq!(body, "hello");
+    assert!(callbacks_while_sending.is_empty());
+    assert_eq!(callbacks.len(), 1);
+    assert_eq!(callbacks[0].url.path(), "/done");
+}
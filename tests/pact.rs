@@ -0,0 +1,124 @@
+#![cfg(feature = "pact")]
+use serde_json::{json, Value};
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+#[async_std::test]
+async fn matched_requests_become_pact_interactions() {
+    // Arrange
+    let mock_server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/users"))
+        .respond_with(ResponseTemplate::new(201).insert_header("x-request-id", "42"))
+        .named("Create user")
+        .mount(&mock_server)
+        .await;
+    let url = format!("{}/users?dry_run=true&tag=a&tag=b", mock_server.uri());
+    for _ in 0..2 {
+        surf::post(&url)
+            .body(json!({ "name": "Ferris" }))
+            .await
+            .unwrap();
+    }
+    surf::get(format!("{}/unmatched", mock_server.uri()))
+        .await
+        .unwrap();
+    let file = std::env::temp_dir().join(format!("wiremock-pact-{}.json", std::process::id()));
+
+    // Act
+    mock_server.write_pact("frontend", "users", &file).await;
+
+    // Assert
+    let pact: Value = serde_json::from_slice(&std::fs::read(&file).unwrap()).unwrap();
+    assert_eq!(pact["consumer"]["name"], "frontend");
+    assert_eq!(pact["metadata"]["pactSpecification"]["version"], "3.0.0");
+    // Identical interactions and unmatched requests are left out.
+    let interactions = pact["interactions"].as_array().unwrap();
+    assert_eq!(interactions.len(), 1);
+    let interaction = &interactions[0];
+    assert_eq!(interaction["description"], "Create user - POST /users");
+    let request = &interaction["request"];
+    assert_eq!(request["method"], "POST");
+    assert_eq!(request["path"], "/users");
+    assert_eq!(
+        request["query"],
+        json!({ "dry_run": ["true"], "tag": ["a", "b"] })
+    );
+    assert_eq!(request["body"], json!({ "name": "Ferris" }));
+    assert!(request["headers"].get("host").is_none());
+    assert_eq!(interaction["response"]["status"], 201);
+    assert_eq!(interaction["response"]["headers"]["x-request-id"], "42");
+    assert!(interaction["response"].get("body").is_none());
+}
+
+#[async_std::test]
+#[should_panic(expected = "Request recording is disabled")]
+async fn writing_a_pact_requires_request_recording() {
+    let mock_server = MockServer::builder()
+        .disable_request_recording()
+        .start()
+        .await;
+    mock_server
+        .write_pact(
+            "frontend",
+            "users",
+            std::env::temp_dir().join("unused.json"),
+        )
+        .await;
+}
+
+#[async_std::test]
+async fn written_pacts_can_be_mounted_on_another_server() {
+    // Arrange
+    let recording_server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/users/1"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({ "name": "Ferris" })))
+        .named("Get user")
+        .mount(&recording_server)
+        .await;
+    Mock::given(method("POST"))
+        .and(path("/notes"))
+        .respond_with(ResponseTemplate::new(201).set_body_string("created"))
+        .mount(&recording_server)
+        .await;
+    surf::get(format!("{}/users/1?verbose=true", recording_server.uri()))
+        .await
+        .unwrap();
+    surf::post(format!("{}/notes", recording_server.uri()))
+        .body("remember the milk")
+        .await
+        .unwrap();
+    let file =
+        std::env::temp_dir().join(format!("wiremock-pact-replay-{}.json", std::process::id()));
+    recording_server
+        .write_pact("frontend", "users", &file)
+        .await;
+    let provider_server = MockServer::start().await;
+
+    // Act
+    provider_server.mount_pact(&file).await;
+
+    // Assert
+    let user: Value = surf::get(format!("{}/users/1?verbose=true", provider_server.uri()))
+        .recv_json()
+        .await
+        .unwrap();
+    assert_eq!(user, json!({ "name": "Ferris" }));
+    // The query has to match exactly.
+    let without_query = surf::get(format!("{}/users/1", provider_server.uri()))
+        .await
+        .unwrap();
+    assert_eq!(without_query.status(), 404);
+    let mut note = surf::post(format!("{}/notes", provider_server.uri()))
+        .body("remember the milk")
+        .await
+        .unwrap();
+    assert_eq!(note.status(), 201);
+    assert_eq!(note.body_string().await.unwrap(), "created");
+    let other_note = surf::post(format!("{}/notes", provider_server.uri()))
+        .body("something else")
+        .await
+        .unwrap();
+    assert_eq!(other_note.status(), 404);
+}
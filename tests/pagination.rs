@@ -0,0 +1,84 @@
+use serde_json::{json, Value};
+use wiremock::fakes::{paginated, PageStyle};
+use wiremock::MockServer;
+
+#[async_std::test]
+async fn numbered_pages_link_to_the_next_one() {
+    // Arrange
+    let mock_server = MockServer::start().await;
+    paginated(
+        "/items",
+        (1..=5).map(|id| json!({ "id": id })),
+        PageStyle::PageNumber,
+    )
+    .page_size(2)
+    .mount(&mock_server)
+    .await;
+
+    // Act
+    let mut first = surf::get(format!("{}/items?sort=desc", mock_server.uri()))
+        .await
+        .unwrap();
+    let mut last = surf::get(format!("{}/items?page=3", mock_server.uri()))
+        .await
+        .unwrap();
+    let mut custom_size = surf::get(format!("{}/items?page=2&per_page=4", mock_server.uri()))
+        .await
+        .unwrap();
+
+    // Assert
+    let next = format!("{}/items?sort=desc&page=2", mock_server.uri());
+    assert_eq!(first.header("X-Total-Count").unwrap().as_str(), "5");
+    assert_eq!(
+        first.header("Link").unwrap().as_str(),
+        format!("<{}>; rel=\"next\"", next)
+    );
+    assert_eq!(
+        first.body_json::<Value>().await.unwrap(),
+        json!({ "items": [{ "id": 1 }, { "id": 2 }], "next": next })
+    );
+
+    assert!(last.header("Link").is_none());
+    assert_eq!(
+        last.body_json::<Value>().await.unwrap(),
+        json!({ "items": [{ "id": 5 }], "next": null })
+    );
+
+    assert_eq!(
+        custom_size.body_json::<Value>().await.unwrap(),
+        json!({ "items": [{ "id": 5 }], "next": null })
+    );
+}
+
+#[async_std::test]
+async fn invalid_pagination_parameters_are_rejected() {
+    // Arrange
+    let mock_server = MockServer::start().await;
+    paginated("/numbered", vec![json!(1)], PageStyle::PageNumber)
+        .mount(&mock_server)
+        .await;
+    paginated("/cursors", vec![json!(1)], PageStyle::Cursor)
+        .mount(&mock_server)
+        .await;
+
+    // Act
+    let mut statuses = vec![];
+    for path in [
+        "/numbered?page=0",
+        "/numbered?per_page=abc",
+        "/cursors?cursor=not-a-cursor",
+        "/cursors?limit=0",
+    ] {
+        let response = surf::get(format!("{}{}", mock_server.uri(), path))
+            .await
+            .unwrap();
+        statuses.push(response.status());
+    }
+
+    // Assert
+    assert!(
+        statuses.iter().all(|status| *status == 400),
+        "{:?}",
+        statuses
+    );
+}
@@ -0,0 +1,25 @@
+use std::net::TcpStream;
+use std::time::Duration;
+use wiremock::pool::{configure, PoolConfig};
+use wiremock::MockServer;
+
+// Each integration test file runs in its own process: the pool configuration set here does not
+// affect the other test suites.
+#[async_std::test]
+async fn servers_are_shut_down_on_drop_if_pooling_is_disabled() {
+    // Arrange
+    configure(PoolConfig::new().disable_pooling());
+    let mock_server = MockServer::start().await;
+    let address = *mock_server.address();
+
+    // Act
+    drop(mock_server);
+
+    // Assert
+    let mut attempts = 0;
+    while TcpStream::connect(address).is_ok() {
+        attempts += 1;
+        assert!(attempts < 100, "The server is still accepting connections");
+        async_std::task::sleep(Duration::from_millis(10)).await;
+    }
+}
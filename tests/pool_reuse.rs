@@ -0,0 +1,31 @@
+use std::time::Duration;
+use wiremock::matchers::{method, received_within};
+use wiremock::pool::{configure, PoolConfig};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+// Each integration test file runs in its own process: with a single server in the pool, every
+// call to `MockServer::start` hands out the same one.
+#[async_std::test]
+async fn recycled_servers_measure_arrival_times_from_when_they_are_handed_out() {
+    // Arrange
+    configure(PoolConfig::new().max_size(1));
+    let mock_server = MockServer::start().await;
+    let address = *mock_server.address();
+    async_std::task::sleep(Duration::from_millis(1500)).await;
+    drop(mock_server);
+
+    let mock_server = MockServer::start().await;
+    assert_eq!(&address, mock_server.address());
+    Mock::given(method("GET"))
+        .and(received_within(..Duration::from_secs(1)))
+        .respond_with(ResponseTemplate::new(200))
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+
+    // Act
+    let status = surf::get(mock_server.uri()).await.unwrap().status();
+
+    // Assert
+    assert_eq!(status, 200);
+}
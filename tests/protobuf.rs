@@ -0,0 +1,119 @@
+#![cfg(feature = "protobuf")]
+use prost::Message;
+use wiremock::matchers::{body_protobuf, body_protobuf_partial, method};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+#[derive(Clone, PartialEq, Message)]
+struct Address {
+    #[prost(string, tag = "1")]
+    city: String,
+    #[prost(string, tag = "2")]
+    street: String,
+}
+
+#[derive(Clone, PartialEq, Message)]
+struct CreateUser {
+    #[prost(string, tag = "1")]
+    name: String,
+    #[prost(uint32, tag = "2")]
+    age: u32,
+    #[prost(message, optional, tag = "3")]
+    address: Option<Address>,
+    #[prost(string, repeated, tag = "4")]
+    roles: Vec<String>,
+}
+
+fn jane() -> CreateUser {
+    CreateUser {
+        name: "Jane".into(),
+        age: 42,
+        address: Some(Address {
+            city: "London".into(),
+            street: "Baker Street".into(),
+        }),
+        roles: vec!["admin".into(), "reader".into()],
+    }
+}
+
+async fn post_status(mock_server: &MockServer, body: Vec<u8>) -> u16 {
+    surf::post(mock_server.uri())
+        .body(body)
+        .await
+        .unwrap()
+        .status()
+        .into()
+}
+
+#[async_std::test]
+async fn body_protobuf_matches_the_whole_decoded_message() {
+    // Arrange
+    let mock_server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(body_protobuf(jane()))
+        .respond_with(ResponseTemplate::new(201))
+        .mount(&mock_server)
+        .await;
+
+    // Fields are encoded in the reverse order: same message, different bytes.
+    let user = jane();
+    let mut reordered = vec![];
+    prost::encoding::string::encode_repeated(4, &user.roles, &mut reordered);
+    prost::encoding::message::encode(3, user.address.as_ref().unwrap(), &mut reordered);
+    prost::encoding::uint32::encode(2, &user.age, &mut reordered);
+    prost::encoding::string::encode(1, &user.name, &mut reordered);
+    assert_ne!(reordered, jane().encode_to_vec());
+    let older = CreateUser { age: 41, ..jane() };
+
+    // Act
+    let exact = post_status(&mock_server, jane().encode_to_vec()).await;
+    let reordered = post_status(&mock_server, reordered).await;
+    let different = post_status(&mock_server, older.encode_to_vec()).await;
+    let garbage = post_status(&mock_server, vec![0xff, 0xff]).await;
+
+    // Assert
+    assert_eq!(exact, 201);
+    assert_eq!(reordered, 201);
+    assert_eq!(different, 404);
+    assert_eq!(garbage, 404);
+}
+
+#[async_std::test]
+async fn body_protobuf_partial_ignores_the_fields_that_are_not_set() {
+    // Arrange
+    let mock_server = MockServer::start().await;
+    let expected = CreateUser {
+        name: "Jane".into(),
+        address: Some(Address {
+            city: "London".into(),
+            ..Default::default()
+        }),
+        roles: vec!["reader".into()],
+        ..Default::default()
+    };
+    Mock::given(method("POST"))
+        .and(body_protobuf_partial(expected))
+        .respond_with(ResponseTemplate::new(201))
+        .mount(&mock_server)
+        .await;
+    let elsewhere = CreateUser {
+        address: Some(Address {
+            city: "Paris".into(),
+            street: "Baker Street".into(),
+        }),
+        ..jane()
+    };
+    let no_roles = CreateUser {
+        roles: vec![],
+        ..jane()
+    };
+
+    // Act
+    let superset = post_status(&mock_server, jane().encode_to_vec()).await;
+    let elsewhere = post_status(&mock_server, elsewhere.encode_to_vec()).await;
+    let no_roles = post_status(&mock_server, no_roles.encode_to_vec()).await;
+
+    // Assert
+    assert_eq!(superset, 201);
+    assert_eq!(elsewhere, 404);
+    assert_eq!(no_roles, 404);
+}
@@ -1,4 +1,6 @@
-use wiremock::matchers::{basic_auth, bearer_token, header, header_regex, headers, method};
+use wiremock::matchers::{
+    basic_auth, bearer_token, cookie, cookie_exists, header, header_regex, headers, method,
+};
 use wiremock::{Mock, MockServer, ResponseTemplate};
 
 #[async_std::test]
@@ -305,3 +307,57 @@ async fn should_not_match_bearer_token_header() {
     // Assert
     assert_eq!(should_not_match.status(), 404);
 }
+
+#[async_std::test]
+async fn should_match_cookie_among_many() {
+    // Arrange
+    let mock_server = MockServer::start().await;
+    let mock = Mock::given(method("GET"))
+        .and(cookie("session", "abc"))
+        .respond_with(ResponseTemplate::new(200));
+    mock_server.register(mock).await;
+
+    // Act
+    let should_match = surf::get(mock_server.uri())
+        .header("cookie", "theme=dark;session=\"abc\"; lang=en")
+        .await
+        .unwrap();
+    // Assert
+    assert_eq!(should_match.status(), 200);
+}
+
+#[async_std::test]
+async fn should_not_match_cookie_with_wrong_value() {
+    // Arrange
+    let mock_server = MockServer::start().await;
+    let mock = Mock::given(method("GET"))
+        .and(cookie("session", "abc"))
+        .respond_with(ResponseTemplate::new(200));
+    mock_server.register(mock).await;
+
+    // Act
+    let should_fail = surf::get(mock_server.uri())
+        .header("cookie", "session=abcd")
+        .await
+        .unwrap();
+    // Assert
+    assert_eq!(should_fail.status(), 404);
+}
+
+#[async_std::test]
+async fn should_not_match_missing_cookie() {
+    // Arrange
+    let mock_server = MockServer::start().await;
+    let mock = Mock::given(method("GET"))
+        .and(cookie_exists("session"))
+        .respond_with(ResponseTemplate::new(200));
+    mock_server.register(mock).await;
+
+    // Act
+    let should_fail = surf::get(mock_server.uri())
+        .header("cookie", "theme=dark")
+        .await
+        .unwrap();
+    // Assert
+    assert_eq!(should_fail.status(), 404);
+}
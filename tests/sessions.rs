@@ -0,0 +1,169 @@
+use http::HeaderName;
+use serde_json::json;
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, MockServer, Request, Respond, ResponseContext, ResponseTemplate, SessionKey};
+
+/// The state of a logged-in user.
+#[derive(Default)]
+struct Account {
+    username: String,
+    visits: u64,
+}
+
+struct Login;
+
+impl Respond for Login {
+    fn respond(&self, _request: &Request) -> ResponseTemplate {
+        unreachable!("The server always calls `respond_with_ctx`")
+    }
+
+    fn respond_with_ctx(&self, request: &Request, ctx: &ResponseContext) -> ResponseTemplate {
+        let session_id = ctx.random.uuid();
+        let account = ctx.session_by_id::<Account>(&session_id);
+        account.lock().unwrap().username = String::from_utf8(request.body.to_vec()).unwrap();
+        ResponseTemplate::new(204).insert_header("Set-Cookie", format!("session={}", session_id))
+    }
+}
+
+struct Profile;
+
+impl Respond for Profile {
+    fn respond(&self, _request: &Request) -> ResponseTemplate {
+        unreachable!("The server always calls `respond_with_ctx`")
+    }
+
+    fn respond_with_ctx(&self, _request: &Request, ctx: &ResponseContext) -> ResponseTemplate {
+        let Some(account) = ctx.session::<Account>() else {
+            return ResponseTemplate::new(401);
+        };
+        let mut account = account.lock().unwrap();
+        if account.username.is_empty() {
+            return ResponseTemplate::new(401);
+        }
+        account.visits += 1;
+        ResponseTemplate::new(200).set_body_json(json!({
+            "username": account.username,
+            "visits": account.visits,
+        }))
+    }
+}
+
+struct Logout;
+
+impl Respond for Logout {
+    fn respond(&self, _request: &Request) -> ResponseTemplate {
+        unreachable!("The server always calls `respond_with_ctx`")
+    }
+
+    fn respond_with_ctx(&self, _request: &Request, ctx: &ResponseContext) -> ResponseTemplate {
+        ctx.end_session();
+        ResponseTemplate::new(204)
+    }
+}
+
+#[async_std::test]
+async fn sessions_keyed_by_cookie_span_login_and_logout() {
+    // Arrange
+    let mock_server = MockServer::builder()
+        .session_key(SessionKey::Cookie("session".into()))
+        .start()
+        .await;
+    Mock::given(method("POST"))
+        .and(path("/login"))
+        .respond_with(Login)
+        .mount(&mock_server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/profile"))
+        .respond_with(Profile)
+        .mount(&mock_server)
+        .await;
+    Mock::given(method("POST"))
+        .and(path("/logout"))
+        .respond_with(Logout)
+        .mount(&mock_server)
+        .await;
+    let url = |path: &str| format!("{}{}", mock_server.uri(), path);
+
+    // Act
+    let login = surf::post(url("/login")).body("jane").await.unwrap();
+    let cookie = login.header("Set-Cookie").unwrap().as_str().to_owned();
+    let profile = |cookie: Option<String>| {
+        let mut request = surf::get(url("/profile"));
+        if let Some(cookie) = cookie {
+            request = request.header("Cookie", cookie);
+        }
+        async move {
+            let mut response = request.await.unwrap();
+            let status: u16 = response.status().into();
+            let body = response.body_string().await.unwrap();
+            (status, body)
+        }
+    };
+    let anonymous = profile(None).await;
+    let first = profile(Some(cookie.clone())).await;
+    let second = profile(Some(cookie.clone())).await;
+    surf::post(url("/logout"))
+        .header("Cookie", cookie.as_str())
+        .await
+        .unwrap();
+    let logged_out = profile(Some(cookie)).await;
+
+    // Assert
+    assert_eq!(anonymous.0, 401);
+    assert_eq!(
+        first,
+        (200, json!({"username": "jane", "visits": 1}).to_string())
+    );
+    assert_eq!(
+        second,
+        (200, json!({"username": "jane", "visits": 2}).to_string())
+    );
+    assert_eq!(logged_out.0, 401);
+}
+
+#[async_std::test]
+async fn sessions_keyed_by_header_are_isolated_from_each_other() {
+    // Arrange
+    struct Counter;
+
+    impl Respond for Counter {
+        fn respond(&self, _request: &Request) -> ResponseTemplate {
+            unreachable!("The server always calls `respond_with_ctx`")
+        }
+
+        fn respond_with_ctx(&self, _request: &Request, ctx: &ResponseContext) -> ResponseTemplate {
+            let counter = ctx.session::<u64>().unwrap();
+            let mut counter = counter.lock().unwrap();
+            *counter += 1;
+            ResponseTemplate::new(200).set_body_string(format!(
+                "{}:{}",
+                ctx.session_id().unwrap(),
+                counter
+            ))
+        }
+    }
+
+    let mock_server = MockServer::builder()
+        .session_key(SessionKey::Header(HeaderName::from_static("x-client-id")))
+        .start()
+        .await;
+    Mock::given(method("GET"))
+        .respond_with(Counter)
+        .mount(&mock_server)
+        .await;
+
+    // Act
+    let mut bodies = vec![];
+    for client_id in ["a", "b", "a"] {
+        let body = surf::get(mock_server.uri())
+            .header("X-Client-Id", client_id)
+            .recv_string()
+            .await
+            .unwrap();
+        bodies.push(body);
+    }
+
+    // Assert
+    assert_eq!(bodies, vec!["a:1", "b:1", "a:2"]);
+}
@@ -0,0 +1,90 @@
+use std::time::Duration;
+use wiremock::matchers::{any, path};
+use wiremock::{Mock, MockServer, ResponseTemplate, ShutdownError};
+
+/// Wait until the mock server has received a request.
+async fn wait_for_request(mock_server: &MockServer) {
+    while mock_server.received_requests().await.unwrap().is_empty() {
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+}
+
+#[tokio::test]
+async fn shutdown_waits_for_in_flight_requests() {
+    // Arrange
+    let mock_server = MockServer::start().await;
+    Mock::given(any())
+        .respond_with(ResponseTemplate::new(200).set_delay(Duration::from_millis(300)))
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+    let uri = mock_server.uri();
+    let request = tokio::spawn(reqwest::get(uri.clone()));
+    wait_for_request(&mock_server).await;
+
+    // Act
+    let outcome = mock_server.shutdown(Duration::from_secs(5)).await;
+
+    // Assert
+    assert!(outcome.is_ok());
+    assert_eq!(request.await.unwrap().unwrap().status(), 200);
+    assert!(reqwest::get(uri).await.is_err());
+}
+
+#[tokio::test]
+async fn shutdown_times_out_if_requests_take_too_long() {
+    // Arrange
+    let mock_server = MockServer::start().await;
+    Mock::given(any())
+        .respond_with(ResponseTemplate::new(200).set_delay(Duration::from_secs(5)))
+        .mount(&mock_server)
+        .await;
+    let _request = tokio::spawn(reqwest::get(mock_server.uri()));
+    wait_for_request(&mock_server).await;
+
+    // Act
+    let outcome = mock_server.shutdown(Duration::from_millis(50)).await;
+
+    // Assert
+    assert!(matches!(
+        outcome,
+        Err(ShutdownError::Timeout { in_flight: 1 })
+    ));
+}
+
+#[tokio::test]
+async fn shutdown_returns_verification_failures_instead_of_panicking() {
+    // Arrange
+    let mock_server = MockServer::start().await;
+    Mock::given(path("/hello"))
+        .respond_with(ResponseTemplate::new(200))
+        .named("Hello")
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+
+    // Act
+    let outcome = mock_server.shutdown(Duration::from_secs(1)).await;
+
+    // Assert
+    match outcome {
+        Err(ShutdownError::VerificationFailed(message)) => assert!(message.contains("Hello")),
+        outcome => panic!("Unexpected outcome: {:?}", outcome),
+    }
+}
+
+#[tokio::test]
+async fn pooled_servers_that_were_shut_down_are_not_reused() {
+    // Arrange
+    let mock_server = MockServer::start().await;
+    let address = *mock_server.address();
+    mock_server.shutdown(Duration::from_secs(1)).await.unwrap();
+
+    // Act
+    let mock_server = MockServer::start().await;
+
+    // Assert
+    assert_ne!(*mock_server.address(), address);
+    let response = reqwest::get(mock_server.uri()).await.unwrap();
+    assert_eq!(response.status(), 404);
+}
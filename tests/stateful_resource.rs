@@ -0,0 +1,61 @@
+use serde_json::{json, Value};
+use wiremock::{MockServer, StatefulResource};
+
+#[async_std::test]
+async fn items_can_be_listed_updated_and_deleted() {
+    // Arrange
+    let mock_server = MockServer::start().await;
+    let users = StatefulResource::new("users")
+        .with_item(json!({"name": "Ferris"}))
+        .with_item(json!({"name": "Corro"}));
+    users.mount(&mock_server).await;
+    let uri = format!("{}/users", mock_server.uri());
+
+    // Act
+    let mut list = surf::get(&uri).await.unwrap();
+    let updated = surf::put(format!("{}/1", uri))
+        .body(json!({"name": "Ferris the crab"}))
+        .await
+        .unwrap();
+    let deleted = surf::delete(format!("{}/2", uri)).await.unwrap();
+    let deleted_again = surf::delete(format!("{}/2", uri)).await.unwrap();
+
+    // Assert
+    let listed: Value = list.body_json().await.unwrap();
+    assert_eq!(
+        listed,
+        json!([{"id": 1, "name": "Ferris"}, {"id": 2, "name": "Corro"}])
+    );
+    assert_eq!(updated.status(), 200);
+    assert_eq!(deleted.status(), 204);
+    assert_eq!(deleted_again.status(), 404);
+    assert_eq!(
+        users.items(),
+        vec![json!({"id": 1, "name": "Ferris the crab"})]
+    );
+}
+
+#[async_std::test]
+async fn invalid_bodies_and_unknown_ids_are_rejected() {
+    // Arrange
+    let mock_server = MockServer::start().await;
+    let users = StatefulResource::new("/users/").id_field("user_id");
+    users.mount(&mock_server).await;
+    let uri = format!("{}/users", mock_server.uri());
+
+    // Act
+    let not_an_object = surf::post(&uri).body(json!([1, 2])).await.unwrap();
+    let missing = surf::get(format!("{}/42", uri)).await.unwrap();
+    let not_an_id = surf::put(format!("{}/abc", uri))
+        .body(json!({}))
+        .await
+        .unwrap();
+    let created = surf::post(&uri).body(json!({})).await.unwrap();
+
+    // Assert
+    assert_eq!(not_an_object.status(), 400);
+    assert_eq!(missing.status(), 404);
+    assert_eq!(not_an_id.status(), 404);
+    assert_eq!(created.status(), 201);
+    assert_eq!(users.get(1), Some(json!({"user_id": 1})));
+}
@@ -0,0 +1,276 @@
+use serde_json::json;
+use std::path::PathBuf;
+use std::time::Duration;
+use wiremock::stub::{RequestPattern, ResponseDefinition, Stub};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+fn stub(request: serde_json::Value, response: serde_json::Value) -> Stub {
+    serde_json::from_value(json!({ "request": request, "response": response })).unwrap()
+}
+
+#[async_std::test]
+async fn stubs_match_on_all_the_conditions_of_their_request_pattern() {
+    // Arrange
+    let mock_server = MockServer::start().await;
+    mock_server
+        .mount_stub(stub(
+            json!({
+                "method": "POST",
+                "path": "/users",
+                "headers": { "x-api-key": "secret" },
+                "query_params": { "dry_run": "true" },
+                "body_partial_json": { "name": "Ferris" }
+            }),
+            json!({ "status": 201, "json_body": { "id": 1 }, "headers": { "x-request-id": "42" } }),
+        ))
+        .await;
+    let url = format!("{}/users?dry_run=true", mock_server.uri());
+
+    // Act
+    let mut matched = surf::post(&url)
+        .header("X-API-Key", "secret")
+        .body(json!({ "name": "Ferris", "age": 7 }))
+        .await
+        .unwrap();
+    let unmatched = surf::post(&url)
+        .body(json!({ "name": "Ferris" }))
+        .await
+        .unwrap();
+
+    // Assert
+    assert_eq!(matched.status(), 201);
+    assert_eq!(matched.header("x-request-id").unwrap().as_str(), "42");
+    let body: serde_json::Value = matched.body_json().await.unwrap();
+    assert_eq!(body, json!({ "id": 1 }));
+    assert_eq!(unmatched.status(), 404);
+}
+
+#[async_std::test]
+async fn an_empty_request_pattern_matches_every_request() {
+    // Arrange
+    let mock_server = MockServer::start().await;
+    mock_server
+        .mount_stub(stub(json!({}), json!({ "status": 204 })))
+        .await;
+
+    // Act
+    let response = surf::delete(format!("{}/anything", mock_server.uri()))
+        .await
+        .unwrap();
+
+    // Assert
+    assert_eq!(response.status(), 204);
+}
+
+#[async_std::test]
+async fn only_mocks_built_from_stubs_are_exported() {
+    // Arrange
+    let mock_server = MockServer::start().await;
+    let stub = Stub {
+        request: RequestPattern {
+            path: Some("/hello".into()),
+            ..Default::default()
+        },
+        response: ResponseDefinition {
+            status: 200,
+            headers: Default::default(),
+            body: Some("world".into()),
+            json_body: None,
+            delay_ms: None,
+        },
+        name: None,
+        priority: None,
+    };
+    Mock::from(stub.clone())
+        .named("Hello")
+        .with_priority(1)
+        .mount(&mock_server)
+        .await;
+    Mock::given(|_: &wiremock::Request| true)
+        .respond_with(ResponseTemplate::new(200))
+        .mount(&mock_server)
+        .await;
+
+    // Act
+    let exported = mock_server.export_stubs().await;
+
+    // Assert
+    let expected = Stub {
+        name: Some("Hello".into()),
+        priority: Some(1),
+        ..stub
+    };
+    assert_eq!(exported, vec![expected.clone()]);
+    let serialized = serde_json::to_string(&exported).unwrap();
+    let deserialized: Vec<Stub> = serde_json::from_str(&serialized).unwrap();
+    assert_eq!(deserialized, vec![expected]);
+}
+
+/// Create an empty directory to store fixture files for the test named `name`.
+fn fixture_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("wiremock-{}-{}", name, std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+#[cfg(feature = "yaml")]
+#[async_std::test]
+async fn all_json_and_yaml_fixture_files_in_a_directory_are_mounted() {
+    // Arrange
+    let dir = fixture_dir("load");
+    std::fs::write(
+        dir.join("hello.json"),
+        r#"{ "request": { "path": "/hello" }, "response": { "status": 200 } }"#,
+    )
+    .unwrap();
+    std::fs::write(
+        dir.join("others.yaml"),
+        "- request: { path: /created }\n  response: { status: 201 }\n\
+         - request: { path: /gone }\n  response: { status: 410 }\n",
+    )
+    .unwrap();
+    std::fs::write(dir.join("README.md"), "Not a fixture").unwrap();
+    let mock_server = MockServer::start().await;
+
+    // Act
+    mock_server.load_fixture_dir(&dir).await;
+
+    // Assert
+    for (path, status) in [("hello", 200), ("created", 201), ("gone", 410)] {
+        let response = surf::get(format!("{}/{}", mock_server.uri(), path))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), status);
+    }
+    assert_eq!(mock_server.export_stubs().await.len(), 3);
+}
+
+#[async_std::test]
+#[should_panic(expected = "Failed to load fixtures")]
+async fn loading_an_invalid_fixture_file_panics() {
+    // Arrange
+    let dir = fixture_dir("invalid");
+    std::fs::write(dir.join("broken.json"), r#"{ "request": {} }"#).unwrap();
+    let mock_server = MockServer::start().await;
+
+    // Act
+    mock_server.load_fixture_dir(&dir).await;
+}
+
+#[async_std::test]
+async fn watched_fixtures_are_reloaded_when_they_change() {
+    // Arrange
+    let dir = fixture_dir("watch");
+    let fixture = dir.join("status.json");
+    std::fs::write(
+        &fixture,
+        r#"{ "request": { "path": "/status" }, "response": { "status": 200 } }"#,
+    )
+    .unwrap();
+    let mock_server = MockServer::start().await;
+    let url = format!("{}/status", mock_server.uri());
+    let _watcher = mock_server
+        .watch_fixture_dir(&dir, Duration::from_millis(10))
+        .await;
+    let before = surf::get(&url).await.unwrap();
+
+    // Act
+    std::fs::write(
+        &fixture,
+        r#"{ "request": { "path": "/status" }, "response": { "status": 503, "body": "down" } }"#,
+    )
+    .unwrap();
+    async_std::task::sleep(Duration::from_millis(200)).await;
+
+    // Assert
+    let after = surf::get(&url).await.unwrap();
+    assert_eq!(before.status(), 200);
+    assert_eq!(after.status(), 503);
+    assert_eq!(mock_server.export_stubs().await.len(), 1);
+}
+
+#[async_std::test]
+async fn watched_fixtures_are_not_reloaded_once_the_server_is_reset() {
+    // Arrange
+    let dir = fixture_dir("watch-reset");
+    let fixture = dir.join("status.json");
+    std::fs::write(
+        &fixture,
+        r#"{ "request": { "path": "/status" }, "response": { "status": 200 } }"#,
+    )
+    .unwrap();
+    let mock_server = MockServer::start().await;
+    let _watcher = mock_server
+        .watch_fixture_dir(&dir, Duration::from_millis(10))
+        .await;
+
+    // Act
+    mock_server.reset().await;
+    std::fs::write(
+        &fixture,
+        r#"{ "request": { "path": "/status" }, "response": { "status": 503 } }"#,
+    )
+    .unwrap();
+    async_std::task::sleep(Duration::from_millis(200)).await;
+
+    // Assert
+    let response = surf::get(format!("{}/status", mock_server.uri()))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), 404);
+    assert!(mock_server.export_stubs().await.is_empty());
+}
+
+#[async_std::test]
+async fn stubs_can_reference_registered_matchers_by_name() {
+    // Arrange
+    wiremock::stub::register_matcher("body-longer-than", |params: &serde_json::Value| {
+        let min = params["min"].as_u64().unwrap() as usize;
+        move |request: &wiremock::Request| request.body.len() > min
+    });
+    let mock_server = MockServer::start().await;
+    mock_server
+        .mount_stub(stub(
+            json!({
+                "path": "/upload",
+                "custom": [{ "name": "body-longer-than", "params": { "min": 3 } }]
+            }),
+            json!({ "status": 201 }),
+        ))
+        .await;
+    let url = format!("{}/upload", mock_server.uri());
+
+    // Act
+    let long = surf::post(&url).body("hello").await.unwrap();
+    let short = surf::post(&url).body("hi").await.unwrap();
+
+    // Assert
+    assert_eq!(long.status(), 201);
+    assert_eq!(short.status(), 404);
+}
+
+#[test]
+#[should_panic(expected = "No matcher named `not-registered` has been registered")]
+fn stubs_referencing_unknown_matchers_cannot_be_converted() {
+    let _ = Mock::from(stub(
+        json!({ "custom": [{ "name": "not-registered" }] }),
+        json!({ "status": 200 }),
+    ));
+}
+
+#[async_std::test]
+#[should_panic(expected = "no matcher named `not-registered-either` has been registered")]
+async fn fixtures_referencing_unknown_matchers_fail_to_load() {
+    // Arrange
+    let dir = fixture_dir("unknown-matcher");
+    std::fs::write(
+        dir.join("stub.json"),
+        r#"{ "request": { "custom": [{ "name": "not-registered-either" }] }, "response": { "status": 200 } }"#,
+    )
+    .unwrap();
+    let mock_server = MockServer::start().await;
+
+    // Act
+    mock_server.load_fixture_dir(&dir).await;
+}
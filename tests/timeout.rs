@@ -17,7 +17,7 @@ async fn test_body() {
         .timeout(std::time::Duration::from_secs(1))
         .build()
         .unwrap()
-        .get(&mock_server.uri())
+        .get(mock_server.uri())
         .send()
         .await;
 
@@ -0,0 +1,185 @@
+#![cfg(feature = "tls")]
+
+use wiremock::matchers::{path, tls_cipher_suite, tls_version_at_least};
+use wiremock::tls::{TlsConfig, TlsVersion};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+fn tls_config() -> TlsConfig {
+    TlsConfig::from_pem(
+        include_bytes!("fixtures/tls/localhost.pem"),
+        include_bytes!("fixtures/tls/localhost.key"),
+    )
+}
+
+fn client() -> reqwest::Client {
+    reqwest::Client::builder()
+        .danger_accept_invalid_certs(true)
+        .build()
+        .unwrap()
+}
+
+#[tokio::test]
+async fn the_negotiated_tls_parameters_are_recorded_and_matched() {
+    // Arrange
+    let mock_server = MockServer::builder().tls(tls_config()).start().await;
+    Mock::given(path("/secure"))
+        .and(tls_version_at_least(TlsVersion::Tls12))
+        .respond_with(ResponseTemplate::new(200))
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+    Mock::given(path("/unknown-cipher"))
+        .and(tls_cipher_suite("TLS_NULL_WITH_NULL_NULL"))
+        .respond_with(ResponseTemplate::new(200))
+        .expect(0)
+        .mount(&mock_server)
+        .await;
+
+    // Act
+    let secure = client()
+        .get(format!("{}/secure", mock_server.uri()))
+        .send()
+        .await
+        .unwrap();
+    let unknown_cipher = client()
+        .get(format!("{}/unknown-cipher", mock_server.uri()))
+        .send()
+        .await
+        .unwrap();
+
+    // Assert
+    assert!(mock_server.uri().starts_with("https://"));
+    assert_eq!(secure.status(), 200);
+    assert_eq!(unknown_cipher.status(), 404);
+    let requests = mock_server.received_requests().await.unwrap();
+    let tls = requests[0].tls().unwrap();
+    assert!(tls.version() >= TlsVersion::Tls12);
+    assert!(!tls.cipher_suite().is_empty());
+    assert_eq!(requests[0].url.scheme(), "https");
+}
+
+#[tokio::test]
+async fn plain_http_requests_do_not_match_tls_matchers() {
+    // Arrange
+    let mock_server = MockServer::start().await;
+    Mock::given(tls_version_at_least(TlsVersion::Tls12))
+        .respond_with(ResponseTemplate::new(200))
+        .mount(&mock_server)
+        .await;
+
+    // Act
+    let response = reqwest::get(mock_server.uri()).await.unwrap();
+
+    // Assert
+    assert_eq!(response.status(), 404);
+    let requests = mock_server.received_requests().await.unwrap();
+    assert!(requests[0].tls().is_none());
+}
+
+#[tokio::test]
+async fn the_http_redirect_points_at_the_https_listener() {
+    // Arrange
+    let mock_server = MockServer::builder()
+        .tls(tls_config())
+        .with_http_redirect()
+        .start()
+        .await;
+    Mock::given(path("/login"))
+        .respond_with(ResponseTemplate::new(200))
+        .mount(&mock_server)
+        .await;
+
+    // Act
+    let redirect_uri = mock_server.http_redirect_uri().unwrap();
+    let response = client()
+        .get(format!("{}/login", redirect_uri))
+        .send()
+        .await
+        .unwrap();
+
+    // Assert
+    assert_eq!(response.status(), 200);
+    assert_eq!(
+        response.url().as_str(),
+        format!("{}/login", mock_server.uri())
+    );
+}
+
+#[tokio::test]
+async fn new_connections_are_served_with_the_rotated_certificate() {
+    // Arrange
+    let mock_server = MockServer::builder().tls(tls_config()).start().await;
+    Mock::given(path("/"))
+        .respond_with(ResponseTemplate::new(200))
+        .mount(&mock_server)
+        .await;
+    let peer_certificate = || async {
+        // A new client, hence a new connection, every time.
+        let client = reqwest::Client::builder()
+            .danger_accept_invalid_certs(true)
+            .tls_info(true)
+            .build()
+            .unwrap();
+        let response = client.get(mock_server.uri()).send().await.unwrap();
+        assert_eq!(response.status(), 200);
+        response
+            .extensions()
+            .get::<reqwest::tls::TlsInfo>()
+            .unwrap()
+            .peer_certificate()
+            .unwrap()
+            .to_vec()
+    };
+    let before = peer_certificate().await;
+
+    // Act
+    mock_server.rotate_tls_cert(TlsConfig::from_pem(
+        include_bytes!("fixtures/tls/localhost-rotated.pem"),
+        include_bytes!("fixtures/tls/localhost-rotated.key"),
+    ));
+    let after = peer_certificate().await;
+
+    // Assert
+    assert_ne!(before, after);
+    assert_eq!(after, peer_certificate().await);
+}
+
+#[tokio::test]
+#[should_panic(expected = "The mock server does not serve its mocks over TLS")]
+async fn rotating_the_certificate_of_a_plain_http_server_panics() {
+    let mock_server = MockServer::start().await;
+    mock_server.rotate_tls_cert(tls_config());
+}
+
+#[tokio::test]
+async fn tls_connections_tunnelled_through_the_proxy_are_intercepted() {
+    // Arrange
+    let mock_server = MockServer::builder().intercept_tls().start().await;
+    Mock::given(wiremock::matchers::host("api.example.com"))
+        .and(path("/users"))
+        .and(tls_version_at_least(TlsVersion::Tls12))
+        .respond_with(ResponseTemplate::new(200).set_body_string("intercepted"))
+        .mount(&mock_server)
+        .await;
+    let ca_certificate = mock_server.proxy_ca_certificate().unwrap();
+    let client = reqwest::Client::builder()
+        .proxy(reqwest::Proxy::https(mock_server.uri()).unwrap())
+        .add_root_certificate(reqwest::Certificate::from_pem(ca_certificate.as_bytes()).unwrap())
+        .build()
+        .unwrap();
+
+    // Act
+    let response = client
+        .get("https://api.example.com/users")
+        .send()
+        .await
+        .unwrap();
+
+    // Assert
+    assert_eq!(response.status(), 200);
+    assert_eq!(response.text().await.unwrap(), "intercepted");
+    let requests = mock_server.received_requests().await.unwrap();
+    assert_eq!(requests[0].url.as_str(), "https://api.example.com/users");
+    assert!(requests[0].connection_id().is_some());
+    assert!(MockServer::start().await.proxy_ca_certificate().is_none());
+}
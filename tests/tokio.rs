@@ -15,7 +15,7 @@ async fn hello_reqwest() {
         .mount(&mock_server)
         .await;
 
-    let resp = Client::new().get(&mock_server.uri()).send().await.unwrap();
+    let resp = Client::new().get(mock_server.uri()).send().await.unwrap();
 
     assert_eq!(resp.status(), 200);
 }
@@ -30,7 +30,7 @@ async fn hello_surf() {
         .mount(&mock_server)
         .await;
 
-    let status = surf::get(&mock_server.uri()).await.unwrap().status();
+    let status = surf::get(mock_server.uri()).await.unwrap().status();
 
     assert_eq!(status, 200);
 }
@@ -45,7 +45,7 @@ async fn hello_reqwest_actix() {
         .mount(&mock_server)
         .await;
 
-    let resp = Client::new().get(&mock_server.uri()).send().await.unwrap();
+    let resp = Client::new().get(mock_server.uri()).send().await.unwrap();
 
     assert_eq!(resp.status(), 200);
 }
@@ -64,7 +64,7 @@ async fn hello_reqwest_http2() {
         .http2_prior_knowledge()
         .build()
         .expect("http client")
-        .get(&mock_server.uri())
+        .get(mock_server.uri())
         .send()
         .await
         .expect("response");
@@ -72,3 +72,211 @@ async fn hello_reqwest_http2() {
     assert_eq!(resp.status(), 200);
     assert_eq!(resp.version(), reqwest::Version::HTTP_2);
 }
+
+#[tokio::test]
+async fn max_concurrent_requests_counts_parallel_requests_across_mocks() {
+    let mock_server = MockServer::start().await;
+    let delayed = ResponseTemplate::new(200).set_delay(std::time::Duration::from_millis(200));
+
+    let a_guard = Mock::given(path("/a"))
+        .respond_with(delayed.clone())
+        .mount_as_scoped(&mock_server)
+        .await;
+    Mock::given(path("/b"))
+        .respond_with(delayed)
+        .mount(&mock_server)
+        .await;
+
+    let client = Client::new();
+    let (a, b, c) = tokio::join!(
+        client.get(format!("{}/a", mock_server.uri())).send(),
+        client.get(format!("{}/b", mock_server.uri())).send(),
+        client.get(format!("{}/b", mock_server.uri())).send(),
+    );
+    a.unwrap();
+    b.unwrap();
+    c.unwrap();
+
+    assert_eq!(mock_server.max_concurrent_requests(), 3);
+    assert_eq!(a_guard.max_concurrency().await, 1);
+}
+
+#[tokio::test]
+async fn slow_async_responders_do_not_block_other_requests() {
+    let mock_server = MockServer::start().await;
+    let (sender, receiver) = tokio::sync::oneshot::channel::<String>();
+    let receiver = std::sync::Mutex::new(Some(receiver));
+
+    Mock::given(path("/wait"))
+        .respond_with_async(move |_: wiremock::Request| {
+            let receiver = receiver.lock().unwrap().take().unwrap();
+            async move { ResponseTemplate::new(200).set_body_string(receiver.await.unwrap()) }
+        })
+        .mount(&mock_server)
+        .await;
+    Mock::given(path("/fast"))
+        .respond_with(ResponseTemplate::new(200))
+        .mount(&mock_server)
+        .await;
+
+    let client = Client::new();
+    let waiting = tokio::spawn(client.get(format!("{}/wait", mock_server.uri())).send());
+    // Give the server the time to pick up the first request.
+    tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+
+    let fast = client
+        .get(format!("{}/fast", mock_server.uri()))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(fast.status(), 200);
+
+    sender.send("released".into()).unwrap();
+    let waiting = waiting.await.unwrap().unwrap();
+    assert_eq!(waiting.text().await.unwrap(), "released");
+
+    let journal = mock_server.request_journal().await.unwrap();
+    assert_eq!(journal.len(), 2);
+    assert_eq!(journal[1].request.url.path(), "/wait");
+    assert_eq!(journal[1].response_status, 200);
+}
+
+#[tokio::test]
+async fn http2_connections_are_shut_down_after_the_configured_number_of_streams() {
+    let mock_server = MockServer::builder().http2_goaway_after(2).start().await;
+    Mock::given(method("GET"))
+        .respond_with(ResponseTemplate::new(200))
+        .mount(&mock_server)
+        .await;
+    let client = Client::builder().http2_prior_knowledge().build().unwrap();
+
+    for _ in 0..5 {
+        let resp = client.get(mock_server.uri()).send().await.unwrap();
+        assert_eq!(resp.status(), 200);
+    }
+
+    assert_eq!(mock_server.connection_count().await, Some(3));
+}
+
+#[tokio::test]
+async fn http2_streams_can_be_reset_without_affecting_the_connection() {
+    use wiremock::StreamReset;
+
+    let mock_server = MockServer::start().await;
+    Mock::given(path("/refused"))
+        .respond_with(ResponseTemplate::new(200).reset_stream(StreamReset::RefusedStream))
+        .mount(&mock_server)
+        .await;
+    Mock::given(path("/cancelled"))
+        .respond_with(ResponseTemplate::new(200).reset_stream(StreamReset::Cancel))
+        .mount(&mock_server)
+        .await;
+    Mock::given(path("/ok"))
+        .respond_with(ResponseTemplate::new(200))
+        .mount(&mock_server)
+        .await;
+    let client = Client::builder().http2_prior_knowledge().build().unwrap();
+
+    let refused = client
+        .get(format!("{}/refused", mock_server.uri()))
+        .send()
+        .await
+        .unwrap_err();
+    let cancelled = client
+        .get(format!("{}/cancelled", mock_server.uri()))
+        .send()
+        .await
+        .unwrap_err();
+    let ok = client
+        .get(format!("{}/ok", mock_server.uri()))
+        .send()
+        .await
+        .unwrap();
+
+    assert!(format!("{:?}", refused).contains("REFUSED_STREAM"));
+    assert!(format!("{:?}", cancelled).contains("CANCEL"));
+    assert_eq!(ok.status(), 200);
+    assert_eq!(mock_server.connection_count().await, Some(1));
+}
+
+#[tokio::test(start_paused = true)]
+async fn servers_running_on_the_current_runtime_follow_its_paused_clock() {
+    let mock_server = MockServer::builder().run_on_current_runtime().start().await;
+    let delay = std::time::Duration::from_secs(24 * 60 * 60);
+    Mock::given(method("GET"))
+        .respond_with(ResponseTemplate::new(200).set_delay(delay))
+        .mount(&mock_server)
+        .await;
+
+    let started_at = std::time::Instant::now();
+    let resp = Client::new().get(mock_server.uri()).send().await.unwrap();
+
+    assert_eq!(resp.status(), 200);
+    assert!(started_at.elapsed() < delay);
+    assert_eq!(mock_server.received_requests().await.unwrap().len(), 1);
+}
+
+#[cfg(feature = "async-std")]
+#[async_std::test]
+async fn servers_can_run_on_the_current_async_std_executor() {
+    let mock_server = MockServer::builder().run_on_current_runtime().start().await;
+    Mock::given(method("GET"))
+        .respond_with(ResponseTemplate::new(200).set_delay(std::time::Duration::from_millis(10)))
+        .mount(&mock_server)
+        .await;
+
+    let resp = surf::get(mock_server.uri()).await.unwrap();
+
+    assert_eq!(resp.status(), 200);
+    assert_eq!(mock_server.received_requests().await.unwrap().len(), 1);
+}
+
+#[tokio::test]
+async fn dropping_a_server_running_on_the_current_runtime_drops_its_connections() {
+    let mock_server = MockServer::builder().run_on_current_runtime().start().await;
+    Mock::given(method("GET"))
+        .respond_with(ResponseTemplate::new(200).hang())
+        .mount(&mock_server)
+        .await;
+    let hanging = tokio::spawn(Client::new().get(mock_server.uri()).send());
+    while mock_server.received_requests().await.unwrap().is_empty() {
+        tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+    }
+
+    drop(mock_server);
+    let outcome = tokio::time::timeout(std::time::Duration::from_secs(5), hanging).await;
+
+    assert!(outcome
+        .expect("The hanging request was not released")
+        .unwrap()
+        .is_err());
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn concurrent_requests_never_exceed_the_match_limit_of_a_mock() {
+    let mock_server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .respond_with(ResponseTemplate::new(200))
+        .up_to_n_times(10)
+        .expect(10)
+        .mount(&mock_server)
+        .await;
+    Mock::given(method("GET"))
+        .respond_with(ResponseTemplate::new(429))
+        .mount(&mock_server)
+        .await;
+
+    let client = Client::new();
+    let requests = (0..50).map(|_| {
+        let (client, uri) = (client.clone(), mock_server.uri());
+        tokio::spawn(async move { client.get(uri).send().await.unwrap().status() })
+    });
+    let statuses = futures::future::join_all(requests).await;
+
+    let n_ok = statuses
+        .iter()
+        .filter(|s| *s.as_ref().unwrap() == 200)
+        .count();
+    assert_eq!(n_ok, 10);
+    assert_eq!(mock_server.received_requests().await.unwrap().len(), 50);
+}